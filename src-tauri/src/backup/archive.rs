@@ -1,31 +1,76 @@
-use fs_extra::dir::move_dir;
-use fs_extra::file::move_file;
-use log::warn;
+use anyhow::Context;
+use chrono::{Datelike, Timelike};
+use log::{error, info, warn};
 use rust_i18n::t;
 use std::{
+    collections::HashMap,
     fs::{self, File},
-    io::{Read, Seek, Write},
+    io::{self, Read, Seek, Write},
     path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tauri::{AppHandle, Emitter};
-use zip::{ZipWriter, write::SimpleFileOptions};
+use zip::{
+    ZipArchive, ZipWriter,
+    extra_fields::ExtraField,
+    read::ZipFile,
+    write::{FullFileOptions, SimpleFileOptions},
+};
 
+use super::progress::ArchiveMonitor;
+use super::relocate::existing_ancestor;
+use super::snapshot_contents::{MANIFEST_NAME, ManifestUnit, SnapshotManifest};
 use crate::{
-    backup::{SaveUnit, SaveUnitType},
+    backup::{ApplyCleanMode, PathFilter, SaveUnit, SaveUnitType, SymlinkMode},
+    config::{BackupRetry, Config},
     device::get_current_device_id,
     ipc_handler::{IpcNotification, NotificationLevel},
     preclude::*,
 };
 
+/// Settings shared by the whole walk of a folder unit
+struct DirWalk<'a> {
+    /// The unit folder, filter patterns are relative to it
+    root: &'a Path,
+    filter: Option<&'a PathFilter>,
+    retry: &'a BackupRetry,
+    symlinks: SymlinkMode,
+    /// Canonical paths of the folders being walked, used to break link loops
+    ancestors: Vec<PathBuf>,
+}
+
+impl<'a> DirWalk<'a> {
+    fn new(
+        unit: &SaveUnit,
+        root: &'a Path,
+        filter: Option<&'a PathFilter>,
+        retry: &'a BackupRetry,
+    ) -> Self {
+        DirWalk {
+            root,
+            filter,
+            retry,
+            symlinks: unit.symlinks,
+            ancestors: fs::canonicalize(root).into_iter().collect(),
+        }
+    }
+}
+
 /// [Code reference](https://github.com/matzefriedrich/zip-extensions-rs/blob/master/src/write.rs#:~:text=%7D-,fn,create_from_directory_with_options,-\()
 ///
 /// Write `origin` folder to zip `writer`, the files will in `prefix_path`
 ///
 /// Normally, `prefix_path` should be the file name of the `origin` folder
+///
+/// Entries are filtered by `walk.filter` with paths relative to `walk.root`,
+/// links are handled according to `walk.symlinks`
 fn add_directory<T>(
     writer: &mut ZipWriter<T>,
     origin: &PathBuf,
     prefix_path: &Path,
+    walk: &mut DirWalk,
+    monitor: &mut ArchiveMonitor,
 ) -> Result<(), BackupFileError>
 where
     T: std::io::Write,
@@ -43,28 +88,68 @@ where
     let mut paths = Vec::new();
     paths.push(origin);
 
-    let mut buffer = Vec::new();
-
     while let Some(next) = paths.pop() {
         let directory_entry_iter = fs::read_dir(next)?;
 
         for entry in directory_entry_iter {
             let entry = entry?;
             let entry_path = entry.path();
-            let entry_metadata = fs::metadata(&entry_path)?;
             let mut cur_path = prefix_path.to_path_buf();
             cur_path = cur_path.join(entry.file_name());
+            let relative = entry_path.strip_prefix(walk.root).unwrap_or(&entry_path);
+            // Junctions are reported as symlinks too
+            let is_link = fs::symlink_metadata(&entry_path)?.is_symlink();
+            if is_link && walk.symlinks != SymlinkMode::Follow {
+                if walk.filter.is_some_and(|f| !f.includes_file(relative)) {
+                    continue;
+                }
+                if walk.symlinks == SymlinkMode::Skip {
+                    warn!(target:"rgsm::backup::archive", "Skipping link {:?}", entry_path);
+                    continue;
+                }
+                let target = fs::read_link(&entry_path)?;
+                writer.add_symlink(
+                    cur_path.to_str().ok_or(BackupFileError::NonePathError)?,
+                    target.to_str().ok_or(BackupFileError::NonePathError)?,
+                    SimpleFileOptions::default(),
+                )?;
+                continue;
+            }
+            let entry_metadata = match fs::metadata(&entry_path) {
+                Ok(metadata) => metadata,
+                Err(e) if is_link => {
+                    warn!(target:"rgsm::backup::archive", "Skipping broken link {:?}: {}", entry_path, e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if let Some(filter) = walk.filter {
+                let skip = if entry_metadata.is_dir() {
+                    filter.is_excluded(relative)
+                } else {
+                    !filter.includes_file(relative)
+                };
+                if skip {
+                    continue;
+                }
+            }
             if entry_metadata.is_file() {
-                let mut f = File::open(&entry_path)?;
-                f.read_to_end(&mut buffer)?;
+                let buffer = read_with_retry(&entry_path, walk.retry)?;
                 writer.start_file(
                     cur_path.to_str().ok_or(BackupFileError::NonePathError)?,
-                    SimpleFileOptions::default().compression_method(zip::CompressionMethod::Bzip2),
+                    file_options(&entry_metadata),
                 )?;
-                writer.write_all(&buffer)?;
-                buffer.clear();
+                write_with_progress(writer, &buffer, &cur_path, monitor)?;
             } else if entry_metadata.is_dir() {
-                add_directory(writer, &entry_path, &cur_path)?;
+                let canonical = fs::canonicalize(&entry_path)?;
+                if walk.ancestors.contains(&canonical) {
+                    warn!(target:"rgsm::backup::archive", "Skipping link loop {:?} -> {:?}", entry_path, canonical);
+                    continue;
+                }
+                walk.ancestors.push(canonical);
+                let result = add_directory(writer, &entry_path, &cur_path, walk, monitor);
+                walk.ancestors.pop();
+                result?;
             }
         }
     }
@@ -72,51 +157,267 @@ where
     Ok(())
 }
 
+/// Windows reports a file locked by another process as a sharing or lock violation
+#[cfg(windows)]
+fn is_sharing_violation(e: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    matches!(
+        e.raw_os_error(),
+        Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+    )
+}
+
+#[cfg(not(windows))]
+fn is_sharing_violation(_: &io::Error) -> bool {
+    false
+}
+
+/// Read a file to back up, retrying while another process holds a lock on it
+///
+/// Games often keep the save locked for a moment after autosaving, so a sharing
+/// violation is retried up to `retry.attempts` times; other errors fail at once
+fn read_with_retry(path: &Path, retry: &BackupRetry) -> Result<Vec<u8>, BackupFileError> {
+    let mut attempt = 1;
+    loop {
+        match fs::read(path) {
+            Ok(data) => return Ok(data),
+            Err(e) if is_sharing_violation(&e) && attempt < retry.attempts => {
+                warn!(target:"rgsm::backup::archive", "{:?} is in use, retrying in {}ms ({}/{})", path, retry.delay_ms, attempt, retry.attempts);
+                thread::sleep(Duration::from_millis(retry.delay_ms));
+                attempt += 1;
+            }
+            Err(e) if is_sharing_violation(&e) => {
+                return Err(BackupFileError::FileInUse(path.to_path_buf()));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Files are written and extracted in chunks of this size, so progress and cancellation
+/// also work inside a single large file
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Write `data` as the current zip entry, `name` is the entry name shown as progress
+fn write_with_progress<W: Write>(
+    writer: &mut W,
+    data: &[u8],
+    name: &Path,
+    monitor: &mut ArchiveMonitor,
+) -> Result<(), BackupFileError> {
+    monitor.check()?;
+    for chunk in data.chunks(CHUNK_SIZE) {
+        writer.write_all(chunk)?;
+        monitor.advance(chunk.len() as u64, name)?;
+    }
+    Ok(())
+}
+
+/// Copy an extracted entry like `io::copy`, reporting progress after every chunk
+fn copy_with_progress<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    name: &Path,
+    monitor: &mut ArchiveMonitor,
+) -> Result<u64, BackupFileError> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut copied = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(copied),
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        writer.write_all(&buffer[..read])?;
+        copied += read as u64;
+        monitor.advance(read as u64, name)?;
+    }
+}
+
+/// Total size of the files under `path`, used as the progress total when compressing
+///
+/// Filters and links are ignored, so this is only an estimate
+fn estimate_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path).map_or(0, |entries| {
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| estimate_size(&entry.path()))
+            .sum()
+    })
+}
+
+/// Header ID of the Info-ZIP extended timestamp extra field
+const EXTENDED_TIMESTAMP_ID: u16 = 0x5455;
+
+/// Options of a file entry, keeping the modification time and unix mode from `metadata`
+///
+/// The modification time is stored twice: as the local DOS time that every zip
+/// tool shows, and as an extended timestamp in UTC. Only the latter is restored,
+/// since older snapshots carry the backup time as their DOS time
+fn file_options(metadata: &fs::Metadata) -> FullFileOptions<'static> {
+    let mut options = FullFileOptions::default().compression_method(zip::CompressionMethod::Bzip2);
+    if let Ok(modified) = metadata.modified() {
+        if let Some(time) = dos_time(modified) {
+            options = options.last_modified_time(time);
+        }
+        // The field holds an unsigned 32-bit timestamp, other times are only kept as DOS time
+        let seconds = modified
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|d| u32::try_from(d.as_secs()).ok());
+        if let Some(seconds) = seconds {
+            // Flags: only the modification time is present
+            let mut field = vec![1];
+            field.extend_from_slice(&seconds.to_le_bytes());
+            if let Err(e) =
+                options.add_extra_data(EXTENDED_TIMESTAMP_ID, field.into_boxed_slice(), false)
+            {
+                warn!(target:"rgsm::backup::archive", "Cannot store the modification time: {}", e);
+            }
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        options = options.unix_permissions(metadata.permissions().mode());
+    }
+    options
+}
+
+/// Convert to a DOS time in the local time zone, `None` outside of 1980-2107
+fn dos_time(time: SystemTime) -> Option<zip::DateTime> {
+    let local = chrono::DateTime::<chrono::Local>::from(time).naive_local();
+    zip::DateTime::from_date_and_time(
+        local.year().try_into().ok()?,
+        local.month() as u8,
+        local.day() as u8,
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second() as u8,
+    )
+    .ok()
+}
+
+fn write_manifest<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    manifest: &SnapshotManifest,
+) -> Result<(), BackupFileError> {
+    let json = serde_json::to_vec(manifest).map_err(|e| BackupFileError::Unexpected(e.into()))?;
+    zip.start_file(MANIFEST_NAME, SimpleFileOptions::default())?;
+    zip.write_all(&json)?;
+    Ok(())
+}
+
+/// A zip file written by `compress_to_file`
+#[derive(Debug, Clone, Copy)]
+pub struct CompressedArchive {
+    /// Size of the compressed file in bytes
+    pub size: u64,
+    /// Number of entries written, including folders and the manifest
+    pub entries: usize,
+}
+
 /// Compress a set of save to a zip file in `backup_path` with name 'date.zip'
-/// Returns the size of the compressed file in bytes if successful
-pub fn compress_to_file(save_paths: &[SaveUnit], zip_path: &Path) -> Result<u64, CompressError> {
-    let file = File::create(zip_path).map_err(|e| CompressError::Single(e.into()))?;
+/// Returns the size and entry count of the compressed file if successful
+///
+/// A manifest mapping the top-level paths to the indices of `save_paths` is written last.
+/// Stops with `BackupFileError::Cancelled` once `monitor` is cancelled, the partial zip is
+/// left for the caller to remove
+pub fn compress_to_file(
+    save_paths: &[SaveUnit],
+    zip_path: &Path,
+    monitor: &mut ArchiveMonitor,
+) -> Result<CompressedArchive, CompressError> {
+    // Opened for reading as well, so the entry count can be taken from the finished writer
+    let file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(to_long_path(zip_path))
+        .map_err(|e| CompressError::Single(e.into()))?;
     let mut zip = ZipWriter::new(file);
+    if let Ok(config) = crate::config::get_config() {
+        let total = save_paths
+            .iter()
+            .filter(|x| x.enabled)
+            .filter_map(|x| x.get_path_for_device(get_current_device_id()))
+            .filter_map(|p| crate::path_resolver::resolve_path(p, None, &config).ok())
+            .map(|p| estimate_size(&to_long_path(&p)))
+            .sum();
+        monitor.set_total(total);
+    }
+    let disabled = save_paths.iter().filter(|x| !x.enabled).count();
+    if disabled > 0 {
+        info!(target:"rgsm::backup::archive", "Skipping {} disabled save units", disabled);
+    }
+    let mut manifest = SnapshotManifest::default();
     let compress_errors: Vec<_> = save_paths
         .iter()
-        .map(|x| {
+        .enumerate()
+        // 停用的单元不进入快照，保留原下标以便清单与配置中的单元对应
+        .filter(|(_, x)| x.enabled)
+        .map(|(index, x)| {
+            monitor.check()?;
             // 获取当前设备 ID，并将 ConfigError 转换为 BackupFileError
             let current_device_id = &get_current_device_id();
-            // 获取当前设备的路径，如果不存在则返回 NonePathError
-            let unit_path_str = x
-                .get_path_for_device(current_device_id)
-                .ok_or(BackupFileError::NonePathError)?;
+            // 没有当前设备路径的单元直接跳过，由调用方提示用户
+            let Some(unit_path_str) = x.get_path_for_device(current_device_id) else {
+                warn!(target:"rgsm::backup::archive", "Skipping save unit without a path for this device: {:?}", x.paths);
+                return Ok(());
+            };
 
             // 使用 path_resolver 解析路径变量
             let config =
                 crate::config::get_config().map_err(|e| BackupFileError::Unexpected(e.into()))?;
             let unit_path = crate::path_resolver::resolve_path(unit_path_str, None, &config)?;
-            if unit_path.exists() {
+            // 深层嵌套的存档可能超过 MAX_PATH，访问文件系统时使用扩展长度路径
+            let long_path = to_long_path(&unit_path);
+            if long_path.exists() {
                 match x.unit_type {
                     SaveUnitType::File => {
-                        let mut original_file = File::open(&unit_path)?;
-                        let mut buf = vec![];
-                        original_file.read_to_end(&mut buf)?;
+                        let buf = read_with_retry(&long_path, &config.settings.backup_retry)?;
+                        let name = long_path
+                            .file_name()
+                            .ok_or(BackupFileError::NonePathError)?;
                         zip.start_file(
-                            unit_path
-                                .file_name()
-                                .ok_or(BackupFileError::NonePathError)?
-                                .to_str()
-                                .ok_or(BackupFileError::NonePathError)?,
-                            SimpleFileOptions::default()
-                                .compression_method(zip::CompressionMethod::Bzip2),
+                            name.to_str().ok_or(BackupFileError::NonePathError)?,
+                            file_options(&fs::metadata(&long_path)?),
                         )?;
-                        zip.write_all(&buf)?;
+                        write_with_progress(&mut zip, &buf, Path::new(name), monitor)?;
                     }
                     SaveUnitType::Folder => {
                         let root = PathBuf::from(
-                            unit_path
+                            long_path
                                 .file_name()
                                 .ok_or(BackupFileError::NonePathError)?,
                         );
-                        add_directory(&mut zip, &unit_path, &root)?;
+                        let filter = x.path_filter()?;
+                        let mut walk = DirWalk::new(
+                            x,
+                            &long_path,
+                            filter.as_ref(),
+                            &config.settings.backup_retry,
+                        );
+                        add_directory(&mut zip, &long_path, &root, &mut walk, monitor)?;
                     }
                 }
+                manifest.units.push(ManifestUnit {
+                    index,
+                    root: long_path
+                        .file_name()
+                        .ok_or(BackupFileError::NonePathError)?
+                        .to_string_lossy()
+                        .to_string(),
+                });
             } else {
                 Err(BackupFileError::NotExists(unit_path))?;
             }
@@ -124,7 +425,15 @@ pub fn compress_to_file(save_paths: &[SaveUnit], zip_path: &Path) -> Result<u64,
         })
         .filter_map(|x| x.err())
         .collect();
-    zip.finish().map_err(|e| CompressError::Single(e.into()))?;
+    if monitor.is_cancelled() {
+        return Err(CompressError::Single(BackupFileError::Cancelled));
+    }
+    write_manifest(&mut zip, &manifest).map_err(CompressError::Single)?;
+    let entries = zip
+        .finish_into_readable()
+        .map_err(|e| CompressError::Single(e.into()))?
+        .len();
+    monitor.finish();
     if !compress_errors.is_empty() {
         Err(CompressError::Multiple(compress_errors))
     } else {
@@ -132,130 +441,1251 @@ pub fn compress_to_file(save_paths: &[SaveUnit], zip_path: &Path) -> Result<u64,
         let file_size = fs::metadata(zip_path)
             .map_err(|e| CompressError::Single(e.into()))?
             .len();
-        Result::Ok(file_size)
+        Result::Ok(CompressedArchive {
+            size: file_size,
+            entries,
+        })
+    }
+}
+
+/// Read a zip written by `compress_to_file` back from disk and check every entry
+///
+/// Each entry is decompressed into a sink, so its CRC is checked without extracting
+/// anything. Fails when an entry cannot be read, a CRC does not match or the archive does
+/// not hold `expected_entries` entries
+pub fn verify_archive(zip_path: &Path, expected_entries: usize) -> anyhow::Result<()> {
+    let file = File::open(to_long_path(zip_path))?;
+    let mut archive = ZipArchive::new(file).context("cannot read the archive")?;
+    if archive.len() != expected_entries {
+        anyhow::bail!(
+            "the archive holds {} entries, {} were written",
+            archive.len(),
+            expected_entries
+        );
+    }
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .with_context(|| format!("cannot read entry {index}"))?;
+        io::copy(&mut entry, &mut io::sink())
+            .with_context(|| format!("entry {} is damaged", entry.name()))?;
+    }
+    Ok(())
+}
+
+/// Remove the files under `dir` that are excluded from the unit, paths are relative to
+/// `root`. Directories emptied by the removal are removed too
+fn remove_excluded(root: &Path, dir: &Path, filter: &PathFilter) -> Result<(), BackupFileError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+        let relative = entry_path.strip_prefix(root).unwrap_or(&entry_path);
+        if entry.file_type()?.is_dir() {
+            if filter.is_excluded(relative) {
+                fs::remove_dir_all(&entry_path)?;
+                continue;
+            }
+            remove_excluded(root, &entry_path, filter)?;
+            if fs::read_dir(&entry_path)?.next().is_none() {
+                fs::remove_dir(&entry_path)?;
+            }
+        } else if !filter.includes_file(relative) {
+            fs::remove_file(&entry_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Bring the entries of the current unit folder `current` that survive the restore into the
+/// extracted folder `staged`, so swapping the two folders keeps them
+///
+/// With `excluded_by`, only the entries excluded by the filter survive, as
+/// `ApplyCleanMode::Full` removes everything else. Without it, every path the snapshot does
+/// not replace survives. A path that is a file on one side and a folder on the other is
+/// replaced as a whole with `replace_conflicts`, otherwise it fails with
+/// `BackupFileError::PathConflict`. Paths are relative to `root`
+fn carry_over(
+    root: &Path,
+    current: &Path,
+    staged: &Path,
+    excluded_by: Option<&PathFilter>,
+    replace_conflicts: bool,
+) -> Result<(), BackupFileError> {
+    for entry in fs::read_dir(current)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+        let relative = entry_path.strip_prefix(root).unwrap_or(&entry_path);
+        let is_dir = entry.file_type()?.is_dir();
+        let keep = match excluded_by {
+            Some(filter) if is_dir => filter.is_excluded(relative),
+            Some(filter) => !filter.includes_file(relative),
+            None => true,
+        };
+        let target = staged.join(entry.file_name());
+        match fs::symlink_metadata(&target) {
+            Err(_) if keep => link_or_copy(&entry_path, &target)?,
+            // Only reached with a filter, the included folder may still hold excluded files
+            Err(_) if is_dir => {
+                carry_over(root, &entry_path, &target, excluded_by, replace_conflicts)?
+            }
+            Ok(metadata) if is_dir && metadata.is_dir() => {
+                carry_over(root, &entry_path, &target, excluded_by, replace_conflicts)?
+            }
+            Ok(metadata) if !replace_conflicts && is_dir != metadata.is_dir() => {
+                return Err(BackupFileError::PathConflict(from_long_path(&entry_path)));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Hard link `from` to `to`, or copy it where hard links are not supported, so the current
+/// save stays untouched until the swap. Links are recreated rather than followed
+fn link_or_copy(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.is_symlink() {
+        create_link(&fs::read_link(from)?, to)
+    } else if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            link_or_copy(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::hard_link(from, to).or_else(|_| fs::copy(from, to).map(|_| ()))
+    }
+}
+
+/// `fs::rename`, falling back to a copy and a removal when `to` is on another file system
+fn move_path(from: &Path, to: &Path) -> Result<(), BackupFileError> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+        result => return Ok(result?),
+    }
+    let is_dir = fs::symlink_metadata(from)?.is_dir();
+    let copied = if is_dir {
+        let options = fs_extra::dir::CopyOptions::new().content_only(true);
+        fs::create_dir_all(to)
+            .map_err(BackupFileError::from)
+            .and_then(|_| Ok(fs_extra::dir::copy(from, to, &options).map(|_| ())?))
+    } else {
+        fs::copy(from, to)
+            .map(|_| ())
+            .map_err(BackupFileError::from)
+    };
+    if let Err(e) = copied {
+        // Do not leave a partial copy behind, `from` is still complete
+        let _ = fs::remove_dir_all(to).or_else(|_| fs::remove_file(to));
+        return Err(e);
+    }
+    if is_dir {
+        fs::remove_dir_all(from)?;
+    } else {
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+/// Replace `target` with `new`
+///
+/// `target` is renamed aside first and only removed once `new` took its place, it is moved
+/// back when that fails, so the save is either fully replaced or left as it was
+fn swap_into_place(new: &Path, target: &Path) -> Result<(), BackupFileError> {
+    if fs::symlink_metadata(target).is_err() {
+        return move_path(new, target);
+    }
+    let aside = free_sibling(target, "replaced").ok_or(BackupFileError::NonePathError)?;
+    move_path(target, &aside)?;
+    if let Err(e) = move_path(new, target) {
+        if let Err(back) = move_path(&aside, target) {
+            error!(target:"rgsm::backup::archive", "Cannot move {:?} back from {:?}: {:?}", from_long_path(target), from_long_path(&aside), back);
+        }
+        return Err(e);
+    }
+    if let Err(e) = fs::remove_dir_all(&aside) {
+        warn!(target:"rgsm::backup::archive", "Cannot remove the replaced save {:?}: {}", from_long_path(&aside), e);
+    }
+    Ok(())
+}
+
+/// A hidden path beside `path` that is not taken yet
+///
+/// Leftovers of an interrupted restore may hold the only copy of a save, so they are kept
+fn free_sibling(path: &Path, label: &str) -> Option<PathBuf> {
+    let parent = path.parent()?;
+    let base = format!(".{}.rgsm-{label}", path.file_name()?.to_string_lossy());
+    let mut sibling = parent.join(&base);
+    let mut n = 1;
+    while fs::symlink_metadata(&sibling).is_ok() {
+        sibling = parent.join(format!("{base}_{n}"));
+        n += 1;
     }
+    Some(sibling)
+}
+
+/// A link stored in a snapshot by `SymlinkMode::Preserve`
+struct StoredLink {
+    /// Relative to the extraction folder, starts with the unit folder name
+    path: PathBuf,
+    target: PathBuf,
+}
+
+/// Attributes of an extracted file, applied once the file is moved into place
+struct StoredAttributes {
+    /// Relative to the extraction folder, starts with the unit folder name
+    path: PathBuf,
+    /// Only present in snapshots that store an extended timestamp
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    mode: Option<u32>,
+}
+
+/// Links and file attributes found while extracting a snapshot
+#[derive(Default)]
+struct Extracted {
+    links: Vec<StoredLink>,
+    files: Vec<StoredAttributes>,
+}
+
+/// Uncompressed size of every unit in the snapshot, keyed by the unit folder or file name
+fn unit_sizes<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+) -> Result<HashMap<PathBuf, u64>, BackupFileError> {
+    let mut sizes = HashMap::new();
+    for i in 0..zip.len() {
+        let entry = zip.by_index(i)?;
+        let Some(unit_name) = entry.enclosed_name().and_then(|path| {
+            path.components()
+                .next()
+                .map(|c| PathBuf::from(c.as_os_str()))
+        }) else {
+            continue;
+        };
+        *sizes.entry(unit_name).or_default() += entry.size();
+    }
+    Ok(sizes)
+}
+
+/// Extract `zip` into `dir` like `ZipArchive::extract`, except that links are
+/// returned instead of created, so moving the units into place never follows them
+///
+/// Only the entries of `unit_name` are extracted when it is given. Every file is checked
+/// against the size stored in the snapshot, so a short write is never taken for a success
+fn extract_archive<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    dir: &Path,
+    unit_name: Option<&Path>,
+    monitor: &mut ArchiveMonitor,
+) -> Result<Extracted, BackupFileError> {
+    let mut extracted = Extracted::default();
+    for i in 0..zip.len() {
+        monitor.check()?;
+        let mut entry = zip.by_index(i)?;
+        let Some(relative) = entry.enclosed_name() else {
+            warn!(target:"rgsm::backup::archive", "Skipping unsafe entry {:?}", entry.name());
+            continue;
+        };
+        // Rebuild with native separators, extended-length paths do not accept `/`
+        let relative: PathBuf = relative.components().collect();
+        if unit_name.is_some_and(|unit_name| !relative.starts_with(unit_name)) {
+            continue;
+        }
+        let out_path = dir.join(&relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path)?;
+            continue;
+        }
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if entry.is_symlink() {
+            let mut target = String::new();
+            entry.read_to_string(&mut target)?;
+            extracted.links.push(StoredLink {
+                path: relative,
+                target: PathBuf::from(target),
+            });
+            continue;
+        }
+        let written = copy_with_progress(
+            &mut entry,
+            &mut File::create(&out_path)?,
+            &relative,
+            monitor,
+        )?;
+        if written != entry.size() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{relative:?} has {written} of {} bytes", entry.size()),
+            )
+            .into());
+        }
+        extracted.files.push(stored_attributes(&entry, relative));
+    }
+    Ok(extracted)
+}
+
+fn stored_attributes<R: Read>(entry: &ZipFile<R>, path: PathBuf) -> StoredAttributes {
+    let modified = entry.extra_data_fields().find_map(|field| match field {
+        ExtraField::ExtendedTimestamp(timestamp) => timestamp.mod_time(),
+        _ => None,
+    });
+    StoredAttributes {
+        path,
+        modified: modified.map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds.into())),
+        #[cfg(unix)]
+        mode: entry.unix_mode(),
+    }
+}
+
+/// Fail with `BackupFileError::FileInUse` when another process keeps `path` open without
+/// sharing it, which only happens on Windows
+pub(super) fn check_not_in_use(path: &Path) -> Result<(), BackupFileError> {
+    match File::options().write(true).open(path) {
+        Err(e) if is_sharing_violation(&e) => Err(BackupFileError::FileInUse(path.to_path_buf())),
+        _ => Ok(()),
+    }
+}
+
+/// Extract the file entry `index` of `zip` to `target`, creating the missing parent folders
+///
+/// The entry is written beside `target` and renamed over it, so a failed extraction leaves
+/// the current file untouched
+pub(super) fn extract_file_to<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    index: usize,
+    target: &Path,
+    restore_time: bool,
+) -> Result<(), BackupFileError> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let staged = free_sibling(target, "restore").ok_or(BackupFileError::NonePathError)?;
+    let mut entry = zip.by_index(index)?;
+    // The file is closed before the rename, Windows cannot rename open files
+    let written = File::create(&staged).and_then(|mut file| io::copy(&mut entry, &mut file));
+    let result = written.map_err(BackupFileError::from).and_then(|written| {
+        if written != entry.size() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{:?} has {written} of {} bytes", entry.name(), entry.size()),
+            )
+            .into());
+        }
+        restore_file_attributes(
+            &staged,
+            &stored_attributes(&entry, PathBuf::new()),
+            restore_time,
+        )?;
+        fs::rename(&staged, target).map_err(|e| {
+            if is_sharing_violation(&e) {
+                BackupFileError::FileInUse(target.to_path_buf())
+            } else {
+                e.into()
+            }
+        })
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    result
+}
+
+/// Compress the file `path` alone into `zip_path` as `entry_name`, which the manifest
+/// attributes to the save unit `index`
+pub(super) fn compress_single_file(
+    path: &Path,
+    entry_name: &str,
+    index: usize,
+    zip_path: &Path,
+) -> Result<(), BackupFileError> {
+    let mut zip = ZipWriter::new(File::create(to_long_path(zip_path))?);
+    zip.start_file(entry_name, file_options(&fs::metadata(path)?))?;
+    zip.write_all(&fs::read(path)?)?;
+    let root = entry_name.split('/').next().unwrap_or(entry_name);
+    write_manifest(
+        &mut zip,
+        &SnapshotManifest {
+            units: vec![ManifestUnit {
+                index,
+                root: root.to_string(),
+            }],
+        },
+    )?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// The files of the unit `unit_name` that are still in the extraction folder `dir`,
+/// files removed by the unit filter are not restored and keep their own attributes
+fn unit_attributes<'a>(
+    files: &'a [StoredAttributes],
+    dir: &Path,
+    unit_name: &Path,
+) -> Vec<&'a StoredAttributes> {
+    files
+        .iter()
+        .filter(|f| f.path.starts_with(unit_name) && dir.join(&f.path).is_file())
+        .collect()
+}
+
+/// Apply the stored attributes to the files moved under `parent`, failures are only logged
+fn restore_attributes(files: &[&StoredAttributes], parent: &Path, restore_time: bool) {
+    for attributes in files {
+        let path = parent.join(&attributes.path);
+        if let Err(e) = restore_file_attributes(&path, attributes, restore_time) {
+            warn!(target:"rgsm::backup::archive", "Cannot restore the attributes of {:?}: {}", from_long_path(&path), e);
+        }
+    }
+}
+
+/// The time is set before the mode, a read-only mode forbids opening the file for writing
+fn restore_file_attributes(
+    path: &Path,
+    attributes: &StoredAttributes,
+    restore_time: bool,
+) -> io::Result<()> {
+    if let Some(modified) = attributes.modified.filter(|_| restore_time) {
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(modified)?;
+    }
+    #[cfg(unix)]
+    if let Some(mode) = attributes.mode {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))?;
+    }
+    Ok(())
+}
+
+/// Create a link at `link` pointing to `target`
+///
+/// An existing link is replaced, but a real file or folder at `link` is kept
+/// and reported as an error
+fn restore_link(link: &Path, target: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(link) {
+        // A link to a folder is a directory on Windows
+        Ok(metadata) if metadata.is_symlink() => {
+            fs::remove_file(link).or_else(|_| fs::remove_dir(link))?
+        }
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{link:?} is not a link"),
+            ));
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    create_link(target, link)
+}
+
+#[cfg(unix)]
+fn create_link(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+/// Windows needs to know whether the target is a folder, creating links may
+/// also require developer mode or administrator rights
+#[cfg(windows)]
+fn create_link(target: &Path, link: &Path) -> io::Result<()> {
+    let resolved = link
+        .parent()
+        .map_or_else(|| target.to_path_buf(), |p| p.join(target));
+    if resolved.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn create_link(_: &Path, _: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// A unit being restored, extracted beside its save path so it can be swapped in by renames
+struct StagedUnit<'a> {
+    unit: &'a SaveUnit,
+    unit_path: PathBuf,
+    /// The unit folder or file name inside the snapshot
+    unit_name: PathBuf,
+    /// Uncompressed size of the unit in the snapshot
+    size: u64,
+    /// Hidden folder beside `unit_path` the unit is extracted to, removed on drop
+    staging: PathBuf,
+}
+
+impl<'a> StagedUnit<'a> {
+    fn new(
+        unit: &'a SaveUnit,
+        config: &Config,
+        sizes: &HashMap<PathBuf, u64>,
+    ) -> Result<Self, BackupFileError> {
+        // 获取当前设备的路径，如果不存在则返回 NoPathForDevice
+        let unit_path_str = unit
+            .get_path_for_device(get_current_device_id())
+            .ok_or(BackupFileError::NoPathForDevice)?;
+        // 使用 path_resolver 解析路径变量
+        let mut unit_path = to_long_path(&crate::path_resolver::resolve_path(
+            unit_path_str,
+            None,
+            config,
+        )?);
+        let unit_name = PathBuf::from(
+            unit_path
+                .file_name()
+                .ok_or(BackupFileError::NonePathError)?,
+        );
+        // A save linked elsewhere stays a link, what it points to is replaced
+        if fs::symlink_metadata(&unit_path).is_ok_and(|m| m.is_symlink()) {
+            unit_path = fs::canonicalize(&unit_path)?;
+        }
+        let size = *sizes
+            .get(&unit_name)
+            .ok_or_else(|| BackupFileError::NotExists(unit_name.clone()))?;
+        let staging = free_sibling(&unit_path, "restore").ok_or(BackupFileError::NonePathError)?;
+        Ok(StagedUnit {
+            unit,
+            unit_path,
+            unit_name,
+            size,
+            staging,
+        })
+    }
+
+    /// Fail before anything is extracted when the unit obviously does not fit beside its save
+    fn check_free_space(&self) -> Result<(), BackupFileError> {
+        let parent = self
+            .unit_path
+            .parent()
+            .ok_or(BackupFileError::NonePathError)?;
+        let available = fs4::available_space(existing_ancestor(parent))?;
+        if self.size > available {
+            return Err(BackupFileError::InsufficientSpace {
+                path: from_long_path(parent),
+                required: self.size,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// Put the extracted unit in place of the save, honoring `delete_before_apply`
+    fn apply(
+        &self,
+        extracted: &Extracted,
+        restore_time: bool,
+        app_handle: Option<&AppHandle>,
+    ) -> Result<(), BackupFileError> {
+        let new_path = self.staging.join(&self.unit_name);
+        if fs::symlink_metadata(&new_path).is_err() {
+            return Err(BackupFileError::NotExists(self.unit_name.clone()));
+        }
+        if self.unit.unit_type == SaveUnitType::File {
+            // 文件单元只有一个被追踪的文件，各种清理方式都是直接替换
+            let files = unit_attributes(&extracted.files, &self.staging, &self.unit_name);
+            restore_attributes(&files, &self.staging, restore_time);
+            // Renaming over a file replaces it atomically
+            return move_path(&new_path, &self.unit_path);
+        }
+        let filter = self.unit.path_filter()?;
+        if let Some(filter) = &filter {
+            // 旧快照可能包含现在被排除的文件，不应恢复它们
+            remove_excluded(&new_path, &new_path, filter)?;
+        }
+        let files = unit_attributes(&extracted.files, &self.staging, &self.unit_name);
+        restore_attributes(&files, &self.staging, restore_time);
+        self.restore_links(extracted, app_handle)?;
+        if fs::symlink_metadata(&self.unit_path).is_ok() {
+            match (self.unit.delete_before_apply, &filter) {
+                (ApplyCleanMode::Full, None) => {}
+                // 只删除会被快照覆盖的文件，保留被排除的文件
+                (ApplyCleanMode::Full, Some(filter)) => carry_over(
+                    &self.unit_path,
+                    &self.unit_path,
+                    &new_path,
+                    Some(filter),
+                    true,
+                )?,
+                // 只替换快照中存在的路径，保留快照之后新建的文件，与快照中路径冲突的文件或文件夹被整个替换
+                (ApplyCleanMode::TrackedOnly, _) => {
+                    carry_over(&self.unit_path, &self.unit_path, &new_path, None, true)?
+                }
+                // 只覆盖快照中的文件，不删除任何文件，文件与文件夹冲突时该单元恢复失败
+                (ApplyCleanMode::None, _) => {
+                    carry_over(&self.unit_path, &self.unit_path, &new_path, None, false)?
+                }
+            }
+        }
+        swap_into_place(&new_path, &self.unit_path)
+    }
+
+    /// Create the links of the unit in the staging folder, failures are reported and skipped
+    fn restore_links(
+        &self,
+        extracted: &Extracted,
+        app_handle: Option<&AppHandle>,
+    ) -> Result<(), BackupFileError> {
+        let parent = self
+            .unit_path
+            .parent()
+            .ok_or(BackupFileError::NonePathError)?;
+        for link in extracted
+            .links
+            .iter()
+            .filter(|l| l.path.starts_with(&self.unit_name))
+        {
+            let Err(e) = restore_link(&self.staging.join(&link.path), &link.target) else {
+                continue;
+            };
+            let link_path = parent.join(&link.path);
+            warn!(target:"rgsm::backup::archive", "Cannot recreate link {:?} -> {:?}: {}", link_path, link.target, e);
+            if let Some(app_handle) = app_handle {
+                app_handle
+                    .emit(
+                        "Notification",
+                        IpcNotification {
+                            level: NotificationLevel::warning,
+                            title: t!("backend.notification.warning").to_string(),
+                            msg: t!(
+                                "backend.archive.link_not_restored",
+                                path = from_long_path(&link_path).to_string_lossy()
+                            )
+                            .to_string(),
+                        },
+                    )
+                    .map_err(anyhow::Error::from)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagedUnit<'_> {
+    fn drop(&mut self) {
+        match fs::remove_dir_all(&self.staging) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                warn!(target:"rgsm::backup::archive", "Cannot remove {:?}: {}", from_long_path(&self.staging), e);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Create the missing parent folder of `unit_path`, warning the user since the save path
+/// may be wrong
+fn create_parent(unit_path: &Path, app_handle: Option<&AppHandle>) -> Result<(), BackupFileError> {
+    let parent = unit_path.parent().ok_or(BackupFileError::NonePathError)?;
+    if parent.exists() {
+        return Ok(());
+    }
+    let shown = from_long_path(parent);
+    // 若文件夹不存在，需要发出警告
+    warn!(target:"rgsm::backup::archive", "Path {:#?} not exists, auto created", shown);
+    if let Some(app_handle) = app_handle {
+        app_handle
+            .emit(
+                "Notification",
+                IpcNotification {
+                    level: NotificationLevel::warning,
+                    title: t!("backend.notification.warning").to_string(),
+                    msg: t!(
+                        "backend.archive.file_not_exist",
+                        path = shown.to_string_lossy()
+                    )
+                    .to_string(),
+                },
+            )
+            .map_err(anyhow::Error::from)?;
+    }
+    fs::create_dir_all(parent)?;
+    Ok(())
 }
 
 /// Decompress a zip file to their original path
+///
+/// Every unit is fully extracted to a hidden folder beside its save path before any save is
+/// touched, then swapped in by renames. A failed or cancelled extraction, or a lack of free
+/// space detected beforehand, leaves all saves as they were
 pub fn decompress_from_file(
     save_paths: &[SaveUnit],
     backup_path: &Path,
     date: &str,
     app_handle: Option<&AppHandle>,
+    monitor: &mut ArchiveMonitor,
 ) -> Result<(), CompressError> {
     let zip_path = backup_path.join([date, ".zip"].concat());
     let file = File::open(zip_path).map_err(|e| CompressError::Single(e.into()))?;
     let mut zip = zip::ZipArchive::new(file).map_err(|e| CompressError::Single(e.into()))?;
+    let config = crate::config::get_config().map_err(|e| BackupFileError::Unexpected(e.into()))?;
+    let sizes = unit_sizes(&mut zip)?;
 
-    let tmp_folder = temp_dir::TempDir::new().map_err(|e| CompressError::Single(e.into()))?; // Temporary directory for extraction
-    let tmp_folder = tmp_folder.path().to_path_buf(); // Convert to PathBuf for easier manipulation
-    fs::create_dir_all(&tmp_folder).map_err(|e| CompressError::Single(e.into()))?;
-    zip.extract(&tmp_folder)
-        .map_err(|e| CompressError::Single(e.into()))?;
+    // 无法解析的单元与其他错误一起报告，不影响其余单元
+    let mut decompress_errors = Vec::new();
+    let mut staged = Vec::new();
+    let disabled = save_paths.iter().filter(|unit| !unit.enabled).count();
+    if disabled > 0 {
+        info!(target:"rgsm::backup::archive", "Skipping {} disabled save units", disabled);
+    }
+    // 停用的单元不会被解压，也不会执行恢复前的删除
+    for unit in save_paths.iter().filter(|unit| unit.enabled) {
+        match StagedUnit::new(unit, &config, &sizes) {
+            Ok(unit) => staged.push(unit),
+            Err(e) => decompress_errors.push(e),
+        }
+    }
+    for unit in &staged {
+        unit.check_free_space()?;
+    }
 
-    let decompress_errors: Vec<_> = save_paths
-        .iter()
-        .map(|unit| {
-            // 获取当前设备 ID，并将 ConfigError 转换为 BackupFileError
-            let current_device_id = &get_current_device_id();
-            // 获取当前设备的路径，如果不存在则返回 NonePathError
-            let unit_path_str = unit.get_path_for_device(current_device_id)
-                .ok_or(BackupFileError::NonePathError)?;
+    monitor.set_total(staged.iter().map(|unit| unit.size).sum());
+    let mut extracted = Vec::with_capacity(staged.len());
+    for unit in &staged {
+        create_parent(&unit.unit_path, app_handle)?;
+        fs::create_dir_all(&unit.staging).map_err(BackupFileError::from)?;
+        extracted.push(extract_archive(
+            &mut zip,
+            &unit.staging,
+            Some(&unit.unit_name),
+            monitor,
+        )?);
+    }
+    monitor.finish();
 
-            // 使用 path_resolver 解析路径变量
-            let config = crate::config::get_config().map_err(|e| BackupFileError::Unexpected(e.into()))?;
-            let unit_path = crate::path_resolver::resolve_path(unit_path_str, None, &config)?;
-            let original_path = tmp_folder.join(
-                unit_path
-                    .file_name()
-                    .ok_or(BackupFileError::NonePathError)?,
-            ); // Temp file location path
-            if original_path.exists() {
-                match unit.unit_type {
-                    SaveUnitType::File => {
-                        let option = fs_extra::file::CopyOptions::new().overwrite(true);
-                        let prefix_root =
-                            unit_path.parent().ok_or(BackupFileError::NonePathError)?;
-                        if !prefix_root.exists() {
-                            // 若文件夹不存在，需要发出警告
-                            warn!(target:"rgsm::backup::archive","Path {:#?} not exists, auto created",prefix_root
-                                                .to_str()
-                                                .unwrap_or("prefix_root.to_str error"));
-                            if let Some(app_handle) = app_handle {
-                                 app_handle
-                                .emit(
-                                    "Notification",
-                                    IpcNotification {
-                                        level: NotificationLevel::warning,
-                                        title: "WARNING".to_string(),
-                                        msg: t!(
-                                            "backend.archive.file_not_exist",
-                                            path = prefix_root
-                                                .to_str()
-                                                .unwrap_or("prefix_root.to_str error")
-                                        )
-                                        .to_string(),
-                                    },
-                                )
-                                .map_err(anyhow::Error::from)?;
-                            }else {
-                                // TODO:发出警告?
-                            }
-                            fs::create_dir_all(prefix_root)?;
-                        }
-                        if unit.delete_before_apply && unit_path.exists() {
-                            fs::remove_file(&unit_path)?;
-                        }
-                        move_file(original_path, &unit_path, &option)?;
-                    }
-                    SaveUnitType::Folder => {
-                        let option = fs_extra::dir::CopyOptions::new().overwrite(true);
-                        let target_path =
-                            unit_path.parent().ok_or(BackupFileError::NonePathError)?;
-                        if !target_path.exists() {
-                            // 若文件夹不存在，需要发出警告
-                            warn!(target:"rgsm::backup::archive","Path {:#?} not exists, auto created",target_path
-                                                .to_str()
-                                                .unwrap_or("prefix_root.to_str error"));
-                            if let Some(app_handle) = app_handle {
-                            app_handle
-                                .emit(
-                                    "Notification",
-                                    IpcNotification {
-                                        level: NotificationLevel::warning,
-                                        title: "WARNING".to_string(),
-                                        msg: t!(
-                                            "backend.archive.file_not_exist",
-                                            path = target_path
-                                                .to_str()
-                                                .unwrap_or("target_path.to_str() error")
-                                        )
-                                        .to_string(),
-                                    },
-                                )
-                                .map_err(anyhow::Error::from)?;
-                            }else{
-                                // TODO:发出警告?
-                            }
-                            fs::create_dir_all(target_path)?;
-                        }
-                        if unit.delete_before_apply && unit_path.exists() {
-                            fs::remove_dir_all(&unit_path)?;
-                        }
-                        move_dir(original_path, target_path, &option)?;
-                    }
-                }
-            } else {
-                Err(BackupFileError::NotExists(original_path))?;
-            }
-            Result::<(), BackupFileError>::Ok(())
-        })
-        .filter_map(|x| x.err())
-        .collect();
-    fs::remove_dir_all(tmp_folder).map_err(|e| CompressError::Single(e.into()))?; //TODO:tmp dir
+    let restore_time = config.settings.restore_timestamps;
+    for (unit, extracted) in staged.iter().zip(&extracted) {
+        if let Err(e) = unit.apply(extracted, restore_time, app_handle) {
+            decompress_errors.push(e);
+        }
+    }
     if !decompress_errors.is_empty() {
         Err(CompressError::Multiple(decompress_errors))
     } else {
         Result::Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn unit(include: &[&str], exclude: &[&str]) -> SaveUnit {
+        SaveUnit {
+            delete_before_apply: ApplyCleanMode::Full,
+            include_globs: include.iter().map(|s| s.to_string()).collect(),
+            exclude_globs: exclude.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Create `files` under `root` and return it
+    fn tree(root: &Path, files: &[&str]) {
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file.as_bytes()).unwrap();
+        }
+    }
+
+    const FILES: [&str; 5] = [
+        "profile.sav",
+        "slots/1/slot.sav",
+        "screenshots/2024/shot.png",
+        "logs/crash/dump.dmp",
+        "shader.cache",
+    ];
+
+    #[test]
+    fn excluded_files_are_not_archived() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        tree(&root, &FILES);
+        let filter = unit(&[], &["screenshots", "**/*.dmp", "*.cache"])
+            .path_filter()
+            .unwrap();
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let retry = BackupRetry::default();
+        let save_unit = unit(&[], &[]);
+        let mut walk = DirWalk::new(&save_unit, &root, filter.as_ref(), &retry);
+        add_directory(
+            &mut zip,
+            &root,
+            Path::new("Save"),
+            &mut walk,
+            &mut ArchiveMonitor::silent(),
+        )
+        .unwrap();
+        let archive = zip::ZipArchive::new(zip.finish().unwrap()).unwrap();
+        let mut files: Vec<_> = archive
+            .file_names()
+            .filter(|n| !n.ends_with('/'))
+            .map(|n| n.replace('\\', "/"))
+            .collect();
+        files.sort();
+        assert_eq!(files, ["Save/profile.sav", "Save/slots/1/slot.sav"]);
+    }
+
+    #[test]
+    fn include_globs_limit_the_archived_files() {
+        let filter = unit(&["slots", "*.sav"], &["slots/2"])
+            .path_filter()
+            .unwrap()
+            .unwrap();
+        assert!(filter.includes_file(Path::new("profile.sav")));
+        assert!(filter.includes_file(Path::new("slots/1/slot.sav")));
+        assert!(!filter.includes_file(Path::new("slots/2/slot.sav")));
+        assert!(!filter.includes_file(Path::new("screenshots/2024/shot.sav")));
+        assert!(unit(&[], &[]).path_filter().unwrap().is_none());
+        assert!(unit(&["[invalid"], &[]).path_filter().is_err());
+    }
+
+    #[test]
+    fn restore_keeps_excluded_files() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        tree(&root, &FILES);
+        let filter = unit(&[], &["screenshots", "**/*.dmp"])
+            .path_filter()
+            .unwrap()
+            .unwrap();
+
+        // excluded files of an extracted snapshot are not restored
+        let extracted = dir.path().join("Extracted");
+        tree(
+            &extracted,
+            &["profile.sav", "screenshots/old.png", "logs/crash/old.dmp"],
+        );
+        remove_excluded(&extracted, &extracted, &filter).unwrap();
+        assert!(!extracted.join("screenshots").exists());
+        assert!(!extracted.join("logs").exists());
+        assert!(extracted.join("profile.sav").is_file());
+
+        // delete_before_apply only replaces what the snapshot covers
+        carry_over(&root, &root, &extracted, Some(&filter), true).unwrap();
+        assert!(extracted.join("screenshots/2024/shot.png").is_file());
+        assert!(extracted.join("logs/crash/dump.dmp").is_file());
+        assert_eq!(
+            fs::read(extracted.join("profile.sav")).unwrap(),
+            b"profile.sav"
+        );
+        assert!(!extracted.join("shader.cache").exists());
+        assert!(!extracted.join("slots").exists());
+        // The current save is left alone until the swap
+        assert!(root.join("shader.cache").is_file());
+    }
+
+    #[test]
+    fn tracked_only_keeps_files_created_after_the_snapshot() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let snapshot = dir.path().join("Snapshot");
+        tree(&snapshot, &["profile.sav", "slots/1/slot.sav", "config"]);
+        let root = dir.path().join("Save");
+        tree(
+            &root,
+            &[
+                "profile.sav",
+                "slots/1/slot.sav",
+                "slots/2/slot.sav",
+                "screenshots/shot.png",
+                "config/user.ini",
+            ],
+        );
+
+        carry_over(&root, &root, &snapshot, None, true).unwrap();
+        assert_eq!(
+            fs::read(snapshot.join("slots/1/slot.sav")).unwrap(),
+            b"slots/1/slot.sav"
+        );
+        assert!(snapshot.join("slots/2/slot.sav").is_file());
+        assert!(snapshot.join("screenshots/shot.png").is_file());
+        // `config` is a file in the snapshot, the folder in its way is replaced
+        assert!(snapshot.join("config").is_file());
+    }
+
+    /// Archive `root` as the folder `Save` and return the sorted entry names
+    fn archive_names(root: &Path, symlinks: SymlinkMode) -> (Vec<String>, Vec<u8>) {
+        let save_unit = SaveUnit {
+            symlinks,
+            ..unit(&[], &[])
+        };
+        let retry = BackupRetry::default();
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let mut walk = DirWalk::new(&save_unit, root, None, &retry);
+        add_directory(
+            &mut zip,
+            &root.to_path_buf(),
+            Path::new("Save"),
+            &mut walk,
+            &mut ArchiveMonitor::silent(),
+        )
+        .unwrap();
+        let data = zip.finish().unwrap().into_inner();
+        let archive = ZipArchive::new(Cursor::new(data.clone())).unwrap();
+        let mut names: Vec<_> = archive
+            .file_names()
+            .filter(|n| !n.ends_with('/'))
+            .map(|n| n.replace('\\', "/"))
+            .collect();
+        names.sort();
+        (names, data)
+    }
+
+    /// `Save/alias` links to `slots`, `slots/1/loop` links back to `Save`
+    #[cfg(unix)]
+    fn linked_tree(root: &Path) {
+        use std::os::unix::fs::symlink;
+        tree(root, &["profile.sav", "slots/1/slot.sav"]);
+        symlink("slots", root.join("alias")).unwrap();
+        symlink("../..", root.join("slots/1/loop")).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn following_links_stops_at_loops() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        linked_tree(&root);
+
+        let (names, _) = archive_names(&root, SymlinkMode::Follow);
+        assert_eq!(
+            names,
+            [
+                "Save/alias/1/slot.sav",
+                "Save/profile.sav",
+                "Save/slots/1/slot.sav"
+            ]
+        );
+        let (names, _) = archive_names(&root, SymlinkMode::Skip);
+        assert_eq!(names, ["Save/profile.sav", "Save/slots/1/slot.sav"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn preserved_links_are_recreated_on_restore() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        linked_tree(&root);
+
+        let (names, data) = archive_names(&root, SymlinkMode::Preserve);
+        assert_eq!(
+            names,
+            [
+                "Save/alias",
+                "Save/profile.sav",
+                "Save/slots/1/loop",
+                "Save/slots/1/slot.sav"
+            ]
+        );
+
+        let extracted = dir.path().join("Extracted");
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        let links = extract_archive(
+            &mut archive,
+            &extracted,
+            None,
+            &mut ArchiveMonitor::silent(),
+        )
+        .unwrap()
+        .links;
+        // Links are not created in the extraction folder
+        assert!(fs::symlink_metadata(extracted.join("Save/alias")).is_err());
+        assert!(extracted.join("Save/slots/1/slot.sav").is_file());
+        let mut stored: Vec<_> = links
+            .iter()
+            .map(|l| (l.path.clone(), l.target.clone()))
+            .collect();
+        stored.sort();
+        assert_eq!(
+            stored,
+            [
+                (PathBuf::from("Save/alias"), PathBuf::from("slots")),
+                (PathBuf::from("Save/slots/1/loop"), PathBuf::from("../..")),
+            ]
+        );
+
+        let restored = dir.path().join("Restored");
+        tree(&restored, &["Save/slots/1/slot.sav"]);
+        std::os::unix::fs::symlink("elsewhere", restored.join("Save/alias")).unwrap();
+        for link in &links {
+            restore_link(&restored.join(&link.path), &link.target).unwrap();
+        }
+        assert_eq!(
+            fs::read_link(restored.join("Save/alias")).unwrap(),
+            Path::new("slots")
+        );
+        assert!(restored.join("Save/alias/1/slot.sav").is_file());
+        // A real folder in the way is kept
+        assert!(restore_link(&restored.join("Save/slots"), Path::new("x")).is_err());
+        assert!(restored.join("Save/slots/1/slot.sav").is_file());
+    }
+
+    #[test]
+    fn modification_times_round_trip() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        let files = ["slot.sav", "sub/profile.sav"];
+        tree(&root, &files);
+        let modified = SystemTime::now() - Duration::from_secs(3 * 24 * 3600 + 17);
+        for file in files {
+            let file = File::options().write(true).open(root.join(file)).unwrap();
+            file.set_modified(modified).unwrap();
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(root.join("slot.sav"), fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let (_, data) = archive_names(&root, SymlinkMode::Follow);
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i).unwrap();
+            if entry.is_file() {
+                assert_eq!(entry.last_modified(), dos_time(modified));
+            }
+        }
+
+        for restore_time in [true, false] {
+            let extracted_dir = dir.path().join(format!("Extracted-{restore_time}"));
+            let extracted = extract_archive(
+                &mut archive,
+                &extracted_dir,
+                None,
+                &mut ArchiveMonitor::silent(),
+            )
+            .unwrap();
+            let unit_files = unit_attributes(&extracted.files, &extracted_dir, Path::new("Save"));
+            assert_eq!(unit_files.len(), files.len());
+            let restored = dir.path().join(format!("Restored-{restore_time}"));
+            fs::create_dir_all(&restored).unwrap();
+            fs::rename(extracted_dir.join("Save"), restored.join("Save")).unwrap();
+            restore_attributes(&unit_files, &restored, restore_time);
+
+            for file in files {
+                let metadata = fs::metadata(restored.join("Save").join(file)).unwrap();
+                let actual = metadata.modified().unwrap();
+                let diff = actual
+                    .duration_since(modified)
+                    .unwrap_or_else(|e| e.duration());
+                // Within the 2 second resolution of DOS time
+                assert_eq!(diff <= Duration::from_secs(2), restore_time, "{file}");
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = fs::metadata(restored.join("Save/slot.sav"))
+                    .unwrap()
+                    .permissions()
+                    .mode();
+                assert_eq!(mode & 0o777, 0o755);
+            }
+        }
+    }
+
+    #[test]
+    fn extraction_reports_progress_and_stops_when_cancelled() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        tree(&root, &["slot.sav", "sub/profile.sav"]);
+        let (_, data) = archive_names(&root, SymlinkMode::Follow);
+
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let mut progress = Vec::new();
+        let mut monitor = ArchiveMonitor::new(cancel.clone(), |processed, total, _: &Path| {
+            progress.push((processed, total))
+        });
+        let extracted = dir.path().join("Extracted");
+        let mut archive = ZipArchive::new(Cursor::new(data.clone())).unwrap();
+        let size = unit_sizes(&mut archive).unwrap()[Path::new("Save")];
+        monitor.set_total(size);
+        extract_archive(&mut archive, &extracted, None, &mut monitor).unwrap();
+        monitor.finish();
+        drop(monitor);
+        // The last report always covers the whole unit
+        assert!(size > 0);
+        assert_eq!(progress.last(), Some(&(size, size)));
+
+        cancel.cancel();
+        let mut monitor = ArchiveMonitor::new(cancel, |_, _, _: &Path| {});
+        let extracted = dir.path().join("Cancelled");
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        assert!(matches!(
+            extract_archive(&mut archive, &extracted, None, &mut monitor),
+            Err(BackupFileError::Cancelled)
+        ));
+        assert!(!extracted.join("Save/slot.sav").exists());
+    }
+
+    #[test]
+    fn restored_units_are_swapped_in_whole() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        tree(&root, &["slot.sav", "sub/profile.sav"]);
+        let (_, data) = archive_names(&root, SymlinkMode::Follow);
+        fs::write(root.join("slot.sav"), b"played on").unwrap();
+        fs::write(root.join("new.sav"), b"new").unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        let save_unit = SaveUnit {
+            delete_before_apply: ApplyCleanMode::TrackedOnly,
+            ..unit(&[], &[])
+        };
+        let staged = StagedUnit {
+            unit: &save_unit,
+            unit_path: root.clone(),
+            unit_name: PathBuf::from("Save"),
+            size: unit_sizes(&mut archive).unwrap()[Path::new("Save")],
+            staging: free_sibling(&root, "restore").unwrap(),
+        };
+        let extracted = extract_archive(
+            &mut archive,
+            &staged.staging,
+            Some(Path::new("Save")),
+            &mut ArchiveMonitor::silent(),
+        )
+        .unwrap();
+        // Nothing is touched before the swap
+        assert_eq!(fs::read(root.join("slot.sav")).unwrap(), b"played on");
+
+        staged.apply(&extracted, false, None).unwrap();
+        drop(staged);
+        assert_eq!(fs::read(root.join("slot.sav")).unwrap(), b"slot.sav");
+        assert_eq!(fs::read(root.join("new.sav")).unwrap(), b"new");
+        assert!(root.join("sub/profile.sav").is_file());
+        // Neither the staging folder nor the replaced save is left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn only_tracked_only_replaces_a_folder_in_the_way_of_a_file() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("Save");
+        tree(&root, &["slot.sav", "config"]);
+        let (_, data) = archive_names(&root, SymlinkMode::Follow);
+        fs::write(root.join("slot.sav"), b"played on").unwrap();
+        fs::remove_file(root.join("config")).unwrap();
+        tree(&root, &["config/user.ini"]);
+
+        let apply = |mode| {
+            let mut archive = ZipArchive::new(Cursor::new(data.clone())).unwrap();
+            let save_unit = SaveUnit {
+                delete_before_apply: mode,
+                ..unit(&[], &[])
+            };
+            let staged = StagedUnit {
+                unit: &save_unit,
+                unit_path: root.clone(),
+                unit_name: PathBuf::from("Save"),
+                size: unit_sizes(&mut archive).unwrap()[Path::new("Save")],
+                staging: free_sibling(&root, "restore").unwrap(),
+            };
+            let extracted = extract_archive(
+                &mut archive,
+                &staged.staging,
+                Some(Path::new("Save")),
+                &mut ArchiveMonitor::silent(),
+            )
+            .unwrap();
+            staged.apply(&extracted, false, None)
+        };
+
+        // Keeping existing files cannot keep the folder where the snapshot has a file
+        assert!(matches!(
+            apply(ApplyCleanMode::None),
+            Err(BackupFileError::PathConflict(path)) if path == root.join("config")
+        ));
+        assert_eq!(fs::read(root.join("slot.sav")).unwrap(), b"played on");
+        assert!(root.join("config/user.ini").is_file());
+
+        apply(ApplyCleanMode::TrackedOnly).unwrap();
+        assert_eq!(fs::read(root.join("slot.sav")).unwrap(), b"slot.sav");
+        assert_eq!(fs::read(root.join("config")).unwrap(), b"config");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn paths_longer_than_max_path_round_trip() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let folders = ["a_rather_long_folder_name_for_saves"; 9];
+        // Extended-length paths are not normalized, so join one component at a time
+        let nested: PathBuf = folders.iter().collect();
+        let root = to_long_path(&dir.path().join("Save"));
+        fs::create_dir_all(root.join(&nested)).unwrap();
+        fs::write(root.join(&nested).join("slot.sav"), b"slot").unwrap();
+        assert!(root.join(&nested).as_os_str().len() > 300);
+
+        let (names, data) = archive_names(&root, SymlinkMode::Follow);
+        assert_eq!(names, [format!("Save/{}/slot.sav", folders.join("/"))]);
+
+        let extracted = to_long_path(&dir.path().join("Extracted"));
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        extract_archive(
+            &mut archive,
+            &extracted,
+            None,
+            &mut ArchiveMonitor::silent(),
+        )
+        .unwrap();
+        let path = extracted.join("Save").join(&nested).join("slot.sav");
+        assert_eq!(fs::read(path).unwrap(), b"slot");
+    }
+
+    #[test]
+    fn legacy_boolean_maps_to_clean_mode() {
+        let parse = |json: &str| {
+            serde_json::from_str::<SaveUnit>(json)
+                .unwrap()
+                .delete_before_apply
+        };
+        let unit = |extra: &str| format!(r#"{{"unit_type":"Folder","paths":{{}}{extra}}}"#);
+        assert_eq!(parse(&unit("")), ApplyCleanMode::None);
+        assert_eq!(
+            parse(&unit(r#","delete_before_apply":true"#)),
+            ApplyCleanMode::Full
+        );
+        assert_eq!(
+            parse(&unit(r#","delete_before_apply":false"#)),
+            ApplyCleanMode::None
+        );
+        assert_eq!(
+            parse(&unit(r#","delete_before_apply":"TrackedOnly""#)),
+            ApplyCleanMode::TrackedOnly
+        );
+    }
+
+    #[test]
+    fn damaged_archives_fail_verification() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let zip_path = dir.path().join("2024-01-01_00-00-00.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+        let stored =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.add_directory("Save", stored).unwrap();
+        zip.start_file("Save/slot.sav", stored).unwrap();
+        zip.write_all(b"a save that the disk will damage").unwrap();
+        zip.finish().unwrap();
+
+        verify_archive(&zip_path, 2).unwrap();
+        assert!(verify_archive(&zip_path, 3).is_err());
+
+        // Flip a byte of the stored data, the metadata is still readable
+        let mut bytes = fs::read(&zip_path).unwrap();
+        let offset = bytes.windows(4).position(|w| w == b"disk").unwrap();
+        bytes[offset] ^= 0xff;
+        fs::write(&zip_path, bytes).unwrap();
+        let error = verify_archive(&zip_path, 2).unwrap_err();
+        assert!(format!("{error:#}").contains("Save/slot.sav"));
+    }
+}
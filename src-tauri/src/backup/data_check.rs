@@ -0,0 +1,936 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use log::{info, warn};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Emitter};
+
+use crate::cloud_sync::{OutboxOperation, sync_or_queue};
+use crate::config::{Config, get_config, modify_config};
+use crate::device::{Device, DeviceId};
+use crate::ipc_handler::{IpcNotification, NotificationLevel};
+use crate::preclude::*;
+
+use super::recovery::is_snapshot_date;
+use super::storage::list_zips;
+use super::utils::create_backup_folder;
+use super::{
+    Game, GameSnapshots, check_backup_root, join_backup_dir, normalize_snapshot_records,
+    rebuild_game_snapshots, sanitize_windows_path_component,
+};
+
+/// 记录的快照大小与磁盘上的压缩包相差超过该字节数时才报告
+const SIZE_TOLERANCE: u64 = 1024;
+/// 启动后等待前端加载完成再检查，否则提示会在前端开始监听前发出
+const STARTUP_CHECK_DELAY: Duration = Duration::from_secs(5);
+
+/// 问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum IssueSeverity {
+    /// 会导致备份或恢复出错
+    Error,
+    /// 数据不一致，但不影响备份与恢复
+    Warning,
+    Info,
+}
+
+/// 配置或快照记录中的问题
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DataIssueKind {
+    /// 有 `count` 个游戏使用同一名称，它们共用同一个备份目录
+    DuplicateGameName { count: usize },
+    /// 游戏没有任何存档单元
+    NoSavePaths,
+    /// Backups.json 无法读取
+    UnreadableRecords { reason: String },
+    /// 快照日期为空或无法解析
+    InvalidSnapshotDate { date: String },
+    /// 有 `count` 条快照记录使用同一日期
+    DuplicateSnapshotDate { date: String, count: usize },
+    /// 快照记录对应的压缩包不存在
+    MissingSnapshotFile { date: String },
+    /// 记录的大小与磁盘上的压缩包不一致
+    SnapshotSizeMismatch {
+        date: String,
+        recorded: u64,
+        actual: u64,
+    },
+    /// 序号为 `unit` 的存档单元中有不在设备列表中的设备路径
+    UnknownDevice { unit: usize, device_id: DeviceId },
+    /// 备份目录不可用，可能是外置硬盘未连接
+    BackupRootUnavailable { path: String },
+    /// 配置中的游戏没有备份文件夹
+    MissingBackupFolder,
+    /// 游戏的备份文件夹名不是它的存储名，如旧版本未安全化的 `Who?`，需要改名为 `expected`
+    MisnamedBackupFolder { folder: String, expected: String },
+    /// 备份目录下的文件夹不属于任何游戏，导入时使用 `name` 作为游戏名
+    OrphanedBackupFolder {
+        folder: String,
+        name: String,
+        snapshots: usize,
+    },
+}
+
+/// 一个检查出的问题
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct DataIssue {
+    /// 问题所属的游戏，与具体游戏无关时为 `None`
+    pub game: Option<String>,
+    pub severity: IssueSeverity,
+    pub kind: DataIssueKind,
+    /// 可以安全地自动修复时为修复操作的 ID，传给 `apply_data_fixes` 执行
+    pub fix: Option<String>,
+}
+
+/// 自动修复操作，只包含不会丢失数据的修改
+#[derive(Debug, Clone, PartialEq)]
+enum DataFix {
+    /// 根据备份目录中的压缩包重建 Backups.json，原文件保留为 Backups.json.broken
+    RebuildRecords { game: String },
+    /// 删除日期无效且没有对应压缩包的记录
+    RemoveSnapshotRecords { game: String, date: String },
+    /// 同一日期只保留第一条记录
+    DedupeSnapshotDate { game: String, date: String },
+    /// 用磁盘上压缩包的大小更新记录
+    UpdateSnapshotSize { game: String, date: String },
+    /// 把未知设备加入设备列表，设备名称可之后在设置中修改
+    RegisterDevice { device_id: DeviceId },
+    /// 为游戏创建空的备份文件夹
+    CreateBackupFolder { game: String },
+    /// 把游戏的备份文件夹改名为存储名，并更新快照记录中的路径
+    RenameBackupFolder { game: String, folder: String },
+    /// 将孤立的备份文件夹导入为新游戏，快照记录根据压缩包重建，存档路径需要用户之后设置
+    ImportOrphanedFolder { folder: String, game: String },
+}
+
+impl DataFix {
+    fn id(&self) -> String {
+        match self {
+            DataFix::RebuildRecords { game } => format!("rebuild_records/{game}"),
+            DataFix::RemoveSnapshotRecords { game, date } => {
+                format!("remove_snapshot_records/{game}/{date}")
+            }
+            DataFix::DedupeSnapshotDate { game, date } => {
+                format!("dedupe_snapshot_date/{game}/{date}")
+            }
+            DataFix::UpdateSnapshotSize { game, date } => {
+                format!("update_snapshot_size/{game}/{date}")
+            }
+            DataFix::RegisterDevice { device_id } => format!("register_device/{device_id}"),
+            DataFix::CreateBackupFolder { game } => format!("create_backup_folder/{game}"),
+            DataFix::RenameBackupFolder { game, .. } => format!("rename_backup_folder/{game}"),
+            DataFix::ImportOrphanedFolder { folder, .. } => {
+                format!("import_orphaned_folder/{folder}")
+            }
+        }
+    }
+}
+
+/// 检查规则的结果，修复操作与问题一一对应
+struct Finding {
+    issue: DataIssue,
+    fix: Option<DataFix>,
+}
+
+impl Finding {
+    fn new(game: Option<&str>, severity: IssueSeverity, kind: DataIssueKind) -> Self {
+        Finding {
+            issue: DataIssue {
+                game: game.map(str::to_string),
+                severity,
+                kind,
+                fix: None,
+            },
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: DataFix) -> Self {
+        self.issue.fix = Some(fix.id());
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// 备份目录下的一个文件夹
+#[derive(Debug, Clone)]
+struct BackupFolder {
+    name: String,
+    has_records: bool,
+    /// Backups.json 中记录的游戏名
+    records_name: Option<String>,
+    snapshots: usize,
+}
+
+/// 检查规则的输入，同名游戏的快照记录只读取一次
+struct DataContext<'a> {
+    config: &'a Config,
+    records: Vec<(String, Result<GameSnapshots, String>)>,
+    /// 备份目录下的文件夹，备份目录不可用时为 `None`
+    folders: Option<Vec<BackupFolder>>,
+}
+
+type Checker = fn(&DataContext) -> Vec<Finding>;
+
+/// 配置中的游戏与备份目录下的文件夹是否一一对应，启动时只运行这些规则
+const FOLDER_CHECKERS: &[Checker] = &[check_backup_folders, check_orphaned_folders];
+
+/// 所有检查规则，新规则追加在这里即可
+const CHECKERS: &[Checker] = &[
+    check_backup_folders,
+    check_orphaned_folders,
+    check_unique_game_names,
+    check_save_paths,
+    check_device_ids,
+    check_records_readable,
+    check_snapshot_dates,
+    check_snapshot_files,
+];
+
+/// 比较文件夹名时使用，Windows 上文件夹名不区分大小写
+fn folder_key(name: &str) -> String {
+    if cfg!(windows) {
+        name.to_lowercase()
+    } else {
+        name.to_string()
+    }
+}
+
+fn has_folder(folders: &[BackupFolder], game: &str) -> bool {
+    let key = folder_key(&sanitize_windows_path_component(game));
+    folders.iter().any(|f| folder_key(&f.name) == key)
+}
+
+/// 没有备份文件夹的游戏，其快照可能在以原名或按旧规则命名的文件夹中
+fn misnamed_folder<'a>(folders: &'a [BackupFolder], game: &str) -> Option<&'a BackupFolder> {
+    if has_folder(folders, game) {
+        return None;
+    }
+    folders
+        .iter()
+        .find(|f| f.name == game || f.records_name.as_deref() == Some(game))
+}
+
+fn check_backup_folders(ctx: &DataContext) -> Vec<Finding> {
+    let Some(folders) = &ctx.folders else {
+        return vec![Finding::new(
+            None,
+            IssueSeverity::Error,
+            DataIssueKind::BackupRootUnavailable {
+                path: ctx.config.backup_path.clone(),
+            },
+        )];
+    };
+    let mut seen = HashSet::new();
+    ctx.config
+        .games
+        .iter()
+        .filter(|game| seen.insert(game.name.as_str()) && !has_folder(folders, &game.name))
+        .map(|game| match misnamed_folder(folders, &game.name) {
+            Some(folder) => Finding::new(
+                Some(game.name.as_str()),
+                IssueSeverity::Error,
+                DataIssueKind::MisnamedBackupFolder {
+                    folder: folder.name.clone(),
+                    expected: sanitize_windows_path_component(&game.name),
+                },
+            )
+            .with_fix(DataFix::RenameBackupFolder {
+                game: game.name.clone(),
+                folder: folder.name.clone(),
+            }),
+            None => Finding::new(
+                Some(game.name.as_str()),
+                IssueSeverity::Error,
+                DataIssueKind::MissingBackupFolder,
+            )
+            .with_fix(DataFix::CreateBackupFolder {
+                game: game.name.clone(),
+            }),
+        })
+        .collect()
+}
+
+/// 孤立文件夹导入后的游戏名，优先使用 Backups.json 中记录的原名
+fn orphan_game_name(folder: &BackupFolder) -> String {
+    folder
+        .records_name
+        .clone()
+        .filter(|name| {
+            !name.trim().is_empty()
+                && folder_key(&sanitize_windows_path_component(name)) == folder_key(&folder.name)
+        })
+        .unwrap_or_else(|| folder.name.clone())
+}
+
+fn check_orphaned_folders(ctx: &DataContext) -> Vec<Finding> {
+    let Some(folders) = &ctx.folders else {
+        return Vec::new();
+    };
+    let mut known: HashSet<String> = HashSet::new();
+    for game in &ctx.config.games {
+        known.insert(folder_key(&sanitize_windows_path_component(&game.name)));
+        // 改名后即属于该游戏，由 `check_backup_folders` 报告
+        if let Some(folder) = misnamed_folder(folders, &game.name) {
+            known.insert(folder_key(&folder.name));
+        }
+    }
+    folders
+        .iter()
+        // 没有记录也没有快照的文件夹不是由本程序创建的
+        .filter(|f| !known.contains(&folder_key(&f.name)) && (f.has_records || f.snapshots > 0))
+        .map(|f| {
+            let name = orphan_game_name(f);
+            Finding::new(
+                None,
+                IssueSeverity::Warning,
+                DataIssueKind::OrphanedBackupFolder {
+                    folder: f.name.clone(),
+                    name: name.clone(),
+                    snapshots: f.snapshots,
+                },
+            )
+            .with_fix(DataFix::ImportOrphanedFolder {
+                folder: f.name.clone(),
+                game: name,
+            })
+        })
+        .collect()
+}
+
+fn check_unique_game_names(ctx: &DataContext) -> Vec<Finding> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for game in &ctx.config.games {
+        match counts.iter_mut().find(|(name, _)| *name == game.name) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((game.name.as_str(), 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(name, count)| {
+            Finding::new(
+                Some(name),
+                IssueSeverity::Error,
+                DataIssueKind::DuplicateGameName { count },
+            )
+        })
+        .collect()
+}
+
+fn check_save_paths(ctx: &DataContext) -> Vec<Finding> {
+    ctx.config
+        .games
+        .iter()
+        .filter(|game| game.save_paths.is_empty())
+        .map(|game| {
+            Finding::new(
+                Some(game.name.as_str()),
+                IssueSeverity::Error,
+                DataIssueKind::NoSavePaths,
+            )
+        })
+        .collect()
+}
+
+fn check_device_ids(ctx: &DataContext) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for game in &ctx.config.games {
+        for (unit, save_unit) in game.save_paths.iter().enumerate() {
+            let mut unknown: Vec<&DeviceId> = save_unit
+                .paths
+                .keys()
+                .filter(|id| !ctx.config.devices.contains_key(*id))
+                .collect();
+            unknown.sort();
+            findings.extend(unknown.into_iter().map(|device_id| {
+                Finding::new(
+                    Some(game.name.as_str()),
+                    IssueSeverity::Warning,
+                    DataIssueKind::UnknownDevice {
+                        unit,
+                        device_id: device_id.clone(),
+                    },
+                )
+                .with_fix(DataFix::RegisterDevice {
+                    device_id: device_id.clone(),
+                })
+            }));
+        }
+    }
+    findings
+}
+
+fn check_records_readable(ctx: &DataContext) -> Vec<Finding> {
+    ctx.records
+        .iter()
+        .filter_map(|(name, records)| {
+            let reason = records.as_ref().err()?;
+            Some(
+                Finding::new(
+                    Some(name.as_str()),
+                    IssueSeverity::Error,
+                    DataIssueKind::UnreadableRecords {
+                        reason: reason.clone(),
+                    },
+                )
+                .with_fix(DataFix::RebuildRecords { game: name.clone() }),
+            )
+        })
+        .collect()
+}
+
+fn check_snapshot_dates(ctx: &DataContext) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (name, info) in readable_records(ctx) {
+        let dir = join_backup_dir(ctx.config, name);
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for snapshot in &info.backups {
+            match counts.iter_mut().find(|(date, _)| *date == snapshot.date) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((snapshot.date.as_str(), 1)),
+            }
+        }
+        for (date, count) in counts {
+            if !is_snapshot_date(date) {
+                let mut finding = Finding::new(
+                    Some(name),
+                    IssueSeverity::Error,
+                    DataIssueKind::InvalidSnapshotDate {
+                        date: date.to_string(),
+                    },
+                );
+                // 压缩包还在时记录可能仍有用处，交给用户决定
+                if date.is_empty() || !dir.join(format!("{date}.zip")).exists() {
+                    finding = finding.with_fix(DataFix::RemoveSnapshotRecords {
+                        game: name.to_string(),
+                        date: date.to_string(),
+                    });
+                }
+                findings.push(finding);
+            } else if count > 1 {
+                findings.push(
+                    Finding::new(
+                        Some(name),
+                        IssueSeverity::Warning,
+                        DataIssueKind::DuplicateSnapshotDate {
+                            date: date.to_string(),
+                            count,
+                        },
+                    )
+                    .with_fix(DataFix::DedupeSnapshotDate {
+                        game: name.to_string(),
+                        date: date.to_string(),
+                    }),
+                );
+            }
+        }
+    }
+    findings
+}
+
+fn check_snapshot_files(ctx: &DataContext) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (name, info) in readable_records(ctx) {
+        let dir = join_backup_dir(ctx.config, name);
+        let mut seen = HashSet::new();
+        for snapshot in &info.backups {
+            // 无效与重复的日期由 `check_snapshot_dates` 报告
+            if !is_snapshot_date(&snapshot.date) || !seen.insert(&snapshot.date) {
+                continue;
+            }
+            let date = snapshot.date.clone();
+            let Ok(metadata) = fs::metadata(dir.join(format!("{date}.zip"))) else {
+                findings.push(Finding::new(
+                    Some(name),
+                    IssueSeverity::Warning,
+                    DataIssueKind::MissingSnapshotFile { date },
+                ));
+                continue;
+            };
+            if metadata.len().abs_diff(snapshot.size) > SIZE_TOLERANCE {
+                findings.push(
+                    Finding::new(
+                        Some(name),
+                        IssueSeverity::Info,
+                        DataIssueKind::SnapshotSizeMismatch {
+                            date: date.clone(),
+                            recorded: snapshot.size,
+                            actual: metadata.len(),
+                        },
+                    )
+                    .with_fix(DataFix::UpdateSnapshotSize {
+                        game: name.to_string(),
+                        date,
+                    }),
+                );
+            }
+        }
+    }
+    findings
+}
+
+fn readable_records<'a>(
+    ctx: &'a DataContext,
+) -> impl Iterator<Item = (&'a str, &'a GameSnapshots)> {
+    ctx.records
+        .iter()
+        .filter_map(|(name, records)| Some((name.as_str(), records.as_ref().ok()?)))
+}
+
+fn run_checkers(ctx: &DataContext) -> Vec<Finding> {
+    CHECKERS.iter().flat_map(|checker| checker(ctx)).collect()
+}
+
+/// 列出备份目录下的文件夹，跳过 `.deleted` 等以 `.` 开头的文件夹
+fn list_backup_folders(root: &Path) -> Vec<BackupFolder> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut folders: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .map(|name| {
+            let dir = root.join(&name);
+            let snapshots = list_zips(&dir)
+                .unwrap_or_default()
+                .iter()
+                .filter(|(file, _)| file.strip_suffix(".zip").is_some_and(is_snapshot_date))
+                .count();
+            let records_name = fs::read(dir.join("Backups.json"))
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<GameSnapshots>(&bytes).ok())
+                .map(|info| info.name);
+            BackupFolder {
+                has_records: dir.join("Backups.json").is_file(),
+                records_name,
+                snapshots,
+                name,
+            }
+        })
+        .collect();
+    folders.sort_by(|a, b| a.name.cmp(&b.name));
+    folders
+}
+
+fn load_folders(config: &Config) -> Option<Vec<BackupFolder>> {
+    check_backup_root(config).ok()?;
+    Some(list_backup_folders(&config.backup_root()))
+}
+
+fn load_context(config: &Config) -> DataContext<'_> {
+    let folders = load_folders(config);
+    let mut seen = HashSet::new();
+    // 没有备份文件夹的游戏由 `check_backup_folders` 报告，不再读取记录
+    let records = config
+        .games
+        .iter()
+        .filter(|game| seen.insert(game.name.as_str()))
+        .filter(|game| folders.as_ref().is_some_and(|f| has_folder(f, &game.name)))
+        .map(|game| {
+            let records = game.get_game_snapshots_info().map_err(|e| e.to_string());
+            (game.name.clone(), records)
+        })
+        .collect();
+    DataContext {
+        config,
+        records,
+        folders,
+    }
+}
+
+/// 检查配置与所有游戏的快照记录
+pub fn validate_data() -> Result<Vec<DataIssue>, BackupError> {
+    let config = get_config()?;
+    let ctx = load_context(&config);
+    Ok(run_checkers(&ctx).into_iter().map(|f| f.issue).collect())
+}
+
+/// 只检查配置中的游戏与备份目录下的文件夹是否一致，修复同样通过 `apply_data_fixes` 执行
+pub fn get_consistency_report() -> Result<Vec<DataIssue>, BackupError> {
+    let config = get_config()?;
+    let ctx = DataContext {
+        config: &config,
+        records: Vec::new(),
+        folders: load_folders(&config),
+    };
+    Ok(FOLDER_CHECKERS
+        .iter()
+        .flat_map(|checker| checker(&ctx))
+        .map(|f| f.issue)
+        .collect())
+}
+
+/// 启动时检查一次，有问题时通知前端
+pub async fn notify_consistency_issues(app: AppHandle) {
+    tokio::time::sleep(STARTUP_CHECK_DELAY).await;
+    let issues = match get_consistency_report() {
+        Ok(issues) if !issues.is_empty() => issues,
+        Ok(_) => return,
+        Err(e) => {
+            warn!(target:"rgsm::backup", "Failed to check backup folders: {:?}", e);
+            return;
+        }
+    };
+    warn!(target:"rgsm::backup", "Backup folders are inconsistent with the config: {:?}", issues);
+    let _ = app.emit(
+        "Notification",
+        IpcNotification {
+            level: NotificationLevel::warning,
+            title: t!("backend.notification.warning").to_string(),
+            msg: t!("backend.backup.consistency_issues", count = issues.len()).to_string(),
+        },
+    );
+}
+
+/// 修改一个游戏的快照记录并保存
+fn update_records(
+    config: &Config,
+    name: &str,
+    update: impl FnOnce(&mut GameSnapshots) -> Result<(), BackupError>,
+) -> Result<(), BackupError> {
+    let game = config
+        .games
+        .iter()
+        .find(|g| g.name == name)
+        .ok_or_else(|| BackupError::GameNotExist(name.to_string()))?;
+    game.update_game_snapshots_info(update)
+}
+
+async fn apply_fix(config: &Config, fix: &DataFix) -> Result<(), BackupError> {
+    match fix {
+        DataFix::RebuildRecords { game } => {
+            let game = config
+                .games
+                .iter()
+                .find(|g| &g.name == game)
+                .ok_or_else(|| BackupError::GameNotExist(game.to_string()))?;
+            rebuild_game_snapshots(game)?;
+        }
+        DataFix::RemoveSnapshotRecords { game, date } => {
+            update_records(config, game, |info| {
+                info.backups.retain(|s| &s.date != date);
+                Ok(())
+            })?;
+        }
+        DataFix::DedupeSnapshotDate { game, date } => {
+            update_records(config, game, |info| {
+                let mut kept = false;
+                info.backups.retain(|s| {
+                    if &s.date != date {
+                        return true;
+                    }
+                    !std::mem::replace(&mut kept, true)
+                });
+                Ok(())
+            })?;
+        }
+        DataFix::UpdateSnapshotSize { game, date } => {
+            let zip = join_backup_dir(config, game).join(format!("{date}.zip"));
+            let size = fs::metadata(zip)?.len();
+            update_records(config, game, |info| {
+                for snapshot in info.backups.iter_mut().filter(|s| &s.date == date) {
+                    snapshot.size = size;
+                }
+                Ok(())
+            })?;
+        }
+        DataFix::CreateBackupFolder { game } => create_backup_folder(game).await?,
+        DataFix::RenameBackupFolder { game, folder } => {
+            let root = config.backup_root();
+            let target = join_backup_dir(config, game);
+            if target.exists() {
+                return Err(BackupError::GameNameConflict(game.clone()));
+            }
+            fs::rename(root.join(folder), &target)?;
+            normalize_snapshot_records(&root, game)?;
+        }
+        DataFix::ImportOrphanedFolder { game, .. } => {
+            if config.games.iter().any(|g| &g.name == game) {
+                return Err(BackupError::GameNameConflict(game.clone()));
+            }
+            let game = Game {
+                name: game.clone(),
+                save_paths: Vec::new(),
+                game_paths: HashMap::new(),
+            };
+            rebuild_game_snapshots(&game)?;
+            modify_config(|config| {
+                if config.games.iter().any(|g| g.name == game.name) {
+                    return Err(BackupError::GameNameConflict(game.name.clone()));
+                }
+                config.games.push(game);
+                Ok(())
+            })
+            .await?;
+        }
+        DataFix::RegisterDevice { device_id } => {
+            let name = t!("backend.backup.unknown_device_name", id = device_id).to_string();
+            modify_config(|config| {
+                config
+                    .devices
+                    .entry(device_id.clone())
+                    .or_insert_with(|| Device {
+                        id: device_id.clone(),
+                        name,
+                    });
+                Ok::<_, BackupError>(())
+            })
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// 执行 `validate_data` 给出的修复操作，返回实际执行了的 ID
+///
+/// 会重新检查一遍，只执行当前仍然存在的问题对应的修复
+pub async fn apply_data_fixes(ids: &[String]) -> Result<Vec<String>, BackupError> {
+    let config = get_config()?;
+    let fixes: Vec<DataFix> = run_checkers(&load_context(&config))
+        .into_iter()
+        .filter_map(|f| f.fix)
+        .filter(|fix| ids.contains(&fix.id()))
+        .collect();
+    let mut applied = Vec::new();
+    let mut changed_games = BTreeSet::new();
+    for fix in &fixes {
+        apply_fix(&config, fix).await?;
+        info!(target:"rgsm::backup", "Applied data fix {}", fix.id());
+        match fix {
+            DataFix::RebuildRecords { game }
+            | DataFix::RemoveSnapshotRecords { game, .. }
+            | DataFix::DedupeSnapshotDate { game, .. }
+            | DataFix::UpdateSnapshotSize { game, .. }
+            | DataFix::RenameBackupFolder { game, .. }
+            | DataFix::ImportOrphanedFolder { game, .. } => {
+                changed_games.insert(game.clone());
+            }
+            // 创建文件夹时已经上传了记录
+            DataFix::CreateBackupFolder { .. } | DataFix::RegisterDevice { .. } => {}
+        }
+        applied.push(fix.id());
+    }
+    if config.settings.cloud_settings.always_sync && !changed_games.is_empty() {
+        let operations = changed_games
+            .into_iter()
+            .map(|game| OutboxOperation::UploadRecords { game })
+            .collect();
+        let _ = sync_or_queue(operations).await;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{Game, Snapshot};
+    use std::collections::HashMap;
+
+    fn snapshot(date: &str, size: u64) -> Snapshot {
+        Snapshot {
+            date: date.to_string(),
+            describe: String::new(),
+            path: format!("Game/{date}.zip"),
+            size,
+        }
+    }
+
+    fn game(name: &str) -> Game {
+        Game {
+            name: name.to_string(),
+            save_paths: Vec::new(),
+            game_paths: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn reports_duplicate_names_and_empty_save_paths() {
+        let config = Config {
+            games: vec![game("Game"), game("Game")],
+            ..Config::default()
+        };
+        let ctx = DataContext {
+            config: &config,
+            records: Vec::new(),
+            folders: Some(vec![BackupFolder {
+                name: "Game".to_string(),
+                has_records: true,
+                records_name: None,
+                snapshots: 0,
+            }]),
+        };
+        let kinds: Vec<_> = run_checkers(&ctx)
+            .into_iter()
+            .map(|f| f.issue.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DataIssueKind::DuplicateGameName { count: 2 },
+                DataIssueKind::NoSavePaths,
+                DataIssueKind::NoSavePaths,
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_records_are_checked_against_the_disk() {
+        let root = temp_dir::TempDir::new().unwrap();
+        let dir = root.path().join("Game");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("2024-01-01_00-00-00.zip"), [0; 4096]).unwrap();
+        fs::write(dir.join("2024-01-02_00-00-00.zip"), [0; 10]).unwrap();
+        let config = Config {
+            backup_path: root.path().to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        let info = GameSnapshots::new(
+            "Game",
+            vec![
+                snapshot("", 0),
+                snapshot("2024-01-01_00-00-00", 0),
+                snapshot("2024-01-02_00-00-00", 10),
+                snapshot("2024-01-02_00-00-00", 10),
+                snapshot("2024-01-03_00-00-00", 10),
+            ],
+        );
+        let ctx = DataContext {
+            config: &config,
+            records: vec![
+                ("Game".to_string(), Ok(info)),
+                ("Broken".to_string(), Err("eof".to_string())),
+            ],
+            folders: Some(Vec::new()),
+        };
+        let fixes: Vec<_> = run_checkers(&ctx)
+            .into_iter()
+            .map(|f| (f.issue.kind, f.issue.fix))
+            .collect();
+        assert_eq!(
+            fixes,
+            vec![
+                (
+                    DataIssueKind::UnreadableRecords {
+                        reason: "eof".to_string()
+                    },
+                    Some("rebuild_records/Broken".to_string())
+                ),
+                (
+                    DataIssueKind::InvalidSnapshotDate {
+                        date: String::new()
+                    },
+                    Some("remove_snapshot_records/Game/".to_string())
+                ),
+                (
+                    DataIssueKind::DuplicateSnapshotDate {
+                        date: "2024-01-02_00-00-00".to_string(),
+                        count: 2
+                    },
+                    Some("dedupe_snapshot_date/Game/2024-01-02_00-00-00".to_string())
+                ),
+                (
+                    DataIssueKind::SnapshotSizeMismatch {
+                        date: "2024-01-01_00-00-00".to_string(),
+                        recorded: 0,
+                        actual: 4096
+                    },
+                    Some("update_snapshot_size/Game/2024-01-01_00-00-00".to_string())
+                ),
+                (
+                    DataIssueKind::MissingSnapshotFile {
+                        date: "2024-01-03_00-00-00".to_string()
+                    },
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn games_and_backup_folders_are_matched() {
+        let root = temp_dir::TempDir::new().unwrap();
+        for folder in ["Kept", "Old_ Game", ".deleted", "Notes", "Who"] {
+            fs::create_dir_all(root.path().join(folder)).unwrap();
+        }
+        fs::write(root.path().join("Kept/Backups.json"), "{}").unwrap();
+        let info = GameSnapshots::new("Old: Game", Vec::new());
+        fs::write(
+            root.path().join("Old_ Game/Backups.json"),
+            serde_json::to_string(&info).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("Old_ Game/2024-01-01_00-00-00.zip"),
+            [0; 4],
+        )
+        .unwrap();
+        // 旧版本把 `Who?` 的快照放在了 `Who` 中，而存储名是 `Who_`
+        fs::write(
+            root.path().join("Who/Backups.json"),
+            serde_json::to_string(&GameSnapshots::new("Who?", Vec::new())).unwrap(),
+        )
+        .unwrap();
+        let config = Config {
+            backup_path: root.path().to_string_lossy().to_string(),
+            games: vec![game("Kept"), game("New"), game("Who?")],
+            ..Config::default()
+        };
+
+        let folders = list_backup_folders(root.path());
+        let names: Vec<_> = folders.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["Kept", "Notes", "Old_ Game", "Who"]);
+        let ctx = DataContext {
+            config: &config,
+            records: Vec::new(),
+            folders: Some(folders),
+        };
+        let fixes: Vec<_> = FOLDER_CHECKERS
+            .iter()
+            .flat_map(|checker| checker(&ctx))
+            .map(|f| (f.issue.kind, f.issue.fix))
+            .collect();
+        assert_eq!(
+            fixes,
+            vec![
+                (
+                    DataIssueKind::MissingBackupFolder,
+                    Some("create_backup_folder/New".to_string())
+                ),
+                (
+                    DataIssueKind::MisnamedBackupFolder {
+                        folder: "Who".to_string(),
+                        expected: "Who_".to_string()
+                    },
+                    Some("rename_backup_folder/Who?".to_string())
+                ),
+                (
+                    DataIssueKind::OrphanedBackupFolder {
+                        folder: "Old_ Game".to_string(),
+                        name: "Old: Game".to_string(),
+                        snapshots: 1
+                    },
+                    Some("import_orphaned_folder/Old_ Game".to_string())
+                ),
+            ]
+        );
+
+        let unavailable = DataContext {
+            folders: None,
+            ..ctx
+        };
+        assert_eq!(check_orphaned_folders(&unavailable).len(), 0);
+        assert!(matches!(
+            check_backup_folders(&unavailable)[0].issue.kind,
+            DataIssueKind::BackupRootUnavailable { .. }
+        ));
+    }
+}
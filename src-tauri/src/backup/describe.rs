@@ -0,0 +1,122 @@
+//! Descriptions of snapshots that are created without one from the user
+//!
+//! `Settings::auto_describe_template` decides how they look, with these placeholders:
+//!
+//! - `{trigger}`: what created the snapshot, e.g. "Quick Backup (Hotkey)"
+//! - `{game}`: the game name
+//! - `{local_time}`: the local time the snapshot was created
+//! - `{device}`: the name of this device
+//!
+//! Unknown placeholders are kept as they are.
+
+use crate::config::Config;
+use crate::device::{get_current_device_id, get_system_hostname};
+
+/// Values the placeholders expand to
+struct DescribeContext<'a> {
+    trigger: &'a str,
+    game: &'a str,
+    local_time: &'a str,
+    device: &'a str,
+}
+
+impl DescribeContext<'_> {
+    fn get(&self, placeholder: &str) -> Option<&str> {
+        match placeholder {
+            "trigger" => Some(self.trigger),
+            "game" => Some(self.game),
+            "local_time" => Some(self.local_time),
+            "device" => Some(self.device),
+            _ => None,
+        }
+    }
+}
+
+fn expand_template(template: &str, context: &DescribeContext) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after
+            .find('}')
+            .and_then(|end| Some((context.get(&after[..end])?, end)));
+        match value {
+            Some((value, end)) => {
+                expanded.push_str(value);
+                rest = &after[end + 1..];
+            }
+            // Not a placeholder, keep the brace and look for one after it
+            None => {
+                expanded.push('{');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Describe a snapshot of `game` created by `trigger` using the configured template
+///
+/// Falls back to `trigger` when the template expands to nothing.
+pub fn auto_describe(config: &Config, trigger: &str, game: &str) -> String {
+    let device = config
+        .devices
+        .get(get_current_device_id())
+        .map(|device| device.name.clone())
+        .unwrap_or_else(get_system_hostname);
+    let local_time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let context = DescribeContext {
+        trigger,
+        game,
+        local_time: &local_time,
+        device: &device,
+    };
+    let describe = expand_template(&config.settings.auto_describe_template, &context);
+    if describe.trim().is_empty() {
+        trigger.to_string()
+    } else {
+        describe
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTEXT: DescribeContext = DescribeContext {
+        trigger: "Quick Backup (Hotkey)",
+        game: "Celeste",
+        local_time: "2024-01-01 12:00:00",
+        device: "Desktop",
+    };
+
+    #[test]
+    fn placeholders_are_expanded() {
+        assert_eq!(
+            expand_template("{trigger}", &CONTEXT),
+            "Quick Backup (Hotkey)"
+        );
+        assert_eq!(
+            expand_template("{game} on {device} – {local_time}", &CONTEXT),
+            "Celeste on Desktop – 2024-01-01 12:00:00"
+        );
+        assert_eq!(
+            expand_template("no placeholders", &CONTEXT),
+            "no placeholders"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        assert_eq!(
+            expand_template("{trigger} {unknown} {}", &CONTEXT),
+            "Quick Backup (Hotkey) {unknown} {}"
+        );
+        assert_eq!(
+            expand_template("{{game}} {trigger", &CONTEXT),
+            "{Celeste} {trigger"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::backup::{SaveUnit, SaveUnitType};
+use crate::config::{Config, modify_config};
+use crate::device::{DeviceId, get_current_device_id};
+use crate::preclude::*;
+
+/// The path of a save unit on one device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct DevicePath {
+    pub device_id: DeviceId,
+    /// `None` when the device is not in `config.devices` anymore
+    pub device_name: Option<String>,
+    /// `None` when the unit has no path for this device yet
+    pub path: Option<String>,
+    pub is_current: bool,
+}
+
+/// Paths of one save unit on every known device, `index` is its position in `save_paths`
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SaveUnitDevicePaths {
+    pub index: usize,
+    pub unit_type: SaveUnitType,
+    pub paths: Vec<DevicePath>,
+}
+
+/// List the paths of every save unit of a game, one entry per known device
+///
+/// Devices listed in `config.devices` are included even without a path, so the
+/// caller can see which ones still need one. The current device comes first
+pub fn list_device_paths(
+    config: &Config,
+    game_name: &str,
+) -> Result<Vec<SaveUnitDevicePaths>, BackupError> {
+    let game = config
+        .games
+        .iter()
+        .find(|g| g.name == game_name)
+        .ok_or_else(|| BackupError::GameNotExist(game_name.to_string()))?;
+    let current = get_current_device_id();
+    Ok(game
+        .save_paths
+        .iter()
+        .enumerate()
+        .map(|(index, unit)| {
+            let mut ids: Vec<&DeviceId> = config.devices.keys().collect();
+            ids.extend(
+                unit.paths
+                    .keys()
+                    .filter(|id| !config.devices.contains_key(*id)),
+            );
+            let mut paths: Vec<DevicePath> = ids
+                .into_iter()
+                .map(|id| DevicePath {
+                    device_id: id.clone(),
+                    device_name: config.devices.get(id).map(|d| d.name.clone()),
+                    path: unit.paths.get(id).cloned(),
+                    is_current: id == current,
+                })
+                .collect();
+            paths.sort_by(|a, b| {
+                b.is_current
+                    .cmp(&a.is_current)
+                    .then_with(|| a.device_name.cmp(&b.device_name))
+                    .then_with(|| a.device_id.cmp(&b.device_id))
+            });
+            SaveUnitDevicePaths {
+                index,
+                unit_type: unit.unit_type.clone(),
+                paths,
+            }
+        })
+        .collect())
+}
+
+/// Set the path of a save unit for any device, including ones other than the current
+pub async fn set_unit_path_for_device(
+    game_name: &str,
+    unit_index: usize,
+    device_id: &str,
+    path: &str,
+) -> Result<(), BackupError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(BackupError::InvalidSavePath(path.to_string()));
+    }
+    modify_config(|config| {
+        if !config.devices.contains_key(device_id) && device_id != get_current_device_id().as_str()
+        {
+            return Err(BackupError::DeviceNotExist(device_id.to_string()));
+        }
+        let unit = find_unit(config, game_name, unit_index)?;
+        unit.paths.insert(device_id.to_string(), path.to_string());
+        refresh_quick_action_game(config, game_name);
+        Ok(())
+    })
+    .await
+}
+
+/// Remove the path of a save unit for a device, removing a missing path is not an error
+pub async fn remove_unit_path_for_device(
+    game_name: &str,
+    unit_index: usize,
+    device_id: &str,
+) -> Result<(), BackupError> {
+    modify_config(|config| {
+        let unit = find_unit(config, game_name, unit_index)?;
+        unit.paths.remove(device_id);
+        refresh_quick_action_game(config, game_name);
+        Ok(())
+    })
+    .await
+}
+
+fn find_unit<'a>(
+    config: &'a mut Config,
+    game_name: &str,
+    unit_index: usize,
+) -> Result<&'a mut SaveUnit, BackupError> {
+    config
+        .games
+        .iter_mut()
+        .find(|g| g.name == game_name)
+        .ok_or_else(|| BackupError::GameNotExist(game_name.to_string()))?
+        .save_paths
+        .get_mut(unit_index)
+        .ok_or_else(|| BackupError::SaveUnitNotExist {
+            name: game_name.to_string(),
+            index: unit_index,
+        })
+}
+
+/// The quick action keeps its own copy of the game, refresh it after the paths changed
+fn refresh_quick_action_game(config: &mut Config, game_name: &str) {
+    let quick_action = &mut config.quick_action;
+    if quick_action
+        .quick_action_game
+        .as_ref()
+        .is_some_and(|g| g.name == game_name)
+    {
+        quick_action.quick_action_game = config.games.iter().find(|g| g.name == game_name).cloned();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::Game;
+    use crate::device::Device;
+    use std::collections::HashMap;
+
+    #[test]
+    fn lists_known_devices_with_current_first() {
+        let current = get_current_device_id().clone();
+        let mut config = Config {
+            devices: HashMap::from([
+                (
+                    current.clone(),
+                    Device {
+                        id: current.clone(),
+                        name: "Laptop".into(),
+                    },
+                ),
+                (
+                    "desktop".to_string(),
+                    Device {
+                        id: "desktop".into(),
+                        name: "Desktop".into(),
+                    },
+                ),
+            ]),
+            ..Config::default()
+        };
+        config.games.push(Game {
+            name: "Game".into(),
+            save_paths: vec![SaveUnit {
+                paths: HashMap::from([
+                    ("desktop".to_string(), "D:/save".to_string()),
+                    ("removed".to_string(), "E:/save".to_string()),
+                ]),
+                ..Default::default()
+            }],
+            game_paths: HashMap::new(),
+        });
+
+        let units = list_device_paths(&config, "Game").unwrap();
+        let paths: Vec<_> = units[0]
+            .paths
+            .iter()
+            .map(|p| (p.device_id.as_str(), p.path.as_deref()))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (current.as_str(), None),
+                ("removed", Some("E:/save")),
+                ("desktop", Some("D:/save")),
+            ]
+        );
+        assert!(matches!(
+            list_device_paths(&config, "Missing"),
+            Err(BackupError::GameNotExist(_))
+        ));
+    }
+}
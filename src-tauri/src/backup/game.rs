@@ -3,13 +3,24 @@ use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 // 移除未使用导入，保持代码简洁
-use std::{collections::HashMap, fs};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex, PoisonError},
+};
 use tauri::{AppHandle, Emitter};
 
-use crate::backup::{GameSnapshots, SaveUnit, Snapshot, compress_to_file, decompress_from_file};
-use crate::cloud_sync::{upload_config, upload_game_snapshots};
-use crate::config::{get_config, set_config};
-use crate::device::DeviceId;
+use crate::backup::{
+    ArchiveMonitor, GameSnapshots, GameSoftDeleted, SaveUnit, Snapshot, auto_describe,
+    compress_to_file, decompress_from_file, snapshot_record_path, verify_archive,
+};
+use crate::cloud_sync::{
+    CloudSyncState, OutboxOperation, SyncDirection, emit_sync_state, schedule_snapshot_upload,
+    sync_or_queue,
+};
+use crate::config::{Config, get_config};
+use crate::device::{DeviceId, get_current_device_id};
 use crate::ipc_handler::{IpcNotification, NotificationLevel};
 use crate::preclude::*;
 
@@ -24,80 +35,323 @@ pub struct Game {
     pub game_paths: HashMap<DeviceId, String>,
 }
 
+/// 每个备份目录一把锁，读取-修改-写回 Backups.json 期间持有
+static RECORDS_LOCKS: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+/// 持有 `dir` 的记录锁执行 `f`
+///
+/// 定时备份与删除快照等操作可能同时修改同一个游戏的 Backups.json，
+/// 不加锁时后写入的一方会覆盖另一方的修改。`f` 中不能等待异步任务
+pub(super) fn with_records_lock<T>(dir: &Path, f: impl FnOnce() -> T) -> T {
+    let lock = Arc::clone(
+        RECORDS_LOCKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(dir.to_path_buf())
+            .or_default(),
+    );
+    let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+    f()
+}
+
+/// 按路径顺序持有两个目录的记录锁执行 `f`，避免方向相反的两个操作互相等待
+pub(super) fn with_records_locks<T>(a: &Path, b: &Path, f: impl FnOnce() -> T) -> T {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    if first == second {
+        return with_records_lock(first, f);
+    }
+    with_records_lock(first, || with_records_lock(second, f))
+}
+
+/// 读取 `dir` 中的快照记录
+pub(super) fn read_records(name: &str, dir: &Path) -> Result<GameSnapshots, BackupError> {
+    let (info, _) = read_json_or_backup::<_, BackupError>(&dir.join("Backups.json"))
+        .map_err(|e| super::recovery::records_error(name, dir, e))?;
+    Ok(info)
+}
+
+/// 写入 `dir` 中的快照记录，调用方需持有记录锁
+pub(super) fn write_records(dir: &Path, info: &GameSnapshots) -> Result<(), BackupError> {
+    // 处理文件夹不存在的情况，一般发生在初次下载云存档时
+    fs::create_dir_all(dir)?;
+    write_atomic(
+        &dir.join("Backups.json"),
+        serde_json::to_string_pretty(info)?.as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// 在记录锁内读取、修改并写回 `dir` 中的快照记录，`update` 出错时不写回
+pub(super) fn update_records<T>(
+    name: &str,
+    dir: &Path,
+    update: impl FnOnce(&mut GameSnapshots) -> Result<T, BackupError>,
+) -> Result<T, BackupError> {
+    with_records_lock(dir, || {
+        let mut info = read_records(name, dir)?;
+        let result = update(&mut info)?;
+        write_records(dir, &info)?;
+        Ok(result)
+    })
+}
+
+/// 删除快照的压缩包与记录，两者在记录锁内一起修改
+fn delete_snapshot_in(
+    name: &str,
+    dir: &Path,
+    date: &str,
+    use_trash: bool,
+) -> Result<(), BackupError> {
+    update_records(name, dir, |info| {
+        super::utils::remove_backup_path(&dir.join(format!("{date}.zip")), use_trash)?;
+        info.backups.retain(|x| x.date != date);
+        Ok(())
+    })
+}
+
+/// Pick a snapshot date that is not taken yet and create its empty zip file
+///
+/// Backups made within the same second get a zero padded `_001`, `_002`, ... suffix,
+/// so the dates keep sorting in creation order as strings. The zip is created with `create_new`, so two
+/// backups racing for the same name end up with different ones.
+fn reserve_snapshot_date(
+    backup_path: &Path,
+    now: &str,
+    existing: &[Snapshot],
+) -> Result<(String, PathBuf), BackupError> {
+    let mut n = 0;
+    loop {
+        let date = match n {
+            0 => now.to_string(),
+            n => format!("{now}_{n:03}"),
+        };
+        n += 1;
+        if existing.iter().any(|s| s.date == date) {
+            continue;
+        }
+        let zip_path = backup_path.join([&date, ".zip"].concat());
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&zip_path)
+        {
+            Ok(_) => return Ok((date, zip_path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
 impl Game {
+    /// 游戏的备份文件夹：`config.backup_path` 下以存储名命名的文件夹
+    ///
+    /// 需要备份文件夹的地方都应通过它得到，不要再用游戏名自行拼接
+    pub fn backup_dir(&self, config: &Config) -> PathBuf {
+        super::utils::join_backup_dir(config, &self.name)
+    }
+    /// 已存在的备份文件夹，还没有创建过备份时返回 `BackupError::BackupFolderMissing`
+    pub fn existing_backup_dir(&self, config: &Config) -> Result<PathBuf, BackupError> {
+        let dir = self.backup_dir(config);
+        if !dir.is_dir() {
+            return Err(BackupError::BackupFolderMissing(dir));
+        }
+        Ok(dir)
+    }
+    /// 在文件管理器中打开备份文件夹
+    pub fn open_backup_folder(&self) -> Result<(), BackupError> {
+        let dir = self.existing_backup_dir(&get_config()?)?;
+        open::that(&dir)?;
+        Ok(())
+    }
     pub fn get_game_snapshots_info(&self) -> Result<GameSnapshots, BackupError> {
         let config = get_config()?;
-        let backup_path = super::utils::join_backup_dir(&config, &self.name)
-            .join("Backups.json");
-        let backup_info = serde_json::from_slice(&fs::read(backup_path)?)?;
-        Ok(backup_info)
+        read_records(&self.name, &self.backup_dir(&config))
     }
     pub fn set_game_snapshots_info(&self, new_info: &GameSnapshots) -> Result<(), BackupError> {
         let config = get_config()?;
-        let saves_path = super::utils::join_backup_dir(&config, &self.name)
-            .join("Backups.json");
-        // 处理文件夹不存在的情况，一般发生在初次下载云存档时
-        let prefix_root = saves_path.parent().ok_or(BackupError::NonePathError)?;
-        if !prefix_root.exists() {
-            fs::create_dir_all(prefix_root)?;
-        }
-        fs::write(saves_path, serde_json::to_string_pretty(&new_info)?)?;
-        Ok(())
+        let backup_dir = self.backup_dir(&config);
+        with_records_lock(&backup_dir, || write_records(&backup_dir, new_info))
+    }
+    /// 在记录锁内读取、修改并写回快照记录，同一游戏的并发修改会依次执行
+    pub fn update_game_snapshots_info<T>(
+        &self,
+        update: impl FnOnce(&mut GameSnapshots) -> Result<T, BackupError>,
+    ) -> Result<T, BackupError> {
+        let config = get_config()?;
+        let backup_dir = self.backup_dir(&config);
+        update_records(&self.name, &backup_dir, update)
     }
-    pub async fn create_snapshot(&self, describe: &str) -> Result<(), BackupError> {
+    /// 创建快照，返回写入记录的快照
+    pub async fn create_snapshot(&self, describe: &str) -> Result<Snapshot, BackupError> {
+        self.create_snapshot_with_monitor(describe, &mut ArchiveMonitor::silent())
+            .await
+    }
+    /// 与 `create_snapshot` 相同，通过 `monitor` 报告进度，取消时删除未完成的压缩包
+    pub async fn create_snapshot_with_monitor(
+        &self,
+        describe: &str,
+        monitor: &mut ArchiveMonitor<'_>,
+    ) -> Result<Snapshot, BackupError> {
+        self.create_and_upload_snapshot(describe, monitor)
+            .await
+            .map(|(snapshot, _)| snapshot)
+    }
+    /// 创建快照并在开启随时同步时上传，返回创建的快照与上传失败的原因
+    ///
+    /// 上传失败不影响本地快照，只作为警告返回给调用方；
+    /// 交给后台上传时不等待结果，失败通过通知提示，并留在云端队列中等待补传
+    pub(super) async fn create_and_upload_snapshot(
+        &self,
+        describe: &str,
+        monitor: &mut ArchiveMonitor<'_>,
+    ) -> Result<(Snapshot, Option<BackendError>), BackupError> {
         let config = get_config()?;
-        let backup_path = super::utils::join_backup_dir(&config, &self.name); // the backup zip file should be placed here
-        let date = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+        let backup_path = self.backup_dir(&config); // the backup zip file should be placed here
+        let now = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
         let save_paths = &self.save_paths; // everything you should copy
 
-        let zip_path = backup_path.join([&date, ".zip"].concat());
-        // 获取压缩后的文件大小
-        let file_size = match compress_to_file(save_paths, &zip_path) {
-            Ok(size) => size,
+        super::utils::check_backup_root(&config)?;
+        self.check_paths_for_current_device()?;
+        let existing = self.get_game_snapshots_info()?.backups;
+        let (date, zip_path) = reserve_snapshot_date(&backup_path, &now, &existing)?;
+        let archive = match compress_to_file(save_paths, &zip_path, monitor) {
+            Ok(archive) => archive,
             Err(e) => {
                 // delete the zip if failed to write
                 fs::remove_file(&zip_path)?;
                 return Err(BackupError::Compress(e));
             }
         };
+        // 读回刚写入的压缩包，损坏的快照不写入记录
+        if config.settings.verify_after_backup {
+            if let Err(e) = verify_archive(&zip_path, archive.entries) {
+                warn!(target:"rgsm::backup::game", "Snapshot {:?} failed verification: {:#}", zip_path, e);
+                fs::remove_file(&zip_path)?;
+                return Err(BackupError::VerificationFailed {
+                    path: zip_path,
+                    reason: format!("{e:#}"),
+                });
+            }
+        }
 
-        let game_snapshots_info = Snapshot {
+        let file_name = format!("{date}.zip");
+        // 未填写描述时按模板生成，避免出现空白描述
+        let describe = if describe.trim().is_empty() {
+            auto_describe(&config, &t!("backend.backup.describe_manual"), &self.name)
+        } else {
+            describe.to_string()
+        };
+        let snapshot = Snapshot {
             date,
-            describe: describe.to_string(),
-            path: zip_path
-                .to_str()
-                .ok_or(BackupError::NonePathError)?
-                .to_string(),
-            size: file_size,
+            describe,
+            path: snapshot_record_path(&self.name, &file_name),
+            size: archive.size,
         };
-        let mut infos = self.get_game_snapshots_info()?;
-        infos.backups.push(game_snapshots_info);
-        self.set_game_snapshots_info(&infos)?;
+        // 压缩期间记录可能已被其他操作修改，重新读取后再追加
+        self.update_game_snapshots_info(|infos| {
+            infos.backups.push(snapshot.clone());
+            Ok(())
+        })?;
 
-        // 随时同步到云端
+        // 随时同步到云端，后台上传时本地快照不必等待网络
         if config.settings.cloud_settings.always_sync {
-            let op = config.settings.cloud_settings.backend.get_op()?;
-            // 上传存档记录信息
-            upload_game_snapshots(&op, infos).await?;
-            // 上传对应压缩包
-            // 此处防止路径中出现反斜杠，导致云端无法识别，替换win的反斜杠为斜杠
-            let p = zip_path
-                .iter()
-                .map(|s| s.to_str().ok_or(BackupError::NonePathError))
-                .collect::<Result<Vec<&str>, BackupError>>()?
-                .join("/");
-            op.write(&p, fs::read(&zip_path)?).await?;
+            if schedule_snapshot_upload(&self.name, &snapshot.date) {
+                return Ok((snapshot, None));
+            }
+            emit_sync_state(
+                Some(&self.name),
+                SyncDirection::Upload,
+                CloudSyncState::Started,
+                None,
+            );
+            let result = sync_or_queue(vec![
+                OutboxOperation::UploadRecords {
+                    game: self.name.clone(),
+                },
+                OutboxOperation::UploadSnapshot {
+                    game: self.name.clone(),
+                    date: snapshot.date.clone(),
+                },
+            ])
+            .await;
+            let (state, error) = match &result {
+                Ok(()) => (CloudSyncState::Finished, None),
+                Err(e) => (CloudSyncState::Failed, Some(e.to_string())),
+            };
+            emit_sync_state(Some(&self.name), SyncDirection::Upload, state, error);
+            // 本地快照已经创建成功，上传失败只提示用户，留待下次同步时补传
+            if result.is_err() {
+                show_notification(
+                    t!("backend.cloud.upload_pending_title"),
+                    t!("backend.cloud.upload_pending_body", name = self.name),
+                );
+            }
+            return Ok((snapshot, result.err()));
         }
-        Result::Ok(())
+        Ok((snapshot, None))
+    }
+    /// 存档单元在当前设备上没有路径时会被跳过，这里提示用户，全部缺失时直接报错
+    ///
+    /// 只检查启用的单元，全部停用时同样报错
+    fn check_paths_for_current_device(&self) -> Result<(), BackupError> {
+        let device_id = get_current_device_id();
+        let enabled = self.save_paths.iter().filter(|unit| unit.enabled).count();
+        if enabled == 0 {
+            return Err(BackupError::NoEnabledSaveUnit(self.name.clone()));
+        }
+        let missing = self
+            .save_paths
+            .iter()
+            .filter(|unit| unit.enabled && unit.get_path_for_device(device_id).is_none())
+            .count();
+        if missing == 0 {
+            return Ok(());
+        }
+        if missing == enabled {
+            return Err(BackupError::NoSavePathForDevice(self.name.clone()));
+        }
+        warn!(target:"rgsm::backup::game", "{} save units of {} have no path for this device", missing, self.name);
+        show_notification(
+            t!("backend.backup.unit_no_path_title"),
+            t!(
+                "backend.backup.unit_no_path_body",
+                name = self.name,
+                count = missing
+            ),
+        );
+        Ok(())
     }
     pub fn restore_snapshot(
         &self,
         date: &str,
         app_handle: Option<&AppHandle>,
+    ) -> Result<(), BackupError> {
+        self.restore_snapshot_with_monitor(date, app_handle, &mut ArchiveMonitor::silent())
+    }
+    /// 与 `restore_snapshot` 相同，通过 `monitor` 报告解压进度
+    pub fn restore_snapshot_with_monitor(
+        &self,
+        date: &str,
+        app_handle: Option<&AppHandle>,
+        monitor: &mut ArchiveMonitor,
+    ) -> Result<(), BackupError> {
+        let extra_backup = get_config()?.settings.extra_backup_when_apply;
+        self.restore_snapshot_with_extra_backup(date, app_handle, extra_backup, monitor)
+    }
+    /// 与 `restore_snapshot_with_monitor` 相同，由调用方决定是否先创建额外备份
+    ///
+    /// 解压完成前取消不会修改任何存档
+    pub fn restore_snapshot_with_extra_backup(
+        &self,
+        date: &str,
+        app_handle: Option<&AppHandle>,
+        extra_backup: bool,
+        monitor: &mut ArchiveMonitor,
     ) -> Result<(), BackupError> {
         let config = get_config()?;
-        let backup_path = super::utils::join_backup_dir(&config, &self.name);
-        if config.settings.extra_backup_when_apply {
+        let backup_path = self.backup_dir(&config);
+        if extra_backup {
             info!(target:"rgsm::backup::game","Creating extra backup.");
             if let Err(e) = self.create_overwrite_snapshot() {
                 if let Some(app_handle) = app_handle {
@@ -106,7 +360,7 @@ impl Game {
                             "Notification",
                             IpcNotification {
                                 level: NotificationLevel::warning,
-                                title: "WARNING".to_string(),
+                                title: t!("backend.notification.warning").to_string(),
                                 msg: t!("backend.backup.extra_backup_file_not_exist").to_string(),
                             },
                         )
@@ -115,13 +369,14 @@ impl Game {
                 warn!(target:"rgsm::backup::game","Failed to create extra backup: {:?}", e);
             }
         }
-        decompress_from_file(&self.save_paths, &backup_path, date, app_handle)?;
+        // 额外备份期间取消时直接返回，额外备份的失败只会被记录
+        monitor.check().map_err(CompressError::from)?;
+        decompress_from_file(&self.save_paths, &backup_path, date, app_handle, monitor)?;
         Result::Ok(())
     }
     pub fn create_overwrite_snapshot(&self) -> Result<(), BackupError> {
         let config = get_config()?;
-        let extra_backup_path = super::utils::join_backup_dir(&config, &self.name)
-            .join("extra_backup");
+        let extra_backup_path = self.backup_dir(&config).join("extra_backup");
 
         // Create extra backup
         if !extra_backup_path.exists() {
@@ -131,96 +386,201 @@ impl Game {
             .format("Overwrite_%Y-%m-%d_%H-%M-%S")
             .to_string();
         let zip_path = &extra_backup_path.join([&date, ".zip"].concat());
-        compress_to_file(&self.save_paths, zip_path)?;
-
-        // Delete oldest extra backup if there are more than 5 file
-        let extra_backups_dir: Vec<_> = extra_backup_path.read_dir()?.collect();
-        let mut extra_backups = Vec::new();
-        if extra_backups_dir.len() >= 5 {
-            extra_backups_dir.into_iter().try_for_each(|f| {
-                extra_backups.push(
-                    f?.file_name()
-                        .to_str()
-                        .ok_or(BackupError::NonePathError)?
-                        .to_string(),
-                );
-                Result::<(), BackupError>::Ok(())
-            })?;
-            extra_backups.sort();
-            let oldest = extra_backups.first().ok_or(BackupError::NonePathError)?; // 一定要改好这一行
-            info!("Remove oldest: {:?}", oldest);
-            fs::remove_file(extra_backup_path.join(oldest))?;
-        }
-        Result::Ok(())
+        compress_to_file(&self.save_paths, zip_path, &mut ArchiveMonitor::silent())?;
+        prune_extra_backups(&extra_backup_path, config.settings.use_trash_on_delete)
     }
     pub async fn delete_snapshot(&self, date: &str) -> Result<(), BackupError> {
         let config = get_config()?;
-        let save_path = super::utils::join_backup_dir(&config, &self.name)
-            .join(date.to_string() + ".zip");
-        fs::remove_file(&save_path)?;
-
-        let mut saves = self.get_game_snapshots_info()?;
-        saves.backups.retain(|x| x.date != date);
-        self.set_game_snapshots_info(&saves)?;
+        let backup_dir = self.backup_dir(&config);
+        delete_snapshot_in(
+            &self.name,
+            &backup_dir,
+            date,
+            config.settings.use_trash_on_delete,
+        )?;
 
-        // 随时同步到云端
+        // 随时同步到云端，失败的操作留在队列中等待下次同步
         if config.settings.cloud_settings.always_sync {
-            let op = config.settings.cloud_settings.backend.get_op()?;
-            // 上传存档记录信息
-            upload_game_snapshots(&op, saves).await?;
-            // 删除对应压缩包
-            // 此处防止路径中出现反斜杠，导致云端无法识别，替换win的反斜杠为斜杠
-            let p = save_path
-                .iter()
-                .map(|s| s.to_str().ok_or(BackupError::NonePathError))
-                .collect::<Result<Vec<&str>, BackupError>>()?
-                .join("/");
-            op.delete(&p).await?;
+            let _ = sync_or_queue(vec![
+                OutboxOperation::UploadRecords {
+                    game: self.name.clone(),
+                },
+                OutboxOperation::DeleteSnapshot {
+                    game: self.name.clone(),
+                    date: date.to_string(),
+                },
+            ])
+            .await;
         }
         Ok(())
     }
-    pub async fn delete_game(&self) -> Result<(), BackupError> {
-        let mut config = get_config()?;
-        let backup_path = super::utils::join_backup_dir(&config, &self.name);
-        fs::remove_dir_all(&backup_path)?;
-
-        config.games.retain(|x| x.name != self.name);
-        set_config(&config).await?;
-
-        // 随时同步到云端
-        if config.settings.cloud_settings.always_sync {
-            let op = config.settings.cloud_settings.backend.get_op()?;
-            info!(target:"rgsm::backup::game",
-                "Delete Game: {:#?}",
-                backup_path.to_str().ok_or(BackupError::NonePathError)?
-            );
-            // 此处防止路径中出现反斜杠，导致云端无法识别，替换win的反斜杠为斜杠
-            let p = backup_path
-                .iter()
-                .map(|s| s.to_str().ok_or(BackupError::NonePathError))
-                .collect::<Result<Vec<&str>, BackupError>>()?
-                .join("/");
-            op.remove_all(&p).await?;
-            // 也上传新的配置文件
-            upload_config(&op).await?;
-        }
-
-        Ok(())
+    /// 删除游戏，备份移入 `.deleted`，保留期内可以撤销，云端备份在保留期结束后才删除
+    pub async fn delete_game(&self) -> Result<GameSoftDeleted, BackupError> {
+        super::soft_delete::soft_delete_game(self).await
     }
     pub async fn set_snapshot_description(
         &self,
         date: &str,
         describe: &str,
     ) -> Result<(), BackupError> {
-        let mut saves = self.get_game_snapshots_info()?;
-        let pos = saves.backups.iter().position(|x| x.date == date).ok_or(
-            BackupError::BackupNotExist {
-                name: self.name.clone(),
-                date: date.to_string(),
-            },
-        )?;
-        saves.backups[pos].describe = describe.to_string();
-        self.set_game_snapshots_info(&saves)?;
-        Ok(())
+        self.update_game_snapshots_info(|saves| {
+            let snapshot = saves.backups.iter_mut().find(|x| x.date == date).ok_or(
+                BackupError::BackupNotExist {
+                    name: self.name.clone(),
+                    date: date.to_string(),
+                },
+            )?;
+            snapshot.describe = describe.to_string();
+            Ok(())
+        })
+    }
+}
+
+/// 额外备份超过 5 个时删除最旧的一个，文件名以时间开头，按名称排序即按时间排序
+pub(super) fn prune_extra_backups(
+    extra_backup_path: &Path,
+    use_trash: bool,
+) -> Result<(), BackupError> {
+    let extra_backups_dir: Vec<_> = extra_backup_path.read_dir()?.collect();
+    let mut extra_backups = Vec::new();
+    if extra_backups_dir.len() >= 5 {
+        extra_backups_dir.into_iter().try_for_each(|f| {
+            extra_backups.push(
+                f?.file_name()
+                    .to_str()
+                    .ok_or(BackupError::NonePathError)?
+                    .to_string(),
+            );
+            Result::<(), BackupError>::Ok(())
+        })?;
+        extra_backups.sort();
+        let oldest = extra_backups.first().ok_or(BackupError::NonePathError)?; // 一定要改好这一行
+        info!("Remove oldest: {:?}", oldest);
+        super::utils::remove_backup_path(&extra_backup_path.join(oldest), use_trash)?;
+    }
+    Result::Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud_sync::cloud_backup_file;
+
+    #[test]
+    fn backup_dir_uses_the_storage_name() {
+        let root = temp_dir::TempDir::new().unwrap();
+        let config = Config {
+            backup_path: root.path().to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        let game = Game {
+            name: "Game: Deluxe Edition.".to_string(),
+            save_paths: Vec::new(),
+            game_paths: HashMap::new(),
+        };
+        let dir = game.backup_dir(&config);
+        assert_eq!(dir, root.path().join("Game_ Deluxe Edition"));
+        assert_eq!(
+            snapshot_record_path(&game.name, "a.zip"),
+            "Game_ Deluxe Edition/a.zip"
+        );
+        assert_eq!(
+            cloud_backup_file(&game.name, "a.zip"),
+            "save_data/Game_ Deluxe Edition/a.zip"
+        );
+
+        // 还没有创建过备份时给出明确的错误
+        assert!(matches!(
+            game.existing_backup_dir(&config),
+            Err(BackupError::BackupFolderMissing(path)) if path == dir
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(game.existing_backup_dir(&config).unwrap(), dir);
+    }
+
+    #[test]
+    fn snapshots_in_the_same_second_get_distinct_dates() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let now = "2024-01-01_00-00-00";
+        let mut existing = Vec::new();
+        for _ in 0..12 {
+            let (date, zip_path) = reserve_snapshot_date(dir.path(), now, &existing).unwrap();
+            assert!(zip_path.is_file());
+            existing.push(Snapshot {
+                date,
+                describe: String::new(),
+                path: zip_path.to_string_lossy().to_string(),
+                size: 0,
+            });
+        }
+        let dates: Vec<_> = existing.iter().map(|s| s.date.as_str()).collect();
+        assert_eq!(
+            dates[..3],
+            [now, "2024-01-01_00-00-00_001", "2024-01-01_00-00-00_002"]
+        );
+        // 按字符串排序与创建顺序一致，`_010` 排在 `_009` 之后
+        assert!(dates.is_sorted());
+
+        // 记录已删除但压缩包仍在时也不会覆盖
+        existing.clear();
+        let (date, _) = reserve_snapshot_date(dir.path(), now, &existing).unwrap();
+        assert_eq!(date, "2024-01-01_00-00-00_012");
+    }
+
+    /// 并发创建与删除同一游戏的快照后，记录与磁盘上的压缩包一致
+    #[test]
+    fn concurrent_creates_and_deletes_keep_records_consistent() {
+        let root = temp_dir::TempDir::new().unwrap();
+        let dir = root.path().join("Game");
+        let snapshot = |date: &str| Snapshot {
+            date: date.to_string(),
+            describe: String::new(),
+            path: snapshot_record_path("Game", &format!("{date}.zip")),
+            size: 0,
+        };
+        let old_dates: Vec<String> = (0..16)
+            .map(|i| format!("2024-01-01_00-00-{i:02}"))
+            .collect();
+        fs::create_dir_all(&dir).unwrap();
+        for date in &old_dates {
+            fs::write(dir.join(format!("{date}.zip")), b"zip").unwrap();
+        }
+        let records = old_dates.iter().map(String::as_str).map(snapshot).collect();
+        write_records(&dir, &GameSnapshots::new("Game", records)).unwrap();
+
+        std::thread::scope(|s| {
+            for date in &old_dates {
+                let dir = &dir;
+                s.spawn(move || delete_snapshot_in("Game", dir, date, false).unwrap());
+                s.spawn(move || {
+                    let (date, _) = reserve_snapshot_date(dir, "2024-01-02_00-00-00", &[]).unwrap();
+                    update_records("Game", dir, |info| {
+                        info.backups.push(snapshot(&date));
+                        Ok(())
+                    })
+                    .unwrap();
+                });
+            }
+        });
+
+        let mut recorded: Vec<String> = read_records("Game", &dir)
+            .unwrap()
+            .backups
+            .into_iter()
+            .map(|s| s.date)
+            .collect();
+        recorded.sort();
+        let mut zips: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                name.strip_suffix(".zip").map(str::to_string)
+            })
+            .collect();
+        zips.sort();
+        assert_eq!(recorded, zips);
+        assert_eq!(recorded.len(), old_dates.len());
+        assert!(recorded.iter().all(|d| d.starts_with("2024-01-02")));
     }
 }
@@ -1,13 +1,44 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use super::Snapshot;
+use super::{Snapshot, sanitize_windows_path_component, snapshot_record_path};
 
 /// A backup list info is a json file in a backup folder for a game.
 /// It contains the name of the game,
 /// and all backups' path
-#[derive(Debug, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct GameSnapshots {
     pub name: String,
+    /// Folder name of the game, both in the backup root and under `save_data` on the cloud.
+    /// Derived from `name` by `sanitize_windows_path_component`, empty in records written
+    /// by older versions until they are normalized
+    #[serde(default)]
+    pub storage_name: String,
     pub backups: Vec<Snapshot>,
 }
+
+impl GameSnapshots {
+    pub fn new(name: &str, backups: Vec<Snapshot>) -> Self {
+        GameSnapshots {
+            name: name.to_string(),
+            storage_name: sanitize_windows_path_component(name),
+            backups,
+        }
+    }
+
+    /// Rewrite every `path` to `<game>/<date>.zip` relative to the backup root and
+    /// fill in `storage_name`, returns whether anything changed
+    pub fn normalize_paths(&mut self) -> bool {
+        let storage_name = sanitize_windows_path_component(&self.name);
+        let mut changed = self.storage_name != storage_name;
+        self.storage_name = storage_name;
+        for snapshot in &mut self.backups {
+            let relative = snapshot_record_path(&self.name, &format!("{}.zip", snapshot.date));
+            if snapshot.path != relative {
+                snapshot.path = relative;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
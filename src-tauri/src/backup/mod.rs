@@ -1,13 +1,50 @@
 mod archive;
+mod data_check;
+mod describe;
+mod device_paths;
 mod game;
 mod game_snapshots;
+mod move_snapshot;
+mod portable;
+mod progress;
+mod recovery;
+mod relocate;
+mod restore_plan;
 mod save_unit;
+mod single_file;
 mod snapshot;
+mod snapshot_contents;
+mod snapshot_diff;
+mod soft_delete;
+mod storage;
 mod utils;
+mod validation;
 
-use archive::{compress_to_file, decompress_from_file};
+use archive::{compress_to_file, decompress_from_file, verify_archive};
+pub use data_check::{
+    DataIssue, apply_data_fixes, get_consistency_report, notify_consistency_issues, validate_data,
+};
+pub use describe::auto_describe;
+pub use device_paths::{
+    SaveUnitDevicePaths, list_device_paths, remove_unit_path_for_device, set_unit_path_for_device,
+};
 pub use game::Game;
 pub use game_snapshots::GameSnapshots;
-pub use save_unit::{SaveUnit, SaveUnitType};
+pub use move_snapshot::move_snapshots;
+pub use portable::{
+    ImportMergeStrategy, export_game_archive, export_sanitized_config, import_game_archive,
+    import_games_from_config,
+};
+pub use progress::{ArchiveMonitor, ArchiveOperation, ArchiveProgress, BackupOperations};
+pub use recovery::rebuild_game_snapshots;
+pub use relocate::{BackupRootMigration, BackupRootMigrationProgress, migrate_backup_root};
+pub use restore_plan::{RestorePlan, preview_restore};
+pub use save_unit::{ApplyCleanMode, PathFilter, SaveUnit, SaveUnitType, SymlinkMode};
+pub use single_file::restore_single_file;
 pub use snapshot::Snapshot;
+pub use snapshot_contents::{SnapshotContents, list_snapshot_contents};
+pub use snapshot_diff::{SnapshotDiff, diff_snapshots};
+pub use soft_delete::{GameSoftDeleted, purge_deleted_games, undo_delete_game};
+pub use storage::{CleanupReport, StorageReport, cleanup_backup_storage, get_storage_report};
 pub use utils::*;
+pub use validation::{GameValidationReport, validate_game};
@@ -0,0 +1,209 @@
+//! Moving snapshots that were taken under the wrong game
+
+use std::fs;
+use std::path::Path;
+
+use log::{error, info, warn};
+
+use super::game::{read_records, with_records_locks, write_records};
+use super::utils::create_backup_folder;
+use crate::backup::{GameSnapshots, check_backup_root, join_backup_dir, snapshot_record_path};
+use crate::cloud_sync::{OutboxOperation, rename_queued, sync_or_queue};
+use crate::config::get_config;
+use crate::preclude::*;
+
+/// Move the records of `dates` from `from` to `to`, keeping both lists sorted by date
+///
+/// Nothing is changed unless every date exists in `from` and none exists in `to`.
+fn move_records(
+    from: &mut GameSnapshots,
+    to: &mut GameSnapshots,
+    dates: &[String],
+) -> Result<(), BackupError> {
+    if let Some(date) = dates
+        .iter()
+        .find(|date| !from.backups.iter().any(|s| &s.date == *date))
+    {
+        return Err(BackupError::BackupNotExist {
+            name: from.name.clone(),
+            date: date.clone(),
+        });
+    }
+    let conflicts: Vec<String> = dates
+        .iter()
+        .filter(|date| to.backups.iter().any(|s| &s.date == *date))
+        .cloned()
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(BackupError::ImportConflict {
+            name: to.name.clone(),
+            dates: conflicts,
+        });
+    }
+    let (moved, kept) = std::mem::take(&mut from.backups)
+        .into_iter()
+        .partition(|s| dates.contains(&s.date));
+    from.backups = kept;
+    for mut snapshot in moved {
+        snapshot.path = snapshot_record_path(&to.name, &format!("{}.zip", snapshot.date));
+        to.backups.push(snapshot);
+    }
+    to.backups.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(())
+}
+
+/// Move the zips of `dates` between two backup folders, moving them back if one fails
+fn move_zips(from_dir: &Path, to_dir: &Path, dates: &[String]) -> Result<(), BackupError> {
+    for (i, date) in dates.iter().enumerate() {
+        let file_name = format!("{date}.zip");
+        if let Err(e) = fs::rename(from_dir.join(&file_name), to_dir.join(&file_name)) {
+            for date in &dates[..i] {
+                let file_name = format!("{date}.zip");
+                if let Err(move_back) =
+                    fs::rename(to_dir.join(&file_name), from_dir.join(&file_name))
+                {
+                    error!(target: "rgsm::backup", "Failed to move {} back: {:?}", file_name, move_back);
+                }
+            }
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+/// Move the snapshots of `dates` from the game `from_name` to the game `to_name`
+///
+/// Both games must exist, the backup folder of the destination is created if needed. Dates
+/// already used by the destination are rejected with `BackupError::ImportConflict` before
+/// anything is moved. The cloud copies are moved through the outbox, a failure leaves
+/// them queued and the local move stands.
+pub async fn move_snapshots(
+    from_name: &str,
+    dates: &[String],
+    to_name: &str,
+) -> Result<(), BackupError> {
+    let config = get_config()?;
+    for name in [from_name, to_name] {
+        if !config.games.iter().any(|g| g.name == name) {
+            return Err(BackupError::GameNotExist(name.to_string()));
+        }
+    }
+    let mut dates = dates.to_vec();
+    dates.sort();
+    dates.dedup();
+    if from_name == to_name || dates.is_empty() {
+        return Ok(());
+    }
+    check_backup_root(&config)?;
+    let from_dir = join_backup_dir(&config, from_name);
+    let to_dir = join_backup_dir(&config, to_name);
+    if !to_dir.exists() {
+        create_backup_folder(to_name).await?;
+    }
+
+    // Both records stay locked until written, so a backup finishing meanwhile is kept
+    with_records_locks(&from_dir, &to_dir, || {
+        let mut from_info = read_records(from_name, &from_dir)?;
+        let mut to_info = read_records(to_name, &to_dir)?;
+        move_records(&mut from_info, &mut to_info, &dates)?;
+        for date in &dates {
+            let file_name = format!("{date}.zip");
+            if !from_dir.join(&file_name).exists() {
+                return Err(BackupError::BackupNotExist {
+                    name: from_name.to_string(),
+                    date: date.clone(),
+                });
+            }
+            if to_dir.join(&file_name).exists() {
+                return Err(BackupError::ImportConflict {
+                    name: to_name.to_string(),
+                    dates: vec![date.clone()],
+                });
+            }
+        }
+
+        info!(target: "rgsm::backup", "Moving snapshots {:?} from {} to {}", dates, from_name, to_name);
+        move_zips(&from_dir, &to_dir, &dates)?;
+        // Destination first, a failure in between leaves the snapshot listed twice rather than lost
+        write_records(&to_dir, &to_info)?;
+        write_records(&from_dir, &from_info)?;
+        Ok::<_, BackupError>(())
+    })?;
+
+    // Uploads queued under the source game would find no local zip and be dropped
+    if let Err(e) = rename_queued(from_name, to_name, Some(&dates)) {
+        warn!(target: "rgsm::backup", "Failed to move queued cloud uploads of {}: {:?}", from_name, e);
+    }
+    if config.settings.cloud_settings.always_sync {
+        let mut operations: Vec<_> = dates
+            .iter()
+            .map(|date| OutboxOperation::MoveSnapshot {
+                from: from_name.to_string(),
+                to: to_name.to_string(),
+                date: date.clone(),
+            })
+            .collect();
+        // Destination first, like the local records
+        operations.push(OutboxOperation::UploadRecords {
+            game: to_name.to_string(),
+        });
+        operations.push(OutboxOperation::UploadRecords {
+            game: from_name.to_string(),
+        });
+        let _ = sync_or_queue(operations).await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::Snapshot;
+
+    fn snapshots(name: &str, dates: &[&str]) -> GameSnapshots {
+        GameSnapshots::new(
+            name,
+            dates
+                .iter()
+                .map(|date| Snapshot {
+                    date: date.to_string(),
+                    describe: String::new(),
+                    path: snapshot_record_path(name, &format!("{date}.zip")),
+                    size: 1,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn records_move_in_date_order() {
+        let mut from = snapshots("B", &["2024-01-01_00-00-00", "2024-01-03_00-00-00"]);
+        let mut to = snapshots("A", &["2024-01-02_00-00-00"]);
+        move_records(&mut from, &mut to, &["2024-01-01_00-00-00".to_string()]).unwrap();
+        assert_eq!(from.backups.len(), 1);
+        let dates: Vec<_> = to.backups.iter().map(|s| s.date.as_str()).collect();
+        assert_eq!(dates, ["2024-01-01_00-00-00", "2024-01-02_00-00-00"]);
+        assert_eq!(to.backups[0].path, "A/2024-01-01_00-00-00.zip");
+    }
+
+    #[test]
+    fn colliding_or_unknown_dates_are_rejected() {
+        let mut from = snapshots("B", &["2024-01-01_00-00-00", "2024-01-02_00-00-00"]);
+        let mut to = snapshots("A", &["2024-01-02_00-00-00"]);
+        let dates = [
+            "2024-01-01_00-00-00".to_string(),
+            "2024-01-02_00-00-00".to_string(),
+        ];
+        assert!(matches!(
+            move_records(&mut from, &mut to, &dates),
+            Err(BackupError::ImportConflict { dates, .. }) if dates == ["2024-01-02_00-00-00"]
+        ));
+        assert!(matches!(
+            move_records(&mut from, &mut to, &["2024-01-05_00-00-00".to_string()]),
+            Err(BackupError::BackupNotExist { .. })
+        ));
+        // Rejected moves leave both lists alone
+        assert_eq!(from.backups.len(), 2);
+        assert_eq!(to.backups.len(), 1);
+    }
+}
@@ -0,0 +1,234 @@
+//! Progress reporting and cancellation of snapshot compression and extraction
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tokio_util::sync::CancellationToken;
+
+use crate::preclude::*;
+
+/// Progress events of one operation are at least this far apart, except the last one
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum ArchiveOperation {
+    Compress,
+    Extract,
+}
+
+/// Emitted while a snapshot of `game_name` is being created or restored from the main window
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct ArchiveProgress {
+    pub game_name: String,
+    pub operation: ArchiveOperation,
+    pub processed_bytes: u64,
+    /// Estimated from the save files when compressing, exact when extracting
+    pub total_bytes: u64,
+    pub current_file: String,
+}
+
+type Report<'a> = Box<dyn FnMut(u64, u64, &Path) + Send + 'a>;
+
+/// Tracks the bytes processed by one archive operation and tells it when to stop
+pub struct ArchiveMonitor<'a> {
+    cancel: CancellationToken,
+    report: Option<Report<'a>>,
+    processed: u64,
+    total: u64,
+    last_report: Option<Instant>,
+}
+
+impl<'a> ArchiveMonitor<'a> {
+    /// `report` receives the processed bytes, the total and the current file
+    pub fn new(cancel: CancellationToken, report: impl FnMut(u64, u64, &Path) + Send + 'a) -> Self {
+        ArchiveMonitor {
+            cancel,
+            report: Some(Box::new(report)),
+            processed: 0,
+            total: 0,
+            last_report: None,
+        }
+    }
+
+    /// Forward the progress of `operation` on `game_name` to the frontend as `ArchiveProgress`
+    pub fn emitting(
+        app: &'a AppHandle,
+        game_name: &'a str,
+        operation: ArchiveOperation,
+        cancel: CancellationToken,
+    ) -> Self {
+        Self::new(
+            cancel,
+            move |processed_bytes, total_bytes, current: &Path| {
+                let progress = ArchiveProgress {
+                    game_name: game_name.to_string(),
+                    operation,
+                    processed_bytes,
+                    total_bytes,
+                    current_file: current.to_string_lossy().to_string(),
+                };
+                if let Err(e) = progress.emit(app) {
+                    warn!(target: "rgsm::backup::archive", "Failed to emit archive progress: {:?}", e);
+                }
+            },
+        )
+    }
+
+    /// A monitor that reports nothing and is never cancelled
+    pub fn silent() -> Self {
+        ArchiveMonitor {
+            cancel: CancellationToken::new(),
+            report: None,
+            processed: 0,
+            total: 0,
+            last_report: None,
+        }
+    }
+
+    pub(super) fn set_total(&mut self, total: u64) {
+        self.total = total;
+    }
+
+    pub(super) fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    pub(super) fn check(&self) -> Result<(), BackupFileError> {
+        if self.is_cancelled() {
+            return Err(BackupFileError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Count `bytes` of `current` as done, then stop if the operation was cancelled
+    pub(super) fn advance(&mut self, bytes: u64, current: &Path) -> Result<(), BackupFileError> {
+        self.processed += bytes;
+        if self
+            .last_report
+            .is_none_or(|last| last.elapsed() >= REPORT_INTERVAL)
+        {
+            self.report(current);
+        }
+        self.check()
+    }
+
+    /// Report the final numbers, skipped by the throttling in `advance`
+    pub(super) fn finish(&mut self) {
+        self.report(Path::new(""));
+    }
+
+    fn report(&mut self, current: &Path) {
+        // The estimate misses files created after the walk
+        let total = self.total.max(self.processed);
+        if let Some(report) = &mut self.report {
+            report(self.processed, total, current);
+            self.last_report = Some(Instant::now());
+        }
+    }
+}
+
+#[derive(Default)]
+struct RunningOperations {
+    next_id: u64,
+    /// Keyed by game name, the id tells apart operations on the same game
+    tokens: HashMap<String, (u64, CancellationToken)>,
+}
+
+/// Cancellation tokens of the snapshots being created or restored from the main window
+#[derive(Default)]
+pub struct BackupOperations(Mutex<RunningOperations>);
+
+impl BackupOperations {
+    /// Register an operation on `game_name`, it is unregistered when the guard is dropped
+    pub fn start(&self, game_name: &str) -> BackupOperationGuard<'_> {
+        let token = CancellationToken::new();
+        let mut running = self.lock();
+        let id = running.next_id;
+        running.next_id += 1;
+        running
+            .tokens
+            .insert(game_name.to_string(), (id, token.clone()));
+        BackupOperationGuard {
+            operations: self,
+            game_name: game_name.to_string(),
+            id,
+            token,
+        }
+    }
+
+    /// Cancel the running operation on `game_name`, returns whether there was one
+    pub fn cancel(&self, game_name: &str) -> bool {
+        match self.lock().tokens.get(game_name) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RunningOperations> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub struct BackupOperationGuard<'a> {
+    operations: &'a BackupOperations,
+    game_name: String,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl BackupOperationGuard<'_> {
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for BackupOperationGuard<'_> {
+    fn drop(&mut self) {
+        let mut running = self.operations.lock();
+        // A newer operation on the same game may have replaced this one
+        if running
+            .tokens
+            .get(&self.game_name)
+            .is_some_and(|(id, _)| *id == self.id)
+        {
+            running.tokens.remove(&self.game_name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_operations_stop_at_the_next_file() {
+        let operations = BackupOperations::default();
+        let guard = operations.start("Game");
+        let mut reports = Vec::new();
+        let mut monitor = ArchiveMonitor::new(guard.token(), |processed, total, _: &Path| {
+            reports.push((processed, total))
+        });
+        monitor.set_total(10);
+        monitor.advance(4, Path::new("a")).unwrap();
+        assert!(operations.cancel("Game"));
+        assert!(matches!(
+            monitor.advance(4, Path::new("b")),
+            Err(BackupFileError::Cancelled)
+        ));
+        drop(monitor);
+        assert_eq!(reports, [(4, 10)]);
+
+        drop(guard);
+        assert!(!operations.cancel("Game"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Rebuilding a game's `Backups.json` from the snapshot zips on disk
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use log::info;
+use rust_i18n::t;
+
+use super::game::with_records_lock;
+use super::storage::list_zips;
+use crate::backup::{Game, GameSnapshots, Snapshot};
+use crate::config::get_config;
+use crate::preclude::*;
+
+const DATE_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// Whether `stem` is a snapshot date, optionally with the `_001`, `_002`, ... suffix given to
+/// backups made within the same second, older versions did not pad it
+pub(super) fn is_snapshot_date(stem: &str) -> bool {
+    let Some(date) = stem.get(..19) else {
+        return false;
+    };
+    let suffix = &stem[19..];
+    chrono::NaiveDateTime::parse_from_str(date, DATE_FORMAT).is_ok()
+        && (suffix.is_empty()
+            || suffix
+                .strip_prefix('_')
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())))
+}
+
+/// Every snapshot record that can still be read from a damaged `Backups.json`
+///
+/// Each `{` is tried as the start of a record, so the records before the damage survive a
+/// truncated file and the ones after it survive garbage in the middle
+fn salvage_snapshots(bytes: &[u8]) -> Vec<Snapshot> {
+    let mut snapshots = Vec::new();
+    let mut start = 0;
+    while let Some(offset) = bytes[start..].iter().position(|b| *b == b'{') {
+        let from = start + offset;
+        let mut stream = serde_json::Deserializer::from_slice(&bytes[from..]).into_iter();
+        match stream.next() {
+            Some(Ok(snapshot)) => {
+                snapshots.push(snapshot);
+                start = from + stream.byte_offset();
+            }
+            _ => start = from + 1,
+        }
+    }
+    snapshots
+}
+
+/// Merge the salvaged records with the zips found on disk
+///
+/// Zips are the source of truth for sizes. Records whose zip is gone are kept, the storage
+/// report lists them as missing.
+fn rebuild_records(
+    name: &str,
+    remnants: Vec<Snapshot>,
+    zips: &[(String, u64)],
+    placeholder: &str,
+) -> GameSnapshots {
+    let mut by_date = BTreeMap::new();
+    for snapshot in remnants {
+        by_date.entry(snapshot.date.clone()).or_insert(snapshot);
+    }
+    for (file_name, size) in zips {
+        let Some(date) = file_name.strip_suffix(".zip") else {
+            continue;
+        };
+        if !is_snapshot_date(date) {
+            continue;
+        }
+        by_date
+            .entry(date.to_string())
+            .and_modify(|snapshot: &mut Snapshot| snapshot.size = *size)
+            .or_insert_with(|| Snapshot {
+                date: date.to_string(),
+                describe: placeholder.to_string(),
+                path: String::new(),
+                size: *size,
+            });
+    }
+    let mut info = GameSnapshots::new(name, by_date.into_values().collect());
+    info.normalize_paths();
+    info
+}
+
+/// Turn a failure to read `Backups.json` into `SnapshotRecordsCorrupted` when the records
+/// can be rebuilt, so the frontend can offer a repair
+pub(super) fn records_error(name: &str, dir: &Path, e: BackupError) -> BackupError {
+    match e {
+        BackupError::Deserialize(_) => BackupError::SnapshotRecordsCorrupted(name.to_string()),
+        BackupError::Io(e)
+            if e.kind() == io::ErrorKind::NotFound
+                && list_zips(dir).is_ok_and(|zips| !zips.is_empty()) =>
+        {
+            BackupError::SnapshotRecordsCorrupted(name.to_string())
+        }
+        e => e,
+    }
+}
+
+/// Rebuild the snapshot records of `game` from the zips in its backup folder
+///
+/// Records still readable from the old `Backups.json` or its `.bak` copy keep their
+/// descriptions, zips without a record get a placeholder description. A damaged
+/// `Backups.json` is kept as `Backups.json.broken`.
+pub fn rebuild_game_snapshots(game: &Game) -> Result<GameSnapshots, BackupError> {
+    let config = get_config()?;
+    let dir = game.backup_dir(&config);
+    let records_path = dir.join("Backups.json");
+
+    let info = with_records_lock(&dir, || {
+        let old = fs::read(&records_path).ok();
+        let mut remnants = Vec::new();
+        for bytes in [old.clone(), fs::read(backup_file_path(&records_path)).ok()]
+            .into_iter()
+            .flatten()
+        {
+            remnants.extend(salvage_snapshots(&bytes));
+        }
+        let zips = list_zips(&dir)?;
+        let info = rebuild_records(
+            &game.name,
+            remnants,
+            &zips,
+            &t!("backend.backup.recovered_describe"),
+        );
+
+        fs::create_dir_all(&dir)?;
+        if old.is_some_and(|bytes| serde_json::from_slice::<GameSnapshots>(&bytes).is_err()) {
+            fs::rename(&records_path, dir.join("Backups.json.broken"))?;
+        }
+        write_atomic(
+            &records_path,
+            serde_json::to_string_pretty(&info)?.as_bytes(),
+        )?;
+        Ok::<_, BackupError>(info)
+    })?;
+    info!(target:"rgsm::backup", "Rebuilt {} snapshot records of {}", info.backups.len(), game.name);
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_salvaged_from_a_truncated_file() {
+        let bytes = br#"{
+  "name": "Game",
+  "backups": [
+    { "date": "2024-01-01_10-00-00", "describe": "boss {fight}", "path": "Game/2024-01-01_10-00-00.zip", "size": 1 },
+    { "date": "2024-01-02_10-00-00", "describe": "second", "path": "Game/2024-01-0"#;
+        let snapshots = salvage_snapshots(bytes);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].describe, "boss {fight}");
+    }
+
+    #[test]
+    fn zips_on_disk_are_merged_with_the_remnants() {
+        let remnants = vec![
+            Snapshot {
+                date: "2024-01-01_10-00-00".to_string(),
+                describe: "kept".to_string(),
+                path: "/old/root/Game/2024-01-01_10-00-00.zip".to_string(),
+                size: 1,
+            },
+            Snapshot {
+                date: "2023-12-31_10-00-00".to_string(),
+                describe: "zip is gone".to_string(),
+                path: "Game/2023-12-31_10-00-00.zip".to_string(),
+                size: 5,
+            },
+        ];
+        let zips = [
+            ("2024-01-01_10-00-00.zip".to_string(), 100),
+            ("2024-01-01_10-00-00_1.zip".to_string(), 200),
+            ("not-a-snapshot.zip".to_string(), 300),
+        ];
+        let info = rebuild_records("Game", remnants, &zips, "(recovered)");
+        let summary: Vec<_> = info
+            .backups
+            .iter()
+            .map(|s| (s.date.as_str(), s.describe.as_str(), s.size))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("2023-12-31_10-00-00", "zip is gone", 5),
+                ("2024-01-01_10-00-00", "kept", 100),
+                ("2024-01-01_10-00-00_1", "(recovered)", 200),
+            ]
+        );
+        assert_eq!(info.backups[2].path, "Game/2024-01-01_10-00-00_1.zip");
+    }
+}
@@ -0,0 +1,201 @@
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::AppHandle;
+use tauri_specta::Event;
+
+use crate::backup::{normalize_snapshot_records, sanitize_windows_path_component};
+use crate::config::{ConfigManager, get_config};
+use crate::preclude::*;
+
+/// Emitted after each game folder is handled by `migrate_backup_root`
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct BackupRootMigrationProgress {
+    pub game_name: String,
+    /// Games handled so far, including the ones without a backup folder
+    pub current: u32,
+    pub total: u32,
+}
+
+/// Result of a successful `migrate_backup_root`
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct BackupRootMigration {
+    pub old_path: String,
+    pub new_path: String,
+    /// Games whose backup folder was moved to the new root
+    pub moved: Vec<String>,
+}
+
+/// A game folder that will be moved from the old root to the new one
+struct PlannedMove {
+    name: String,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+/// Point `backup_path` at `new_path`, moving every game folder there first when
+/// `move_files` is set
+///
+/// Conflicts and free space are checked before anything is moved. If a move
+/// fails, the folders moved so far are moved back and the config is left
+/// untouched; games that could not be moved back are listed in the error.
+/// Snapshot records are rewritten to paths relative to the backup root, so
+/// later moves do not need to touch them again.
+pub async fn migrate_backup_root(
+    new_path: &str,
+    move_files: bool,
+    app: Option<&AppHandle>,
+) -> Result<BackupRootMigration, BackupError> {
+    let mut config = get_config()?;
+    let new_path = new_path.trim();
+    if new_path.is_empty() {
+        return Err(BackupError::InvalidBackupRoot(new_path.to_string()));
+    }
+    let old_root = config.backup_root();
+    let new_root = PathBuf::from(new_path);
+    let old_abs = std::path::absolute(&old_root)?;
+    let new_abs = std::path::absolute(&new_root)?;
+    if old_abs == new_abs {
+        return Err(BackupError::InvalidBackupRoot(new_path.to_string()));
+    }
+    // Moving the root into itself would nest game folders into each other
+    if move_files && new_abs.starts_with(&old_abs) {
+        return Err(BackupError::InvalidBackupRoot(new_path.to_string()));
+    }
+
+    let mut plan = Vec::new();
+    let mut required = 0;
+    if move_files {
+        for game in &config.games {
+            let dir_name = sanitize_windows_path_component(&game.name);
+            let from = old_root.join(&dir_name);
+            if !from.exists() {
+                continue;
+            }
+            let to = new_root.join(&dir_name);
+            if to.exists() {
+                return Err(BackupError::BackupRootConflict(to));
+            }
+            required += fs_extra::dir::get_size(&from)
+                .map_err(|e| BackupError::Compress(CompressError::Single(e.into())))?;
+            plan.push(PlannedMove {
+                name: game.name.clone(),
+                from,
+                to,
+            });
+        }
+        // Conservative: a rename on the same volume needs no space at all
+        let available = fs4::available_space(existing_ancestor(&new_abs))?;
+        if required > available {
+            return Err(BackupError::InsufficientSpace {
+                required,
+                available,
+            });
+        }
+    }
+
+    // Relative records are valid under both roots, so this is safe to do first
+    let records_root = if move_files { &old_root } else { &new_root };
+    for game in &config.games {
+        normalize_snapshot_records(records_root, &game.name)?;
+    }
+
+    info!(target: "rgsm::backup::relocate", "Migrating backup root {:?} to {:?}, move files: {}", old_root, new_root, move_files);
+    fs::create_dir_all(&new_root)?;
+    let total = config.games.len();
+    let mut moved: Vec<&PlannedMove> = Vec::new();
+    for (i, game) in config.games.iter().enumerate() {
+        if let Some(planned) = plan.iter().find(|p| p.name == game.name) {
+            if let Err(e) = move_dir(&planned.from, &planned.to) {
+                error!(target: "rgsm::backup::relocate", "Failed to move {:?}: {:?}", planned.from, e);
+                return Err(BackupError::BackupRootMigrationFailed {
+                    reason: format!("{}: {}", planned.name, e),
+                    stuck: roll_back(&moved),
+                });
+            }
+            moved.push(planned);
+        }
+        if let Some(app) = app {
+            let _ = BackupRootMigrationProgress {
+                game_name: game.name.clone(),
+                current: (i + 1) as u32,
+                total: total as u32,
+            }
+            .emit(app);
+        }
+    }
+
+    let old_path = std::mem::replace(&mut config.backup_path, new_path.to_string());
+    if let Err(e) = ConfigManager::global().set(&config).await {
+        error!(target: "rgsm::backup::relocate", "Failed to save config after migration: {:?}", e);
+        return Err(BackupError::BackupRootMigrationFailed {
+            reason: e.to_string(),
+            stuck: roll_back(&moved),
+        });
+    }
+    Ok(BackupRootMigration {
+        old_path,
+        new_path: config.backup_path,
+        moved: moved.iter().map(|p| p.name.clone()).collect(),
+    })
+}
+
+/// Rename when possible, copy and delete when the roots are on different volumes
+fn move_dir(from: &Path, to: &Path) -> Result<(), BackupError> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let options = fs_extra::dir::CopyOptions::new().content_only(true);
+    fs::create_dir_all(to)?;
+    if let Err(e) = fs_extra::dir::copy(from, to, &options) {
+        // Do not leave a partial copy behind, the source is still complete
+        let _ = fs::remove_dir_all(to);
+        return Err(BackupError::Compress(CompressError::Single(e.into())));
+    }
+    if let Err(e) = fs::remove_dir_all(from) {
+        // The copy is complete, a leftover source only wastes space
+        warn!(target: "rgsm::backup::relocate", "Cannot remove {:?} after copying it: {:?}", from, e);
+    }
+    Ok(())
+}
+
+/// Move folders back to the old root, returning the games that are still in the new one
+fn roll_back(moved: &[&PlannedMove]) -> Vec<String> {
+    let mut stuck = Vec::new();
+    for planned in moved.iter().rev() {
+        if let Err(e) = move_dir(&planned.to, &planned.from) {
+            error!(target: "rgsm::backup::relocate", "Failed to move {:?} back: {:?}", planned.to, e);
+            stuck.push(planned.name.clone());
+        }
+    }
+    stuck
+}
+
+/// `available_space` needs an existing path, `path` may not be created yet
+pub(super) fn existing_ancestor(path: &Path) -> &Path {
+    path.ancestors().find(|p| p.exists()).unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_dir_keeps_contents() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let from = dir.path().join("old").join("Game");
+        fs::create_dir_all(from.join("extra_backup")).unwrap();
+        fs::write(from.join("Backups.json"), b"{}").unwrap();
+        let to = dir.path().join("new").join("Game");
+        fs::create_dir_all(to.parent().unwrap()).unwrap();
+
+        move_dir(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert!(to.join("extra_backup").is_dir());
+        assert_eq!(fs::read(to.join("Backups.json")).unwrap(), b"{}");
+    }
+}
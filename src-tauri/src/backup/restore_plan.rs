@@ -0,0 +1,440 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::config::get_config;
+use crate::device::get_current_device_id;
+use crate::path_resolver::resolve_path;
+use crate::preclude::*;
+
+use super::{ApplyCleanMode, Game, SaveUnit, SaveUnitType};
+
+/// 文件的大小与修改时间（本地时间）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct FileStat {
+    pub size: u64,
+    pub modified: Option<String>,
+}
+
+/// 恢复时单个文件的变化，`unit_index` 为其所属存档单元的序号
+///
+/// - `snapshot`：快照中的文件，被删除的文件没有
+/// - `current`：当前磁盘上的文件，新增的文件没有
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct RestoreFileChange {
+    pub path: String,
+    pub unit_index: usize,
+    pub snapshot: Option<FileStat>,
+    pub current: Option<FileStat>,
+}
+
+/// 存档单元不参与恢复的原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RestoreSkipReason {
+    /// 当前设备没有为该单元配置路径
+    NoPathForDevice,
+    /// 路径中的变量无法解析
+    Unresolvable { reason: String },
+    /// 快照中没有该单元，通常是快照创建后才添加的单元
+    NotInSnapshot,
+    /// 单元已停用，恢复时不会处理
+    Disabled,
+}
+
+/// 被跳过的存档单元
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SkippedRestoreUnit {
+    pub index: usize,
+    pub reason: RestoreSkipReason,
+}
+
+/// 恢复快照前的预览，不会修改任何文件
+///
+/// - `added`：目标位置不存在的文件
+/// - `overwritten`：目标位置存在且内容不同的文件
+/// - `deleted`：按单元的 `delete_before_apply` 会被删除、且不会被快照重新写入的文件
+/// - `unchanged`：大小与 CRC32 都相同的文件
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct RestorePlan {
+    pub added: Vec<RestoreFileChange>,
+    pub overwritten: Vec<RestoreFileChange>,
+    pub deleted: Vec<RestoreFileChange>,
+    pub unchanged: Vec<RestoreFileChange>,
+    pub skipped_units: Vec<SkippedRestoreUnit>,
+}
+
+/// 快照压缩包中央目录里的一个文件
+struct SnapshotEntry {
+    /// 使用 `/` 分隔的路径，文件夹以 `/` 结尾
+    name: String,
+    is_dir: bool,
+    size: u64,
+    crc32: u32,
+    modified: Option<NaiveDateTime>,
+}
+
+/// 预览恢复快照 `date` 会对当前设备上的存档做出的修改
+///
+/// 只读取压缩包的中央目录并查询目标文件的信息，大小相同的文件会计算 CRC32 判断是否变化
+pub fn preview_restore(game: &Game, date: &str) -> Result<RestorePlan, BackupError> {
+    let config = get_config()?;
+    let zip_path = game.backup_dir(&config).join(format!("{date}.zip"));
+    if !zip_path.exists() {
+        return Err(BackupError::BackupNotExist {
+            name: game.name.clone(),
+            date: date.to_string(),
+        });
+    }
+    let entries = read_snapshot_entries(&zip_path)?;
+
+    let device_id = get_current_device_id();
+    let mut plan = RestorePlan::default();
+    for (index, unit) in game.save_paths.iter().enumerate() {
+        if !unit.enabled {
+            plan.skipped_units.push(SkippedRestoreUnit {
+                index,
+                reason: RestoreSkipReason::Disabled,
+            });
+            continue;
+        }
+        let Some(raw) = unit.get_path_for_device(device_id) else {
+            plan.skipped_units.push(SkippedRestoreUnit {
+                index,
+                reason: RestoreSkipReason::NoPathForDevice,
+            });
+            continue;
+        };
+        // 与恢复时一致，使用扩展长度路径访问深层嵌套的存档
+        let unit_path = match resolve_path(raw, Some(game), &config) {
+            Ok(path) => to_long_path(&path),
+            Err(e) => {
+                plan.skipped_units.push(SkippedRestoreUnit {
+                    index,
+                    reason: RestoreSkipReason::Unresolvable {
+                        reason: e.to_string(),
+                    },
+                });
+                continue;
+            }
+        };
+        if !plan_unit(&mut plan, index, unit, &unit_path, &entries).map_err(CompressError::from)? {
+            plan.skipped_units.push(SkippedRestoreUnit {
+                index,
+                reason: RestoreSkipReason::NotInSnapshot,
+            });
+        }
+    }
+    Ok(plan)
+}
+
+fn read_snapshot_entries(zip_path: &Path) -> Result<Vec<SnapshotEntry>, BackupError> {
+    let zip_err =
+        |e: zip::result::ZipError| BackupError::from(CompressError::from(BackupFileError::from(e)));
+    let mut archive = zip::ZipArchive::new(File::open(zip_path)?).map_err(zip_err)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        // 只读取中央目录，不解压
+        let file = archive.by_index_raw(i).map_err(zip_err)?;
+        entries.push(SnapshotEntry {
+            name: file.name().replace('\\', "/"),
+            is_dir: file.is_dir(),
+            size: file.size(),
+            crc32: file.crc32(),
+            modified: file.last_modified().and_then(|t| {
+                NaiveDate::from_ymd_opt(t.year().into(), t.month().into(), t.day().into())?
+                    .and_hms_opt(t.hour().into(), t.minute().into(), t.second().into())
+            }),
+        });
+    }
+    Ok(entries)
+}
+
+/// 将一个存档单元的变化加入 `plan`，快照中没有该单元时返回 false
+///
+/// 与 `decompress_from_file` 保持一致：被 include/exclude 排除的文件既不会恢复也不会删除
+fn plan_unit(
+    plan: &mut RestorePlan,
+    index: usize,
+    unit: &SaveUnit,
+    unit_path: &Path,
+    entries: &[SnapshotEntry],
+) -> Result<bool, BackupFileError> {
+    let root_name = unit_path
+        .file_name()
+        .ok_or(BackupFileError::NonePathError)?
+        .to_string_lossy()
+        .to_string();
+
+    if unit.unit_type == SaveUnitType::File {
+        let Some(entry) = entries.iter().find(|e| !e.is_dir && e.name == root_name) else {
+            return Ok(false);
+        };
+        classify(plan, index, unit_path, entry)?;
+        return Ok(true);
+    }
+
+    // 空文件夹在压缩包中只有 `root_name/` 这一项
+    let prefix = format!("{root_name}/");
+    if !entries.iter().any(|e| e.name.starts_with(&prefix)) {
+        return Ok(false);
+    }
+    let unit_entries = entries
+        .iter()
+        .filter(|e| !e.is_dir)
+        .filter_map(|e| e.name.strip_prefix(&prefix).map(|rel| (rel, e)));
+    let filter = unit.path_filter()?;
+    let mut tracked = HashSet::new();
+    let mut removed = HashSet::new();
+    for (rel, entry) in unit_entries {
+        if filter
+            .as_ref()
+            .is_some_and(|f| !f.includes_file(Path::new(rel)))
+        {
+            continue;
+        }
+        let target = unit_path.join(rel);
+        tracked.insert(target.clone());
+        if unit.delete_before_apply == ApplyCleanMode::TrackedOnly {
+            // 只删除快照中的路径：与快照文件同名的文件夹，或挡在快照路径上的文件
+            if target.is_dir() {
+                collect_files(&target, &mut removed)?;
+            }
+            removed.extend(
+                target
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|p| *p != unit_path)
+                    .filter(|p| p.is_file())
+                    .map(Path::to_path_buf),
+            );
+        }
+        classify(plan, index, &target, entry)?;
+    }
+
+    if unit.delete_before_apply == ApplyCleanMode::Full && unit_path.is_dir() {
+        let mut existing = HashSet::new();
+        collect_files(unit_path, &mut existing)?;
+        removed.extend(existing.into_iter().filter(|path| {
+            let rel = path.strip_prefix(unit_path).unwrap_or(path);
+            filter.as_ref().is_none_or(|f| f.includes_file(rel))
+        }));
+    }
+    let mut deleted: Vec<_> = removed.difference(&tracked).collect();
+    deleted.sort();
+    for path in deleted {
+        plan.deleted.push(RestoreFileChange {
+            path: from_long_path(path).to_string_lossy().to_string(),
+            unit_index: index,
+            snapshot: None,
+            current: Some(stat(path)?),
+        });
+    }
+    Ok(true)
+}
+
+/// 比较快照中的文件与目标位置的文件
+fn classify(
+    plan: &mut RestorePlan,
+    index: usize,
+    target: &Path,
+    entry: &SnapshotEntry,
+) -> Result<(), BackupFileError> {
+    let snapshot = FileStat {
+        size: entry.size,
+        modified: entry.modified.map(format_time),
+    };
+    let mut change = RestoreFileChange {
+        path: from_long_path(target).to_string_lossy().to_string(),
+        unit_index: index,
+        snapshot: Some(snapshot),
+        current: None,
+    };
+    if !target.is_file() {
+        plan.added.push(change);
+        return Ok(());
+    }
+    let current = stat(target)?;
+    let same = current.size == entry.size && crc32_of(target)? == entry.crc32;
+    change.current = Some(current);
+    if same {
+        plan.unchanged.push(change);
+    } else {
+        plan.overwritten.push(change);
+    }
+    Ok(())
+}
+
+/// 递归收集文件夹中的文件，不跟随符号链接
+fn collect_files(dir: &Path, files: &mut HashSet<PathBuf>) -> Result<(), BackupFileError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            files.insert(entry.path());
+        }
+    }
+    Ok(())
+}
+
+fn stat(path: &Path) -> Result<FileStat, BackupFileError> {
+    let metadata = fs::metadata(path)?;
+    Ok(FileStat {
+        size: metadata.len(),
+        modified: metadata
+            .modified()
+            .ok()
+            .map(|t: SystemTime| format_time(chrono::DateTime::<Local>::from(t).naive_local())),
+    })
+}
+
+fn format_time(time: NaiveDateTime) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn crc32_of(path: &Path) -> Result<u32, BackupFileError> {
+    let mut file = File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(hasher.finalize());
+        }
+        hasher.update(&buffer[..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn unit(unit_type: SaveUnitType, mode: ApplyCleanMode, exclude: &[&str]) -> SaveUnit {
+        SaveUnit {
+            unit_type,
+            delete_before_apply: mode,
+            exclude_globs: exclude.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// 写入快照压缩包并读取其中央目录
+    fn snapshot(dir: &Path, files: &[(&str, &str)]) -> Vec<SnapshotEntry> {
+        let zip_path = dir.join("snapshot.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+        zip.add_directory("Save", SimpleFileOptions::default())
+            .unwrap();
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        read_snapshot_entries(&zip_path).unwrap()
+    }
+
+    fn write(root: &Path, files: &[(&str, &str)]) {
+        for (name, content) in files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    fn names(changes: &[RestoreFileChange], root: &Path) -> Vec<String> {
+        let mut names: Vec<_> = changes
+            .iter()
+            .map(|c| {
+                Path::new(&c.path)
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn classifies_files_of_a_folder_unit() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let entries = snapshot(
+            dir.path(),
+            &[
+                ("Save/same.sav", "same"),
+                ("Save/slots/changed.sav", "old"),
+                ("Save/new.sav", "new"),
+            ],
+        );
+        let root = dir.path().join("Save");
+        write(
+            &root,
+            &[
+                ("same.sav", "same"),
+                ("slots/changed.sav", "new"),
+                ("extra.sav", "extra"),
+                ("screenshots/shot.png", "png"),
+            ],
+        );
+
+        let full = unit(SaveUnitType::Folder, ApplyCleanMode::Full, &["screenshots"]);
+        let mut plan = RestorePlan::default();
+        assert!(plan_unit(&mut plan, 0, &full, &root, &entries).unwrap());
+        assert_eq!(names(&plan.added, &root), ["new.sav"]);
+        assert_eq!(names(&plan.overwritten, &root), ["slots/changed.sav"]);
+        assert_eq!(names(&plan.unchanged, &root), ["same.sav"]);
+        // 被排除的截图不会被删除
+        assert_eq!(names(&plan.deleted, &root), ["extra.sav"]);
+        assert_eq!(plan.overwritten[0].current.as_ref().unwrap().size, 3);
+
+        let keep = unit(SaveUnitType::Folder, ApplyCleanMode::None, &[]);
+        let mut plan = RestorePlan::default();
+        plan_unit(&mut plan, 0, &keep, &root, &entries).unwrap();
+        assert!(plan.deleted.is_empty());
+    }
+
+    #[test]
+    fn missing_target_counts_everything_as_added() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let entries = snapshot(
+            dir.path(),
+            &[
+                ("Save/a.sav", "a"),
+                ("Save/b/c.sav", "c"),
+                ("profile.dat", "p"),
+            ],
+        );
+        let root = dir.path().join("missing").join("Save");
+        let full = unit(SaveUnitType::Folder, ApplyCleanMode::Full, &[]);
+        let mut plan = RestorePlan::default();
+        assert!(plan_unit(&mut plan, 0, &full, &root, &entries).unwrap());
+        assert_eq!(names(&plan.added, &root), ["a.sav", "b/c.sav"]);
+        assert!(plan.deleted.is_empty() && plan.overwritten.is_empty());
+
+        // 文件单元按文件名匹配，不在快照中的单元返回 false
+        let file = unit(SaveUnitType::File, ApplyCleanMode::None, &[]);
+        let mut plan = RestorePlan::default();
+        assert!(
+            plan_unit(
+                &mut plan,
+                1,
+                &file,
+                &dir.path().join("profile.dat"),
+                &entries
+            )
+            .unwrap()
+        );
+        assert_eq!(plan.added[0].unit_index, 1);
+        assert!(!plan_unit(&mut plan, 2, &file, &dir.path().join("other.dat"), &entries).unwrap());
+    }
+}
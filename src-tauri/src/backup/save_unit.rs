@@ -1,17 +1,74 @@
-use serde::{Deserialize, Serialize};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Deserializer, Serialize};
 use specta::Type;
 use std::collections::HashMap;
+use std::path::Path;
 
 use crate::default_value;
 use crate::device::DeviceId;
+use crate::preclude::BackupFileError;
 
 /// A save unit should be a file or a folder
-#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
 pub enum SaveUnitType {
     File,
     Folder,
 }
 
+/// What to delete under the unit path before a snapshot is applied
+///
+/// With include/exclude globs on a folder unit, `Full` only deletes the files
+/// matched by the filter, and `TrackedOnly` never sees excluded files because
+/// they are not restored, so excluded files survive both modes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Type)]
+pub enum ApplyCleanMode {
+    /// Overwrite files in the snapshot, keep everything else. A file in the way of
+    /// a folder of the snapshot, or the reverse, fails the restore of the unit
+    #[default]
+    None,
+    /// Delete only the paths that exist in the snapshot before applying it,
+    /// files created after the snapshot are kept
+    TrackedOnly,
+    /// Delete the whole unit before applying the snapshot
+    Full,
+}
+
+/// How symbolic links and junctions inside a folder unit are backed up
+///
+/// The unit path itself is always followed, only links found while walking
+/// the folder are affected
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Type)]
+pub enum SymlinkMode {
+    /// Back up what the link points to. A link back to one of its own parent
+    /// folders is skipped, so link loops do not recurse forever
+    #[default]
+    Follow,
+    /// Leave links out of the snapshot and untouched on restore
+    Skip,
+    /// Store the link itself and recreate it on restore where the platform
+    /// allows creating links
+    Preserve,
+}
+
+/// Accept the boolean `delete_before_apply` of older configs,
+/// `true` used to delete the whole unit
+fn deserialize_clean_mode<'de, D>(deserializer: D) -> Result<ApplyCleanMode, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Legacy(bool),
+        Mode(ApplyCleanMode),
+    }
+    Ok(match Repr::deserialize(deserializer)? {
+        Repr::Legacy(true) => ApplyCleanMode::Full,
+        Repr::Legacy(false) => ApplyCleanMode::None,
+        Repr::Mode(mode) => mode,
+    })
+}
+
 /// A save unit declares one of the files/folders
 /// that should be backup for a game
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
@@ -19,8 +76,39 @@ pub struct SaveUnit {
     pub unit_type: SaveUnitType,
     #[serde(default)] // 如果反序列化时字段不存在，则使用默认值 (空 HashMap)
     pub paths: HashMap<DeviceId, String>, // 存储不同设备的路径
-    #[serde(default = "default_value::default_false")]
-    pub delete_before_apply: bool,
+    #[serde(default, deserialize_with = "deserialize_clean_mode")]
+    pub delete_before_apply: ApplyCleanMode,
+    /// Glob patterns relative to the unit root, only matching entries of a
+    /// folder unit are backed up. Empty means everything
+    #[serde(default)]
+    pub include_globs: Vec<String>,
+    /// Glob patterns relative to the unit root, matching entries of a folder
+    /// unit are neither backed up nor deleted on restore
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    /// How links inside a folder unit are handled
+    #[serde(default)]
+    pub symlinks: SymlinkMode,
+    /// Disabled units keep their definition but are left out of new snapshots
+    /// and are not touched on restore
+    #[serde(default = "default_value::default_true")]
+    pub enabled: bool,
+}
+
+/// An enabled folder unit without paths, the other fields take the values
+/// a config without them is read with
+impl Default for SaveUnit {
+    fn default() -> Self {
+        Self {
+            unit_type: SaveUnitType::Folder,
+            paths: HashMap::new(),
+            delete_before_apply: ApplyCleanMode::default(),
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
+            symlinks: SymlinkMode::default(),
+            enabled: true,
+        }
+    }
 }
 
 impl SaveUnit {
@@ -28,4 +116,68 @@ impl SaveUnit {
     pub fn get_path_for_device(&self, device_id: &DeviceId) -> Option<&String> {
         self.paths.get(device_id)
     }
+
+    /// Build the filter for the entries of a folder unit,
+    /// `None` when neither include nor exclude patterns are set
+    pub fn path_filter(&self) -> Result<Option<PathFilter>, BackupFileError> {
+        if self.include_globs.is_empty() && self.exclude_globs.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(PathFilter {
+            include: (!self.include_globs.is_empty())
+                .then(|| build_glob_set(&self.include_globs))
+                .transpose()?,
+            exclude: build_glob_set(&self.exclude_globs)?,
+        }))
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, BackupFileError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        // `*` should not cross directories, use `**` for that
+        let glob = GlobBuilder::new(pattern.trim_start_matches("./"))
+            .literal_separator(true)
+            .build()?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// Include/exclude filter of a folder unit, paths are relative to the unit root
+///
+/// A pattern matching a directory applies to everything inside it,
+/// so `screenshots` excludes the whole folder just like `screenshots/**`
+#[derive(Debug, Clone)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    /// Whether the entry or one of its parent directories is excluded,
+    /// excluded directories are skipped entirely
+    pub fn is_excluded(&self, relative: &Path) -> bool {
+        ancestors(relative).any(|p| self.exclude.is_match(p))
+    }
+
+    /// Whether the file belongs to the unit
+    pub fn includes_file(&self, relative: &Path) -> bool {
+        if self.is_excluded(relative) {
+            return false;
+        }
+        match &self.include {
+            Some(include) => ancestors(relative).any(|p| include.is_match(p)),
+            None => true,
+        }
+    }
+}
+
+/// `a/b/c` yields `a/b/c`, `a/b` and `a`, joined with `/` on every platform
+fn ancestors(relative: &Path) -> impl Iterator<Item = String> {
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    (1..=parts.len()).rev().map(move |n| parts[..n].join("/"))
 }
@@ -0,0 +1,206 @@
+//! Restoring one file of a snapshot without touching the rest of the save
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::info;
+
+use super::archive::{check_not_in_use, compress_single_file, extract_file_to};
+use super::game::prune_extra_backups;
+use super::snapshot_contents::{open_snapshot, read_manifest, zip_error};
+use crate::backup::{Game, SaveUnit, SaveUnitType};
+use crate::config::{Config, get_config};
+use crate::device::get_current_device_id;
+use crate::preclude::*;
+
+/// `entry_path` as stored in the zip, separated by `/` and without a leading slash
+fn normalize_entry(entry_path: &str) -> String {
+    entry_path
+        .replace('\\', "/")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+/// Where `entry` of a snapshot belongs when `unit` lives at `unit_path`
+fn target_path(unit: &SaveUnit, unit_path: &Path, entry: &str) -> PathBuf {
+    match unit.unit_type {
+        SaveUnitType::File => unit_path.to_path_buf(),
+        SaveUnitType::Folder => entry
+            .split('/')
+            .skip(1)
+            .fold(unit_path.to_path_buf(), |path, part| path.join(part)),
+    }
+}
+
+/// The path of `unit` on this device with its variables resolved
+fn resolve_unit_path(unit: &SaveUnit, config: &Config) -> Option<Result<PathBuf, BackupError>> {
+    let raw = unit.get_path_for_device(get_current_device_id())?;
+    Some(
+        crate::path_resolver::resolve_path(raw, None, config)
+            .map(|path| to_long_path(&path))
+            .map_err(|e| CompressError::from(BackupFileError::from(e)).into()),
+    )
+}
+
+/// Extract the file `entry_path` of the snapshot `date` back to where it belongs on this
+/// device, returns the restored path
+///
+/// The owning save unit is found through the snapshot manifest, or by the unit folder or
+/// file name for older snapshots. An existing file is only replaced when `overwrite` is set,
+/// and is first saved to `extra_backup` when `Settings::extra_backup_when_apply` is on.
+pub fn restore_single_file(
+    game: &Game,
+    date: &str,
+    entry_path: &str,
+    overwrite: bool,
+) -> Result<PathBuf, BackupError> {
+    let config = get_config()?;
+    let (mut archive, zip_path) = open_snapshot(game, date)?;
+
+    let entry = normalize_entry(entry_path);
+    let not_found = || BackupError::SnapshotEntryNotFound {
+        date: date.to_string(),
+        entry: entry.clone(),
+    };
+    let mut index = None;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(zip_error)?;
+        if !file.is_dir() && file.name().replace('\\', "/") == entry {
+            index = Some(i);
+            break;
+        }
+    }
+    let index = index.ok_or_else(not_found)?;
+    let top = entry.split('/').next().unwrap_or_default();
+
+    let manifest_unit = read_manifest(&mut archive, &zip_path)?
+        .and_then(|manifest| manifest.units.into_iter().find(|unit| unit.root == top));
+    let (unit_index, unit_path) = match manifest_unit {
+        Some(found) => {
+            let unit =
+                game.save_paths
+                    .get(found.index)
+                    .ok_or_else(|| BackupError::SaveUnitNotExist {
+                        name: game.name.clone(),
+                        index: found.index,
+                    })?;
+            let unit_path = resolve_unit_path(unit, &config)
+                .ok_or_else(|| BackupError::NoSavePathForDevice(game.name.clone()))??;
+            (found.index, unit_path)
+        }
+        None => {
+            let mut missing_path = false;
+            let mut found = None;
+            for (i, unit) in game.save_paths.iter().enumerate() {
+                let Some(unit_path) = resolve_unit_path(unit, &config) else {
+                    missing_path = true;
+                    continue;
+                };
+                let unit_path = unit_path?;
+                if unit_path.file_name().is_some_and(|name| name == top) {
+                    found = Some((i, unit_path));
+                    break;
+                }
+            }
+            match found {
+                Some(found) => found,
+                None if missing_path => {
+                    return Err(BackupError::NoSavePathForDevice(game.name.clone()));
+                }
+                None => return Err(BackupError::InvalidSavePath(top.to_string())),
+            }
+        }
+    };
+    let unit = &game.save_paths[unit_index];
+    if unit.unit_type == SaveUnitType::File && entry != top {
+        return Err(not_found());
+    }
+
+    let mut target = target_path(unit, &unit_path, &entry);
+    // A save linked elsewhere stays a link, what it points to is replaced
+    if fs::symlink_metadata(&target).is_ok_and(|m| m.is_symlink()) {
+        target = fs::canonicalize(&target)?;
+    }
+    let exists = target.exists();
+    if exists && !overwrite {
+        return Err(BackupError::RestoreTargetExists(from_long_path(&target)));
+    }
+    if exists {
+        check_not_in_use(&target).map_err(CompressError::from)?;
+        if config.settings.extra_backup_when_apply {
+            let extra_backup_path = game.backup_dir(&config).join("extra_backup");
+            fs::create_dir_all(&extra_backup_path)?;
+            let file_name = target
+                .file_name()
+                .ok_or(BackupError::NonePathError)?
+                .to_string_lossy();
+            let zip_name = chrono::Local::now()
+                .format("Overwrite_%Y-%m-%d_%H-%M-%S_")
+                .to_string()
+                + &file_name
+                + ".zip";
+            compress_single_file(
+                &target,
+                &entry,
+                unit_index,
+                &extra_backup_path.join(zip_name),
+            )
+            .map_err(CompressError::from)?;
+            prune_extra_backups(&extra_backup_path, config.settings.use_trash_on_delete)?;
+        }
+    }
+
+    extract_file_to(
+        &mut archive,
+        index,
+        &target,
+        config.settings.restore_timestamps,
+    )
+    .map_err(CompressError::from)?;
+    info!(target: "rgsm::backup", "Restored {} of {} {} to {:?}", entry, game.name, date, target);
+    Ok(from_long_path(&target))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::write::SimpleFileOptions;
+    use zip::{ZipArchive, ZipWriter};
+
+    use super::*;
+
+    #[test]
+    fn entries_land_inside_their_unit() {
+        let mut unit = SaveUnit::default();
+        let unit_path = Path::new("saves").join("Save");
+        let entry = normalize_entry("\\Save\\slot\\1.sav");
+        assert_eq!(entry, "Save/slot/1.sav");
+        assert_eq!(
+            target_path(&unit, &unit_path, &entry),
+            unit_path.join("slot").join("1.sav")
+        );
+        unit.unit_type = SaveUnitType::File;
+        assert_eq!(target_path(&unit, &unit_path, "Save"), unit_path);
+    }
+
+    #[test]
+    fn extracted_file_replaces_the_current_one() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("Save/slot/1.sav", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"old save").unwrap();
+        let mut archive = ZipArchive::new(zip.finish().unwrap()).unwrap();
+
+        let dir = temp_dir::TempDir::new().unwrap();
+        let target = dir.path().join("Save").join("slot").join("1.sav");
+        extract_file_to(&mut archive, 0, &target, false).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"old save");
+
+        fs::write(&target, b"new save").unwrap();
+        extract_file_to(&mut archive, 0, &target, false).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"old save");
+        // Nothing is left beside the restored file
+        assert_eq!(fs::read_dir(target.parent().unwrap()).unwrap().count(), 1);
+    }
+}
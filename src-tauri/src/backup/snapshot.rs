@@ -6,11 +6,13 @@ use crate::default_value;
 /// A backup is a zip file that contains
 /// all the file that the save unit has declared.
 /// The date is the unique indicator for a backup
-#[derive(Debug, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct Snapshot {
     pub date: String,
     pub describe: String,
-    pub path: String, // like "D:\\SaveManager\save_data\Game1\date.zip"
+    /// Relative to `backup_path`, like "Game1/date.zip".
+    /// Records written by older versions may still hold absolute paths
+    pub path: String,
     #[serde(default = "default_value::default_zero")]
     pub size: u64, // in bytes
 }
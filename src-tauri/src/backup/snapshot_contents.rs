@@ -0,0 +1,214 @@
+//! Listing what a snapshot holds without extracting it
+
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+use crate::backup::Game;
+use crate::config::get_config;
+use crate::preclude::*;
+
+/// Name of the manifest entry written at the end of every snapshot
+pub(super) const MANIFEST_NAME: &str = "__rgsm_manifest.json";
+
+/// Maps the top-level paths of a snapshot to the save units they were backed up from
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct SnapshotManifest {
+    pub units: Vec<ManifestUnit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct ManifestUnit {
+    /// Index of the unit in `Game::save_paths` when the snapshot was taken
+    pub index: usize,
+    /// The unit folder or file name at the top of the snapshot
+    pub root: String,
+}
+
+/// A file or folder stored in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotContentEntry {
+    /// Separated by `/` and starting with the unit folder or file name
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub compressed_size: u64,
+    /// Local time as stored in the zip, like "2024-01-01 12:00:00"
+    pub modified: Option<String>,
+}
+
+/// The entries backed up from one save unit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotUnitContents {
+    /// Index of the unit in `Game::save_paths` when the snapshot was taken
+    pub index: usize,
+    pub root: String,
+    pub entries: Vec<SnapshotContentEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotContents {
+    pub units: Vec<SnapshotUnitContents>,
+    /// Entries of no known unit, which is every entry of a snapshot taken before manifests
+    /// were written
+    pub ungrouped: Vec<SnapshotContentEntry>,
+}
+
+/// Sort `entries` into the units of `manifest`
+fn group_entries(
+    entries: Vec<SnapshotContentEntry>,
+    manifest: Option<SnapshotManifest>,
+) -> SnapshotContents {
+    let mut contents = SnapshotContents {
+        units: manifest
+            .map(|manifest| manifest.units)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|unit| SnapshotUnitContents {
+                index: unit.index,
+                root: unit.root,
+                entries: Vec::new(),
+            })
+            .collect(),
+        ungrouped: Vec::new(),
+    };
+    for entry in entries {
+        let top = entry.path.split('/').next().unwrap_or_default();
+        match contents.units.iter_mut().find(|unit| unit.root == top) {
+            Some(unit) => unit.entries.push(entry),
+            None => contents.ungrouped.push(entry),
+        }
+    }
+    contents
+}
+
+/// List the entries of the snapshot `date` of `game`
+///
+/// Only the central directory and the manifest are read, nothing is extracted
+pub fn list_snapshot_contents(game: &Game, date: &str) -> Result<SnapshotContents, BackupError> {
+    let (mut archive, zip_path) = open_snapshot(game, date)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(zip_error)?;
+        let path = file.name().replace('\\', "/");
+        if path == MANIFEST_NAME {
+            continue;
+        }
+        entries.push(SnapshotContentEntry {
+            path: path.trim_end_matches('/').to_string(),
+            is_dir: file.is_dir(),
+            size: file.size(),
+            compressed_size: file.compressed_size(),
+            modified: file.last_modified().map(|t| {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                    t.year(),
+                    t.month(),
+                    t.day(),
+                    t.hour(),
+                    t.minute(),
+                    t.second()
+                )
+            }),
+        });
+    }
+
+    let manifest = read_manifest(&mut archive, &zip_path)?;
+    Ok(group_entries(entries, manifest))
+}
+
+pub(super) fn zip_error(e: ZipError) -> BackupError {
+    CompressError::from(BackupFileError::from(e)).into()
+}
+
+/// Open the zip of the snapshot `date` of `game`, returns it with its path
+pub(super) fn open_snapshot(
+    game: &Game,
+    date: &str,
+) -> Result<(ZipArchive<File>, PathBuf), BackupError> {
+    let config = get_config()?;
+    let zip_path = game.backup_dir(&config).join(format!("{date}.zip"));
+    if !zip_path.exists() {
+        return Err(BackupError::BackupNotExist {
+            name: game.name.clone(),
+            date: date.to_string(),
+        });
+    }
+    let archive = ZipArchive::new(File::open(&zip_path)?).map_err(zip_error)?;
+    Ok((archive, zip_path))
+}
+
+/// The manifest of a snapshot, `None` for snapshots taken before manifests were written
+pub(super) fn read_manifest<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    zip_path: &Path,
+) -> Result<Option<SnapshotManifest>, BackupError> {
+    let mut file = match archive.by_name(MANIFEST_NAME) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(zip_error(e)),
+    };
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+    Ok(serde_json::from_str(&text)
+        .inspect_err(|e| {
+            warn!(target: "rgsm::backup", "Ignoring the unreadable manifest of {:?}: {:?}", zip_path, e);
+        })
+        .ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> SnapshotContentEntry {
+        SnapshotContentEntry {
+            path: path.to_string(),
+            is_dir: false,
+            size: 1,
+            compressed_size: 1,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn entries_are_grouped_by_unit() {
+        let entries = vec![
+            entry("Save"),
+            entry("Save/slot.sav"),
+            entry("profile.ini"),
+            entry("Saved/other.sav"),
+        ];
+        let manifest = SnapshotManifest {
+            units: vec![
+                ManifestUnit {
+                    index: 0,
+                    root: "Save".to_string(),
+                },
+                ManifestUnit {
+                    index: 2,
+                    root: "profile.ini".to_string(),
+                },
+            ],
+        };
+        let contents = group_entries(entries.clone(), Some(manifest));
+        let paths: Vec<Vec<_>> = contents
+            .units
+            .iter()
+            .map(|unit| unit.entries.iter().map(|e| e.path.as_str()).collect())
+            .collect();
+        assert_eq!(paths, [vec!["Save", "Save/slot.sav"], vec!["profile.ini"]]);
+        assert_eq!(contents.ungrouped, [entry("Saved/other.sav")]);
+
+        // Snapshots without a manifest are listed flat
+        let contents = group_entries(entries.clone(), None);
+        assert!(contents.units.is_empty());
+        assert_eq!(contents.ungrouped, entries);
+    }
+}
@@ -0,0 +1,200 @@
+//! Comparing two snapshots by their central directories
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use zip::ZipArchive;
+
+use super::snapshot_contents::{
+    MANIFEST_NAME, ManifestUnit, open_snapshot, read_manifest, zip_error,
+};
+use crate::backup::Game;
+use crate::preclude::*;
+
+/// A file present in at least one of the compared snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotFileDiff {
+    /// Separated by `/` and starting with the unit folder or file name
+    pub path: String,
+    /// `None` when the file is missing from the first snapshot
+    pub size_a: Option<u64>,
+    /// `None` when the file is missing from the second snapshot
+    pub size_b: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotFileChanges {
+    /// Only in the second snapshot
+    pub added: Vec<SnapshotFileDiff>,
+    /// Only in the first snapshot
+    pub removed: Vec<SnapshotFileDiff>,
+    /// In both, with a different size or CRC
+    pub modified: Vec<SnapshotFileDiff>,
+    pub unchanged: Vec<SnapshotFileDiff>,
+}
+
+/// The changes under one save unit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotUnitDiff {
+    /// Index of the unit in `Game::save_paths`, taken from the second snapshot when both
+    /// know the unit
+    pub index: usize,
+    pub root: String,
+    pub changes: SnapshotFileChanges,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotDiff {
+    pub units: Vec<SnapshotUnitDiff>,
+    /// Files of no unit known to either manifest
+    pub ungrouped: SnapshotFileChanges,
+}
+
+/// Size and CRC of a file entry
+type EntryInfo = (u64, u32);
+
+/// The file entries of a snapshot keyed by path, folders and the manifest are left out
+fn file_entries(
+    archive: &mut ZipArchive<File>,
+) -> Result<BTreeMap<String, EntryInfo>, BackupError> {
+    let mut entries = BTreeMap::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i).map_err(zip_error)?;
+        let path = file.name().replace('\\', "/");
+        if file.is_dir() || path == MANIFEST_NAME {
+            continue;
+        }
+        entries.insert(path, (file.size(), file.crc32()));
+    }
+    Ok(entries)
+}
+
+/// Sort the files of both snapshots into changes, grouped by the units of both manifests
+fn diff_entries(
+    a: &BTreeMap<String, EntryInfo>,
+    b: &BTreeMap<String, EntryInfo>,
+    units_a: Vec<ManifestUnit>,
+    units_b: Vec<ManifestUnit>,
+) -> SnapshotDiff {
+    let mut diff = SnapshotDiff::default();
+    // A unit of only one snapshot still gets a group, its files are all added or removed
+    for unit in units_b.into_iter().chain(units_a) {
+        if !diff.units.iter().any(|u| u.root == unit.root) {
+            diff.units.push(SnapshotUnitDiff {
+                index: unit.index,
+                root: unit.root,
+                changes: SnapshotFileChanges::default(),
+            });
+        }
+    }
+    diff.units.sort_by_key(|unit| unit.index);
+
+    let paths: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    for path in paths {
+        let old = a.get(path);
+        let new = b.get(path);
+        let top = path.split('/').next().unwrap_or_default();
+        let changes = match diff.units.iter_mut().find(|unit| unit.root == top) {
+            Some(unit) => &mut unit.changes,
+            None => &mut diff.ungrouped,
+        };
+        let file = SnapshotFileDiff {
+            path: path.clone(),
+            size_a: old.map(|(size, _)| *size),
+            size_b: new.map(|(size, _)| *size),
+        };
+        match (old, new) {
+            (None, _) => changes.added.push(file),
+            (_, None) => changes.removed.push(file),
+            (Some(old), Some(new)) if old != new => changes.modified.push(file),
+            _ => changes.unchanged.push(file),
+        }
+    }
+    diff
+}
+
+/// Compare the snapshots `date_a` and `date_b` of `game` without extracting them
+///
+/// Files are matched by path and compared by size and CRC, both read from the central
+/// directory.
+pub fn diff_snapshots(
+    game: &Game,
+    date_a: &str,
+    date_b: &str,
+) -> Result<SnapshotDiff, BackupError> {
+    let (mut archive_a, zip_path_a) = open_snapshot(game, date_a)?;
+    let (mut archive_b, zip_path_b) = open_snapshot(game, date_b)?;
+    let entries_a = file_entries(&mut archive_a)?;
+    let entries_b = file_entries(&mut archive_b)?;
+    let units_a = read_manifest(&mut archive_a, &zip_path_a)?
+        .unwrap_or_default()
+        .units;
+    let units_b = read_manifest(&mut archive_b, &zip_path_b)?
+        .unwrap_or_default()
+        .units;
+    Ok(diff_entries(&entries_a, &entries_b, units_a, units_b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(files: &[(&str, u64, u32)]) -> BTreeMap<String, EntryInfo> {
+        files
+            .iter()
+            .map(|(path, size, crc)| (path.to_string(), (*size, *crc)))
+            .collect()
+    }
+
+    fn unit(index: usize, root: &str) -> ManifestUnit {
+        ManifestUnit {
+            index,
+            root: root.to_string(),
+        }
+    }
+
+    fn paths(files: &[SnapshotFileDiff]) -> Vec<&str> {
+        files.iter().map(|f| f.path.as_str()).collect()
+    }
+
+    #[test]
+    fn changes_are_grouped_by_the_units_of_both_snapshots() {
+        let a = entries(&[
+            ("Save/alive.sav", 10, 1),
+            ("Save/same.sav", 5, 2),
+            ("Old/config.ini", 3, 3),
+            ("loose.txt", 1, 4),
+        ]);
+        let b = entries(&[
+            ("Save/alive.sav", 10, 9),
+            ("Save/same.sav", 5, 2),
+            ("Save/new.sav", 7, 5),
+            ("New/profile.dat", 2, 6),
+        ]);
+        let diff = diff_entries(
+            &a,
+            &b,
+            vec![unit(0, "Save"), unit(1, "Old")],
+            vec![unit(0, "Save"), unit(1, "New")],
+        );
+
+        let roots: Vec<_> = diff.units.iter().map(|u| u.root.as_str()).collect();
+        assert_eq!(roots, ["Save", "New", "Old"]);
+        let save = &diff.units[0].changes;
+        assert_eq!(paths(&save.added), ["Save/new.sav"]);
+        assert_eq!(paths(&save.modified), ["Save/alive.sav"]);
+        assert_eq!(paths(&save.unchanged), ["Save/same.sav"]);
+        assert_eq!(paths(&diff.units[1].changes.added), ["New/profile.dat"]);
+        assert_eq!(paths(&diff.units[2].changes.removed), ["Old/config.ini"]);
+        assert_eq!(
+            diff.ungrouped.removed,
+            [SnapshotFileDiff {
+                path: "loose.txt".to_string(),
+                size_a: Some(1),
+                size_b: None,
+            }]
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Undoable game deletion
+//!
+//! Deleting a game moves its backup folder to `<backup_path>/.deleted/<game>-<timestamp>`
+//! and stashes what is needed to put it back in a `game.json` inside. The deletion only
+//! becomes final, locally and on the cloud, once `purge_deleted_games` finds the entry older
+//! than `Settings::deleted_game_retention_days`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDateTime;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri_specta::Event;
+
+use crate::backup::{Game, join_backup_dir, remove_backup_path, sanitize_windows_path_component};
+use crate::cloud_sync::{CloudSettings, cloud_backup_dir, upload_config};
+use crate::config::{
+    BackupSchedule, Config, QuickActionOverride, contains_favorite_game, get_config, modify_config,
+    prune_favorite_game, push_favorite_game,
+};
+use crate::preclude::*;
+
+const DELETED_DIR: &str = ".deleted";
+const STASH_FILE: &str = "game.json";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// Emitted when a game was deleted, the frontend offers to undo it
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct GameSoftDeleted {
+    pub game_name: String,
+    /// Days the deletion can be undone for
+    pub retention_days: u32,
+}
+
+/// Everything `undo_delete_game` needs to put a game back
+#[derive(Debug, Serialize, Deserialize)]
+struct DeletedGame {
+    game: Game,
+    /// In the snapshot date format
+    deleted_at: String,
+    #[serde(default)]
+    quick_action_override: Option<QuickActionOverride>,
+    #[serde(default)]
+    schedules: Vec<BackupSchedule>,
+    /// Whether the game was in the favorites tree, undoing puts it back at the top level
+    #[serde(default)]
+    favorited: bool,
+    /// Games that were never backed up have no folder, their entry only holds the stash
+    had_backups: bool,
+}
+
+fn deleted_root(config: &Config) -> PathBuf {
+    config.backup_root().join(DELETED_DIR)
+}
+
+/// A folder name under `.deleted` that is not taken yet
+fn entry_dir(deleted_root: &Path, name: &str, deleted_at: &str) -> PathBuf {
+    let base = format!("{}-{deleted_at}", sanitize_windows_path_component(name));
+    let mut dir = deleted_root.join(&base);
+    let mut n = 1;
+    while dir.exists() {
+        dir = deleted_root.join(format!("{base}_{n}"));
+        n += 1;
+    }
+    dir
+}
+
+/// Deleted games with readable stashes, entries without one are left alone
+fn list_deleted(deleted_root: &Path) -> Result<Vec<(PathBuf, DeletedGame)>, BackupError> {
+    if !deleted_root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(deleted_root)? {
+        let dir = entry?.path();
+        let stash = fs::read(dir.join(STASH_FILE))
+            .map_err(BackupError::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<DeletedGame>(&bytes)?));
+        match stash {
+            Ok(stash) => entries.push((dir, stash)),
+            Err(e) => warn!(target: "rgsm::backup::soft_delete", "Skipping {:?}: {:?}", dir, e),
+        }
+    }
+    Ok(entries)
+}
+
+fn is_expired(deleted_at: &str, now: NaiveDateTime, retention_days: u32) -> bool {
+    NaiveDateTime::parse_from_str(deleted_at, TIMESTAMP_FORMAT)
+        .is_ok_and(|deleted_at| now - deleted_at >= chrono::Duration::days(retention_days.into()))
+}
+
+/// Move the backup folder of `game` to `.deleted` and remove the game from the config
+pub async fn soft_delete_game(game: &Game) -> Result<GameSoftDeleted, BackupError> {
+    let config = get_config()?;
+    let deleted_at = chrono::Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let deleted_root = deleted_root(&config);
+    let entry = entry_dir(&deleted_root, &game.name, &deleted_at);
+    let backup_dir = game.backup_dir(&config);
+
+    let stash = DeletedGame {
+        game: game.clone(),
+        deleted_at,
+        quick_action_override: config.quick_action.overrides.get(&game.name).cloned(),
+        schedules: config
+            .quick_action
+            .schedules
+            .iter()
+            .filter(|s| s.game_name == game.name)
+            .cloned()
+            .collect(),
+        favorited: contains_favorite_game(&config.favorites, &game.name),
+        had_backups: backup_dir.exists(),
+    };
+    fs::create_dir_all(&deleted_root)?;
+    if stash.had_backups {
+        fs::rename(&backup_dir, &entry)?;
+    } else {
+        fs::create_dir_all(&entry)?;
+    }
+    let result = async {
+        write_atomic(
+            &entry.join(STASH_FILE),
+            serde_json::to_string_pretty(&stash)?.as_bytes(),
+        )?;
+        modify_config(|config| {
+            config.games.retain(|g| g.name != game.name);
+            config.quick_action.overrides.remove(&game.name);
+            config
+                .quick_action
+                .schedules
+                .retain(|s| s.game_name != game.name);
+            prune_favorite_game(&mut config.favorites, &game.name);
+            Ok::<_, BackupError>(config.settings.deleted_game_retention_days)
+        })
+        .await
+    }
+    .await;
+    match result {
+        Ok(retention_days) => {
+            info!(target: "rgsm::backup::soft_delete", "Moved {} to {:?}", game.name, entry);
+            Ok(GameSoftDeleted {
+                game_name: game.name.clone(),
+                retention_days,
+            })
+        }
+        Err(e) => {
+            // The config is untouched, put the folder back so the game stays as it was
+            let _ = fs::remove_file(entry.join(STASH_FILE));
+            let restored = if stash.had_backups {
+                fs::rename(&entry, &backup_dir)
+            } else {
+                fs::remove_dir(&entry)
+            };
+            if let Err(restore_err) = restored {
+                warn!(target: "rgsm::backup::soft_delete", "Cannot move {:?} back: {:?}", entry, restore_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Put back the most recently deleted game named `game_name`
+pub async fn undo_delete_game(game_name: &str) -> Result<Game, BackupError> {
+    let config = get_config()?;
+    if config.games.iter().any(|g| g.name == game_name) {
+        return Err(BackupError::GameNameConflict(game_name.to_string()));
+    }
+    let (entry, stash) = list_deleted(&deleted_root(&config))?
+        .into_iter()
+        .filter(|(_, stash)| stash.game.name == game_name)
+        .max_by(|(_, a), (_, b)| a.deleted_at.cmp(&b.deleted_at))
+        .ok_or_else(|| BackupError::GameNotExist(game_name.to_string()))?;
+    let backup_dir = join_backup_dir(&config, game_name);
+    if stash.had_backups && backup_dir.exists() {
+        return Err(BackupError::BackupRootConflict(backup_dir));
+    }
+
+    // Config first, so a failed write leaves the deleted entry where it is
+    modify_config(|config| {
+        if config.games.iter().any(|g| g.name == game_name) {
+            return Err(BackupError::GameNameConflict(game_name.to_string()));
+        }
+        config.games.push(stash.game.clone());
+        if let Some(game_override) = &stash.quick_action_override {
+            config
+                .quick_action
+                .overrides
+                .insert(game_name.to_string(), game_override.clone());
+        }
+        config
+            .quick_action
+            .schedules
+            .extend(stash.schedules.clone());
+        if stash.favorited && !contains_favorite_game(&config.favorites, game_name) {
+            push_favorite_game(&mut config.favorites, game_name);
+        }
+        Ok(())
+    })
+    .await?;
+    fs::remove_file(entry.join(STASH_FILE))?;
+    if stash.had_backups {
+        fs::rename(&entry, &backup_dir)?;
+    } else {
+        fs::remove_dir_all(&entry)?;
+    }
+    info!(target: "rgsm::backup::soft_delete", "Restored deleted game {game_name}");
+    Ok(stash.game)
+}
+
+/// Finalize deletions older than the retention period, returns the names of the purged games
+///
+/// Cloud copies are removed first, unless a game with the same name was added since. An
+/// entry whose cloud copies cannot be removed is kept and retried on the next purge, so the
+/// local stash is never gone while the cloud still holds the game. Local folders go through
+/// `remove_backup_path`, so they end up in the trash when enabled.
+pub async fn purge_deleted_games() -> Result<Vec<String>, BackupError> {
+    let config = get_config()?;
+    let now = chrono::Local::now().naive_local();
+    let retention_days = config.settings.deleted_game_retention_days;
+    let cloud = &config.settings.cloud_settings;
+    let mut purged = Vec::new();
+    for (entry, stash) in list_deleted(&deleted_root(&config))? {
+        if !is_expired(&stash.deleted_at, now, retention_days) {
+            continue;
+        }
+        let name = stash.game.name;
+        if cloud.always_sync && !config.games.iter().any(|g| g.name == name) {
+            if let Err(e) = purge_cloud_copies(cloud, &name).await {
+                warn!(target: "rgsm::backup::soft_delete", "Failed to purge cloud copies of {name}, keeping {:?}: {:?}", entry, e);
+                continue;
+            }
+        }
+        if let Err(e) = remove_backup_path(&entry, config.settings.use_trash_on_delete) {
+            warn!(target: "rgsm::backup::soft_delete", "Failed to purge {:?}: {:?}", entry, e);
+            continue;
+        }
+        info!(target: "rgsm::backup::soft_delete", "Purged deleted game {name}");
+        purged.push(name);
+    }
+    if !purged.is_empty() && cloud.always_sync {
+        for backend in cloud.enabled_backends() {
+            let result = match backend.get_op() {
+                Ok(op) => upload_config(&op).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!(target: "rgsm::backup::soft_delete", "Failed to upload config to {}: {:?}", backend.name, e);
+            }
+        }
+    }
+    Ok(purged)
+}
+
+/// Remove the folder of a purged game from every enabled backend
+async fn purge_cloud_copies(cloud: &CloudSettings, name: &str) -> Result<(), BackendError> {
+    for backend in cloud.enabled_backends() {
+        backend
+            .get_op()?
+            .remove_all(&format!("{}/", cloud_backup_dir(name)))
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deletions_expire_after_the_retention_period() {
+        let now = NaiveDateTime::parse_from_str("2024-01-08_12-00-00", TIMESTAMP_FORMAT).unwrap();
+        assert!(is_expired("2024-01-01_12-00-00", now, 7));
+        assert!(!is_expired("2024-01-01_12-00-01", now, 7));
+        assert!(is_expired("2024-01-08_12-00-00", now, 0));
+        // Unreadable dates never expire
+        assert!(!is_expired("not a date", now, 0));
+    }
+
+    #[test]
+    fn entry_folders_never_collide() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let first = entry_dir(dir.path(), "A:B", "2024-01-01_00-00-00");
+        assert_eq!(first, dir.path().join("A_B-2024-01-01_00-00-00"));
+        fs::create_dir_all(&first).unwrap();
+        assert_eq!(
+            entry_dir(dir.path(), "A:B", "2024-01-01_00-00-00"),
+            dir.path().join("A_B-2024-01-01_00-00-00_1")
+        );
+    }
+}
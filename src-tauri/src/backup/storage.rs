@@ -0,0 +1,601 @@
+use log::info;
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::backup::{GameSnapshots, sanitize_windows_path_component};
+use crate::cloud_sync::{
+    ENCRYPTED_SUFFIX, SessionKeys, cloud_backup_dir, cloud_backup_file, read_snapshot,
+    remote_snapshot_len, upload_game_snapshots,
+};
+use crate::config::get_config;
+use crate::preclude::*;
+
+/// How many of the largest snapshots are listed in `StorageReport::largest`
+const LARGEST_SNAPSHOTS: usize = 10;
+
+/// Size statistics over a set of snapshots, all in bytes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct SizeStats {
+    pub count: u32,
+    pub total: u64,
+    pub min: u64,
+    pub max: u64,
+    pub avg: u64,
+}
+
+impl SizeStats {
+    fn from_sizes(sizes: impl IntoIterator<Item = u64>) -> Self {
+        let mut stats = Self::default();
+        for size in sizes {
+            stats.min = if stats.count == 0 {
+                size
+            } else {
+                stats.min.min(size)
+            };
+            stats.max = stats.max.max(size);
+            stats.total += size;
+            stats.count += 1;
+        }
+        if stats.count > 0 {
+            stats.avg = stats.total / u64::from(stats.count);
+        }
+        stats
+    }
+}
+
+/// One snapshot as a point on a size chart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SnapshotSize {
+    pub game: String,
+    pub date: String,
+    pub size: u64,
+}
+
+/// A zip in a game's backup folder that no `Backups.json` entry refers to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct OrphanedSnapshot {
+    /// Relative to `backup_path`, like "Game1/date.zip"
+    pub path: String,
+    pub size: u64,
+}
+
+/// Storage used by a single game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct GameStorage {
+    pub name: String,
+    /// Snapshots listed in `Backups.json` that still exist on disk
+    pub snapshots: SizeStats,
+    /// Snapshot sizes in date order, for charting growth over time
+    pub timeline: Vec<SnapshotSize>,
+    /// Size of the `extra_backup` folder written before each restore
+    pub extra_backup_bytes: u64,
+    pub orphaned: Vec<OrphanedSnapshot>,
+    /// Dates listed in `Backups.json` whose zip is gone
+    pub missing: Vec<String>,
+}
+
+/// Disk usage of the whole backup folder, returned by `get_storage_report`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct StorageReport {
+    pub games: Vec<GameStorage>,
+    /// Statistics over the snapshots of all games
+    pub snapshots: SizeStats,
+    pub extra_backup_bytes: u64,
+    /// Bytes taken by orphaned zips, which a cleanup could free
+    pub reclaimable_bytes: u64,
+    /// The largest snapshots across all games, largest first
+    pub largest: Vec<SnapshotSize>,
+}
+
+/// Build a storage report for every configured game
+pub fn get_storage_report() -> Result<StorageReport, BackupError> {
+    let config = get_config()?;
+    let names: Vec<_> = config.games.iter().map(|g| g.name.clone()).collect();
+    storage_report(&config.backup_root(), &names)
+}
+
+/// Aggregate the `Backups.json` of `names` under `root` and cross-check them
+/// against the zips actually on disk
+///
+/// Recorded sizes are used for the statistics; records from older versions
+/// without a size fall back to the file size on disk.
+fn storage_report(root: &Path, names: &[String]) -> Result<StorageReport, BackupError> {
+    let games = names
+        .iter()
+        .map(|name| game_storage(root, name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let timeline = || games.iter().flat_map(|g| g.timeline.iter());
+    let mut largest: Vec<_> = timeline().cloned().collect();
+    largest.sort_by_key(|s| std::cmp::Reverse(s.size));
+    largest.truncate(LARGEST_SNAPSHOTS);
+
+    Ok(StorageReport {
+        snapshots: SizeStats::from_sizes(timeline().map(|s| s.size)),
+        extra_backup_bytes: games.iter().map(|g| g.extra_backup_bytes).sum(),
+        reclaimable_bytes: games
+            .iter()
+            .flat_map(|g| g.orphaned.iter())
+            .map(|o| o.size)
+            .sum(),
+        largest,
+        games,
+    })
+}
+
+fn game_storage(root: &Path, name: &str) -> Result<GameStorage, BackupError> {
+    let folder = sanitize_windows_path_component(name);
+    let dir = root.join(&folder);
+    let mut backups = read_records(&dir, name)?.backups;
+    backups.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let zips = list_zips(&dir)?;
+    let referenced: HashSet<_> = backups.iter().map(|s| format!("{}.zip", s.date)).collect();
+
+    let mut timeline = Vec::new();
+    let mut missing = Vec::new();
+    for snapshot in backups {
+        let file_name = format!("{}.zip", snapshot.date);
+        match zips.iter().find(|(n, _)| *n == file_name) {
+            Some((_, disk_size)) => timeline.push(SnapshotSize {
+                game: name.to_string(),
+                size: if snapshot.size > 0 {
+                    snapshot.size
+                } else {
+                    *disk_size
+                },
+                date: snapshot.date,
+            }),
+            None => missing.push(snapshot.date),
+        }
+    }
+    let orphaned = zips
+        .into_iter()
+        .filter(|(n, _)| !referenced.contains(n))
+        .map(|(n, size)| OrphanedSnapshot {
+            path: format!("{folder}/{n}"),
+            size,
+        })
+        .collect();
+
+    Ok(GameStorage {
+        name: name.to_string(),
+        snapshots: SizeStats::from_sizes(timeline.iter().map(|s| s.size)),
+        timeline,
+        extra_backup_bytes: dir_size(&dir.join("extra_backup"))?,
+        orphaned,
+        missing,
+    })
+}
+
+/// Read `Backups.json` in `dir`, games that were never backed up have none
+fn read_records(dir: &Path, name: &str) -> Result<GameSnapshots, BackupError> {
+    match read_json_or_backup::<GameSnapshots, BackupError>(&dir.join("Backups.json")) {
+        Ok((info, _)) => Ok(info),
+        Err(BackupError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+            Ok(GameSnapshots::new(name, Vec::new()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Zip files directly inside `dir` with their sizes, empty if `dir` does not exist
+pub(super) fn list_zips(dir: &Path) -> io::Result<Vec<(String, u64)>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut zips = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let is_zip = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        let meta = entry.metadata()?;
+        if is_zip && meta.is_file() {
+            zips.push((entry.file_name().to_string_lossy().into_owned(), meta.len()));
+        }
+    }
+    zips.sort();
+    Ok(zips)
+}
+
+/// Total size of the files under `dir`, 0 if it does not exist
+fn dir_size(dir: &Path) -> io::Result<u64> {
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    let mut total = 0;
+    let mut stack: Vec<PathBuf> = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                stack.push(entry.path());
+            } else if file_type.is_file() {
+                total += entry.metadata()?.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// One change made, or proposed on a dry run, by `cleanup_backup_storage`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CleanupAction {
+    /// A zip no record refers to, moved into the game's `orphaned` folder
+    QuarantineOrphan {
+        game: String,
+        /// Relative to `backup_path`, like "Game1/date.zip"
+        path: String,
+        size: u64,
+    },
+    /// A record whose zip is gone locally but still on the cloud, downloaded again
+    RecoverFromCloud { game: String, date: String },
+    /// A record whose zip is gone, removed from `Backups.json`
+    DropDangling { game: String, date: String },
+    /// A record written before sizes were tracked, filled in from the zip
+    FixSize {
+        game: String,
+        date: String,
+        size: u64,
+    },
+    /// A cloud object no record refers to, deleted from the cloud
+    DeleteRemoteOrphan { game: String, path: String },
+}
+
+/// Returned by `cleanup_backup_storage`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct CleanupReport {
+    /// Whether the actions were only proposed
+    pub dry_run: bool,
+    pub actions: Vec<CleanupAction>,
+    /// Bytes of orphaned zips moved out of the snapshot folders
+    pub quarantined_bytes: u64,
+}
+
+/// The local part of a cleanup for a single game
+struct GameCleanup {
+    actions: Vec<CleanupAction>,
+    /// `Backups.json` after the cleanup
+    records: GameSnapshots,
+    records_changed: bool,
+}
+
+/// Reconcile every configured game's backup folder with its `Backups.json`
+///
+/// Orphaned zips are moved to `<game>/orphaned/` instead of being deleted, records
+/// whose zip is gone are dropped, and missing sizes are filled in. With
+/// `always_sync` on, missing zips are downloaded from the primary backend when possible,
+/// the fixed records are uploaded to it and its zips no record refers to are deleted.
+/// On a dry run nothing is touched and the report lists what would be done. `keys` decrypt
+/// the recovered zips that were uploaded encrypted.
+pub async fn cleanup_backup_storage(
+    dry_run: bool,
+    keys: Option<&SessionKeys>,
+) -> Result<CleanupReport, BackupError> {
+    let config = get_config()?;
+    let cloud = &config.settings.cloud_settings;
+    let op = if cloud.always_sync {
+        Some(cloud.find_backend(None)?.get_op()?)
+    } else {
+        None
+    };
+    let mut report = CleanupReport {
+        dry_run,
+        ..Default::default()
+    };
+    for game in &config.games {
+        let dir = game.backup_dir(&config);
+        let records = read_records(&dir, &game.name)?;
+        let zips = list_zips(&dir)?;
+
+        // A zip missing locally is downloaded again rather than dropping its record
+        let mut in_cloud = HashMap::new();
+        if let Some(op) = &op {
+            for snapshot in &records.backups {
+                let file_name = format!("{}.zip", snapshot.date);
+                if zips.iter().any(|(n, _)| *n == file_name) {
+                    continue;
+                }
+                let remote = cloud_backup_file(&game.name, &file_name);
+                if let Some(len) = remote_snapshot_len(op, &remote).await? {
+                    in_cloud.insert(snapshot.date.clone(), len);
+                }
+            }
+        }
+
+        let cleanup = plan_game_cleanup(&game.name, records, &zips, &in_cloud);
+        if !dry_run {
+            for action in &cleanup.actions {
+                match action {
+                    CleanupAction::QuarantineOrphan { path, .. } => {
+                        quarantine(&config.backup_root(), path)?
+                    }
+                    CleanupAction::RecoverFromCloud { date, .. } => {
+                        let file_name = format!("{date}.zip");
+                        let remote = cloud_backup_file(&game.name, &file_name);
+                        // `in_cloud` is only filled when there is an operator
+                        if let Some(op) = &op {
+                            let data = read_snapshot(op, cloud, keys, &remote).await?;
+                            fs::write(dir.join(&file_name), data)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if cleanup.records_changed {
+                write_atomic(
+                    &dir.join("Backups.json"),
+                    serde_json::to_string_pretty(&cleanup.records)?.as_bytes(),
+                )?;
+            }
+        }
+        report.quarantined_bytes += cleanup
+            .actions
+            .iter()
+            .map(|a| match a {
+                CleanupAction::QuarantineOrphan { size, .. } => *size,
+                _ => 0,
+            })
+            .sum::<u64>();
+        report.actions.extend(cleanup.actions);
+
+        if let Some(op) = &op {
+            let remote_orphans = list_remote_orphans(op, &cleanup.records).await?;
+            if !dry_run {
+                if cleanup.records_changed {
+                    upload_game_snapshots(op, cleanup.records).await?;
+                }
+                for path in &remote_orphans {
+                    op.delete(path).await?;
+                }
+            }
+            report
+                .actions
+                .extend(
+                    remote_orphans
+                        .into_iter()
+                        .map(|path| CleanupAction::DeleteRemoteOrphan {
+                            game: game.name.clone(),
+                            path,
+                        }),
+                );
+        }
+    }
+    info!(target:"rgsm::backup::storage", "Storage cleanup {} {} actions", if dry_run { "proposed" } else { "applied" }, report.actions.len());
+    Ok(report)
+}
+
+/// Decide what to do with the records and zips of one game, `in_cloud` maps dates
+/// missing locally to their size on the cloud
+fn plan_game_cleanup(
+    name: &str,
+    mut records: GameSnapshots,
+    zips: &[(String, u64)],
+    in_cloud: &HashMap<String, u64>,
+) -> GameCleanup {
+    let mut actions = Vec::new();
+    let mut records_changed = false;
+    let folder = sanitize_windows_path_component(name);
+
+    let referenced: HashSet<_> = records
+        .backups
+        .iter()
+        .map(|s| format!("{}.zip", s.date))
+        .collect();
+    for (file_name, size) in zips.iter().filter(|(n, _)| !referenced.contains(n)) {
+        actions.push(CleanupAction::QuarantineOrphan {
+            game: name.to_string(),
+            path: format!("{folder}/{file_name}"),
+            size: *size,
+        });
+    }
+
+    records.backups.retain_mut(|snapshot| {
+        let file_name = format!("{}.zip", snapshot.date);
+        let size = match zips.iter().find(|(n, _)| *n == file_name) {
+            Some((_, size)) => *size,
+            None => match in_cloud.get(&snapshot.date) {
+                Some(size) => {
+                    actions.push(CleanupAction::RecoverFromCloud {
+                        game: name.to_string(),
+                        date: snapshot.date.clone(),
+                    });
+                    *size
+                }
+                None => {
+                    actions.push(CleanupAction::DropDangling {
+                        game: name.to_string(),
+                        date: snapshot.date.clone(),
+                    });
+                    records_changed = true;
+                    return false;
+                }
+            },
+        };
+        if snapshot.size == 0 {
+            snapshot.size = size;
+            records_changed = true;
+            actions.push(CleanupAction::FixSize {
+                game: name.to_string(),
+                date: snapshot.date.clone(),
+                size,
+            });
+        }
+        true
+    });
+
+    GameCleanup {
+        actions,
+        records,
+        records_changed,
+    }
+}
+
+/// Move `<backup_path>/<path>` into the `orphaned` folder next to it
+fn quarantine(root: &Path, path: &str) -> io::Result<()> {
+    let from = root.join(path);
+    let dir = from
+        .parent()
+        .map(|p| p.join("orphaned"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, path.to_string()))?;
+    fs::create_dir_all(&dir)?;
+    fs::rename(&from, dir.join(from.file_name().unwrap_or_default()))
+}
+
+/// Snapshot zips in the game's cloud folder that `records` does not list
+async fn list_remote_orphans(
+    op: &Operator,
+    records: &GameSnapshots,
+) -> Result<Vec<String>, BackupError> {
+    let prefix = format!("{}/", cloud_backup_dir(&records.name));
+    if !op.exists(&prefix).await? {
+        return Ok(Vec::new());
+    }
+    let dates: HashSet<_> = records.backups.iter().map(|s| s.date.as_str()).collect();
+    let mut orphans = Vec::new();
+    for entry in op.list(&prefix).await? {
+        if entry.metadata().is_dir() {
+            continue;
+        }
+        let name = entry.name();
+        let zip_name = name.strip_suffix(ENCRYPTED_SUFFIX).unwrap_or(name);
+        if zip_name
+            .strip_suffix(".zip")
+            .is_some_and(|date| !dates.contains(date))
+        {
+            orphans.push(entry.path().to_string());
+        }
+    }
+    Ok(orphans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::Snapshot;
+
+    fn snapshot(date: &str, size: u64) -> Snapshot {
+        Snapshot {
+            date: date.to_string(),
+            describe: String::new(),
+            path: format!("Game/{date}.zip"),
+            size,
+        }
+    }
+
+    #[test]
+    fn reports_orphaned_and_missing_snapshots() {
+        let root = temp_dir::TempDir::new().unwrap();
+        let game = root.path().join("Game");
+        fs::create_dir_all(game.join("extra_backup")).unwrap();
+        let info = GameSnapshots::new(
+            "Game",
+            vec![snapshot("b", 30), snapshot("a", 0), snapshot("gone", 5)],
+        );
+        fs::write(
+            game.join("Backups.json"),
+            serde_json::to_vec(&info).unwrap(),
+        )
+        .unwrap();
+        fs::write(game.join("a.zip"), [0; 10]).unwrap();
+        fs::write(game.join("b.zip"), [0; 30]).unwrap();
+        fs::write(game.join("stray.zip"), [0; 7]).unwrap();
+        fs::write(game.join("extra_backup").join("x.zip"), [0; 4]).unwrap();
+
+        let names = ["Game".to_string(), "Never backed up".to_string()];
+        let report = storage_report(root.path(), &names).unwrap();
+        let game = &report.games[0];
+        let dates: Vec<_> = game.timeline.iter().map(|s| s.date.as_str()).collect();
+        assert_eq!(dates, ["a", "b"]);
+        assert_eq!(
+            game.snapshots,
+            SizeStats {
+                count: 2,
+                total: 40,
+                min: 10,
+                max: 30,
+                avg: 20
+            }
+        );
+        assert_eq!(game.missing, ["gone"]);
+        assert_eq!(
+            game.orphaned,
+            [OrphanedSnapshot {
+                path: "Game/stray.zip".to_string(),
+                size: 7
+            }]
+        );
+        assert_eq!(report.reclaimable_bytes, 7);
+        assert_eq!(report.extra_backup_bytes, 4);
+        assert_eq!(report.largest[0].date, "b");
+        assert_eq!(report.games[1].snapshots, SizeStats::default());
+    }
+
+    #[test]
+    fn cleanup_quarantines_orphans_and_drops_dangling_records() {
+        let root = temp_dir::TempDir::new().unwrap();
+        let game = root.path().join("Game");
+        fs::create_dir_all(&game).unwrap();
+        fs::write(game.join("a.zip"), [0; 10]).unwrap();
+        fs::write(game.join("stray.zip"), [0; 7]).unwrap();
+        let records = GameSnapshots::new(
+            "Game",
+            vec![snapshot("a", 0), snapshot("gone", 5), snapshot("cloud", 0)],
+        );
+        let zips = list_zips(&game).unwrap();
+        let in_cloud = HashMap::from([("cloud".to_string(), 3)]);
+
+        let cleanup = plan_game_cleanup("Game", records, &zips, &in_cloud);
+        let game_name = || "Game".to_string();
+        assert_eq!(
+            cleanup.actions,
+            [
+                CleanupAction::QuarantineOrphan {
+                    game: game_name(),
+                    path: "Game/stray.zip".to_string(),
+                    size: 7
+                },
+                CleanupAction::FixSize {
+                    game: game_name(),
+                    date: "a".to_string(),
+                    size: 10
+                },
+                CleanupAction::DropDangling {
+                    game: game_name(),
+                    date: "gone".to_string()
+                },
+                CleanupAction::RecoverFromCloud {
+                    game: game_name(),
+                    date: "cloud".to_string()
+                },
+                CleanupAction::FixSize {
+                    game: game_name(),
+                    date: "cloud".to_string(),
+                    size: 3
+                },
+            ]
+        );
+        assert!(cleanup.records_changed);
+        let dates: Vec<_> = cleanup
+            .records
+            .backups
+            .iter()
+            .map(|s| s.date.as_str())
+            .collect();
+        assert_eq!(dates, ["a", "cloud"]);
+
+        quarantine(root.path(), "Game/stray.zip").unwrap();
+        assert!(!game.join("stray.zip").exists());
+        assert!(game.join("orphaned").join("stray.zip").exists());
+    }
+}
@@ -1,19 +1,25 @@
-use crate::cloud_sync::upload_game_snapshots;
-use crate::config::{get_config, set_config, Config};
+use crate::cloud_sync::{OutboxOperation, rename_queued, sync_or_queue};
+use crate::config::{Config, ConfigManager, get_config, set_config};
 use crate::preclude::*;
 
-use log::{error, info};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 use tauri::AppHandle;
 
-use super::{Game, GameSnapshots};
+use super::game::with_records_lock;
+use super::{ArchiveMonitor, Game, GameSnapshots, Snapshot};
 
 /// 对 Windows 路径组件进行安全化处理
 ///
 /// - 规则：移除/替换非法字符 `< > : " / \ | ? *` 为下划线 `_`
 /// - 处理：去除结尾的空格与点；若结果为空则使用 `Game`
 /// - 保留：大小写与非 ASCII 字符不做额外转换
+/// - 用途：结果即游戏的存储名，本地备份文件夹与云端 `save_data` 下的目录都只能由它得到，
+///   在任何平台上结果都相同，见 `GameSnapshots::storage_name`
 pub fn sanitize_windows_path_component(name: &str) -> String {
     let mut s: String = name
         .chars()
@@ -23,41 +29,106 @@ pub fn sanitize_windows_path_component(name: &str) -> String {
         })
         .collect();
     // 去除结尾的空格与点
-    while s.ends_with(' ') || s.ends_with('.') { s.pop(); }
-    if s.trim().is_empty() { return String::from("Game"); }
+    while s.ends_with(' ') || s.ends_with('.') {
+        s.pop();
+    }
+    if s.trim().is_empty() {
+        return String::from("Game");
+    }
     s
 }
 
-/// 组合本地备份目录：`config.backup_path` + 安全化后的游戏名
+/// 组合本地备份目录：`config.backup_root()` + 安全化后的游戏名
 pub fn join_backup_dir(config: &Config, name: &str) -> PathBuf {
     let safe = sanitize_windows_path_component(name);
-    PathBuf::from(&config.backup_path).join(safe)
+    config.backup_root().join(safe)
 }
 
-async fn create_backup_folder(name: &str) -> Result<(), BackupError> {
-    let config = get_config()?;
-    let backup_path = join_backup_dir(&config, name);
-    let info: GameSnapshots = if !backup_path.exists() {
-        fs::create_dir_all(&backup_path)?;
-        GameSnapshots {
-            name: name.to_string(),
-            backups: Vec::new(),
+/// 检查备份目录是否可用，用于在备份前发现已断开的外置硬盘或网络驱动器
+///
+/// - 目录存在时可用
+/// - 还没有游戏时目录可能尚未创建，由第一次备份创建，也视为可用
+/// - 否则返回 `BackupError::BackupRootUnavailable`，而不是在写入时才得到 IO 错误
+pub fn check_backup_root(config: &Config) -> Result<(), BackupError> {
+    if config.games.is_empty() || config.backup_root().is_dir() {
+        return Ok(());
+    }
+    Err(BackupError::BackupRootUnavailable(
+        config.backup_path.clone(),
+    ))
+}
+
+/// 删除本地备份中的文件或目录
+///
+/// - `use_trash` 为真时移入系统回收站，误删后仍可找回
+/// - 回收站不可用时（如没有桌面环境的 Linux）返回 `BackupError::Trash`，不会改为永久删除
+pub fn remove_backup_path(path: &Path, use_trash: bool) -> Result<(), BackupError> {
+    if use_trash {
+        // 回收站对不存在的路径报告的错误难以区分，先按永久删除的行为返回 NotFound
+        if !path.exists() {
+            return Err(io::Error::from(io::ErrorKind::NotFound).into());
         }
+        return trash::delete(path).map_err(|e| BackupError::Trash {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        });
+    }
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
     } else {
-        // 如果已经存在，info从原来的文件中读取
-        let bytes = fs::read(backup_path.join("Backups.json"));
-        serde_json::from_slice(&bytes?)?
-    };
-    fs::write(
-        backup_path.join("Backups.json"),
-        serde_json::to_string_pretty(&info)?,
-    )?;
-
-    // 处理云同步
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// 快照记录中保存的路径：相对于 `config.backup_path`，统一使用 `/`，更换备份目录时无需改写
+pub fn snapshot_record_path(name: &str, file_name: &str) -> String {
+    format!("{}/{}", sanitize_windows_path_component(name), file_name)
+}
+
+/// 将 `root` 下某个游戏的快照记录改写为相对路径，返回是否有改动
+///
+/// 旧版本写入的是绝对路径，换机器或更换备份目录后就会失效
+pub fn normalize_snapshot_records(root: &Path, name: &str) -> Result<bool, BackupError> {
+    let path = root
+        .join(sanitize_windows_path_component(name))
+        .join("Backups.json");
+    if !path.exists() {
+        return Ok(false);
+    }
+    let (mut info, _) = read_json_or_backup::<GameSnapshots, BackupError>(&path)?;
+    if !info.normalize_paths() {
+        return Ok(false);
+    }
+    write_atomic(&path, serde_json::to_string_pretty(&info)?.as_bytes())?;
+    Ok(true)
+}
+
+/// 创建游戏的备份目录与空的 Backups.json，目录已存在时保留原有记录
+pub(super) async fn create_backup_folder(name: &str) -> Result<(), BackupError> {
+    let config = get_config()?;
+    let backup_path = join_backup_dir(&config, name);
+    with_records_lock(&backup_path, || {
+        let info: GameSnapshots = if !backup_path.exists() {
+            fs::create_dir_all(&backup_path)?;
+            GameSnapshots::new(name, Vec::new())
+        } else {
+            // 如果已经存在，info从原来的文件中读取
+            read_json_or_backup::<_, BackupError>(&backup_path.join("Backups.json"))?.0
+        };
+        write_atomic(
+            &backup_path.join("Backups.json"),
+            serde_json::to_string_pretty(&info)?.as_bytes(),
+        )?;
+        Ok::<_, BackupError>(())
+    })?;
+
+    // 处理云同步，上传失败时留在队列中等待下次同步
     if config.settings.cloud_settings.always_sync {
-        let op = config.settings.cloud_settings.backend.get_op()?;
-        // 上传存档记录信息
-        upload_game_snapshots(&op, info).await?;
+        let _ = sync_or_queue(vec![OutboxOperation::UploadRecords {
+            game: name.to_string(),
+        }])
+        .await;
     }
 
     Ok(())
@@ -83,35 +154,335 @@ pub async fn create_game_backup(game: &Game) -> Result<(), BackupError> {
     Ok(())
 }
 
-pub async fn backup_all() -> Result<(), BackupError> {
-    let config = get_config()?;
-    for game in &config.games {
-        if let Err(e) = game.create_snapshot("Backup all").await {
-            error!(target: "rgsm::backup", "Backup all failed for game {:#?}", game);
-            return Err(e);
-        } else {
-            info!(target: "rgsm::backup", "Backup all succeeded for game {:#?}", game.name);
+/// Rename a game together with its backup folder, snapshot records and cloud copy
+///
+/// Every check happens before the first change, so a rejected rename leaves
+/// everything untouched. The cloud copy is renamed through the outbox, a failure leaves
+/// it queued and the local rename stands.
+pub async fn rename_game(old_name: &str, new_name: &str) -> Result<(), BackupError> {
+    let mut config = get_config()?;
+    let new_name = new_name.trim();
+    // 含 Windows 非法字符的名称与添加游戏时一样允许，备份文件夹使用安全化后的存储名
+    if new_name.is_empty() {
+        return Err(BackupError::InvalidGameName(new_name.to_string()));
+    }
+    let index = config
+        .games
+        .iter()
+        .position(|g| g.name == old_name)
+        .ok_or_else(|| BackupError::GameNotExist(old_name.to_string()))?;
+    // 游戏名不区分大小写，只改变大小写时允许
+    if config
+        .games
+        .iter()
+        .any(|g| g.name != old_name && g.name.eq_ignore_ascii_case(new_name))
+    {
+        return Err(BackupError::GameNameConflict(new_name.to_string()));
+    }
+    let old_dir = join_backup_dir(&config, old_name);
+    let new_dir = join_backup_dir(&config, new_name);
+    // 旧名称安全化后可能已经是新名称，此时文件夹不变
+    if new_dir.exists() && new_dir != old_dir && !old_name.eq_ignore_ascii_case(new_name) {
+        return Err(BackupError::GameNameConflict(new_name.to_string()));
+    }
+
+    let mut game = config.games[index].clone();
+    let mut info = game.get_game_snapshots_info()?;
+    game.name = new_name.to_string();
+    info.name = new_name.to_string();
+    info.normalize_paths();
+
+    config.games[index] = game.clone();
+    let quick_action = &mut config.quick_action;
+    if quick_action
+        .quick_action_game
+        .as_ref()
+        .is_some_and(|g| g.name == old_name)
+    {
+        quick_action.quick_action_game = Some(game.clone());
+    }
+    for binding in &mut quick_action.game_hotkeys {
+        if binding.game_name == old_name {
+            binding.game_name = new_name.to_string();
+        }
+    }
+    for schedule in &mut quick_action.schedules {
+        if schedule.game_name == old_name {
+            schedule.game_name = new_name.to_string();
         }
     }
+    if let Some(game_override) = quick_action.overrides.remove(old_name) {
+        quick_action
+            .overrides
+            .insert(new_name.to_string(), game_override);
+    }
+    for node in &mut config.favorites {
+        node.rename_game(old_name, new_name);
+    }
+
+    info!(target: "rgsm::backup", "Renaming game {} to {}", old_name, new_name);
+    fs::rename(&old_dir, &new_dir)?;
+    // 配置保存失败时把文件夹移回去，保持本地状态一致
+    if let Err(e) = ConfigManager::global().set(&config).await {
+        if let Err(move_back) = fs::rename(&new_dir, &old_dir) {
+            error!(target: "rgsm::backup", "Failed to move {:?} back after rename failed: {:?}", new_dir, move_back);
+        }
+        return Err(e.into());
+    }
+    game.set_game_snapshots_info(&info)?;
+
+    // 旧名称下排队的上传改到新名称下，否则重放时找不到本地文件会被丢弃
+    if let Err(e) = rename_queued(old_name, new_name, None) {
+        warn!(target: "rgsm::backup", "Failed to rename queued cloud uploads of {}: {:?}", old_name, e);
+    }
+    // 本地已经完成，云端失败时留在队列中等待下次同步
+    if config.settings.cloud_settings.always_sync {
+        let _ = sync_or_queue(vec![
+            OutboxOperation::RenameGame {
+                from: old_name.to_string(),
+                to: new_name.to_string(),
+            },
+            OutboxOperation::UploadRecords {
+                game: new_name.to_string(),
+            },
+            OutboxOperation::UploadConfig,
+        ])
+        .await;
+    }
     Ok(())
 }
 
-pub async fn apply_all(app_handle: Option<&AppHandle>) -> Result<(), BackupError> {
+/// `backup_all` 与 `apply_all` 的结果，单个游戏失败不会中断其他游戏
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct BatchSummary {
+    pub succeeded: Vec<String>,
+    /// 游戏名与失败原因
+    pub failed: Vec<(String, String)>,
+    /// 本地操作成功但有需要提示的问题，如云端上传失败
+    pub warnings: Vec<(String, String)>,
+    /// 没有快照可恢复的游戏
+    pub skipped: Vec<String>,
+    /// `backup_all` 为成功的游戏创建的快照，与游戏名对应
+    pub snapshots: Vec<(String, Snapshot)>,
+}
+
+pub async fn backup_all() -> Result<BatchSummary, BackupError> {
     let config = get_config()?;
+    check_backup_root(&config)?;
+    let mut summary = BatchSummary::default();
     for game in &config.games {
-        let date = game
-            .get_game_snapshots_info()?
-            .backups
-            .last()
-            .ok_or(BackupError::NoBackupAvailable)?
-            .date
-            .clone();
-        if let Err(e) = game.restore_snapshot(&date, app_handle) {
-            error!(target: "rgsm::backup", "Apply all failed for game {:#?} with date {}", game, date);
-            return Err(e);
-        } else {
+        let describe = super::auto_describe(&config, "Backup all", &game.name);
+        match game
+            .create_and_upload_snapshot(&describe, &mut ArchiveMonitor::silent())
+            .await
+        {
+            Ok((snapshot, upload_error)) => {
+                info!(target: "rgsm::backup", "Backup all succeeded for game {:#?}", game.name);
+                if let Some(e) = upload_error {
+                    warn!(target: "rgsm::backup", "Backup all failed to upload game {}: {:?}", game.name, e);
+                    summary.warnings.push((game.name.clone(), e.to_string()));
+                }
+                summary.succeeded.push(game.name.clone());
+                summary.snapshots.push((game.name.clone(), snapshot));
+            }
+            Err(e) => {
+                error!(target: "rgsm::backup", "Backup all failed for game {}: {:?}", game.name, e);
+                summary.failed.push((game.name.clone(), e.to_string()));
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// 恢复游戏最新的快照，没有快照的游戏记入 `skipped`
+fn apply_latest_to(game: &Game, app_handle: Option<&AppHandle>, summary: &mut BatchSummary) {
+    let result = game
+        .get_game_snapshots_info()
+        .and_then(|info| match info.backups.last() {
+            Some(snapshot) => game
+                .restore_snapshot(&snapshot.date, app_handle)
+                .map(|()| Some(snapshot.date.clone())),
+            None => Ok(None),
+        });
+    match result {
+        Ok(Some(date)) => {
             info!(target: "rgsm::backup", "Apply all succeeded for game {:#?} with date {}", game.name, date);
+            summary.succeeded.push(game.name.clone());
+        }
+        Ok(None) => {
+            info!(target: "rgsm::backup", "Apply all skipped game {:#?} without snapshots", game.name);
+            summary.skipped.push(game.name.clone());
+        }
+        Err(e) => {
+            error!(target: "rgsm::backup", "Apply all failed for game {}: {:?}", game.name, e);
+            summary.failed.push((game.name.clone(), e.to_string()));
         }
     }
-    Ok(())
+}
+
+/// 没有任何游戏被恢复时返回错误，全部被跳过时为 `NoBackupAvailable`
+fn finish_apply(summary: BatchSummary) -> Result<BatchSummary, BackupError> {
+    if !summary.succeeded.is_empty() {
+        return Ok(summary);
+    }
+    if !summary.failed.is_empty() {
+        return Err(BackupError::NothingApplied(summary.failed));
+    }
+    if !summary.skipped.is_empty() {
+        return Err(BackupError::NoBackupAvailable);
+    }
+    Ok(summary)
+}
+
+pub async fn apply_all(app_handle: Option<&AppHandle>) -> Result<BatchSummary, BackupError> {
+    let config = get_config()?;
+    let mut summary = BatchSummary::default();
+    for game in &config.games {
+        apply_latest_to(game, app_handle, &mut summary);
+    }
+    finish_apply(summary)
+}
+
+/// 与 `apply_all` 相同，只恢复 `names` 中的游戏
+pub async fn apply_latest(
+    names: &[String],
+    app_handle: Option<&AppHandle>,
+) -> Result<BatchSummary, BackupError> {
+    let config = get_config()?;
+    let mut summary = BatchSummary::default();
+    for name in names {
+        match config.games.iter().find(|game| &game.name == name) {
+            Some(game) => apply_latest_to(game, app_handle, &mut summary),
+            None => {
+                let e = BackupError::GameNotExist(name.clone());
+                error!(target: "rgsm::backup", "Apply latest failed for game {}: {:?}", name, e);
+                summary.failed.push((name.clone(), e.to_string()));
+            }
+        }
+    }
+    finish_apply(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_fails_only_when_nothing_was_restored() {
+        let skipped = BatchSummary {
+            skipped: vec!["New".into()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            finish_apply(skipped.clone()),
+            Err(BackupError::NoBackupAvailable)
+        ));
+
+        let failed = BatchSummary {
+            failed: vec![("Broken".into(), "IO error".into())],
+            ..skipped
+        };
+        assert!(matches!(
+            finish_apply(failed.clone()),
+            Err(BackupError::NothingApplied(f)) if f.len() == 1
+        ));
+
+        let partial = BatchSummary {
+            succeeded: vec!["Celeste".into()],
+            ..failed
+        };
+        let summary = finish_apply(partial).unwrap();
+        assert_eq!(summary.skipped, vec!["New".to_string()]);
+        assert!(finish_apply(BatchSummary::default()).is_ok());
+    }
+
+    #[test]
+    fn normalize_snapshot_records_makes_paths_relative() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let game_dir = dir.path().join("Game");
+        fs::create_dir_all(&game_dir).unwrap();
+        let info = GameSnapshots::new(
+            "Game",
+            vec![Snapshot {
+                date: "2024-01-01_00-00-00".into(),
+                describe: String::new(),
+                path: "D:\\SaveManager\\save_data\\Game\\2024-01-01_00-00-00.zip".into(),
+                size: 0,
+            }],
+        );
+        fs::write(
+            game_dir.join("Backups.json"),
+            serde_json::to_string(&info).unwrap(),
+        )
+        .unwrap();
+
+        assert!(normalize_snapshot_records(dir.path(), "Game").unwrap());
+        assert!(!normalize_snapshot_records(dir.path(), "Game").unwrap());
+        let (info, _) =
+            read_json_or_backup::<GameSnapshots, BackupError>(&game_dir.join("Backups.json"))
+                .unwrap();
+        assert_eq!(info.backups[0].path, "Game/2024-01-01_00-00-00.zip");
+    }
+
+    #[test]
+    fn missing_backup_root_is_unavailable_once_games_exist() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let mut config = Config {
+            backup_path: dir.path().join("unplugged").to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        // 还没有游戏时由第一次备份创建目录
+        assert!(check_backup_root(&config).is_ok());
+
+        config.games.push(Game {
+            name: "Game".to_string(),
+            save_paths: Vec::new(),
+            game_paths: Default::default(),
+        });
+        assert!(matches!(
+            check_backup_root(&config),
+            Err(BackupError::BackupRootUnavailable(_))
+        ));
+        config.backup_path = dir.path().to_string_lossy().to_string();
+        assert!(check_backup_root(&config).is_ok());
+    }
+
+    #[test]
+    fn permanent_removal_deletes_files_and_folders() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let zip = dir.path().join("2024-01-01_00-00-00.zip");
+        let game_dir = dir.path().join("Game");
+        fs::write(&zip, b"zip").unwrap();
+        fs::create_dir_all(game_dir.join("extra_backup")).unwrap();
+
+        remove_backup_path(&zip, false).unwrap();
+        remove_backup_path(&game_dir, false).unwrap();
+        assert!(!zip.exists());
+        assert!(!game_dir.exists());
+        assert!(matches!(
+            remove_backup_path(&zip, false),
+            Err(BackupError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn trashed_paths_are_moved_or_reported() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let zip = dir.path().join("2024-01-01_00-00-00.zip");
+        fs::write(&zip, b"zip").unwrap();
+
+        // 没有可用回收站的环境中必须报错并保留文件，而不是悄悄永久删除
+        match remove_backup_path(&zip, true) {
+            Ok(()) => assert!(!zip.exists()),
+            Err(BackupError::Trash { path, .. }) => {
+                assert_eq!(path, zip);
+                assert!(zip.exists());
+            }
+            Err(e) => panic!("unexpected error: {e:?}"),
+        }
+        assert!(matches!(
+            remove_backup_path(&dir.path().join("missing.zip"), true),
+            Err(BackupError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+    }
 }
@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::config::Config;
+use crate::device::get_current_device_id;
+use crate::path_resolver::resolve_path;
+
+use super::{Game, SaveUnitType};
+
+/// 单个存档单元存在的问题
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SaveUnitIssue {
+    /// 当前设备没有为该单元配置路径
+    NoPathForDevice,
+    /// 路径中的变量无法解析
+    Unresolvable { reason: String },
+    /// 路径不存在，通常是游戏被卸载或移动
+    NotExists,
+    /// 声明为文件但实际是文件夹，或者相反
+    TypeMismatch { expected: SaveUnitType },
+    /// 与序号为 `other` 的单元指向同一路径
+    Duplicate { other: usize },
+}
+
+/// 单个存档单元的检查结果，`index` 为其在 `save_paths` 中的序号
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SaveUnitReport {
+    pub index: usize,
+    pub resolved_path: Option<String>,
+    pub issues: Vec<SaveUnitIssue>,
+}
+
+/// 一个游戏所有存档路径的检查结果
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GameValidationReport {
+    pub game_name: String,
+    pub units: Vec<SaveUnitReport>,
+}
+
+impl GameValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.units.iter().all(|u| u.issues.is_empty())
+    }
+}
+
+/// 按当前设备解析并检查游戏的每个存档单元
+pub fn validate_game(game: &Game, config: &Config) -> GameValidationReport {
+    let device_id = get_current_device_id();
+    let mut seen: Vec<(usize, PathBuf)> = Vec::new();
+    let units = game
+        .save_paths
+        .iter()
+        .enumerate()
+        .map(|(index, unit)| {
+            let mut issues = Vec::new();
+            let Some(raw) = unit.get_path_for_device(device_id) else {
+                issues.push(SaveUnitIssue::NoPathForDevice);
+                return SaveUnitReport {
+                    index,
+                    resolved_path: None,
+                    issues,
+                };
+            };
+            let path = match resolve_path(raw, Some(game), config) {
+                Ok(path) => path,
+                Err(e) => {
+                    issues.push(SaveUnitIssue::Unresolvable {
+                        reason: e.to_string(),
+                    });
+                    return SaveUnitReport {
+                        index,
+                        resolved_path: None,
+                        issues,
+                    };
+                }
+            };
+
+            match (&unit.unit_type, path.exists()) {
+                (_, false) => issues.push(SaveUnitIssue::NotExists),
+                (SaveUnitType::File, true) if !path.is_file() => {
+                    issues.push(SaveUnitIssue::TypeMismatch {
+                        expected: SaveUnitType::File,
+                    })
+                }
+                (SaveUnitType::Folder, true) if !path.is_dir() => {
+                    issues.push(SaveUnitIssue::TypeMismatch {
+                        expected: SaveUnitType::Folder,
+                    })
+                }
+                _ => {}
+            }
+            if let Some((other, _)) = seen.iter().find(|(_, p)| *p == path) {
+                issues.push(SaveUnitIssue::Duplicate { other: *other });
+            }
+            seen.push((index, path.clone()));
+
+            SaveUnitReport {
+                index,
+                resolved_path: Some(path.to_string_lossy().to_string()),
+                issues,
+            }
+        })
+        .collect();
+
+    GameValidationReport {
+        game_name: game.name.clone(),
+        units,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::SaveUnit;
+    use std::collections::HashMap;
+
+    fn unit(unit_type: SaveUnitType, path: &std::path::Path) -> SaveUnit {
+        SaveUnit {
+            unit_type,
+            paths: HashMap::from([(
+                get_current_device_id().clone(),
+                path.to_string_lossy().to_string(),
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reports_missing_mismatched_and_duplicate_units() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let file = dir.path().join("save.dat");
+        std::fs::write(&file, b"save").unwrap();
+
+        let game = Game {
+            name: "Game".into(),
+            save_paths: vec![
+                unit(SaveUnitType::File, &file),
+                unit(SaveUnitType::Folder, &file),
+                unit(SaveUnitType::Folder, &dir.path().join("missing")),
+                SaveUnit {
+                    paths: HashMap::new(),
+                    ..unit(SaveUnitType::Folder, dir.path())
+                },
+            ],
+            game_paths: HashMap::new(),
+        };
+        let report = validate_game(&game, &Config::default());
+        let issues: Vec<_> = report.units.iter().map(|u| u.issues.clone()).collect();
+        assert_eq!(
+            issues,
+            [
+                vec![],
+                vec![
+                    SaveUnitIssue::TypeMismatch {
+                        expected: SaveUnitType::Folder
+                    },
+                    SaveUnitIssue::Duplicate { other: 0 },
+                ],
+                vec![SaveUnitIssue::NotExists],
+                vec![SaveUnitIssue::NoPathForDevice],
+            ]
+        );
+        assert!(!report.is_ok());
+    }
+}
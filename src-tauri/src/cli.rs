@@ -0,0 +1,260 @@
+//! 无界面的命令行模式
+//!
+//! 带有下列参数启动时直接执行对应操作后退出，不会创建窗口，便于在脚本与计划任务中使用：
+//!
+//! - `--backup-all`：备份所有游戏
+//! - `--backup <game>`：备份指定游戏
+//! - `--apply <game> [--date <date>]`：恢复指定快照，未指定日期时恢复最新的快照
+//! - `--cloud-upload-all`：上传所有备份到云端
+//! - `--list-games [--json]`：列出所有游戏
+//!
+//! 日志输出到标准输出，失败时以非零退出码结束。Windows 发布版没有控制台窗口，
+//! 需要输出时请重定向到文件，如 `rgsm.exe --backup-all > backup.log`。
+
+use std::io::Write;
+
+use anyhow::{Context, Result, anyhow, bail};
+use log::{Log, Metadata, Record, info, warn};
+use serde::Serialize;
+
+use crate::backup::{self, Game};
+use crate::cloud_sync::upload_all;
+use crate::config::{config_check, config_path, get_config};
+use crate::diagnostics::parse_log_level;
+use crate::preclude::*;
+
+/// 命令行模式支持的操作
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliCommand {
+    BackupAll,
+    Backup { game: String },
+    Apply { game: String, date: Option<String> },
+    CloudUploadAll,
+    ListGames { json: bool },
+}
+
+fn set_command(command: &mut Option<CliCommand>, new: CliCommand) -> Result<()> {
+    if command.is_some() {
+        bail!("Only one command can be given at a time");
+    }
+    *command = Some(new);
+    Ok(())
+}
+
+/// 解析启动参数（不含程序名），没有命令行操作时返回 `None` 以正常启动界面
+///
+/// `--config <path>` 由配置模块读取，这里直接跳过。
+/// 给出了命令行操作时，未知的 `--` 参数视为错误，以免拼错的选项被忽略；
+/// 没有命令行操作时只记录警告，仍正常启动界面
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<CliCommand>> {
+    let mut args = args.into_iter();
+    let mut command = None;
+    let mut date = None;
+    let mut json = false;
+    let mut unknown = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .filter(|value| !value.starts_with("--"))
+                .ok_or_else(|| anyhow!("{name} needs a value"))
+        };
+        match arg.as_str() {
+            "--backup-all" => set_command(&mut command, CliCommand::BackupAll)?,
+            "--backup" => {
+                let game = value("--backup")?;
+                set_command(&mut command, CliCommand::Backup { game })?
+            }
+            "--apply" => {
+                let game = value("--apply")?;
+                set_command(&mut command, CliCommand::Apply { game, date: None })?
+            }
+            "--date" => date = Some(value("--date")?),
+            "--cloud-upload-all" => set_command(&mut command, CliCommand::CloudUploadAll)?,
+            "--list-games" => set_command(&mut command, CliCommand::ListGames { json: false })?,
+            "--json" => json = true,
+            "--config" => {
+                value("--config")?;
+            }
+            arg if arg.starts_with("--config=") => {}
+            arg if arg.starts_with("--") => unknown.push(arg.to_string()),
+            _ => {}
+        }
+    }
+    match &mut command {
+        Some(CliCommand::Apply {
+            date: apply_date, ..
+        }) => *apply_date = date,
+        _ if date.is_some() => bail!("--date only applies to --apply"),
+        _ => {}
+    }
+    match &mut command {
+        Some(CliCommand::ListGames { json: list_json }) => *list_json = json,
+        _ if json => bail!("--json only applies to --list-games"),
+        _ => {}
+    }
+    if !unknown.is_empty() {
+        if command.is_some() {
+            bail!("Unknown option {}", unknown.join(", "));
+        }
+        warn!(target: "rgsm::cli", "Ignoring unknown options {:?}", unknown);
+    }
+    Ok(command)
+}
+
+/// 输出到标准输出的日志，格式与日志文件相同
+struct StdoutLogger;
+
+impl Log for StdoutLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            println!("{}", format_log_record(record.args(), record));
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+    }
+}
+
+/// `--list-games --json` 输出的单个游戏
+#[derive(Serialize)]
+struct GameSummary {
+    name: String,
+    save_units: usize,
+    snapshots: usize,
+    latest_snapshot: Option<String>,
+}
+
+fn find_game(name: &str) -> Result<Game> {
+    get_config()?
+        .games
+        .into_iter()
+        .find(|game| game.name == name)
+        .ok_or_else(|| BackupError::GameNotExist(name.to_string()).into())
+}
+
+/// 执行命令行操作，失败时返回错误，由 `main` 以非零退出码结束进程
+pub fn run(command: CliCommand) -> Result<()> {
+    let _ = log::set_boxed_logger(Box::new(StdoutLogger));
+    log::set_max_level(log::LevelFilter::Info);
+    // 相对的备份路径与旧版配置都以程序所在目录为准，计划任务的工作目录则不确定。
+    // 先确定配置文件位置，相对的 `--config` 仍按调用时的工作目录解析
+    if config_path().is_absolute() {
+        if let Some(dir) = std::env::current_exe()?.parent() {
+            std::env::set_current_dir(dir)?;
+        }
+    }
+    config_check()?;
+    log::set_max_level(parse_log_level(&get_config()?.settings.log_level));
+    info!(target: "rgsm::cli", "Running {:?}", command);
+
+    tauri::async_runtime::block_on(async {
+        match command {
+            CliCommand::BackupAll => {
+                let summary = backup::backup_all().await?;
+                info!(target: "rgsm::cli", "Backed up: {:?}", summary.succeeded);
+                if !summary.failed.is_empty() {
+                    bail!("{} games failed to back up", summary.failed.len());
+                }
+            }
+            CliCommand::Backup { game } => {
+                let snapshot = find_game(&game)?
+                    .create_snapshot("Command line backup")
+                    .await?;
+                info!(target: "rgsm::cli", "Created snapshot {}", snapshot.date);
+            }
+            CliCommand::Apply {
+                game,
+                date: Some(date),
+            } => find_game(&game)?.restore_snapshot(&date, None)?,
+            CliCommand::Apply { game, date: None } => {
+                backup::apply_latest(&[game], None).await?;
+            }
+            CliCommand::CloudUploadAll => {
+                let config = get_config()?;
+                let backend = config.settings.cloud_settings.find_backend(None)?;
+                let summary = upload_all(backend, None).await?;
+                info!(target: "rgsm::cli", "Uploaded: {:?}", summary);
+                if summary.failed > 0 {
+                    bail!("{} snapshots failed to upload", summary.failed);
+                }
+            }
+            CliCommand::ListGames { json } => {
+                let games: Vec<GameSummary> = get_config()?
+                    .games
+                    .iter()
+                    .map(|game| {
+                        let snapshots = game
+                            .get_game_snapshots_info()
+                            .map(|info| info.backups)
+                            .unwrap_or_default();
+                        GameSummary {
+                            name: game.name.clone(),
+                            save_units: game.save_paths.len(),
+                            snapshots: snapshots.len(),
+                            latest_snapshot: snapshots.last().map(|s| s.date.clone()),
+                        }
+                    })
+                    .collect();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&games)?);
+                } else {
+                    for game in games {
+                        println!("{}", game.name);
+                    }
+                }
+            }
+        }
+        Ok::<(), anyhow::Error>(())
+    })
+    .context("Command line operation failed")?;
+    info!(target: "rgsm::cli", "Done");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<CliCommand>> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(parse(&[]).unwrap(), None);
+        assert_eq!(parse(&["--config", "a.json"]).unwrap(), None);
+        assert_eq!(parse(&["--config=a.json"]).unwrap(), None);
+        assert_eq!(
+            parse(&["--backup-all"]).unwrap(),
+            Some(CliCommand::BackupAll)
+        );
+        assert_eq!(
+            parse(&["--date", "2024-01-01_00-00-00", "--apply", "Celeste"]).unwrap(),
+            Some(CliCommand::Apply {
+                game: "Celeste".to_string(),
+                date: Some("2024-01-01_00-00-00".to_string()),
+            })
+        );
+        assert_eq!(
+            parse(&["--list-games", "--json"]).unwrap(),
+            Some(CliCommand::ListGames { json: true })
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        assert!(parse(&["--backup"]).is_err());
+        assert!(parse(&["--backup", "--json"]).is_err());
+        assert!(parse(&["--backup-all", "--list-games"]).is_err());
+        assert!(parse(&["--backup-all", "--date", "2024-01-01_00-00-00"]).is_err());
+        assert!(parse(&["--backup", "Celeste", "--json"]).is_err());
+        assert!(parse(&["--backup-all", "--dry-run"]).is_err());
+        // 没有命令行操作时未知参数不影响启动界面
+        assert_eq!(parse(&["--backup-al"]).unwrap(), None);
+    }
+}
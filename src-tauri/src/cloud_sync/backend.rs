@@ -1,4 +1,10 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
 use opendal::Operator;
+use opendal::layers::{HttpClientLayer, RetryLayer};
+use opendal::raw::HttpClient;
 use opendal::services;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -6,6 +12,8 @@ use specta::Type;
 use crate::config::get_config;
 use crate::preclude::*;
 
+use super::cloud_settings::normalize_root_path;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
 #[serde(tag = "type")]
 pub enum Backend {
@@ -18,6 +26,12 @@ pub enum Backend {
         endpoint: String,
         username: String,
         password: String,
+        /// 不校验服务器证书，仅用于局域网内使用自签名证书的服务器
+        #[serde(default)]
+        danger_accept_invalid_certs: bool,
+        /// 额外信任的 CA 证书文件，PEM 或 DER 格式
+        #[serde(default)]
+        ca_cert_path: Option<String>,
     },
     /// Amazon S3 后端
     /// 参考：https://docs.rs/opendal/latest/opendal/services/struct.S3.html
@@ -29,25 +43,66 @@ pub enum Backend {
         access_key_id: String,
         secret_access_key: String,
     },
+    /// Google Drive 后端
+    /// 参考：https://docs.rs/opendal/latest/opendal/services/struct.Gdrive.html
+    /// 可只填写 access_token，或填写 refresh_token、client_id、client_secret 以自动刷新令牌
+    GoogleDrive {
+        access_token: String,
+        refresh_token: String,
+        client_id: String,
+        client_secret: String,
+    },
+    /// OneDrive 后端
+    /// 参考：https://docs.rs/opendal/latest/opendal/services/struct.Onedrive.html
+    OneDrive {
+        access_token: String,
+    },
+    /// 本地文件夹后端，可指向第二块硬盘或已挂载的网络共享（如 NAS 的 SMB 共享）
+    /// 参考：https://docs.rs/opendal/latest/opendal/services/struct.Fs.html
+    LocalFs {
+        path: String,
+    },
+}
+
+/// 临时错误（如 429、503）按指数退避并加入随机抖动后重试，`max_attempts` 包含首次请求
+fn retry_layer(max_attempts: u32) -> RetryLayer {
+    RetryLayer::new()
+        .with_max_times(max_attempts.saturating_sub(1) as usize)
+        .with_factor(2.0)
+        .with_min_delay(Duration::from_millis(500))
+        .with_max_delay(Duration::from_secs(30))
+        .with_jitter()
 }
 
 impl Backend {
-    /// 获取 Operator 实例
-    pub fn get_op(&self) -> Result<Operator, BackendError> {
-        let root = get_config()?.settings.cloud_settings.root_path;
-        match self {
-            Backend::Disabled => Err(BackendError::Disabled),
+    /// 获取以 `root_path` 为根目录的 Operator 实例
+    pub fn get_op(&self, root_path: &str) -> Result<Operator, BackendError> {
+        let cloud_settings = get_config()?.settings.cloud_settings;
+        let root = normalize_root_path(root_path)?;
+        // 尚未保存的后端（如测试连接时）也可能出现在错误日志中
+        register_secrets(self.secrets());
+        let op = match self {
+            Backend::Disabled => return Err(BackendError::Disabled),
             Backend::WebDAV {
                 endpoint,
                 username,
                 password,
+                danger_accept_invalid_certs,
+                ca_cert_path,
             } => {
                 let builder = services::Webdav::default()
                     .endpoint(endpoint)
                     .username(username)
                     .password(password)
                     .root(&root);
-                Ok(Operator::new(builder)?.finish())
+                let op = Operator::new(builder)?.finish();
+                let ca_cert_path = ca_cert_path.as_deref().filter(|p| !p.trim().is_empty());
+                if *danger_accept_invalid_certs || ca_cert_path.is_some() {
+                    let client = tls_http_client(*danger_accept_invalid_certs, ca_cert_path)?;
+                    op.layer(HttpClientLayer::new(HttpClient::with(client)))
+                } else {
+                    op
+                }
             }
             Backend::S3 {
                 endpoint,
@@ -63,31 +118,90 @@ impl Backend {
                     .access_key_id(access_key_id)
                     .secret_access_key(secret_access_key)
                     .root(&root);
-                Ok(Operator::new(builder)?.finish())
+                Operator::new(builder)?.finish()
+            }
+            Backend::GoogleDrive {
+                access_token,
+                refresh_token,
+                client_id,
+                client_secret,
+            } => {
+                let mut builder = services::Gdrive::default().root(&root);
+                // 空字符串会被当作有效凭据，只设置已填写的字段
+                if !access_token.is_empty() {
+                    builder = builder.access_token(access_token);
+                }
+                if !refresh_token.is_empty() {
+                    builder = builder
+                        .refresh_token(refresh_token)
+                        .client_id(client_id)
+                        .client_secret(client_secret);
+                }
+                Operator::new(builder)?.finish()
+            }
+            Backend::OneDrive { access_token } => {
+                let builder = services::Onedrive::default()
+                    .access_token(access_token)
+                    .root(&root);
+                Operator::new(builder)?.finish()
+            }
+            Backend::LocalFs { path } => {
+                if path.trim().is_empty() {
+                    return Err(BackendError::OperatorCheck(
+                        "Local folder path is empty.".into(),
+                    ));
+                }
+                // 与其他后端一致，root_path 作为该文件夹下的子目录
+                let dir = Path::new(path).join(root.trim_start_matches(['/', '\\']));
+                fs::create_dir_all(&dir)?;
+                let dir = dir.canonicalize()?;
+                let builder = services::Fs::default().root(&dir.to_string_lossy());
+                Operator::new(builder)?.finish()
             }
+        };
+        Ok(op.layer(retry_layer(cloud_settings.max_attempts)))
+    }
+
+    /// 后端配置中的凭据，用于日志脱敏
+    pub fn secrets(&self) -> Vec<&str> {
+        match self {
+            Backend::Disabled | Backend::LocalFs { .. } => Vec::new(),
+            Backend::WebDAV { password, .. } => vec![password],
+            Backend::S3 {
+                access_key_id,
+                secret_access_key,
+                ..
+            } => vec![access_key_id, secret_access_key],
+            Backend::GoogleDrive {
+                access_token,
+                refresh_token,
+                client_secret,
+                ..
+            } => vec![access_token, refresh_token, client_secret],
+            Backend::OneDrive { access_token } => vec![access_token],
         }
     }
 
-    /// 检查后端是否可用
-    pub async fn check(&self) -> Result<(), BackendError> {
+    /// 检查后端在 `root_path` 下是否可用
+    pub async fn check(&self, root_path: &str) -> Result<(), BackendError> {
         const TEST_FILENAME: &str = "test.txt";
         const TEST_CONTENT: &str = "Hello from game save manager";
         const TEST_DIR: &str = "test_dir";
 
-        let op = self.get_op()?;
+        let op = self.get_op(root_path)?;
         // Step1: 检查是否可以列出文件
         op.list(".")
             .await
-            .map_err(|_| BackendError::OperatorCheck("Failed to list files.".into()))?;
+            .map_err(check_error("Failed to list files"))?;
         // Step2: 检查是否可以创建文件
         op.write(TEST_FILENAME, TEST_CONTENT)
             .await
-            .map_err(|_| BackendError::OperatorCheck("Failed to create test file.".into()))?;
+            .map_err(check_error("Failed to create test file"))?;
         // Step3: 检查是否可以读取文件
         let text = op
             .read(TEST_FILENAME)
             .await
-            .map_err(|_| BackendError::OperatorCheck("Failed to read test file.".into()))?;
+            .map_err(check_error("Failed to read test file"))?;
         let text = String::from_utf8(text.to_vec()).map_err(|_| {
             BackendError::OperatorCheck("Failed to convert test file to string.".into())
         })?;
@@ -99,19 +213,69 @@ impl Backend {
         // Step4: 检查是否可以删除文件
         op.delete(TEST_FILENAME)
             .await
-            .map_err(|_| BackendError::OperatorCheck("Failed to delete test file.".into()))?;
+            .map_err(check_error("Failed to delete test file"))?;
         // Step5: 检查是否可以创建目录
         op.create_dir(TEST_DIR)
             .await
-            .map_err(|_| BackendError::OperatorCheck("Failed to create test directory.".into()))?;
+            .map_err(check_error("Failed to create test directory"))?;
         // Step6: 检查是否可以删除目录
         op.delete(TEST_DIR)
             .await
-            .map_err(|_| BackendError::OperatorCheck("Failed to delete test directory.".into()))?;
+            .map_err(check_error("Failed to delete test directory"))?;
         Ok(())
     }
 }
 
+/// 使用自定义 TLS 设置的 HTTP 客户端，用于自签名证书的服务器
+fn tls_http_client(
+    accept_invalid_certs: bool,
+    ca_cert_path: Option<&str>,
+) -> Result<reqwest::Client, BackendError> {
+    let mut builder = reqwest::Client::builder().danger_accept_invalid_certs(accept_invalid_certs);
+    if let Some(path) = ca_cert_path {
+        let bytes = fs::read(path)?;
+        let certs = if bytes.starts_with(b"-----BEGIN") {
+            reqwest::Certificate::from_pem_bundle(&bytes)
+        } else {
+            reqwest::Certificate::from_der(&bytes).map(|cert| vec![cert])
+        }
+        .map_err(|e| BackendError::Certificate(format!("Invalid CA certificate {path}: {e}")))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    builder
+        .build()
+        .map_err(|e| BackendError::Certificate(e.to_string()))
+}
+
+/// 错误链中是否有证书校验失败，各平台的 TLS 实现措辞不同，只能按关键字判断
+fn is_certificate_error(e: &opendal::Error) -> bool {
+    const KEYWORDS: &[&str] = &["certificate", "self signed", "self-signed", "unknownissuer"];
+    let mut source: Option<&dyn std::error::Error> = Some(e);
+    while let Some(err) = source {
+        let text = err.to_string().to_lowercase();
+        if KEYWORDS.iter().any(|k| text.contains(k)) {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// 带上 OpenDAL 的错误类型，便于区分认证失败（PermissionDenied）与网络问题（Unexpected）等
+///
+/// 证书校验失败单独返回 `BackendError::Certificate`，避免被误认为是凭据错误
+fn check_error(step: &'static str) -> impl FnOnce(opendal::Error) -> BackendError {
+    move |e| {
+        let message = redact_secrets(&e.to_string()).into_owned();
+        if is_certificate_error(&e) {
+            return BackendError::Certificate(format!("{step}: {message}"));
+        }
+        BackendError::OperatorCheck(format!("{step}: {} ({message})", e.kind()))
+    }
+}
+
 impl Sanitizable for Backend {
     fn sanitize(self) -> Self {
         match self {
@@ -120,10 +284,16 @@ impl Sanitizable for Backend {
                 endpoint,
                 username: _,
                 password: _,
+                danger_accept_invalid_certs,
+                ca_cert_path,
             } => Backend::WebDAV {
-                endpoint: endpoint.clone(),
+                // endpoint 中可能直接写了 `user:password@`
+                endpoint: redact_secrets(&endpoint).into_owned(),
                 username: "*username*".to_string(),
                 password: "*password*".to_string(),
+                // TLS 设置不属于敏感信息，保留以便排查证书问题
+                danger_accept_invalid_certs,
+                ca_cert_path,
             },
             Backend::S3 {
                 endpoint: _,
@@ -138,6 +308,47 @@ impl Sanitizable for Backend {
                 access_key_id: "*access_key_id*".to_string(),
                 secret_access_key: "*secret_access_key*".to_string(),
             },
+            Backend::GoogleDrive {
+                access_token: _,
+                refresh_token: _,
+                client_id: _,
+                client_secret: _,
+            } => Backend::GoogleDrive {
+                access_token: "*access_token*".to_string(),
+                refresh_token: "*refresh_token*".to_string(),
+                client_id: "*client_id*".to_string(),
+                client_secret: "*client_secret*".to_string(),
+            },
+            Backend::OneDrive { access_token: _ } => Backend::OneDrive {
+                access_token: "*access_token*".to_string(),
+            },
+            // 路径不属于敏感信息，保留以便排查问题
+            Backend::LocalFs { path } => Backend::LocalFs { path },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate_errors_are_found_in_the_source_chain() {
+        let tls = opendal::Error::new(opendal::ErrorKind::Unexpected, "send http request")
+            .set_source(anyhow::anyhow!(
+                "error trying to connect: certificate verify failed: self signed certificate"
+            ));
+        assert!(is_certificate_error(&tls));
+        assert!(matches!(
+            check_error("Failed to list files")(tls),
+            BackendError::Certificate(_)
+        ));
+
+        let denied = opendal::Error::new(opendal::ErrorKind::PermissionDenied, "401 Unauthorized");
+        assert!(!is_certificate_error(&denied));
+        assert!(matches!(
+            check_error("Failed to list files")(denied),
+            BackendError::OperatorCheck(_)
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use opendal::Operator;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -5,6 +6,53 @@ use crate::default_value;
 use crate::preclude::*;
 
 use super::Backend;
+use super::EncryptionSettings;
+
+/// 一个已配置的云同步后端
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct NamedBackend {
+    /// 后端名称，在所有后端中唯一
+    pub name: String,
+    /// 云同步后端设置
+    pub backend: Backend,
+    /// 该后端的云同步根目录
+    #[serde(default = "default_value::default_root_path")]
+    pub root_path: String,
+    /// 是否参与跟随云同步与定时同步
+    #[serde(default = "default_value::default_true")]
+    pub enabled: bool,
+}
+
+impl NamedBackend {
+    /// 使用默认根目录的已启用后端
+    pub fn new(name: impl Into<String>, backend: Backend) -> Self {
+        NamedBackend {
+            name: name.into(),
+            backend,
+            root_path: default_value::default_root_path(),
+            enabled: true,
+        }
+    }
+
+    /// 获取 Operator 实例，根目录为该后端的 `root_path`
+    pub fn get_op(&self) -> Result<Operator, BackendError> {
+        self.backend.get_op(&self.root_path)
+    }
+
+    /// 检查后端是否可用
+    pub async fn check(&self) -> Result<(), BackendError> {
+        self.backend.check(&self.root_path).await
+    }
+}
+
+impl Sanitizable for NamedBackend {
+    fn sanitize(self) -> Self {
+        NamedBackend {
+            backend: self.backend.sanitize(),
+            ..self
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct CloudSettings {
@@ -14,12 +62,21 @@ pub struct CloudSettings {
     /// 同步间隔，单位分钟，为0则不自动同步
     #[serde(default = "default_value::default_zero")]
     pub auto_sync_interval: u64,
-    /// 云同步根目录
-    #[serde(default = "default_value::default_root_path")]
-    pub root_path: String,
-    /// 云同步后端设置
-    #[serde(default = "default_value::default_backend")]
-    pub backend: Backend,
+    /// 已配置的云同步后端，跟随云同步与定时同步会同步到其中每个已启用的后端
+    #[serde(default)]
+    pub backends: Vec<NamedBackend>,
+    /// 主后端的名称，未指定后端的操作（如全部上传、全部下载）使用主后端
+    #[serde(default)]
+    pub primary: String,
+    /// 上传前加密存档，口令仅在运行时输入，不会写入配置
+    #[serde(default)]
+    pub encryption: Option<EncryptionSettings>,
+    /// 单个云端请求的最大尝试次数，只有限流、服务暂时不可用等临时错误会重试
+    #[serde(default = "default_value::default_cloud_max_attempts")]
+    pub max_attempts: u32,
+    /// 跟随云同步在后台同时上传的快照数量，同一游戏的存档记录只在其快照上传完后写入一次
+    #[serde(default = "default_value::default_upload_concurrency")]
+    pub upload_concurrency: u32,
 }
 
 impl Default for CloudSettings {
@@ -27,17 +84,134 @@ impl Default for CloudSettings {
         CloudSettings {
             always_sync: false,
             auto_sync_interval: 0,
-            root_path: "/game-save-manager".to_string(),
-            backend: Backend::Disabled,
+            backends: Vec::new(),
+            primary: String::new(),
+            encryption: None,
+            max_attempts: default_value::default_cloud_max_attempts(),
+            upload_concurrency: default_value::default_upload_concurrency(),
+        }
+    }
+}
+
+impl CloudSettings {
+    /// 按名称查找后端，`None` 表示主后端
+    ///
+    /// 没有配置主后端时返回 `BackendError::Disabled`
+    pub fn find_backend(&self, name: Option<&str>) -> Result<&NamedBackend, BackendError> {
+        let found = self
+            .backends
+            .iter()
+            .find(|b| b.name == name.unwrap_or(self.primary.as_str()));
+        match (found, name) {
+            (Some(backend), _) => Ok(backend),
+            (None, Some(name)) => Err(BackendError::UnknownBackend(name.to_string())),
+            (None, None) => Err(BackendError::Disabled),
         }
     }
+
+    /// 跟随云同步与定时同步使用的后端：已启用且不是 `Backend::Disabled`
+    pub fn enabled_backends(&self) -> impl Iterator<Item = &NamedBackend> {
+        self.backends
+            .iter()
+            .filter(|b| b.enabled && !matches!(b.backend, Backend::Disabled))
+    }
+
+    /// 所有后端配置中的凭据，用于日志脱敏
+    pub fn secrets(&self) -> Vec<&str> {
+        self.backends
+            .iter()
+            .flat_map(|b| b.backend.secrets())
+            .collect()
+    }
+}
+
+/// 规范化云同步根目录：统一使用 `/`，以 `/` 开头和结尾，去掉空段与首尾空白
+///
+/// 含有 `.` 或 `..` 的路径会被拒绝，避免写到根目录之外
+pub fn normalize_root_path(root: &str) -> Result<String, BackendError> {
+    let segments: Vec<&str> = root
+        .trim()
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    if segments.contains(&".") || segments.contains(&"..") {
+        return Err(BackendError::OperatorCheck(format!(
+            "Invalid cloud root path: {root}"
+        )));
+    }
+    if segments.is_empty() {
+        return Ok("/".to_string());
+    }
+    Ok(format!("/{}/", segments.join("/")))
 }
 
 impl Sanitizable for CloudSettings {
     fn sanitize(self) -> Self {
         CloudSettings {
-            backend: self.backend.sanitize(),
+            backends: self.backends.into_iter().map(|b| b.sanitize()).collect(),
             ..self
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_path_is_normalized() {
+        assert_eq!(
+            normalize_root_path("game-save-manager").unwrap(),
+            "/game-save-manager/"
+        );
+        assert_eq!(
+            normalize_root_path(" //dav\\游戏 存档/ ").unwrap(),
+            "/dav/游戏 存档/"
+        );
+        assert_eq!(normalize_root_path("").unwrap(), "/");
+        assert!(normalize_root_path("/saves/../other").is_err());
+    }
+
+    #[test]
+    fn backends_are_found_by_name_or_primary() {
+        let mut settings = CloudSettings::default();
+        assert!(matches!(
+            settings.find_backend(None),
+            Err(BackendError::Disabled)
+        ));
+        settings.backends = vec![
+            NamedBackend::new(
+                "nas",
+                Backend::LocalFs {
+                    path: "/mnt/nas".to_string(),
+                },
+            ),
+            NamedBackend {
+                enabled: false,
+                ..NamedBackend::new(
+                    "off",
+                    Backend::OneDrive {
+                        access_token: "fake-onedrive-token".to_string(),
+                    },
+                )
+            },
+            NamedBackend::new("none", Backend::Disabled),
+        ];
+        settings.primary = "nas".to_string();
+        assert_eq!(settings.find_backend(None).unwrap().name, "nas");
+        assert_eq!(settings.find_backend(Some("off")).unwrap().name, "off");
+        assert!(matches!(
+            settings.find_backend(Some("gone")),
+            Err(BackendError::UnknownBackend(name)) if name == "gone"
+        ));
+        let enabled: Vec<_> = settings
+            .enabled_backends()
+            .map(|b| b.name.as_str())
+            .collect();
+        assert_eq!(enabled, ["nas"]);
+
+        // Every entry is masked, not only the primary one
+        let sanitized = serde_json::to_string(&settings.sanitize()).unwrap();
+        assert!(!sanitized.contains("fake-onedrive-token"));
+    }
+}
@@ -0,0 +1,404 @@
+//! Client-side encryption of snapshot archives stored on the cloud backend
+//!
+//! Only the cloud copies are encrypted, local snapshots stay plain zips so
+//! restoring never needs the passphrase. Encrypted files get `ENCRYPTED_SUFFIX`
+//! appended, which lets encrypted and plain snapshots coexist in one history.
+
+use std::sync::RwLock;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use opendal::{ErrorKind, Operator};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::preclude::*;
+
+use super::CloudSettings;
+
+/// Appended to the cloud file name of encrypted snapshots, e.g. `date.zip.enc`
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+/// Header of every encrypted file, followed by the nonce and the ciphertext
+const MAGIC: &[u8] = b"RGSMENC1";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+const TAG_LEN: usize = 16;
+/// Plaintext of `EncryptionSettings::key_check`
+const KEY_CHECK: &[u8] = b"RGSM passphrase check";
+/// Size difference between an encrypted file and the zip it contains
+const ENCRYPTION_OVERHEAD: u64 = (MAGIC.len() + NONCE_LEN + TAG_LEN) as u64;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Type)]
+pub struct EncryptionSettings {
+    /// Encrypt snapshots before uploading them
+    pub enabled: bool,
+    /// Hex encoded Argon2 salt, generated when the passphrase is first set
+    #[serde(default)]
+    pub key_derivation_salt: String,
+    /// Hex encoded `KEY_CHECK` encrypted with the first key derived for the salt,
+    /// used to reject a different passphrase
+    #[serde(default)]
+    pub key_check: String,
+}
+
+struct SessionKey {
+    salt: String,
+    key: [u8; 32],
+}
+
+/// Keys derived from the passphrase, managed by the app
+///
+/// The passphrase is never written to disk, only the derived key is kept in memory
+/// for the lifetime of the process
+#[derive(Default)]
+pub struct SessionKeys {
+    key: RwLock<Option<SessionKey>>,
+}
+
+impl SessionKeys {
+    /// Derive the key from `passphrase` and keep it for later uploads and downloads
+    ///
+    /// The first passphrase set for a salt is recorded in `key_check`, which the caller
+    /// saves with the settings, and any other passphrase is rejected afterwards
+    pub fn set_passphrase(
+        &self,
+        passphrase: &str,
+        settings: &mut EncryptionSettings,
+    ) -> Result<(), BackendError> {
+        let key = derive_key(passphrase, &settings.key_derivation_salt)?;
+        if settings.key_check.is_empty() {
+            settings.key_check = encode_hex(&encrypt(KEY_CHECK, &key)?);
+        } else {
+            let check = decode_hex(&settings.key_check)
+                .ok_or_else(|| BackendError::Encryption("Invalid passphrase check".into()))?;
+            if decrypt(&check, &key, "").is_err() {
+                return Err(BackendError::PassphraseMismatch);
+            }
+        }
+        *self.key.write().expect("session key lock poisoned") = Some(SessionKey {
+            salt: settings.key_derivation_salt.clone(),
+            key,
+        });
+        Ok(())
+    }
+
+    /// Forget the key derived by `set_passphrase`
+    pub fn clear(&self) {
+        *self.key.write().expect("session key lock poisoned") = None;
+    }
+
+    /// Key derived for the configured salt, a key for an old salt is never used
+    fn key_for(&self, settings: &CloudSettings) -> Option<[u8; 32]> {
+        let salt = settings
+            .encryption
+            .as_ref()
+            .map(|e| e.key_derivation_salt.as_str())
+            .unwrap_or_default();
+        self.key
+            .read()
+            .expect("session key lock poisoned")
+            .as_ref()
+            .filter(|k| !salt.is_empty() && k.salt == salt)
+            .map(|k| k.key)
+    }
+}
+
+/// Generate a new random salt for key derivation
+pub fn generate_salt() -> String {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    encode_hex(&salt)
+}
+
+fn derive_key(passphrase: &str, salt: &str) -> Result<[u8; 32], BackendError> {
+    let salt = decode_hex(salt)
+        .ok_or_else(|| BackendError::Encryption("Invalid key derivation salt".into()))?;
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| BackendError::Encryption(e.to_string()))?;
+    Ok(key)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.is_empty() || s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn session_key(
+    settings: &CloudSettings,
+    keys: Option<&SessionKeys>,
+) -> Result<[u8; 32], BackendError> {
+    keys.and_then(|keys| keys.key_for(settings))
+        .ok_or(BackendError::PassphraseRequired)
+}
+
+fn encryption_enabled(settings: &CloudSettings) -> bool {
+    settings.encryption.as_ref().is_some_and(|e| e.enabled)
+}
+
+fn encrypt(data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, BackendError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|e| BackendError::Encryption(e.to_string()))?;
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt(data: &[u8], key: &[u8; 32], path: &str) -> Result<Vec<u8>, BackendError> {
+    let body = data
+        .strip_prefix(MAGIC)
+        .filter(|b| b.len() > NONCE_LEN)
+        .ok_or_else(|| BackendError::Encryption(format!("{path} is not an encrypted snapshot")))?;
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    // GCM authenticates the data, so a wrong key fails here instead of yielding a broken zip
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| BackendError::WrongPassphrase(path.to_string()))
+}
+
+/// Upload a snapshot zip to `path`, encrypting it first when enabled
+///
+/// The copy of the other kind is deleted afterwards, otherwise `read_snapshot` could
+/// keep downloading an outdated encrypted copy
+pub async fn write_snapshot(
+    op: &Operator,
+    settings: &CloudSettings,
+    keys: Option<&SessionKeys>,
+    path: &str,
+    data: Vec<u8>,
+) -> Result<(), BackendError> {
+    let encrypted_path = format!("{path}{ENCRYPTED_SUFFIX}");
+    if encryption_enabled(settings) {
+        let data = encrypt(&data, &session_key(settings, keys)?)?;
+        op.write(&encrypted_path, data).await?;
+        op.delete(path).await?;
+    } else {
+        op.write(path, data).await?;
+        op.delete(&encrypted_path).await?;
+    }
+    Ok(())
+}
+
+/// Delete the snapshot zip at `path` from the cloud, both its plain and encrypted copy
+///
+/// Deleting a file that does not exist succeeds, so this works whether encryption was
+/// on or off when the snapshot was uploaded
+pub async fn delete_snapshot(op: &Operator, path: &str) -> Result<(), BackendError> {
+    op.delete(path).await?;
+    op.delete(&format!("{path}{ENCRYPTED_SUFFIX}")).await?;
+    Ok(())
+}
+
+/// Move the snapshot zip at `from` to `to` on the cloud, both its plain and encrypted copy
+///
+/// A copy that does not exist is skipped, e.g. a snapshot taken before cloud sync was
+/// enabled, so replaying a finished move does nothing
+pub async fn move_snapshot(op: &Operator, from: &str, to: &str) -> Result<(), BackendError> {
+    let can_rename = op.info().full_capability().rename;
+    for suffix in ["", ENCRYPTED_SUFFIX] {
+        let source = format!("{from}{suffix}");
+        let target = format!("{to}{suffix}");
+        if !op.exists(&source).await? {
+            continue;
+        }
+        if can_rename {
+            op.rename(&source, &target).await?;
+        } else {
+            let data = op.read(&source).await?;
+            op.write(&target, data).await?;
+            op.delete(&source).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Download a snapshot zip from `path`, preferring the encrypted copy if there is one
+///
+/// Decryption does not depend on `enabled`, snapshots uploaded while encryption
+/// was on stay readable after turning it off
+pub async fn read_snapshot(
+    op: &Operator,
+    settings: &CloudSettings,
+    keys: Option<&SessionKeys>,
+    path: &str,
+) -> Result<Vec<u8>, BackendError> {
+    let encrypted_path = format!("{path}{ENCRYPTED_SUFFIX}");
+    if op.exists(&encrypted_path).await? {
+        let data = op.read(&encrypted_path).await?.to_vec();
+        decrypt(&data, &session_key(settings, keys)?, &encrypted_path)
+    } else {
+        Ok(op.read(path).await?.to_vec())
+    }
+}
+
+/// Size of the remote file at `path`, minus `overhead`, or `None` if it does not exist
+async fn stat_len(op: &Operator, path: &str, overhead: u64) -> Result<Option<u64>, BackendError> {
+    match op.stat(path).await {
+        Ok(meta) => Ok(Some(meta.content_length().saturating_sub(overhead))),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Zip size of the copy `write_snapshot` would produce for `path`, if it is already uploaded
+pub async fn uploaded_snapshot_len(
+    op: &Operator,
+    settings: &CloudSettings,
+    path: &str,
+) -> Result<Option<u64>, BackendError> {
+    if encryption_enabled(settings) {
+        stat_len(
+            op,
+            &format!("{path}{ENCRYPTED_SUFFIX}"),
+            ENCRYPTION_OVERHEAD,
+        )
+        .await
+    } else {
+        stat_len(op, path, 0).await
+    }
+}
+
+/// Zip size of the copy `read_snapshot` would download for `path`, if any
+pub async fn remote_snapshot_len(op: &Operator, path: &str) -> Result<Option<u64>, BackendError> {
+    match stat_len(
+        op,
+        &format!("{path}{ENCRYPTED_SUFFIX}"),
+        ENCRYPTION_OVERHEAD,
+    )
+    .await?
+    {
+        Some(len) => Ok(Some(len)),
+        None => stat_len(op, path, 0).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_and_wrong_key() {
+        let salt = generate_salt();
+        let key = derive_key("correct horse", &salt).unwrap();
+        let encrypted = encrypt(b"zip bytes", &key).unwrap();
+        assert!(encrypted.starts_with(MAGIC));
+        assert_eq!(encrypted.len() as u64, 9 + ENCRYPTION_OVERHEAD);
+        assert_eq!(decrypt(&encrypted, &key, "a").unwrap(), b"zip bytes");
+
+        let wrong = derive_key("battery staple", &salt).unwrap();
+        assert!(matches!(
+            decrypt(&encrypted, &wrong, "a"),
+            Err(BackendError::WrongPassphrase(_))
+        ));
+        assert!(matches!(
+            decrypt(b"PK\x03\x04plain zip", &key, "a"),
+            Err(BackendError::Encryption(_))
+        ));
+    }
+
+    #[test]
+    fn passphrase_check_rejects_a_different_passphrase() {
+        let keys = SessionKeys::default();
+        let mut settings = EncryptionSettings {
+            enabled: true,
+            key_derivation_salt: generate_salt(),
+            key_check: String::new(),
+        };
+        keys.set_passphrase("correct horse", &mut settings).unwrap();
+        assert!(!settings.key_check.is_empty());
+
+        keys.clear();
+        assert!(matches!(
+            keys.set_passphrase("battery staple", &mut settings),
+            Err(BackendError::PassphraseMismatch)
+        ));
+        keys.set_passphrase("correct horse", &mut settings).unwrap();
+    }
+
+    #[tokio::test]
+    async fn deleting_an_encrypted_snapshot_removes_the_encrypted_copy() {
+        let remote = temp_dir::TempDir::new().unwrap();
+        let op =
+            Operator::new(opendal::services::Fs::default().root(&remote.path().to_string_lossy()))
+                .unwrap()
+                .finish();
+        let keys = SessionKeys::default();
+        let mut encryption = EncryptionSettings {
+            enabled: true,
+            key_derivation_salt: generate_salt(),
+            key_check: String::new(),
+        };
+        keys.set_passphrase("correct horse", &mut encryption)
+            .unwrap();
+        let settings = CloudSettings {
+            encryption: Some(encryption),
+            ..Default::default()
+        };
+        let path = "save_data/Game/2024-01-01_00-00-00.zip";
+        let encrypted_path = format!("{path}{ENCRYPTED_SUFFIX}");
+        write_snapshot(&op, &settings, Some(&keys), path, b"zip bytes".to_vec())
+            .await
+            .unwrap();
+        assert!(op.exists(&encrypted_path).await.unwrap());
+        assert!(!op.exists(path).await.unwrap());
+
+        delete_snapshot(&op, path).await.unwrap();
+        assert!(!op.exists(&encrypted_path).await.unwrap());
+        assert_eq!(remote_snapshot_len(&op, path).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn moving_a_snapshot_moves_the_copy_that_exists() {
+        let remote = temp_dir::TempDir::new().unwrap();
+        let op =
+            Operator::new(opendal::services::Fs::default().root(&remote.path().to_string_lossy()))
+                .unwrap()
+                .finish();
+        let plain = "save_data/B/2024-01-01_00-00-00.zip";
+        let encrypted = "save_data/B/2024-01-02_00-00-00.zip";
+        op.write(plain, b"zip".to_vec()).await.unwrap();
+        op.write(&format!("{encrypted}{ENCRYPTED_SUFFIX}"), b"enc".to_vec())
+            .await
+            .unwrap();
+
+        move_snapshot(&op, plain, "save_data/A/2024-01-01_00-00-00.zip")
+            .await
+            .unwrap();
+        move_snapshot(&op, encrypted, "save_data/A/2024-01-02_00-00-00.zip")
+            .await
+            .unwrap();
+        assert!(!op.exists(plain).await.unwrap());
+        assert!(
+            op.exists("save_data/A/2024-01-01_00-00-00.zip")
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            op.read("save_data/A/2024-01-02_00-00-00.zip.enc")
+                .await
+                .unwrap()
+                .to_vec(),
+            b"enc"
+        );
+        assert_eq!(remote_snapshot_len(&op, encrypted).await.unwrap(), None);
+    }
+}
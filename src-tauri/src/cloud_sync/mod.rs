@@ -1,7 +1,41 @@
 mod backend;
 mod cloud_settings;
+mod encryption;
+mod outbox;
+mod scheduler;
+mod sync_state;
+mod upload_coordinator;
 mod utils;
 
 pub use backend::Backend;
-pub use cloud_settings::CloudSettings;
+pub use cloud_settings::{CloudSettings, NamedBackend};
+pub use encryption::{
+    ENCRYPTED_SUFFIX, EncryptionSettings, SessionKeys, delete_snapshot, generate_salt,
+    move_snapshot, read_snapshot, remote_snapshot_len, uploaded_snapshot_len, write_snapshot,
+};
+pub use outbox::{
+    OutboxEntry, OutboxFlushSummary, OutboxOperation, flush_in_background, flush_outbox,
+    get_outbox, rename_queued, sync_or_queue,
+};
+pub use scheduler::CloudSyncScheduler;
+pub use sync_state::{
+    CloudSyncState, CloudSyncStateChanged, GameSyncStatus, emit_sync_state, get_sync_status,
+    record_probe, record_sync,
+};
+pub use upload_coordinator::schedule_snapshot_upload;
 pub use utils::*;
+
+pub fn setup(app: &mut tauri::App) -> anyhow::Result<()> {
+    use tauri::Manager;
+    app.manage(SessionKeys::default());
+    sync_state::init_sync_state(app.handle())?;
+    outbox::init_outbox(app.handle())?;
+    upload_coordinator::init_upload_coordinator();
+    let minutes = crate::config::get_config()?
+        .settings
+        .cloud_settings
+        .auto_sync_interval;
+    let scheduler = CloudSyncScheduler::new(app.handle(), minutes);
+    app.manage(scheduler);
+    Ok(())
+}
@@ -0,0 +1,662 @@
+//! Durable queue of the cloud writes and deletes made by `always_sync`
+//!
+//! Each side effect is appended to `AppData/RGSM/cloud_outbox.json` once for every enabled
+//! backend and then replayed in order, so changes made while a backend is unreachable are
+//! sent once it is back instead of being lost. Backends are replayed independently, one that
+//! is down does not hold back the others. The queue is flushed on startup, periodically,
+//! after a successful backend check, and on demand. Before `init_outbox` runs, e.g. in the
+//! command line mode, operations are sent directly without queueing.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::Context;
+use log::{info, warn};
+use opendal::Operator;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::backup::{GameSnapshots, join_backup_dir};
+use crate::config::{Config, get_config};
+use crate::preclude::*;
+
+use super::{
+    CloudSyncState, NamedBackend, SessionKeys, SyncDirection, cloud_backup_dir, cloud_backup_file,
+    delete_snapshot, emit_sync_state, move_remote_folder, move_snapshot, record_sync,
+    upload_config, upload_game_snapshots, write_snapshot,
+};
+
+const OUTBOX_FILE: &str = "cloud_outbox.json";
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+static OUTBOX_PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Gives replayed uploads access to the app's `SessionKeys`
+static OUTBOX_APP: OnceLock<AppHandle> = OnceLock::new();
+/// Guards reads and writes of the queue file
+static OUTBOX_FILE_LOCK: Mutex<()> = Mutex::new(());
+/// Only one flush runs at a time, so entries are replayed in order
+static FLUSH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum OutboxOperation {
+    UploadConfig,
+    /// Upload the snapshot records of a game as they are when the entry is replayed
+    UploadRecords {
+        game: String,
+    },
+    UploadSnapshot {
+        game: String,
+        date: String,
+    },
+    DeleteSnapshot {
+        game: String,
+        date: String,
+    },
+    /// Move the cloud folder of a renamed game
+    RenameGame {
+        from: String,
+        to: String,
+    },
+    /// Move the zip of a snapshot that was moved to another game
+    MoveSnapshot {
+        from: String,
+        to: String,
+        date: String,
+    },
+}
+
+impl OutboxOperation {
+    fn remote_path(&self) -> String {
+        match self {
+            OutboxOperation::UploadConfig => "/GameSaveManager.config.json".to_string(),
+            OutboxOperation::UploadRecords { game } => cloud_backup_file(game, "Backups.json"),
+            OutboxOperation::UploadSnapshot { game, date }
+            | OutboxOperation::DeleteSnapshot { game, date } => {
+                cloud_backup_file(game, &format!("{date}.zip"))
+            }
+            OutboxOperation::RenameGame { to, .. } => cloud_backup_dir(to),
+            OutboxOperation::MoveSnapshot { to, date, .. } => {
+                cloud_backup_file(to, &format!("{date}.zip"))
+            }
+        }
+    }
+
+    /// The game whose snapshots are waiting for this operation
+    pub fn game(&self) -> Option<&str> {
+        match self {
+            OutboxOperation::UploadConfig => None,
+            OutboxOperation::UploadRecords { game }
+            | OutboxOperation::UploadSnapshot { game, .. }
+            | OutboxOperation::DeleteSnapshot { game, .. }
+            | OutboxOperation::RenameGame { to: game, .. }
+            | OutboxOperation::MoveSnapshot { to: game, .. } => Some(game),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct OutboxEntry {
+    pub operation: OutboxOperation,
+    /// Name of the backend the entry is sent to, empty in entries queued before several
+    /// backends could be configured, which go to the primary one
+    #[serde(default)]
+    pub backend: String,
+    /// The remote file written or deleted
+    pub remote_path: String,
+    /// When the entry was queued, RFC 3339
+    pub queued_at: String,
+}
+
+/// Result of replaying the queue
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Type)]
+pub struct OutboxFlushSummary {
+    pub completed: u32,
+    /// Entries whose local files no longer exist
+    pub dropped: u32,
+}
+
+/// Resolve the queue file and start the periodic flusher, whose first run is at startup
+pub fn init_outbox(app: &AppHandle) -> anyhow::Result<()> {
+    let dir = app
+        .path()
+        .resolve("RGSM", BaseDirectory::AppData)
+        .context("Failed to resolve AppData/RGSM directory")?;
+    let _ = OUTBOX_PATH.set(dir.join(OUTBOX_FILE));
+    let _ = OUTBOX_APP.set(app.clone());
+    tauri::async_runtime::spawn(async {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            flush_in_background().await;
+        }
+    });
+    Ok(())
+}
+
+/// Flush without reporting to the caller, skipped when nothing is queued or cloud is off
+pub async fn flush_in_background() {
+    let has_entries = get_outbox().is_ok_and(|entries| !entries.is_empty());
+    let enabled = get_config().is_ok_and(|c| {
+        c.settings
+            .cloud_settings
+            .enabled_backends()
+            .next()
+            .is_some()
+    });
+    if !has_entries || !enabled {
+        return;
+    }
+    if let Err(e) = flush_outbox().await {
+        info!(target:"rgsm::cloud::outbox", "Cloud outbox is still pending: {:?}", e);
+    }
+}
+
+/// A queue file cut short by a crash is restored from the copy kept by `write_atomic`
+fn load_entries(path: &Path) -> Result<Vec<OutboxEntry>, BackendError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(read_json_or_backup::<_, BackendError>(path)?.0)
+}
+
+fn save_entries(path: &Path, entries: &[OutboxEntry]) -> Result<(), BackendError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_atomic(path, serde_json::to_string_pretty(entries)?.as_bytes())?;
+    Ok(())
+}
+
+fn read_queue(path: &Path) -> Result<Vec<OutboxEntry>, BackendError> {
+    let _guard = OUTBOX_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    load_entries(path)
+}
+
+/// Queued entries in replay order
+pub fn get_outbox() -> Result<Vec<OutboxEntry>, BackendError> {
+    match OUTBOX_PATH.get() {
+        Some(path) => read_queue(path),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Append operations for `backend` that are not queued for it yet
+///
+/// `first_in_flight` is set while a flush runs. It may be replaying the first entry of
+/// the backend, which may have read the local state already, so it does not count as queued
+fn enqueue(
+    path: &Path,
+    backend: &str,
+    operations: &[OutboxOperation],
+    first_in_flight: bool,
+) -> Result<(), BackendError> {
+    let _guard = OUTBOX_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = load_entries(path)?;
+    let queued_at = chrono::Local::now().to_rfc3339();
+    for operation in operations {
+        let skip = usize::from(first_in_flight);
+        if entries
+            .iter()
+            .filter(|e| e.backend == backend)
+            .skip(skip)
+            .any(|e| e.operation == *operation)
+        {
+            continue;
+        }
+        entries.push(OutboxEntry {
+            remote_path: operation.remote_path(),
+            operation: operation.clone(),
+            backend: backend.to_string(),
+            queued_at: queued_at.clone(),
+        });
+    }
+    save_entries(path, &entries)
+}
+
+/// Point the queued uploads of the game `from` at the game `to` after a local rename or move
+///
+/// Uploads read the local files when they are replayed, so uploads still queued under the
+/// old name would be dropped as deleted. With `dates` only the uploads of those snapshots
+/// are rewritten. Without them the whole game was renamed and its records uploads are
+/// dropped too, the caller queues one after the cloud folder is moved.
+pub fn rename_queued(from: &str, to: &str, dates: Option<&[String]>) -> Result<(), BackendError> {
+    match OUTBOX_PATH.get() {
+        Some(path) => rewrite_queued(path, from, to, dates, FLUSH_LOCK.try_lock().is_err()),
+        None => Ok(()),
+    }
+}
+
+/// With `first_in_flight` the first entry of each backend is left to the running flush,
+/// which removes it itself, and its rewritten copy is queued right after it
+fn rewrite_queued(
+    path: &Path,
+    from: &str,
+    to: &str,
+    dates: Option<&[String]>,
+    first_in_flight: bool,
+) -> Result<(), BackendError> {
+    let _guard = OUTBOX_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let entries = load_entries(path)?;
+    let mut rewritten = Vec::with_capacity(entries.len());
+    let mut seen_backends: Vec<String> = Vec::new();
+    for entry in entries {
+        let first = !seen_backends.contains(&entry.backend);
+        if first {
+            seen_backends.push(entry.backend.clone());
+        }
+        let replacement = match &entry.operation {
+            OutboxOperation::UploadSnapshot { game, date }
+                if game == from && dates.is_none_or(|dates| dates.contains(date)) =>
+            {
+                Some(OutboxOperation::UploadSnapshot {
+                    game: to.to_string(),
+                    date: date.clone(),
+                })
+            }
+            OutboxOperation::UploadRecords { game } if game == from && dates.is_none() => None,
+            _ => {
+                rewritten.push(entry);
+                continue;
+            }
+        };
+        if first && first_in_flight {
+            rewritten.push(entry.clone());
+        }
+        if let Some(operation) = replacement {
+            rewritten.push(OutboxEntry {
+                remote_path: operation.remote_path(),
+                operation,
+                backend: entry.backend,
+                queued_at: entry.queued_at,
+            });
+        }
+    }
+    save_entries(path, &rewritten)
+}
+
+/// Remove the first entry of `backend` after it was replayed, entries queued meanwhile are kept
+fn remove_first_of(path: &Path, backend: &str) -> Result<(), BackendError> {
+    let _guard = OUTBOX_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = load_entries(path)?;
+    if let Some(index) = entries.iter().position(|e| e.backend == backend) {
+        entries.remove(index);
+    }
+    save_entries(path, &entries)
+}
+
+/// Returns `false` when the entry is dropped because its local files are gone
+async fn replay(
+    op: &Operator,
+    backend: &str,
+    config: &Config,
+    entry: &OutboxEntry,
+) -> Result<bool, BackendError> {
+    match &entry.operation {
+        OutboxOperation::UploadConfig => upload_config(op).await?,
+        OutboxOperation::UploadRecords { game } => {
+            // A new game uploads its records before it is in the config, so check the file instead
+            let records = join_backup_dir(config, game).join("Backups.json");
+            if !records.exists() {
+                info!(target:"rgsm::cloud::outbox", "Dropping records upload of deleted game {}", game);
+                return Ok(false);
+            }
+            let info: GameSnapshots = serde_json::from_slice(&fs::read(&records)?)?;
+            upload_game_snapshots(op, info).await?;
+        }
+        OutboxOperation::UploadSnapshot { game, date } => {
+            let local_path = join_backup_dir(config, game).join(format!("{date}.zip"));
+            if !local_path.exists() {
+                info!(target:"rgsm::cloud::outbox", "Dropping upload of deleted snapshot {} of {}", date, game);
+                return Ok(false);
+            }
+            let data = fs::read(&local_path)?;
+            let keys = OUTBOX_APP
+                .get()
+                .and_then(|app| app.try_state::<SessionKeys>());
+            write_snapshot(
+                op,
+                &config.settings.cloud_settings,
+                keys.as_deref(),
+                &entry.remote_path,
+                data,
+            )
+            .await?;
+            record_sync(game, backend, SyncDirection::Upload, Some(date));
+        }
+        OutboxOperation::DeleteSnapshot { .. } => delete_snapshot(op, &entry.remote_path).await?,
+        OutboxOperation::RenameGame { from, .. } => {
+            move_remote_folder(op, &cloud_backup_dir(from), &entry.remote_path).await?
+        }
+        OutboxOperation::MoveSnapshot { from, date, .. } => {
+            let source = cloud_backup_file(from, &format!("{date}.zip"));
+            move_snapshot(op, &source, &entry.remote_path).await?
+        }
+    }
+    Ok(true)
+}
+
+/// Replay queued entries in order, each backend stops at its first entry that fails
+///
+/// Entries of a backend removed from the settings are dropped, those of a disabled one
+/// are kept until it is enabled again. The first failure is returned after the other
+/// backends are flushed.
+async fn flush(path: &Path) -> Result<OutboxFlushSummary, BackendError> {
+    let _flush = FLUSH_LOCK.lock().await;
+    let mut summary = OutboxFlushSummary::default();
+    // Backends whose remaining entries wait for the next flush
+    let mut stopped: Vec<String> = Vec::new();
+    let mut error = None;
+    loop {
+        let next = read_queue(path)?
+            .into_iter()
+            .find(|e| !stopped.contains(&e.backend));
+        let Some(entry) = next else {
+            break;
+        };
+        // Reload for every entry, the config may change while the queue is replayed
+        let config = get_config()?;
+        let name = Some(entry.backend.as_str()).filter(|name| !name.is_empty());
+        let result = match config.settings.cloud_settings.find_backend(name) {
+            Ok(backend) if !backend.enabled => {
+                stopped.push(entry.backend);
+                continue;
+            }
+            Ok(backend) => match backend.get_op() {
+                Ok(op) => replay(&op, &backend.name, &config, &entry).await,
+                Err(e) => Err(e),
+            },
+            Err(BackendError::UnknownBackend(_)) => {
+                info!(target:"rgsm::cloud::outbox", "Dropping {:?} for removed backend {}", entry.operation, entry.backend);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(true) => summary.completed += 1,
+            Ok(false) => summary.dropped += 1,
+            Err(e) => {
+                warn!(target:"rgsm::cloud::outbox", "Stopped replaying for backend {:?}: {:?}", entry.backend, e);
+                stopped.push(entry.backend);
+                error.get_or_insert(e);
+                continue;
+            }
+        }
+        remove_first_of(path, &entry.backend)?;
+    }
+    match error {
+        Some(e) => Err(e),
+        None => Ok(summary),
+    }
+}
+
+/// Replay the queue now
+pub async fn flush_outbox() -> Result<OutboxFlushSummary, BackendError> {
+    let Some(path) = OUTBOX_PATH.get() else {
+        return Ok(OutboxFlushSummary::default());
+    };
+    emit_sync_state(None, SyncDirection::Upload, CloudSyncState::Started, None);
+    let result = flush(path).await;
+    match &result {
+        Ok(summary) => {
+            info!(target:"rgsm::cloud::outbox", "Cloud outbox flushed: {:?}", summary);
+            emit_sync_state(None, SyncDirection::Upload, CloudSyncState::Finished, None);
+        }
+        Err(e) => emit_sync_state(
+            None,
+            SyncDirection::Upload,
+            CloudSyncState::Failed,
+            Some(e.to_string()),
+        ),
+    }
+    result
+}
+
+/// Send `operations` to every enabled backend after everything queued before them
+///
+/// On failure they stay queued for the next flush. The error is logged here, callers
+/// whose local change already succeeded can ignore it.
+pub async fn sync_or_queue(operations: Vec<OutboxOperation>) -> Result<(), BackendError> {
+    let config = get_config()?;
+    let backends = config.settings.cloud_settings.enabled_backends();
+    let Some(path) = OUTBOX_PATH.get() else {
+        for backend in backends {
+            for operation in &operations {
+                send_now(backend, operation).await?;
+            }
+        }
+        return Ok(());
+    };
+    let first_in_flight = FLUSH_LOCK.try_lock().is_err();
+    for backend in backends {
+        enqueue(path, &backend.name, &operations, first_in_flight)?;
+    }
+    flush(path).await.map(|_| ()).inspect_err(|e| {
+        warn!(target:"rgsm::cloud::outbox", "Cloud operations queued for later: {:?}", e);
+    })
+}
+
+/// Send `operation` to `backend` right away without queueing it
+pub(super) async fn send_now(
+    backend: &NamedBackend,
+    operation: &OutboxOperation,
+) -> Result<(), BackendError> {
+    let config = get_config()?;
+    let entry = OutboxEntry {
+        remote_path: operation.remote_path(),
+        operation: operation.clone(),
+        backend: backend.name.clone(),
+        queued_at: String::new(),
+    };
+    replay(&backend.get_op()?, &backend.name, &config, &entry)
+        .await
+        .map(|_| ())
+}
+
+/// Queue `operations` for `backend` without sending them, the next flush retries them
+pub(super) fn queue_for_retry(
+    backend: &str,
+    operations: &[OutboxOperation],
+) -> Result<(), BackendError> {
+    match OUTBOX_PATH.get() {
+        Some(path) => enqueue(path, backend, operations, FLUSH_LOCK.try_lock().is_err()),
+        None => Ok(()),
+    }
+}
+
+/// Remove the entry of `operation` for `backend` after `send_now` sent it
+pub(super) fn remove_sent(
+    backend: &str,
+    operation: &OutboxOperation,
+    sent_at: chrono::DateTime<chrono::Local>,
+) -> Result<(), BackendError> {
+    match OUTBOX_PATH.get() {
+        Some(path) => remove_queued(
+            path,
+            backend,
+            operation,
+            sent_at,
+            FLUSH_LOCK.try_lock().is_err(),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Remove the first entry of `operation` for `backend` queued before `sent_at`
+///
+/// An entry queued later may stand for newer local state and is kept. With
+/// `first_in_flight` the first entry of the backend is left to the running flush,
+/// which may be replaying it and removes it itself.
+fn remove_queued(
+    path: &Path,
+    backend: &str,
+    operation: &OutboxOperation,
+    sent_at: chrono::DateTime<chrono::Local>,
+    first_in_flight: bool,
+) -> Result<(), BackendError> {
+    let _guard = OUTBOX_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = load_entries(path)?;
+    let index = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.backend == backend)
+        .skip(usize::from(first_in_flight))
+        .find(|(_, e)| {
+            e.operation == *operation
+                && chrono::DateTime::parse_from_rfc3339(&e.queued_at).is_ok_and(|t| t <= sent_at)
+        })
+        .map(|(i, _)| i);
+    let Some(index) = index else {
+        return Ok(());
+    };
+    entries.remove(index);
+    save_entries(path, &entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_queued_once_per_backend_and_removed_in_order() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let path = dir.path().join(OUTBOX_FILE);
+        let upload = OutboxOperation::UploadSnapshot {
+            game: "Celeste".to_string(),
+            date: "2024-01-01_00-00-00".to_string(),
+        };
+        let records = OutboxOperation::UploadRecords {
+            game: "Celeste".to_string(),
+        };
+        enqueue(&path, "nas", &[records.clone(), upload.clone()], false).unwrap();
+        enqueue(
+            &path,
+            "nas",
+            &[records.clone(), OutboxOperation::UploadConfig],
+            false,
+        )
+        .unwrap();
+
+        let entries = load_entries(&path).unwrap();
+        let operations: Vec<_> = entries.iter().map(|e| e.operation.clone()).collect();
+        assert_eq!(
+            operations,
+            vec![records.clone(), upload, OutboxOperation::UploadConfig]
+        );
+        assert_eq!(
+            entries[1].remote_path,
+            "save_data/Celeste/2024-01-01_00-00-00.zip"
+        );
+
+        // Every backend gets its own copy of an operation
+        enqueue(&path, "s3", std::slice::from_ref(&records), false).unwrap();
+        assert_eq!(load_entries(&path).unwrap().len(), 4);
+
+        // The records being uploaded may be stale, so they are queued again
+        enqueue(&path, "nas", std::slice::from_ref(&records), true).unwrap();
+        assert_eq!(load_entries(&path).unwrap().len(), 5);
+        remove_first_of(&path, "s3").unwrap();
+        remove_first_of(&path, "nas").unwrap();
+        let entries = load_entries(&path).unwrap();
+        assert!(entries.iter().all(|e| e.backend == "nas"));
+        assert_eq!(entries[2].operation, records);
+    }
+
+    #[test]
+    fn sent_entries_are_removed_unless_queued_later_or_in_flight() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let path = dir.path().join(OUTBOX_FILE);
+        let upload = OutboxOperation::UploadSnapshot {
+            game: "Celeste".to_string(),
+            date: "2024-01-01_00-00-00".to_string(),
+        };
+        let records = OutboxOperation::UploadRecords {
+            game: "Celeste".to_string(),
+        };
+        enqueue(&path, "nas", &[upload.clone(), records.clone()], false).unwrap();
+        let sent_at = chrono::Local::now();
+
+        // The running flush may be replaying the first entry
+        remove_queued(&path, "nas", &upload, sent_at, true).unwrap();
+        assert_eq!(load_entries(&path).unwrap().len(), 2);
+        remove_queued(&path, "nas", &upload, sent_at, false).unwrap();
+        let entries = load_entries(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation, records);
+
+        // Records queued after the upload started are newer than what was sent
+        let before = sent_at - chrono::Duration::seconds(1);
+        remove_queued(&path, "nas", &records, before, false).unwrap();
+        assert_eq!(load_entries(&path).unwrap().len(), 1);
+        remove_queued(&path, "s3", &records, sent_at, false).unwrap();
+        assert_eq!(load_entries(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn queued_uploads_follow_renamed_games_and_moved_snapshots() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let path = dir.path().join(OUTBOX_FILE);
+        let upload = |game: &str, date: &str| OutboxOperation::UploadSnapshot {
+            game: game.to_string(),
+            date: date.to_string(),
+        };
+        let records = |game: &str| OutboxOperation::UploadRecords {
+            game: game.to_string(),
+        };
+        let delete = OutboxOperation::DeleteSnapshot {
+            game: "B".to_string(),
+            date: "2024-01-03_00-00-00".to_string(),
+        };
+        enqueue(
+            &path,
+            "nas",
+            &[
+                upload("B", "2024-01-01_00-00-00"),
+                upload("B", "2024-01-02_00-00-00"),
+                records("B"),
+                delete.clone(),
+            ],
+            false,
+        )
+        .unwrap();
+
+        let moved = ["2024-01-02_00-00-00".to_string()];
+        rewrite_queued(&path, "B", "A", Some(&moved), false).unwrap();
+        let entries = load_entries(&path).unwrap();
+        let operations: Vec<_> = entries.iter().map(|e| e.operation.clone()).collect();
+        assert_eq!(
+            operations,
+            vec![
+                upload("B", "2024-01-01_00-00-00"),
+                upload("A", "2024-01-02_00-00-00"),
+                records("B"),
+                delete.clone(),
+            ]
+        );
+        assert_eq!(
+            entries[1].remote_path,
+            "save_data/A/2024-01-02_00-00-00.zip"
+        );
+
+        // The running flush may be replaying the first entry, it is kept besides its copy
+        rewrite_queued(&path, "B", "C", None, true).unwrap();
+        let operations: Vec<_> = load_entries(&path)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.operation)
+            .collect();
+        assert_eq!(
+            operations,
+            vec![
+                upload("B", "2024-01-01_00-00-00"),
+                upload("C", "2024-01-01_00-00-00"),
+                upload("A", "2024-01-02_00-00-00"),
+                delete,
+            ]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::Duration;
+
+use log::{info, warn};
+use rust_i18n::t;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::{self, Instant, Interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::get_config;
+use crate::ipc_handler::{IpcNotification, NotificationLevel};
+use crate::preclude::*;
+
+use super::{SyncSummary, upload_all};
+
+enum CloudSyncCommand {
+    UpdateInterval { minutes: u64 },
+}
+
+/// Periodically uploads everything to the cloud, independent of `always_sync`
+///
+/// The interval comes from `CloudSettings.auto_sync_interval` (minutes, 0 disables it)
+/// and is updated whenever the config is saved
+pub struct CloudSyncScheduler {
+    command_tx: UnboundedSender<CloudSyncCommand>,
+    cancel_token: CancellationToken,
+}
+
+impl Drop for CloudSyncScheduler {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+    }
+}
+
+impl CloudSyncScheduler {
+    pub fn new(app: &AppHandle, minutes: u64) -> Self {
+        let cancel_token = CancellationToken::new();
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+
+        CloudSyncWorker::spawn(app.clone(), command_rx, cancel_token.clone());
+        let scheduler = Self {
+            command_tx,
+            cancel_token,
+        };
+        scheduler.update_interval(minutes);
+        scheduler
+    }
+
+    pub fn update_interval(&self, minutes: u64) {
+        if let Err(err) = self
+            .command_tx
+            .send(CloudSyncCommand::UpdateInterval { minutes })
+        {
+            warn!(target: "rgsm::cloud::scheduler", "Failed to send UpdateInterval command: {err}");
+        }
+    }
+}
+
+struct CloudSyncWorker {
+    app: AppHandle,
+    command_rx: UnboundedReceiver<CloudSyncCommand>,
+    cancel_token: CancellationToken,
+    minutes: u64,
+    interval: Option<Interval>,
+    syncing: Arc<AtomicBool>,
+}
+
+impl CloudSyncWorker {
+    fn spawn(
+        app: AppHandle,
+        command_rx: UnboundedReceiver<CloudSyncCommand>,
+        cancel_token: CancellationToken,
+    ) {
+        let mut worker = Self {
+            app,
+            command_rx,
+            cancel_token,
+            minutes: 0,
+            interval: None,
+            syncing: Arc::new(AtomicBool::new(false)),
+        };
+
+        tauri::async_runtime::spawn(async move { worker.run().await });
+    }
+
+    async fn run(&mut self) {
+        loop {
+            if let Some(interval) = self.interval.as_mut() {
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => break,
+                    _ = interval.tick() => self.handle_tick(),
+                    cmd = self.command_rx.recv() => {
+                        match cmd {
+                            Some(cmd) => self.handle_command(cmd),
+                            None => break,
+                        }
+                    }
+                }
+            } else {
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => break,
+                    cmd = self.command_rx.recv() => {
+                        match cmd {
+                            Some(cmd) => self.handle_command(cmd),
+                            None => break,
+                        }
+                    }
+                }
+            }
+        }
+        info!(
+            target: "rgsm::cloud::scheduler",
+            "CloudSyncWorker received cancel signal or channel closed, shutting down gracefully"
+        );
+    }
+
+    fn handle_command(&mut self, command: CloudSyncCommand) {
+        match command {
+            CloudSyncCommand::UpdateInterval { minutes } => {
+                // Saving unrelated settings must not push the next sync further away
+                if minutes == self.minutes {
+                    return;
+                }
+                self.minutes = minutes;
+                self.interval = (minutes > 0).then(|| {
+                    let period = Duration::from_secs(minutes * 60);
+                    let mut interval = time::interval_at(Instant::now() + period, period);
+                    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                    interval
+                });
+                info!(target: "rgsm::cloud::scheduler", "Scheduled sync interval set to {minutes} minutes");
+            }
+        }
+    }
+
+    fn handle_tick(&self) {
+        if self.syncing.swap(true, Ordering::AcqRel) {
+            info!(target: "rgsm::cloud::scheduler", "Previous scheduled sync still running, skipping this cycle");
+            return;
+        }
+        let app = self.app.clone();
+        let syncing = Arc::clone(&self.syncing);
+        tauri::async_runtime::spawn(async move {
+            info!(target: "rgsm::cloud::scheduler", "Running scheduled sync");
+            match sync_once(&app).await {
+                Ok(Some(summary)) if summary.failed > 0 => {
+                    warn!(target: "rgsm::cloud::scheduler", "Scheduled sync finished with {} failures", summary.failed);
+                    let _ = app.emit(
+                        "Notification",
+                        IpcNotification {
+                            level: NotificationLevel::warning,
+                            title: t!("backend.notification.warning").to_string(),
+                            msg: t!(
+                                "backend.cloud.scheduled_sync_partial",
+                                failed = summary.failed
+                            )
+                            .to_string(),
+                        },
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(target: "rgsm::cloud::scheduler", "Scheduled sync failed: {e:?}");
+                    let _ = app.emit(
+                        "Notification",
+                        IpcNotification {
+                            level: NotificationLevel::error,
+                            title: t!("backend.notification.error").to_string(),
+                            msg: t!("backend.cloud.scheduled_sync_failed", error = e.to_string())
+                                .to_string(),
+                        },
+                    );
+                }
+            }
+            syncing.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Upload to every enabled backend, returns `None` when cloud sync is disabled
+async fn sync_once(app: &AppHandle) -> Result<Option<SyncSummary>, BackendError> {
+    let config = get_config()?;
+    let mut total = None;
+    for backend in config.settings.cloud_settings.enabled_backends() {
+        let summary = upload_all(backend, Some(app)).await?;
+        let sum = total.get_or_insert_with(SyncSummary::default);
+        sum.transferred += summary.transferred;
+        sum.skipped += summary.skipped;
+        sum.failed += summary.failed;
+    }
+    // Nothing to sync to, not worth a notification every cycle
+    Ok(total)
+}
@@ -0,0 +1,252 @@
+//! Per game record of the last successful cloud sync
+//!
+//! Kept in `AppData/RGSM/sync_state.json` instead of the config, because the config is
+//! itself uploaded and would otherwise change with every sync.
+//! Nothing is recorded before `init_sync_state` runs, e.g. in the command line mode.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+
+use crate::config::Config;
+
+use super::{SyncDirection, get_outbox};
+
+const SYNC_STATE_FILE: &str = "sync_state.json";
+
+struct SyncStateStore {
+    app: AppHandle,
+    path: PathBuf,
+    /// Serializes the read-modify-write of the file
+    lock: Mutex<()>,
+}
+
+static SYNC_STATE: OnceLock<SyncStateStore> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct GameSyncRecord {
+    last_upload: Option<String>,
+    last_download: Option<String>,
+    remote_probe_time: Option<String>,
+    /// Latest snapshot present on both sides after the last successful sync
+    synced_snapshot: Option<String>,
+    /// Last successful upload to each backend, by backend name
+    #[serde(default)]
+    backend_uploads: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SyncState {
+    #[serde(default)]
+    games: HashMap<String, GameSyncRecord>,
+}
+
+/// Cloud sync status of one game, times are RFC 3339
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GameSyncStatus {
+    pub name: String,
+    pub last_upload: Option<String>,
+    pub last_download: Option<String>,
+    /// The game has snapshots newer than the last successful sync
+    pub local_newer: bool,
+    /// Last time the remote copy of this game was reached, even if the sync then failed
+    pub remote_probe_time: Option<String>,
+    /// Changes of the game are waiting in the cloud outbox
+    pub pending_upload: bool,
+    /// Last successful upload to each backend, by backend name
+    pub backend_uploads: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum CloudSyncState {
+    Started,
+    Finished,
+    Failed,
+}
+
+/// Emitted when a cloud operation starts, finishes or fails
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct CloudSyncStateChanged {
+    /// `None` for operations covering all games
+    pub game: Option<String>,
+    pub direction: SyncDirection,
+    pub state: CloudSyncState,
+    pub error: Option<String>,
+}
+
+/// Resolve the state file and keep the app handle used to emit `CloudSyncStateChanged`
+pub fn init_sync_state(app: &AppHandle) -> Result<()> {
+    let dir = app
+        .path()
+        .resolve("RGSM", BaseDirectory::AppData)
+        .context("Failed to resolve AppData/RGSM directory")?;
+    let _ = SYNC_STATE.set(SyncStateStore {
+        app: app.clone(),
+        path: dir.join(SYNC_STATE_FILE),
+        lock: Mutex::new(()),
+    });
+    Ok(())
+}
+
+fn load_state(store: &SyncStateStore) -> Result<SyncState> {
+    if !store.path.exists() {
+        return Ok(SyncState::default());
+    }
+    let text = fs::read_to_string(&store.path)
+        .with_context(|| format!("Failed to read sync state at {}", store.path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse sync state at {}", store.path.display()))
+}
+
+fn save_state(store: &SyncStateStore, state: &SyncState) -> Result<()> {
+    if let Some(dir) = store.path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create data dir at {}", dir.display()))?;
+    }
+    let text = serde_json::to_string_pretty(state).context("Failed to serialize sync state")?;
+    fs::write(&store.path, text)
+        .with_context(|| format!("Failed to write sync state at {}", store.path.display()))
+}
+
+fn update_record(game: &str, update: impl FnOnce(&mut GameSyncRecord)) {
+    let Some(store) = SYNC_STATE.get() else {
+        return;
+    };
+    let _guard = store.lock.lock().unwrap_or_else(|e| e.into_inner());
+    let result = load_state(store).and_then(|mut state| {
+        update(state.games.entry(game.to_string()).or_default());
+        save_state(store, &state)
+    });
+    if let Err(e) = result {
+        warn!(target:"rgsm::cloud::sync_state", "Failed to record sync state of {}: {:?}", game, e);
+    }
+}
+
+/// Record a successful sync of `game` with `backend`, `latest_snapshot` is its newest
+/// snapshot afterwards
+pub fn record_sync(
+    game: &str,
+    backend: &str,
+    direction: SyncDirection,
+    latest_snapshot: Option<&str>,
+) {
+    let now = chrono::Local::now().to_rfc3339();
+    update_record(game, |record| {
+        match direction {
+            SyncDirection::Upload => {
+                record.last_upload = Some(now.clone());
+                record
+                    .backend_uploads
+                    .insert(backend.to_string(), now.clone());
+            }
+            SyncDirection::Download => record.last_download = Some(now.clone()),
+        }
+        record.remote_probe_time = Some(now);
+        record.synced_snapshot = latest_snapshot.map(str::to_string);
+    });
+}
+
+/// Record that the remote copy of `game` was reached without completing a sync
+pub fn record_probe(game: &str) {
+    let now = chrono::Local::now().to_rfc3339();
+    update_record(game, |record| record.remote_probe_time = Some(now));
+}
+
+/// Tell the frontend about a cloud operation, does nothing before `init_sync_state`
+pub fn emit_sync_state(
+    game: Option<&str>,
+    direction: SyncDirection,
+    state: CloudSyncState,
+    error: Option<String>,
+) {
+    if let Some(store) = SYNC_STATE.get() {
+        let _ = CloudSyncStateChanged {
+            game: game.map(str::to_string),
+            direction,
+            state,
+            error,
+        }
+        .emit(&store.app);
+    }
+}
+
+fn status_of(
+    name: &str,
+    latest_snapshot: Option<&str>,
+    record: GameSyncRecord,
+    pending_upload: bool,
+) -> GameSyncStatus {
+    GameSyncStatus {
+        name: name.to_string(),
+        local_newer: latest_snapshot.is_some()
+            && latest_snapshot != record.synced_snapshot.as_deref(),
+        last_upload: record.last_upload,
+        last_download: record.last_download,
+        remote_probe_time: record.remote_probe_time,
+        pending_upload,
+        backend_uploads: record.backend_uploads,
+    }
+}
+
+/// Sync status of every game in `config`
+pub fn get_sync_status(config: &Config) -> Result<Vec<GameSyncStatus>> {
+    let mut state = match SYNC_STATE.get() {
+        Some(store) => {
+            let _guard = store.lock.lock().unwrap_or_else(|e| e.into_inner());
+            load_state(store)?
+        }
+        None => SyncState::default(),
+    };
+    let outbox = get_outbox()?;
+    let statuses = config
+        .games
+        .iter()
+        .map(|game| {
+            let latest = game
+                .get_game_snapshots_info()
+                .ok()
+                .and_then(|mut info| info.backups.pop())
+                .map(|snapshot| snapshot.date);
+            let record = state.games.remove(&game.name).unwrap_or_default();
+            let pending = outbox
+                .iter()
+                .any(|entry| entry.operation.game() == Some(game.name.as_str()));
+            status_of(&game.name, latest.as_deref(), record, pending)
+        })
+        .collect();
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_newer_follows_the_synced_snapshot() {
+        let record = GameSyncRecord {
+            synced_snapshot: Some("2024-01-01_00-00-00".to_string()),
+            ..Default::default()
+        };
+        assert!(!status_of("a", Some("2024-01-01_00-00-00"), record.clone(), false).local_newer);
+        assert!(status_of("a", Some("2024-01-02_00-00-00"), record, false).local_newer);
+        assert!(
+            status_of(
+                "a",
+                Some("2024-01-01_00-00-00"),
+                GameSyncRecord::default(),
+                false
+            )
+            .local_newer
+        );
+        assert!(!status_of("a", None, GameSyncRecord::default(), false).local_newer);
+    }
+}
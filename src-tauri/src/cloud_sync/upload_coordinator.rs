@@ -0,0 +1,337 @@
+//! Background uploads of the snapshots created with `always_sync`
+//!
+//! `create_snapshot` hands the upload off here instead of awaiting it, so taking a
+//! snapshot does not wait for the network. Zips go through a queue that runs at most
+//! `upload_concurrency` uploads at once. The records of a game are uploaded once after
+//! its last queued zip, reading `Backups.json` at that time, so snapshots taken in quick
+//! succession write it once with the newest content. Handed off uploads are queued in the
+//! cloud outbox right away and removed once sent, so uploads cut short by quitting the app
+//! or by a failure are retried by the next flush. Before `init_upload_coordinator` runs,
+//! e.g. in the command line mode, nothing is handed off and callers upload inline.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::OnceLock;
+
+use log::warn;
+use rust_i18n::t;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::{Id, JoinError, JoinSet};
+
+use crate::config::get_config;
+use crate::preclude::*;
+
+use super::outbox::{queue_for_retry, remove_sent, send_now};
+use super::{CloudSyncState, OutboxOperation, SyncDirection, emit_sync_state};
+
+static COORDINATOR: OnceLock<UnboundedSender<SnapshotUpload>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SnapshotUpload {
+    game: String,
+    date: String,
+}
+
+impl SnapshotUpload {
+    fn operation(&self) -> OutboxOperation {
+        OutboxOperation::UploadSnapshot {
+            game: self.game.clone(),
+            date: self.date.clone(),
+        }
+    }
+}
+
+/// What to do after the records upload of a game finished
+#[derive(Debug, PartialEq, Eq)]
+enum RecordsFinished {
+    /// The records changed meanwhile and are uploaded again
+    Again,
+    /// Nothing is left to upload for the game, with the first error of its uploads
+    Done(Option<String>),
+}
+
+/// Bookkeeping of the coordinator, the uploads themselves run in its tasks
+#[derive(Debug, Default)]
+struct UploadQueue {
+    /// Zips waiting for a free slot, in the order their snapshots were taken
+    pending: VecDeque<SnapshotUpload>,
+    uploading: usize,
+    /// Zips of each game that are queued or uploading
+    outstanding: HashMap<String, usize>,
+    records_running: HashSet<String>,
+    /// Games whose records changed while their upload was running
+    records_dirty: HashSet<String>,
+    /// First error of each game since its uploads were last reported
+    errors: HashMap<String, String>,
+}
+
+impl UploadQueue {
+    fn push(&mut self, upload: SnapshotUpload) {
+        *self.outstanding.entry(upload.game.clone()).or_default() += 1;
+        self.pending.push_back(upload);
+    }
+
+    /// Zips to start now, so that at most `limit` are uploading
+    fn start_zips(&mut self, limit: usize) -> Vec<SnapshotUpload> {
+        let mut started = Vec::new();
+        while self.uploading < limit.max(1) {
+            let Some(upload) = self.pending.pop_front() else {
+                break;
+            };
+            self.uploading += 1;
+            started.push(upload);
+        }
+        started
+    }
+
+    /// Returns whether the records of `game` should be uploaded now
+    fn zip_finished(&mut self, game: &str, error: Option<String>) -> bool {
+        self.uploading = self.uploading.saturating_sub(1);
+        if let Some(error) = error {
+            self.errors.entry(game.to_string()).or_insert(error);
+        }
+        let Some(count) = self.outstanding.get_mut(game) else {
+            return false;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return false;
+        }
+        self.outstanding.remove(game);
+        if self.records_running.contains(game) {
+            self.records_dirty.insert(game.to_string());
+            return false;
+        }
+        self.records_running.insert(game.to_string());
+        true
+    }
+
+    fn records_finished(&mut self, game: &str, error: Option<String>) -> RecordsFinished {
+        if let Some(error) = error {
+            self.errors.entry(game.to_string()).or_insert(error);
+        }
+        if self.records_dirty.remove(game) {
+            return RecordsFinished::Again;
+        }
+        self.records_running.remove(game);
+        RecordsFinished::Done(self.errors.remove(game))
+    }
+}
+
+/// Start the coordinator task, needs a running async runtime
+pub fn init_upload_coordinator() {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    if COORDINATOR.set(sender).is_ok() {
+        tauri::async_runtime::spawn(run(receiver));
+    }
+}
+
+/// Hand the upload of a new snapshot to the coordinator
+///
+/// Returns `false` when the coordinator is not running, the caller should upload inline
+pub fn schedule_snapshot_upload(game: &str, date: &str) -> bool {
+    let Some(sender) = COORDINATOR.get() else {
+        return false;
+    };
+    let upload = SnapshotUpload {
+        game: game.to_string(),
+        date: date.to_string(),
+    };
+    persist(&upload);
+    sender.send(upload).is_ok()
+}
+
+/// Queue the zip and records uploads in the outbox until they are sent
+fn persist(upload: &SnapshotUpload) {
+    let operations = [
+        upload.operation(),
+        OutboxOperation::UploadRecords {
+            game: upload.game.clone(),
+        },
+    ];
+    let config = match get_config() {
+        Ok(config) => config,
+        Err(e) => {
+            warn!(target:"rgsm::cloud::upload", "Failed to queue {:?}: {:?}", operations, e);
+            return;
+        }
+    };
+    for backend in config.settings.cloud_settings.enabled_backends() {
+        if let Err(e) = queue_for_retry(&backend.name, &operations) {
+            warn!(target:"rgsm::cloud::upload", "Failed to queue {:?} for {}: {:?}", operations, backend.name, e);
+        }
+    }
+}
+
+/// Game and error of a finished upload task, a task that panicked counts as failed
+///
+/// `games` maps the running tasks to their games, so a failed task still frees its slot
+fn finished_task(
+    finished: Result<(Id, Option<String>), JoinError>,
+    games: &mut HashMap<Id, String>,
+) -> Option<(String, Option<String>)> {
+    match finished {
+        Ok((id, error)) => games.remove(&id).map(|game| (game, error)),
+        Err(e) => {
+            warn!(target:"rgsm::cloud::upload", "Upload task failed: {:?}", e);
+            games
+                .remove(&e.id())
+                .map(|game| (game, Some(e.to_string())))
+        }
+    }
+}
+
+async fn run(mut receiver: UnboundedReceiver<SnapshotUpload>) {
+    let mut queue = UploadQueue::default();
+    let mut zips = JoinSet::new();
+    let mut records = JoinSet::new();
+    let mut zip_games = HashMap::new();
+    let mut records_games = HashMap::new();
+    loop {
+        // Read for every round, a changed concurrency applies to the next zip
+        let limit = get_config()
+            .map(|c| c.settings.cloud_settings.upload_concurrency as usize)
+            .unwrap_or(1);
+        for upload in queue.start_zips(limit) {
+            let game = upload.game.clone();
+            let task = zips.spawn(upload_zip(upload));
+            zip_games.insert(task.id(), game);
+        }
+        tokio::select! {
+            upload = receiver.recv() => match upload {
+                Some(upload) => queue.push(upload),
+                None => break,
+            },
+            Some(finished) = zips.join_next_with_id() => {
+                let Some((game, error)) = finished_task(finished, &mut zip_games) else {
+                    continue;
+                };
+                if queue.zip_finished(&game, error) {
+                    let task = records.spawn(upload_records(game.clone()));
+                    records_games.insert(task.id(), game);
+                }
+            }
+            Some(finished) = records.join_next_with_id() => {
+                let Some((game, error)) = finished_task(finished, &mut records_games) else {
+                    continue;
+                };
+                match queue.records_finished(&game, error) {
+                    RecordsFinished::Again => {
+                        let task = records.spawn(upload_records(game.clone()));
+                        records_games.insert(task.id(), game);
+                    }
+                    RecordsFinished::Done(error) => report(&game, error),
+                }
+            }
+        }
+    }
+}
+
+/// Returns the error, if any
+async fn upload_zip(upload: SnapshotUpload) -> Option<String> {
+    emit_sync_state(
+        Some(&upload.game),
+        SyncDirection::Upload,
+        CloudSyncState::Started,
+        None,
+    );
+    send_to_backends(upload.operation()).await.err()
+}
+
+/// Returns the error, if any
+async fn upload_records(game: String) -> Option<String> {
+    send_to_backends(OutboxOperation::UploadRecords { game })
+        .await
+        .err()
+}
+
+/// Send `operation` to every enabled backend
+///
+/// Sent operations are removed from the outbox, failed ones stay queued there
+async fn send_to_backends(operation: OutboxOperation) -> Result<(), String> {
+    let config = get_config().map_err(|e| e.to_string())?;
+    let mut first_error = None;
+    for backend in config.settings.cloud_settings.enabled_backends() {
+        let sent_at = chrono::Local::now();
+        if let Err(e) = send_now(backend, &operation).await {
+            warn!(target:"rgsm::cloud::upload", "Failed to send {:?} to {}, queued for later: {:?}", operation, backend.name, e);
+            if let Err(e) = queue_for_retry(&backend.name, std::slice::from_ref(&operation)) {
+                warn!(target:"rgsm::cloud::upload", "Failed to queue {:?}: {:?}", operation, e);
+            }
+            first_error.get_or_insert(e.to_string());
+        } else if let Err(e) = remove_sent(&backend.name, &operation, sent_at) {
+            warn!(target:"rgsm::cloud::upload", "Failed to remove sent {:?} from the outbox: {:?}", operation, e);
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Report the uploads of `game` once nothing is left for it
+fn report(game: &str, error: Option<String>) {
+    let state = if error.is_some() {
+        // The local snapshot was created anyway, the outbox sends the rest later
+        show_notification(
+            t!("backend.cloud.upload_pending_title"),
+            t!("backend.cloud.upload_pending_body", name = game),
+        );
+        CloudSyncState::Failed
+    } else {
+        CloudSyncState::Finished
+    };
+    emit_sync_state(Some(game), SyncDirection::Upload, state, error);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(game: &str, date: &str) -> SnapshotUpload {
+        SnapshotUpload {
+            game: game.to_string(),
+            date: date.to_string(),
+        }
+    }
+
+    #[test]
+    fn records_are_uploaded_once_after_the_last_zip() {
+        let mut queue = UploadQueue::default();
+        for date in ["1", "2", "3"] {
+            queue.push(upload("Celeste", date));
+        }
+        assert_eq!(queue.start_zips(1), [upload("Celeste", "1")]);
+        // The slot is taken until the running zip finishes
+        assert!(queue.start_zips(1).is_empty());
+        assert!(!queue.zip_finished("Celeste", None));
+        assert_eq!(queue.start_zips(1), [upload("Celeste", "2")]);
+        assert!(!queue.zip_finished("Celeste", Some("offline".to_string())));
+        assert_eq!(queue.start_zips(1), [upload("Celeste", "3")]);
+        assert!(queue.zip_finished("Celeste", None));
+
+        // A snapshot taken while the records upload runs marks them dirty
+        queue.push(upload("Celeste", "4"));
+        assert_eq!(queue.start_zips(1), [upload("Celeste", "4")]);
+        assert!(!queue.zip_finished("Celeste", None));
+        assert_eq!(
+            queue.records_finished("Celeste", None),
+            RecordsFinished::Again
+        );
+        assert_eq!(
+            queue.records_finished("Celeste", None),
+            RecordsFinished::Done(Some("offline".to_string()))
+        );
+        assert!(queue.errors.is_empty() && queue.records_running.is_empty());
+    }
+
+    #[test]
+    fn zips_run_up_to_the_concurrency_limit() {
+        let mut queue = UploadQueue::default();
+        queue.push(upload("A", "1"));
+        queue.push(upload("B", "1"));
+        queue.push(upload("A", "2"));
+        assert_eq!(queue.start_zips(2).len(), 2);
+        assert!(queue.start_zips(2).is_empty());
+        // Each game uploads its records after its own zips
+        assert!(queue.zip_finished("B", None));
+        assert!(!queue.zip_finished("A", None));
+        assert_eq!(queue.start_zips(0), [upload("A", "2")]);
+    }
+}
@@ -1,76 +1,381 @@
 use std::fs;
+use std::path::Path;
 
-use log::info;
+use log::{info, warn};
 use opendal::Operator;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
 
-use crate::backup::GameSnapshots;
+use crate::backup::{GameSnapshots, sanitize_windows_path_component};
 use crate::config::{Config, get_config, set_config};
 use crate::preclude::*;
 
-pub async fn upload_all(op: &Operator) -> Result<(), BackendError> {
+use super::{
+    CloudSettings, CloudSyncState, NamedBackend, SessionKeys, emit_sync_state, read_snapshot,
+    record_probe, record_sync, remote_snapshot_len, uploaded_snapshot_len, write_snapshot,
+};
+
+/// Counters returned by `upload_all` and `download_all`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Type)]
+pub struct SyncSummary {
+    /// Snapshots actually transferred
+    pub transferred: u32,
+    /// Snapshots already present with the same size on the other side
+    pub skipped: u32,
+    pub failed: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub enum SyncDirection {
+    Upload,
+    Download,
+}
+
+/// Emitted after each snapshot is handled by `upload_all` or `download_all`
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct CloudSyncProgress {
+    pub direction: SyncDirection,
+    /// Snapshots handled so far, including skipped and failed ones
+    pub current: u32,
+    pub total: u32,
+    pub transferred: u32,
+}
+
+fn emit_progress(
+    app: Option<&AppHandle>,
+    direction: SyncDirection,
+    current: usize,
+    total: usize,
+    summary: &SyncSummary,
+) {
+    if let Some(app) = app {
+        let _ = CloudSyncProgress {
+            direction,
+            current: current as u32,
+            total: total as u32,
+            transferred: summary.transferred,
+        }
+        .emit(app);
+    }
+}
+
+/// A game handled by `upload_all` or `download_all`
+struct SyncedGame {
+    name: String,
+    latest_snapshot: Option<String>,
+    /// Every snapshot of the game was transferred or already up to date
+    complete: bool,
+}
+
+impl SyncedGame {
+    fn new(name: &str, info: &GameSnapshots) -> Self {
+        Self {
+            name: name.to_string(),
+            latest_snapshot: info.backups.last().map(|s| s.date.clone()),
+            complete: true,
+        }
+    }
+}
+
+fn record_synced_games(backend: &str, direction: SyncDirection, games: &[SyncedGame]) {
+    for game in games {
+        if game.complete {
+            record_sync(
+                &game.name,
+                backend,
+                direction,
+                game.latest_snapshot.as_deref(),
+            );
+        } else {
+            record_probe(&game.name);
+        }
+    }
+}
+
+/// Failed snapshots also count as a failed operation, so the frontend can warn about them
+fn emit_sync_result(direction: SyncDirection, result: &Result<SyncSummary, BackendError>) {
+    let (state, error) = match result {
+        Ok(summary) if summary.failed == 0 => (CloudSyncState::Finished, None),
+        Ok(summary) => (
+            CloudSyncState::Failed,
+            Some(format!("{} snapshots failed", summary.failed)),
+        ),
+        Err(e) => (CloudSyncState::Failed, Some(e.to_string())),
+    };
+    emit_sync_state(None, direction, state, error);
+}
+
+/// 由若干段组成云端路径，相对于云端根目录
+///
+/// 所有云端路径都应由这里生成：`\` 统一为 `/`，去掉空段与首尾的分隔符，
+/// 空格与非 ASCII 字符保持原样，由 OpenDAL 负责编码
+pub fn cloud_path(segments: &[&str]) -> String {
+    segments
+        .iter()
+        .flat_map(|segment| segment.split(['/', '\\']))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 云端的备份目录，必须固定为 `save_data/<存储名>`，不能随本地的 `backup_path` 变化
+///
+/// 存储名与本地备份文件夹相同，在 Windows 上非法的字符已被替换，任何平台下载都能创建
+pub fn cloud_backup_dir(name: &str) -> String {
+    cloud_path(&["save_data", &sanitize_windows_path_component(name)])
+}
+
+/// 游戏云端备份目录中的文件，如 `Backups.json` 与快照压缩包
+pub fn cloud_backup_file(name: &str, file_name: &str) -> String {
+    cloud_path(&[&cloud_backup_dir(name), file_name])
+}
+
+/// Move everything under the cloud folder `from` to `to`, both relative to the cloud root
+pub async fn move_remote_folder(op: &Operator, from: &str, to: &str) -> Result<(), BackendError> {
+    let old_prefix = format!("{from}/");
+    let new_prefix = format!("{to}/");
+    if old_prefix == new_prefix || !op.exists(&old_prefix).await? {
+        return Ok(());
+    }
+    let can_rename = op.info().full_capability().rename;
+    for entry in op.list_with(&old_prefix).recursive(true).await? {
+        if entry.metadata().is_dir() {
+            continue;
+        }
+        let Some(relative) = entry.path().strip_prefix(&old_prefix) else {
+            warn!(target:"rgsm::cloud::utils", "Unexpected cloud entry {} under {}", entry.path(), old_prefix);
+            continue;
+        };
+        let target = format!("{new_prefix}{relative}");
+        if can_rename {
+            op.rename(entry.path(), &target).await?;
+        } else {
+            let data = op.read(entry.path()).await?;
+            op.write(&target, data).await?;
+        }
+    }
+    op.remove_all(&old_prefix).await?;
+    Ok(())
+}
+
+/// 旧版本直接用游戏名作为云端目录，把与存储名不同的旧目录移到 `cloud_backup_dir`
+///
+/// 两个目录都存在时不做改动，只记录警告，避免覆盖其他设备新上传的快照
+async fn migrate_legacy_remote_folders(op: &Operator, config: &Config) -> Result<(), BackendError> {
+    for game in &config.games {
+        let legacy = cloud_path(&["save_data", &game.name]);
+        let current = cloud_backup_dir(&game.name);
+        if legacy == current || !op.exists(&format!("{legacy}/")).await? {
+            continue;
+        }
+        if op.exists(&format!("{current}/")).await? {
+            warn!(target:"rgsm::cloud::utils", "Both {} and {} exist on the cloud, keeping the legacy folder", legacy, current);
+            continue;
+        }
+        info!(target:"rgsm::cloud::utils", "Moving legacy cloud folder {} to {}", legacy, current);
+        move_remote_folder(op, &legacy, &current).await?;
+    }
+    Ok(())
+}
+
+/// Upload the config, all snapshot records, and every snapshot zip missing on the remote
+///
+/// A zip is skipped when the remote copy exists with the same size, failures of
+/// single zips are counted instead of aborting the whole run
+pub async fn upload_all(
+    backend: &NamedBackend,
+    app: Option<&AppHandle>,
+) -> Result<SyncSummary, BackendError> {
+    emit_sync_state(None, SyncDirection::Upload, CloudSyncState::Started, None);
+    let result = match backend.get_op() {
+        Ok(op) => upload_all_games(&op, &backend.name, app).await,
+        Err(e) => Err(e),
+    };
+    emit_sync_result(SyncDirection::Upload, &result);
+    result
+}
+
+async fn upload_all_games(
+    op: &Operator,
+    backend: &str,
+    app: Option<&AppHandle>,
+) -> Result<SyncSummary, BackendError> {
     let config = get_config()?;
+    let settings = &config.settings.cloud_settings;
     // 上传配置文件
     upload_config(op).await?;
-    // 依次上传所有游戏的存档记录和存档
-    for game in config.games {
-        // !NOTICE: 这个地方必须硬编码，因为云端目录必须固定
-        let cloud_backup_path = format!("save_data/{}", game.name);
+    migrate_legacy_remote_folders(op, &config).await?;
+    // 依次上传所有游戏的存档记录，并收集需要上传的存档
+    let mut synced = Vec::new();
+    let mut pending = Vec::new();
+    for game in &config.games {
+        let local_backup_path = game.backup_dir(&config);
         let backup_info = game.get_game_snapshots_info()?;
         // 写入存档记录
         op.write(
-            &format!("{}/Backups.json", &cloud_backup_path),
+            &cloud_backup_file(&game.name, "Backups.json"),
             serde_json::to_string_pretty(&backup_info)?,
         )
         .await?;
-        // 写入存档zip文件（不包括额外备份）
+        synced.push(SyncedGame::new(&game.name, &backup_info));
+        // 存档zip文件（不包括额外备份）
         for backup in backup_info.backups {
-            // TODO: 此处的cloud_backup_path应当改为本地的路径
-            let save_path = format!("{}/{}.zip", &cloud_backup_path, backup.date);
-            info!(target:"rgsm::cloud::utils","Uploading {}", save_path);
-            op.write(&save_path, fs::read(&save_path)?).await?;
+            let file_name = format!("{}.zip", backup.date);
+            pending.push((
+                synced.len() - 1,
+                local_backup_path.join(&file_name),
+                cloud_backup_file(&game.name, &file_name),
+            ));
         }
     }
-    Ok(())
+
+    let keys = app.and_then(|app| app.try_state::<SessionKeys>());
+    let mut summary = SyncSummary::default();
+    for (i, (game, local_path, save_path)) in pending.iter().enumerate() {
+        match upload_snapshot(op, settings, keys.as_deref(), local_path, save_path).await {
+            Ok(true) => summary.transferred += 1,
+            Ok(false) => summary.skipped += 1,
+            Err(e) => {
+                warn!(target:"rgsm::cloud::utils","Failed to upload {}: {:?}", save_path, e);
+                summary.failed += 1;
+                synced[*game].complete = false;
+            }
+        }
+        emit_progress(app, SyncDirection::Upload, i + 1, pending.len(), &summary);
+    }
+    record_synced_games(backend, SyncDirection::Upload, &synced);
+    info!(
+        target:"rgsm::cloud::utils",
+        "Upload finished, uploaded: {}, skipped: {}, failed: {}",
+        summary.transferred, summary.skipped, summary.failed
+    );
+    Ok(summary)
+}
+
+/// Returns whether the zip was written, `false` if the remote copy is up to date
+async fn upload_snapshot(
+    op: &Operator,
+    settings: &CloudSettings,
+    keys: Option<&SessionKeys>,
+    local_path: &Path,
+    save_path: &str,
+) -> Result<bool, BackendError> {
+    let local_len = fs::metadata(local_path)?.len();
+    if uploaded_snapshot_len(op, settings, save_path).await? == Some(local_len) {
+        return Ok(false);
+    }
+    info!(target:"rgsm::cloud::utils","Uploading {}", save_path);
+    let data = fs::read(local_path)?;
+    write_snapshot(op, settings, keys, save_path, data).await?;
+    Ok(true)
 }
 
-pub async fn download_all(op: &Operator) -> Result<(), BackendError> {
+/// Download the config, all snapshot records, and every snapshot zip missing locally
+///
+/// Mirrors `upload_all`, a local zip with the same size as the remote one is kept
+pub async fn download_all(
+    backend: &NamedBackend,
+    app: Option<&AppHandle>,
+) -> Result<SyncSummary, BackendError> {
+    emit_sync_state(None, SyncDirection::Download, CloudSyncState::Started, None);
+    let result = match backend.get_op() {
+        Ok(op) => download_all_games(&op, &backend.name, app).await,
+        Err(e) => Err(e),
+    };
+    emit_sync_result(SyncDirection::Download, &result);
+    result
+}
+
+async fn download_all_games(
+    op: &Operator,
+    backend: &str,
+    app: Option<&AppHandle>,
+) -> Result<SyncSummary, BackendError> {
     // 下载配置文件
     let config = String::from_utf8(op.read("/GameSaveManager.config.json").await?.to_vec())?;
-    let config: Config = serde_json::from_str(&config)?;
+    let mut config: Config = serde_json::from_str(&config)?;
+    // 备份目录与云同步后端是本机的设置，不随云端的配置改变
+    let local = get_config()?;
+    config.backup_path = local.backup_path;
+    config.settings.cloud_settings.backends = local.settings.cloud_settings.backends;
+    config.settings.cloud_settings.primary = local.settings.cloud_settings.primary;
     set_config(&config).await?;
-    // 依次下载所有游戏的存档记录和存档
-    for game in config.games {
-        // !NOTICE: 这个地方必须硬编码，因为云端目录必须固定
-        let backup_path = format!("save_data/{}", game.name);
+    migrate_legacy_remote_folders(op, &config).await?;
+    let settings = &config.settings.cloud_settings;
+    // 依次下载所有游戏的存档记录，并收集需要下载的存档
+    let mut synced = Vec::new();
+    let mut pending = Vec::new();
+    for game in &config.games {
+        let local_backup_path = game.backup_dir(&config);
         let backup_info = op
-            .read(&format!("{}/Backups.json", &backup_path))
+            .read(&cloud_backup_file(&game.name, "Backups.json"))
             .await?
             .to_vec();
-        let backup_info: GameSnapshots = serde_json::from_str(&String::from_utf8(backup_info)?)?;
-        game.set_game_snapshots_info(&backup_info)?;
+        let mut backup_info: GameSnapshots =
+            serde_json::from_str(&String::from_utf8(backup_info)?)?;
+        // 其他设备上传的记录可能是旧版本的绝对路径
+        backup_info.normalize_paths();
         // 写入存档记录
-        // TODO: 此处的cloud_backup_path应当改为本地的路径
-        fs::write(
-            format!("{}/Backups.json", &backup_path),
-            serde_json::to_string_pretty(&backup_info)?,
-        )?;
-        // 写入存档zip文件（不包括额外备份）
+        game.set_game_snapshots_info(&backup_info)?;
+        synced.push(SyncedGame::new(&game.name, &backup_info));
+        // 存档zip文件（不包括额外备份）
         for backup in backup_info.backups {
-            let save_path = format!("{}/{}.zip", &backup_path, backup.date);
-            info!(target:"rgsm::cloud::utils","Downloading {}", save_path);
-            let data = op.read(&save_path).await?.to_vec();
-            fs::write(&save_path, &data)?;
+            let file_name = format!("{}.zip", backup.date);
+            pending.push((
+                synced.len() - 1,
+                local_backup_path.join(&file_name),
+                cloud_backup_file(&game.name, &file_name),
+            ));
         }
     }
-    Ok(())
+
+    let keys = app.and_then(|app| app.try_state::<SessionKeys>());
+    let mut summary = SyncSummary::default();
+    for (i, (game, local_path, save_path)) in pending.iter().enumerate() {
+        match download_snapshot(op, settings, keys.as_deref(), local_path, save_path).await {
+            Ok(true) => summary.transferred += 1,
+            Ok(false) => summary.skipped += 1,
+            Err(e) => {
+                warn!(target:"rgsm::cloud::utils","Failed to download {}: {:?}", save_path, e);
+                summary.failed += 1;
+                synced[*game].complete = false;
+            }
+        }
+        emit_progress(app, SyncDirection::Download, i + 1, pending.len(), &summary);
+    }
+    record_synced_games(backend, SyncDirection::Download, &synced);
+    info!(
+        target:"rgsm::cloud::utils",
+        "Download finished, downloaded: {}, skipped: {}, failed: {}",
+        summary.transferred, summary.skipped, summary.failed
+    );
+    Ok(summary)
+}
+
+/// Returns whether the zip was written, `false` if the local copy is up to date
+async fn download_snapshot(
+    op: &Operator,
+    settings: &CloudSettings,
+    keys: Option<&SessionKeys>,
+    local_path: &Path,
+    save_path: &str,
+) -> Result<bool, BackendError> {
+    let local_len = fs::metadata(local_path).ok().map(|m| m.len());
+    if local_len.is_some() && remote_snapshot_len(op, save_path).await? == local_len {
+        return Ok(false);
+    }
+    info!(target:"rgsm::cloud::utils","Downloading {}", save_path);
+    let data = read_snapshot(op, settings, keys, save_path).await?;
+    fs::write(local_path, &data)?;
+    Ok(true)
 }
 
 /// 上传单个游戏的配置文件
 pub async fn upload_game_snapshots(op: &Operator, info: GameSnapshots) -> Result<(), BackendError> {
-    // !NOTICE: 这个地方必须硬编码，因为云端目录必须固定
-    let backup_path = format!("save_data/{}", info.name);
     op.write(
-        &format!("{}/Backups.json", &backup_path),
+        &cloud_backup_file(&info.name, "Backups.json"),
         serde_json::to_string_pretty(&info)?,
     )
     .await?;
@@ -89,3 +394,274 @@ pub async fn upload_config(op: &Operator) -> Result<(), BackendError> {
     .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::{Game, SaveUnit, SaveUnitType, Snapshot, create_game_backup};
+    use crate::cloud_sync::Backend;
+    use crate::device::get_current_device_id;
+    use std::collections::HashMap;
+
+    /// 测试会改写全局配置，不能并行执行
+    static TEST_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+    /// 备份目录为 `work` 下绝对路径 `save_data` 的配置
+    fn config_in(work: &Path) -> Config {
+        Config {
+            backup_path: work.join("save_data").to_string_lossy().to_string(),
+            ..Config::default()
+        }
+    }
+
+    /// 指向 `path` 的本地文件夹后端，名为 `local`
+    fn local_backend(path: &Path) -> NamedBackend {
+        NamedBackend::new(
+            "local",
+            Backend::LocalFs {
+                path: path.to_string_lossy().to_string(),
+            },
+        )
+    }
+
+    /// 测试：含空格与中文的游戏名在上传、下载与删除时使用同一个云端路径，且不会被转义
+    #[tokio::test]
+    async fn cjk_and_space_names_use_identical_keys() {
+        let remote = temp_dir::TempDir::new().unwrap();
+        let op =
+            Operator::new(opendal::services::Fs::default().root(&remote.path().to_string_lossy()))
+                .unwrap()
+                .finish();
+        let name = "原神 存档";
+        let key = cloud_backup_file(name, "2024-01-01_00-00-00.zip");
+        assert_eq!(key, "save_data/原神 存档/2024-01-01_00-00-00.zip");
+        assert_eq!(
+            cloud_path(&["save_data\\", "/原神 存档/", "a.zip"]),
+            "save_data/原神 存档/a.zip"
+        );
+
+        upload_game_snapshots(&op, GameSnapshots::new(name, Vec::new()))
+            .await
+            .unwrap();
+        op.write(&key, b"data".to_vec()).await.unwrap();
+        let dir = remote.path().join("save_data").join(name);
+        assert!(dir.join("Backups.json").is_file());
+        assert!(dir.join("2024-01-01_00-00-00.zip").is_file());
+
+        let records = op
+            .read(&cloud_backup_file(name, "Backups.json"))
+            .await
+            .unwrap();
+        let records: GameSnapshots = serde_json::from_slice(&records.to_vec()).unwrap();
+        assert_eq!(records.name, name);
+        assert_eq!(op.read(&key).await.unwrap().to_vec(), b"data");
+        op.delete(&key).await.unwrap();
+        assert!(!op.exists(&key).await.unwrap());
+    }
+
+    /// 测试：旧版本以原名上传的云端目录在同步时移到存储名下
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn legacy_remote_folders_are_migrated() {
+        let remote = temp_dir::TempDir::new().unwrap();
+        let op =
+            Operator::new(opendal::services::Fs::default().root(&remote.path().to_string_lossy()))
+                .unwrap()
+                .finish();
+        let mut config = Config::default();
+        for name in ["Who?", "Game"] {
+            config.games.push(Game {
+                name: name.into(),
+                save_paths: Vec::new(),
+                game_paths: HashMap::new(),
+            });
+        }
+        op.write("save_data/Who?/Backups.json", b"{}".to_vec())
+            .await
+            .unwrap();
+        op.write("save_data/Game/Backups.json", b"{}".to_vec())
+            .await
+            .unwrap();
+
+        migrate_legacy_remote_folders(&op, &config).await.unwrap();
+        assert!(op.exists("save_data/Who_/Backups.json").await.unwrap());
+        assert!(!op.exists("save_data/Who?/").await.unwrap());
+        assert!(op.exists("save_data/Game/Backups.json").await.unwrap());
+    }
+
+    /// 测试：含 Windows 非法字符的游戏名在创建、上传、下载与恢复时使用同一个存储名
+    #[tokio::test]
+    async fn illegal_windows_names_round_trip() {
+        let _guard = TEST_LOCK.lock().await;
+        let work = temp_dir::TempDir::new().unwrap();
+        let remote = temp_dir::TempDir::new().unwrap();
+        let saves = temp_dir::TempDir::new().unwrap();
+        let root = work.path().join("save_data");
+
+        let names = ["Ratchet & Clank: Rift Apart", "Who?", "Trailing."];
+        let mut config = config_in(work.path());
+        config.settings.extra_backup_when_apply = false;
+        config.settings.cloud_settings.backends = vec![local_backend(remote.path())];
+        config.settings.cloud_settings.primary = "local".to_string();
+        for (i, name) in names.iter().enumerate() {
+            let save_file = saves.path().join(format!("{i}.dat"));
+            fs::write(&save_file, name).unwrap();
+            config.games.push(Game {
+                name: name.to_string(),
+                save_paths: vec![SaveUnit {
+                    unit_type: SaveUnitType::File,
+                    paths: HashMap::from([(
+                        get_current_device_id().clone(),
+                        save_file.to_string_lossy().to_string(),
+                    )]),
+                    ..Default::default()
+                }],
+                game_paths: HashMap::new(),
+            });
+        }
+        set_config(&config).await.unwrap();
+        for game in &config.games {
+            create_game_backup(game).await.unwrap();
+            game.create_snapshot("").await.unwrap();
+        }
+        let backend = config.settings.cloud_settings.backends[0].clone();
+        let summary = upload_all(&backend, None).await.unwrap();
+        assert_eq!(summary.failed, 0);
+        for folder in ["Ratchet & Clank_ Rift Apart", "Who_", "Trailing"] {
+            assert!(root.join(folder).is_dir());
+            let remote_dir = remote
+                .path()
+                .join("game-save-manager/save_data")
+                .join(folder);
+            assert!(remote_dir.join("Backups.json").is_file());
+        }
+
+        // 另一台设备：本地还没有任何备份
+        fs::remove_dir_all(&root).unwrap();
+        for i in 0..names.len() {
+            fs::write(saves.path().join(format!("{i}.dat")), b"changed").unwrap();
+        }
+        let summary = download_all(&backend, None).await.unwrap();
+        assert_eq!((summary.transferred, summary.failed), (3, 0));
+        for (i, game) in config.games.iter().enumerate() {
+            let info = game.get_game_snapshots_info().unwrap();
+            assert_eq!(
+                info.storage_name,
+                sanitize_windows_path_component(&game.name)
+            );
+            game.restore_snapshot(&info.backups[0].date, None).unwrap();
+            let restored = fs::read_to_string(saves.path().join(format!("{i}.dat"))).unwrap();
+            assert_eq!(restored, names[i]);
+        }
+    }
+
+    /// 测试：通过本地文件夹后端完整地上传并下载所有备份
+    #[tokio::test]
+    async fn local_fs_round_trip() {
+        let _guard = TEST_LOCK.lock().await;
+        let work = temp_dir::TempDir::new().unwrap();
+        let remote = temp_dir::TempDir::new().unwrap();
+        let root = work.path().join("save_data");
+
+        let mut config = config_in(work.path());
+        config.settings.cloud_settings.backends = vec![local_backend(remote.path())];
+        config.settings.cloud_settings.primary = "local".to_string();
+        config.games.push(Game {
+            name: "Game".into(),
+            save_paths: Vec::new(),
+            game_paths: HashMap::new(),
+        });
+        set_config(&config).await.unwrap();
+        let snapshots = GameSnapshots::new(
+            "Game",
+            vec![Snapshot {
+                date: "2024-01-01_00-00-00".into(),
+                describe: String::new(),
+                path: "save_data/Game/2024-01-01_00-00-00.zip".into(),
+                size: 4,
+            }],
+        );
+        config.games[0].set_game_snapshots_info(&snapshots).unwrap();
+        fs::write(root.join("Game/2024-01-01_00-00-00.zip"), b"data").unwrap();
+
+        let backend = config.settings.cloud_settings.backends[0].clone();
+        let summary = upload_all(&backend, None).await.unwrap();
+        assert_eq!((summary.transferred, summary.skipped), (1, 0));
+        assert!(
+            remote
+                .path()
+                .join("game-save-manager/save_data/Game/Backups.json")
+                .is_file()
+        );
+
+        // 云端已有相同大小的存档，不会重复上传
+        let summary = upload_all(&backend, None).await.unwrap();
+        assert_eq!((summary.transferred, summary.skipped), (0, 1));
+
+        fs::remove_dir_all(&root).unwrap();
+        let summary = download_all(&backend, None).await.unwrap();
+        assert_eq!((summary.transferred, summary.failed), (1, 0));
+        assert_eq!(
+            fs::read(root.join("Game/2024-01-01_00-00-00.zip")).unwrap(),
+            b"data"
+        );
+        let restored = config.games[0].get_game_snapshots_info().unwrap();
+        assert_eq!(restored.backups.len(), 1);
+    }
+
+    /// 测试：旧版本上传的快照在备份目录不同的另一台设备上可以下载并恢复
+    #[tokio::test]
+    async fn restore_on_another_machine() {
+        let _guard = TEST_LOCK.lock().await;
+        let work = temp_dir::TempDir::new().unwrap();
+        let remote = temp_dir::TempDir::new().unwrap();
+        let saves = temp_dir::TempDir::new().unwrap();
+        let save_file = saves.path().join("save.dat");
+        fs::write(&save_file, b"progress").unwrap();
+
+        let mut config = config_in(work.path());
+        config.settings.extra_backup_when_apply = false;
+        config.settings.cloud_settings.backends = vec![local_backend(remote.path())];
+        config.settings.cloud_settings.primary = "local".to_string();
+        config.games.push(Game {
+            name: "Game".into(),
+            save_paths: vec![SaveUnit {
+                unit_type: SaveUnitType::File,
+                paths: HashMap::from([(
+                    get_current_device_id().clone(),
+                    save_file.to_string_lossy().to_string(),
+                )]),
+                ..Default::default()
+            }],
+            game_paths: HashMap::new(),
+        });
+        set_config(&config).await.unwrap();
+        let game = &config.games[0];
+        game.set_game_snapshots_info(&GameSnapshots::new("Game", Vec::new()))
+            .unwrap();
+        game.create_snapshot("").await.unwrap();
+        // 模拟旧版本在 Windows 上写入的绝对路径
+        let mut info = game.get_game_snapshots_info().unwrap();
+        let date = info.backups[0].date.clone();
+        info.backups[0].path = format!("D:\\SaveManager\\save_data\\Game\\{date}.zip");
+        game.set_game_snapshots_info(&info).unwrap();
+        let backend = config.settings.cloud_settings.backends[0].clone();
+        upload_all(&backend, None).await.unwrap();
+
+        // 另一台设备：备份目录不同，本地还没有任何备份
+        fs::remove_dir_all(work.path().join("save_data")).unwrap();
+        fs::write(&save_file, b"changed").unwrap();
+        let other_root = work.path().join("other_root").to_string_lossy().to_string();
+        config.backup_path = other_root.clone();
+        set_config(&config).await.unwrap();
+
+        let summary = download_all(&backend, None).await.unwrap();
+        assert_eq!((summary.transferred, summary.failed), (1, 0));
+        // 云端配置中的备份目录不会覆盖本机的设置
+        assert_eq!(get_config().unwrap().backup_path, other_root);
+        let info = game.get_game_snapshots_info().unwrap();
+        assert_eq!(info.backups[0].path, format!("Game/{date}.zip"));
+        game.restore_snapshot(&date, None).unwrap();
+        assert_eq!(fs::read(&save_file).unwrap(), b"progress");
+    }
+}
@@ -0,0 +1,422 @@
+//! 收藏夹树
+//!
+//! 游戏节点（叶子）以游戏名作为 `label` 指向游戏。每次修改都在 `modify_config` 中基于最新配置完成，
+//! 修改后校验整棵树，校验失败时不会保存，避免多个窗口同时修改时丢失节点。
+
+use std::collections::HashSet;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use thiserror::Error;
+
+use crate::backup::Game;
+use crate::config::{Config, modify_config};
+use crate::preclude::*;
+
+/// 收藏夹树的节点，是文件夹或指向游戏的游戏节点
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct FavoriteTreeNode {
+    node_id: String,
+    label: String,
+    is_leaf: bool,
+    children: Option<Vec<Self>>,
+}
+
+/// 收藏夹树中的不一致之处
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FavoriteIssue {
+    #[error("Favorite node id {0} is used more than once")]
+    DuplicateId(String),
+    #[error("Favorite node {0} is a game but has children")]
+    LeafWithChildren(String),
+    #[error("Favorite node {node_id} points at missing game {game}")]
+    MissingGame { node_id: String, game: String },
+    #[error("Favorite folder {0} has an empty name")]
+    EmptyLabel(String),
+}
+
+impl FavoriteTreeNode {
+    /// 创建节点，游戏节点的 `label` 为游戏名，文件夹创建时没有子节点
+    pub fn new(node_id: impl Into<String>, label: impl Into<String>, is_leaf: bool) -> Self {
+        FavoriteTreeNode {
+            node_id: node_id.into(),
+            label: label.into(),
+            is_leaf,
+            children: (!is_leaf).then(Vec::new),
+        }
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.is_leaf
+    }
+
+    pub fn children(&self) -> &[Self] {
+        self.children.as_deref().unwrap_or_default()
+    }
+
+    /// 文件夹的子节点，游戏节点不能有子节点，返回 `None`
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Self>> {
+        if self.is_leaf {
+            return None;
+        }
+        Some(self.children.get_or_insert_with(Vec::new))
+    }
+
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = label.into();
+    }
+
+    /// 将指向游戏 `old` 的收藏项（含子节点）改为指向 `new`
+    pub fn rename_game(&mut self, old: &str, new: &str) {
+        if self.is_leaf && self.label == old {
+            self.label = new.to_string();
+        }
+        for child in self.children.iter_mut().flatten() {
+            child.rename_game(old, new);
+        }
+    }
+
+    /// 先序遍历自身及所有子孙节点
+    pub fn walk(&self) -> impl Iterator<Item = &Self> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children().iter().rev());
+            Some(node)
+        })
+    }
+
+    /// 检查以该节点为根的子树
+    pub fn validate(&self, games: &[Game]) -> Vec<FavoriteIssue> {
+        validate_favorites(std::slice::from_ref(self), games)
+    }
+}
+
+/// 检查整棵收藏夹树：节点 id 唯一，游戏节点没有子节点且指向存在的游戏，文件夹名不为空
+///
+/// 树由嵌套的 `Vec` 组成，本身不会有环，移动节点时另行检查不会移入自己的子树
+pub fn validate_favorites(nodes: &[FavoriteTreeNode], games: &[Game]) -> Vec<FavoriteIssue> {
+    let mut ids = HashSet::new();
+    let mut issues = Vec::new();
+    for node in nodes.iter().flat_map(FavoriteTreeNode::walk) {
+        if !ids.insert(node.node_id()) {
+            issues.push(FavoriteIssue::DuplicateId(node.node_id.clone()));
+        }
+        if node.is_leaf {
+            if !node.children().is_empty() {
+                issues.push(FavoriteIssue::LeafWithChildren(node.node_id.clone()));
+            }
+            if !games.iter().any(|g| g.name == node.label) {
+                issues.push(FavoriteIssue::MissingGame {
+                    node_id: node.node_id.clone(),
+                    game: node.label.clone(),
+                });
+            }
+        } else if node.label.trim().is_empty() {
+            issues.push(FavoriteIssue::EmptyLabel(node.node_id.clone()));
+        }
+    }
+    issues
+}
+
+/// 在 `nodes` 及其子树中查找节点
+fn find_node_mut<'a>(
+    nodes: &'a mut [FavoriteTreeNode],
+    node_id: &str,
+) -> Option<&'a mut FavoriteTreeNode> {
+    for node in nodes {
+        if node.node_id == node_id {
+            return Some(node);
+        }
+        let children = node.children.as_deref_mut().unwrap_or_default();
+        if let Some(found) = find_node_mut(children, node_id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// 从 `nodes` 及其子树中移除节点，返回被移除的节点（含子节点）
+fn take_node(nodes: &mut Vec<FavoriteTreeNode>, node_id: &str) -> Option<FavoriteTreeNode> {
+    if let Some(index) = nodes.iter().position(|node| node.node_id == node_id) {
+        return Some(nodes.remove(index));
+    }
+    nodes
+        .iter_mut()
+        .find_map(|node| take_node(node.children.as_mut()?, node_id))
+}
+
+/// `parent_id` 的子节点列表，为 `None` 时是最外层
+fn children_of<'a>(
+    tree: &'a mut Vec<FavoriteTreeNode>,
+    parent_id: Option<&str>,
+) -> Result<&'a mut Vec<FavoriteTreeNode>, FavoriteError> {
+    let Some(parent_id) = parent_id else {
+        return Ok(tree);
+    };
+    find_node_mut(tree, parent_id)
+        .ok_or_else(|| FavoriteError::NodeNotExist(parent_id.to_string()))?
+        .children_mut()
+        .ok_or_else(|| FavoriteError::LeafParent(parent_id.to_string()))
+}
+
+/// 生成树中未使用的节点 id
+fn new_node_id(tree: &mut [FavoriteTreeNode]) -> String {
+    let base = format!(
+        "{:x}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    );
+    let mut node_id = base.clone();
+    let mut n = 1;
+    while find_node_mut(tree, &node_id).is_some() {
+        node_id = format!("{base}-{n}");
+        n += 1;
+    }
+    node_id
+}
+
+/// 移除指向游戏 `game_name` 的游戏节点，返回移除的数量
+pub fn prune_favorite_game(nodes: &mut Vec<FavoriteTreeNode>, game_name: &str) -> usize {
+    let before = nodes.len();
+    nodes.retain(|node| !(node.is_leaf && node.label == game_name));
+    let mut removed = before - nodes.len();
+    for node in nodes.iter_mut() {
+        if let Some(children) = node.children.as_mut() {
+            removed += prune_favorite_game(children, game_name);
+        }
+    }
+    removed
+}
+
+/// 是否有指向游戏 `game_name` 的游戏节点
+pub fn contains_favorite_game(nodes: &[FavoriteTreeNode], game_name: &str) -> bool {
+    nodes
+        .iter()
+        .flat_map(FavoriteTreeNode::walk)
+        .any(|node| node.is_leaf && node.label == game_name)
+}
+
+/// 在最外层添加指向游戏 `game_name` 的游戏节点
+pub fn push_favorite_game(nodes: &mut Vec<FavoriteTreeNode>, game_name: &str) {
+    let node_id = new_node_id(nodes);
+    nodes.push(FavoriteTreeNode::new(node_id, game_name, true));
+}
+
+/// 移除指向不存在游戏的游戏节点，旧版本删除游戏时不会清理收藏夹
+fn prune_missing_games(nodes: &mut Vec<FavoriteTreeNode>, games: &[Game]) -> usize {
+    let before = nodes.len();
+    nodes.retain(|node| !node.is_leaf || games.iter().any(|g| g.name == node.label));
+    let mut removed = before - nodes.len();
+    for node in nodes.iter_mut() {
+        if let Some(children) = node.children.as_mut() {
+            removed += prune_missing_games(children, games);
+        }
+    }
+    removed
+}
+
+/// 树中有任何不一致时返回第一个
+fn validate_tree(tree: &[FavoriteTreeNode], games: &[Game]) -> Result<(), FavoriteError> {
+    match validate_favorites(tree, games).into_iter().next() {
+        Some(issue) => Err(FavoriteError::Invalid(issue)),
+        None => Ok(()),
+    }
+}
+
+/// 在最新配置上修改收藏夹，校验通过后保存
+async fn modify_favorites<T>(
+    f: impl FnOnce(&mut Vec<FavoriteTreeNode>) -> Result<T, FavoriteError>,
+) -> Result<T, FavoriteError> {
+    modify_config(|config: &mut Config| {
+        let pruned = prune_missing_games(&mut config.favorites, &config.games);
+        if pruned > 0 {
+            warn!(target: "rgsm::config::favorites", "Removed {} favorites of missing games", pruned);
+        }
+        let result = f(&mut config.favorites)?;
+        validate_tree(&config.favorites, &config.games)?;
+        Ok(result)
+    })
+    .await
+}
+
+/// 在 `parent_id` 下添加节点，返回新节点的 id
+///
+/// 游戏节点以游戏名作为 `label`，因此使用 `game_ref` 指定的游戏名，为空时使用 `label`
+pub async fn add_favorite(
+    parent_id: Option<&str>,
+    label: &str,
+    is_leaf: bool,
+    game_ref: Option<&str>,
+) -> Result<String, FavoriteError> {
+    let label = match game_ref {
+        Some(game) if is_leaf => game,
+        _ => label.trim(),
+    };
+    let node_id = modify_favorites(|tree| {
+        let node_id = new_node_id(tree);
+        children_of(tree, parent_id)?.push(FavoriteTreeNode::new(node_id.clone(), label, is_leaf));
+        Ok(node_id)
+    })
+    .await?;
+    info!(target: "rgsm::config::favorites", "Added favorite {} ({})", label, node_id);
+    Ok(node_id)
+}
+
+/// 移除节点及其子节点
+pub async fn remove_favorite(node_id: &str) -> Result<(), FavoriteError> {
+    modify_favorites(|tree| {
+        take_node(tree, node_id)
+            .map(|_| ())
+            .ok_or_else(|| FavoriteError::NodeNotExist(node_id.to_string()))
+    })
+    .await?;
+    info!(target: "rgsm::config::favorites", "Removed favorite {}", node_id);
+    Ok(())
+}
+
+/// 将节点移动到 `new_parent_id` 下的第 `index` 个位置，超出范围时放到最后
+pub async fn move_favorite(
+    node_id: &str,
+    new_parent_id: Option<&str>,
+    index: usize,
+) -> Result<(), FavoriteError> {
+    modify_favorites(|tree| {
+        let node = find_node_mut(tree, node_id)
+            .ok_or_else(|| FavoriteError::NodeNotExist(node_id.to_string()))?;
+        if new_parent_id.is_some_and(|parent_id| node.walk().any(|n| n.node_id == parent_id)) {
+            return Err(FavoriteError::MoveIntoItself(node_id.to_string()));
+        }
+        let node = take_node(tree, node_id)
+            .ok_or_else(|| FavoriteError::NodeNotExist(node_id.to_string()))?;
+        let siblings = children_of(tree, new_parent_id)?;
+        siblings.insert(index.min(siblings.len()), node);
+        Ok(())
+    })
+    .await?;
+    info!(target: "rgsm::config::favorites", "Moved favorite {} to {:?}", node_id, new_parent_id);
+    Ok(())
+}
+
+/// 重命名节点，游戏节点改名相当于指向另一个游戏
+pub async fn rename_favorite(node_id: &str, label: &str) -> Result<(), FavoriteError> {
+    let label = label.trim();
+    modify_favorites(|tree| {
+        let node = find_node_mut(tree, node_id)
+            .ok_or_else(|| FavoriteError::NodeNotExist(node_id.to_string()))?;
+        node.set_label(label);
+        Ok(())
+    })
+    .await?;
+    info!(target: "rgsm::config::favorites", "Renamed favorite {} to {}", node_id, label);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(node_id: &str, label: &str, children: Vec<FavoriteTreeNode>) -> FavoriteTreeNode {
+        let mut node = FavoriteTreeNode::new(node_id, label, false);
+        node.children_mut().unwrap().extend(children);
+        node
+    }
+
+    fn game(name: &str) -> Game {
+        Game {
+            name: name.to_string(),
+            save_paths: vec![],
+            game_paths: Default::default(),
+        }
+    }
+
+    fn tree() -> Vec<FavoriteTreeNode> {
+        vec![
+            folder(
+                "folder",
+                "RPG",
+                vec![
+                    FavoriteTreeNode::new("a", "Elden Ring", true),
+                    folder("inner", "Souls", vec![]),
+                ],
+            ),
+            FavoriteTreeNode::new("b", "Celeste", true),
+        ]
+    }
+
+    #[test]
+    fn nested_trees_are_walked_in_order() {
+        let ids: Vec<_> = tree()
+            .iter()
+            .flat_map(FavoriteTreeNode::walk)
+            .map(|node| node.node_id().to_string())
+            .collect();
+        assert_eq!(ids, ["folder", "a", "inner", "b"]);
+        assert!(
+            FavoriteTreeNode::new("b", "Celeste", true)
+                .children_mut()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn tree_is_validated() {
+        let games = [game("Elden Ring"), game("Celeste")];
+        assert!(validate_favorites(&tree(), &games).is_empty());
+        assert_eq!(
+            validate_favorites(&tree(), &games[..1]),
+            [FavoriteIssue::MissingGame {
+                node_id: "b".to_string(),
+                game: "Celeste".to_string(),
+            }]
+        );
+
+        let mut duplicated = tree();
+        duplicated[0]
+            .children_mut()
+            .unwrap()
+            .push(FavoriteTreeNode::new("b", "Celeste", true));
+        assert_eq!(
+            validate_favorites(&duplicated, &games),
+            [FavoriteIssue::DuplicateId("b".to_string())]
+        );
+
+        let mut leaf = FavoriteTreeNode::new("c", "Celeste", true);
+        leaf.children = Some(vec![FavoriteTreeNode::new("d", "Elden Ring", true)]);
+        assert_eq!(
+            leaf.validate(&games),
+            [FavoriteIssue::LeafWithChildren("c".to_string())]
+        );
+        assert_eq!(
+            folder("e", " ", vec![]).validate(&games),
+            [FavoriteIssue::EmptyLabel("e".to_string())]
+        );
+    }
+
+    #[test]
+    fn nodes_are_moved_and_pruned() {
+        let mut tree = tree();
+        let moved = take_node(&mut tree, "a").unwrap();
+        children_of(&mut tree, Some("inner"))
+            .unwrap()
+            .insert(0, moved);
+        assert!(find_node_mut(&mut tree, "a").is_some());
+        assert!(matches!(
+            children_of(&mut tree, Some("b")),
+            Err(FavoriteError::LeafParent(_))
+        ));
+
+        assert_eq!(prune_favorite_game(&mut tree, "Elden Ring"), 1);
+        assert!(!contains_favorite_game(&tree, "Elden Ring"));
+        assert_eq!(prune_missing_games(&mut tree, &[]), 1);
+        assert_eq!(tree.len(), 1);
+    }
+}
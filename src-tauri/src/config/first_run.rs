@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::config::{Config, config_path, get_config, modify_config};
+use crate::device::{
+    Device, ensure_current_device, get_current_device_id, take_current_device_added,
+};
+use crate::preclude::*;
+
+/// 默认备份目录下的文件夹名
+const BACKUP_DIR_NAME: &str = "GameSaveManager";
+/// 检查目录是否可写时创建的临时文件
+const WRITE_PROBE_NAME: &str = ".rgsm_write_test";
+
+/// 首次运行初始化的结果，供引导页展示
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FirstRunSetup {
+    /// 最终使用的备份目录
+    pub backup_path: String,
+    /// 配置文件所在位置
+    pub config_path: String,
+    /// 当前设备
+    pub device: Device,
+    /// 本次调用是否修改了配置，已完成初始化的配置不会被修改
+    pub changed: bool,
+    /// 当前设备是否在本次启动时才写入配置，为真时引导页应让用户确认设备名称
+    pub new_device: bool,
+}
+
+/// 推荐的备份目录：Windows 下为“文档”目录，其他系统为用户数据目录（Linux 下即 XDG_DATA_HOME）
+fn default_backup_root() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        dirs::document_dir()
+    } else {
+        dirs::data_dir()
+    };
+    base.map(|dir| dir.join(BACKUP_DIR_NAME))
+}
+
+/// 只有仍在使用默认的相对路径且尚未添加游戏时才更换备份目录，避免覆盖用户已有的设置
+fn proposed_backup_root(config: &Config, default_root: Option<PathBuf>) -> Option<PathBuf> {
+    if config.backup_path != Config::default().backup_path || !config.games.is_empty() {
+        return None;
+    }
+    default_root
+}
+
+/// 创建目录并写入临时文件，确认目录可写
+fn ensure_writable(dir: &Path) -> Result<(), BackupError> {
+    let invalid = || BackupError::InvalidBackupRoot(dir.to_string_lossy().to_string());
+    fs::create_dir_all(dir).map_err(|_| invalid())?;
+    let probe = dir.join(WRITE_PROBE_NAME);
+    fs::write(&probe, b"").map_err(|_| invalid())?;
+    fs::remove_file(&probe)?;
+    Ok(())
+}
+
+/// 首次运行时的初始化，可重复调用
+///
+/// - 备份目录仍为默认的 `./save_data` 且没有游戏时，改为用户目录下的 `GameSaveManager`
+/// - 当前设备不在配置中时，以主机名登记（通常已在加载配置时完成）
+/// - 备份目录必须可写，否则返回 `BackupError::InvalidBackupRoot`
+pub async fn initialize_first_run() -> Result<FirstRunSetup, BackupError> {
+    let config = get_config()?;
+    let backup_root = proposed_backup_root(&config, default_backup_root());
+    let checked_root = backup_root.clone().unwrap_or_else(|| config.backup_root());
+    // 目录检查放在写配置之前，失败时配置保持不变
+    ensure_writable(&checked_root)?;
+
+    let device_id = get_current_device_id();
+    if backup_root.is_none() && config.devices.contains_key(device_id) {
+        return Ok(setup_result(&config, false));
+    }
+    let config = modify_config(|config| {
+        // 等待写锁期间配置可能已被修改，需要重新判断
+        if let Some(root) = proposed_backup_root(config, backup_root) {
+            config.backup_path = root.to_string_lossy().to_string();
+        }
+        ensure_current_device(config);
+        Ok::<_, BackupError>(config.clone())
+    })
+    .await?;
+    info!(target:"rgsm::config", "First run initialized, backup path: {}", config.backup_path);
+    Ok(setup_result(&config, true))
+}
+
+fn setup_result(config: &Config, changed: bool) -> FirstRunSetup {
+    FirstRunSetup {
+        backup_path: config.backup_path.clone(),
+        config_path: config_path().to_string_lossy().to_string(),
+        device: config
+            .devices
+            .get(get_current_device_id())
+            .cloned()
+            .unwrap_or_default(),
+        changed,
+        new_device: take_current_device_added(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_untouched_configs_get_a_new_backup_root() {
+        let root = Some(PathBuf::from("/home/me/.local/share/GameSaveManager"));
+        let mut config = Config::default();
+        assert_eq!(proposed_backup_root(&config, root.clone()), root);
+
+        config.backup_path = "/mnt/backups".to_string();
+        assert_eq!(proposed_backup_root(&config, root.clone()), None);
+
+        let mut config = Config::default();
+        config.games.push(crate::backup::Game {
+            name: "Game".to_string(),
+            save_paths: Vec::new(),
+            game_paths: Default::default(),
+        });
+        assert_eq!(proposed_backup_root(&config, root), None);
+    }
+
+    #[test]
+    fn unwritable_backup_root_is_rejected() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let root = dir.path().join("nested").join(BACKUP_DIR_NAME);
+        ensure_writable(&root).unwrap();
+        assert!(root.is_dir());
+        assert!(!root.join(WRITE_PROBE_NAME).exists());
+
+        // 已存在同名文件时无法创建目录
+        let file = dir.path().join("file");
+        fs::write(&file, b"").unwrap();
+        assert!(matches!(
+            ensure_writable(&file.join("sub")),
+            Err(BackupError::InvalidBackupRoot(_))
+        ));
+    }
+}
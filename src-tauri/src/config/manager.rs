@@ -0,0 +1,111 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::{Mutex, watch};
+
+use crate::config::{Config, config_path};
+use crate::preclude::*;
+
+static CONFIG_MANAGER: OnceLock<ConfigManager> = OnceLock::new();
+
+/// 缓存在内存中的配置，避免每次读取都打开并解析配置文件
+///
+/// 几乎所有调用方都没有 AppHandle，因此以全局单例的形式提供，而不是 tauri 的托管状态
+pub struct ConfigManager {
+    /// 当前配置，首次读取时从磁盘加载，修改时通知所有订阅者
+    current: watch::Sender<Option<Config>>,
+    /// 串行化写入，防止并发的 `set_config` 交错写入文件
+    write_lock: Mutex<()>,
+    /// 配置文件损坏并已从备份恢复，等待通知前端
+    recovered: AtomicBool,
+}
+
+impl ConfigManager {
+    pub fn global() -> &'static ConfigManager {
+        CONFIG_MANAGER.get_or_init(|| ConfigManager {
+            current: watch::Sender::new(None),
+            write_lock: Mutex::new(()),
+            recovered: AtomicBool::new(false),
+        })
+    }
+
+    /// 获取当前配置的副本
+    pub fn get(&self) -> Result<Config, ConfigError> {
+        if let Some(config) = self.current.borrow().as_ref() {
+            return Ok(config.clone());
+        }
+        let config = self.load_from_disk()?;
+        // 首次加载不算修改，不通知订阅者
+        self.current.send_if_modified(|current| {
+            current.get_or_insert_with(|| config.clone());
+            false
+        });
+        Ok(config)
+    }
+
+    /// 写入配置文件并更新缓存
+    pub async fn set(&self, config: &Config) -> Result<(), ConfigError> {
+        let _guard = self.write_lock.lock().await;
+        self.write(config)
+    }
+
+    /// 在写锁内读取、修改并写入配置，避免并发修改时丢失其他调用方的改动
+    ///
+    /// `f` 返回错误时不会写入任何内容
+    pub async fn update<T, E: From<ConfigError>>(
+        &self,
+        f: impl FnOnce(&mut Config) -> Result<T, E>,
+    ) -> Result<(T, Config), E> {
+        let _guard = self.write_lock.lock().await;
+        let mut config = self.get()?;
+        let result = f(&mut config)?;
+        self.write(&config)?;
+        Ok((result, config))
+    }
+
+    /// 配置文件被直接改写后（如版本迁移）重新从磁盘读取
+    pub fn reload(&self) -> Result<Config, ConfigError> {
+        let config = self.load_from_disk()?;
+        self.current.send_replace(Some(config.clone()));
+        Ok(config)
+    }
+
+    /// 订阅配置的变化，值为 `None` 表示配置尚未加载
+    pub fn subscribe(&self) -> watch::Receiver<Option<Config>> {
+        self.current.subscribe()
+    }
+
+    /// 配置文件不是完整的 JSON 时从备份恢复，在版本迁移读取文件之前调用
+    pub fn repair(&self) -> Result<(), ConfigError> {
+        let (_, recovered) = read_json_or_backup::<serde_json::Value, ConfigError>(config_path())?;
+        if recovered {
+            self.recovered.store(true, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    /// 配置是否曾从备份恢复，只返回一次 `true`
+    pub fn take_recovered(&self) -> bool {
+        self.recovered.swap(false, Ordering::AcqRel)
+    }
+
+    fn write(&self, config: &Config) -> Result<(), ConfigError> {
+        register_secrets(config.settings.cloud_settings.secrets());
+        write_atomic(
+            config_path(),
+            serde_json::to_string_pretty(config)?.as_bytes(),
+        )?;
+        self.current.send_replace(Some(config.clone()));
+        Ok(())
+    }
+
+    fn load_from_disk(&self) -> Result<Config, ConfigError> {
+        let (config, recovered) = read_json_or_backup::<Config, ConfigError>(config_path())?;
+        if recovered {
+            self.recovered.store(true, Ordering::Release);
+        }
+        // 登记后即使配置被完整打印到日志，凭据也会被隐藏
+        register_secrets(config.settings.cloud_settings.secrets());
+        Ok(config)
+    }
+}
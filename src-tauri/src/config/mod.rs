@@ -1,12 +1,22 @@
 mod app_config;
+mod favorites;
+mod first_run;
+mod manager;
 mod quick_actions_settings;
 mod settings;
 mod utils;
 
-pub use app_config::{Config, FavoriteTreeNode};
+pub use app_config::Config;
+pub use favorites::{
+    FavoriteIssue, FavoriteTreeNode, add_favorite, contains_favorite_game, move_favorite,
+    prune_favorite_game, push_favorite_game, remove_favorite, rename_favorite, validate_favorites,
+};
+pub use first_run::{FirstRunSetup, initialize_first_run};
+pub use manager::ConfigManager;
 pub use quick_actions_settings::{
-    QuickActionSoundPreferences, QuickActionSoundSlots, QuickActionSoundSource,
-    QuickActionsSettings,
+    BackupSchedule, ChangeBackupTiming, QuickActionBusyBehavior, QuickActionGameHotkeys,
+    QuickActionOverride, QuickActionSoundPreferences, QuickActionSoundSlots,
+    QuickActionSoundSource, QuickActionsSettings,
 };
-pub use settings::{SaveListExpandBehavior, Settings};
+pub use settings::{BackupRetry, Settings};
 pub use utils::*;
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -18,6 +20,16 @@ impl Default for QuickActionHotkeys {
     }
 }
 
+/// Hotkeys bound to one specific game, independent of `quick_action_game`
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct QuickActionGameHotkeys {
+    pub game_name: String,
+    #[serde(default)]
+    pub backup_keys: Vec<String>,
+    #[serde(default)]
+    pub apply_keys: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum QuickActionSoundSource {
@@ -31,12 +43,29 @@ impl Default for QuickActionSoundSource {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default, Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct QuickActionSoundSlots {
     #[serde(default)]
     pub success: QuickActionSoundSource,
     #[serde(default)]
     pub failure: QuickActionSoundSource,
+    /// Longest time the success sound plays, in seconds, 0 plays the whole file
+    #[serde(default = "default_value::default_sound_max_seconds")]
+    pub success_max_seconds: u32,
+    /// Longest time the failure sound plays, in seconds, 0 plays the whole file
+    #[serde(default = "default_value::default_sound_max_seconds")]
+    pub failure_max_seconds: u32,
+}
+
+impl Default for QuickActionSoundSlots {
+    fn default() -> Self {
+        Self {
+            success: QuickActionSoundSource::default(),
+            failure: QuickActionSoundSource::default(),
+            success_max_seconds: default_value::default_sound_max_seconds(),
+            failure_max_seconds: default_value::default_sound_max_seconds(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
@@ -56,18 +85,90 @@ impl Default for QuickActionSoundPreferences {
     }
 }
 
+/// What to do when a quick action is triggered while one for the same game is still running
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickActionBusyBehavior {
+    /// Skip the new request and notify the user
+    #[default]
+    Drop,
+    /// Run the new request once the current one finished
+    Queue,
+}
+
+/// Auto backup of one game on its own interval, independent of the tray timer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+pub struct BackupSchedule {
+    pub game_name: String,
+    /// 0 disables the schedule
+    pub interval_minutes: u32,
+    /// Reserved for process detection, schedules currently run whether the game is running or not
+    #[serde(default)]
+    pub only_when_running: bool,
+}
+
+/// Quick action settings of one game, `None` falls back to the global setting
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Type)]
+pub struct QuickActionOverride {
+    #[serde(default)]
+    pub enable_sound: Option<bool>,
+    #[serde(default)]
+    pub enable_notification: Option<bool>,
+    /// Overrides `Settings::extra_backup_when_apply` for quick apply
+    #[serde(default)]
+    pub extra_backup_when_apply: Option<bool>,
+    /// Take a snapshot automatically once the saves changed and settled, opt-in per game
+    #[serde(default)]
+    pub auto_backup_on_change: bool,
+}
+
+/// Timing of the snapshots taken when saves change, shared by all games
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(default)]
+pub struct ChangeBackupTiming {
+    /// Seconds without writes before the saves are considered settled
+    pub settle_seconds: u32,
+    /// At most one automatic snapshot per game within this many minutes
+    pub min_interval_minutes: u32,
+}
+
+impl Default for ChangeBackupTiming {
+    fn default() -> Self {
+        Self {
+            settle_seconds: 10,
+            min_interval_minutes: 10,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct QuickActionsSettings {
     #[serde(default = "default_value::default_none")]
     pub quick_action_game: Option<Game>,
     #[serde(default = "default_value::default")]
     pub hotkeys: QuickActionHotkeys,
+    /// Per-game hotkeys, registered alongside the global pair
+    #[serde(default)]
+    pub game_hotkeys: Vec<QuickActionGameHotkeys>,
+    /// Auto backup interval picked in the tray, in minutes, 0 means off
+    #[serde(default)]
+    pub auto_backup_minutes: u32,
+    #[serde(default)]
+    pub busy_behavior: QuickActionBusyBehavior,
     #[serde(default = "default_value::default_true")]
     pub enable_sound: bool,
     #[serde(default = "default_value::default_true")]
     pub enable_notification: bool,
     #[serde(default)]
     pub sounds: QuickActionSoundSlots,
+    /// Per-game overrides, keyed by game name
+    #[serde(default)]
+    pub overrides: HashMap<String, QuickActionOverride>,
+    /// Per-game auto backups, running alongside the tray timer
+    #[serde(default)]
+    pub schedules: Vec<BackupSchedule>,
+    #[serde(default)]
+    pub change_backup: ChangeBackupTiming,
 }
 
 impl Default for QuickActionsSettings {
@@ -75,10 +176,46 @@ impl Default for QuickActionsSettings {
         Self {
             quick_action_game: default_value::default_none(),
             hotkeys: QuickActionHotkeys::default(),
+            game_hotkeys: Vec::new(),
+            auto_backup_minutes: 0,
+            busy_behavior: QuickActionBusyBehavior::default(),
             enable_sound: default_value::default_true(),
             enable_notification: default_value::default_true(),
             sounds: QuickActionSoundSlots::default(),
+            overrides: HashMap::new(),
+            schedules: Vec::new(),
+            change_backup: ChangeBackupTiming::default(),
+        }
+    }
+}
+
+impl QuickActionsSettings {
+    /// The settings used for quick actions of `game_name`, with its override applied
+    pub fn for_game(&self, game_name: &str) -> Self {
+        let mut settings = self.clone();
+        if let Some(game_override) = self.overrides.get(game_name) {
+            settings.enable_sound = game_override.enable_sound.unwrap_or(self.enable_sound);
+            settings.enable_notification = game_override
+                .enable_notification
+                .unwrap_or(self.enable_notification);
         }
+        settings
+    }
+
+    /// Whether quick apply of `game_name` creates an extra backup, `global` is the
+    /// value of `Settings::extra_backup_when_apply`
+    pub fn extra_backup_when_apply(&self, game_name: &str, global: bool) -> bool {
+        self.overrides
+            .get(game_name)
+            .and_then(|o| o.extra_backup_when_apply)
+            .unwrap_or(global)
+    }
+
+    /// Whether `game_name` is backed up automatically when its saves change
+    pub fn auto_backup_on_change(&self, game_name: &str) -> bool {
+        self.overrides
+            .get(game_name)
+            .is_some_and(|o| o.auto_backup_on_change)
     }
 }
 
@@ -90,3 +227,30 @@ impl From<&QuickActionsSettings> for QuickActionSoundPreferences {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_falls_back_to_global_settings() {
+        let mut settings = QuickActionsSettings::default();
+        settings.overrides.insert(
+            "Rogue".to_string(),
+            QuickActionOverride {
+                enable_sound: Some(false),
+                extra_backup_when_apply: Some(false),
+                ..Default::default()
+            },
+        );
+
+        let rogue = settings.for_game("Rogue");
+        assert!(!rogue.enable_sound);
+        assert!(rogue.enable_notification);
+        assert!(!settings.extra_backup_when_apply("Rogue", true));
+
+        let other = settings.for_game("RPG");
+        assert!(other.enable_sound);
+        assert!(settings.extra_backup_when_apply("RPG", true));
+    }
+}
@@ -15,6 +15,26 @@ pub enum SaveListExpandBehavior {
     RememberLast,
 }
 
+/// 备份时文件被游戏占用的重试策略
+///
+/// 不少游戏自动存档后一秒内就会释放文件锁，默认只尝试一次，与旧版本行为一致
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct BackupRetry {
+    /// 读取单个文件的总尝试次数，小于 1 时按 1 处理
+    pub attempts: u32,
+    /// 两次尝试之间的等待时间（毫秒）
+    pub delay_ms: u64,
+}
+
+impl Default for BackupRetry {
+    fn default() -> Self {
+        BackupRetry {
+            attempts: 1,
+            delay_ms: 500,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct Settings {
     #[serde(default = "default_value::default_true")]
@@ -39,12 +59,50 @@ pub struct Settings {
     pub home_page: String,
     #[serde(default = "default_value::default_true")]
     pub log_to_file: bool,
+    /// 日志等级，如 "info"、"debug"，重启后生效
+    #[serde(default = "default_value::default_log_level")]
+    pub log_level: String,
     #[serde(default = "default_value::default_false")]
     pub add_new_to_favorites: bool,
     #[serde(default)]
     pub save_list_expand_behavior: SaveListExpandBehavior,
     #[serde(default = "default_value::default_false")]
     pub save_list_last_expanded: bool,
+    /// 通过管理器启动游戏前先创建快照
+    #[serde(default = "default_value::default_false")]
+    pub backup_before_launch: bool,
+    /// 通过管理器启动的游戏进程退出后创建快照，仅对直接启动的可执行文件有效
+    #[serde(default = "default_value::default_false")]
+    pub backup_after_exit: bool,
+    /// 备份时文件被占用的重试策略
+    #[serde(default = "default_value::default")]
+    pub backup_retry: BackupRetry,
+    /// 恢复快照时还原文件的修改时间，关闭后恢复的文件以恢复时间为修改时间
+    #[serde(default = "default_value::default_true")]
+    pub restore_timestamps: bool,
+    /// 删除快照、游戏与多余的额外备份时移入系统回收站，关闭后永久删除
+    #[serde(default = "default_value::default_true")]
+    pub use_trash_on_delete: bool,
+    /// 创建快照后从磁盘读回压缩包并校验每个文件，未通过时删除该快照并报错
+    #[serde(default = "default_value::default_false")]
+    pub verify_after_backup: bool,
+    /// 删除的游戏保留的天数，期间可以撤销删除，之后才真正删除本地与云端的备份
+    #[serde(default = "default_value::default_retention_days")]
+    pub deleted_game_retention_days: u32,
+    /// 定期检查 GitHub 上是否有新版本
+    #[serde(default = "default_value::default_true")]
+    pub check_updates: bool,
+    /// 检查新版本时请求的 GitHub releases API 地址
+    #[serde(default = "default_value::default_update_check_url")]
+    pub update_check_url: String,
+    /// 自动生成的快照描述模板，用于快捷操作、全部备份以及未填写描述的快照
+    ///
+    /// 支持 `{trigger}`、`{game}`、`{local_time}` 与 `{device}` 占位符
+    #[serde(default = "default_value::default_auto_describe_template")]
+    pub auto_describe_template: String,
+    /// 解析 `<steamUserId>` 时使用的 Steam 账号 ID，为空时自动选择最近登录的账号
+    #[serde(default = "default_value::default_none")]
+    pub steam_user_id: Option<String>,
 }
 
 impl Default for Settings {
@@ -61,9 +119,21 @@ impl Default for Settings {
             default_expend_favorites_tree: default_value::default_false(),
             home_page: default_value::default_home_page(),
             log_to_file: default_value::default_true(),
+            log_level: default_value::default_log_level(),
             add_new_to_favorites: default_value::default_false(),
             save_list_expand_behavior: SaveListExpandBehavior::default(),
             save_list_last_expanded: default_value::default_false(),
+            backup_before_launch: default_value::default_false(),
+            backup_after_exit: default_value::default_false(),
+            backup_retry: BackupRetry::default(),
+            restore_timestamps: default_value::default_true(),
+            use_trash_on_delete: default_value::default_true(),
+            verify_after_backup: default_value::default_false(),
+            deleted_game_retention_days: default_value::default_retention_days(),
+            check_updates: default_value::default_true(),
+            update_check_url: default_value::default_update_check_url(),
+            auto_describe_template: default_value::default_auto_describe_template(),
+            steam_user_id: default_value::default_none(),
         }
     }
 }
@@ -1,10 +1,115 @@
-use std::fs::File;
-use std::{fs, path};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use crate::config::Config;
+use crate::cloud_sync::{OutboxOperation, sync_or_queue};
+use crate::config::{Config, ConfigManager, validate_favorites};
 use crate::preclude::*;
-use crate::updater::update_config;
-use log::info;
+use crate::updater::{normalize_snapshot_paths, update_config};
+use log::{debug, info, warn};
+
+/// 配置文件名
+const CONFIG_FILE_NAME: &str = "GameSaveManager.config.json";
+/// 配置目录下的应用文件夹，与 tauri.conf.json 的 identifier 一致
+const APP_DIR_NAME: &str = "com.game-save-manager";
+/// 指定配置文件路径的环境变量，供便携版使用
+#[cfg_attr(test, allow(dead_code))]
+const CONFIG_PATH_ENV: &str = "RGSM_CONFIG_PATH";
+/// 指定配置文件路径的命令行参数，优先于环境变量
+const CONFIG_PATH_ARG: &str = "--config";
+
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// 配置文件的位置，首次调用时确定，之后不再改变
+///
+/// 优先级：`--config <path>` 参数 > `RGSM_CONFIG_PATH` 环境变量 > 系统配置目录
+#[cfg(not(test))]
+pub fn config_path() -> &'static Path {
+    CONFIG_PATH.get_or_init(|| {
+        resolve_config_path(
+            config_path_arg(std::env::args()),
+            std::env::var_os(CONFIG_PATH_ENV).map(PathBuf::from),
+            dirs::config_dir(),
+        )
+    })
+}
+
+/// 测试使用系统临时目录中每个测试进程独有的配置文件，不依赖也不写入工作目录
+#[cfg(test)]
+pub fn config_path() -> &'static Path {
+    CONFIG_PATH.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("rgsm-test-{}", std::process::id()));
+        let _ = fs::create_dir_all(&dir);
+        dir.join(CONFIG_FILE_NAME)
+    })
+}
+
+/// 程序所在的目录，工作目录下没有旧版配置时在这里查找
+fn app_base_dir() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+}
+
+/// 旧版本的配置文件，与旧版一样优先使用工作目录下的文件
+fn legacy_config_path() -> PathBuf {
+    find_legacy_config(Path::new("."), &app_base_dir())
+}
+
+/// 工作目录下没有旧配置时，再查找程序所在目录；都不存在时返回工作目录下的路径
+fn find_legacy_config(cwd: &Path, base_dir: &Path) -> PathBuf {
+    let in_cwd = cwd.join(CONFIG_FILE_NAME);
+    let in_base_dir = base_dir.join(CONFIG_FILE_NAME);
+    if !in_cwd.is_file() && in_base_dir.is_file() {
+        in_base_dir
+    } else {
+        in_cwd
+    }
+}
+
+/// 从命令行参数中读取 `--config <path>` 或 `--config=<path>`
+fn config_path_arg(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_PATH_ARG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+fn resolve_config_path(
+    arg: Option<PathBuf>,
+    env: Option<PathBuf>,
+    config_dir: Option<PathBuf>,
+) -> PathBuf {
+    arg.or(env.filter(|p| !p.as_os_str().is_empty()))
+        .or_else(|| config_dir.map(|dir| dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME)))
+        .unwrap_or_else(legacy_config_path)
+}
+
+/// 将工作目录下的旧配置文件移动到新位置，目标已存在时不做任何事
+///
+/// 返回是否发生了迁移
+fn migrate_legacy_config(legacy: &Path, target: &Path) -> Result<bool, ConfigError> {
+    if target.exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // 跨分区时 rename 会失败，此时退回到复制后删除
+    if fs::rename(legacy, target).is_err() {
+        fs::copy(legacy, target)?;
+        if let Err(e) = fs::remove_file(legacy) {
+            warn!(target:"rgsm::config", "Failed to remove legacy config {:?}: {e}", legacy);
+        }
+    }
+    Ok(true)
+}
 
 /// Set settings to original state
 pub async fn reset_settings() -> Result<(), ConfigError> {
@@ -16,47 +121,160 @@ pub async fn reset_settings() -> Result<(), ConfigError> {
 
 /// Create a config file
 fn init_config() -> Result<(), ConfigError> {
-    info!("Init config file.");
-    fs::write(
-        "./GameSaveManager.config.json",
-        serde_json::to_string_pretty(&Config::default())?,
+    info!("Init config file at {:?}.", config_path());
+    if let Some(parent) = config_path().parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(
+        config_path(),
+        serde_json::to_string_pretty(&Config::default())?.as_bytes(),
     )?;
     Ok(())
 }
 
-/// Get the current config file
+/// 以脱敏后的形式将配置写入调试日志，所有打印配置的地方都应使用它
+pub fn log_config_safely(context: &str, config: &Config) {
+    debug!(target:"rgsm::config", "{context}: {:?}", config.clone().sanitize());
+}
+
+/// Get the current config, served from the cache in `ConfigManager`
 pub fn get_config() -> Result<Config, ConfigError> {
-    let file = File::open("./GameSaveManager.config.json")?;
-    Ok(serde_json::from_reader(file)?)
+    ConfigManager::global().get()
 }
 
 /// Replace the config file with a new config struct
 pub async fn set_config(config: &Config) -> Result<(), ConfigError> {
-    fs::write(
-        "./GameSaveManager.config.json",
-        serde_json::to_string_pretty(&config)?,
-    )?;
-    // 处理云同步，上传新的配置文件
+    ConfigManager::global().set(config).await?;
+    // 处理云同步，上传新的配置文件，失败时留在队列中等待下次同步
     if config.settings.cloud_settings.always_sync {
-        let op = config.settings.cloud_settings.backend.get_op()?;
-        crate::cloud_sync::upload_config(&op).await?;
+        let _ = sync_or_queue(vec![OutboxOperation::UploadConfig]).await;
     }
     Ok(())
 }
 
+/// 基于最新的配置进行修改并保存，修改期间不会被其他写入打断
+pub async fn modify_config<T, E: From<ConfigError>>(
+    f: impl FnOnce(&mut Config) -> Result<T, E>,
+) -> Result<T, E> {
+    let (result, config) = ConfigManager::global().update(f).await?;
+    // 处理云同步，上传新的配置文件，失败时留在队列中等待下次同步
+    if config.settings.cloud_settings.always_sync {
+        let _ = sync_or_queue(vec![OutboxOperation::UploadConfig]).await;
+    }
+    Ok(result)
+}
+
 /// Check the config file exists or not
 /// if not, then create one
 /// then send the config to the front end
 pub fn config_check() -> Result<(), ConfigError> {
-    let config_path = path::Path::new("./GameSaveManager.config.json");
+    let config_path = config_path();
+    let legacy = legacy_config_path();
+    if config_path != legacy && migrate_legacy_config(&legacy, config_path)? {
+        info!(target:"rgsm::config", "Moved config file to {:?}", config_path);
+    }
     if !config_path.is_file() || !config_path.exists() {
         init_config()?;
     }
+    // 写入中途崩溃可能留下截断的文件，先尝试从备份恢复
+    ConfigManager::global().repair()?;
     // 执行配置迁移与升级
     update_config(config_path)?;
-    // 重新加载配置
-    let config = get_config()?;
+    // 重新加载配置，迁移可能已经改写了文件
+    let mut config = ConfigManager::global().reload()?;
+    // 新配置或新设备上首次运行时登记当前设备，否则设备名无处显示与修改
+    if crate::device::ensure_current_device(&mut config) {
+        info!(target:"rgsm::config", "Registered current device {:?}", config.devices.get(crate::device::get_current_device_id()));
+        write_atomic(
+            config_path,
+            serde_json::to_string_pretty(&config)?.as_bytes(),
+        )?;
+        config = ConfigManager::global().reload()?;
+    }
+    // 旧版本的快照记录保存的是绝对路径
+    normalize_snapshot_paths(&config);
+    // 收藏夹的不一致只记录，指向已删除游戏的节点会在下次修改收藏夹时清理
+    for issue in validate_favorites(&config.favorites, &config.games) {
+        warn!(target:"rgsm::config", "{}", issue);
+    }
     // 应用本地化语言
     rust_i18n::set_locale(&config.settings.locale);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_path_precedence() {
+        let args = |a: &[&str]| {
+            a.iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        assert_eq!(
+            config_path_arg(args(&["rgsm", "--config", "a.json"])),
+            Some(PathBuf::from("a.json"))
+        );
+        assert_eq!(
+            config_path_arg(args(&["rgsm", "--config=b.json"])),
+            Some(PathBuf::from("b.json"))
+        );
+        assert_eq!(config_path_arg(args(&["rgsm", "--config"])), None);
+
+        let dir = Some(PathBuf::from("/cfg"));
+        assert_eq!(
+            resolve_config_path(Some("a.json".into()), Some("b.json".into()), dir.clone()),
+            PathBuf::from("a.json")
+        );
+        assert_eq!(
+            resolve_config_path(None, Some("b.json".into()), dir.clone()),
+            PathBuf::from("b.json")
+        );
+        assert_eq!(
+            resolve_config_path(None, Some("".into()), dir.clone()),
+            Path::new("/cfg").join(APP_DIR_NAME).join(CONFIG_FILE_NAME)
+        );
+        assert_eq!(resolve_config_path(None, None, None), legacy_config_path());
+    }
+
+    #[test]
+    fn legacy_config_is_moved_once() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let legacy = dir.path().join(CONFIG_FILE_NAME);
+        let target = dir
+            .path()
+            .join("config")
+            .join(APP_DIR_NAME)
+            .join(CONFIG_FILE_NAME);
+        fs::write(&legacy, "old").unwrap();
+
+        assert!(migrate_legacy_config(&legacy, &target).unwrap());
+        assert!(!legacy.exists());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old");
+
+        // 新位置已有配置时不会被旧文件覆盖
+        fs::write(&legacy, "older").unwrap();
+        assert!(!migrate_legacy_config(&legacy, &target).unwrap());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old");
+    }
+
+    #[test]
+    fn legacy_config_prefers_working_directory() {
+        let cwd = temp_dir::TempDir::new().unwrap();
+        let base_dir = temp_dir::TempDir::new().unwrap();
+        let in_cwd = cwd.path().join(CONFIG_FILE_NAME);
+        let in_base_dir = base_dir.path().join(CONFIG_FILE_NAME);
+
+        // 都不存在时仍指向工作目录
+        assert_eq!(find_legacy_config(cwd.path(), base_dir.path()), in_cwd);
+
+        fs::write(&in_base_dir, "base").unwrap();
+        assert_eq!(find_legacy_config(cwd.path(), base_dir.path()), in_base_dir);
+
+        fs::write(&in_cwd, "cwd").unwrap();
+        assert_eq!(find_legacy_config(cwd.path(), base_dir.path()), in_cwd);
+    }
+}
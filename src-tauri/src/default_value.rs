@@ -1,7 +1,5 @@
 use std::collections::HashMap;
 
-use crate::cloud_sync::Backend;
-
 pub fn default_false() -> bool {
     false
 }
@@ -11,15 +9,33 @@ pub fn default_true() -> bool {
 pub fn default_zero() -> u64 {
     0
 }
+pub fn default_retention_days() -> u32 {
+    7
+}
+pub fn default_sound_max_seconds() -> u32 {
+    10
+}
+pub fn default_log_level() -> String {
+    "info".to_string()
+}
+pub fn default_update_check_url() -> String {
+    "https://api.github.com/repos/mcthesw/game-save-manager/releases/latest".to_string()
+}
+pub fn default_auto_describe_template() -> String {
+    "{trigger}".to_string()
+}
+pub fn default_cloud_max_attempts() -> u32 {
+    4
+}
+pub fn default_upload_concurrency() -> u32 {
+    1
+}
 pub fn default_root_path() -> String {
     "/game-save-manager".to_string()
 }
 pub fn default_home_page() -> String {
     "/".to_string()
 }
-pub fn default_backend() -> Backend {
-    Backend::Disabled
-}
 pub fn default_locale() -> String {
     "zh_SIMPLIFIED".to_owned()
 }
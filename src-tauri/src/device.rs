@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::{Config, modify_config};
+use crate::preclude::*;
 
 // 使用 String 作为设备 ID 的类型别名
 pub type DeviceId = String;
@@ -15,6 +19,9 @@ pub struct Device {
 // 存储当前设备的静态变量，使用 OnceLock 确保只初始化一次
 static CURRENT_DEVICE_ID: OnceLock<DeviceId> = OnceLock::new();
 
+// 本次启动时当前设备是否刚被写入配置，等待前端显示设备设置对话框
+static CURRENT_DEVICE_ADDED: AtomicBool = AtomicBool::new(false);
+
 /// 获取当前设备的ID。
 /// 首次调用时会生成 UUID 作为设备 ID。
 /// 后续调用将返回缓存的设备ID。
@@ -40,11 +47,131 @@ impl Default for Device {
     }
 }
 
+/// 列出所有已知设备，当前设备排在最前面
+///
+/// 当前设备尚未写入配置时使用默认的设备信息
+pub fn list_devices(config: &Config) -> Vec<Device> {
+    let current = get_current_device_id();
+    let mut devices: Vec<Device> = config
+        .devices
+        .values()
+        .filter(|d| &d.id != current)
+        .cloned()
+        .collect();
+    devices.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    devices.insert(0, config.devices.get(current).cloned().unwrap_or_default());
+    devices
+}
+
+/// 确保当前设备已写入配置，设备名默认为主机名
+///
+/// 返回是否新增了设备，新增时记录下来供 `take_current_device_added` 读取
+pub fn ensure_current_device(config: &mut Config) -> bool {
+    let id = get_current_device_id();
+    if config.devices.contains_key(id) {
+        return false;
+    }
+    config.devices.insert(
+        id.clone(),
+        Device {
+            id: id.clone(),
+            name: get_system_hostname(),
+        },
+    );
+    CURRENT_DEVICE_ADDED.store(true, Ordering::Release);
+    true
+}
+
+/// 当前设备是否在本次启动时才被写入配置，只返回一次 `true`
+pub fn take_current_device_added() -> bool {
+    CURRENT_DEVICE_ADDED.swap(false, Ordering::AcqRel)
+}
+
+/// 修改当前设备的名称
+pub async fn set_current_device_name(new_name: &str) -> Result<(), DeviceError> {
+    rename_device(get_current_device_id(), new_name).await
+}
+
+/// 修改设备名称，当前设备尚未写入配置时会一并写入
+pub async fn rename_device(device_id: &str, new_name: &str) -> Result<(), DeviceError> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err(DeviceError::InvalidName(new_name.to_string()));
+    }
+    modify_config(|config| {
+        match config.devices.get_mut(device_id) {
+            Some(device) => device.name = new_name.to_string(),
+            None if device_id == get_current_device_id().as_str() => {
+                config.devices.insert(
+                    device_id.to_string(),
+                    Device {
+                        id: device_id.to_string(),
+                        name: new_name.to_string(),
+                    },
+                );
+            }
+            None => return Err(DeviceError::NotExist(device_id.to_string())),
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 移除设备，`strip_paths` 为真时同时删除所有游戏中该设备的路径
+///
+/// 不允许移除当前设备。设备已不在列表中但仍残留路径时，也可以用来清理这些路径
+pub async fn remove_device(device_id: &str, strip_paths: bool) -> Result<(), DeviceError> {
+    if device_id == get_current_device_id().as_str() {
+        return Err(DeviceError::RemoveCurrent);
+    }
+    modify_config(|config| {
+        let removed = config.devices.remove(device_id).is_some();
+        let stripped = if strip_paths {
+            strip_device_paths(config, device_id)
+        } else {
+            0
+        };
+        if !removed && stripped == 0 {
+            return Err(DeviceError::NotExist(device_id.to_string()));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 删除所有游戏（包括快捷操作中保存的副本）中该设备的存档路径与启动路径，返回删除的数量
+fn strip_device_paths(config: &mut Config, device_id: &str) -> usize {
+    let games = config
+        .games
+        .iter_mut()
+        .chain(config.quick_action.quick_action_game.as_mut());
+    let mut stripped = 0;
+    for game in games {
+        stripped += game.game_paths.remove(device_id).is_some() as usize;
+        for unit in &mut game.save_paths {
+            stripped += unit.paths.remove(device_id).is_some() as usize;
+        }
+    }
+    stripped
+}
+
 // 单元测试
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn current_device_is_registered_once() {
+        let id = get_current_device_id();
+        let mut config = Config::default();
+        assert!(ensure_current_device(&mut config));
+        assert_eq!(config.devices[id].name, get_system_hostname());
+
+        config.devices.get_mut(id).unwrap().name = "Renamed".into();
+        assert!(!ensure_current_device(&mut config));
+        assert_eq!(config.devices[id].name, "Renamed");
+    }
+
     #[test]
     fn test_get_current_device_returns_consistent_info() {
         // 多次调用应返回相同的设备信息（在同一次运行中）
@@ -54,4 +181,31 @@ mod tests {
         assert!(!device1.is_empty());
         println!("Device ID: {}", device1);
     }
+
+    #[test]
+    fn strip_device_paths_removes_every_entry() {
+        use crate::backup::{Game, SaveUnit};
+        use std::collections::HashMap;
+
+        let game = Game {
+            name: "Game".into(),
+            save_paths: vec![SaveUnit {
+                paths: HashMap::from([
+                    ("old".to_string(), "D:/save".to_string()),
+                    ("new".to_string(), "/home/save".to_string()),
+                ]),
+                ..Default::default()
+            }],
+            game_paths: HashMap::from([("old".to_string(), "D:/game.exe".to_string())]),
+        };
+        let mut config = Config::default();
+        config.games.push(game.clone());
+        config.quick_action.quick_action_game = Some(game);
+
+        assert_eq!(strip_device_paths(&mut config, "old"), 4);
+        assert_eq!(strip_device_paths(&mut config, "old"), 0);
+        let unit = &config.games[0].save_paths[0];
+        assert!(!unit.paths.contains_key("old"));
+        assert!(unit.paths.contains_key("new"));
+    }
 }
@@ -0,0 +1,137 @@
+//! 日志与诊断信息
+//!
+//! 用户反馈问题时，可以把最近的日志、脱敏后的配置与上次扫描结果打包成一个压缩包附在 issue 中。
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::{LevelFilter, info, warn};
+use tauri::{AppHandle, Manager};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::config::get_config;
+use crate::game_scan::load_last_scan;
+use crate::preclude::*;
+
+/// 日志插件写入的文件名前缀，轮换后的文件名为 `logs_<时间>.log`
+const LOG_FILE_PREFIX: &str = "logs";
+/// 单个日志文件的最大字节数，超过后轮换
+pub const LOG_MAX_FILE_SIZE: u128 = 2_000_000;
+/// 诊断包中最多包含的日志文件数
+const DIAGNOSTIC_LOG_FILES: usize = 5;
+
+/// 解析配置中的日志等级，无法识别时使用 Info
+pub fn parse_log_level(level: &str) -> LevelFilter {
+    level.trim().parse().unwrap_or_else(|_| {
+        warn!(target: "rgsm::diagnostics", "Unknown log level {:?}, using info", level);
+        LevelFilter::Info
+    })
+}
+
+/// 日志插件 `LogDir` 目标所在的目录
+pub fn log_dir(app: &AppHandle) -> Result<PathBuf> {
+    app.path()
+        .app_log_dir()
+        .context("Failed to resolve the log directory")
+}
+
+/// 最近修改的日志文件，最新的在前
+fn recent_log_files(dir: &Path, count: usize) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(LOG_FILE_PREFIX) && name.ends_with(".log")
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(files
+        .into_iter()
+        .take(count)
+        .map(|(_, path)| path)
+        .collect())
+}
+
+/// 将最近的日志、脱敏后的配置与上次扫描结果打包到 `target`
+///
+/// 配置经过 `Sanitizable` 去除云存储凭据后才写入
+pub fn collect_diagnostics(app: &AppHandle, target: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(
+        File::create(target).with_context(|| format!("Failed to create {}", target.display()))?,
+    );
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("info.txt", options)?;
+    writeln!(zip, "version: {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(
+        zip,
+        "os: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )?;
+
+    let config = get_config()?.sanitize();
+    zip.start_file("config.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&config)?.as_bytes())?;
+
+    match load_last_scan(app) {
+        Ok(Some(scan)) => {
+            zip.start_file("last_scan.json", options)?;
+            zip.write_all(serde_json::to_string_pretty(&scan)?.as_bytes())?;
+        }
+        Ok(None) => {}
+        Err(e) => warn!(target: "rgsm::diagnostics", "Skipping the last scan: {:?}", e),
+    }
+
+    let dir = log_dir(app)?;
+    if dir.is_dir() {
+        for path in recent_log_files(&dir, DIAGNOSTIC_LOG_FILES)? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            zip.start_file(format!("logs/{name}"), options)?;
+            zip.write_all(&fs::read(&path)?)?;
+        }
+    }
+    zip.finish()?;
+    info!(target: "rgsm::diagnostics", "Collected diagnostics to {}", target.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_levels_fall_back_to_info() {
+        assert_eq!(parse_log_level("debug"), LevelFilter::Debug);
+        assert_eq!(parse_log_level(" WARN "), LevelFilter::Warn);
+        assert_eq!(parse_log_level("verbose"), LevelFilter::Info);
+    }
+
+    #[test]
+    fn only_log_files_are_collected() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        for name in [
+            "logs.log",
+            "logs_2024-01-01_00-00-00.log",
+            "notes.txt",
+            "other.log",
+        ] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        let mut names: Vec<_> = recent_log_files(dir.path(), 5)
+            .unwrap()
+            .into_iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["logs.log", "logs_2024-01-01_00-00-00.log"]);
+        assert_eq!(recent_log_files(dir.path(), 1).unwrap().len(), 1);
+    }
+}
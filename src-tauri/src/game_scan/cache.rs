@@ -0,0 +1,146 @@
+//! PCGW 索引内存缓存（由 Tauri 托管）
+//!
+//! 首次访问时从 SQLite 加载并构建预规范化的查找表，后续命令直接复用；
+//! 刷新或导入索引时调用 `invalidate` 使缓存失效，下次访问重新加载。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use log::info;
+use tauri::AppHandle;
+use tokio::sync::RwLock;
+
+use super::db::load_pcgw_index;
+use super::fuzzy::FuzzyTerms;
+use super::types::GameInfo;
+
+/// 单个条目预先计算的查找键（与 `PcgwIndexData::games` 一一对应）
+#[derive(Debug, Clone)]
+pub(super) struct EntryKeys {
+    /// 小写主名称
+    pub name_lower: String,
+    /// 小写别名
+    pub aliases_lower: Vec<String>,
+    /// 小写平台集合（来自保存规则）
+    pub platforms_lower: Vec<String>,
+    /// 主名称的模糊匹配词项
+    pub name_terms: FuzzyTerms,
+    /// 别名的模糊匹配词项
+    pub alias_terms: Vec<FuzzyTerms>,
+}
+
+/// 已加载的 PCGW 索引及其查找表
+#[derive(Debug, Default)]
+pub struct PcgwIndexData {
+    /// 原始条目，保持数据库中的顺序
+    pub games: Vec<GameInfo>,
+    pub(super) keys: Vec<EntryKeys>,
+    /// 小写名称/别名 → 首个匹配条目的下标
+    exact: HashMap<String, usize>,
+}
+
+impl PcgwIndexData {
+    /// 由条目列表构建查找表
+    pub fn new(games: Vec<GameInfo>) -> Self {
+        let mut exact = HashMap::new();
+        let keys = games
+            .iter()
+            .enumerate()
+            .map(|(idx, g)| {
+                let name_lower = g.name.to_lowercase();
+                let aliases_lower: Vec<String> =
+                    g.aliases.iter().map(|a| a.to_lowercase()).collect();
+                // 保留最靠前的条目，与线性查找的结果一致
+                exact.entry(name_lower.clone()).or_insert(idx);
+                for a in &aliases_lower {
+                    exact.entry(a.clone()).or_insert(idx);
+                }
+                let mut platforms_lower: Vec<String> = g
+                    .save_rules
+                    .iter()
+                    .flat_map(|r| r.platforms.iter().map(|p| p.to_lowercase()))
+                    .collect();
+                platforms_lower.sort();
+                platforms_lower.dedup();
+                EntryKeys {
+                    name_terms: FuzzyTerms::new(&g.name),
+                    alias_terms: g.aliases.iter().map(|a| FuzzyTerms::new(a)).collect(),
+                    name_lower,
+                    aliases_lower,
+                    platforms_lower,
+                }
+            })
+            .collect();
+        Self { games, keys, exact }
+    }
+
+    /// 按名称或别名精确查找（大小写不敏感）
+    pub fn find_by_name(&self, name: &str) -> Option<&GameInfo> {
+        let lower = name.trim().to_lowercase();
+        self.exact.get(&lower).map(|&idx| &self.games[idx])
+    }
+
+    /// 条目及其预计算查找键
+    pub(super) fn entries(&self) -> impl Iterator<Item = (&GameInfo, &EntryKeys)> {
+        self.games.iter().zip(self.keys.iter())
+    }
+}
+
+/// PCGW 索引缓存
+#[derive(Default)]
+pub struct PcgwIndexCache {
+    inner: RwLock<Option<Arc<PcgwIndexData>>>,
+}
+
+impl PcgwIndexCache {
+    /// 获取索引，首次访问时加载；加载失败不会被缓存，下次访问将重试
+    pub async fn get(&self, app: &AppHandle) -> Result<Arc<PcgwIndexData>> {
+        if let Some(data) = self.inner.read().await.as_ref() {
+            return Ok(data.clone());
+        }
+        let mut guard = self.inner.write().await;
+        // 等待写锁期间可能已被其他任务加载
+        if let Some(data) = guard.as_ref() {
+            return Ok(data.clone());
+        }
+        let data = Arc::new(PcgwIndexData::new(load_pcgw_index(app).await?));
+        info!(target:"rgsm::pcgw", "PCGW index cached, entries: {}", data.games.len());
+        *guard = Some(data.clone());
+        Ok(data)
+    }
+
+    /// 使缓存失效
+    pub async fn invalidate(&self) {
+        *self.inner.write().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(name: &str, aliases: &[&str]) -> GameInfo {
+        GameInfo {
+            name: name.into(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            pcgw_id: None,
+            install_rules: Vec::new(),
+            save_rules: Vec::new(),
+        }
+    }
+
+    /// 测试：精确查找返回最靠前的匹配条目，且名称与别名均大小写不敏感
+    #[test]
+    fn find_by_name_prefers_first_entry() {
+        let data = PcgwIndexData::new(vec![
+            game("Alpha", &["Shared"]),
+            game("Shared", &[]),
+            game("Beta", &["B"]),
+        ]);
+        assert_eq!(data.find_by_name(" shared ").unwrap().name, "Alpha");
+        assert_eq!(data.find_by_name("b").unwrap().name, "Beta");
+        assert!(data.find_by_name("gamma").is_none());
+        assert_eq!(data.keys[0].name_terms.compact(), "alpha");
+    }
+}
@@ -1,13 +1,13 @@
 use anyhow::{Context, Result};
+use log::info;
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
 use std::path::Path;
+use std::path::PathBuf;
 use tauri::AppHandle;
-use tauri::path::BaseDirectory;
 use tauri::Manager;
-use log::info;
-use rusqlite::Connection;
+use tauri::path::BaseDirectory;
 // 注意：索引加载已固定使用默认 SQLite 路径，不再读取配置文件
 
 /// 远端 PCGW 索引候选地址（优先顺序）
@@ -70,7 +70,10 @@ pub async fn load_pcgw_index_meta(app: &AppHandle) -> Result<PcgwIndexMeta> {
 
     let games = load_pcgw_index_from_sqlite_direct(&sqlite_path)
         .with_context(|| format!("Failed to load sqlite index at {}", sqlite_path.display()))?;
-    Ok(PcgwIndexMeta { version: Some("sqlite".into()), count: games.len() })
+    Ok(PcgwIndexMeta {
+        version: Some("sqlite".into()),
+        count: games.len(),
+    })
 }
 
 /// 远端下载并缓存 PCGW 索引到 AppData
@@ -88,11 +91,14 @@ pub async fn update_pcgw_index_remote(app: &AppHandle) -> Result<PcgwIndexMeta>
 /// - 输入：`src_path` 本地 JSON 文件路径
 /// - 行为：读取并校验结构后写入 `AppData/RGSM/pcgw_index.json`
 /// - 返回：索引元信息
-pub async fn import_pcgw_index_from_file(app: &AppHandle, src_path: &Path) -> Result<PcgwIndexMeta> {
+pub async fn import_pcgw_index_from_file(
+    app: &AppHandle,
+    src_path: &Path,
+) -> Result<PcgwIndexMeta> {
     let text = fs::read_to_string(src_path)
         .with_context(|| format!("Failed to read source file at {}", src_path.display()))?;
-    let index: PcgwIndex = serde_json::from_str(&text)
-        .context("Failed to parse provided PCGW index json")?;
+    let index: PcgwIndex =
+        serde_json::from_str(&text).context("Failed to parse provided PCGW index json")?;
 
     let cache_dir = app
         .path()
@@ -106,7 +112,10 @@ pub async fn import_pcgw_index_from_file(app: &AppHandle, src_path: &Path) -> Re
     fs::write(&cache_path, &text)
         .with_context(|| format!("Failed to write index at {}", cache_path.display()))?;
 
-    Ok(PcgwIndexMeta { version: Some(index.version), count: index.games.len() })
+    Ok(PcgwIndexMeta {
+        version: Some(index.version),
+        count: index.games.len(),
+    })
 }
 
 /// 从SQLite数据库（如 Game-Save-Manager 的 `database.db`）导入并转换为PCGW索引
@@ -114,7 +123,10 @@ pub async fn import_pcgw_index_from_file(app: &AppHandle, src_path: &Path) -> Re
 /// - 输入：`sqlite_path` SQLite文件路径
 /// - 行为：尽可能智能地探测表和列，提取 `name`、`aliases`、`pcgw_id` 以及可能的保存路径字段，生成最小可用的索引
 /// - 输出：索引元信息（版本与条目数量）并写入缓存 `AppData/RGSM/pcgw_index.json`
-pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path) -> Result<PcgwIndexMeta> {
+pub async fn import_pcgw_index_from_sqlite(
+    app: &AppHandle,
+    sqlite_path: &Path,
+) -> Result<PcgwIndexMeta> {
     let conn = Connection::open(sqlite_path)
         .with_context(|| format!("Failed to open sqlite at {}", sqlite_path.display()))?;
 
@@ -137,7 +149,10 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
             .query_map([], |row| row.get::<usize, String>(1))?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| anyhow::anyhow!(e))?;
-        if cols.iter().any(|c| c.eq_ignore_ascii_case("name") || c.eq_ignore_ascii_case("title")) {
+        if cols
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case("name") || c.eq_ignore_ascii_case("title"))
+        {
             game_table = Some(t.clone());
             game_columns = cols;
             break;
@@ -145,7 +160,9 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
     }
 
     let Some(game_table) = game_table else {
-        return Err(anyhow::anyhow!("No suitable game table found (requires a 'name' column)").into());
+        return Err(
+            anyhow::anyhow!("No suitable game table found (requires a 'name' column)").into(),
+        );
     };
 
     // 判断可能的列名
@@ -156,30 +173,39 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
         .unwrap();
     let alias_col = game_columns
         .iter()
-        .find(|c| c.eq_ignore_ascii_case("aliases") || c.eq_ignore_ascii_case("alias") || c.eq_ignore_ascii_case("aka"))
+        .find(|c| {
+            c.eq_ignore_ascii_case("aliases")
+                || c.eq_ignore_ascii_case("alias")
+                || c.eq_ignore_ascii_case("aka")
+        })
         .cloned();
     // 增强：识别本地化列（如 zh_CN、zh-cn、name_zh_cn 等）并作为别名来源
     let zh_like_col = game_columns
         .iter()
         .find(|c| {
             let lc = c.to_lowercase();
-            lc == "zh_cn" || lc == "zh-cn" || lc == "zh" || lc.contains("name_zh") || lc.contains("chinese")
+            lc == "zh_cn"
+                || lc == "zh-cn"
+                || lc == "zh"
+                || lc.contains("name_zh")
+                || lc.contains("chinese")
         })
         .cloned();
     let pcgw_col = game_columns
         .iter()
-        .find(|c| c.eq_ignore_ascii_case("pcgw_id") || c.eq_ignore_ascii_case("slug") || c.eq_ignore_ascii_case("wiki_id") || c.eq_ignore_ascii_case("pcgw"))
+        .find(|c| {
+            c.eq_ignore_ascii_case("pcgw_id")
+                || c.eq_ignore_ascii_case("slug")
+                || c.eq_ignore_ascii_case("wiki_id")
+                || c.eq_ignore_ascii_case("pcgw")
+        })
         .cloned();
 
     // 提取行并转换为 GameInfo（基于列索引以保证稳定性）
     let mut games: Vec<GameInfo> = Vec::new();
     let sql = format!("SELECT * FROM {}", game_table);
     let mut s = conn.prepare(&sql)?;
-    let col_names: Vec<String> = s
-        .column_names()
-        .iter()
-        .map(|c| c.to_string())
-        .collect();
+    let col_names: Vec<String> = s.column_names().iter().map(|c| c.to_string()).collect();
 
     // 计算列索引
     let name_idx = col_names
@@ -202,7 +228,11 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
         .enumerate()
         .filter_map(|(i, c)| {
             let lc = c.to_lowercase();
-            if lc.contains("path") || lc.contains("save") || lc.contains("location") || lc.contains("documents") {
+            if lc.contains("path")
+                || lc.contains("save")
+                || lc.contains("location")
+                || lc.contains("documents")
+            {
                 Some(i)
             } else {
                 None
@@ -213,17 +243,13 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
     let mut rows = s.query([])?;
     while let Some(row) = rows.next()? {
         // 读取基础字段
-        let name: String = row
-            .get::<usize, String>(name_idx)
-            .unwrap_or_default();
+        let name: String = row.get::<usize, String>(name_idx).unwrap_or_default();
         if name.trim().is_empty() {
             continue;
         }
 
         let mut aliases: Vec<String> = if let Some(ai) = alias_idx {
-            let sopt: Option<String> = row
-                .get::<usize, Option<String>>(ai)
-                .unwrap_or(None);
+            let sopt: Option<String> = row.get::<usize, Option<String>>(ai).unwrap_or(None);
             sopt.map(|s| split_aliases(&s)).unwrap_or_default()
         } else {
             Vec::new()
@@ -240,7 +266,9 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
 
         let pcgw_id: Option<String> = if let Some(pi) = pcgw_idx {
             row.get::<usize, Option<String>>(pi).unwrap_or(None)
-        } else { None };
+        } else {
+            None
+        };
 
         let mut gi = GameInfo {
             name,
@@ -252,14 +280,15 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
 
         // 读取可能的路径列
         for idx in &path_like_idxs {
-            let val_opt: Option<String> = row
-                .get::<usize, Option<String>>(*idx)
-                .unwrap_or(None);
+            let val_opt: Option<String> = row.get::<usize, Option<String>>(*idx).unwrap_or(None);
             if let Some(val) = val_opt {
                 if !val.trim().is_empty() {
                     gi.save_rules.push(super::types::SavePathRule {
                         id: format!("{}-{}", gi.name.replace(' ', "_"), col_names[*idx].as_str()),
-                        description: Some(format!("Imported from {}.{}", game_table, col_names[*idx])),
+                        description: Some(format!(
+                            "Imported from {}.{}",
+                            game_table, col_names[*idx]
+                        )),
                         path_template: normalize_path_template(&val),
                         requires: None,
                         platforms: vec!["windows".into()],
@@ -273,7 +302,10 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
     }
 
     // 写入缓存
-    let index = PcgwIndex { version: "db-import".into(), games };
+    let index = PcgwIndex {
+        version: "db-import".into(),
+        games,
+    };
     let cache_dir = app
         .path()
         .resolve("RGSM", BaseDirectory::AppData)
@@ -287,7 +319,10 @@ pub async fn import_pcgw_index_from_sqlite(app: &AppHandle, sqlite_path: &Path)
     fs::write(&cache_path, &text)
         .with_context(|| format!("Failed to write index at {}", cache_path.display()))?;
 
-    Ok(PcgwIndexMeta { version: Some(index.version), count: index.games.len() })
+    Ok(PcgwIndexMeta {
+        version: Some(index.version),
+        count: index.games.len(),
+    })
 }
 
 /// 直接从指定 SQLite 数据库加载 PCGW 索引（无需写入缓存）
@@ -318,7 +353,10 @@ fn load_pcgw_index_from_sqlite_direct(sqlite_path: &Path) -> Result<Vec<GameInfo
             .query_map([], |row| row.get::<usize, String>(1))?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| anyhow::anyhow!(e))?;
-        if cols.iter().any(|c| c.eq_ignore_ascii_case("name") || c.eq_ignore_ascii_case("title")) {
+        if cols
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case("name") || c.eq_ignore_ascii_case("title"))
+        {
             game_table = Some(t.clone());
             game_columns = cols;
             break;
@@ -326,7 +364,9 @@ fn load_pcgw_index_from_sqlite_direct(sqlite_path: &Path) -> Result<Vec<GameInfo
     }
 
     let Some(game_table) = game_table else {
-        return Err(anyhow::anyhow!("No suitable game table found (requires a 'name' column)").into());
+        return Err(
+            anyhow::anyhow!("No suitable game table found (requires a 'name' column)").into(),
+        );
     };
 
     // 判断可能的列名
@@ -337,30 +377,39 @@ fn load_pcgw_index_from_sqlite_direct(sqlite_path: &Path) -> Result<Vec<GameInfo
         .unwrap();
     let alias_col = game_columns
         .iter()
-        .find(|c| c.eq_ignore_ascii_case("aliases") || c.eq_ignore_ascii_case("alias") || c.eq_ignore_ascii_case("aka"))
+        .find(|c| {
+            c.eq_ignore_ascii_case("aliases")
+                || c.eq_ignore_ascii_case("alias")
+                || c.eq_ignore_ascii_case("aka")
+        })
         .cloned();
     // 增强：识别本地化列（如 zh_CN、zh-cn、name_zh_cn 等）并作为别名来源
     let zh_like_col = game_columns
         .iter()
         .find(|c| {
             let lc = c.to_lowercase();
-            lc == "zh_cn" || lc == "zh-cn" || lc == "zh" || lc.contains("name_zh") || lc.contains("chinese")
+            lc == "zh_cn"
+                || lc == "zh-cn"
+                || lc == "zh"
+                || lc.contains("name_zh")
+                || lc.contains("chinese")
         })
         .cloned();
     let pcgw_col = game_columns
         .iter()
-        .find(|c| c.eq_ignore_ascii_case("pcgw_id") || c.eq_ignore_ascii_case("slug") || c.eq_ignore_ascii_case("wiki_id") || c.eq_ignore_ascii_case("pcgw"))
+        .find(|c| {
+            c.eq_ignore_ascii_case("pcgw_id")
+                || c.eq_ignore_ascii_case("slug")
+                || c.eq_ignore_ascii_case("wiki_id")
+                || c.eq_ignore_ascii_case("pcgw")
+        })
         .cloned();
 
     // 提取行并转换为 GameInfo
     let mut games: Vec<GameInfo> = Vec::new();
     let sql = format!("SELECT * FROM {}", game_table);
     let mut s = conn.prepare(&sql)?;
-    let col_names: Vec<String> = s
-        .column_names()
-        .iter()
-        .map(|c| c.to_string())
-        .collect();
+    let col_names: Vec<String> = s.column_names().iter().map(|c| c.to_string()).collect();
 
     // 计算列索引
     let name_idx = col_names
@@ -383,7 +432,11 @@ fn load_pcgw_index_from_sqlite_direct(sqlite_path: &Path) -> Result<Vec<GameInfo
         .enumerate()
         .filter_map(|(i, c)| {
             let lc = c.to_lowercase();
-            if lc.contains("path") || lc.contains("save") || lc.contains("location") || lc.contains("documents") {
+            if lc.contains("path")
+                || lc.contains("save")
+                || lc.contains("location")
+                || lc.contains("documents")
+            {
                 Some(i)
             } else {
                 None
@@ -394,17 +447,13 @@ fn load_pcgw_index_from_sqlite_direct(sqlite_path: &Path) -> Result<Vec<GameInfo
     let mut rows = s.query([])?;
     while let Some(row) = rows.next()? {
         // 读取基础字段
-        let name: String = row
-            .get::<usize, String>(name_idx)
-            .unwrap_or_default();
+        let name: String = row.get::<usize, String>(name_idx).unwrap_or_default();
         if name.trim().is_empty() {
             continue;
         }
 
         let mut aliases: Vec<String> = if let Some(ai) = alias_idx {
-            let sopt: Option<String> = row
-                .get::<usize, Option<String>>(ai)
-                .unwrap_or(None);
+            let sopt: Option<String> = row.get::<usize, Option<String>>(ai).unwrap_or(None);
             sopt.map(|s| split_aliases(&s)).unwrap_or_default()
         } else {
             Vec::new()
@@ -421,7 +470,9 @@ fn load_pcgw_index_from_sqlite_direct(sqlite_path: &Path) -> Result<Vec<GameInfo
 
         let pcgw_id: Option<String> = if let Some(pi) = pcgw_idx {
             row.get::<usize, Option<String>>(pi).unwrap_or(None)
-        } else { None };
+        } else {
+            None
+        };
 
         let mut gi = GameInfo {
             name,
@@ -433,14 +484,15 @@ fn load_pcgw_index_from_sqlite_direct(sqlite_path: &Path) -> Result<Vec<GameInfo
 
         // 读取可能的路径列
         for idx in &path_like_idxs {
-            let val_opt: Option<String> = row
-                .get::<usize, Option<String>>(*idx)
-                .unwrap_or(None);
+            let val_opt: Option<String> = row.get::<usize, Option<String>>(*idx).unwrap_or(None);
             if let Some(val) = val_opt {
                 if !val.trim().is_empty() {
                     gi.save_rules.push(super::types::SavePathRule {
                         id: format!("{}-{}", gi.name.replace(' ', "_"), col_names[*idx].as_str()),
-                        description: Some(format!("Imported from {}.{}", game_table, col_names[*idx])),
+                        description: Some(format!(
+                            "Imported from {}.{}",
+                            game_table, col_names[*idx]
+                        )),
                         path_template: normalize_path_template(&val),
                         requires: None,
                         platforms: vec!["windows".into()],
@@ -467,7 +519,11 @@ fn normalize_path_template(p: &str) -> String {
         s = s.replace("C:/Users/%USERNAME%", "<home>");
         s = s.replace("%USERNAME%", "<osUserName>");
         if !s.to_lowercase().contains("<home>") {
-            let suffix = if s.starts_with('/') { s.clone() } else { format!("/{}", s) };
+            let suffix = if s.starts_with('/') {
+                s.clone()
+            } else {
+                format!("/{}", s)
+            };
             s = format!("<home>{}", suffix);
         }
     }
@@ -487,21 +543,6 @@ fn split_aliases(s: &str) -> Vec<String> {
         .collect()
 }
 
-/// 通过名称或别名在索引中查找游戏
-///
-/// - 输入：索引切片与待匹配名称
-/// - 输出：找到的 `GameInfo`（若存在）
-/// - 行为：大小写不敏感匹配，忽略前后空白；优先匹配主名称，其次匹配别名
-pub fn find_by_name<'a>(index: &'a [GameInfo], name: &str) -> Option<&'a GameInfo> {
-    let lower = name.trim().to_lowercase();
-    index.iter().find(|g| {
-        if g.name.to_lowercase() == lower {
-            return true;
-        }
-        g.aliases.iter().any(|a| a.to_lowercase() == lower)
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -532,7 +573,8 @@ mod tests {
         assert_eq!(idx.version, "1.0.0");
         assert_eq!(idx.games.len(), 2);
 
-        let g = find_by_name(&idx.games, "sv").expect("find by alias");
+        let data = super::super::cache::PcgwIndexData::new(idx.games);
+        let g = data.find_by_name("sv").expect("find by alias");
         assert_eq!(g.name, "Stardew Valley");
         assert_eq!(g.pcgw_id.as_deref(), Some("stardew-valley"));
     }
@@ -0,0 +1,384 @@
+//! 启发式存档位置发现（深度扫描）
+//!
+//! 索引中没有存档规则的游戏只能依靠名称匹配兜底。深度扫描会遍历安装目录与常见用户目录，
+//! 按存档特征为目录评分，以低可信度的 `SaveMatchResult` 返回候选，供用户确认。
+//!
+//! - 不跟随符号链接与目录联接（junction），避免遍历整个磁盘
+//! - 每个游戏有时间预算，超时后返回已找到的结果
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use log::info;
+
+use super::types::{GameInfo, SaveMatchResult};
+use crate::preclude::to_long_path;
+
+/// 深度扫描结果使用的规则 ID
+pub const DEEP_SCAN_RULE_ID: &str = "heuristic-deep-scan";
+
+/// 每个游戏的深度扫描时间预算
+pub const DEEP_SCAN_BUDGET: Duration = Duration::from_secs(10);
+
+/// 视为存档目录的最低分数
+const MIN_DEEP_SCAN_SCORE: f32 = 0.5;
+
+/// 每个游戏最多返回的候选数量
+const MAX_DEEP_SCAN_RESULTS: usize = 5;
+
+/// 修改时间在此范围内的配置文件才视为存档（旧文件多为安装时附带的默认配置）
+const RECENT_PROFILE_AGE: Duration = Duration::from_secs(180 * 24 * 60 * 60);
+
+/// 深度扫描的限制条件
+#[derive(Debug, Clone)]
+pub struct DeepScanLimits {
+    /// 相对于每个根目录的最大层数
+    pub max_depth: u32,
+    /// 时间预算，超时后停止遍历
+    pub budget: Duration,
+}
+
+/// 目录中观察到的存档特征
+#[derive(Debug, Default)]
+struct SaveSignals {
+    /// 含 `.sav` / `.save` / `.slot` 文件
+    save_file: bool,
+    /// 含 `.dat` 文件（游戏数据中也很常见，权重较低）
+    data_file: bool,
+    /// 含近期修改、名称带 profile / slot / save 的 `.json` / `.bin` 文件
+    profile_file: bool,
+    /// 含名称带 save 的子目录
+    save_dir: bool,
+    /// 含名称带 profile / slot 的子目录
+    profile_dir: bool,
+    /// 目录自身名称带 save / profile / slot
+    self_named: bool,
+}
+
+impl SaveSignals {
+    fn collect(path: &Path) -> Self {
+        let mut signals = SaveSignals {
+            self_named: path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .is_some_and(|n| has_save_keyword(n, true)),
+            ..Default::default()
+        };
+        let Ok(rd) = fs::read_dir(to_long_path(path)) else {
+            return signals;
+        };
+        for entry in rd.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let p = entry.path();
+            let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+            if file_type.is_file() {
+                match extension_of(&p).as_deref() {
+                    Some("sav" | "save" | "slot") => signals.save_file = true,
+                    Some("dat") => signals.data_file = true,
+                    _ => {}
+                }
+                if is_recent_profile_file(&p) {
+                    signals.profile_file = true;
+                }
+            } else if file_type.is_dir() {
+                if name.contains("save") {
+                    signals.save_dir = true;
+                } else if has_save_keyword(&name, true) {
+                    signals.profile_dir = true;
+                }
+            }
+        }
+        signals
+    }
+
+    fn score(&self) -> f32 {
+        let mut score = 0.0;
+        if self.save_file {
+            score += 0.5;
+        }
+        if self.data_file {
+            score += 0.2;
+        }
+        if self.profile_file {
+            score += 0.4;
+        }
+        if self.save_dir {
+            score += 0.3;
+        }
+        if self.profile_dir {
+            score += 0.2;
+        }
+        if self.self_named {
+            score += 0.3;
+        }
+        f32::min(score, 1.0)
+    }
+}
+
+/// 判断目录（或文件）是否“像”存档
+///
+/// - 文件：扩展名为 `.sav` / `.save` / `.slot` / `.dat`，或为近期修改的 profile / slot 配置文件
+/// - 目录：包含上述文件，或包含名称带 save / profile / slot 的子目录
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn is_plausible_save_dir(path: &Path) -> bool {
+    if path.is_file() {
+        return matches!(
+            extension_of(path).as_deref(),
+            Some("sav" | "save" | "slot" | "dat")
+        ) || is_recent_profile_file(path);
+    }
+    if !path.is_dir() {
+        return false;
+    }
+    let s = SaveSignals::collect(path);
+    s.save_file || s.data_file || s.profile_file || s.save_dir || s.profile_dir
+}
+
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+}
+
+fn has_save_keyword(name: &str, include_profile: bool) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.contains("save")
+        || (include_profile && (name.contains("profile") || name.contains("slot")))
+}
+
+/// 近期修改、名称带 profile / slot / save 的 `.json` / `.bin` 文件
+fn is_recent_profile_file(path: &Path) -> bool {
+    if !matches!(extension_of(path).as_deref(), Some("json" | "bin")) {
+        return false;
+    }
+    let named = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .is_some_and(|n| has_save_keyword(n, true));
+    named
+        && fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .is_some_and(|age| age <= RECENT_PROFILE_AGE)
+}
+
+/// 用于目录名匹配的游戏名/别名（去除空白与常见标点，过短的名称不参与匹配）
+fn name_tokens(game: &GameInfo) -> Vec<String> {
+    std::iter::once(&game.name)
+        .chain(game.aliases.iter())
+        .map(|s| normalize_dir_name(s))
+        .filter(|t| t.chars().count() >= 3)
+        .collect()
+}
+
+fn normalize_dir_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+/// 可能存放存档的用户目录
+fn user_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = [
+        dirs::document_dir(),
+        dirs::data_dir(),
+        dirs::data_local_dir(),
+        dirs::config_dir(),
+        dirs::home_dir().map(|h| h.join("Saved Games")),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|p| p.is_dir())
+    .collect();
+    roots.sort();
+    roots.dedup();
+    roots
+}
+
+/// 对单个游戏执行深度扫描
+///
+/// - 安装目录：层数内所有评分达标的目录都是候选
+/// - 用户目录：只有路径中某一级目录名包含游戏名/别名时，其下的目录才是候选，避免返回其他游戏的存档
+/// - 命中的目录不再向下遍历；结果按分数排序，最多返回 `MAX_DEEP_SCAN_RESULTS` 个
+pub fn deep_scan_save_paths(
+    game: &GameInfo,
+    install_path: &Path,
+    limits: &DeepScanLimits,
+) -> Vec<SaveMatchResult> {
+    let deadline = Instant::now() + limits.budget;
+    let tokens = name_tokens(game);
+    let mut found: Vec<(PathBuf, f32)> = Vec::new();
+
+    let mut roots = vec![(install_path.to_path_buf(), true)];
+    if !tokens.is_empty() {
+        roots.extend(user_roots().into_iter().map(|r| (r, false)));
+    }
+    for (root, matched) in roots {
+        if !walk(
+            &root,
+            matched,
+            &tokens,
+            limits.max_depth,
+            deadline,
+            &mut found,
+        ) {
+            info!(target: "rgsm::game_scan", "Deep scan of {} ran out of time", game.name);
+            break;
+        }
+    }
+
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found.dedup_by(|a, b| a.0 == b.0);
+    found.truncate(MAX_DEEP_SCAN_RESULTS);
+    found
+        .into_iter()
+        .map(|(path, score)| SaveMatchResult {
+            rule_id: DEEP_SCAN_RULE_ID.into(),
+            resolved_path: path,
+            exists: true,
+            confidence: 0.2 + 0.3 * score,
+        })
+        .collect()
+}
+
+/// 广度优先遍历 `root`，返回 false 表示超出时间预算
+fn walk(
+    root: &Path,
+    root_matched: bool,
+    tokens: &[String],
+    max_depth: u32,
+    deadline: Instant,
+    found: &mut Vec<(PathBuf, f32)>,
+) -> bool {
+    let mut queue = std::collections::VecDeque::from([(root.to_path_buf(), 0u32, root_matched)]);
+    while let Some((dir, depth, matched)) = queue.pop_front() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        if matched && depth > 0 {
+            let score = SaveSignals::collect(&dir).score();
+            if score >= MIN_DEEP_SCAN_SCORE {
+                found.push((dir, score));
+                continue;
+            }
+        }
+        if depth >= max_depth {
+            continue;
+        }
+        // 深层目录可能超过 MAX_PATH，候选路径仍使用普通路径保存
+        let Ok(rd) = fs::read_dir(to_long_path(&dir)) else {
+            continue;
+        };
+        for entry in rd.flatten() {
+            // `DirEntry::file_type` 不跟随链接；Windows 上目录联接同样被视为符号链接
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if !file_type.is_dir() || file_type.is_symlink() {
+                continue;
+            }
+            let child_matched = matched || {
+                let name = normalize_dir_name(&entry.file_name().to_string_lossy());
+                tokens.iter().any(|t| name.contains(t.as_str()))
+            };
+            queue.push_back((dir.join(entry.file_name()), depth + 1, child_matched));
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(name: &str) -> GameInfo {
+        GameInfo {
+            name: name.into(),
+            aliases: Vec::new(),
+            pcgw_id: None,
+            install_rules: Vec::new(),
+            save_rules: Vec::new(),
+        }
+    }
+
+    /// 测试：在安装目录中找到存档目录，忽略只有游戏数据的目录，并遵守层数限制
+    #[test]
+    fn finds_save_dirs_in_install_path() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let install = base.path().join("MyGame");
+        let saves = install.join("UserData").join("Saves");
+        fs::create_dir_all(&saves).unwrap();
+        fs::write(saves.join("slot1.sav"), b"save").unwrap();
+        let data = install.join("Data");
+        fs::create_dir_all(&data).unwrap();
+        fs::write(data.join("level.dat"), b"data").unwrap();
+        let profiles = install.join("Profiles");
+        fs::create_dir_all(&profiles).unwrap();
+        fs::write(profiles.join("profile_0.json"), b"{}").unwrap();
+
+        let limits = DeepScanLimits {
+            max_depth: 3,
+            budget: Duration::from_secs(5),
+        };
+        let results = deep_scan_save_paths(&game("My Game"), &install, &limits);
+        let paths: Vec<_> = results.iter().map(|r| r.resolved_path.clone()).collect();
+        assert!(paths.contains(&saves));
+        assert!(paths.contains(&profiles));
+        assert!(!paths.contains(&data));
+        assert!(
+            results
+                .iter()
+                .all(|r| r.rule_id == DEEP_SCAN_RULE_ID && r.confidence <= 0.5)
+        );
+
+        // 深度不足时找不到更深的存档目录
+        let shallow = DeepScanLimits {
+            max_depth: 1,
+            budget: Duration::from_secs(5),
+        };
+        let results = deep_scan_save_paths(&game("My Game"), &install, &shallow);
+        assert!(results.iter().all(|r| r.resolved_path != saves));
+    }
+
+    /// 测试：不跟随指向安装目录之外的符号链接
+    #[cfg(unix)]
+    #[test]
+    fn does_not_follow_symlinks() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let outside = base.path().join("Outside").join("Saves");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("slot1.sav"), b"save").unwrap();
+        let install = base.path().join("MyGame");
+        fs::create_dir_all(&install).unwrap();
+        std::os::unix::fs::symlink(base.path().join("Outside"), install.join("Link")).unwrap();
+
+        let limits = DeepScanLimits {
+            max_depth: 3,
+            budget: Duration::from_secs(5),
+        };
+        let results = deep_scan_save_paths(&game("My Game"), &install, &limits);
+        assert!(
+            results
+                .iter()
+                .all(|r| !r.resolved_path.starts_with(&install))
+        );
+    }
+
+    /// 测试：名称带 profile / slot 且近期修改的配置文件被识别为存档
+    #[test]
+    fn recognizes_recent_profile_files() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let profile = base.path().join("PlayerProfile.bin");
+        fs::write(&profile, b"profile").unwrap();
+        let settings = base.path().join("settings.json");
+        fs::write(&settings, b"{}").unwrap();
+        assert!(is_plausible_save_dir(&profile));
+        assert!(!is_plausible_save_dir(&settings));
+        assert!(is_plausible_save_dir(base.path()));
+    }
+}
@@ -0,0 +1,357 @@
+//! 游戏名称模糊匹配
+//!
+//! 供 `pcgw_search` 使用的评分器，组合以下几种信号并取最高分：
+//! - 规范化后完全相同（忽略大小写、空白、标点与全角差异）
+//! - 逐词匹配：查询中的每个词都能在候选中找到相同、前缀或拼写相近（Jaro-Winkler）的词
+//! - 缩写匹配：查询等于候选各词首字母（数字整体保留），如 `bg3` → `Baldur's Gate 3`
+//! - 连续片段包含：用于无空格查询与中文名称（如 `巫师3` → `巫师3：狂猎`）
+//!
+//! 冠词等停用词不参与逐词匹配，避免 `the` 之类的查询命中大量无关条目。
+
+/// 模糊匹配的最低入选分数
+pub const MIN_FUZZY_SCORE: f32 = 0.70;
+
+/// 模糊匹配的最高分数，保证低于别名精确匹配（0.95）
+const MAX_FUZZY_SCORE: f32 = 0.94;
+
+/// 逐词匹配时视为拼写相近的 Jaro-Winkler 阈值
+const WORD_SIMILARITY_THRESHOLD: f64 = 0.85;
+
+const STOP_WORDS: &[&str] = &["the", "a", "an", "of", "and", "to", "in", "on"];
+
+/// 预处理后的匹配词项（索引条目在缓存构建时预计算，查询在每次搜索时计算一次）
+#[derive(Debug, Clone, Default)]
+pub struct FuzzyTerms {
+    /// 去除空白与标点后的小写字符串
+    compact: String,
+    /// 去除停用词后的小写词列表
+    words: Vec<String>,
+    /// 首字母缩写（含停用词与不含停用词两种）
+    initials: Vec<String>,
+    /// 是否包含 CJK 字符
+    has_cjk: bool,
+    /// 名称中出现的数字（排序后），用于区分续作
+    numbers: Vec<String>,
+}
+
+impl FuzzyTerms {
+    pub fn new(text: &str) -> Self {
+        let folded: String = text
+            .chars()
+            .filter(|c| !matches!(c, '\'' | '’'))
+            .map(fold_fullwidth)
+            .flat_map(char::to_lowercase)
+            .collect();
+        let all_words: Vec<String> = folded
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect();
+        let compact: String = all_words.concat();
+        let has_cjk = compact.chars().any(is_cjk);
+
+        let words: Vec<String> = all_words
+            .iter()
+            .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+            .cloned()
+            .collect();
+
+        let mut initials = Vec::new();
+        if !has_cjk && all_words.len() > 1 {
+            for source in [&all_words, &words] {
+                let acronym = acronym_of(source);
+                if acronym.chars().count() > 1 && !initials.contains(&acronym) {
+                    initials.push(acronym);
+                }
+            }
+        }
+
+        let mut numbers: Vec<String> = all_words
+            .iter()
+            .flat_map(|w| w.split(|c: char| !c.is_ascii_digit()))
+            .filter(|n| !n.is_empty())
+            .map(str::to_string)
+            .collect();
+        numbers.sort();
+
+        Self {
+            compact,
+            words,
+            initials,
+            has_cjk,
+            numbers,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.compact.is_empty()
+    }
+
+    /// 去除空白与标点后的小写字符串，可用于规范化后的精确比较
+    pub fn compact(&self) -> &str {
+        &self.compact
+    }
+
+    /// 两者包含的数字是否一致，`Portal` 与 `Portal 2`、`Portal2` 视为不同游戏
+    pub fn same_numbers(&self, other: &FuzzyTerms) -> bool {
+        self.numbers == other.numbers
+    }
+}
+
+/// 计算查询与候选的模糊匹配分数
+///
+/// - 返回 `None` 表示低于 `MIN_FUZZY_SCORE`
+/// - 分数区间为 `[MIN_FUZZY_SCORE, MAX_FUZZY_SCORE]`，越高越相关
+pub fn fuzzy_score(query: &FuzzyTerms, candidate: &FuzzyTerms) -> Option<f32> {
+    if query.is_empty() || candidate.is_empty() {
+        return None;
+    }
+
+    let mut best = 0.0f32;
+
+    // 规范化后完全相同
+    if query.compact == candidate.compact {
+        best = MAX_FUZZY_SCORE;
+    }
+
+    best = best.max(word_score(query, candidate));
+    best = best.max(acronym_score(query, candidate));
+    best = best.max(contains_score(query, candidate));
+
+    // 整体拼写相近（短名称的笔误，如 `celest` → `Celeste`）
+    let jw = jaro_winkler(&query.compact, &candidate.compact) as f32;
+    if jw >= 0.9 {
+        best = best.max(0.70 + (jw - 0.9) * 2.0);
+    }
+
+    let best = best.min(MAX_FUZZY_SCORE);
+    (best >= MIN_FUZZY_SCORE).then_some(best)
+}
+
+/// 逐词匹配：查询的每个词都必须在候选中找到对应词
+///
+/// 单词相似度：相同 1.0；候选词以查询词开头 0.9；拼写相近取 Jaro-Winkler；数字必须完全相同。
+/// 分数同时考虑平均相似度与覆盖率（查询词数 / 候选词数），覆盖率越高越接近完整名称。
+fn word_score(query: &FuzzyTerms, candidate: &FuzzyTerms) -> f32 {
+    if query.words.is_empty() || candidate.words.is_empty() {
+        return 0.0;
+    }
+    let mut total = 0.0f64;
+    for qw in &query.words {
+        let best = candidate
+            .words
+            .iter()
+            .map(|cw| word_similarity(qw, cw))
+            .fold(0.0f64, f64::max);
+        if best == 0.0 {
+            return 0.0;
+        }
+        total += best;
+    }
+    let similarity = (total / query.words.len() as f64) as f32;
+    let coverage = (query.words.len() as f32 / candidate.words.len() as f32).min(1.0);
+    0.60 + 0.35 * similarity * (0.7 + 0.3 * coverage)
+}
+
+fn word_similarity(query: &str, candidate: &str) -> f64 {
+    if query == candidate {
+        return 1.0;
+    }
+    if query.chars().all(|c| c.is_ascii_digit()) || candidate.chars().all(|c| c.is_ascii_digit()) {
+        return 0.0;
+    }
+    if query.chars().count() >= 2 && candidate.starts_with(query) {
+        return 0.9;
+    }
+    let jw = jaro_winkler(query, candidate);
+    if query.chars().count() >= 3 && jw >= WORD_SIMILARITY_THRESHOLD {
+        jw
+    } else {
+        0.0
+    }
+}
+
+/// 缩写匹配：完全相同 0.88，前缀 0.78~0.86
+fn acronym_score(query: &FuzzyTerms, candidate: &FuzzyTerms) -> f32 {
+    let q = &query.compact;
+    if q.chars().count() < 2 || query.has_cjk {
+        return 0.0;
+    }
+    let mut best = 0.0f32;
+    for acronym in &candidate.initials {
+        if acronym == q {
+            best = best.max(0.88);
+        } else if acronym.starts_with(q.as_str()) {
+            let ratio = q.len() as f32 / acronym.len() as f32;
+            best = best.max(0.78 + 0.08 * ratio);
+        }
+    }
+    best
+}
+
+/// 连续片段包含：英文查询至少 4 个字符，含中文时至少 2 个字符；从开头匹配额外加分
+fn contains_score(query: &FuzzyTerms, candidate: &FuzzyTerms) -> f32 {
+    let q = &query.compact;
+    let c = &candidate.compact;
+    let q_len = q.chars().count();
+    let min_len = if query.has_cjk { 2 } else { 4 };
+    if q_len < min_len {
+        return 0.0;
+    }
+    let Some(pos) = c.find(q.as_str()) else {
+        return 0.0;
+    };
+    let ratio = q_len as f32 / c.chars().count().max(1) as f32;
+    let prefix_bonus = if pos == 0 { 0.05 } else { 0.0 };
+    0.70 + 0.15 * ratio + prefix_bonus
+}
+
+fn acronym_of(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|w| {
+            if w.chars().all(|c| c.is_ascii_digit()) {
+                w.clone()
+            } else {
+                w.chars().next().map(String::from).unwrap_or_default()
+            }
+        })
+        .collect()
+}
+
+/// 全角 ASCII 字符转半角（如 `３` → `3`、`：` → `:`）
+fn fold_fullwidth(c: char) -> char {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{3000}' => ' ',
+        _ => c,
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'   // 平假名、片假名
+        | '\u{3400}'..='\u{4DBF}' // CJK 扩展 A
+        | '\u{4E00}'..='\u{9FFF}' // CJK 统一表意文字
+        | '\u{AC00}'..='\u{D7AF}' // 韩文音节
+        | '\u{F900}'..='\u{FAFF}' // CJK 兼容表意文字
+    )
+}
+
+/// Jaro-Winkler 相似度（0.0~1.0），按字符计算以支持非 ASCII 文本
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0usize;
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == *ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let mut transpositions = 0usize;
+    let mut k = 0usize;
+    for (i, ca) in a.iter().enumerate() {
+        if !a_matched[i] {
+            continue;
+        }
+        while !b_matched[k] {
+            k += 1;
+        }
+        if *ca != b[k] {
+            transpositions += 1;
+        }
+        k += 1;
+    }
+
+    let m = matches as f64;
+    let jaro =
+        (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64 / 2.0) / m) / 3.0;
+    let prefix = a
+        .iter()
+        .zip(b.iter())
+        .take(4)
+        .take_while(|(x, y)| x == y)
+        .count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, candidate: &str) -> Option<f32> {
+        fuzzy_score(&FuzzyTerms::new(query), &FuzzyTerms::new(candidate))
+    }
+
+    /// 测试：拼写错误仍可命中，且更完整的名称得分更高
+    #[test]
+    fn typo_matches_closest_title() {
+        let full = score("Wither 3", "The Witcher 3: Wild Hunt").expect("typo should match");
+        let short = score("Wither 3", "The Witcher 3").expect("typo should match");
+        assert!(short > full);
+        assert!(score("Wither 3", "The Witcher 2").is_none());
+    }
+
+    /// 测试：首字母缩写查询
+    #[test]
+    fn acronym_matches_initials() {
+        assert!(score("bg3", "Baldur's Gate 3").is_some());
+        assert!(score("gta", "Grand Theft Auto V").is_some());
+        assert!(score("bg3", "Borderlands 3").is_none());
+    }
+
+    /// 测试：停用词与过短的查询不会产生大量无关命中
+    #[test]
+    fn stop_words_do_not_match() {
+        assert!(score("the", "The Witcher 3: Wild Hunt").is_none());
+        assert!(score("the", "Theme Hospital").is_none());
+    }
+
+    /// 测试：中文名称按片段匹配，且忽略全角标点
+    #[test]
+    fn cjk_contains_matches() {
+        assert!(score("巫师3", "巫师3：狂猎").is_some());
+        assert!(score("巫师３", "巫师3：狂猎").is_some());
+        assert!(score("狂猎", "巫师3：狂猎").is_some());
+        assert!(score("只狼", "巫师3：狂猎").is_none());
+    }
+
+    /// 测试：数字不同的名称（续作）可被区分
+    #[test]
+    fn numbers_tell_sequels_apart() {
+        let portal = FuzzyTerms::new("Portal");
+        assert!(!portal.same_numbers(&FuzzyTerms::new("Portal 2")));
+        assert!(!portal.same_numbers(&FuzzyTerms::new("Portal2")));
+        assert!(FuzzyTerms::new("Portal2").same_numbers(&FuzzyTerms::new("Portal 2")));
+        assert!(FuzzyTerms::new("巫师３").same_numbers(&FuzzyTerms::new("巫师3：狂猎")));
+    }
+
+    /// 测试：Jaro-Winkler 基准值
+    #[test]
+    fn jaro_winkler_reference_values() {
+        assert!((jaro_winkler("martha", "marhta") - 0.961).abs() < 0.001);
+        assert_eq!(jaro_winkler("same", "same"), 1.0);
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+    }
+}
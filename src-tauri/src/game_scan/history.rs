@@ -0,0 +1,161 @@
+//! 扫描结果持久化与重新扫描对比
+//!
+//! 每次完整扫描结束后将结果写入 `AppData/RGSM/last_scan.json`，
+//! 前端重新加载后可直接取回；重新扫描时与上次结果对比，标注新增、未变化与已消失（卸载）的游戏。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use super::types::{DetectedGame, ScanDiffStatus, ScanOptions, ScanResult};
+
+const LAST_SCAN_FILE: &str = "last_scan.json";
+
+/// 持久化的上次扫描记录
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct LastScan {
+    /// 扫描结果（不含对比标注）
+    pub result: ScanResult,
+    /// 扫描完成时间（RFC 3339）
+    pub scanned_at: String,
+    /// 本次扫描使用的选项
+    pub options: ScanOptions,
+}
+
+fn last_scan_path(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .resolve("RGSM", BaseDirectory::AppData)
+        .context("Failed to resolve AppData/RGSM directory")?;
+    Ok(dir.join(LAST_SCAN_FILE))
+}
+
+/// 读取上次扫描记录，文件不存在时返回 None
+pub fn load_last_scan(app: &AppHandle) -> Result<Option<LastScan>> {
+    let path = last_scan_path(app)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read last scan at {}", path.display()))?;
+    let scan = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse last scan at {}", path.display()))?;
+    Ok(Some(scan))
+}
+
+/// 保存扫描记录，对比标注不会被写入
+pub fn save_last_scan(app: &AppHandle, result: &ScanResult, options: &ScanOptions) -> Result<()> {
+    let path = last_scan_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache dir at {}", dir.display()))?;
+    }
+    let mut result = result.clone();
+    for d in result.detected.iter_mut() {
+        d.diff = None;
+    }
+    let scan = LastScan {
+        result,
+        scanned_at: chrono::Local::now().to_rfc3339(),
+        options: options.clone(),
+    };
+    let text = serde_json::to_string(&scan).context("Failed to serialize last scan")?;
+    fs::write(&path, text)
+        .with_context(|| format!("Failed to write last scan at {}", path.display()))?;
+    Ok(())
+}
+
+/// 识别同一个检测结果：优先使用安装路径，没有安装路径时使用来源与名称
+fn identity(detected: &DetectedGame) -> String {
+    match &detected.install_path {
+        Some(path) => path.to_string_lossy().replace('\\', "/").to_lowercase(),
+        None => format!(
+            "{:?}:{}",
+            detected.source,
+            detected.info.name.to_lowercase()
+        ),
+    }
+}
+
+/// 将本次检测结果与上次对比并标注 `diff`
+///
+/// - 本次存在、上次不存在：`New`；两次都存在：`Unchanged`
+/// - 上次存在、本次不存在的条目追加到末尾并标注为 `Missing`（通常为已卸载）
+pub fn annotate_diff(detected: &mut Vec<DetectedGame>, previous: &[DetectedGame]) {
+    let previous_ids: HashSet<String> = previous.iter().map(identity).collect();
+    let current_ids: HashSet<String> = detected.iter().map(identity).collect();
+    for d in detected.iter_mut() {
+        d.diff = Some(if previous_ids.contains(&identity(d)) {
+            ScanDiffStatus::Unchanged
+        } else {
+            ScanDiffStatus::New
+        });
+    }
+    detected.extend(
+        previous
+            .iter()
+            .filter(|p| !current_ids.contains(&identity(p)))
+            .cloned()
+            .map(|mut p| {
+                p.diff = Some(ScanDiffStatus::Missing);
+                p
+            }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_scan::types::{DetectionSource, GameInfo};
+
+    fn game(name: &str, install_path: Option<&str>) -> DetectedGame {
+        DetectedGame {
+            info: GameInfo {
+                name: name.into(),
+                aliases: Vec::new(),
+                pcgw_id: None,
+                install_rules: Vec::new(),
+                save_rules: Vec::new(),
+            },
+            install_path: install_path.map(PathBuf::from),
+            source: DetectionSource::Steam,
+            platform_id: None,
+            pcgw_score: None,
+            ambiguous_with: None,
+            diff: None,
+        }
+    }
+
+    /// 测试：按安装路径对比，标注新增、未变化与已消失的游戏
+    #[test]
+    fn annotates_new_unchanged_and_missing() {
+        let previous = vec![
+            game("Celeste", Some("D:\\Games\\Celeste")),
+            game("Hades", Some("D:/Games/Hades")),
+        ];
+        let mut detected = vec![
+            game("Celeste", Some("d:/games/celeste")),
+            game("Portal", Some("D:/Games/Portal")),
+        ];
+        annotate_diff(&mut detected, &previous);
+
+        let diffs: Vec<_> = detected
+            .iter()
+            .map(|d| (d.info.name.as_str(), d.diff.clone()))
+            .collect();
+        assert_eq!(
+            diffs,
+            [
+                ("Celeste", Some(ScanDiffStatus::Unchanged)),
+                ("Portal", Some(ScanDiffStatus::New)),
+                ("Hades", Some(ScanDiffStatus::Missing)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! 扫描结果忽略列表
+//!
+//! 工具、原声音轨等非游戏条目每次扫描都会出现。忽略列表保存在 `AppData/RGSM/scan_ignore.json`，
+//! 扫描时在丰富 PCGW 信息之前将其过滤掉，仍通过 `ScanResult::skipped_ignored` 计数以便前端显示。
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use super::types::DetectedGame;
+
+const IGNORE_FILE: &str = "scan_ignore.json";
+
+/// 持久化的忽略列表
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct ScanIgnoreList {
+    /// 忽略的安装路径（统一为小写与 `/` 分隔），其子目录同样被忽略
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// 忽略的游戏名（小写）
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+/// 含路径分隔符的视为安装路径，否则视为游戏名
+enum IgnoreKey {
+    Path(String),
+    Name(String),
+}
+
+impl IgnoreKey {
+    fn parse(path_or_name: &str) -> Option<Self> {
+        let raw = path_or_name.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        if raw.contains(['/', '\\']) {
+            let path = normalize_path(raw);
+            (!path.is_empty()).then_some(Self::Path(path))
+        } else {
+            Some(Self::Name(raw.to_lowercase()))
+        }
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+impl ScanIgnoreList {
+    /// 添加忽略项，已存在时返回 false
+    pub fn add(&mut self, path_or_name: &str) -> bool {
+        let (list, key) = match IgnoreKey::parse(path_or_name) {
+            Some(IgnoreKey::Path(p)) => (&mut self.paths, p),
+            Some(IgnoreKey::Name(n)) => (&mut self.names, n),
+            None => return false,
+        };
+        if list.contains(&key) {
+            return false;
+        }
+        list.push(key);
+        true
+    }
+
+    /// 移除忽略项，不存在时返回 false
+    pub fn remove(&mut self, path_or_name: &str) -> bool {
+        let (list, key) = match IgnoreKey::parse(path_or_name) {
+            Some(IgnoreKey::Path(p)) => (&mut self.paths, p),
+            Some(IgnoreKey::Name(n)) => (&mut self.names, n),
+            None => return false,
+        };
+        let len = list.len();
+        list.retain(|k| *k != key);
+        list.len() != len
+    }
+
+    /// 检测结果是否被忽略：安装路径等于或位于某个忽略路径之下，或名称命中
+    pub fn is_ignored(&self, detected: &DetectedGame) -> bool {
+        let by_path = detected.install_path.as_ref().is_some_and(|p| {
+            let path = normalize_path(&p.to_string_lossy());
+            self.paths.iter().any(|ignored| {
+                path == *ignored
+                    || path
+                        .strip_prefix(ignored.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+        });
+        by_path || self.names.contains(&detected.info.name.to_lowercase())
+    }
+
+    /// 将检测结果分为保留与被忽略两部分
+    pub fn partition(&self, detected: Vec<DetectedGame>) -> (Vec<DetectedGame>, Vec<DetectedGame>) {
+        detected.into_iter().partition(|d| !self.is_ignored(d))
+    }
+}
+
+fn ignore_list_path(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .resolve("RGSM", BaseDirectory::AppData)
+        .context("Failed to resolve AppData/RGSM directory")?;
+    Ok(dir.join(IGNORE_FILE))
+}
+
+/// 读取忽略列表，文件不存在时返回空列表
+pub fn load_ignore_list(app: &AppHandle) -> Result<ScanIgnoreList> {
+    let path = ignore_list_path(app)?;
+    if !path.exists() {
+        return Ok(ScanIgnoreList::default());
+    }
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read ignore list at {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse ignore list at {}", path.display()))
+}
+
+/// 保存忽略列表
+pub fn save_ignore_list(app: &AppHandle, list: &ScanIgnoreList) -> Result<()> {
+    let path = ignore_list_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache dir at {}", dir.display()))?;
+    }
+    let text = serde_json::to_string_pretty(list).context("Failed to serialize ignore list")?;
+    fs::write(&path, text)
+        .with_context(|| format!("Failed to write ignore list at {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_scan::types::{DetectionSource, GameInfo};
+
+    fn game(name: &str, install_path: &str) -> DetectedGame {
+        DetectedGame {
+            info: GameInfo {
+                name: name.into(),
+                aliases: Vec::new(),
+                pcgw_id: None,
+                install_rules: Vec::new(),
+                save_rules: Vec::new(),
+            },
+            install_path: Some(PathBuf::from(install_path)),
+            source: DetectionSource::Steam,
+            platform_id: None,
+            pcgw_score: None,
+            ambiguous_with: None,
+            diff: None,
+        }
+    }
+
+    /// 测试：按路径（含子目录）与名称忽略，移除后恢复
+    #[test]
+    fn ignores_by_path_and_name() {
+        let mut list = ScanIgnoreList::default();
+        assert!(list.add("D:\\SteamLibrary\\steamapps\\common\\Soundtracks\\"));
+        assert!(list.add("Wallpaper Engine"));
+        assert!(!list.add("wallpaper engine"));
+
+        let detected = vec![
+            game("Celeste", "D:/SteamLibrary/steamapps/common/Celeste"),
+            game(
+                "Wallpaper Engine",
+                "D:/SteamLibrary/steamapps/common/wallpaper_engine",
+            ),
+            game(
+                "Hades OST",
+                "d:/steamlibrary/steamapps/common/soundtracks/Hades OST",
+            ),
+            game(
+                "Soundtracks Remix",
+                "D:/SteamLibrary/steamapps/common/Soundtracks Remix",
+            ),
+        ];
+        let (kept, ignored) = list.partition(detected.clone());
+        let names = |v: &[DetectedGame]| v.iter().map(|d| d.info.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&kept), ["Celeste", "Soundtracks Remix"]);
+        assert_eq!(names(&ignored), ["Wallpaper Engine", "Hades OST"]);
+
+        assert!(list.remove("WALLPAPER ENGINE"));
+        assert!(!list.remove("Wallpaper Engine"));
+        let (kept, _) = list.partition(detected);
+        assert_eq!(
+            names(&kept),
+            ["Celeste", "Wallpaper Engine", "Soundtracks Remix"]
+        );
+    }
+}
@@ -1,21 +1,40 @@
 use anyhow::Result;
-use log::{info, warn};
+use log::{error, info, warn};
 use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 use tauri_specta::Event;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 
-use super::types::{DetectedGame, SaveMatchResult, ScanOptions, ScanProgressEvent, ScanResult};
-use crate::game_scan::platform::{detect_installed_games, match_save_paths, generate_save_units};
-use super::db::{load_pcgw_index, find_by_name};
-use super::types::{PcgwQueryOptions, PcgwQueryItem, PcgwIndexMeta};
-use super::db::{update_pcgw_index_remote, import_pcgw_index_from_file, import_pcgw_index_from_sqlite};
+use super::cache::{EntryKeys, PcgwIndexCache, PcgwIndexData};
+use super::db::{
+    import_pcgw_index_from_file, import_pcgw_index_from_sqlite, update_pcgw_index_remote,
+};
+use super::deep_scan::{DEEP_SCAN_BUDGET, DeepScanLimits, deep_scan_save_paths};
+use super::fuzzy::{FuzzyTerms, fuzzy_score};
+use super::history::{LastScan, annotate_diff, load_last_scan, save_last_scan};
+use super::ignore::{ScanIgnoreList, load_ignore_list, save_ignore_list};
+use super::options::{load_scan_options, preferred_scan_options, save_scan_options};
+use super::state::ScanState;
+use super::types::{
+    DetectedGame, GeneratedSaveUnits, SaveMatchOutcome, SaveMatchResult, SaveUnitOptions,
+    ScanOptions, ScanProgressEvent, ScanResult,
+};
+use super::types::{PcgwIndexMeta, PcgwQueryItem, PcgwQueryOptions};
+use crate::backup::{Game, create_game_backup};
+use crate::config::get_config;
+use crate::device::get_current_device_id;
+use crate::game_scan::platform::{detect_installed_games, generate_save_units, match_save_paths};
+use crate::preclude::{ErrorCode, IpcError};
+use crate::quick_actions::QuickActionManager;
 
 /// 扫描进度事件（用于前端订阅显示）
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
-#[derive(Event)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
 pub struct ScanProgress(pub ScanProgressEvent);
 
 /// 进度事件发送器（带节流）
@@ -96,23 +115,310 @@ impl ProgressEmitter {
 /// - 输入：`options` 控制扫描行为；`app` 用于事件发送与资源解析
 /// - 行为：阶段化发送 `ScanProgress` 事件，并记录各阶段耗时；
 /// - 输出：返回聚合的检测与存档匹配结果
+/// - 取消：同一时间只运行一个扫描，新扫描会取消旧扫描；被取消的扫描返回部分结果并置 `cancelled`
+/// - 持久化：未被取消的扫描结果写入 `AppData/RGSM/last_scan.json`，写入失败只记录日志
+/// - 平台：`options.platform` 与当前系统不一致时返回 `ScanPlatformMismatch`，不会执行扫描；
+///   通过校验的选项写入 `AppData/RGSM/scan_options.json`，供 `get_default_scan_options` 返回
 #[tauri::command]
 #[specta::specta]
-pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResult, String> {
+pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResult, IpcError> {
+    if !options.matches_current_platform() {
+        return Err(IpcError::new(
+            ErrorCode::ScanPlatformMismatch,
+            format!(
+                "Scan platform {} does not match the current platform {}",
+                options.platform,
+                ScanOptions::current_platform()
+            ),
+        )
+        .with_context(options.platform.clone()));
+    }
+    if let Err(e) = save_scan_options(&app, &options) {
+        warn!(target:"rgsm::game_scan", "Failed to save scan options: {e:#}");
+    }
+    let state = app.state::<ScanState>();
+    let ticket = state.begin();
+    let result = run_scan(&app, options.clone(), &ticket.token).await;
+    state.finish(&ticket);
+    if let Ok(result) = &result {
+        if !result.cancelled {
+            if let Err(e) = save_last_scan(&app, result, &options) {
+                warn!(target:"rgsm::game_scan", "Failed to save last scan result: {e:#}");
+            }
+        }
+    }
+    result
+}
+
+/// 获取扫描选项的默认值
+///
+/// - 优先返回上次扫描使用的选项，记录不适用于当前系统时返回平台默认值
+/// - 记录无法读取时视为没有记录
+#[tauri::command]
+#[specta::specta]
+pub async fn get_default_scan_options(app: AppHandle) -> Result<ScanOptions, String> {
+    let stored = load_scan_options(&app).unwrap_or_else(|e| {
+        warn!(target:"rgsm::game_scan", "Ignoring unreadable scan options: {e:#}");
+        None
+    });
+    Ok(preferred_scan_options(stored))
+}
+
+/// 获取上次完成的扫描结果（含扫描时间与选项），从未扫描过时返回 `None`
+#[tauri::command]
+#[specta::specta]
+pub async fn get_last_scan_result(app: AppHandle) -> Result<Option<LastScan>, String> {
+    let mut last = load_last_scan(&app).map_err(|e| {
+        error!(target:"rgsm::game_scan", "Failed to load last scan result: {e:#}");
+        e.to_string()
+    })?;
+    // 扫描之后忽略列表可能已变化，按当前列表重新划分
+    if let Some(last) = last.as_mut() {
+        let ignore_list = load_ignore_list(&app).unwrap_or_default();
+        let result = &mut last.result;
+        let all = std::mem::take(&mut result.detected)
+            .into_iter()
+            .chain(std::mem::take(&mut result.ignored))
+            .collect();
+        (result.detected, result.ignored) = ignore_list.partition(all);
+        result.skipped_ignored = result.ignored.len() as u32;
+    }
+    Ok(last)
+}
+
+/// 重新扫描并与上次结果对比
+///
+/// - 行为：与 `scan_games` 相同地执行并保存扫描，然后为每个检测结果标注 `diff`（New / Unchanged），
+///   上次存在而本次未检测到的游戏以 `Missing` 追加到列表末尾
+/// - 上次记录无法读取时视为没有记录，所有游戏均为 New
+/// - 被取消的扫描结果不完整，不做对比
+#[tauri::command]
+#[specta::specta]
+pub async fn rescan_diff(app: AppHandle, options: ScanOptions) -> Result<ScanResult, IpcError> {
+    let previous = load_last_scan(&app).unwrap_or_else(|e| {
+        warn!(target:"rgsm::game_scan", "Ignoring unreadable last scan result: {e:#}");
+        None
+    });
+    let mut result = scan_games(app.clone(), options).await?;
+    if !result.cancelled {
+        // 上次之后才被忽略的游戏不应标注为 Missing
+        let ignore_list = load_ignore_list(&app).unwrap_or_default();
+        let mut previous = previous.map(|p| p.result.detected).unwrap_or_default();
+        previous.retain(|p| !ignore_list.is_ignored(p));
+        annotate_diff(&mut result.detected, &previous);
+    }
+    Ok(result)
+}
+
+/// 取消正在运行的扫描
+///
+/// - 行为：触发当前扫描的取消令牌，扫描将在下一个检查点返回部分结果
+/// - 无扫描运行时为空操作
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_scan(app: AppHandle) -> Result<(), String> {
+    if app.state::<ScanState>().cancel() {
+        info!(target:"rgsm::game_scan", "Scan cancellation requested");
+    }
+    Ok(())
+}
+
+/// 获取扫描忽略列表
+#[tauri::command]
+#[specta::specta]
+pub async fn get_scan_ignore_list(app: AppHandle) -> Result<ScanIgnoreList, String> {
+    load_ignore_list(&app).map_err(|e| {
+        error!(target:"rgsm::game_scan", "Failed to load scan ignore list: {e:#}");
+        e.to_string()
+    })
+}
+
+/// 将检测结果加入忽略列表，之后的扫描会跳过它
+///
+/// - 输入：`path_or_name` 含路径分隔符时按安装路径忽略（包括其子目录），否则按游戏名忽略，均不区分大小写
+/// - 输出：更新后的忽略列表
+#[tauri::command]
+#[specta::specta]
+pub async fn ignore_detected_game(
+    app: AppHandle,
+    path_or_name: String,
+) -> Result<ScanIgnoreList, String> {
+    info!(target:"rgsm::game_scan", "Ignoring scan entry: {}", path_or_name);
+    update_ignore_list(&app, |list| list.add(&path_or_name))
+}
+
+/// 将条目移出忽略列表，`path_or_name` 的含义与 `ignore_detected_game` 相同
+#[tauri::command]
+#[specta::specta]
+pub async fn unignore_detected_game(
+    app: AppHandle,
+    path_or_name: String,
+) -> Result<ScanIgnoreList, String> {
+    info!(target:"rgsm::game_scan", "Unignoring scan entry: {}", path_or_name);
+    update_ignore_list(&app, |list| list.remove(&path_or_name))
+}
+
+/// 读取、修改并在有变化时保存忽略列表
+fn update_ignore_list(
+    app: &AppHandle,
+    f: impl FnOnce(&mut ScanIgnoreList) -> bool,
+) -> Result<ScanIgnoreList, String> {
+    let mut list = load_ignore_list(app).map_err(|e| e.to_string())?;
+    if f(&mut list) {
+        save_ignore_list(app, &list).map_err(|e| {
+            error!(target:"rgsm::game_scan", "Failed to save scan ignore list: {e:#}");
+            e.to_string()
+        })?;
+    }
+    Ok(list)
+}
+
+/// 构造被取消扫描的部分结果，并发送最终的 `cancelled` 进度事件
+fn cancelled_result(
+    emitter: &mut ProgressEmitter,
+    detected: Vec<DetectedGame>,
+    ignored: Vec<DetectedGame>,
+    matches: Vec<SaveMatchResult>,
+) -> ScanResult {
+    info!(target:"rgsm::game_scan", "Scan cancelled, detected: {}, matches: {}", detected.len(), matches.len());
+    emitter.emit(ScanProgressEvent {
+        step: "cancelled".into(),
+        current: 4,
+        total: 4,
+        message: Some(t!("backend.scan.cancelled").to_string()),
+    });
+    ScanResult {
+        detected,
+        matches,
+        errors: Vec::new(),
+        cancelled: true,
+        skipped_ignored: ignored.len() as u32,
+        ignored,
+    }
+}
+
+/// 存档匹配的最大并发数
+const MATCH_CONCURRENCY: usize = 8;
+
+/// 并发匹配所有检测到的游戏的存档路径
+///
+/// - 并发：同时最多运行 `MATCH_CONCURRENCY` 个匹配任务；匹配涉及大量阻塞式目录遍历，放入 `spawn_blocking` 执行
+/// - 进度：每完成一个游戏，通过 `emitter` 发送 `match_saves` 事件（`current/total` 为已完成/总游戏数）
+/// - 顺序：结果按 `detected` 的顺序聚合，与任务完成先后无关，避免前端列表跳动
+/// - 取消：`cancel` 被触发后不再等待任务，返回已完成游戏的匹配结果；尚未开始的任务被中止，
+///   已在运行的阻塞任务无法中止，会在下一条规则前检查 `cancel` 并跳过剩余规则与深度扫描后结束
+/// - 深度扫描：开启 `options.deep_scan` 时，对没有存档规则的游戏追加启发式候选（与已有结果路径去重）
+/// - 规则容错：解析失败的规则被跳过，原因作为第二个返回值，填入 `ScanResult.errors`
+async fn match_detected_games(
+    detected: &[DetectedGame],
+    options: &ScanOptions,
+    cancel: &CancellationToken,
+    emitter: &mut ProgressEmitter,
+) -> Result<(Vec<SaveMatchResult>, Vec<String>), IpcError> {
+    let total = detected.iter().filter(|d| d.install_path.is_some()).count() as u32;
+    let mut slots: Vec<Option<SaveMatchOutcome>> = vec![None; detected.len()];
+    let mut pending = detected
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, d)| d.install_path.clone().map(|p| (idx, d.info.clone(), p)));
+    let mut tasks = JoinSet::new();
+    let mut completed = 0u32;
+
+    loop {
+        while tasks.len() < MATCH_CONCURRENCY {
+            let Some((idx, info, install)) = pending.next() else {
+                break;
+            };
+            let handle = tokio::runtime::Handle::current();
+            let cancel = cancel.clone();
+            let deep_limits =
+                (options.deep_scan && info.save_rules.is_empty()).then_some(DeepScanLimits {
+                    max_depth: options.deep_scan_max_depth,
+                    budget: DEEP_SCAN_BUDGET,
+                });
+            tasks.spawn_blocking(move || {
+                let res = handle
+                    .block_on(match_save_paths(&info, &install, &cancel))
+                    .map(|mut outcome| {
+                        if let Some(limits) = deep_limits.filter(|_| !cancel.is_cancelled()) {
+                            for m in deep_scan_save_paths(&info, &install, &limits) {
+                                if !outcome
+                                    .matches
+                                    .iter()
+                                    .any(|e| e.resolved_path == m.resolved_path)
+                                {
+                                    outcome.matches.push(m);
+                                }
+                            }
+                        }
+                        outcome
+                    });
+                (idx, res)
+            });
+        }
+
+        let joined = tokio::select! {
+            _ = cancel.cancelled() => {
+                tasks.abort_all();
+                break;
+            }
+            joined = tasks.join_next() => joined,
+        };
+        let Some(joined) = joined else {
+            break;
+        };
+        let (idx, res) = joined.map_err(|e| IpcError::new(ErrorCode::ScanFailed, e.to_string()))?;
+        let outcome = res.map_err(|e| IpcError::new(ErrorCode::ScanFailed, format!("{e:#}")))?;
+        slots[idx] = Some(outcome);
+        completed += 1;
+        emitter.emit(ScanProgressEvent {
+            step: "match_saves".into(),
+            current: completed,
+            total,
+            message: Some(detected[idx].info.name.clone()),
+        });
+    }
+
+    let mut matches = Vec::new();
+    let mut errors = Vec::new();
+    for (idx, outcome) in slots.into_iter().enumerate() {
+        let Some(outcome) = outcome else {
+            continue;
+        };
+        matches.extend(outcome.matches);
+        errors.extend(outcome.skipped_rules.into_iter().map(|rule| {
+            t!(
+                "backend.scan.rule_skipped",
+                game = detected[idx].info.name,
+                rule = rule.rule_id,
+                reason = rule.reason
+            )
+            .to_string()
+        }));
+    }
+    Ok((matches, errors))
+}
+
+/// 扫描流程主体，在各阶段之间检查 `cancel`
+async fn run_scan(
+    app: &AppHandle,
+    options: ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<ScanResult, IpcError> {
     info!(target:"rgsm::game_scan", "Starting scan with options: {:?}", options);
     let mut emitter = ProgressEmitter::new(app.clone(), Duration::from_millis(250));
     let t_total = Instant::now();
 
     // 预读取 PCGW 索引（最小实现）：用于丰富检测结果的规则信息
     let t_index = Instant::now();
-    let pcgw_index: Vec<super::types::GameInfo> = match load_pcgw_index(&app).await {
-        Ok(list) => list,
+    let pcgw_index = match app.state::<PcgwIndexCache>().get(app).await {
+        Ok(data) => data,
         Err(e) => {
             warn!(target:"rgsm::game_scan", "Failed to load PCGW index: {e}");
-            Vec::new()
+            Default::default()
         }
     };
-    info!(target:"rgsm::game_scan", "PCGW index loaded in {:?}, entries: {}", t_index.elapsed(), pcgw_index.len());
+    info!(target:"rgsm::game_scan", "PCGW index loaded in {:?}, entries: {}", t_index.elapsed(), pcgw_index.games.len());
 
     // Step 1: 发送索引加载进度
     emitter.emit(ScanProgressEvent {
@@ -122,7 +428,14 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
         message: Some(t!("backend.scan.index_load").to_string()),
     });
 
-    // TODO: 后续实现实际的索引加载、Windows 检测与路径匹配
+    if cancel.is_cancelled() {
+        return Ok(cancelled_result(
+            &mut emitter,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        ));
+    }
 
     // Step 2: 发送检测游戏进度
     emitter.emit(ScanProgressEvent {
@@ -138,7 +451,7 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
             step: "epic_scanning".into(),
             current: 2,
             total: 4,
-            message: Some("Scanning Epic manifests".into()),
+            message: Some(t!("backend.scan.epic_scanning").to_string()),
         });
     }
     if options.search_origin {
@@ -146,7 +459,7 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
             step: "origin_scanning".into(),
             current: 2,
             total: 4,
-            message: Some("Scanning EA/Origin installed list".into()),
+            message: Some(t!("backend.scan.origin_scanning").to_string()),
         });
     }
     if options.search_common_dirs {
@@ -154,37 +467,47 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
             step: "common_directories_scanning".into(),
             current: 2,
             total: 4,
-            message: Some("Scanning common game directories".into()),
+            message: Some(t!("backend.scan.common_directories_scanning").to_string()),
         });
     }
 
-    // 执行平台检测（Windows 基础版）
+    // 执行平台检测（Windows / macOS，其余平台返回空集合）
     let t_detect = Instant::now();
-    let detected: Vec<DetectedGame> = {
-        #[cfg(target_os = "windows")]
-        {
-            detect_installed_games(&options)
-                .await
-                .map_err(|e| e.to_string())?
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            Vec::new()
-        }
-    };
+    let detected: Vec<DetectedGame> = detect_installed_games(&options, cancel)
+        .await
+        .map_err(|e| IpcError::new(ErrorCode::ScanFailed, format!("{e:#}")))?;
     info!(target:"rgsm::game_scan", "Detected {} game candidates in {:?}", detected.len(), t_detect.elapsed());
 
+    // 过滤忽略列表中的条目（工具、原声音轨等），读取失败时不过滤
+    let ignore_list = load_ignore_list(app).unwrap_or_else(|e| {
+        warn!(target:"rgsm::game_scan", "Ignoring unreadable scan ignore list: {e:#}");
+        ScanIgnoreList::default()
+    });
+    let (detected, ignored) = ignore_list.partition(detected);
+    if !ignored.is_empty() {
+        info!(target:"rgsm::game_scan", "Skipped {} ignored game candidates", ignored.len());
+    }
+
     // 合并/丰富检测结果：按名称或别名匹配 PCGW 索引，将规则注入
     let detected = enrich_with_pcgw(detected, &pcgw_index);
     info!(target:"rgsm::game_scan", "Enriched detections with PCGW, total: {}", detected.len());
 
+    if cancel.is_cancelled() {
+        return Ok(cancelled_result(
+            &mut emitter,
+            detected,
+            ignored,
+            Vec::new(),
+        ));
+    }
+
     // 平台扫描完成事件（Epic / Origin）
     if options.search_epic {
         emitter.emit(ScanProgressEvent {
             step: "epic_done".into(),
             current: 2,
             total: 4,
-            message: Some("Epic scan done".into()),
+            message: Some(t!("backend.scan.epic_done").to_string()),
         });
     }
     if options.search_origin {
@@ -192,7 +515,7 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
             step: "origin_done".into(),
             current: 2,
             total: 4,
-            message: Some("Origin scan done".into()),
+            message: Some(t!("backend.scan.origin_done").to_string()),
         });
     }
     if options.search_common_dirs {
@@ -200,7 +523,7 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
             step: "common_done".into(),
             current: 2,
             total: 4,
-            message: Some("Common directories scan done".into()),
+            message: Some(t!("backend.scan.common_done").to_string()),
         });
     }
 
@@ -212,24 +535,22 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
         message: Some(t!("backend.scan.match_saves").to_string()),
     });
 
-    // 执行存档匹配（Windows 基础版）
-    let mut matches: Vec<SaveMatchResult> = Vec::new();
+    // 执行存档匹配（Windows / macOS），有界并发，结果按检测顺序聚合
     let t_match = Instant::now();
-    #[cfg(target_os = "windows")]
-    for d in &detected {
-        if let Some(ref install) = d.install_path {
-            let ms = match_save_paths(&d.info, install)
-                .await
-                .map_err(|e| e.to_string())?;
-            matches.extend(ms);
-        }
-    }
+    let (matches, errors) = match_detected_games(&detected, &options, cancel, &mut emitter).await?;
     info!(target:"rgsm::game_scan", "Matched save paths: {}, elapsed: {:?}", matches.len(), t_match.elapsed());
 
+    if cancel.is_cancelled() {
+        return Ok(cancelled_result(&mut emitter, detected, ignored, matches));
+    }
+
     let result = ScanResult {
         detected,
         matches,
-        errors: Vec::new(),
+        errors,
+        cancelled: false,
+        skipped_ignored: ignored.len() as u32,
+        ignored,
     };
 
     // Step 4: 发送完成进度
@@ -251,106 +572,214 @@ pub async fn scan_games(app: AppHandle, options: ScanOptions) -> Result<ScanResu
 /// - 错误：资源读取或解析失败返回错误信息字符串（已转换为友好可读）
 #[tauri::command]
 #[specta::specta]
-pub async fn pcgw_query(app: AppHandle, name: String) -> Result<Option<super::types::GameInfo>, String> {
-    let index = load_pcgw_index(&app).await.map_err(|e| e.to_string())?;
-    Ok(find_by_name(&index, &name).cloned())
+pub async fn pcgw_query(
+    app: AppHandle,
+    name: String,
+) -> Result<Option<super::types::GameInfo>, String> {
+    let index = app
+        .state::<PcgwIndexCache>()
+        .get(&app)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(index.find_by_name(&name).cloned())
 }
 
+/// 别名模糊命中的降权系数，使同等相似度下主名称排在前面
+const ALIAS_FUZZY_WEIGHT: f32 = 0.97;
+
 /// 完整查询 PCGamingWiki 索引（支持模糊、平台过滤与结果上限）
 ///
 /// - 输入：`name` 查询关键字（名称或别名），`options` 查询选项
 /// - 行为：按以下优先级计算评分并排序：
 ///   1. 主名称完全匹配：score=1.0，matched_by="name"
 ///   2. 别名完全匹配：score=0.95，matched_by="alias"
-///   3. 模糊匹配（包含）：name 包含则 score≈0.75~1.0，alias 包含则 score≈0.7~1.0，matched_by="fuzzy"
+///   3. 模糊匹配：score≈0.68~0.94，matched_by="fuzzy"，低于任何精确匹配；
+///      综合拼写相近、逐词/前缀、首字母缩写与片段包含（见 `fuzzy` 模块），别名命中乘以 0.97
 /// - 过滤：若设置 `platform`，仅保留有保存规则包含该平台的条目
 /// - 限制：返回不超过 `limit` 个结果（默认 20）
 #[tauri::command]
 #[specta::specta]
-pub async fn pcgw_search(app: AppHandle, name: String, options: PcgwQueryOptions) -> Result<Vec<PcgwQueryItem>, String> {
-    let index = load_pcgw_index(&app).await.map_err(|e| e.to_string())?;
+pub async fn pcgw_search(
+    app: AppHandle,
+    name: String,
+    options: PcgwQueryOptions,
+) -> Result<Vec<PcgwQueryItem>, String> {
+    let index = app
+        .state::<PcgwIndexCache>()
+        .get(&app)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(search_index(&index, &name, &options))
+}
+
+/// `pcgw_search` 的评分与排序实现
+fn search_index(
+    index: &PcgwIndexData,
+    name: &str,
+    options: &PcgwQueryOptions,
+) -> Vec<PcgwQueryItem> {
     let q = name.trim().to_lowercase();
+    let q_terms = FuzzyTerms::new(&q);
     let limit = options.limit.unwrap_or(20);
 
     // 平台过滤器
-    let platform_ok = |gi: &super::types::GameInfo| -> bool {
-        if let Some(ref p) = options.platform {
-            let pl = p.to_lowercase();
-            return gi.save_rules.iter().any(|r| r.platforms.iter().any(|rp| rp.to_lowercase() == pl));
+    let platform = options.platform.as_ref().map(|p| p.to_lowercase());
+    let platform_ok = |keys: &EntryKeys| -> bool {
+        match platform {
+            Some(ref pl) => keys.platforms_lower.iter().any(|rp| rp == pl),
+            None => true,
         }
-        true
     };
 
     // 评分计算
     let mut items: Vec<PcgwQueryItem> = Vec::new();
-    for gi in index.iter() {
-        if !platform_ok(gi) { continue; }
-        let name_l = gi.name.to_lowercase();
+    for (gi, keys) in index.entries() {
+        if !platform_ok(keys) {
+            continue;
+        }
 
         // 完全匹配（名称）
-        if name_l == q {
-            items.push(PcgwQueryItem { info: gi.clone(), score: 1.0, matched_by: "name".into() });
+        if keys.name_lower == q {
+            items.push(PcgwQueryItem {
+                info: gi.clone(),
+                score: 1.0,
+                matched_by: "name".into(),
+            });
             continue;
         }
 
         // 完全匹配（别名）
-        if gi.aliases.iter().any(|a| a.to_lowercase() == q) {
-            items.push(PcgwQueryItem { info: gi.clone(), score: 0.95, matched_by: "alias".into() });
+        if keys.aliases_lower.iter().any(|a| a == &q) {
+            items.push(PcgwQueryItem {
+                info: gi.clone(),
+                score: 0.95,
+                matched_by: "alias".into(),
+            });
             continue;
         }
 
-        // 模糊匹配（包含）
+        // 模糊匹配：名称与别名分别评分取最高，别名略微降权
         if options.fuzzy {
-            let mut pushed = false;
-            if name_l.contains(&q) {
-                // 简单长度比例评分（0.75~1.0）
-                let ratio = (q.len() as f32) / (gi.name.len().max(1) as f32);
-                let score = 0.75 + 0.25 * ratio.min(1.0);
-                items.push(PcgwQueryItem { info: gi.clone(), score, matched_by: "fuzzy".into() });
-                pushed = true;
-            }
-            if !pushed {
-                for a in gi.aliases.iter() {
-                    let al = a.to_lowercase();
-                    if al.contains(&q) {
-                        let ratio = (q.len() as f32) / (a.len().max(1) as f32);
-                        let score = 0.70 + 0.30 * ratio.min(1.0);
-                        items.push(PcgwQueryItem { info: gi.clone(), score, matched_by: "fuzzy".into() });
-                        break;
-                    }
-                }
+            let name_score = fuzzy_score(&q_terms, &keys.name_terms);
+            let alias_score = keys
+                .alias_terms
+                .iter()
+                .filter_map(|t| fuzzy_score(&q_terms, t))
+                .map(|s| s * ALIAS_FUZZY_WEIGHT)
+                .reduce(f32::max);
+            let score = match (name_score, alias_score) {
+                (Some(n), Some(a)) => Some(n.max(a)),
+                (n, a) => n.or(a),
+            };
+            if let Some(score) = score {
+                items.push(PcgwQueryItem {
+                    info: gi.clone(),
+                    score,
+                    matched_by: "fuzzy".into(),
+                });
             }
         }
     }
 
     // 排序并截断
-    items.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-    if items.len() > limit { items.truncate(limit); }
-    Ok(items)
+    items.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    if items.len() > limit {
+        items.truncate(limit);
+    }
+    items
 }
 
 /// 为已检测到的游戏生成 SaveUnit 列表（带设备映射）
 ///
-/// - 输入：`game_info`（PCGW 索引中的游戏信息）、`install_path`（解析出的安装目录字符串）
+/// - 输入：`game_info`（PCGW 索引中的游戏信息）、`install_path`（解析出的安装目录字符串）、
+///   `options`（候选的可信度阈值、数量上限与取舍策略，为空时采用所有存在的候选）
 /// - 行为：调用平台实现的 `generate_save_units`，只返回存在的文件/文件夹并映射到当前设备
-/// - 输出：`SaveUnit` 列表，供前端“一键填充/添加”使用
+/// - 输出：采用的 `SaveUnit` 列表，供前端“一键填充/添加”使用；未采用的候选作为“其他可能位置”
 #[tauri::command]
 #[specta::specta]
 pub async fn generate_save_units_for_game(
     game_info: super::types::GameInfo,
     install_path: String,
-) -> Result<Vec<crate::backup::SaveUnit>, String> {
-    #[cfg(target_os = "windows")]
-    {
-        use std::path::Path;
-        generate_save_units(&game_info, Path::new(&install_path))
-            .await
-            .map_err(|e| e.to_string())
+    options: Option<SaveUnitOptions>,
+) -> Result<GeneratedSaveUnits, String> {
+    use std::path::Path;
+    let options = options.unwrap_or_default();
+    generate_save_units(&game_info, Path::new(&install_path), &options)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 将扫描检测到的游戏直接转换为已配置的 `Game`（含备份目录）
+///
+/// - 输入：`detected` 扫描结果条目；`name_override` 自定义游戏名（为空时使用检测到的名称）
+/// - 行为：
+///   1. 调用平台实现的 `generate_save_units` 生成存档单元，无可用存档单元时返回错误
+///   2. 将安装路径记录到当前设备的 `game_paths`
+///   3. 调用 `backup::create_game_backup` 写入配置并创建备份目录
+///   4. `take_initial_snapshot` 为真时立即创建首个快照
+/// - 约束：已存在同名游戏时返回错误，除非 `overwrite` 为真
+/// - 输出：新建的 `Game`，供前端直接刷新列表
+#[tauri::command]
+#[specta::specta]
+pub async fn adopt_detected_game(
+    detected: DetectedGame,
+    name_override: Option<String>,
+    take_initial_snapshot: bool,
+    overwrite: bool,
+    app: AppHandle,
+) -> Result<Game, String> {
+    let name = name_override
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| detected.info.name.clone());
+    let Some(install_path) = detected.install_path.as_ref() else {
+        return Err(t!("backend.scan.adopt_no_install_path", name = name).to_string());
+    };
+
+    let config = get_config().map_err(|e| e.to_string())?;
+    if !overwrite && config.games.iter().any(|g| g.name == name) {
+        return Err(t!("backend.scan.adopt_game_exists", name = name).to_string());
     }
-    #[cfg(not(target_os = "windows"))]
-    {
-        Ok(Vec::new())
+
+    let save_paths = generate_save_units(&detected.info, install_path, &SaveUnitOptions::default())
+        .await
+        .map_err(|e| e.to_string())?
+        .units;
+    if save_paths.is_empty() {
+        return Err(t!("backend.scan.adopt_no_save_units", name = name).to_string());
+    }
+
+    let mut game_paths = HashMap::new();
+    game_paths.insert(
+        get_current_device_id().clone(),
+        install_path.to_string_lossy().to_string(),
+    );
+    let game = Game {
+        name,
+        save_paths,
+        game_paths,
+    };
+
+    info!(target:"rgsm::game_scan", "Adopting detected game: {:?}", game);
+    create_game_backup(&game).await.map_err(|e| {
+        error!(target:"rgsm::game_scan", "Failed to adopt detected game: {:?}", e);
+        e.to_string()
+    })?;
+    app.state::<Arc<QuickActionManager>>().rebuild_tray_menu();
+
+    if take_initial_snapshot {
+        game.create_snapshot(&t!("backend.scan.adopt_initial_snapshot"))
+            .await
+            .map_err(|e| {
+                error!(target:"rgsm::game_scan", "Failed to take initial snapshot: {:?}", e);
+                e.to_string()
+            })?;
     }
+    Ok(game)
 }
 
 /// 刷新 PCGW 索引（返回版本与条目数量）
@@ -360,36 +789,47 @@ pub async fn generate_save_units_for_game(
 #[tauri::command]
 #[specta::specta]
 pub async fn pcgw_refresh_index(app: AppHandle) -> Result<PcgwIndexMeta, String> {
-    match update_pcgw_index_remote(&app).await {
-        Ok(meta) => Ok(meta),
-        Err(e) => Err(format!("{}", e)),
-    }
+    let meta = update_pcgw_index_remote(&app)
+        .await
+        .map_err(|e| e.to_string())?;
+    app.state::<PcgwIndexCache>().invalidate().await;
+    Ok(meta)
 }
 
 /// 从本地文件导入 PCGW 索引（覆盖缓存并返回元信息）
 #[tauri::command]
 #[specta::specta]
-pub async fn pcgw_import_index_from_file(app: AppHandle, file_path: String) -> Result<PcgwIndexMeta, String> {
+pub async fn pcgw_import_index_from_file(
+    app: AppHandle,
+    file_path: String,
+) -> Result<PcgwIndexMeta, String> {
     let path = std::path::PathBuf::from(file_path);
-    import_pcgw_index_from_file(&app, &path)
+    let meta = import_pcgw_index_from_file(&app, &path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    app.state::<PcgwIndexCache>().invalidate().await;
+    Ok(meta)
 }
 
 /// 从SQLite数据库导入 PCGW 索引（例如 Game-Save-Manager 的 database.db）
 #[tauri::command]
 #[specta::specta]
-pub async fn pcgw_import_index_from_sqlite(app: AppHandle, file_path: String) -> Result<PcgwIndexMeta, String> {
+pub async fn pcgw_import_index_from_sqlite(
+    app: AppHandle,
+    file_path: String,
+) -> Result<PcgwIndexMeta, String> {
     let path = std::path::PathBuf::from(file_path);
-    import_pcgw_index_from_sqlite(&app, &path)
+    let meta = import_pcgw_index_from_sqlite(&app, &path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    app.state::<PcgwIndexCache>().invalidate().await;
+    Ok(meta)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::types::{DetectedGame, DetectionSource, GameInfo};
     use super::*;
-    use super::super::types::{DetectedGame, GameInfo, DetectionSource};
 
     /// 测试：将检测到的游戏集合与 PCGW 索引合并，规则被正确注入
     #[test]
@@ -404,6 +844,10 @@ mod tests {
             },
             install_path: None,
             source: DetectionSource::CommonDir,
+            platform_id: None,
+            pcgw_score: None,
+            ambiguous_with: None,
+            diff: None,
         }];
 
         let index = vec![GameInfo {
@@ -411,24 +855,88 @@ mod tests {
             aliases: vec!["SV".into()],
             pcgw_id: Some("stardew-valley".into()),
             install_rules: Vec::new(),
-            save_rules: vec![
-                super::super::types::SavePathRule {
-                    id: "save-stardew-default".into(),
-                    description: Some("AppData saves".into()),
-                    path_template: "<winAppData>/StardewValley/Saves".into(),
-                    requires: None,
-                    platforms: vec!["windows".into()],
-                    confidence: 0.95,
-                }
-            ],
+            save_rules: vec![super::super::types::SavePathRule {
+                id: "save-stardew-default".into(),
+                description: Some("AppData saves".into()),
+                path_template: "<winAppData>/StardewValley/Saves".into(),
+                requires: None,
+                platforms: vec!["windows".into()],
+                confidence: 0.95,
+            }],
         }];
 
-        let merged = enrich_with_pcgw(detected, &index);
+        let merged = enrich_with_pcgw(detected, &PcgwIndexData::new(index));
         assert_eq!(merged.len(), 1);
         let info = &merged[0].info;
         assert_eq!(info.pcgw_id.as_deref(), Some("stardew-valley"));
         assert_eq!(info.save_rules.len(), 1);
-        assert_eq!(info.save_rules[0].path_template, "<winAppData>/StardewValley/Saves");
+        assert_eq!(
+            info.save_rules[0].path_template,
+            "<winAppData>/StardewValley/Saves"
+        );
+    }
+
+    fn pcgw_entry(name: &str, aliases: &[&str]) -> GameInfo {
+        GameInfo {
+            name: name.into(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            pcgw_id: None,
+            install_rules: Vec::new(),
+            save_rules: Vec::new(),
+        }
+    }
+
+    fn search_names(index: &PcgwIndexData, q: &str) -> Vec<String> {
+        let options = PcgwQueryOptions {
+            fuzzy: true,
+            platform: None,
+            limit: None,
+        };
+        search_index(index, q, &options)
+            .into_iter()
+            .map(|i| i.info.name)
+            .collect()
+    }
+
+    /// 测试：模糊搜索覆盖拼写错误、缩写与中文别名（来自 zh_CN 列）
+    #[test]
+    fn search_index_fuzzy_queries() {
+        let index = PcgwIndexData::new(vec![
+            pcgw_entry("The Witcher 3: Wild Hunt", &["巫师3：狂猎"]),
+            pcgw_entry("Baldur's Gate 3", &["博德之门3"]),
+            pcgw_entry("Theme Hospital", &[]),
+        ]);
+
+        assert_eq!(
+            search_names(&index, "Wither 3"),
+            vec!["The Witcher 3: Wild Hunt"]
+        );
+        assert_eq!(search_names(&index, "bg3"), vec!["Baldur's Gate 3"]);
+        assert_eq!(
+            search_names(&index, "巫师3"),
+            vec!["The Witcher 3: Wild Hunt"]
+        );
+        assert_eq!(search_names(&index, "博德之门"), vec!["Baldur's Gate 3"]);
+        assert!(search_names(&index, "the").is_empty());
+    }
+
+    /// 测试：精确匹配始终排在模糊匹配之前
+    #[test]
+    fn search_index_exact_before_fuzzy() {
+        let index = PcgwIndexData::new(vec![
+            pcgw_entry("Celeste Classic", &[]),
+            pcgw_entry("Celeste", &[]),
+        ]);
+        let options = PcgwQueryOptions {
+            fuzzy: true,
+            platform: None,
+            limit: None,
+        };
+        let items = search_index(&index, "celeste", &options);
+        assert_eq!(items[0].info.name, "Celeste");
+        assert_eq!(items[0].matched_by, "name");
+        assert_eq!(items[1].matched_by, "fuzzy");
+        assert!(items[1].score < 0.95);
     }
 
     /// 测试：规范化+模糊匹配可将目录名 "BlackMythWukong" 匹配到 "Black Myth: Wukong"
@@ -444,6 +952,10 @@ mod tests {
             },
             install_path: None,
             source: DetectionSource::CommonDir,
+            platform_id: None,
+            pcgw_score: None,
+            ambiguous_with: None,
+            diff: None,
         }];
 
         let index = vec![GameInfo {
@@ -451,116 +963,210 @@ mod tests {
             aliases: vec!["Black Myth Wukong".into()],
             pcgw_id: Some("black-myth-wukong".into()),
             install_rules: Vec::new(),
-            save_rules: vec![
-                super::super::types::SavePathRule {
-                    id: "save-bmw-default".into(),
-                    description: Some("AppData saves".into()),
-                    path_template: "<winAppData>/BlackMythWukong/Saved/SaveGames".into(),
-                    requires: None,
-                    platforms: vec!["windows".into()],
-                    confidence: 0.90,
-                }
-            ],
+            save_rules: vec![super::super::types::SavePathRule {
+                id: "save-bmw-default".into(),
+                description: Some("AppData saves".into()),
+                path_template: "<winAppData>/BlackMythWukong/Saved/SaveGames".into(),
+                requires: None,
+                platforms: vec!["windows".into()],
+                confidence: 0.90,
+            }],
         }];
 
-        let merged = enrich_with_pcgw(detected, &index);
+        let merged = enrich_with_pcgw(detected, &PcgwIndexData::new(index));
         assert_eq!(merged.len(), 1);
         let info = &merged[0].info;
         assert_eq!(info.pcgw_id.as_deref(), Some("black-myth-wukong"));
         assert_eq!(info.save_rules.len(), 1);
         assert!(info.save_rules[0].path_template.contains("BlackMythWukong"));
     }
+
+    fn detected_game(name: &str) -> DetectedGame {
+        DetectedGame {
+            info: pcgw_entry(name, &[]),
+            install_path: None,
+            source: DetectionSource::CommonDir,
+            platform_id: None,
+            pcgw_score: None,
+            ambiguous_with: None,
+            diff: None,
+        }
+    }
+
+    /// 测试：Portal 不会被合并到续作或相关作品；多个检测结果争用同一条目时只保留最佳配对
+    #[test]
+    fn enrich_with_pcgw_portal_ambiguity() {
+        let index = PcgwIndexData::new(vec![
+            pcgw_entry("Portal 2", &[]),
+            pcgw_entry("Portal with RTX", &[]),
+            pcgw_entry("Portal", &[]),
+        ]);
+        let detected = vec![
+            detected_game("Portal2"),
+            detected_game("Portal"),
+            detected_game("Portal 2"),
+        ];
+        let merged = enrich_with_pcgw(detected, &index);
+        assert_eq!(merged[1].info.name, "Portal");
+        assert_eq!(merged[1].pcgw_score, Some(1.0));
+        assert_eq!(merged[2].info.name, "Portal 2");
+        assert_eq!(merged[2].pcgw_score, Some(1.0));
+        // "Portal2" 规范化后同样指向 Portal 2，但分数低于名称精确匹配
+        assert_eq!(merged[0].info.name, "Portal2");
+        assert_eq!(merged[0].pcgw_score, None);
+        assert_eq!(merged[0].ambiguous_with.as_deref(), Some("Portal 2"));
+
+        // 索引中没有 Portal 时，也不会退而合并到相关作品
+        let index = PcgwIndexData::new(vec![
+            pcgw_entry("Portal 2", &[]),
+            pcgw_entry("Portal with RTX", &[]),
+        ]);
+        let merged = enrich_with_pcgw(vec![detected_game("Portal")], &index);
+        assert_eq!(merged[0].info.name, "Portal");
+        assert_eq!(merged[0].pcgw_score, None);
+        assert!(merged[0].ambiguous_with.is_none());
+    }
+
+    /// 测试：中文目录名通过别名合并，全角数字与标点不影响匹配，只有前缀的名称不会被合并
+    #[test]
+    fn enrich_with_pcgw_chinese_alias() {
+        let index = PcgwIndexData::new(vec![
+            pcgw_entry("The Witcher 2: Assassins of Kings", &["巫师2：国王刺客"]),
+            pcgw_entry("The Witcher 3: Wild Hunt", &["巫师3：狂猎"]),
+        ]);
+        let merged = enrich_with_pcgw(
+            vec![detected_game("巫师３ 狂猎"), detected_game("巫师")],
+            &index,
+        );
+        assert_eq!(merged[0].info.name, "The Witcher 3: Wild Hunt");
+        assert_eq!(merged[0].pcgw_score, Some(0.96));
+        assert_eq!(merged[1].info.name, "巫师");
+        assert_eq!(merged[1].pcgw_score, None);
+    }
 }
-/// 将平台检测到的游戏集合与 PCGW 索引进行合并，丰富规则信息
-///
-/// - 输入：检测结果与 PCGW 索引切片
-/// - 输出：带有 `install_rules` / `save_rules` 等信息的检测结果集合
-/// - 行为：按名称或别名进行大小写不敏感匹配；若匹配成功，保留原安装路径与来源，替换为索引中的 `GameInfo`
+/// 扫描结果合并 PCGW 条目所需的最低分数，高于搜索使用的 `MIN_FUZZY_SCORE`，
+/// 避免 `Portal` 这类短名称被合并到 `Portal with RTX` 等相关作品
+const MIN_ENRICH_SCORE: f32 = 0.90;
+
+/// 计算检测名称与单个索引条目的匹配分数，低于 `MIN_ENRICH_SCORE` 返回 None
 ///
-/// 注意：该函数不会修改 `install_path` 与 `source` 字段，仅替换 `info`
-fn enrich_with_pcgw(mut detected: Vec<DetectedGame>, index: &[super::types::GameInfo]) -> Vec<DetectedGame> {
-    // 辅助：规范化字符串，仅保留 ASCII 字母数字并转小写
-    fn normalize_key(s: &str) -> String {
-        s.to_lowercase()
-            .chars()
-            .filter(|c| c.is_ascii_alphanumeric())
-            .collect::<String>()
-    }
-
-    // 辅助：在索引中进行模糊查找（包含与规范化对比），返回最优候选
-    fn find_by_name_fuzzy<'a>(index: &'a [super::types::GameInfo], name: &str) -> Option<&'a super::types::GameInfo> {
-        let q_raw = name.trim().to_lowercase();
-        let q_norm = normalize_key(&q_raw);
-
-        let mut best: Option<(&super::types::GameInfo, f32)> = None;
-        for gi in index.iter() {
-            // 主名称优先
-            let name_l = gi.name.to_lowercase();
-            if name_l == q_raw {
-                return Some(gi);
+/// 分数由高到低：名称精确 1.0 > 别名精确 0.98 > 名称规范化相同 0.97 > 别名规范化相同 0.96 > 模糊匹配（≤ 0.94）。
+/// 数字不同的名称视为不同作品（如续作），不参与模糊匹配
+fn enrich_score(query: &str, q_terms: &FuzzyTerms, keys: &EntryKeys) -> Option<f32> {
+    if keys.name_lower == query {
+        return Some(1.0);
+    }
+    if keys.aliases_lower.iter().any(|a| a == query) {
+        return Some(0.98);
+    }
+    if q_terms.is_empty() {
+        return None;
+    }
+    // 规范化后相同，处理去空格/去标点的目录名（如 BlackMythWukong vs Black Myth: Wukong）
+    if keys.name_terms.compact() == q_terms.compact() {
+        return Some(0.97);
+    }
+    if keys
+        .alias_terms
+        .iter()
+        .any(|t| t.compact() == q_terms.compact())
+    {
+        return Some(0.96);
+    }
+
+    let name_score = if keys.name_terms.same_numbers(q_terms) {
+        fuzzy_score(q_terms, &keys.name_terms)
+    } else {
+        None
+    };
+    let alias_score = keys
+        .alias_terms
+        .iter()
+        .filter(|t| t.same_numbers(q_terms))
+        .filter_map(|t| fuzzy_score(q_terms, t))
+        .map(|s| s * ALIAS_FUZZY_WEIGHT)
+        .reduce(f32::max);
+    let score = name_score.into_iter().chain(alias_score).reduce(f32::max)?;
+    (score >= MIN_ENRICH_SCORE).then_some(score)
+}
+
+/// 为单个检测结果（名称及其别名）找出分数最高的条目下标，同分时取索引中靠前的条目
+fn best_pcgw_entry(detected: &DetectedGame, index: &PcgwIndexData) -> Option<(usize, f32)> {
+    let queries: Vec<(String, FuzzyTerms)> = std::iter::once(&detected.info.name)
+        .chain(detected.info.aliases.iter())
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !n.is_empty())
+        .map(|q| {
+            let terms = FuzzyTerms::new(&q);
+            (q, terms)
+        })
+        .collect();
+
+    let mut best: Option<(usize, f32)> = None;
+    for (idx, (_, keys)) in index.entries().enumerate() {
+        let score = queries
+            .iter()
+            .filter_map(|(q, terms)| enrich_score(q, terms, keys))
+            .reduce(f32::max);
+        if let Some(score) = score {
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((idx, score));
             }
-            if gi.aliases.iter().any(|a| a.to_lowercase() == q_raw) {
-                return Some(gi);
+            if score >= 1.0 {
+                break;
             }
+        }
+    }
+    best
+}
 
-            // 规范化后比较，处理去空格/去标点的目录名（如 BlackMythWukong vs Black Myth: Wukong）
-            let gi_norm = normalize_key(&gi.name);
-            if !gi_norm.is_empty() && !q_norm.is_empty() {
-                if gi_norm == q_norm {
-                    return Some(gi);
-                }
-                let contains = gi_norm.contains(&q_norm) || q_norm.contains(&gi_norm);
-                if contains {
-                    // 简单长度比例作为评分，越接近越高
-                    let shorter = gi_norm.len().min(q_norm.len()) as f32;
-                    let longer = gi_norm.len().max(q_norm.len()) as f32;
-                    let score = 0.80 + 0.20 * (shorter / longer);
-                    match best {
-                        Some((_, s)) if s >= score => {}
-                        _ => best = Some((gi, score)),
-                    }
-                }
-            }
+/// 将平台检测到的游戏集合与 PCGW 索引进行合并，丰富规则信息
+///
+/// - 输入：检测结果与 PCGW 索引
+/// - 输出：带有 `install_rules` / `save_rules` 等信息的检测结果集合
+/// - 行为：每个检测结果对所有条目评分，只取分数最高且不低于 `MIN_ENRICH_SCORE` 的条目；
+///   多个检测结果指向同一条目时，只有分数最高者（同分取检测顺序靠前者）被合并，
+///   其余保持原样并在 `ambiguous_with` 中记录胜出者的名称
+///
+/// 注意：该函数不会修改 `install_path` 与 `source` 字段，仅替换 `info`，检测时附带的存档规则会被保留
+fn enrich_with_pcgw(mut detected: Vec<DetectedGame>, index: &PcgwIndexData) -> Vec<DetectedGame> {
+    let best: Vec<Option<(usize, f32)>> =
+        detected.iter().map(|d| best_pcgw_entry(d, index)).collect();
 
-            // 别名的规范化包含匹配
-            for a in gi.aliases.iter() {
-                let al = a.to_lowercase();
-                let an = normalize_key(&al);
-                if an.is_empty() || q_norm.is_empty() { continue; }
-                if an == q_norm {
-                    return Some(gi);
-                }
-                if an.contains(&q_norm) || q_norm.contains(&an) {
-                    let shorter = an.len().min(q_norm.len()) as f32;
-                    let longer = an.len().max(q_norm.len()) as f32;
-                    let score = 0.75 + 0.25 * (shorter / longer);
-                    match best {
-                        Some((_, s)) if s >= score => {}
-                        _ => best = Some((gi, score)),
-                    }
-                    break;
+    // 条目下标 → 胜出的检测结果下标
+    let mut owners: HashMap<usize, usize> = HashMap::new();
+    for (di, candidate) in best.iter().enumerate() {
+        let Some((entry, score)) = *candidate else {
+            continue;
+        };
+        owners
+            .entry(entry)
+            .and_modify(|owner| {
+                if best[*owner].is_some_and(|(_, s)| score > s) {
+                    *owner = di;
                 }
-            }
-        }
-        best.map(|(gi, _)| gi)
+            })
+            .or_insert(di);
     }
+    let owner_names: HashMap<usize, String> = owners
+        .iter()
+        .map(|(&entry, &di)| (entry, detected[di].info.name.clone()))
+        .collect();
 
-    for d in detected.iter_mut() {
-        let name = d.info.name.clone();
-        // 1) 优先精确匹配（名称或别名）
-        if let Some(gi) = find_by_name(index, &name) {
-            d.info = gi.clone();
+    for (di, d) in detected.iter_mut().enumerate() {
+        let Some((entry, score)) = best[di] else {
+            continue;
+        };
+        if owners[&entry] == di {
+            // 检测时生成的存档规则（如 Steam 云存档目录）与 PCGW 规则一并保留
+            let detected_rules = std::mem::take(&mut d.info.save_rules);
+            d.info = index.games[entry].clone();
+            d.info.save_rules.extend(detected_rules);
+            d.pcgw_score = Some(score);
         } else {
-            // 2) 模糊匹配（包含与规范化对比）
-            if let Some(gi) = find_by_name_fuzzy(index, &name) {
-                d.info = gi.clone();
-            } else if let Some(alias) = d.info.aliases.first() {
-                // 3) 兜底：尝试别名精确匹配（若后续补充了别名）
-                if let Some(gi) = find_by_name(index, alias) {
-                    d.info = gi.clone();
-                }
-            }
+            warn!(target:"rgsm::game_scan", "{} also matches PCGW entry {}, kept for {}", d.info.name, index.games[entry].name, owner_names[&entry]);
+            d.ambiguous_with = Some(owner_names[&entry].clone());
         }
     }
     detected
-}
\ No newline at end of file
+}
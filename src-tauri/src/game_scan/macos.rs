@@ -1,34 +1,427 @@
 #![cfg(target_os = "macos")]
 
 use anyhow::Result;
+use log::{info, warn};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
 use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
 
-use crate::backup::SaveUnit;
-use super::types::{DetectedGame, GameInfo, SaveMatchResult, ScanOptions};
+use super::types::{
+    DetectedGame, DetectionSource, GameInfo, GeneratedSaveUnits, SaveMatchOutcome, SaveMatchResult,
+    SaveUnitOptions, ScanOptions,
+};
+use crate::game_scan::ranking::build_save_units;
+use crate::game_scan::resolver::{default_env, resolve_save_rules};
+use crate::game_scan::steam::{find_steam_path, read_steam_library_folders, scan_steam_library};
 
-/// 在 macOS 平台检测已安装的游戏（存根实现）
+/// 在 macOS 平台检测已安装的游戏
 ///
 /// - 输入：`ScanOptions` 控制不同来源的扫描开关
-/// - 输出：返回空列表；后续将实现 Steam/Epic/Application 目录等来源解析
-pub async fn detect_installed_games(_options: &ScanOptions) -> Result<Vec<DetectedGame>> {
-    log::info!(target: "rgsm::scan", "{}", rust_i18n::t!("scan.platform_beta"));
-    Ok(Vec::new())
+/// - 输出：`DetectedGame` 列表
+/// - 行为：
+///   - 当启用 `search_steam` 时读取 `~/Library/Application Support/Steam` 的 `libraryfolders.vdf` 并枚举游戏目录
+///   - 当启用 `search_common_dirs` 时枚举 `/Applications` 与 `~/Applications` 下的 `.app` 包（跳过系统应用）
+///   - `cancel` 被触发后跳过剩余来源，返回已检测到的部分结果
+pub async fn detect_installed_games(
+    options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
+    let mut detected = Vec::new();
+
+    if options.search_steam {
+        detected.extend(scan_steam_games(options, cancel).await?);
+    }
+
+    if options.search_common_dirs && !cancel.is_cancelled() {
+        detected.extend(scan_application_bundles(options, cancel).await?);
+    }
+
+    Ok(dedup_detected(detected))
+}
+
+/// 扫描 Steam 库目录中的已安装游戏
+///
+/// - 遍历 `<library>/steamapps/common` 子目录，将每个子目录视为一个候选游戏
+/// - 名称与 appid 取自库中的 `appmanifest_<appid>.acf`，没有清单时使用目录名
+/// - 将来源标注为 `DetectionSource::Steam`
+pub async fn scan_steam_games(
+    _options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
+    let mut detected = Vec::new();
+
+    let Some(steam_path) = find_steam_path() else {
+        warn!(target:"rgsm::game_scan::macos", "Steam path not found");
+        return Ok(detected);
+    };
+    info!(target:"rgsm::game_scan::macos", "Steam path: {}", steam_path.display());
+
+    let libraries = match read_steam_library_folders(&steam_path) {
+        Ok(libs) => libs,
+        Err(e) => {
+            warn!(target:"rgsm::game_scan::macos", "Failed to read library folders: {e}");
+            vec![steam_path.clone()]
+        }
+    };
+
+    for lib in libraries {
+        if cancel.is_cancelled() {
+            break;
+        }
+        detected.extend(scan_steam_library(&lib, &steam_path));
+    }
+
+    Ok(detected)
+}
+
+/// 扫描应用程序目录中的 `.app` 包
+///
+/// - 目录来源：`/Applications` 与 `~/Applications`
+/// - 名称优先取 `Info.plist` 中的 `CFBundleName`，失败则回退为包名
+/// - 跳过 `com.apple.` 开头的系统应用；来源标注为 `CommonDir`
+pub async fn scan_application_bundles(
+    _options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
+    let mut detected = Vec::new();
+
+    let mut roots = vec![PathBuf::from("/Applications")];
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join("Applications"));
+    }
+
+    for root in roots {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let Ok(rd) = fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in rd.flatten() {
+            let path = entry.path();
+            if !is_app_bundle(&path) {
+                continue;
+            }
+            let plist = read_bundle_info(&path);
+            if plist
+                .bundle_id
+                .as_deref()
+                .is_some_and(|id| id.starts_with("com.apple."))
+            {
+                continue;
+            }
+            let name = plist.name.or_else(|| {
+                path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.to_string())
+            });
+            if let Some(name) = name {
+                detected.push(DetectedGame {
+                    info: empty_game_info(&name),
+                    install_path: Some(path),
+                    source: DetectionSource::CommonDir,
+                    platform_id: None,
+                    pcgw_score: None,
+                    ambiguous_with: None,
+                    diff: None,
+                });
+            }
+        }
+    }
+
+    Ok(detected)
 }
 
-/// 在 macOS 平台匹配存档路径（存根实现）
+/// 在 macOS 平台为指定游戏尝试匹配存档路径
 ///
-/// - 输入：游戏信息与安装路径
-/// - 输出：返回空匹配；后续将结合 `~/Library/Application Support` 等规则/PCGW 索引实现
-pub async fn match_save_paths(_game: &GameInfo, _install_path: &Path) -> Result<Vec<SaveMatchResult>> {
-    log::info!(target: "rgsm::scan", "{}", rust_i18n::t!("scan.platform_beta"));
-    Ok(Vec::new())
+/// - 输入：`GameInfo` 与安装路径（Steam 游戏目录或 `.app` 包）
+/// - 输出：`SaveMatchOutcome`，包含路径存在性与可信度，以及解析失败被跳过的规则
+/// - 行为：
+///   1. 解析适用于 macOS 的 PCGW 规则（平台为 `macos`/`mac`/`osx` 或未声明平台）
+///   2. 读取 `.app` 包的 Bundle ID，匹配 `~/Library/Containers/<bundle-id>/Data` 与 `~/Library/Preferences/<bundle-id>.plist`
+///   3. 在 `~/Library/Application Support` 中按游戏名/别名匹配目录
+pub async fn match_save_paths(
+    game: &GameInfo,
+    install_path: &Path,
+    cancel: &CancellationToken,
+) -> Result<SaveMatchOutcome> {
+    let env = default_env(&crate::config::Config::default());
+    let mut results = Vec::new();
+
+    let (resolved, skipped_rules) = resolve_save_rules(
+        game.save_rules
+            .iter()
+            .filter(|r| is_macos_rule(&r.platforms))
+            .take_while(|_| !cancel.is_cancelled()),
+        &env,
+    );
+    for (rule, p) in resolved {
+        let exists = p.exists();
+        let confidence = if exists {
+            rule.confidence.min(1.0)
+        } else {
+            rule.confidence * 0.5
+        };
+        results.push(SaveMatchResult {
+            rule_id: rule.id.clone(),
+            resolved_path: p,
+            exists,
+            confidence,
+        });
+    }
+
+    // 扫描被取消后跳过兜底匹配
+    let home = dirs::home_dir().filter(|_| !cancel.is_cancelled());
+    let Some(home) = home else {
+        return Ok(SaveMatchOutcome {
+            matches: results,
+            skipped_rules,
+        });
+    };
+    let library = home.join("Library");
+
+    // 沙盒应用的容器目录与偏好设置文件
+    if let Some(bundle_id) =
+        find_bundle(install_path).and_then(|app| read_bundle_info(&app).bundle_id)
+    {
+        let container = library.join("Containers").join(&bundle_id).join("Data");
+        let container_app_support = container.join("Library").join("Application Support");
+        if container_app_support.is_dir() {
+            results.push(SaveMatchResult {
+                rule_id: "macos-container-app-support".into(),
+                resolved_path: container_app_support,
+                exists: true,
+                confidence: 0.90,
+            });
+        } else if container.is_dir() {
+            results.push(SaveMatchResult {
+                rule_id: "macos-container-data".into(),
+                resolved_path: container,
+                exists: true,
+                confidence: 0.80,
+            });
+        }
+
+        let prefs = library
+            .join("Preferences")
+            .join(format!("{bundle_id}.plist"));
+        if prefs.is_file() {
+            results.push(SaveMatchResult {
+                rule_id: "macos-preferences-plist".into(),
+                resolved_path: prefs,
+                exists: true,
+                confidence: 0.70,
+            });
+        }
+    }
+
+    // 通用兜底：在 Application Support 中按游戏名/别名匹配
+    for p in search_name_matches(&library.join("Application Support"), game) {
+        results.push(SaveMatchResult {
+            rule_id: "macos-app-support-name-match".into(),
+            resolved_path: p,
+            exists: true,
+            confidence: 0.85,
+        });
+    }
+
+    Ok(SaveMatchOutcome {
+        matches: results,
+        skipped_rules,
+    })
 }
 
-/// 在 macOS 平台生成保存单元（存根实现）
+/// 生成 SaveUnit（含设备路径映射）
 ///
-/// - 输入：游戏信息与安装路径
-/// - 输出：返回空；后续将把匹配结果转换为 `SaveUnit`
-pub async fn generate_save_units(_game: &GameInfo, _install_path: &Path) -> Result<Vec<SaveUnit>> {
-    log::info!(target: "rgsm::scan", "{}", rust_i18n::t!("scan.platform_beta"));
-    Ok(Vec::new())
-}
\ No newline at end of file
+/// - 输入：`GameInfo` 与安装路径，用于路径解析与存在性检查；`options` 控制候选的取舍
+/// - 输出：采用的 `SaveUnit`（仅包含存在的路径，并映射到当前设备 ID）与未采用的其他候选
+pub async fn generate_save_units(
+    game: &GameInfo,
+    install_path: &Path,
+    options: &SaveUnitOptions,
+) -> Result<GeneratedSaveUnits> {
+    let matches = match_save_paths(game, install_path, &CancellationToken::new())
+        .await?
+        .matches;
+
+    // 按路径去重，保留可信度最高的匹配；按路径排序保证生成顺序稳定
+    let mut best_by_path: BTreeMap<String, SaveMatchResult> = BTreeMap::new();
+    for m in matches.into_iter().filter(|m| m.exists) {
+        let key = m.resolved_path.to_string_lossy().to_string();
+        match best_by_path.get(&key) {
+            Some(prev) if prev.confidence >= m.confidence => {}
+            _ => {
+                best_by_path.insert(key, m);
+            }
+        }
+    }
+
+    let scored = best_by_path
+        .into_values()
+        .map(|m| (m.confidence, m))
+        .collect();
+    Ok(build_save_units(scored, options))
+}
+
+/// `Info.plist` 中与匹配相关的字段
+#[derive(Debug, Default)]
+struct BundleInfo {
+    name: Option<String>,
+    bundle_id: Option<String>,
+}
+
+fn empty_game_info(name: &str) -> GameInfo {
+    GameInfo {
+        name: name.to_string(),
+        aliases: Vec::new(),
+        pcgw_id: None,
+        install_rules: Vec::new(),
+        save_rules: Vec::new(),
+    }
+}
+
+fn is_app_bundle(path: &Path) -> bool {
+    path.is_dir()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("app"))
+}
+
+/// 判断规则是否适用于 macOS（未声明平台的规则视为通用）
+fn is_macos_rule(platforms: &[String]) -> bool {
+    platforms.is_empty()
+        || platforms
+            .iter()
+            .any(|p| matches!(p.to_ascii_lowercase().as_str(), "macos" | "mac" | "osx"))
+}
+
+/// 查找安装路径对应的 `.app` 包
+///
+/// - 安装路径本身为 `.app` 时直接返回
+/// - 否则在安装目录的一级子项中查找（Steam 游戏通常为 `common/<Game>/<Game>.app`）
+fn find_bundle(install_path: &Path) -> Option<PathBuf> {
+    if is_app_bundle(install_path) {
+        return Some(install_path.to_path_buf());
+    }
+    fs::read_dir(install_path)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| is_app_bundle(p))
+}
+
+/// 读取 `.app/Contents/Info.plist` 中的名称与 Bundle ID
+///
+/// 仅支持 XML 格式的 plist；二进制 plist 将返回空字段，由调用方回退处理
+fn read_bundle_info(app: &Path) -> BundleInfo {
+    match fs::read_to_string(app.join("Contents").join("Info.plist")) {
+        Ok(content) => parse_info_plist(&content),
+        Err(_) => BundleInfo::default(),
+    }
+}
+
+fn parse_info_plist(content: &str) -> BundleInfo {
+    let read_key = |key: &str| {
+        let re = Regex::new(&format!(
+            r"<key>{}</key>\s*<string>([^<]+)</string>",
+            regex::escape(key)
+        ))
+        .ok()?;
+        re.captures(content)
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    BundleInfo {
+        name: read_key("CFBundleDisplayName").or_else(|| read_key("CFBundleName")),
+        bundle_id: read_key("CFBundleIdentifier"),
+    }
+}
+
+/// 在指定根目录中按游戏名/别名（去除空格与标点后比较）匹配一级子目录
+fn search_name_matches(root: &Path, game: &GameInfo) -> Vec<PathBuf> {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let tokens: Vec<String> = std::iter::once(&game.name)
+        .chain(game.aliases.iter())
+        .map(|s| normalize(s))
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let Ok(rd) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    rd.flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|s| s.to_str())
+                .map(|n| tokens.contains(&normalize(n)))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// 按安装路径对检测结果去重
+fn dedup_detected(items: Vec<DetectedGame>) -> Vec<DetectedGame> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .filter(|d| {
+            let key = match &d.install_path {
+                Some(p) => p.to_string_lossy().trim_end_matches('/').to_lowercase(),
+                None => format!("{}::{:?}", d.info.name.to_lowercase(), d.source),
+            };
+            seen.insert(key)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::create_dir_all;
+
+    /// 测试：解析 XML 格式的 Info.plist
+    #[test]
+    fn test_parse_info_plist() {
+        let sample = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <plist version="1.0">
+        <dict>
+            <key>CFBundleIdentifier</key>
+            <string>com.example.mygame</string>
+            <key>CFBundleName</key>
+            <string>My Game</string>
+        </dict>
+        </plist>"#;
+        let info = parse_info_plist(sample);
+        assert_eq!(info.bundle_id.as_deref(), Some("com.example.mygame"));
+        assert_eq!(info.name.as_deref(), Some("My Game"));
+    }
+
+    /// 测试：在安装目录中查找 `.app` 包
+    #[test]
+    fn test_find_bundle_in_install_dir() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let app = base.path().join("MyGame.app");
+        create_dir_all(app.join("Contents")).unwrap();
+        assert_eq!(find_bundle(base.path()), Some(app.clone()));
+        assert_eq!(find_bundle(&app), Some(app));
+    }
+
+    /// 测试：只解析适用于 macOS 的规则
+    #[test]
+    fn test_is_macos_rule() {
+        assert!(is_macos_rule(&[]));
+        assert!(is_macos_rule(&["macOS".to_string()]));
+        assert!(!is_macos_rule(&["windows".to_string()]));
+    }
+}
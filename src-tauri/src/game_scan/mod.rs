@@ -8,11 +8,20 @@
 //!
 //! 当前步骤仅提供类型与函数存根，后续步骤将逐步完善实现与命令注册。
 
+mod cache;
 mod db;
-mod resolver;
-pub mod types;
+mod deep_scan;
+mod fuzzy;
+mod history;
+mod ignore;
 mod ipc;
+mod options;
 mod platform;
+mod ranking;
+mod resolver;
+mod state;
+mod steam;
+pub mod types;
 
 // 仅在 Windows 平台编译 Windows 检测逻辑
 #[cfg(target_os = "windows")]
@@ -22,9 +31,21 @@ mod windows;
 #[cfg(target_os = "linux")]
 mod linux;
 
-// 仅在 macOS 平台编译 macOS 检测逻辑（存根）
+// 仅在 macOS 平台编译 macOS 检测逻辑
 #[cfg(target_os = "macos")]
 mod macos;
 
 // 对外导出常用类型
+pub use cache::PcgwIndexCache;
+pub use history::load_last_scan;
 pub use ipc::*;
+pub use state::ScanState;
+pub use steam::{find_steam_path, find_steam_user_id};
+
+/// 注册扫描模块所需的托管状态
+pub fn setup(app: &mut tauri::App) -> anyhow::Result<()> {
+    use tauri::Manager;
+    app.manage(ScanState::default());
+    app.manage(PcgwIndexCache::default());
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! 上次使用的扫描选项
+//!
+//! 每次开始扫描时将选项写入 `AppData/RGSM/scan_options.json`，之后获取默认选项时优先返回它，
+//! 没有记录或记录不适用于当前系统时返回平台默认值。
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use super::types::{SCAN_OPTIONS_VERSION, ScanOptions};
+
+const OPTIONS_FILE: &str = "scan_options.json";
+
+fn options_path(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .resolve("RGSM", BaseDirectory::AppData)
+        .context("Failed to resolve AppData/RGSM directory")?;
+    Ok(dir.join(OPTIONS_FILE))
+}
+
+/// 读取上次使用的扫描选项，文件不存在时返回 None
+pub fn load_scan_options(app: &AppHandle) -> Result<Option<ScanOptions>> {
+    let path = options_path(app)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read scan options at {}", path.display()))?;
+    let options = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse scan options at {}", path.display()))?;
+    Ok(Some(options))
+}
+
+/// 保存本次使用的扫描选项
+pub fn save_scan_options(app: &AppHandle, options: &ScanOptions) -> Result<()> {
+    let path = options_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache dir at {}", dir.display()))?;
+    }
+    let text = serde_json::to_string(options).context("Failed to serialize scan options")?;
+    fs::write(&path, text)
+        .with_context(|| format!("Failed to write scan options at {}", path.display()))?;
+    Ok(())
+}
+
+/// 选择返回给前端的默认选项
+///
+/// - 记录属于当前平台且版本不高于当前版本时沿用它，版本升级为当前版本
+/// - 其他情况（没有记录、来自其他系统、由更新的版本写入）返回平台默认值
+pub fn preferred_scan_options(stored: Option<ScanOptions>) -> ScanOptions {
+    match stored {
+        Some(mut options)
+            if options.matches_current_platform() && options.version <= SCAN_OPTIONS_VERSION =>
+        {
+            options.version = SCAN_OPTIONS_VERSION;
+            options
+        }
+        _ => ScanOptions::platform_defaults(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试：沿用当前平台的记录，忽略其他平台或更新版本写入的记录
+    #[test]
+    fn stored_options_are_preferred_when_applicable() {
+        let defaults = ScanOptions::platform_defaults();
+        assert_eq!(preferred_scan_options(None), defaults);
+
+        let stored = ScanOptions {
+            version: 0,
+            deep_scan: true,
+            ..defaults.clone()
+        };
+        let preferred = preferred_scan_options(Some(stored));
+        assert!(preferred.deep_scan);
+        assert_eq!(preferred.version, SCAN_OPTIONS_VERSION);
+
+        let other_platform = ScanOptions {
+            platform: "plan9".into(),
+            deep_scan: true,
+            ..defaults.clone()
+        };
+        assert_eq!(preferred_scan_options(Some(other_platform)), defaults);
+
+        let newer = ScanOptions {
+            version: SCAN_OPTIONS_VERSION + 1,
+            deep_scan: true,
+            ..defaults.clone()
+        };
+        assert_eq!(preferred_scan_options(Some(newer)), defaults);
+    }
+}
@@ -2,29 +2,44 @@
 //!
 //! 提供统一的接口以屏蔽不同操作系统的实现差异。
 //! - Windows：复用已实现的 `windows` 模块。
-//! - macOS/Linux：当前为安全存根，返回空结果并记录日志，逐步迭代完善。
+//! - macOS：复用 `macos` 模块（Steam 库与 `/Applications` 包）。
+//! - Linux：当前为安全存根，返回空结果并记录日志，逐步迭代完善。
 
 use anyhow::Result;
 use std::path::Path;
+use tokio_util::sync::CancellationToken;
 // 移除未使用的导入，保持编译无警告
 
-use crate::backup::SaveUnit;
-use super::types::{DetectedGame, GameInfo, SaveMatchResult, ScanOptions};
+use super::types::{
+    DetectedGame, GameInfo, GeneratedSaveUnits, SaveMatchOutcome, SaveUnitOptions, ScanOptions,
+};
 
+#[cfg(target_os = "macos")]
+use crate::game_scan::macos;
 #[cfg(target_os = "windows")]
 use crate::game_scan::windows;
 
 /// 检测已安装的游戏（跨平台入口）
 ///
 /// - Windows：调用 `windows::detect_installed_games`
-/// - 非 Windows：返回空列表并输出 Beta/受限提示日志
-pub async fn detect_installed_games(options: &ScanOptions) -> Result<Vec<DetectedGame>> {
+/// - macOS：调用 `macos::detect_installed_games`
+/// - 其他平台：返回空列表并输出 Beta/受限提示日志
+/// - `cancel` 被触发时平台实现应尽快返回已检测到的部分结果
+pub async fn detect_installed_games(
+    options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
     #[cfg(target_os = "windows")]
     {
-        return windows::detect_installed_games(options).await;
+        return windows::detect_installed_games(options, cancel).await;
     }
 
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "macos")]
+    {
+        return macos::detect_installed_games(options, cancel).await;
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
     {
         log::info!(target: "rgsm::scan", "{}", rust_i18n::t!("scan.platform_beta"));
         Ok(Vec::new())
@@ -34,33 +49,56 @@ pub async fn detect_installed_games(options: &ScanOptions) -> Result<Vec<Detecte
 /// 匹配存档路径（跨平台入口）
 ///
 /// - Windows：调用 `windows::match_save_paths`
-/// - 非 Windows：返回空匹配并记录提示日志
-pub async fn match_save_paths(game: &GameInfo, install_path: &Path) -> Result<Vec<SaveMatchResult>> {
+/// - macOS：调用 `macos::match_save_paths`
+/// - 其他平台：返回空匹配并记录提示日志
+/// - 单条规则解析失败不会中断匹配，被跳过的规则记录在 `SaveMatchOutcome::skipped_rules` 中
+/// - `cancel` 在每条规则之前检查，被触发时跳过剩余规则与兜底匹配，返回已匹配的部分结果
+pub async fn match_save_paths(
+    game: &GameInfo,
+    install_path: &Path,
+    cancel: &CancellationToken,
+) -> Result<SaveMatchOutcome> {
     #[cfg(target_os = "windows")]
     {
-        return windows::match_save_paths(game, install_path).await;
+        return windows::match_save_paths(game, install_path, cancel).await;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        return macos::match_save_paths(game, install_path, cancel).await;
     }
 
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
     {
         log::info!(target: "rgsm::scan", "{}", rust_i18n::t!("scan.platform_beta"));
-        Ok(Vec::new())
+        Ok(SaveMatchOutcome::default())
     }
 }
 
 /// 生成保存单元（跨平台入口）
 ///
 /// - Windows：调用 `windows::generate_save_units`
-/// - 非 Windows：返回空并记录提示日志
-pub async fn generate_save_units(game: &GameInfo, install_path: &Path) -> Result<Vec<SaveUnit>> {
+/// - macOS：调用 `macos::generate_save_units`
+/// - 其他平台：返回空并记录提示日志
+/// - `options` 控制候选的取舍，未采用的候选在 `GeneratedSaveUnits::alternatives` 中返回
+pub async fn generate_save_units(
+    game: &GameInfo,
+    install_path: &Path,
+    options: &SaveUnitOptions,
+) -> Result<GeneratedSaveUnits> {
     #[cfg(target_os = "windows")]
     {
-        return windows::generate_save_units(game, install_path).await;
+        return windows::generate_save_units(game, install_path, options).await;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        return macos::generate_save_units(game, install_path, options).await;
     }
 
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
     {
         log::info!(target: "rgsm::scan", "{}", rust_i18n::t!("scan.platform_beta"));
-        Ok(Vec::new())
+        Ok(GeneratedSaveUnits::default())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,143 @@
+//! 存档候选的排序与取舍
+//!
+//! 各平台先按路径去重并为候选打分，再由这里按 `SaveUnitOptions` 决定哪些候选
+//! 生成存档单元，其余候选作为“其他可能位置”返回给前端。
+
+use std::collections::HashMap;
+
+use super::types::{GeneratedSaveUnits, SaveMatchResult, SaveUnitOptions, SaveUnitStrategy};
+use crate::backup::{SaveUnit, SaveUnitType};
+use crate::device::get_current_device_id;
+
+/// 将匹配结果转换为映射到当前设备的存档单元
+fn to_save_unit(m: &SaveMatchResult) -> SaveUnit {
+    let unit_type = if m.resolved_path.is_file() {
+        SaveUnitType::File
+    } else {
+        SaveUnitType::Folder
+    };
+    let mut paths = HashMap::new();
+    paths.insert(
+        get_current_device_id().clone(),
+        m.resolved_path.to_string_lossy().to_string(),
+    );
+    SaveUnit {
+        unit_type,
+        paths,
+        ..Default::default()
+    }
+}
+
+/// 按得分排序并拆分为采用与备选两部分
+///
+/// - 输入：已去重的 `(得分, 匹配结果)` 列表与选项
+/// - 输出：`(采用, 备选)`，均按得分从高到低，得分相同时按路径排序保证结果稳定
+/// - `InteractiveAll` 不采用任何候选，阈值与数量上限只对其余两种策略生效
+pub fn split_candidates(
+    mut scored: Vec<(f32, SaveMatchResult)>,
+    options: &SaveUnitOptions,
+) -> (Vec<SaveMatchResult>, Vec<SaveMatchResult>) {
+    scored.sort_by(|(a, ma), (b, mb)| {
+        b.total_cmp(a)
+            .then_with(|| ma.resolved_path.cmp(&mb.resolved_path))
+    });
+    let limit = match options.prefer {
+        SaveUnitStrategy::HighestConfidenceOnly => 1,
+        SaveUnitStrategy::AllAboveThreshold => options.max_units.map_or(usize::MAX, |n| n as usize),
+        SaveUnitStrategy::InteractiveAll => 0,
+    };
+    let mut selected = Vec::new();
+    let mut alternatives = Vec::new();
+    for (score, m) in scored {
+        if selected.len() < limit && score >= options.min_confidence {
+            selected.push(m);
+        } else {
+            alternatives.push(m);
+        }
+    }
+    (selected, alternatives)
+}
+
+/// 按选项从候选生成存档单元
+pub fn build_save_units(
+    scored: Vec<(f32, SaveMatchResult)>,
+    options: &SaveUnitOptions,
+) -> GeneratedSaveUnits {
+    let (selected, alternatives) = split_candidates(scored, options);
+    GeneratedSaveUnits {
+        units: selected.iter().map(to_save_unit).collect(),
+        alternatives,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn candidate(path: &str, score: f32) -> (f32, SaveMatchResult) {
+        let m = SaveMatchResult {
+            rule_id: "r".into(),
+            resolved_path: PathBuf::from(path),
+            exists: true,
+            confidence: score,
+        };
+        (score, m)
+    }
+
+    fn paths(matches: &[SaveMatchResult]) -> Vec<&str> {
+        matches
+            .iter()
+            .map(|m| m.resolved_path.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn strategies_split_candidates_by_score() {
+        let scored = vec![
+            candidate("/documents/Game", 0.9),
+            candidate("/install/SaveGames", 1.1),
+            candidate("/appdata/Game Launcher", 0.5),
+        ];
+        let options = |prefer, min_confidence, max_units| SaveUnitOptions {
+            min_confidence,
+            max_units,
+            prefer,
+        };
+
+        let (selected, alternatives) =
+            split_candidates(scored.clone(), &SaveUnitOptions::default());
+        assert_eq!(
+            paths(&selected),
+            [
+                "/install/SaveGames",
+                "/documents/Game",
+                "/appdata/Game Launcher"
+            ]
+        );
+        assert!(alternatives.is_empty());
+
+        let (selected, alternatives) = split_candidates(
+            scored.clone(),
+            &options(SaveUnitStrategy::AllAboveThreshold, 0.8, Some(1)),
+        );
+        assert_eq!(paths(&selected), ["/install/SaveGames"]);
+        assert_eq!(
+            paths(&alternatives),
+            ["/documents/Game", "/appdata/Game Launcher"]
+        );
+
+        let (selected, _) = split_candidates(
+            scored.clone(),
+            &options(SaveUnitStrategy::HighestConfidenceOnly, 2.0, None),
+        );
+        assert!(selected.is_empty());
+
+        let (selected, alternatives) = split_candidates(
+            scored,
+            &options(SaveUnitStrategy::InteractiveAll, 0.0, None),
+        );
+        assert!(selected.is_empty());
+        assert_eq!(alternatives.len(), 3);
+    }
+}
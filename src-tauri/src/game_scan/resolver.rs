@@ -1,12 +1,18 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use globset::GlobBuilder;
+use log::warn;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::env;
+use std::path::{Component, Path, PathBuf};
+use std::{env, fs};
 
-use super::types::SavePathRule;
+use super::steam::find_steam_path;
+use super::types::{SavePathRule, SkippedRule};
 use crate::config::Config;
 use crate::path_resolver;
 
+/// 通配符展开得到的路径数量上限，防止过宽的规则遍历大量目录
+const MAX_WILDCARD_MATCHES: usize = 32;
+
 /// 变量解析环境
 #[derive(Debug, Clone)]
 pub struct ResolverEnv {
@@ -17,9 +23,9 @@ pub struct ResolverEnv {
 /// 构建默认解析环境
 ///
 /// - 输入：全局配置 `Config`
-/// - 输出：包含常用变量（如 `<home>`, `<documents>`, `<winAppData>` 等）的 `ResolverEnv`
-/// - 行为：从系统环境变量与常规路径推断变量值，不进行 IO 检查
-pub fn default_env(_config: &Config) -> ResolverEnv {
+/// - 输出：包含常用变量（如 `<home>`, `<documents>`, `<winAppData>`, `<steamPath>` 等）的 `ResolverEnv`
+/// - 行为：从系统环境变量与常规路径推断变量值；Steam 相关变量需要读取 Steam 目录，未安装时不填充
+pub fn default_env(config: &Config) -> ResolverEnv {
     let mut vars: HashMap<String, PathBuf> = HashMap::new();
 
     // 推断 home 目录（跨平台）
@@ -50,6 +56,14 @@ pub fn default_env(_config: &Config) -> ResolverEnv {
         }
     }
 
+    // Steam 根目录与账号 ID，用于 `<steamPath>/userdata/<steamUserId>/...` 形式的规则
+    if let Some(steam_path) = find_steam_path() {
+        vars.insert("steamPath".into(), steam_path);
+    }
+    if let Ok(user_id) = path_resolver::steam_user_id(config) {
+        vars.insert("steamUserId".into(), PathBuf::from(user_id));
+    }
+
     ResolverEnv { variables: vars }
 }
 
@@ -87,8 +101,173 @@ pub fn resolve_template(template: &str, _env: &ResolverEnv) -> Result<PathBuf> {
 /// 将保存规则解析为实际路径集合
 ///
 /// - 输入：`SavePathRule` 与解析环境
-/// - 输出：解析出的路径集合；后续可扩展到多模板与平台过滤
+/// - 输出：解析出的路径集合；模板中含 `*`、`?` 时为通配符展开后的每个实际路径
 pub fn resolve_save_rule(rule: &SavePathRule, env: &ResolverEnv) -> Result<Vec<PathBuf>> {
     let p = resolve_template(&rule.path_template, env)?;
-    Ok(vec![p])
+    expand_wildcards(&p)
+}
+
+/// 判断路径片段是否包含通配符
+fn has_wildcard(segment: &str) -> bool {
+    segment.contains(['*', '?'])
+}
+
+/// 展开路径中的 `*` 与 `?` 通配符（如 `PlayerProfiles/*/Savegames` 中的用户 ID 目录）
+///
+/// - 输入：变量替换后的路径
+/// - 输出：逐级读取目录、按片段匹配得到的路径，按路径排序；不含通配符时原样返回
+/// - 限制：第一个通配符之前至少要有一级固定目录，避免从根目录或盘符开始遍历；
+///   结果最多 `MAX_WILDCARD_MATCHES` 个，超出部分被丢弃
+/// - 通配符不跨越目录；Windows 下大小写不敏感
+pub fn expand_wildcards(path: &Path) -> Result<Vec<PathBuf>> {
+    if !has_wildcard(&path.to_string_lossy()) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let components: Vec<Component> = path.components().collect();
+    let mut candidates = vec![PathBuf::new()];
+    let mut fixed_dirs = 0;
+    for (i, component) in components.iter().enumerate() {
+        // 只展开普通片段，Windows 的 `\\?\` 前缀不是通配符
+        let segment = match component {
+            Component::Normal(segment) => segment.to_string_lossy(),
+            _ => {
+                for candidate in &mut candidates {
+                    candidate.push(component);
+                }
+                continue;
+            }
+        };
+        if !has_wildcard(&segment) {
+            fixed_dirs += 1;
+            for candidate in &mut candidates {
+                candidate.push(component);
+            }
+            continue;
+        }
+        if fixed_dirs == 0 {
+            bail!(
+                "Wildcard above the first fixed directory: {}",
+                path.display()
+            );
+        }
+        let matcher = GlobBuilder::new(&segment)
+            .literal_separator(true)
+            .case_insensitive(cfg!(target_os = "windows"))
+            .build()?
+            .compile_matcher();
+        // 后面还有片段时只保留目录
+        let is_last = i + 1 == components.len();
+        let mut expanded = Vec::new();
+        for candidate in &candidates {
+            let Ok(entries) = fs::read_dir(candidate) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                if matcher.is_match(entry.file_name()) && (is_last || entry.path().is_dir()) {
+                    expanded.push(entry.path());
+                }
+            }
+        }
+        expanded.sort();
+        if expanded.len() > MAX_WILDCARD_MATCHES {
+            warn!(target:"rgsm::game_scan", "Wildcard path {} matches too many entries, keeping the first {}", path.display(), MAX_WILDCARD_MATCHES);
+            expanded.truncate(MAX_WILDCARD_MATCHES);
+        }
+        candidates = expanded;
+    }
+    Ok(candidates)
+}
+
+/// 逐条解析存档规则，单条规则失败时记录并跳过，不影响其余规则
+///
+/// - 输入：待解析的规则与解析环境
+/// - 输出：成功解析的 `(规则, 路径)` 列表，以及被跳过的规则
+pub fn resolve_save_rules<'a>(
+    rules: impl IntoIterator<Item = &'a SavePathRule>,
+    env: &ResolverEnv,
+) -> (Vec<(&'a SavePathRule, PathBuf)>, Vec<SkippedRule>) {
+    let mut resolved = Vec::new();
+    let mut skipped = Vec::new();
+    for rule in rules {
+        match resolve_save_rule(rule, env) {
+            Ok(paths) => resolved.extend(paths.into_iter().map(|p| (rule, p))),
+            Err(e) => {
+                warn!(target:"rgsm::game_scan", "Skipping rule {}: {e:#}", rule.id);
+                skipped.push(SkippedRule {
+                    rule_id: rule.id.clone(),
+                    reason: format!("{e:#}"),
+                });
+            }
+        }
+    }
+    (resolved, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, path_template: &str) -> SavePathRule {
+        SavePathRule {
+            id: id.into(),
+            description: None,
+            path_template: path_template.into(),
+            requires: None,
+            platforms: Vec::new(),
+            confidence: 0.8,
+        }
+    }
+
+    #[test]
+    fn unknown_variable_only_skips_its_own_rule() {
+        let env = ResolverEnv {
+            variables: HashMap::new(),
+        };
+        let rules = [
+            rule("bad", "<notAVariable>/Saves"),
+            rule("good", "/games/Celeste/Saves"),
+        ];
+        let (resolved, skipped) = resolve_save_rules(&rules, &env);
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].0.id, "good");
+        assert_eq!(resolved[0].1, PathBuf::from("/games/Celeste/Saves"));
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].rule_id, "bad");
+        assert!(skipped[0].reason.contains("<notAVariable>"));
+    }
+
+    #[test]
+    fn wildcard_segments_expand_to_every_profile() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let profiles = dir.path().join("Baldur's Gate 3").join("PlayerProfiles");
+        for profile in ["Public", "1a2b3c"] {
+            fs::create_dir_all(profiles.join(profile).join("Savegames")).unwrap();
+        }
+        fs::write(profiles.join("profile.lsf"), b"").unwrap();
+        let env = ResolverEnv {
+            variables: HashMap::from([("root".to_string(), dir.path().to_path_buf())]),
+        };
+
+        let paths = resolve_save_rule(
+            &rule("bg3", "<root>/Baldur's Gate 3/PlayerProfiles/*/Savegames"),
+            &env,
+        )
+        .unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                profiles.join("1a2b3c").join("Savegames"),
+                profiles.join("Public").join("Savegames"),
+            ]
+        );
+        let paths = expand_wildcards(&profiles.join("?a*")).unwrap();
+        assert_eq!(paths, vec![profiles.join("1a2b3c")]);
+    }
+
+    #[test]
+    fn wildcards_need_a_fixed_directory_first() {
+        assert!(expand_wildcards(Path::new("/*/Saves")).is_err());
+    }
 }
@@ -0,0 +1,80 @@
+//! 扫描任务状态（由 Tauri 托管）
+//!
+//! 同一时间只允许一个扫描任务运行：启动新的扫描会取消仍在运行的旧扫描，
+//! 旧扫描将带着已完成的部分结果返回并标记为 `cancelled`。
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio_util::sync::CancellationToken;
+
+/// 当前扫描任务的取消令牌与代数（用于区分先后启动的扫描）
+#[derive(Default)]
+pub struct ScanState {
+    current: Mutex<Option<(u64, CancellationToken)>>,
+    generation: AtomicU64,
+}
+
+/// 一次扫描的句柄，扫描结束时需调用 `ScanState::finish` 归还
+pub struct ScanTicket {
+    generation: u64,
+    pub token: CancellationToken,
+}
+
+impl ScanState {
+    /// 开始新的扫描：取消仍在运行的旧扫描，并登记新的取消令牌
+    pub fn begin(&self) -> ScanTicket {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let token = CancellationToken::new();
+        let mut current = self.current.lock().expect("ScanState poisoned");
+        if let Some((_, old)) = current.replace((generation, token.clone())) {
+            old.cancel();
+        }
+        ScanTicket { generation, token }
+    }
+
+    /// 取消正在运行的扫描，返回是否确实存在被取消的扫描
+    pub fn cancel(&self) -> bool {
+        let current = self.current.lock().expect("ScanState poisoned");
+        match current.as_ref() {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 扫描结束：仅当登记的仍是本次扫描时才清除
+    pub fn finish(&self, ticket: &ScanTicket) {
+        let mut current = self.current.lock().expect("ScanState poisoned");
+        if current
+            .as_ref()
+            .is_some_and(|(generation, _)| *generation == ticket.generation)
+        {
+            *current = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试：新扫描会取消旧扫描，旧扫描结束时不会清除新扫描的登记
+    #[test]
+    fn begin_cancels_previous_scan() {
+        let state = ScanState::default();
+        let first = state.begin();
+        let second = state.begin();
+        assert!(first.token.is_cancelled());
+        assert!(!second.token.is_cancelled());
+
+        state.finish(&first);
+        assert!(state.cancel());
+        assert!(second.token.is_cancelled());
+
+        state.finish(&second);
+        assert!(!state.cancel());
+    }
+}
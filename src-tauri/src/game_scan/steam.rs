@@ -0,0 +1,427 @@
+//! Steam 库解析（跨平台）
+//!
+//! 各平台的检测逻辑共用此处的 Steam 根目录定位、`libraryfolders.vdf` 与
+//! `appmanifest_<appid>.acf` 解析，路径变量 `<steamPath>`、`<steamUserId>` 也由此解析。
+
+use anyhow::{Context, Result};
+use log::warn;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+use std::{env, fs};
+
+use super::types::{DetectedGame, DetectionSource, GameInfo, SavePathRule};
+
+/// 由 Steam 云存档目录生成的存档规则 ID 前缀
+const STEAM_USERDATA_RULE_PREFIX: &str = "steam-userdata";
+
+/// `libraryfolders.vdf` 中的 `"path" "<dir>"` 键值
+static LIBRARY_PATH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"path"\s*"([^"]+)"#).unwrap());
+/// `appmanifest_<appid>.acf` 中一行的 `"key" "value"`，允许转义的引号
+static MANIFEST_KEY_VALUE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^"((?:[^"\\]|\\.)*)"\s+"((?:[^"\\]|\\.)*)""#).unwrap());
+
+/// 定位 Steam 根目录
+///
+/// - 提供环境变量 `RGSM_STEAM_PATH_OVERRIDE` 作为覆盖，便于测试与异常场景
+/// - 其次按平台的默认位置依次查找，返回第一个存在的目录
+pub fn find_steam_path() -> Option<PathBuf> {
+    if let Ok(override_path) = env::var("RGSM_STEAM_PATH_OVERRIDE") {
+        let p = PathBuf::from(override_path);
+        if p.exists() {
+            return Some(p);
+        }
+    }
+    default_steam_paths().into_iter().find(|p| p.exists())
+}
+
+/// Windows 上的候选位置
+///
+/// - 优先读取 `HKCU\Software\Valve\Steam` 的 `SteamPath`/`InstallPath`
+/// - 其次读取 `HKLM\Software\WOW6432Node\Valve\Steam`（32 位路径）
+/// - 兜底为 `Program Files` 下的默认安装位置
+#[cfg(target_os = "windows")]
+fn default_steam_paths() -> Vec<PathBuf> {
+    use winreg::RegKey;
+    use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+
+    let mut candidates = Vec::new();
+    let keys = [
+        (HKEY_CURRENT_USER, "Software\\Valve\\Steam"),
+        (HKEY_LOCAL_MACHINE, "Software\\WOW6432Node\\Valve\\Steam"),
+    ];
+    for (hive, path) in keys {
+        let Ok(key) = RegKey::predef(hive).open_subkey(path) else {
+            continue;
+        };
+        for name in ["SteamPath", "InstallPath"] {
+            if let Ok(val) = key.get_value::<String, _>(name) {
+                candidates.push(PathBuf::from(val));
+            }
+        }
+    }
+    for var in ["PROGRAMFILES", "PROGRAMFILES(X86)"] {
+        if let Ok(dir) = env::var(var) {
+            candidates.push(PathBuf::from(dir).join("Steam"));
+        }
+    }
+    candidates
+}
+
+/// macOS 上的候选位置：`~/Library/Application Support/Steam`
+#[cfg(target_os = "macos")]
+fn default_steam_paths() -> Vec<PathBuf> {
+    dirs::home_dir()
+        .map(|home| {
+            home.join("Library")
+                .join("Application Support")
+                .join("Steam")
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Linux 上的候选位置：`~/.steam/steam` 与 `~/.local/share/Steam`
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn default_steam_paths() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    vec![
+        home.join(".steam").join("steam"),
+        home.join(".local").join("share").join("Steam"),
+    ]
+}
+
+/// 选择 Steam 账号 ID，即 `<steam_path>/userdata` 下的目录名
+///
+/// - 只考虑纯数字且不为 `0` 的目录
+/// - 有多个账号时取 `config/localconfig.vdf` 修改时间最新的一个，即最近登录的账号
+pub fn find_steam_user_id(steam_path: &Path) -> Option<String> {
+    let rd = fs::read_dir(steam_path.join("userdata")).ok()?;
+    rd.flatten()
+        .filter_map(|entry| {
+            let id = entry.file_name().to_str()?.to_string();
+            if id == "0" || !id.bytes().all(|b| b.is_ascii_digit()) || !entry.path().is_dir() {
+                return None;
+            }
+            let modified = fs::metadata(entry.path().join("config").join("localconfig.vdf"))
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((modified, id))
+        })
+        .max()
+        .map(|(_, id)| id)
+}
+
+/// 解析 Steam 库文件 `libraryfolders.vdf` 并返回所有库路径
+///
+/// - 文件位置：`<steam_path>/steamapps/libraryfolders.vdf`
+/// - 解析策略：容错地提取所有 `"path"` 字段的值
+/// - 返回：存在的库路径，并附带主库（`steam_path`）本身
+pub fn read_steam_library_folders(steam_path: &Path) -> Result<Vec<PathBuf>> {
+    let vdf_path = steam_path.join("steamapps").join("libraryfolders.vdf");
+    let content = fs::read_to_string(&vdf_path)
+        .with_context(|| format!("Failed to read libraryfolders.vdf: {}", vdf_path.display()))?;
+    let paths = parse_libraryfolders_vdf(&content);
+    let mut out = Vec::new();
+    for p in paths {
+        let pb = PathBuf::from(p);
+        if pb.exists() && !out.contains(&pb) {
+            out.push(pb);
+        }
+    }
+    // 将主库也加入（SteamPath）
+    if steam_path.exists() && !out.iter().any(|p| p == steam_path) {
+        out.push(steam_path.to_path_buf());
+    }
+    Ok(out)
+}
+
+/// 简易解析 `libraryfolders.vdf` 内容，收集所有 `path` 值
+///
+/// - 适配新版/旧版 KeyValues 格式，尽可能宽松地匹配
+/// - 返回原始字符串路径列表（不判断存在性）
+pub fn parse_libraryfolders_vdf(content: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    for cap in LIBRARY_PATH.captures_iter(content) {
+        if let Some(m) = cap.get(1) {
+            let raw = m.as_str().trim();
+            if !raw.is_empty() {
+                // 规范化双反斜杠为单反斜杠，便于后续 Path 处理
+                let normalized = raw.replace("\\\\", "\\");
+                paths.push(normalized);
+            }
+        }
+    }
+    paths
+}
+
+/// `appmanifest_<appid>.acf` 中与检测相关的字段
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteamAppManifest {
+    pub appid: String,
+    /// 商店中的游戏名称
+    pub name: String,
+    /// `steamapps/common` 下的安装目录名
+    pub installdir: String,
+}
+
+/// 解析 `appmanifest_<appid>.acf` 内容
+///
+/// - 只读取 `AppState` 顶层的 `appid`、`name`、`installdir`，忽略 `UserConfig` 等嵌套块中的同名键
+/// - 按行宽松解析，任一字段缺失时返回 None
+pub fn parse_appmanifest_acf(content: &str) -> Option<SteamAppManifest> {
+    let (mut appid, mut name, mut installdir) = (None, None, None);
+    let mut depth = 0usize;
+    for line in content.lines().map(str::trim) {
+        match line {
+            "{" => depth += 1,
+            "}" => depth = depth.saturating_sub(1),
+            _ if depth == 1 => {
+                let Some(cap) = MANIFEST_KEY_VALUE.captures(line) else {
+                    continue;
+                };
+                let value = cap[2].replace("\\\"", "\"").replace("\\\\", "\\");
+                let slot = match cap[1].to_ascii_lowercase().as_str() {
+                    "appid" => &mut appid,
+                    "name" => &mut name,
+                    "installdir" => &mut installdir,
+                    _ => continue,
+                };
+                slot.get_or_insert(value);
+            }
+            _ => {}
+        }
+    }
+    Some(SteamAppManifest {
+        appid: appid.filter(|s| !s.is_empty())?,
+        name: name.filter(|s| !s.is_empty())?,
+        installdir: installdir.filter(|s| !s.is_empty())?,
+    })
+}
+
+/// 读取库目录 `<library>/steamapps` 下所有的应用清单，无法解析的文件会被跳过
+pub fn read_steam_app_manifests(library: &Path) -> Vec<SteamAppManifest> {
+    let Ok(rd) = fs::read_dir(library.join("steamapps")) else {
+        return Vec::new();
+    };
+    let mut manifests = Vec::new();
+    for entry in rd.flatten() {
+        let path = entry.path();
+        let is_manifest = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .is_some_and(|n| n.starts_with("appmanifest_") && n.ends_with(".acf"));
+        if !is_manifest {
+            continue;
+        }
+        match fs::read_to_string(&path)
+            .ok()
+            .as_deref()
+            .and_then(parse_appmanifest_acf)
+        {
+            Some(manifest) => manifests.push(manifest),
+            None => {
+                warn!(target:"rgsm::game_scan::steam", "Failed to parse app manifest: {}", path.display())
+            }
+        }
+    }
+    manifests
+}
+
+/// 为 `appid` 生成 Steam 云存档规则
+///
+/// - 路径形式：`<steam>/userdata/<uid>/<appid>/remote`，每个登录过的账号一条
+/// - 只保留实际存在的目录，避免为不使用 Steam 云的游戏生成无效规则
+pub fn steam_userdata_save_rules(steam_path: &Path, appid: &str) -> Vec<SavePathRule> {
+    let Ok(rd) = fs::read_dir(steam_path.join("userdata")) else {
+        return Vec::new();
+    };
+    let mut rules: Vec<SavePathRule> = rd
+        .flatten()
+        .filter_map(|entry| {
+            let uid = entry.file_name().to_str()?.to_string();
+            let remote = entry.path().join(appid).join("remote");
+            remote.is_dir().then(|| SavePathRule {
+                id: format!("{STEAM_USERDATA_RULE_PREFIX}-{uid}"),
+                description: Some(format!("Steam Cloud ({uid})")),
+                path_template: remote.to_string_lossy().to_string(),
+                requires: None,
+                platforms: vec![std::env::consts::OS.to_string()],
+                confidence: 0.9,
+            })
+        })
+        .collect();
+    rules.sort_by(|a, b| a.id.cmp(&b.id));
+    rules
+}
+
+/// 枚举单个库的 `steamapps/common` 子目录，每个子目录视为一个候选游戏
+///
+/// - 有对应应用清单时使用商店名称（目录名作为别名）、记录 appid，并附带 Steam 云存档规则
+/// - 没有清单时回退为目录名
+pub fn scan_steam_library(library: &Path, steam_path: &Path) -> Vec<DetectedGame> {
+    let manifests = read_steam_app_manifests(library);
+    let Ok(rd) = fs::read_dir(library.join("steamapps").join("common")) else {
+        return Vec::new();
+    };
+    let mut detected = Vec::new();
+    for entry in rd.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let Some(folder) = path.file_name().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let manifest = manifests
+            .iter()
+            .find(|m| m.installdir.eq_ignore_ascii_case(folder));
+        let mut info = GameInfo {
+            name: folder.to_string(),
+            aliases: Vec::new(),
+            pcgw_id: None,
+            install_rules: Vec::new(),
+            save_rules: Vec::new(),
+        };
+        if let Some(m) = manifest {
+            if m.name != folder {
+                info.name = m.name.clone();
+                info.aliases.push(folder.to_string());
+            }
+            info.save_rules = steam_userdata_save_rules(steam_path, &m.appid);
+        }
+        detected.push(DetectedGame {
+            info,
+            install_path: Some(path.clone()),
+            source: DetectionSource::Steam,
+            platform_id: manifest.map(|m| m.appid.clone()),
+            pcgw_score: None,
+            ambiguous_with: None,
+            diff: None,
+        });
+    }
+    detected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::create_dir_all;
+    use std::io::Write;
+
+    /// 测试：解析 libraryfolders.vdf 内容提取路径（Windows 与 Unix 风格）
+    #[test]
+    fn test_parse_libraryfolders_vdf() {
+        let sample = r#"
+        "libraryfolders"
+        {
+            "0"
+            {
+                "path"    "/Users/me/Library/Application Support/Steam"
+            }
+            "1"
+            {
+                "path"    "D:\\SteamLibrary"
+                "label"   "Secondary"
+            }
+        }
+        "#;
+        let paths = parse_libraryfolders_vdf(sample);
+        assert!(paths.contains(&"/Users/me/Library/Application Support/Steam".to_string()));
+        assert!(paths.contains(&"D:\\SteamLibrary".to_string()));
+    }
+
+    /// 测试：读取 libraryfolders.vdf 返回存在的库目录且主库不重复
+    #[test]
+    fn test_read_steam_library_folders_dedup() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let steam_path = base.path().join("Steam");
+        let steamapps = steam_path.join("steamapps");
+        create_dir_all(&steamapps).unwrap();
+
+        let mut f = std::fs::File::create(steamapps.join("libraryfolders.vdf")).unwrap();
+        write!(
+            f,
+            "\n\"libraryfolders\"\n{{\n\"0\"\n{{\n\"path\"\t\"{}\"\n}}\n}}\n",
+            steam_path.display()
+        )
+        .unwrap();
+
+        let libs = read_steam_library_folders(&steam_path).unwrap();
+        assert_eq!(libs, vec![steam_path]);
+    }
+
+    /// 测试：解析应用清单，只取 AppState 顶层字段
+    #[test]
+    fn test_parse_appmanifest_acf() {
+        let manifest =
+            parse_appmanifest_acf(include_str!("fixtures/appmanifest_1151640.acf")).unwrap();
+        assert_eq!(
+            manifest,
+            SteamAppManifest {
+                appid: "1151640".into(),
+                name: "Horizon Zero Dawn Complete Edition".into(),
+                installdir: "Horizon Zero Dawn".into(),
+            }
+        );
+        assert!(parse_appmanifest_acf("\"AppState\"\n{\n\"appid\"\t\"1\"\n}\n").is_none());
+    }
+
+    /// 测试：库扫描使用清单名称与 appid，并生成存在的 Steam 云存档规则
+    #[test]
+    fn test_scan_steam_library_with_manifest() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let steam_path = base.path().join("Steam");
+        let steamapps = steam_path.join("steamapps");
+        create_dir_all(steamapps.join("common").join("Horizon Zero Dawn")).unwrap();
+        create_dir_all(steamapps.join("common").join("NoManifest")).unwrap();
+        std::fs::write(
+            steamapps.join("appmanifest_1151640.acf"),
+            include_str!("fixtures/appmanifest_1151640.acf"),
+        )
+        .unwrap();
+        let remote = steam_path
+            .join("userdata")
+            .join("42")
+            .join("1151640")
+            .join("remote");
+        create_dir_all(&remote).unwrap();
+        create_dir_all(steam_path.join("userdata").join("43")).unwrap();
+
+        let mut detected = scan_steam_library(&steam_path, &steam_path);
+        detected.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+        assert_eq!(detected.len(), 2);
+        let hzd = &detected[0];
+        assert_eq!(hzd.info.name, "Horizon Zero Dawn Complete Edition");
+        assert_eq!(hzd.info.aliases, vec!["Horizon Zero Dawn".to_string()]);
+        assert_eq!(hzd.platform_id.as_deref(), Some("1151640"));
+        assert_eq!(hzd.info.save_rules.len(), 1);
+        assert_eq!(hzd.info.save_rules[0].id, "steam-userdata-42");
+        assert_eq!(PathBuf::from(&hzd.info.save_rules[0].path_template), remote);
+        assert_eq!(detected[1].info.name, "NoManifest");
+        assert!(detected[1].platform_id.is_none());
+    }
+
+    /// 测试：多个账号时选择 localconfig.vdf 最新的账号
+    #[test]
+    fn test_find_steam_user_id_prefers_recent_login() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let userdata = base.path().join("userdata");
+        for id in ["0", "111", "222", "333"] {
+            create_dir_all(userdata.join(id).join("config")).unwrap();
+        }
+        let old = std::fs::File::create(userdata.join("111/config/localconfig.vdf")).unwrap();
+        old.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000))
+            .unwrap();
+        let recent = std::fs::File::create(userdata.join("222/config/localconfig.vdf")).unwrap();
+        recent
+            .set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(2_000))
+            .unwrap();
+
+        assert_eq!(find_steam_user_id(base.path()).as_deref(), Some("222"));
+        assert_eq!(find_steam_user_id(&base.path().join("missing")), None);
+    }
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 use std::path::PathBuf;
 
+use crate::backup::SaveUnit;
+
 /// 游戏基础信息与路径规则集合
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct GameInfo {
@@ -20,7 +22,7 @@ pub struct GameInfo {
 /// PCGW 查询选项
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct PcgwQueryOptions {
-    /// 是否启用模糊匹配（令包含与前后缀匹配生效）
+    /// 是否启用模糊匹配（拼写相近、逐词前缀、首字母缩写与片段包含）
     pub fuzzy: bool,
     /// 可选的平台过滤（例如 `windows`、`macos`、`linux`），为空则不筛选
     pub platform: Option<String>,
@@ -33,7 +35,7 @@ pub struct PcgwQueryOptions {
 pub struct PcgwQueryItem {
     /// 命中的游戏信息
     pub info: GameInfo,
-    /// 匹配评分（0.0~1.0），用于排序显示
+    /// 匹配评分（0.0~1.0），用于排序显示：名称精确匹配为 1.0，别名精确匹配为 0.95，模糊匹配恒低于 0.95
     pub score: f32,
     /// 命中依据（`name`/`alias`/`fuzzy`），便于前端标注
     pub matched_by: String,
@@ -78,9 +80,15 @@ pub struct SavePathRule {
     pub confidence: f32,
 }
 
+/// 当前的扫描选项版本，新增字段时递增，旧的持久化副本按字段默认值补齐
+pub const SCAN_OPTIONS_VERSION: u32 = 1;
+
 /// 扫描选项
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
 pub struct ScanOptions {
+    /// 选项版本，缺失时视为首个版本
+    #[serde(default = "default_scan_options_version")]
+    pub version: u32,
     /// 平台标识（如 `windows`、`macos`、`linux`）
     pub platform: String,
     /// 是否扫描 Steam 安装目录
@@ -95,6 +103,55 @@ pub struct ScanOptions {
     pub search_common_dirs: bool,
     /// 是否通过当前运行进程进行辅助匹配
     pub search_processes: bool,
+    /// 是否对没有存档规则的游戏启用启发式深度扫描（较慢）
+    #[serde(default)]
+    pub deep_scan: bool,
+    /// 深度扫描时相对于每个根目录的最大层数
+    #[serde(default = "default_deep_scan_max_depth")]
+    pub deep_scan_max_depth: u32,
+}
+
+fn default_deep_scan_max_depth() -> u32 {
+    4
+}
+
+fn default_scan_options_version() -> u32 {
+    1
+}
+
+impl ScanOptions {
+    /// 当前系统的平台标识，与 `std::env::consts::OS` 一致
+    pub fn current_platform() -> &'static str {
+        std::env::consts::OS
+    }
+
+    /// 当前系统的默认扫描选项
+    ///
+    /// - Windows：除进程匹配外全部开启
+    /// - Linux：仅扫描 Steam 与常见目录，Epic / Origin / 注册表在该平台没有意义
+    /// - macOS：扫描 Steam 与常见目录（`/Applications`）
+    pub fn platform_defaults() -> Self {
+        let windows = cfg!(target_os = "windows");
+        Self {
+            version: SCAN_OPTIONS_VERSION,
+            platform: Self::current_platform().to_string(),
+            search_steam: true,
+            search_epic: windows,
+            search_origin: windows,
+            search_registry: windows,
+            search_common_dirs: true,
+            search_processes: false,
+            deep_scan: false,
+            deep_scan_max_depth: default_deep_scan_max_depth(),
+        }
+    }
+
+    /// 选项的平台是否与当前系统一致（忽略大小写与首尾空白）
+    pub fn matches_current_platform(&self) -> bool {
+        self.platform
+            .trim()
+            .eq_ignore_ascii_case(Self::current_platform())
+    }
 }
 
 /// 安装来源，用于标注检测到的依据
@@ -109,6 +166,17 @@ pub enum DetectionSource {
     Manual,
 }
 
+/// 重新扫描时与上次扫描结果的对比
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+pub enum ScanDiffStatus {
+    /// 上次扫描中没有的游戏
+    New,
+    /// 上次扫描中已存在的游戏
+    Unchanged,
+    /// 上次扫描中存在、本次未检测到的游戏（通常为已卸载）
+    Missing,
+}
+
 /// 已检测到的游戏条目
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct DetectedGame {
@@ -118,6 +186,18 @@ pub struct DetectedGame {
     pub install_path: Option<PathBuf>,
     /// 检测来源
     pub source: DetectionSource,
+    /// 来源平台中的游戏 ID，目前为 Steam 应用清单中的 appid
+    #[serde(default)]
+    pub platform_id: Option<String>,
+    /// 与 PCGW 索引的匹配分数（1.0 为名称精确匹配），未匹配时为 None
+    #[serde(default)]
+    pub pcgw_score: Option<f32>,
+    /// 最佳匹配的 PCGW 条目已分配给另一个检测结果时，记录该结果的名称，此时 `info` 保持原样
+    #[serde(default)]
+    pub ambiguous_with: Option<String>,
+    /// 与上次扫描的对比结果，仅由 `rescan_diff` 填充
+    #[serde(default)]
+    pub diff: Option<ScanDiffStatus>,
 }
 
 /// 存档路径匹配结果
@@ -133,6 +213,59 @@ pub struct SaveMatchResult {
     pub confidence: f32,
 }
 
+/// 生成存档单元时候选路径的取舍策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Type)]
+pub enum SaveUnitStrategy {
+    /// 只采用得分最高的一个候选
+    HighestConfidenceOnly,
+    /// 采用所有达到阈值的候选（受 `max_units` 限制）
+    #[default]
+    AllAboveThreshold,
+    /// 不自动采用，所有候选都作为备选交给用户选择
+    InteractiveAll,
+}
+
+/// 生成存档单元的选项，默认采用所有存在的候选
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct SaveUnitOptions {
+    /// 候选得分的下限，低于该值的候选只作为备选
+    #[serde(default)]
+    pub min_confidence: f32,
+    /// 最多采用的存档单元数量，为空时不限制
+    #[serde(default)]
+    pub max_units: Option<u32>,
+    /// 取舍策略
+    #[serde(default)]
+    pub prefer: SaveUnitStrategy,
+}
+
+/// 存档单元生成结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct GeneratedSaveUnits {
+    /// 自动采用的存档单元，按得分从高到低
+    pub units: Vec<SaveUnit>,
+    /// 未被采用的其他可能位置，按得分从高到低，不会自动添加
+    pub alternatives: Vec<SaveMatchResult>,
+}
+
+/// 解析失败而被跳过的存档规则（如模板中含有未知变量）
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedRule {
+    /// 对应的规则 ID
+    pub rule_id: String,
+    /// 解析失败的原因
+    pub reason: String,
+}
+
+/// 单个游戏的存档匹配输出（后端内部使用）
+#[derive(Debug, Clone, Default)]
+pub struct SaveMatchOutcome {
+    /// 匹配到的存档路径
+    pub matches: Vec<SaveMatchResult>,
+    /// 被跳过的规则，不影响其余规则的匹配
+    pub skipped_rules: Vec<SkippedRule>,
+}
+
 /// 扫描进度事件载荷（用于前端进度显示）
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ScanProgressEvent {
@@ -155,6 +288,14 @@ pub struct ScanResult {
     pub matches: Vec<SaveMatchResult>,
     /// 错误消息（若有）
     pub errors: Vec<String>,
+    /// 扫描是否被取消（为真时上述结果仅为取消前已完成的部分）
+    pub cancelled: bool,
+    /// 因命中忽略列表而跳过的检测结果数量
+    #[serde(default)]
+    pub skipped_ignored: u32,
+    /// 被忽略的检测结果（不参与 PCGW 丰富与存档匹配），供前端“显示已忽略”使用
+    #[serde(default)]
+    pub ignored: Vec<DetectedGame>,
 }
 
 #[cfg(test)]
@@ -166,6 +307,7 @@ mod tests {
     #[test]
     fn serde_roundtrip_scan_options() {
         let opts = ScanOptions {
+            version: SCAN_OPTIONS_VERSION,
             platform: "windows".into(),
             search_steam: true,
             search_epic: false,
@@ -173,6 +315,8 @@ mod tests {
             search_registry: true,
             search_common_dirs: true,
             search_processes: false,
+            deep_scan: false,
+            deep_scan_max_depth: 4,
         };
         let s = serde_json::to_string(&opts).expect("serialize ScanOptions");
         let d: ScanOptions = serde_json::from_str(&s).expect("deserialize ScanOptions");
@@ -183,6 +327,38 @@ mod tests {
         assert!(!d.search_epic);
         assert!(!d.search_origin);
         assert!(!d.search_processes);
+        assert_eq!(d.version, SCAN_OPTIONS_VERSION);
+    }
+
+    /// 测试：缺少版本与新增字段的旧副本仍可读取
+    #[test]
+    fn old_scan_options_deserialize_with_defaults() {
+        let old = r#"{"platform":"linux","search_steam":true,"search_epic":false,"search_origin":false,"search_registry":false,"search_common_dirs":true,"search_processes":false}"#;
+        let d: ScanOptions = serde_json::from_str(old).expect("deserialize old ScanOptions");
+        assert_eq!(d.version, 1);
+        assert!(!d.deep_scan);
+        assert_eq!(d.deep_scan_max_depth, 4);
+    }
+
+    /// 测试：默认选项属于当前平台，且只在 Windows 开启 Windows 专属来源
+    #[test]
+    fn platform_defaults_match_current_os() {
+        let d = ScanOptions::platform_defaults();
+        assert!(d.matches_current_platform());
+        assert_eq!(d.search_registry, cfg!(target_os = "windows"));
+        assert_eq!(d.search_epic, cfg!(target_os = "windows"));
+        assert!(d.search_steam && d.search_common_dirs && !d.search_processes);
+
+        let other = if cfg!(target_os = "windows") {
+            "linux"
+        } else {
+            "windows"
+        };
+        let mismatched = ScanOptions {
+            platform: other.into(),
+            ..d
+        };
+        assert!(!mismatched.matches_current_platform());
     }
 
     /// 测试 GameInfo 的序列化与反序列化是否正确
@@ -232,4 +408,4 @@ mod tests {
         assert!(!d.exists);
         assert!((d.confidence - 0.75).abs() < f32::EPSILON);
     }
-}
\ No newline at end of file
+}
@@ -1,19 +1,23 @@
 #![cfg(target_os = "windows")]
 
-use anyhow::{Context, Result};
+use anyhow::Result;
+use log::{info, warn};
 use std::path::{Path, PathBuf};
 use std::{env, fs};
-use log::{info, warn};
 
-use super::types::{DetectedGame, GameInfo, SaveMatchResult, ScanOptions};
 use super::types::DetectionSource;
-use crate::game_scan::resolver::{default_env, resolve_save_rule};
-use crate::backup::{SaveUnit, SaveUnitType};
-use crate::device::get_current_device_id;
-use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
-use winreg::RegKey;
-use regex::Regex;
+use super::types::{
+    DetectedGame, GameInfo, GeneratedSaveUnits, SaveMatchOutcome, SaveMatchResult, SaveUnitOptions,
+    ScanOptions,
+};
+use crate::game_scan::deep_scan::is_plausible_save_dir;
+use crate::game_scan::ranking::build_save_units;
+use crate::game_scan::resolver::{default_env, resolve_save_rules};
+use crate::game_scan::steam::{find_steam_path, read_steam_library_folders, scan_steam_library};
 use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+use winreg::RegKey;
+use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
 
 /// 在 Windows 平台检测已安装的游戏
 ///
@@ -38,30 +42,40 @@ use serde_json::Value;
 /// - 输入：`ScanOptions` 控制不同来源的扫描开关
 /// - 输出：`DetectedGame` 列表
 /// - 合并策略：优先保留来源更可信的条目（平台特定 > 常见目录），按安装路径进行去重
-pub async fn detect_installed_games(options: &ScanOptions) -> Result<Vec<DetectedGame>> {
+/// - 取消：`cancel` 被触发后跳过剩余来源，返回已检测到的部分结果
+pub async fn detect_installed_games(
+    options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
     let mut detected = Vec::new();
 
     // 优先进行 Steam 深度扫描（注册表 + VDF）
     if options.search_steam {
-        let steam_games = scan_steam_games(options).await?;
+        let steam_games = scan_steam_games(options, cancel).await?;
         detected.extend(steam_games);
     }
 
     // Epic（Manifest 解析）
-    if options.search_epic {
-        let epic_games = scan_epic_games(options).await?;
+    if options.search_epic && !cancel.is_cancelled() {
+        let epic_games = scan_epic_games(options, cancel).await?;
         detected.extend(epic_games);
     }
 
     // Origin/EA（installedGames.json / 目录兜底）
-    if options.search_origin {
-        let origin_games = scan_origin_games(options).await?;
+    if options.search_origin && !cancel.is_cancelled() {
+        let origin_games = scan_origin_games(options, cancel).await?;
         detected.extend(origin_games);
     }
 
+    // 注册表卸载项（不经过启动器安装的游戏）
+    if options.search_registry && !cancel.is_cancelled() {
+        let registry_games = scan_registry_uninstall_entries(options, cancel).await?;
+        detected.extend(registry_games);
+    }
+
     // 常见目录兜底扫描（统一标注为 CommonDir）
-    if options.search_common_dirs {
-        let common = scan_common_game_directories(options).await?;
+    if options.search_common_dirs && !cancel.is_cancelled() {
+        let common = scan_common_game_directories(options, cancel).await?;
         detected.extend(common);
     }
 
@@ -75,12 +89,16 @@ pub async fn detect_installed_games(options: &ScanOptions) -> Result<Vec<Detecte
 /// - 当前覆盖：Steam/Epic/Origin/GOG/Ubisoft 的常见安装根目录
 /// - 检测策略：枚举一级子目录，作为安装目录候选；来源标注为 `CommonDir`
 /// - 返回：尽可能多的候选列表，后续由去重逻辑与规则匹配进一步筛选
-pub async fn scan_common_game_directories(_options: &ScanOptions) -> Result<Vec<DetectedGame>> {
+pub async fn scan_common_game_directories(
+    _options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
     let mut detected = Vec::new();
 
     // 读取 Program Files 根路径（支持覆盖）
     let pf = env::var("PROGRAMFILES").unwrap_or_else(|_| String::from("C\\\\Program Files"));
-    let pfx86 = env::var("PROGRAMFILES(X86)").unwrap_or_else(|_| String::from("C\\\\Program Files (x86)"));
+    let pfx86 =
+        env::var("PROGRAMFILES(X86)").unwrap_or_else(|_| String::from("C\\\\Program Files (x86)"));
 
     // 常见目录集合
     let candidates: Vec<PathBuf> = vec![
@@ -103,6 +121,9 @@ pub async fn scan_common_game_directories(_options: &ScanOptions) -> Result<Vec<
 
     // 遍历一级子目录作为候选游戏安装目录
     for root in candidates.into_iter() {
+        if cancel.is_cancelled() {
+            break;
+        }
         if let Ok(rd) = fs::read_dir(&root) {
             for entry in rd.flatten() {
                 let path = entry.path();
@@ -119,6 +140,10 @@ pub async fn scan_common_game_directories(_options: &ScanOptions) -> Result<Vec<
                             info,
                             install_path: Some(path),
                             source: DetectionSource::CommonDir,
+                            platform_id: None,
+                            pcgw_score: None,
+                            ambiguous_with: None,
+                            diff: None,
                         });
                     }
                 }
@@ -129,97 +154,6 @@ pub async fn scan_common_game_directories(_options: &ScanOptions) -> Result<Vec<
     Ok(detected)
 }
 
-/// 从注册表与环境变量解析 Steam 安装路径
-///
-/// - 优先读取 `HKCU\Software\Valve\Steam` 的 `SteamPath`
-/// - 若失败，尝试 `HKLM\Software\WOW6432Node\Valve\Steam` 的 `InstallPath`/`SteamPath`
-/// - 提供环境变量 `RGSM_STEAM_PATH_OVERRIDE` 作为覆盖，便于测试与异常场景
-fn get_steam_path_from_registry() -> Result<PathBuf> {
-    // 环境变量覆盖（用于测试或自定义路径）
-    if let Ok(override_path) = env::var("RGSM_STEAM_PATH_OVERRIDE") {
-        let p = PathBuf::from(override_path);
-        if p.exists() {
-            return Ok(p);
-        }
-    }
-
-    // HKCU
-    let hcu = RegKey::predef(HKEY_CURRENT_USER);
-    if let Ok(key) = hcu.open_subkey("Software\\Valve\\Steam") {
-        if let Ok(val) = key.get_value::<String, _>("SteamPath") {
-            let p = PathBuf::from(val);
-            if p.exists() { return Ok(p); }
-        }
-        if let Ok(val) = key.get_value::<String, _>("InstallPath") {
-            let p = PathBuf::from(val);
-            if p.exists() { return Ok(p); }
-        }
-    }
-
-    // HKLM - WOW6432Node（32 位路径）
-    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-    if let Ok(key) = hklm.open_subkey("Software\\WOW6432Node\\Valve\\Steam") {
-        if let Ok(val) = key.get_value::<String, _>("SteamPath") {
-            let p = PathBuf::from(val);
-            if p.exists() { return Ok(p); }
-        }
-        if let Ok(val) = key.get_value::<String, _>("InstallPath") {
-            let p = PathBuf::from(val);
-            if p.exists() { return Ok(p); }
-        }
-    }
-
-    // 兜底：常见默认位置
-    let pf = env::var("PROGRAMFILES").unwrap_or_else(|_| String::from("C\\\\Program Files"));
-    let pfx86 = env::var("PROGRAMFILES(X86)").unwrap_or_else(|_| String::from("C\\\\Program Files (x86)"));
-    let candidates = [
-        PathBuf::from(format!("{}\\Steam", pf)),
-        PathBuf::from(format!("{}\\Steam", pfx86)),
-    ];
-    for c in candidates { if c.exists() { return Ok(c); } }
-
-    Err(anyhow::anyhow!("Steam path not found via registry or defaults"))
-}
-
-/// 解析 Steam 库文件 `libraryfolders.vdf` 并返回所有库路径
-///
-/// - 文件位置：`<steam_path>/steamapps/libraryfolders.vdf`
-/// - 解析策略：容错地提取所有 `"path"` 字段的值
-fn read_steam_library_folders(steam_path: &Path) -> Result<Vec<PathBuf>> {
-    let vdf_path = steam_path.join("steamapps").join("libraryfolders.vdf");
-    let content = fs::read_to_string(&vdf_path)
-        .with_context(|| format!("Failed to read libraryfolders.vdf: {}", vdf_path.display()))?;
-    let paths = parse_libraryfolders_vdf(&content);
-    let mut out = Vec::new();
-    for p in paths {
-        let pb = PathBuf::from(p);
-        if pb.exists() { out.push(pb); }
-    }
-    // 将主库也加入（SteamPath）
-    if steam_path.exists() { out.push(steam_path.to_path_buf()); }
-    Ok(out)
-}
-
-/// 简易解析 `libraryfolders.vdf` 内容，收集所有 `path` 值
-///
-/// - 适配新版/旧版 KeyValues 格式，尽可能宽松地匹配
-/// - 返回原始字符串路径列表（不判断存在性）
-fn parse_libraryfolders_vdf(content: &str) -> Vec<String> {
-    let mut paths = Vec::new();
-    let re = Regex::new(r#"path"\s*"([^"]+)"#).unwrap();
-    for cap in re.captures_iter(content) {
-        if let Some(m) = cap.get(1) {
-            let raw = m.as_str().trim();
-            if !raw.is_empty() {
-                // 规范化双反斜杠为单反斜杠，便于后续 Path 处理
-                let normalized = raw.replace("\\\\", "\\");
-                paths.push(normalized);
-            }
-        }
-    }
-    paths
-}
-
 /// 对检测到的游戏结果进行去重
 ///
 /// - 主键：`install_path` 字符串（小写规范化）
@@ -262,16 +196,17 @@ fn dedup_detected(items: Vec<DetectedGame>) -> Vec<DetectedGame> {
 /// 扫描 Steam 库目录中的已安装游戏
 ///
 /// - 解析库列表后，遍历 `<library>/steamapps/common` 子目录，将每个子目录视为一个候选游戏
+/// - 名称与 appid 取自库中的 `appmanifest_<appid>.acf`，没有清单时使用目录名
 /// - 将来源标注为 `DetectionSource::Steam`
-pub async fn scan_steam_games(_options: &ScanOptions) -> Result<Vec<DetectedGame>> {
+pub async fn scan_steam_games(
+    _options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
     let mut detected = Vec::new();
 
-    let steam_path = match get_steam_path_from_registry() {
-        Ok(p) => p,
-        Err(e) => {
-            warn!(target:"rgsm::game_scan::windows", "Steam path not found: {e}");
-            return Ok(detected); // 无法定位 Steam，返回空集合
-        }
+    let Some(steam_path) = find_steam_path() else {
+        warn!(target:"rgsm::game_scan::windows", "Steam path not found");
+        return Ok(detected); // 无法定位 Steam，返回空集合
     };
 
     info!(target:"rgsm::game_scan::windows", "Steam path: {}", steam_path.display());
@@ -285,28 +220,10 @@ pub async fn scan_steam_games(_options: &ScanOptions) -> Result<Vec<DetectedGame
     };
 
     for lib in libraries {
-        let common_dir = lib.join("steamapps").join("common");
-        if let Ok(rd) = fs::read_dir(&common_dir) {
-            for entry in rd.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                        let info = GameInfo {
-                            name: name.to_string(),
-                            aliases: Vec::new(),
-                            pcgw_id: None,
-                            install_rules: Vec::new(),
-                            save_rules: Vec::new(),
-                        };
-                        detected.push(DetectedGame {
-                            info,
-                            install_path: Some(path),
-                            source: DetectionSource::Steam,
-                        });
-                    }
-                }
-            }
+        if cancel.is_cancelled() {
+            break;
         }
+        detected.extend(scan_steam_library(&lib, &steam_path));
     }
 
     Ok(detected)
@@ -320,11 +237,15 @@ pub async fn scan_steam_games(_options: &ScanOptions) -> Result<Vec<DetectedGame
 fn program_data_root() -> PathBuf {
     if let Ok(override_path) = env::var("RGSM_PROGRAMDATA_OVERRIDE") {
         let p = PathBuf::from(override_path);
-        if p.exists() { return p; }
+        if p.exists() {
+            return p;
+        }
     }
     if let Ok(pd) = env::var("PROGRAMDATA") {
         let p = PathBuf::from(pd);
-        if p.exists() { return p; }
+        if p.exists() {
+            return p;
+        }
     }
     PathBuf::from("C\\ProgramData")
 }
@@ -336,42 +257,64 @@ fn program_data_root() -> PathBuf {
 fn parse_epic_manifest_file(path: &Path) -> Option<(String, PathBuf)> {
     let content = fs::read_to_string(path).ok()?;
     let v: Value = serde_json::from_str(&content).ok()?;
-    let name = v.get("DisplayName")
+    let name = v
+        .get("DisplayName")
         .and_then(|x| x.as_str())
         .map(|s| s.to_string())
-        .or_else(|| v.get("AppName").and_then(|x| x.as_str()).map(|s| s.to_string()))?;
-    let install_str = v.get("InstallLocation")
+        .or_else(|| {
+            v.get("AppName")
+                .and_then(|x| x.as_str())
+                .map(|s| s.to_string())
+        })?;
+    let install_str = v
+        .get("InstallLocation")
         .and_then(|x| x.as_str())
         .or_else(|| v.get("installLocation").and_then(|x| x.as_str()))?;
     let install_path = PathBuf::from(install_str);
-    if install_path.exists() { Some((name, install_path)) } else { None }
+    if install_path.exists() {
+        Some((name, install_path))
+    } else {
+        None
+    }
 }
 
 /// 扫描 Epic 已安装游戏（通过 ProgramData Manifests）
 ///
 /// - 读取 Manifests 目录中 `.item`/`.manifest` 文件，解析安装路径
 /// - 为每个有效条目创建 `DetectedGame`，来源标注为 `Epic`
-pub async fn scan_epic_games(_options: &ScanOptions) -> Result<Vec<DetectedGame>> {
+pub async fn scan_epic_games(
+    _options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
     let mut detected = Vec::new();
     let pd = program_data_root();
 
     let candidates = [
-        pd.join("Epic").join("EpicGamesLauncher").join("Data").join("Manifests"),
+        pd.join("Epic")
+            .join("EpicGamesLauncher")
+            .join("Data")
+            .join("Manifests"),
         pd.join("EpicGamesLauncher").join("Data").join("Manifests"),
     ];
 
     let mut seen_paths = std::collections::HashSet::new();
 
     for dir in candidates.iter() {
+        if cancel.is_cancelled() {
+            break;
+        }
         if let Ok(rd) = fs::read_dir(dir) {
             for entry in rd.flatten() {
                 let p = entry.path();
                 // 只处理常见扩展
-                let ext_ok = p.extension()
+                let ext_ok = p
+                    .extension()
                     .and_then(|s| s.to_str())
                     .map(|e| matches!(e.to_ascii_lowercase().as_str(), "item" | "manifest"))
                     .unwrap_or(false);
-                if !ext_ok { continue; }
+                if !ext_ok {
+                    continue;
+                }
 
                 if let Some((name, install_path)) = parse_epic_manifest_file(&p) {
                     // 去重（按安装路径）
@@ -388,6 +331,10 @@ pub async fn scan_epic_games(_options: &ScanOptions) -> Result<Vec<DetectedGame>
                             info,
                             install_path: Some(install_path),
                             source: DetectionSource::Epic,
+                            platform_id: None,
+                            pcgw_score: None,
+                            ambiguous_with: None,
+                            diff: None,
                         });
                     }
                 }
@@ -404,20 +351,32 @@ pub async fn scan_epic_games(_options: &ScanOptions) -> Result<Vec<DetectedGame>
 /// - 解析策略：兼容对象或数组两种结构，优先读取 `displayName` 与 `installLocation`
 fn parse_ea_installed_games_json(file: &Path) -> Vec<(String, PathBuf)> {
     let mut out = Vec::new();
-    let content = match fs::read_to_string(file) { Ok(s) => s, Err(_) => return out };
-    let root: Value = match serde_json::from_str(&content) { Ok(v) => v, Err(_) => return out };
+    let content = match fs::read_to_string(file) {
+        Ok(s) => s,
+        Err(_) => return out,
+    };
+    let root: Value = match serde_json::from_str(&content) {
+        Ok(v) => v,
+        Err(_) => return out,
+    };
 
     fn extract_from_value(v: &Value, out: &mut Vec<(String, PathBuf)>) {
         match v {
             Value::Array(arr) => {
-                for item in arr { extract_from_value(item, out); }
+                for item in arr {
+                    extract_from_value(item, out);
+                }
             }
             Value::Object(map) => {
                 // 常见字段
-                let name = map.get("displayName").and_then(|x| x.as_str())
+                let name = map
+                    .get("displayName")
+                    .and_then(|x| x.as_str())
                     .or_else(|| map.get("productName").and_then(|x| x.as_str()))
                     .or_else(|| map.get("title").and_then(|x| x.as_str()));
-                let install = map.get("installLocation").and_then(|x| x.as_str())
+                let install = map
+                    .get("installLocation")
+                    .and_then(|x| x.as_str())
                     .or_else(|| map.get("installationPath").and_then(|x| x.as_str()))
                     .or_else(|| map.get("path").and_then(|x| x.as_str()));
                 if let (Some(n), Some(p)) = (name, install) {
@@ -426,7 +385,9 @@ fn parse_ea_installed_games_json(file: &Path) -> Vec<(String, PathBuf)> {
                     return;
                 }
                 // 深度遍历
-                for (_, vv) in map.iter() { extract_from_value(vv, out); }
+                for (_, vv) in map.iter() {
+                    extract_from_value(vv, out);
+                }
             }
             _ => {}
         }
@@ -440,11 +401,17 @@ fn parse_ea_installed_games_json(file: &Path) -> Vec<(String, PathBuf)> {
 ///
 /// - 优先读取 EA Desktop 的 `installedGames.json`
 /// - 若失败，回退枚举 `Origin Games` 目录
-pub async fn scan_origin_games(_options: &ScanOptions) -> Result<Vec<DetectedGame>> {
+pub async fn scan_origin_games(
+    _options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
     let mut detected = Vec::new();
     let pd = program_data_root();
 
-    let ea_json = pd.join("Electronic Arts").join("EA Desktop").join("installedGames.json");
+    let ea_json = pd
+        .join("Electronic Arts")
+        .join("EA Desktop")
+        .join("installedGames.json");
     if ea_json.exists() {
         for (name, install_path) in parse_ea_installed_games_json(&ea_json) {
             let info = GameInfo {
@@ -458,19 +425,26 @@ pub async fn scan_origin_games(_options: &ScanOptions) -> Result<Vec<DetectedGam
                 info,
                 install_path: Some(install_path),
                 source: DetectionSource::Origin,
+                platform_id: None,
+                pcgw_score: None,
+                ambiguous_with: None,
+                diff: None,
             });
         }
     }
 
     // 兜底：枚举常见的 Origin 安装目录
     let pf = env::var("PROGRAMFILES").unwrap_or_else(|_| String::from("C\\Program Files"));
-    let pfx86 = env::var("PROGRAMFILES(X86)")
-        .unwrap_or_else(|_| String::from("C\\Program Files (x86)"));
+    let pfx86 =
+        env::var("PROGRAMFILES(X86)").unwrap_or_else(|_| String::from("C\\Program Files (x86)"));
     let origin_dirs = [
         PathBuf::from(format!("{}\\Origin Games", pf)),
         PathBuf::from(format!("{}\\Origin Games", pfx86)),
     ];
     for d in origin_dirs.iter() {
+        if cancel.is_cancelled() {
+            break;
+        }
         if let Ok(rd) = fs::read_dir(d) {
             for entry in rd.flatten() {
                 let path = entry.path();
@@ -487,6 +461,10 @@ pub async fn scan_origin_games(_options: &ScanOptions) -> Result<Vec<DetectedGam
                             info,
                             install_path: Some(path),
                             source: DetectionSource::Origin,
+                            platform_id: None,
+                            pcgw_score: None,
+                            ambiguous_with: None,
+                            diff: None,
                         });
                     }
                 }
@@ -497,9 +475,211 @@ pub async fn scan_origin_games(_options: &ScanOptions) -> Result<Vec<DetectedGam
     Ok(detected)
 }
 
+/// 注册表卸载项中与游戏识别相关的字段
+#[derive(Debug, Clone, Default)]
+struct UninstallEntry {
+    display_name: String,
+    install_location: Option<String>,
+    publisher: Option<String>,
+    /// `SystemComponent = 1`，系统组件不会出现在“程序和功能”中
+    system_component: bool,
+    /// 存在 `ParentKeyName`，通常为补丁或更新
+    is_update: bool,
+}
+
+/// 卸载项数据源，测试时可注入伪造数据（CI 中无法写入 HKLM）
+trait UninstallSource {
+    fn entries(&self) -> Vec<UninstallEntry>;
+}
+
+/// 读取系统注册表中的卸载项（HKLM 64/32 位视图与 HKCU）
+struct RegistryUninstallSource;
+
+impl UninstallSource for RegistryUninstallSource {
+    fn entries(&self) -> Vec<UninstallEntry> {
+        let uninstall_keys = [
+            (
+                HKEY_LOCAL_MACHINE,
+                "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall",
+            ),
+            (
+                HKEY_LOCAL_MACHINE,
+                "SOFTWARE\\WOW6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall",
+            ),
+            (
+                HKEY_CURRENT_USER,
+                "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall",
+            ),
+        ];
+        let mut entries = Vec::new();
+        for (hive, path) in uninstall_keys {
+            let Ok(root) = RegKey::predef(hive).open_subkey(path) else {
+                continue;
+            };
+            for name in root.enum_keys().flatten() {
+                let Ok(key) = root.open_subkey(&name) else {
+                    continue;
+                };
+                let Ok(display_name) = key.get_value::<String, _>("DisplayName") else {
+                    continue;
+                };
+                entries.push(UninstallEntry {
+                    display_name,
+                    install_location: key.get_value::<String, _>("InstallLocation").ok(),
+                    publisher: key.get_value::<String, _>("Publisher").ok(),
+                    system_component: key
+                        .get_value::<u32, _>("SystemComponent")
+                        .is_ok_and(|v| v == 1),
+                    is_update: key.get_value::<String, _>("ParentKeyName").is_ok(),
+                });
+            }
+        }
+        entries
+    }
+}
+
+/// 名称中包含这些片段的卸载项不是游戏（驱动、运行库、补丁等），匹配时忽略大小写
+const NON_GAME_NAME_PATTERNS: &[&str] = &[
+    "driver",
+    "redistributable",
+    "runtime",
+    "visual c++",
+    ".net",
+    "directx",
+    "physx",
+    "vulkan",
+    "sdk",
+    "hotfix",
+    "update for",
+    "language pack",
+    "webview2",
+    "chipset",
+    "uninstall",
+];
+
+/// 与这些名称完全相同的卸载项是启动器本身而不是游戏
+const LAUNCHER_NAMES: &[&str] = &[
+    "steam",
+    "epic games launcher",
+    "ea app",
+    "origin",
+    "ubisoft connect",
+    "gog galaxy",
+    "battle.net",
+    "itch",
+    "rockstar games launcher",
+];
+
+/// 这些发行商只发布驱动、运行库或常用软件
+const NON_GAME_PUBLISHERS: &[&str] = &[
+    "microsoft corporation",
+    "nvidia corporation",
+    "intel corporation",
+    "intel(r) corporation",
+    "advanced micro devices, inc.",
+    "realtek semiconductor corp.",
+    "oracle corporation",
+    "python software foundation",
+    "google llc",
+    "adobe inc.",
+    "mozilla",
+];
+
+/// 判断卸载项是否可能为游戏
+fn is_probably_game(entry: &UninstallEntry) -> bool {
+    if entry.system_component || entry.is_update {
+        return false;
+    }
+    let name = entry.display_name.trim().to_lowercase();
+    if name.is_empty()
+        || LAUNCHER_NAMES.contains(&name.as_str())
+        || NON_GAME_NAME_PATTERNS.iter().any(|p| name.contains(p))
+    {
+        return false;
+    }
+    let publisher = entry
+        .publisher
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    !NON_GAME_PUBLISHERS.contains(&publisher.as_str())
+}
+
+/// 将卸载项转换为检测结果，只保留安装目录存在的条目
+fn detect_from_uninstall_entries(
+    source: &dyn UninstallSource,
+    cancel: &CancellationToken,
+) -> Vec<DetectedGame> {
+    let mut detected = Vec::new();
+    for entry in source.entries() {
+        if cancel.is_cancelled() {
+            break;
+        }
+        if !is_probably_game(&entry) {
+            continue;
+        }
+        let Some(location) = entry
+            .install_location
+            .as_deref()
+            .map(|l| l.trim().trim_matches('"'))
+        else {
+            continue;
+        };
+        if location.is_empty() {
+            continue;
+        }
+        let install_path = PathBuf::from(location);
+        if !install_path.is_dir() {
+            continue;
+        }
+        // 安装目录名常与 PCGW 名称更接近（显示名可能带版本号），作为别名参与匹配
+        let name = entry.display_name.trim().to_string();
+        let aliases = install_path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .filter(|dir| !dir.eq_ignore_ascii_case(&name))
+            .map(|dir| vec![dir.to_string()])
+            .unwrap_or_default();
+        detected.push(DetectedGame {
+            info: GameInfo {
+                name,
+                aliases,
+                pcgw_id: None,
+                install_rules: Vec::new(),
+                save_rules: Vec::new(),
+            },
+            install_path: Some(install_path),
+            source: DetectionSource::Registry,
+            platform_id: None,
+            pcgw_score: None,
+            ambiguous_with: None,
+            diff: None,
+        });
+    }
+    detected
+}
+
+/// 扫描注册表卸载项中的游戏
+///
+/// - 覆盖不经过启动器安装的游戏（GOG 离线安装包、itch、大量日本游戏等），它们会在
+///   `HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\*` 下登记 `DisplayName` 与 `InstallLocation`
+/// - 按名称、发行商的黑名单过滤驱动、运行库与启动器本身，来源标注为 `Registry`
+/// - 名称后续与其他来源一样经过 PCGW 索引丰富
+pub async fn scan_registry_uninstall_entries(
+    _options: &ScanOptions,
+    cancel: &CancellationToken,
+) -> Result<Vec<DetectedGame>> {
+    let detected = detect_from_uninstall_entries(&RegistryUninstallSource, cancel);
+    info!(target:"rgsm::game_scan", "Registry uninstall entries yielded {} game candidates", detected.len());
+    Ok(detected)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::device::get_current_device_id;
+    use crate::game_scan::steam::parse_libraryfolders_vdf;
     use crate::game_scan::types;
     use std::fs::create_dir_all;
     use std::io::Write;
@@ -587,6 +767,7 @@ mod tests {
             std::env::set_var("RGSM_STEAM_PATH_OVERRIDE", &steam_path);
         }
         let opts = ScanOptions {
+            version: types::SCAN_OPTIONS_VERSION,
             platform: "windows".into(),
             search_steam: true,
             search_epic: false,
@@ -594,10 +775,14 @@ mod tests {
             search_registry: true,
             search_common_dirs: false,
             search_processes: false,
+            deep_scan: false,
+            deep_scan_max_depth: 4,
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let res = rt.block_on(scan_steam_games(&opts)).unwrap();
+        let res = rt
+            .block_on(scan_steam_games(&opts, &CancellationToken::new()))
+            .unwrap();
         assert!(res.iter().any(|d| d.info.name == "MyTestGame"));
     }
 
@@ -619,7 +804,10 @@ mod tests {
         }
 
         let manifests = pd
-            .join("Epic").join("EpicGamesLauncher").join("Data").join("Manifests");
+            .join("Epic")
+            .join("EpicGamesLauncher")
+            .join("Data")
+            .join("Manifests");
         create_dir_all(&manifests).expect("mkdir manifests");
 
         let install_dir = pd.join("Games").join("MyEpicGame");
@@ -627,17 +815,21 @@ mod tests {
 
         let item_path = manifests.join("mygame.item");
         let install_str = install_dir.display().to_string().replace("\\", "\\\\");
-        let sample = format!(r#"{{
+        let sample = format!(
+            r#"{{
             "DisplayName": "My Epic Game",
             "AppName": "MyEpicGame",
             "InstallLocation": "{}"
-        }}"#, install_str);
+        }}"#,
+            install_str
+        );
         std::fs::write(&item_path, sample).expect("write item");
 
         // 直接验证清单解析是否生效
         assert!(parse_epic_manifest_file(&item_path).is_some());
 
         let opts = ScanOptions {
+            version: types::SCAN_OPTIONS_VERSION,
             platform: "windows".into(),
             search_steam: false,
             search_epic: true,
@@ -645,10 +837,14 @@ mod tests {
             search_registry: false,
             search_common_dirs: false,
             search_processes: false,
+            deep_scan: false,
+            deep_scan_max_depth: 4,
         };
 
         let rt = tokio::runtime::Runtime::new().expect("rt");
-        let res = rt.block_on(scan_epic_games(&opts)).expect("scan epic");
+        let res = rt
+            .block_on(scan_epic_games(&opts, &CancellationToken::new()))
+            .expect("scan epic");
         assert!(!res.is_empty());
         assert_eq!(res[0].source, DetectionSource::Epic);
         assert_eq!(res[0].info.name, "My Epic Game");
@@ -679,12 +875,15 @@ mod tests {
 
         let json = ea_dir.join("installedGames.json");
         let install_str = install_dir.display().to_string().replace("\\", "\\\\");
-        let sample = format!(r#"[
+        let sample = format!(
+            r#"[
             {{
                 "displayName": "My EA Game",
                 "installLocation": "{}"
             }}
-        ]"#, install_str);
+        ]"#,
+            install_str
+        );
         std::fs::write(&json, sample).expect("write json");
 
         // 直接验证 JSON 解析是否生效
@@ -692,6 +891,7 @@ mod tests {
         assert!(!parsed.is_empty());
 
         let opts = ScanOptions {
+            version: types::SCAN_OPTIONS_VERSION,
             platform: "windows".into(),
             search_steam: false,
             search_epic: false,
@@ -699,10 +899,14 @@ mod tests {
             search_registry: false,
             search_common_dirs: false,
             search_processes: false,
+            deep_scan: false,
+            deep_scan_max_depth: 4,
         };
 
         let rt = tokio::runtime::Runtime::new().expect("rt");
-        let res = rt.block_on(scan_origin_games(&opts)).expect("scan origin");
+        let res = rt
+            .block_on(scan_origin_games(&opts, &CancellationToken::new()))
+            .expect("scan origin");
         assert!(!res.is_empty());
         assert_eq!(res[0].source, DetectionSource::Origin);
         assert_eq!(res[0].info.name, "My EA Game");
@@ -713,7 +917,13 @@ mod tests {
     #[test]
     fn test_scan_common_dirs_with_override() {
         let _guard = TEST_ENV_LOCK.lock().unwrap();
-        let base = std::env::temp_dir().join(format!("rgsm_pf_common_{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis()));
+        let base = std::env::temp_dir().join(format!(
+            "rgsm_pf_common_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+        ));
         create_dir_all(&base).expect("mkdir base");
         let pf_str = base.to_string_lossy().to_string();
         unsafe {
@@ -726,6 +936,7 @@ mod tests {
         create_dir_all(&my_game).expect("mkdir gog game");
 
         let opts = ScanOptions {
+            version: types::SCAN_OPTIONS_VERSION,
             platform: "windows".into(),
             search_steam: false,
             search_epic: false,
@@ -733,13 +944,87 @@ mod tests {
             search_registry: false,
             search_common_dirs: true,
             search_processes: false,
+            deep_scan: false,
+            deep_scan_max_depth: 4,
         };
 
         let rt = tokio::runtime::Runtime::new().expect("rt");
         let res = rt
-            .block_on(super::scan_common_game_directories(&opts))
+            .block_on(super::scan_common_game_directories(
+                &opts,
+                &CancellationToken::new(),
+            ))
             .expect("scan common");
-        assert!(res.iter().any(|d| d.source == DetectionSource::CommonDir && d.info.name == "MyCommonGame"));
+        assert!(
+            res.iter()
+                .any(|d| d.source == DetectionSource::CommonDir && d.info.name == "MyCommonGame")
+        );
+    }
+
+    /// 测试用的卸载项数据源
+    struct FakeUninstallSource(Vec<UninstallEntry>);
+
+    impl UninstallSource for FakeUninstallSource {
+        fn entries(&self) -> Vec<UninstallEntry> {
+            self.0.clone()
+        }
+    }
+
+    fn uninstall_entry(name: &str, location: Option<&Path>, publisher: &str) -> UninstallEntry {
+        UninstallEntry {
+            display_name: name.into(),
+            install_location: location.map(|p| p.to_string_lossy().to_string()),
+            publisher: Some(publisher.into()),
+            ..Default::default()
+        }
+    }
+
+    /// 测试：注册表卸载项只保留安装目录存在的游戏，过滤驱动、运行库、启动器与系统组件
+    #[test]
+    fn test_detect_from_uninstall_entries() {
+        let base = temp_dir::TempDir::new().unwrap();
+        let game_dir = base.path().join("Touhou18");
+        create_dir_all(&game_dir).unwrap();
+        let other_dir = base.path().join("Other");
+        create_dir_all(&other_dir).unwrap();
+
+        let mut system = uninstall_entry("Hidden Component", Some(&other_dir), "Someone");
+        system.system_component = true;
+        let mut patch = uninstall_entry("Some Game Patch 1.1", Some(&other_dir), "Someone");
+        patch.is_update = true;
+        let source = FakeUninstallSource(vec![
+            uninstall_entry(
+                "東方虹龍洞 ～ Unconnected Marketeers",
+                Some(&game_dir),
+                "上海アリス幻樂団",
+            ),
+            uninstall_entry(
+                "NVIDIA Graphics Driver 551.23",
+                Some(&other_dir),
+                "NVIDIA Corporation",
+            ),
+            uninstall_entry(
+                "Microsoft Visual C++ 2015-2022 Redistributable (x64)",
+                Some(&other_dir),
+                "Microsoft Corporation",
+            ),
+            uninstall_entry("Steam", Some(&other_dir), "Valve Corporation"),
+            uninstall_entry(
+                "Removed Game",
+                Some(&base.path().join("Missing")),
+                "Someone",
+            ),
+            uninstall_entry("No Location Game", None, "Someone"),
+            system,
+            patch,
+        ]);
+
+        let res = detect_from_uninstall_entries(&source, &CancellationToken::new());
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].source, DetectionSource::Registry);
+        assert_eq!(res[0].info.name, "東方虹龍洞 ～ Unconnected Marketeers");
+        assert_eq!(res[0].info.aliases, vec!["Touhou18".to_string()]);
+        assert_eq!(res[0].install_path.as_deref(), Some(game_dir.as_path()));
     }
 
     /// 验证 SaveUnit 生成逻辑（基于存在路径与当前设备映射）
@@ -774,13 +1059,21 @@ mod tests {
 
         let rt = tokio::runtime::Runtime::new().expect("rt");
         let units = rt
-            .block_on(super::generate_save_units(&game, &base))
-            .expect("generate units");
+            .block_on(super::generate_save_units(
+                &game,
+                &base,
+                &SaveUnitOptions::default(),
+            ))
+            .expect("generate units")
+            .units;
 
         assert!(!units.is_empty(), "should generate at least one save unit");
         let device_id = get_current_device_id().clone();
         let has_mapping = units.iter().any(|u| u.paths.get(&device_id).is_some());
-        assert!(has_mapping, "save unit should contain path mapping for current device");
+        assert!(
+            has_mapping,
+            "save unit should contain path mapping for current device"
+        );
     }
 }
 
@@ -793,11 +1086,12 @@ mod tests {
 /// 按规则匹配 Windows 平台的存档路径（基础版）
 ///
 /// - 输入：`GameInfo` 与安装路径
-/// - 输出：`SaveMatchResult` 列表，包含路径存在性与可信度
+/// - 输出：`SaveMatchOutcome`，包含路径存在性与可信度，以及解析失败被跳过的规则
 pub async fn match_save_paths(
     game: &GameInfo,
     install_path: &Path,
-) -> Result<Vec<SaveMatchResult>> {
+    cancel: &CancellationToken,
+) -> Result<SaveMatchOutcome> {
     // 针对指定游戏匹配存档路径（Windows）
     // - 基于 PCGW 规则解析 `<...>` 与环境变量，生成候选路径
     // - 额外为特殊游戏提供兜底匹配（如 Black Myth: Wukong 存档在安装目录下）
@@ -807,19 +1101,34 @@ pub async fn match_save_paths(
 
     let mut results = Vec::new();
 
-    // 遍历规则，解析模板并进行存在性校验
-    for rule in &game.save_rules {
-        let paths = resolve_save_rule(rule, &env)?;
-        for p in paths {
-            let exists = p.exists();
-            let confidence = if exists { rule.confidence.min(1.0) } else { rule.confidence * 0.5 };
-            results.push(SaveMatchResult {
-                rule_id: rule.id.clone(),
-                resolved_path: p,
-                exists,
-                confidence,
-            });
-        }
+    // 遍历规则，解析模板并进行存在性校验；单条规则解析失败时跳过，不影响其余规则
+    // 每条规则之前检查取消，扫描被取消后不再解析剩余规则
+    let (resolved, skipped_rules) = resolve_save_rules(
+        game.save_rules
+            .iter()
+            .take_while(|_| !cancel.is_cancelled()),
+        &env,
+    );
+    for (rule, p) in resolved {
+        let exists = p.exists();
+        let confidence = if exists {
+            rule.confidence.min(1.0)
+        } else {
+            rule.confidence * 0.5
+        };
+        results.push(SaveMatchResult {
+            rule_id: rule.id.clone(),
+            resolved_path: p,
+            exists,
+            confidence,
+        });
+    }
+
+    if cancel.is_cancelled() {
+        return Ok(SaveMatchOutcome {
+            matches: results,
+            skipped_rules,
+        });
     }
 
     // 预留：可利用安装路径提升匹配质量（如通过占位符替换）
@@ -830,7 +1139,10 @@ pub async fn match_save_paths(
     // 若存在 .sav 文件的子目录，则返回该子目录；否则返回 SaveGames 目录本身。
     let normalized = |s: &str| s.to_ascii_lowercase().replace([' ', ':', '_'], "");
     let is_bmw = normalized(&game.name).contains("blackmythwukong")
-        || game.aliases.iter().any(|a| normalized(a).contains("blackmythwukong"));
+        || game
+            .aliases
+            .iter()
+            .any(|a| normalized(a).contains("blackmythwukong"));
     if is_bmw {
         let base = install_path.join("b1").join("Saved").join("SaveGames");
         if base.is_dir() {
@@ -877,27 +1189,35 @@ pub async fn match_save_paths(
         });
     }
 
-    Ok(results)
+    Ok(SaveMatchOutcome {
+        matches: results,
+        skipped_rules,
+    })
 }
 
 /// 生成 SaveUnit（含设备路径映射）
 ///
-/// - 输入：`GameInfo` 与安装路径，用于路径解析与存在性检查
-/// - 输出：`SaveUnit` 列表，仅包含存在的路径，并映射到当前设备 ID
+/// - 输入：`GameInfo` 与安装路径，用于路径解析与存在性检查；`options` 控制候选的取舍
+/// - 输出：采用的 `SaveUnit`（仅包含存在的路径，并映射到当前设备 ID）与未采用的其他候选
 pub async fn generate_save_units(
     game: &GameInfo,
     install_path: &Path,
-) -> Result<Vec<SaveUnit>> {
-    let matches = match_save_paths(game, install_path).await?;
-    let device_id = get_current_device_id().clone();
+    options: &SaveUnitOptions,
+) -> Result<GeneratedSaveUnits> {
+    let matches = match_save_paths(game, install_path, &CancellationToken::new())
+        .await?
+        .matches;
 
     // 去重并优先保留更“像存档”的路径（含典型扩展或命名）
-    let mut units = Vec::new();
     let mut best_by_path: std::collections::HashMap<String, (f32, SaveMatchResult)> =
         std::collections::HashMap::new();
     for m in matches.into_iter().filter(|m| m.exists) {
         let key = m.resolved_path.to_string_lossy().to_string();
-        let score_bonus = if is_plausible_save_dir(&m.resolved_path) { 0.1 } else { 0.0 };
+        let score_bonus = if is_plausible_save_dir(&m.resolved_path) {
+            0.1
+        } else {
+            0.0
+        };
         let score = m.confidence + score_bonus;
         match best_by_path.get(&key) {
             Some((prev, _)) if *prev >= score => {}
@@ -907,61 +1227,10 @@ pub async fn generate_save_units(
         }
     }
 
-    for (_, (_, m)) in best_by_path.into_iter() {
-        let unit_type = if m.resolved_path.is_file() {
-            SaveUnitType::File
-        } else {
-            SaveUnitType::Folder
-        };
-        let mut paths = std::collections::HashMap::new();
-        paths.insert(device_id.clone(), m.resolved_path.to_string_lossy().to_string());
-        units.push(SaveUnit { unit_type, paths, delete_before_apply: false });
-    }
-
-    Ok(units)
-}
-
-/// 判断目录是否“像”存档目录
-///
-/// - 规则：包含常见扩展的文件（如 `.sav`, `.save`, `.slot`, `.dat`）或名称包含 `save` 的子目录
-/// - 目的：提高候选路径质量评分，减少错误目录被加入配置
-fn is_plausible_save_dir(path: &Path) -> bool {
-    if path.is_file() {
-        return path.extension()
-            .and_then(|e| e.to_str())
-            .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "sav" | "save" | "slot" | "dat"))
-            .unwrap_or(false);
-    }
-
-    if !path.is_dir() {
-        return false;
-    }
-
-    let mut has_save_file = false;
-    let mut has_save_named_dir = false;
-    if let Ok(rd) = std::fs::read_dir(path) {
-        for entry in rd.flatten() {
-            let p = entry.path();
-            if p.is_file() {
-                if p.extension()
-                    .and_then(|e| e.to_str())
-                    .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "sav" | "save" | "slot" | "dat"))
-                    .unwrap_or(false) {
-                    has_save_file = true;
-                }
-            } else if p.is_dir() {
-                if p.file_name()
-                    .and_then(|s| s.to_str())
-                    .map(|n| n.to_ascii_lowercase().contains("save"))
-                    .unwrap_or(false) {
-                    has_save_named_dir = true;
-                }
-            }
-            if has_save_file || has_save_named_dir { break; }
-        }
-    }
-
-    has_save_file || has_save_named_dir
+    Ok(build_save_units(
+        best_by_path.into_values().collect(),
+        options,
+    ))
 }
 
 /// 在常见用户目录中按游戏名/别名匹配潜在的存档根目录
@@ -994,12 +1263,19 @@ fn search_common_save_roots(game: &GameInfo) -> Result<Vec<PathBuf>> {
         .collect();
 
     for root in roots {
-        if !root.is_dir() { continue; }
+        if !root.is_dir() {
+            continue;
+        }
         if let Ok(rd) = std::fs::read_dir(&root) {
             for entry in rd.flatten() {
                 let p = entry.path();
-                if !p.is_dir() { continue; }
-                let name = p.file_name().and_then(|s| s.to_str()).map(|s| s.to_ascii_lowercase());
+                if !p.is_dir() {
+                    continue;
+                }
+                let name = p
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.to_ascii_lowercase());
 
                 // 1) 直接匹配：目录名包含游戏名/别名
                 let mut matched_dirs: Vec<PathBuf> = Vec::new();
@@ -1015,8 +1291,13 @@ fn search_common_save_roots(game: &GameInfo) -> Result<Vec<PathBuf>> {
                     if let Ok(sub) = std::fs::read_dir(&p) {
                         for s in sub.flatten() {
                             let q = s.path();
-                            if !q.is_dir() { continue; }
-                            let qn = q.file_name().and_then(|x| x.to_str()).map(|x| x.to_ascii_lowercase());
+                            if !q.is_dir() {
+                                continue;
+                            }
+                            let qn = q
+                                .file_name()
+                                .and_then(|x| x.to_str())
+                                .map(|x| x.to_ascii_lowercase());
                             if let Some(qn) = qn {
                                 let qnorm = qn.replace([' ', ':', '_'], "");
                                 if tokens.iter().any(|t| qnorm.contains(t)) {
@@ -1043,7 +1324,9 @@ fn search_common_save_roots(game: &GameInfo) -> Result<Vec<PathBuf>> {
                             }
                         }
                     }
-                    if let Some(c) = candidate { candidates.push(c); }
+                    if let Some(c) = candidate {
+                        candidates.push(c);
+                    }
                 }
             }
         }
@@ -1,19 +1,20 @@
-use crate::backup::{Game, GameSnapshots};
-use crate::cloud_sync::{self, Backend, upload_all};
-use crate::config::{Config, QuickActionSoundPreferences, get_config};
+use crate::backup::{BatchSummary, Game, GameSnapshots};
+use crate::cloud_sync::{self, SyncSummary, upload_all};
+use crate::config::{Config, QuickActionOverride, QuickActionSoundPreferences, get_config};
 use crate::device::{Device, get_current_device_id};
 use crate::path_resolver;
 use crate::preclude::*;
-use crate::{backup, config, quick_actions, sound};
+use crate::{backup, config, device, launcher, quick_actions, save_watcher, sound, updater};
 
 use anyhow::Result;
 use log::{debug, error, info, warn};
 use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 use tauri::{AppHandle, Emitter, Manager, Window};
-use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
 use tauri_specta::Event;
 
 #[allow(non_camel_case_types)]
@@ -40,6 +41,17 @@ pub async fn open_url(url: String) -> Result<(), String> {
     })
 }
 
+/// 检查 GitHub 上是否有新版本，结果会缓存数小时，网络错误放在返回值的 error 中
+#[tauri::command]
+#[specta::specta]
+pub async fn check_for_app_update(app: AppHandle) -> Result<updater::AppUpdateInfo, IpcError> {
+    info!(target:"rgsm::ipc", "Checking for app update");
+    updater::check_for_app_update(&app).await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to check for app update: {:?}", e);
+        IpcError::from(e)
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn open_file_or_folder(path: String) -> Result<(), String> {
@@ -60,62 +72,376 @@ pub async fn open_file_or_folder(path: String) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn choose_save_file(app: AppHandle) -> Result<String, String> {
+pub async fn launch_game(game: Game, app: AppHandle) -> Result<launcher::LaunchResult, String> {
+    info!(target:"rgsm::ipc", "Launching game: {}", game.name);
+    launcher::launch_game(&app, &game).await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to launch game: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// 选择文件或文件夹的对话框选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct PickerOptions {
+    /// 对话框打开的目录，可以包含 `<winAppData>` 等变量，不存在时使用最近的上级目录。
+    /// 为空时使用本次运行中上次选择的目录
+    pub start_dir: Option<String>,
+    /// 只显示这些扩展名的文件，不含 `.`，为空时显示所有文件。选择文件夹时忽略
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+/// 本次运行中上次在对话框中选择的目录，不会写入配置
+#[derive(Debug, Default)]
+pub struct LastPickedDir(Mutex<Option<PathBuf>>);
+
+fn picker_start_dir(app: &AppHandle, options: &PickerOptions) -> Option<PathBuf> {
+    let requested = options
+        .start_dir
+        .as_deref()
+        .filter(|dir| !dir.trim().is_empty())
+        .and_then(|dir| {
+            let config = get_config().ok()?;
+            path_resolver::resolve_path(dir, None, &config)
+                .inspect_err(|e| {
+                    warn!(target:"rgsm::ipc", "Failed to resolve dialog start dir {}: {:?}", dir, e)
+                })
+                .ok()
+        });
+    let start = requested.or_else(|| {
+        let last = app.state::<LastPickedDir>();
+        let last = last.0.lock().unwrap_or_else(PoisonError::into_inner);
+        last.clone()
+    })?;
+    start
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+fn remember_picked_dir(app: &AppHandle, dir: Option<&Path>) {
+    if let Some(dir) = dir {
+        let last = app.state::<LastPickedDir>();
+        *last.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(dir.to_path_buf());
+    }
+}
+
+fn file_dialog(app: &AppHandle, options: &PickerOptions) -> FileDialogBuilder<tauri::Wry> {
+    let mut dialog = app.dialog().file();
+    if let Some(dir) = picker_start_dir(app, options) {
+        dialog = dialog.set_directory(dir);
+    }
+    let extensions: Vec<&str> = options
+        .extensions
+        .iter()
+        .map(|ext| ext.trim().trim_start_matches('.'))
+        .filter(|ext| !ext.is_empty())
+        .collect();
+    if !extensions.is_empty() {
+        dialog = dialog.add_filter(extensions.join(", "), &extensions);
+    }
+    dialog
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn choose_save_file(
+    app: AppHandle,
+    options: Option<PickerOptions>,
+) -> Result<String, String> {
     info!(target:"rgsm::ipc", "Opening file dialog.");
-    if let Some(path) = app.dialog().file().blocking_pick_file() {
+    let options = options.unwrap_or_default();
+    if let Some(path) = file_dialog(&app, &options).blocking_pick_file() {
         info!(target:"rgsm::ipc","Successfully picked file: {:#?}",path);
+        remember_picked_dir(&app, path.as_path().and_then(Path::parent));
         Ok(path.to_string())
     } else {
         warn!(target:"rgsm::ipc", "Failed to open dialog or user close the dialog.");
-        Err("Failed to open dialog.".to_string())
+        Err(t!("backend.dialog.no_file_selected").to_string())
+    }
+}
+
+/// 一次选择多个文件，用于同时添加多个存档文件
+#[tauri::command]
+#[specta::specta]
+pub async fn choose_save_files(
+    app: AppHandle,
+    options: Option<PickerOptions>,
+) -> Result<Vec<String>, String> {
+    info!(target:"rgsm::ipc", "Opening multi-file dialog.");
+    let options = options.unwrap_or_default();
+    match file_dialog(&app, &options).blocking_pick_files() {
+        Some(paths) if !paths.is_empty() => {
+            info!(target:"rgsm::ipc","Successfully picked files: {:#?}",paths);
+            remember_picked_dir(&app, paths[0].as_path().and_then(Path::parent));
+            Ok(paths.iter().map(ToString::to_string).collect())
+        }
+        _ => {
+            warn!(target:"rgsm::ipc", "Failed to open dialog or user close the dialog.");
+            Err(t!("backend.dialog.no_file_selected").to_string())
+        }
     }
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn choose_save_dir(app: AppHandle) -> Result<String, String> {
+pub async fn choose_save_dir(
+    app: AppHandle,
+    options: Option<PickerOptions>,
+) -> Result<String, String> {
     info!(target:"rgsm::ipc","Opening folder dialog.");
-    if let Some(path) = app.dialog().file().blocking_pick_folder() {
+    let options = options.unwrap_or_default();
+    if let Some(path) = file_dialog(&app, &options).blocking_pick_folder() {
         info!(target:"rgsm::ipc","Successfully picked folder: {:#?}",path);
+        remember_picked_dir(&app, path.as_path());
+        Ok(path.to_string())
+    } else {
+        warn!(target:"rgsm::ipc", "Failed to open dialog or user close the dialog.");
+        Err(t!("backend.dialog.no_folder_selected").to_string())
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn choose_export_path(default_name: String, app: AppHandle) -> Result<String, String> {
+    info!(target:"rgsm::ipc","Opening save dialog.");
+    if let Some(path) = app
+        .dialog()
+        .file()
+        .set_file_name(&default_name)
+        .blocking_save_file()
+    {
+        info!(target:"rgsm::ipc","Successfully picked export path: {:#?}",path);
         Ok(path.to_string())
     } else {
         warn!(target:"rgsm::ipc", "Failed to open dialog or user close the dialog.");
-        Err("Failed to open dialog.".to_string())
+        Err(t!("backend.dialog.no_file_selected").to_string())
     }
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_local_config() -> Result<Config, String> {
+pub async fn get_local_config(app: AppHandle) -> Result<Config, String> {
     info!(target:"rgsm::ipc", "Getting local config.");
-    get_config().map_err(|e| e.to_string())
+    let config = get_config().map_err(|e| e.to_string())?;
+    if config::ConfigManager::global().take_recovered() {
+        let backup = backup_file_path(config::config_path());
+        let _ = app.emit(
+            "Notification",
+            IpcNotification {
+                level: NotificationLevel::warning,
+                title: t!("backend.notification.warning").to_string(),
+                msg: t!(
+                    "backend.config.recovered_from_backup",
+                    path = backup.display()
+                )
+                .to_string(),
+            },
+        );
+    }
+    Ok(config)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn add_game(game: Game) -> Result<(), String> {
+pub async fn add_game(game: Game, app: AppHandle) -> Result<(), String> {
     info!(target:"rgsm::ipc", "Adding game: {:?}", game);
     backup::create_game_backup(&game).await.map_err(|e| {
         error!(target:"rgsm::ipc", "Failed to add game: {:?}", e);
         e.to_string()
     })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
     info!(target:"rgsm::ipc", "Successfully added game: {:?}", game);
     Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn restore_snapshot(game: Game, date: String, app: AppHandle) -> Result<(), String> {
-    //handle_backup_err(game.restore_snapshot(&date,window), )
-    info!(target:"rgsm::ipc", "Applying backup: {:?} for game: {:?}", date, game);
-    game.restore_snapshot(&date, Some(&app)).map_err(|e| {
-        error!(target:"rgsm::ipc", "Failed to apply backup: {:?}", e);
+pub async fn validate_game(game: Game) -> Result<backup::GameValidationReport, String> {
+    info!(target:"rgsm::ipc", "Validating save paths of game: {}", game.name);
+    let config = get_config().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to get config: {:?}", e);
+        e.to_string()
+    })?;
+    Ok(backup::validate_game(&game, &config))
+}
+
+/// Validate every configured game, so the UI can point out stale save paths
+#[tauri::command]
+#[specta::specta]
+pub async fn check_all_games() -> Result<Vec<backup::GameValidationReport>, String> {
+    info!(target:"rgsm::ipc", "Validating save paths of all games");
+    let config = get_config().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to get config: {:?}", e);
         e.to_string()
     })?;
+    let reports: Vec<_> = config
+        .games
+        .iter()
+        .map(|game| backup::validate_game(game, &config))
+        .collect();
+    for report in reports.iter().filter(|r| !r.is_ok()) {
+        warn!(target:"rgsm::ipc", "Game {} has save path issues: {:?}", report.game_name, report.units);
+    }
+    Ok(reports)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn export_game_archive(game: Game, target_path: String) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Exporting game archive: {:?} to {}", game.name, target_path);
+    // Packing the backup folder is blocking I/O, keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        backup::export_game_archive(&game, std::path::Path::new(&target_path))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to export game archive: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn import_game_archive(
+    file_path: String,
+    merge_strategy: Option<backup::ImportMergeStrategy>,
+    app: AppHandle,
+) -> Result<Game, String> {
+    info!(target:"rgsm::ipc", "Importing game archive: {}", file_path);
+    let game = backup::import_game_archive(std::path::Path::new(&file_path), merge_strategy)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to import game archive: {:?}", e);
+            e.to_string()
+        })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
+    Ok(game)
+}
+
+/// Exports the config without cloud credentials, for sharing or bug reports
+#[tauri::command]
+#[specta::specta]
+pub async fn export_sanitized_config(target_path: String) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Exporting sanitized config to {}", target_path);
+    backup::export_sanitized_config(std::path::Path::new(&target_path)).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to export sanitized config: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Imports only the games of an exported config, merged by name or replacing the local ones
+#[tauri::command]
+#[specta::specta]
+pub async fn import_games_from_config(
+    file_path: String,
+    merge: bool,
+    app: AppHandle,
+) -> Result<Vec<Game>, String> {
+    info!(target:"rgsm::ipc", "Importing games from config: {}, merge: {}", file_path, merge);
+    let games = backup::import_games_from_config(std::path::Path::new(&file_path), merge)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to import games from config: {:?}", e);
+            e.to_string()
+        })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
+    Ok(games)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_snapshot(game: Game, date: String, app: AppHandle) -> Result<(), IpcError> {
+    //handle_backup_err(game.restore_snapshot(&date,window), )
+    info!(target:"rgsm::ipc", "Applying backup: {:?} for game: {:?}", date, game);
+    // 恢复期间持有游戏锁，避免存档变动触发的自动备份与恢复同时进行
+    let _guard = app
+        .state::<Arc<quick_actions::QuickActionManager>>()
+        .lock_game(&game.name, config::QuickActionBusyBehavior::Queue)
+        .await;
+    let operations = app.state::<backup::BackupOperations>();
+    let operation = operations.start(&game.name);
+    let mut monitor = backup::ArchiveMonitor::emitting(
+        &app,
+        &game.name,
+        backup::ArchiveOperation::Extract,
+        operation.token(),
+    );
+    game.restore_snapshot_with_monitor(&date, Some(&app), &mut monitor)
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to apply backup: {:?}", e);
+            IpcError::from(e)
+        })?;
     info!(target:"rgsm::ipc", "Successfully applied backup: {:?} for game: {:?}", date, game);
     Ok(())
 }
 
+/// 预览恢复快照会新增、覆盖、删除哪些文件，不会修改任何文件
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_restore(game: Game, date: String) -> Result<backup::RestorePlan, String> {
+    info!(target:"rgsm::ipc", "Previewing restore of {:?} for game: {}", date, game.name);
+    backup::preview_restore(&game, &date).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to preview restore: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// 列出快照中的文件，按存档单元分组，不会解压
+#[tauri::command]
+#[specta::specta]
+pub async fn list_snapshot_contents(
+    game: Game,
+    date: String,
+) -> Result<backup::SnapshotContents, IpcError> {
+    info!(target:"rgsm::ipc", "Listing contents of snapshot {:?} for game: {}", date, game.name);
+    backup::list_snapshot_contents(&game, &date).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to list snapshot contents: {:?}", e);
+        IpcError::from(e)
+    })
+}
+
+/// 比较两个快照中的文件，按存档单元分组，不会解压
+#[tauri::command]
+#[specta::specta]
+pub async fn diff_snapshots(
+    game: Game,
+    date_a: String,
+    date_b: String,
+) -> Result<backup::SnapshotDiff, IpcError> {
+    info!(target:"rgsm::ipc", "Comparing snapshots {:?} and {:?} for game: {}", date_a, date_b, game.name);
+    backup::diff_snapshots(&game, &date_a, &date_b).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to compare snapshots: {:?}", e);
+        IpcError::from(e)
+    })
+}
+
+/// 从快照中恢复单个文件到其原位置，返回恢复后的路径
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_single_file(
+    game: Game,
+    date: String,
+    entry_path: String,
+    overwrite: bool,
+    app: AppHandle,
+) -> Result<String, IpcError> {
+    info!(target:"rgsm::ipc", "Restoring {:?} of snapshot {:?} for game: {}", entry_path, date, game.name);
+    let _guard = app
+        .state::<Arc<quick_actions::QuickActionManager>>()
+        .lock_game(&game.name, config::QuickActionBusyBehavior::Queue)
+        .await;
+    let path = backup::restore_single_file(&game, &date, &entry_path, overwrite).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to restore single file: {:?}", e);
+        IpcError::from(e)
+    })?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn delete_snapshot(game: Game, date: String) -> Result<(), String> {
@@ -128,125 +454,522 @@ pub async fn delete_snapshot(game: Game, date: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 将误记在 `from_game` 下的快照移动到 `to_game`
+#[tauri::command]
+#[specta::specta]
+pub async fn move_snapshot(
+    from_game: String,
+    date: String,
+    to_game: String,
+    app: AppHandle,
+) -> Result<(), IpcError> {
+    move_snapshots(from_game, vec![date], to_game, app).await
+}
+
+/// `move_snapshot` 的批量版本，任一日期冲突时不会移动任何快照
+#[tauri::command]
+#[specta::specta]
+pub async fn move_snapshots(
+    from_game: String,
+    dates: Vec<String>,
+    to_game: String,
+    app: AppHandle,
+) -> Result<(), IpcError> {
+    info!(target:"rgsm::ipc", "Moving snapshots {:?} from {} to {}", dates, from_game, to_game);
+    // 按名称顺序加锁，避免与自动备份同时修改 Backups.json，也避免互相等待
+    let manager = app.state::<Arc<quick_actions::QuickActionManager>>();
+    let mut names = [&from_game, &to_game];
+    names.sort();
+    let _first = manager
+        .lock_game(names[0], config::QuickActionBusyBehavior::Queue)
+        .await;
+    let _second = if names[0] != names[1] {
+        manager
+            .lock_game(names[1], config::QuickActionBusyBehavior::Queue)
+            .await
+    } else {
+        None
+    };
+    backup::move_snapshots(&from_game, &dates, &to_game)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to move snapshots: {:?}", e);
+            IpcError::from(e)
+        })?;
+    info!(target:"rgsm::ipc", "Successfully moved snapshots {:?} from {} to {}", dates, from_game, to_game);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
-pub async fn delete_game(game: Game) -> Result<(), String> {
+pub async fn delete_game(game: Game, app: AppHandle) -> Result<(), String> {
     info!(target:"rgsm::ipc", "Deleting game: {:?}", game);
-    game.delete_game().await.map_err(|e| {
+    let deleted = game.delete_game().await.map_err(|e| {
         error!(target:"rgsm::ipc", "Failed to delete game: {:?}", e);
         e.to_string()
     })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
+    if let Err(e) = deleted.emit(&app) {
+        warn!(target:"rgsm::ipc", "Failed to emit game deletion: {:?}", e);
+    }
     info!(target:"rgsm::ipc", "Successfully deleted game: {:?}", game);
     Ok(())
 }
 
+/// Put back a game deleted within the retention period
+#[tauri::command]
+#[specta::specta]
+pub async fn undo_delete_game(game_name: String, app: AppHandle) -> Result<Game, IpcError> {
+    info!(target:"rgsm::ipc", "Undoing deletion of game: {}", game_name);
+    let game = backup::undo_delete_game(&game_name).await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to undo deletion of game: {:?}", e);
+        IpcError::from(e)
+    })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
+    info!(target:"rgsm::ipc", "Successfully restored game: {}", game_name);
+    Ok(game)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_game(old_name: String, new_name: String, app: AppHandle) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Renaming game {} to {}", old_name, new_name);
+    backup::rename_game(&old_name, &new_name)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to rename game: {:?}", e);
+            e.to_string()
+        })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
+    info!(target:"rgsm::ipc", "Successfully renamed game {} to {}", old_name, new_name);
+    Ok(())
+}
+
+/// Add a favorites node, `game_ref` names the game a leaf points at
+#[tauri::command]
+#[specta::specta]
+pub async fn favorites_add_node(
+    parent_id: Option<String>,
+    label: String,
+    is_leaf: bool,
+    game_ref: Option<String>,
+) -> Result<String, String> {
+    info!(target:"rgsm::ipc", "Adding favorite {} under {:?}", label, parent_id);
+    config::add_favorite(parent_id.as_deref(), &label, is_leaf, game_ref.as_deref())
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to add favorite: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn favorites_remove_node(node_id: String) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Removing favorite {}", node_id);
+    config::remove_favorite(&node_id).await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to remove favorite: {:?}", e);
+        e.to_string()
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
-pub async fn get_game_snapshots_info(game: Game) -> Result<GameSnapshots, String> {
+pub async fn favorites_move_node(
+    node_id: String,
+    new_parent_id: Option<String>,
+    index: u32,
+) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Moving favorite {} to {:?} at {}", node_id, new_parent_id, index);
+    config::move_favorite(&node_id, new_parent_id.as_deref(), index as usize)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to move favorite: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn favorites_rename_node(node_id: String, label: String) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Renaming favorite {} to {}", node_id, label);
+    config::rename_favorite(&node_id, &label)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to rename favorite: {:?}", e);
+            e.to_string()
+        })
+}
+
+/// Disk usage of the backup folder, including zips that no snapshot record refers to
+#[tauri::command]
+#[specta::specta]
+pub async fn get_storage_report() -> Result<backup::StorageReport, String> {
+    info!(target:"rgsm::ipc", "Building storage report");
+    backup::get_storage_report().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to build storage report: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Reconcile snapshot zips with their records, only reporting the changes when `dry_run` is set
+#[tauri::command]
+#[specta::specta]
+pub async fn cleanup_backup_storage(
+    dry_run: bool,
+    app: AppHandle,
+) -> Result<backup::CleanupReport, String> {
+    info!(target:"rgsm::ipc", "Cleaning up backup storage, dry run: {}", dry_run);
+    let keys = app.try_state::<cloud_sync::SessionKeys>();
+    backup::cleanup_backup_storage(dry_run, keys.as_deref())
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to clean up backup storage: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn migrate_backup_root(
+    new_path: String,
+    move_files: bool,
+    app: AppHandle,
+) -> Result<backup::BackupRootMigration, String> {
+    info!(target:"rgsm::ipc", "Migrating backup root to {}, move files: {}", new_path, move_files);
+    backup::migrate_backup_root(&new_path, move_files, Some(&app))
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to migrate backup root: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_game_snapshots_info(game: Game) -> Result<GameSnapshots, IpcError> {
     info!(target:"rgsm::ipc", "Getting backup list info for game: {:?}", game);
     game.get_game_snapshots_info().map_err(|e| {
         error!(target:"rgsm::ipc", "Failed to get backup list info: {:?}", e);
-        e.to_string()
+        e.into()
     })
 }
 
+/// 根据备份目录中的压缩包重建 Backups.json，旧文件中仍可读取的记录会被保留
 #[tauri::command]
 #[specta::specta]
-pub async fn set_config(config: Config) -> Result<(), String> {
-    debug!(target:"rgsm::ipc", "Setting config: {:?}", config.clone().sanitize());
+pub async fn rebuild_game_snapshots(game: Game) -> Result<GameSnapshots, IpcError> {
+    info!(target:"rgsm::ipc", "Rebuilding snapshot records for game: {}", game.name);
+    backup::rebuild_game_snapshots(&game).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to rebuild snapshot records: {:?}", e);
+        e.into()
+    })
+}
+
+/// 检查配置与所有游戏的快照记录，返回发现的问题
+#[tauri::command]
+#[specta::specta]
+pub async fn validate_data() -> Result<Vec<backup::DataIssue>, IpcError> {
+    info!(target:"rgsm::ipc", "Validating config and snapshot records");
+    backup::validate_data().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to validate data: {:?}", e);
+        e.into()
+    })
+}
+
+/// 检查配置中的游戏与备份目录下的文件夹是否一致，修复同样通过 `apply_data_fixes` 执行
+#[tauri::command]
+#[specta::specta]
+pub async fn get_consistency_report() -> Result<Vec<backup::DataIssue>, IpcError> {
+    info!(target:"rgsm::ipc", "Checking backup folders against the config");
+    backup::get_consistency_report().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to check backup folders: {:?}", e);
+        e.into()
+    })
+}
+
+/// 执行 `validate_data` 给出的修复操作，返回实际执行了的 ID
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_data_fixes(ids: Vec<String>) -> Result<Vec<String>, IpcError> {
+    info!(target:"rgsm::ipc", "Applying data fixes: {:?}", ids);
+    backup::apply_data_fixes(&ids).await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to apply data fixes: {:?}", e);
+        e.into()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_config(config: Config, app: AppHandle) -> Result<(), String> {
+    config::log_config_safely("Setting config", &config);
     config::set_config(&config).await.map_err(|e| {
         error!(target:"rgsm::ipc", "Failed to set config: {:?}", e);
         e.to_string()
-    })
+    })?;
+    // 语言修改立即生效，之后的托盘菜单与通知使用新语言
+    rust_i18n::set_locale(&config.settings.locale);
+    app.state::<cloud_sync::CloudSyncScheduler>()
+        .update_interval(config.settings.cloud_settings.auto_sync_interval);
+    let quick_action_manager = app.state::<Arc<quick_actions::QuickActionManager>>();
+    // 游戏列表可能通过配置修改（如重命名），同步刷新托盘菜单
+    quick_action_manager.rebuild_tray_menu();
+    // 配置已经保存，快捷键冲突单独报告给前端
+    quick_action_manager.reload_hotkeys(&config).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to reload hotkeys: {:?}", e);
+        e.to_string()
+    })?;
+    Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn reset_settings() -> Result<(), String> {
+pub async fn reset_settings(app: AppHandle) -> Result<(), String> {
     info!(target:"rgsm::ipc", "Resetting settings.");
     config::reset_settings().await.map_err(|e| {
         error!(target:"rgsm::ipc", "Failed to reset settings: {:?}", e);
         e.to_string()
-    })
+    })?;
+    app.state::<cloud_sync::CloudSyncScheduler>()
+        .update_interval(Config::default().settings.cloud_settings.auto_sync_interval);
+    Ok(())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn create_snapshot(game: Game, describe: String, window: Window) -> Result<(), String> {
+pub async fn create_snapshot(
+    game: Game,
+    describe: String,
+    app: AppHandle,
+    window: Window,
+) -> Result<backup::Snapshot, IpcError> {
     info!(target:"rgsm::ipc", "Backing up save for game: {:?}", game);
-    handle_backup_err(game.create_snapshot(&describe).await, window)?;
+    let operations = app.state::<backup::BackupOperations>();
+    let operation = operations.start(&game.name);
+    let mut monitor = backup::ArchiveMonitor::emitting(
+        &app,
+        &game.name,
+        backup::ArchiveOperation::Compress,
+        operation.token(),
+    );
+    let result = game
+        .create_snapshot_with_monitor(&describe, &mut monitor)
+        .await;
+    let snapshot = handle_backup_err(result, window)?;
     info!(target:"rgsm::ipc", "Successfully backed up save for game: {:?}", game);
-    Ok(())
+    Ok(snapshot)
+}
+
+/// 取消正在创建或恢复的快照，返回是否有正在进行的操作
+///
+/// 创建时会删除未完成的压缩包，恢复时只有在解压完成前才能取消，此时存档不会被修改
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_backup_operation(game: Game, app: AppHandle) -> Result<bool, String> {
+    info!(target:"rgsm::ipc", "Cancelling backup operation of game: {}", game.name);
+    Ok(app.state::<backup::BackupOperations>().cancel(&game.name))
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn open_backup_folder(game: Game) -> Result<bool, String> {
+pub async fn open_backup_folder(game: Game) -> Result<(), IpcError> {
     info!(target:"rgsm::ipc", "Opening backup folder for game: {:?}", game);
-    let config = get_config().map_err(|e| {
-        error!(target:"rgsm::ipc", "Failed to get config: {:?}", e);
+    game.open_backup_folder().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to open backup folder: {:?}", e);
+        e.into()
+    })
+}
+
+/// 打开日志所在的文件夹
+#[tauri::command]
+#[specta::specta]
+pub async fn open_log_folder(app: AppHandle) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Opening log folder");
+    let dir = crate::diagnostics::log_dir(&app).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to resolve log folder: {:?}", e);
         e.to_string()
     })?;
-    let p = crate::backup::join_backup_dir(&config, &game.name);
-    Ok(open::that(p).is_ok())
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| open::that(&dir))
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to open log folder {:?}: {:?}", dir, e);
+            e.to_string()
+        })
+}
+
+/// 将最近的日志、脱敏后的配置与上次扫描结果打包到 `target_path`，便于反馈问题
+#[tauri::command]
+#[specta::specta]
+pub async fn collect_diagnostics(target_path: String, app: AppHandle) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Collecting diagnostics to {}", target_path);
+    crate::diagnostics::collect_diagnostics(&app, std::path::Path::new(&target_path)).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to collect diagnostics: {:?}", e);
+        e.to_string()
+    })
 }
 
+/// 检查已保存的云同步后端，`backend` 为后端名称，未指定时检查主后端
 #[tauri::command]
 #[specta::specta]
-pub async fn check_cloud_backend(backend: Backend) -> Result<(), String> {
+pub async fn check_cloud_backend(backend: Option<String>) -> Result<(), IpcError> {
+    let config = get_config()?;
+    let backend = config
+        .settings
+        .cloud_settings
+        .find_backend(backend.as_deref())?;
     info!(target:"rgsm::ipc", "Checking cloud backend: {:?}", backend.clone().sanitize());
     match backend.check().await {
         Ok(_) => {
-            info!(target:"rgsm::ipc", "Successfully checked cloud backend: {:?}", backend.sanitize());
+            info!(target:"rgsm::ipc", "Successfully checked cloud backend {}", backend.name);
+            // 后端可用时顺便发送离线期间排队的操作
+            tauri::async_runtime::spawn(cloud_sync::flush_in_background());
             Ok(())
         }
         Err(e) => {
             error!(target:"rgsm::ipc", "Failed to check cloud backend: {:?}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
+/// 上传全部备份，`backend` 为后端名称，未指定时使用主后端
 #[tauri::command]
 #[specta::specta]
-pub async fn cloud_upload_all(backend: Backend) -> Result<(), String> {
+pub async fn cloud_upload_all(
+    backend: Option<String>,
+    app: AppHandle,
+) -> Result<SyncSummary, IpcError> {
+    let config = get_config()?;
+    let backend = config
+        .settings
+        .cloud_settings
+        .find_backend(backend.as_deref())?;
     info!(target:"rgsm::ipc", "Uploading all backups to cloud backend: {:?}", backend.clone().sanitize());
-    let op = backend.get_op().map_err(|e| {
-        error!(target:"rgsm::ipc", "Failed to get cloud backend operator: {:?}", e);
-        e.to_string()
-    })?;
-    match upload_all(&op).await {
-        Ok(_) => {
-            info!(target:"rgsm::ipc", "Successfully uploaded all backups to cloud backend: {:?}", backend.sanitize());
-            Ok(())
+    match upload_all(backend, Some(&app)).await {
+        Ok(summary) => {
+            info!(target:"rgsm::ipc", "Successfully uploaded all backups to cloud backend {}", backend.name);
+            Ok(summary)
         }
         Err(e) => {
             error!(target:"rgsm::ipc", "Failed to upload all backups to cloud backend: {:?}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
+/// 下载全部备份，`backend` 为后端名称，未指定时使用主后端
 #[tauri::command]
 #[specta::specta]
-pub async fn cloud_download_all(backend: Backend) -> Result<(), String> {
+pub async fn cloud_download_all(
+    backend: Option<String>,
+    app: AppHandle,
+) -> Result<SyncSummary, IpcError> {
+    let config = get_config()?;
+    let backend = config
+        .settings
+        .cloud_settings
+        .find_backend(backend.as_deref())?;
     info!(target:"rgsm::ipc", "Downloading all backups from cloud backend: {:?}", backend.clone().sanitize());
-    let op = backend.get_op().map_err(|e| {
-        error!(target:"rgsm::ipc", "Failed to get cloud backend operator: {:?}", e);
-        e.to_string()
-    })?;
-    match cloud_sync::download_all(&op).await {
-        Ok(_) => {
-            info!(target:"rgsm::ipc", "Successfully downloaded all backups from cloud backend: {:?}", backend.sanitize());
-            Ok(())
+    match cloud_sync::download_all(backend, Some(&app)).await {
+        Ok(summary) => {
+            info!(target:"rgsm::ipc", "Successfully downloaded all backups from cloud backend {}", backend.name);
+            Ok(summary)
         }
         Err(e) => {
             error!(target:"rgsm::ipc", "Failed to download all backups from cloud backend: {:?}", e);
-            Err(e.to_string())
+            Err(e.into())
         }
     }
 }
 
+/// Last cloud sync of each game, read from the local sync state instead of the remote
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_status() -> Result<Vec<cloud_sync::GameSyncStatus>, String> {
+    let config = get_config().map_err(|e| e.to_string())?;
+    cloud_sync::get_sync_status(&config).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to get cloud sync status: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Cloud operations waiting to be sent, oldest first
+#[tauri::command]
+#[specta::specta]
+pub async fn get_cloud_outbox() -> Result<Vec<cloud_sync::OutboxEntry>, String> {
+    cloud_sync::get_outbox().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to read cloud outbox: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// Send the queued cloud operations now
+#[tauri::command]
+#[specta::specta]
+pub async fn flush_cloud_outbox() -> Result<cloud_sync::OutboxFlushSummary, IpcError> {
+    info!(target:"rgsm::ipc", "Flushing cloud outbox");
+    cloud_sync::flush_outbox().await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to flush cloud outbox: {:?}", e);
+        IpcError::from(e)
+    })
+}
+
+/// Enable snapshot encryption for this session, generating the salt on first use
+///
+/// The first passphrase is recorded with a check value, a different one is rejected later
+#[tauri::command]
+#[specta::specta]
+pub async fn set_cloud_passphrase(passphrase: String, app: AppHandle) -> Result<(), IpcError> {
+    let mut config = get_config().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to get config: {:?}", e);
+        IpcError::from(e)
+    })?;
+    let mut encryption = config
+        .settings
+        .cloud_settings
+        .encryption
+        .clone()
+        .unwrap_or_default();
+    if encryption.key_derivation_salt.is_empty() {
+        encryption.key_derivation_salt = cloud_sync::generate_salt();
+    }
+    // Key derivation is deliberately slow, keep it off the async runtime
+    let handle = app.clone();
+    let checked = tokio::task::spawn_blocking(move || {
+        handle
+            .state::<cloud_sync::SessionKeys>()
+            .set_passphrase(&passphrase, &mut encryption)
+            .map(|_| encryption)
+    })
+    .await
+    .map_err(|e| IpcError::new(ErrorCode::Unexpected, e.to_string()))?
+    .map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to set cloud passphrase: {:?}", e);
+        IpcError::from(e)
+    })?;
+    let current = config.settings.cloud_settings.encryption.as_ref();
+    if current.map(|e| (&e.key_derivation_salt, &e.key_check))
+        != Some((&checked.key_derivation_salt, &checked.key_check))
+    {
+        config.settings.cloud_settings.encryption = Some(checked);
+        config::set_config(&config).await.map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to save passphrase check: {:?}", e);
+            IpcError::from(e)
+        })?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_cloud_passphrase(app: AppHandle) -> Result<(), String> {
+    app.state::<cloud_sync::SessionKeys>().clear();
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn set_snapshot_description(
@@ -267,26 +990,43 @@ pub async fn set_snapshot_description(
 
 #[tauri::command]
 #[specta::specta]
-pub async fn backup_all() -> Result<(), String> {
+pub async fn backup_all() -> Result<BatchSummary, String> {
     info!(target:"rgsm::ipc","Backing up all games.");
-    backup::backup_all().await.map_err(|e| {
+    let summary = backup::backup_all().await.map_err(|e| {
         error!(target:"rgsm::ipc", "Failed to backup all games: {:?}", e);
         e.to_string()
     })?;
-    info!(target:"rgsm::ipc","Successfully backed up all games.");
-    Ok(())
+    info!(target:"rgsm::ipc","Backed up all games, {} succeeded, {} failed.", summary.succeeded.len(), summary.failed.len());
+    Ok(summary)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn apply_all(app_handle: AppHandle) -> Result<(), String> {
+pub async fn apply_all(app_handle: AppHandle) -> Result<BatchSummary, String> {
     info!(target:"rgsm::ipc","Applying all backups.");
-    backup::apply_all(Some(&app_handle)).await.map_err(|e| {
+    let summary = backup::apply_all(Some(&app_handle)).await.map_err(|e| {
         error!(target:"rgsm::ipc", "Failed to apply all backups: {:?}", e);
         e.to_string()
     })?;
-    info!(target:"rgsm::ipc","Successfully applied all backups.");
-    Ok(())
+    info!(target:"rgsm::ipc","Applied all backups, {} succeeded, {} failed.", summary.succeeded.len(), summary.failed.len());
+    Ok(summary)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_latest(
+    app_handle: AppHandle,
+    game_names: Vec<String>,
+) -> Result<BatchSummary, String> {
+    info!(target:"rgsm::ipc","Applying latest backups of: {:?}", game_names);
+    let summary = backup::apply_latest(&game_names, Some(&app_handle))
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to apply latest backups: {:?}", e);
+            e.to_string()
+        })?;
+    info!(target:"rgsm::ipc","Applied latest backups, {} succeeded, {} failed.", summary.succeeded.len(), summary.failed.len());
+    Ok(summary)
 }
 
 #[tauri::command]
@@ -306,13 +1046,61 @@ pub async fn set_quick_backup_game(app_handle: AppHandle, game: Game) -> Result<
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn set_quick_action_override(
+    game_name: String,
+    game_override: QuickActionOverride,
+) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Setting quick action override of {}: {:?}", game_name, game_override);
+    quick_actions::set_quick_action_override(&game_name, game_override)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to set quick action override: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_quick_action_override(game_name: String) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Clearing quick action override of {}", game_name);
+    quick_actions::clear_quick_action_override(&game_name)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to clear quick action override: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_auto_backup_status(
+    app_handle: AppHandle,
+) -> Result<quick_actions::AutoBackupTick, String> {
+    let manager_state: tauri::State<Arc<quick_actions::QuickActionManager>> = app_handle.state();
+    Ok(manager_state.auto_backup_status())
+}
+
+/// 重新检查备份目录，可用时恢复已暂停的自动备份，返回备份目录是否可用
+#[tauri::command]
+#[specta::specta]
+pub async fn retry_backup_root(app_handle: AppHandle) -> Result<bool, String> {
+    info!(target:"rgsm::ipc", "Re-checking the backup root");
+    let manager_state: tauri::State<Arc<quick_actions::QuickActionManager>> = app_handle.state();
+    manager_state.retry_backup_root().await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to re-check the backup root: {:?}", e);
+        e.to_string()
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn toggle_quick_action_sound_preview(
     app: AppHandle,
     preferences: QuickActionSoundPreferences,
     effect: sound::QuickActionSoundEffect,
-) -> Result<(), String> {
+) -> Result<sound::SoundPreviewState, String> {
     let manager = app.state::<sound::SoundManager>();
     manager
         .toggle_preview(preferences, effect)
@@ -335,8 +1123,67 @@ pub async fn stop_sound_playback(app: AppHandle) -> Result<(), String> {
 
 #[tauri::command]
 #[specta::specta]
-pub async fn choose_quick_action_sound_file(app: AppHandle) -> Result<String, String> {
-    sound::choose_quick_action_sound_file(&app)
+pub async fn choose_quick_action_sound_file(
+    app: AppHandle,
+    copy_to_app_dir: bool,
+) -> Result<Option<String>, String> {
+    sound::choose_quick_action_sound_file(&app, copy_to_app_dir)
+}
+
+/// 读取音频文件的总时长（秒），供设置页提示过长的提示音
+#[tauri::command]
+#[specta::specta]
+pub async fn probe_sound_file(path: String) -> Result<f64, String> {
+    sound::probe_sound_file(&path)
+        .map(|duration| duration.as_secs_f64())
+        .map_err(|err| {
+            error!(target: "rgsm::sound", "Failed to probe sound file: {err:?}");
+            format!("{err:#}")
+        })
+}
+
+/// 显示一条测试通知，便于检查系统的通知设置
+#[tauri::command]
+#[specta::specta]
+pub async fn test_notification(app: AppHandle) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Showing test notification.");
+    quick_actions::show_app_notification(
+        &app,
+        &t!("backend.notification.test_title"),
+        &t!("backend.notification.test_body"),
+        None,
+    )
+    .map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to show test notification: {:?}", e);
+        e.to_string()
+    })
+}
+
+/// 点击通知重新打开主窗口后，前端读取需要打开的游戏
+#[tauri::command]
+#[specta::specta]
+pub async fn take_notification_target(app: AppHandle) -> Result<Option<String>, String> {
+    Ok(app
+        .state::<quick_actions::PendingNotificationTarget>()
+        .take())
+}
+
+/// Starts watching the save paths of all games, `SaveChanged` is emitted on changes
+#[tauri::command]
+#[specta::specta]
+pub async fn start_save_watchers(app: AppHandle) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Starting save watchers");
+    app.state::<save_watcher::SaveWatcher>().start();
+    Ok(())
+}
+
+/// Stops watching the save paths started by `start_save_watchers`
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_save_watchers(app: AppHandle) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Stopping save watchers");
+    app.state::<save_watcher::SaveWatcher>().stop();
+    Ok(())
 }
 
 /// Resolves a path string containing variables to an actual filesystem path
@@ -367,6 +1214,17 @@ pub async fn resolve_path(path: String) -> Result<String, String> {
     Ok(path_str.to_string())
 }
 
+/// 首次运行时设置默认备份目录并登记当前设备，已完成设置时不会修改配置
+#[tauri::command]
+#[specta::specta]
+pub async fn initialize_first_run() -> Result<config::FirstRunSetup, IpcError> {
+    info!(target:"rgsm::ipc", "Initializing first run");
+    config::initialize_first_run().await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to initialize first run: {:?}", e);
+        e.into()
+    })
+}
+
 /// Returns the current device, if not found, returns a default device
 #[tauri::command]
 #[specta::specta]
@@ -382,37 +1240,146 @@ pub async fn get_current_device_info() -> Result<Device, String> {
     Ok(config.devices.get(device_id).cloned().unwrap_or_default())
 }
 
-fn handle_backup_err(res: Result<(), BackupError>, window: Window) -> Result<(), String> {
-    if let Err(e) = res {
-        match &e {
-            BackupError::Compress(CompressError::Multiple(files)) => {
-                files.iter().for_each(|file| {
-                    error!(target:"rgsm::ipc","{}",file);
-                    if let BackupFileError::NotExists(path) = file {
-                        window
-                            .emit(
-                                "Notification",
-                                IpcNotification {
-                                    level: NotificationLevel::error,
-                                    title: "ERROR".to_string(),
-                                    msg: t!(
-                                        "backend.backup.backup_file_not_exist",
-                                        name = path.to_str().unwrap_or("Cannot get path")
-                                    )
-                                    .to_string(),
-                                },
-                            )
-                            .unwrap(); // safe: ipc方法通过前端调用，此时window必然存在
-                    }
-                });
-            }
-            other => {
-                error!(target:"rgsm::ipc","{}",other);
-            }
+#[tauri::command]
+#[specta::specta]
+pub async fn list_device_paths(
+    game_name: String,
+) -> Result<Vec<backup::SaveUnitDevicePaths>, String> {
+    info!(target:"rgsm::ipc", "Listing device paths of game: {}", game_name);
+    let config = get_config().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to get config: {:?}", e);
+        e.to_string()
+    })?;
+    backup::list_device_paths(&config, &game_name).map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to list device paths: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_unit_path_for_device(
+    game_name: String,
+    unit_index: usize,
+    device_id: String,
+    path: String,
+    app: AppHandle,
+) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Setting path of unit {} of {} for device {}: {}", unit_index, game_name, device_id, path);
+    backup::set_unit_path_for_device(&game_name, unit_index, &device_id, &path)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to set unit path: {:?}", e);
+            e.to_string()
+        })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_unit_path_for_device(
+    game_name: String,
+    unit_index: usize,
+    device_id: String,
+    app: AppHandle,
+) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Removing path of unit {} of {} for device {}", unit_index, game_name, device_id);
+    backup::remove_unit_path_for_device(&game_name, unit_index, &device_id)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to remove unit path: {:?}", e);
+            e.to_string()
+        })?;
+    app.state::<Arc<quick_actions::QuickActionManager>>()
+        .rebuild_tray_menu();
+    Ok(())
+}
+
+/// Returns all known devices, the current one first
+#[tauri::command]
+#[specta::specta]
+pub async fn list_devices() -> Result<Vec<Device>, String> {
+    info!(target:"rgsm::ipc", "Listing devices");
+    let config = get_config().map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to get config: {:?}", e);
+        e.to_string()
+    })?;
+    Ok(device::list_devices(&config))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_device(device_id: String, new_name: String) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Renaming device {} to {}", device_id, new_name);
+    device::rename_device(&device_id, &new_name)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to rename device: {:?}", e);
+            e.to_string()
+        })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_current_device_name(name: String) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Renaming current device to {}", name);
+    device::set_current_device_name(&name).await.map_err(|e| {
+        error!(target:"rgsm::ipc", "Failed to rename current device: {:?}", e);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_device(device_id: String, strip_paths: bool) -> Result<(), String> {
+    info!(target:"rgsm::ipc", "Removing device {}, strip paths: {}", device_id, strip_paths);
+    device::remove_device(&device_id, strip_paths)
+        .await
+        .map_err(|e| {
+            error!(target:"rgsm::ipc", "Failed to remove device: {:?}", e);
+            e.to_string()
+        })
+}
+
+fn handle_backup_err<T>(res: Result<T, BackupError>, window: Window) -> Result<T, IpcError> {
+    let e = match res {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    match &e {
+        BackupError::Compress(CompressError::Multiple(files)) => {
+            files.iter().for_each(|file| {
+                error!(target:"rgsm::ipc","{}",file);
+                let msg = match file {
+                    BackupFileError::NotExists(path) => t!(
+                        "backend.backup.backup_file_not_exist",
+                        name = path.to_str().unwrap_or("Cannot get path")
+                    ),
+                    BackupFileError::FileInUse(path) => t!(
+                        "backend.backup.file_in_use",
+                        name = path.to_str().unwrap_or("Cannot get path")
+                    ),
+                    _ => return,
+                };
+                window
+                    .emit(
+                        "Notification",
+                        IpcNotification {
+                            level: NotificationLevel::error,
+                            title: t!("backend.notification.error").to_string(),
+                            msg: msg.to_string(),
+                        },
+                    )
+                    .unwrap(); // safe: ipc方法通过前端调用，此时window必然存在
+            });
+        }
+        other => {
+            error!(target:"rgsm::ipc","{}",other);
         }
-        return Err(format!("{}", e));
     }
-    Ok(())
+    Err(e.into())
 }
 
 #[cfg(test)]
@@ -0,0 +1,212 @@
+use log::{info, warn};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::{io, path::Path, process::Stdio, sync::Arc};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::process::{Child, Command};
+
+use crate::backup::Game;
+use crate::config::{QuickActionBusyBehavior, get_config};
+use crate::device::get_current_device_id;
+use crate::ipc_handler::{IpcNotification, NotificationLevel};
+use crate::path_resolver::resolve_path;
+use crate::preclude::*;
+use crate::quick_actions::QuickActionManager;
+
+/// 启动游戏的结果
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LaunchResult {
+    /// 直接启动了可执行文件，可以追踪进程
+    Process { pid: u32 },
+    /// 交给系统打开，例如 `steam://` 链接或快捷方式，无法追踪进程
+    Opened,
+}
+
+/// 使用当前设备的 `game_paths` 启动游戏
+///
+/// - 开启 `backup_before_launch` 时先创建快照，失败只会提示，不会阻止启动
+/// - 开启 `backup_after_exit` 时在进程退出后创建快照。只能追踪直接启动的可执行文件，
+///   若可执行文件只是启动器，它退出时游戏可能仍在运行，快照会提前创建
+/// - 与快捷操作共用同一把游戏锁，避免同时备份同一个游戏
+pub async fn launch_game(app: &AppHandle, game: &Game) -> Result<LaunchResult, LaunchError> {
+    let config = get_config()?;
+    // 以配置中的游戏为准，前端传入的可能已经过时
+    let game = config
+        .games
+        .iter()
+        .find(|g| g.name == game.name)
+        .unwrap_or(game)
+        .clone();
+    let Some(raw) = game.game_paths.get(get_current_device_id()).cloned() else {
+        notify(
+            app,
+            NotificationLevel::error,
+            t!("backend.launch.no_launch_path", name = game.name).to_string(),
+        );
+        return Err(LaunchError::NoPathForDevice(game.name.clone()));
+    };
+    let target = if is_url(&raw) {
+        None
+    } else {
+        let path = resolve_path(&raw, Some(&game), &config)?;
+        if !path.exists() {
+            return Err(LaunchError::NotExist(path));
+        }
+        Some(path)
+    };
+
+    if config.settings.backup_before_launch {
+        snapshot_with_lock(app, &game, &t!("backend.launch.before_launch_describe")).await;
+    }
+
+    let result = match target {
+        Some(path) if is_executable(&path) => {
+            let child = spawn_detached(&path)?;
+            let pid = child.id().unwrap_or_default();
+            info!(target: "rgsm::launcher", "Launched {} from {:?}, pid: {}", game.name, path, pid);
+            if config.settings.backup_after_exit {
+                tauri::async_runtime::spawn(backup_after_exit(app.clone(), game, child));
+            }
+            LaunchResult::Process { pid }
+        }
+        Some(path) => {
+            open::that(&path)?;
+            info!(target: "rgsm::launcher", "Opened {:?} for {}", path, game.name);
+            LaunchResult::Opened
+        }
+        None => {
+            open::that(&raw)?;
+            info!(target: "rgsm::launcher", "Opened {} for {}", raw, game.name);
+            LaunchResult::Opened
+        }
+    };
+    if config.settings.backup_after_exit && matches!(result, LaunchResult::Opened) {
+        info!(target: "rgsm::launcher", "Cannot track the process of {}, no snapshot will be taken on exit", raw);
+    }
+    Ok(result)
+}
+
+/// 等待进程退出后创建快照
+async fn backup_after_exit(app: AppHandle, game: Game, mut child: Child) {
+    match child.wait().await {
+        Ok(status) => {
+            info!(target: "rgsm::launcher", "{} exited with {}", game.name, status)
+        }
+        Err(e) => {
+            warn!(target: "rgsm::launcher", "Cannot wait for {}: {:?}", game.name, e);
+            return;
+        }
+    }
+    if snapshot_with_lock(&app, &game, &t!("backend.launch.after_exit_describe")).await {
+        notify(
+            &app,
+            NotificationLevel::info,
+            t!("backend.launch.after_exit_done", name = game.name).to_string(),
+        );
+    }
+}
+
+/// 持有快捷操作的游戏锁创建快照，返回是否成功
+async fn snapshot_with_lock(app: &AppHandle, game: &Game, describe: &str) -> bool {
+    let manager = app.state::<Arc<QuickActionManager>>();
+    let _guard = manager
+        .lock_game(&game.name, QuickActionBusyBehavior::Queue)
+        .await;
+    match game.create_snapshot(describe).await {
+        Ok(_) => true,
+        Err(e) => {
+            warn!(target: "rgsm::launcher", "Failed to create snapshot for {}: {:?}", game.name, e);
+            notify(
+                app,
+                NotificationLevel::warning,
+                t!(
+                    "backend.launch.snapshot_failed",
+                    name = game.name,
+                    error = e
+                )
+                .to_string(),
+            );
+            false
+        }
+    }
+}
+
+fn notify(app: &AppHandle, level: NotificationLevel, msg: String) {
+    let title = match level {
+        NotificationLevel::info => t!("backend.notification.info"),
+        NotificationLevel::warning => t!("backend.notification.warning"),
+        NotificationLevel::error => t!("backend.notification.error"),
+    };
+    let _ = app.emit(
+        "Notification",
+        IpcNotification {
+            level,
+            title: title.to_string(),
+            msg,
+        },
+    );
+}
+
+/// 判断是否为 `steam://rungameid/...` 这类协议链接，`C://` 这样的盘符不算
+fn is_url(raw: &str) -> bool {
+    raw.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+}
+
+#[cfg(not(windows))]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+/// 启动的游戏不应随管理器退出
+fn spawn_detached(path: &Path) -> io::Result<Child> {
+    let mut command = Command::new(path);
+    // 不少游戏按工作目录查找资源
+    if let Some(dir) = path.parent() {
+        command.current_dir(dir);
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+    command.spawn()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_protocol_links() {
+        assert!(is_url("steam://rungameid/570"));
+        assert!(is_url(
+            "com.epicgames.launcher://apps/Fortnite?action=launch"
+        ));
+        assert!(!is_url("C://Games/game.exe"));
+        assert!(!is_url("C:\\Games\\game.exe"));
+        assert!(!is_url("<home>/Games/game"));
+    }
+}
@@ -13,21 +13,30 @@ use log::{error, info};
 use tauri_plugin_window_state::{AppHandleExt, StateFlags};
 
 use crate::config::config_check;
+use crate::preclude::format_log_record;
 
 mod backup;
+mod cli;
 mod cloud_sync;
 mod config;
 mod default_value;
 mod device;
+mod diagnostics;
 mod game_scan;
 mod ipc_handler;
+mod launcher;
 mod path_resolver;
 mod preclude;
 mod quick_actions;
+mod save_watcher;
 mod sound;
 mod updater;
 
 pub fn run() -> anyhow::Result<()> {
+    // 带有命令行操作时直接执行后退出，不启动界面
+    if let Some(command) = cli::parse_args(std::env::args().skip(1))? {
+        return cli::run(command);
+    }
     info!("{}", t!("home.hello_world"));
     config_check()?;
 
@@ -58,35 +67,98 @@ pub fn run() -> anyhow::Result<()> {
     let command_builder = tauri_specta::Builder::<tauri::Wry>::new()
         .commands(tauri_specta::collect_commands![
             ipc_handler::open_url,
+            ipc_handler::check_for_app_update,
             ipc_handler::open_file_or_folder,
+            ipc_handler::launch_game,
             ipc_handler::choose_save_file,
+            ipc_handler::choose_save_files,
             ipc_handler::choose_save_dir,
+            ipc_handler::choose_export_path,
             ipc_handler::get_local_config,
             ipc_handler::add_game,
+            ipc_handler::validate_game,
+            ipc_handler::check_all_games,
+            ipc_handler::export_game_archive,
+            ipc_handler::import_game_archive,
+            ipc_handler::export_sanitized_config,
+            ipc_handler::import_games_from_config,
             ipc_handler::restore_snapshot,
+            ipc_handler::preview_restore,
+            ipc_handler::list_snapshot_contents,
+            ipc_handler::diff_snapshots,
+            ipc_handler::restore_single_file,
             ipc_handler::delete_snapshot,
+            ipc_handler::move_snapshot,
+            ipc_handler::move_snapshots,
             ipc_handler::delete_game,
+            ipc_handler::undo_delete_game,
+            ipc_handler::rename_game,
+            ipc_handler::favorites_add_node,
+            ipc_handler::favorites_remove_node,
+            ipc_handler::favorites_move_node,
+            ipc_handler::favorites_rename_node,
+            ipc_handler::migrate_backup_root,
+            ipc_handler::get_storage_report,
+            ipc_handler::cleanup_backup_storage,
             ipc_handler::get_game_snapshots_info,
+            ipc_handler::rebuild_game_snapshots,
+            ipc_handler::validate_data,
+            ipc_handler::get_consistency_report,
+            ipc_handler::apply_data_fixes,
             ipc_handler::set_config,
             ipc_handler::reset_settings,
             ipc_handler::create_snapshot,
+            ipc_handler::cancel_backup_operation,
             ipc_handler::open_backup_folder,
+            ipc_handler::open_log_folder,
+            ipc_handler::collect_diagnostics,
             ipc_handler::check_cloud_backend,
             ipc_handler::cloud_upload_all,
             ipc_handler::cloud_download_all,
+            ipc_handler::get_sync_status,
+            ipc_handler::get_cloud_outbox,
+            ipc_handler::flush_cloud_outbox,
+            ipc_handler::set_cloud_passphrase,
+            ipc_handler::clear_cloud_passphrase,
             ipc_handler::set_snapshot_description,
             ipc_handler::backup_all,
             ipc_handler::apply_all,
+            ipc_handler::apply_latest,
             ipc_handler::set_quick_backup_game,
+            ipc_handler::set_quick_action_override,
+            ipc_handler::clear_quick_action_override,
+            ipc_handler::get_auto_backup_status,
+            ipc_handler::retry_backup_root,
             ipc_handler::resolve_path,
+            ipc_handler::initialize_first_run,
             ipc_handler::get_current_device_info,
+            ipc_handler::list_devices,
+            ipc_handler::rename_device,
+            ipc_handler::set_current_device_name,
+            ipc_handler::remove_device,
+            ipc_handler::list_device_paths,
+            ipc_handler::set_unit_path_for_device,
+            ipc_handler::remove_unit_path_for_device,
             ipc_handler::toggle_quick_action_sound_preview,
             ipc_handler::stop_sound_playback,
             ipc_handler::choose_quick_action_sound_file,
+            ipc_handler::probe_sound_file,
+            ipc_handler::test_notification,
+            ipc_handler::take_notification_target,
+            ipc_handler::start_save_watchers,
+            ipc_handler::stop_save_watchers,
             game_scan::scan_games,
+            game_scan::get_default_scan_options,
+            game_scan::cancel_scan,
+            game_scan::get_last_scan_result,
+            game_scan::rescan_diff,
+            game_scan::get_scan_ignore_list,
+            game_scan::ignore_detected_game,
+            game_scan::unignore_detected_game,
             game_scan::pcgw_query,
             game_scan::pcgw_search,
             game_scan::generate_save_units_for_game,
+            game_scan::adopt_detected_game,
             game_scan::pcgw_refresh_index,
             game_scan::pcgw_import_index_from_file,
             game_scan::pcgw_import_index_from_sqlite,
@@ -94,7 +166,16 @@ pub fn run() -> anyhow::Result<()> {
         .events(tauri_specta::collect_events![
             ipc_handler::IpcNotification,
             quick_actions::QuickActionCompleted,
-            game_scan::ScanProgress
+            quick_actions::AutoBackupTick,
+            quick_actions::BackupScheduleFired,
+            quick_actions::NotificationClicked,
+            game_scan::ScanProgress,
+            cloud_sync::CloudSyncProgress,
+            cloud_sync::CloudSyncStateChanged,
+            backup::BackupRootMigrationProgress,
+            save_watcher::SaveChanged,
+            backup::GameSoftDeleted,
+            backup::ArchiveProgress
         ])
         .constant("DEFAULT_CONFIG", config::Config::default());
 
@@ -105,6 +186,10 @@ pub fn run() -> anyhow::Result<()> {
         "../src/bindings.ts",
     )?;
 
+    let log_level = get_config()
+        .map(|config| diagnostics::parse_log_level(&config.settings.log_level))
+        .unwrap_or(log::LevelFilter::Info);
+
     // Init app
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_window_state::Builder::new().build())
@@ -115,23 +200,53 @@ pub fn run() -> anyhow::Result<()> {
                         file_name: Some("logs".to_string()),
                     },
                 ))
-                .max_file_size(50_000 /* bytes */)
-                .timezone_strategy(tauri_plugin_log::TimezoneStrategy::UseLocal)
+                .max_file_size(diagnostics::LOG_MAX_FILE_SIZE)
+                .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepSome(5))
+                .level(log_level)
+                // 替换默认格式以便在写入前隐藏凭据，时间使用本地时区
+                .format(|out, message, record| {
+                    out.finish(format_args!("{}", format_log_record(message, record)))
+                })
                 .build(),
         )
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            // 链接由 deep-link 插件转发处理，不需要切换到窗口
+            if quick_actions::is_deep_link_args(&args) {
+                return;
+            }
             app.get_webview_window("main")
                 .expect("no main window")
                 .set_focus()
                 .expect("failed to set focus");
         }))
+        // 需要在 single-instance 之后注册，才能收到第二个实例转发的链接
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .invoke_handler(command_builder.invoke_handler())
         .setup(move |app| {
             sound::setup(app).expect("Cannot setup sound manager");
+            app.manage(backup::BackupOperations::default());
+            app.manage(ipc_handler::LastPickedDir::default());
             // 处理快捷备份，包括托盘、定时、快捷键
             quick_actions::setup(app).expect("Cannot setup quick actions");
+            cloud_sync::setup(app).expect("Cannot setup scheduled cloud sync");
+            game_scan::setup(app).expect("Cannot setup game scan state");
+            save_watcher::setup(app).expect("Cannot setup save watcher");
+            // 清理超过保留期的已删除游戏
+            tauri::async_runtime::spawn(async {
+                match backup::purge_deleted_games().await {
+                    Ok(purged) if !purged.is_empty() => {
+                        info!(target: "rgsm::main", "Purged deleted games: {:?}", purged);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        error!(target: "rgsm::main", "Failed to purge deleted games: {:?}", e)
+                    }
+                }
+            });
+            // 检查配置中的游戏与备份目录是否一致
+            tauri::async_runtime::spawn(backup::notify_consistency_issues(app.handle().clone()));
             // 注册命令
             command_builder.mount_events(app);
             Ok(())
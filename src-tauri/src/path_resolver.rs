@@ -2,8 +2,9 @@ use std::env;
 use std::path::PathBuf;
 use thiserror::Error;
 
-use crate::backup::Game;
+use crate::backup::{Game, sanitize_windows_path_component};
 use crate::config::Config;
+use crate::game_scan::{find_steam_path, find_steam_user_id};
 
 /// Errors that may occur during path resolution
 #[derive(Debug, Error)]
@@ -20,6 +21,12 @@ pub enum ResolveError {
     #[error("Path conversion error: {0}")]
     PathConversion(String),
 
+    #[error("Steam is not installed")]
+    SteamNotFound,
+
+    #[error("No Steam user found in {0}")]
+    SteamUserNotFound(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -76,14 +83,14 @@ pub fn resolve_path(
 
     // Resolve <root> variable（使用配置中的备份根路径）
     if result.contains("<root>") {
-        let root = &config.backup_path;
-        result = result.replace("<root>", root);
+        let root = config.backup_root();
+        result = result.replace("<root>", &root.to_string_lossy());
     }
 
     // Resolve <game> variable（使用传入的游戏名）
     if result.contains("<game>") {
         if let Some(g) = game {
-            let name = sanitize_filename(&g.name);
+            let name = sanitize_windows_path_component(&g.name);
             result = result.replace("<game>", &name);
         } else {
             return Err(ResolveError::UnimplementedVar("<game>".to_string()));
@@ -93,14 +100,28 @@ pub fn resolve_path(
     // Resolve <base> variable（组合 `<root>/<game>`）
     if result.contains("<base>") {
         if let Some(g) = game {
-            let name = sanitize_filename(&g.name);
-            let base = format!("{}/{}", &config.backup_path, name);
-            result = result.replace("<base>", &base);
+            let base = g.backup_dir(config);
+            result = result.replace("<base>", &base.to_string_lossy());
         } else {
             return Err(ResolveError::UnimplementedVar("<base>".to_string()));
         }
     }
 
+    // Resolve <steamPath> variable（Steam 根目录）
+    if result.contains("<steamPath>") {
+        let steam_path = find_steam_path().ok_or(ResolveError::SteamNotFound)?;
+        let steam_str = steam_path.to_str().ok_or_else(|| {
+            ResolveError::PathConversion("Cannot convert Steam path to string".to_string())
+        })?;
+        result = result.replace("<steamPath>", steam_str);
+    }
+
+    // Resolve <steamUserId> variable（`userdata` 下的账号目录名）
+    if result.contains("<steamUserId>") {
+        let user_id = steam_user_id(config)?;
+        result = result.replace("<steamUserId>", &user_id);
+    }
+
     // Windows specific variables
     // Resolve <winAppData> variable
     if result.contains("<winAppData>") {
@@ -204,6 +225,32 @@ pub fn resolve_path(
         result = result.replace("<xdgConfig>", &xdg_config);
     }
 
+    // macOS specific variables
+
+    // Resolve <macAppSupport> variable
+    if result.contains("<macAppSupport>") {
+        let home_dir =
+            dirs::home_dir().ok_or(ResolveError::DirNotFound("Home directory".to_string()))?;
+        let app_support = home_dir.join("Library").join("Application Support");
+        let app_support_str = app_support.to_str().ok_or_else(|| {
+            ResolveError::PathConversion(
+                "Cannot convert Application Support path to string".to_string(),
+            )
+        })?;
+        result = result.replace("<macAppSupport>", app_support_str);
+    }
+
+    // Resolve <macContainers> variable
+    if result.contains("<macContainers>") {
+        let home_dir =
+            dirs::home_dir().ok_or(ResolveError::DirNotFound("Home directory".to_string()))?;
+        let containers = home_dir.join("Library").join("Containers");
+        let containers_str = containers.to_str().ok_or_else(|| {
+            ResolveError::PathConversion("Cannot convert Containers path to string".to_string())
+        })?;
+        result = result.replace("<macContainers>", containers_str);
+    }
+
     // Check for unresolved variables
     if result.contains('<') && result.contains('>') {
         // Extract the unresolved variable name
@@ -220,14 +267,19 @@ pub fn resolve_path(
     Ok(PathBuf::from(result))
 }
 
-/// 清理文件/文件夹名中的非法字符，避免路径非法
-fn sanitize_filename(s: &str) -> String {
-    let invalid = ["<", ">", ":", "\"", "\\", "/", "|", "?", "*"];
-    let mut out = s.to_string();
-    for ch in &invalid {
-        out = out.replace(ch, "_");
+/// 解析 `<steamUserId>` 使用的 Steam 账号 ID
+///
+/// - 优先使用设置中的 `steam_user_id`
+/// - 否则选择 `<steamPath>/userdata` 下最近登录的账号
+pub fn steam_user_id(config: &Config) -> Result<String, ResolveError> {
+    if let Some(id) = config.settings.steam_user_id.as_deref().map(str::trim) {
+        if !id.is_empty() {
+            return Ok(id.to_string());
+        }
     }
-    out
+    let steam_path = find_steam_path().ok_or(ResolveError::SteamNotFound)?;
+    find_steam_user_id(&steam_path)
+        .ok_or_else(|| ResolveError::SteamUserNotFound(steam_path.display().to_string()))
 }
 
 /// 展开 Windows 环境变量语法（如：%APPDATA%、%LOCALAPPDATA%、%USERPROFILE%）
@@ -340,9 +392,11 @@ mod tests {
             game_paths: std::collections::HashMap::new(),
         };
 
+        let root = config.backup_root().to_string_lossy().to_string();
+
         // <root>
         let r = resolve_path("<root>/saves", Some(&game), &config).unwrap();
-        assert!(r.to_str().unwrap().starts_with(&config.backup_path));
+        assert!(r.to_str().unwrap().starts_with(&root));
 
         // <game>
         let g = resolve_path("/games/<game>", Some(&game), &config).unwrap();
@@ -351,7 +405,49 @@ mod tests {
         // <base>
         let b = resolve_path("<base>/slot1", Some(&game), &config).unwrap();
         let s = b.to_str().unwrap().to_string();
-        assert!(s.contains(&config.backup_path) && s.contains("Test_Game"));
+        assert!(s.contains(&root) && s.contains("Test_Game"));
+    }
+
+    #[test]
+    fn test_resolve_base_matches_backup_dir() {
+        let config = create_test_config();
+        let game = crate::backup::Game {
+            name: "Foo. ".to_string(),
+            save_paths: vec![],
+            game_paths: std::collections::HashMap::new(),
+        };
+
+        let b = resolve_path("<base>", Some(&game), &config).unwrap();
+        assert_eq!(b, game.backup_dir(&config));
+    }
+
+    #[test]
+    fn test_resolve_configured_steam_user_id() {
+        let mut config = create_test_config();
+        config.settings.steam_user_id = Some(" 12345 ".to_string());
+        let p = resolve_path("/Steam/userdata/<steamUserId>/remote", None, &config).unwrap();
+        assert_eq!(p, PathBuf::from("/Steam/userdata/12345/remote"));
+    }
+
+    #[test]
+    fn test_resolve_mac_variables() {
+        let config = create_test_config();
+        let home = dirs::home_dir().unwrap();
+
+        let app_support = resolve_path("<macAppSupport>/Game", None, &config).unwrap();
+        assert_eq!(
+            app_support,
+            home.join("Library")
+                .join("Application Support")
+                .join("Game")
+        );
+
+        let containers = resolve_path("<macContainers>/com.example.game/Data", None, &config);
+        assert!(
+            containers
+                .unwrap()
+                .starts_with(home.join("Library").join("Containers"))
+        );
     }
 
     // Linux specific tests
@@ -1,6 +1,8 @@
-use std::{io, path::PathBuf, string::FromUtf8Error};
+use std::{fmt, io, ops::Deref, path::PathBuf, string::FromUtf8Error};
 use thiserror::Error;
 
+use super::redaction::redact_secrets;
+use crate::config::FavoriteIssue;
 use crate::path_resolver::ResolveError;
 
 #[derive(Debug, Error)]
@@ -15,8 +17,24 @@ pub enum BackupFileError {
     Fs(#[from] fs_extra::error::Error),
     #[error("Cannot convert path to string")]
     NonePathError,
+    #[error("Save unit has no path for this device")]
+    NoPathForDevice,
     #[error("Path resolution error: {0:#?}")]
     PathResolution(#[from] ResolveError),
+    #[error("Invalid glob pattern: {0}")]
+    InvalidGlob(#[from] globset::Error),
+    #[error("File is in use by another process: {0:#?}")]
+    FileInUse(PathBuf),
+    #[error("Operation cancelled")]
+    Cancelled,
+    #[error("A file and a folder of the same name conflict at {0:#?}")]
+    PathConflict(PathBuf),
+    #[error("Not enough free space in {path:?}: {required} bytes required, {available} available")]
+    InsufficientSpace {
+        path: PathBuf,
+        required: u64,
+        available: u64,
+    },
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
@@ -36,22 +54,64 @@ pub enum CompressError {
 pub enum BackendError {
     #[error("Backend is disabled")]
     Disabled,
+    #[error("No cloud backend named {0}")]
+    UnknownBackend(String),
     #[error("IO error: {0:#?}")]
     Io(#[from] io::Error),
     #[error("Opendal error: {0:#?}")]
-    Cloud(Box<opendal::Error>),
+    Cloud(Box<CloudError>),
     #[error("Cannot read cloud file: {0:#?}")]
     ReadCloudInfo(#[from] FromUtf8Error),
     #[error("Deserialize error: {0:#?}")]
     Deserialize(#[from] serde_json::Error),
     #[error("Cloud operator error: {0:#?}")]
     OperatorCheck(String),
+    #[error("TLS certificate error: {0}")]
+    Certificate(String),
+    #[error("Encryption passphrase is not set for this session")]
+    PassphraseRequired,
+    #[error("Cannot decrypt {0}, the passphrase is wrong or the file is corrupted")]
+    WrongPassphrase(String),
+    #[error("The passphrase differs from the one set before")]
+    PassphraseMismatch,
+    #[error("Encryption error: {0}")]
+    Encryption(String),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
 impl From<opendal::Error> for BackendError {
     fn from(value: opendal::Error) -> Self {
-        Self::Cloud(Box::new(value))
+        Self::Cloud(Box::new(CloudError(value)))
+    }
+}
+
+/// OpenDAL 的错误，格式化时隐藏其中的凭据
+///
+/// OpenDAL 的错误上下文可能包含带用户名密码的 endpoint 或请求头，这些错误会被写入日志或返回前端
+pub struct CloudError(pub opendal::Error);
+
+impl Deref for CloudError {
+    type Target = opendal::Error;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Debug for CloudError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = if f.alternate() {
+            format!("{:#?}", self.0)
+        } else {
+            format!("{:?}", self.0)
+        };
+        f.write_str(&redact_secrets(&raw))
+    }
+}
+
+impl fmt::Display for CloudError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&redact_secrets(&self.0.to_string()))
     }
 }
 
@@ -83,6 +143,10 @@ pub enum BackupError {
     BackupNotExist { name: String, date: String },
     #[error("No backups available")]
     NoBackupAvailable,
+    #[error("Snapshot {date} has no file {entry}")]
+    SnapshotEntryNotFound { date: String, entry: String },
+    #[error("{0:?} already exists")]
+    RestoreTargetExists(PathBuf),
     #[error("Backend error: {0:#?}")]
     Backend(Box<BackendError>),
     #[error("Compress/Decompress error: {0:#?}")]
@@ -93,9 +157,70 @@ pub enum BackupError {
     NonePathError,
     #[error("IO error: {0:#?}")]
     Io(#[from] io::Error),
+    #[error("Invalid game archive, missing {0}")]
+    InvalidArchive(String),
+    #[error("Invalid snapshot date in game archive: {0:?}")]
+    InvalidSnapshotDate(String),
+    #[error("Snapshot records of {0} are corrupted")]
+    SnapshotRecordsCorrupted(String),
+    #[error("Snapshots of {name} already exist: {dates:?}")]
+    ImportConflict { name: String, dates: Vec<String> },
+    #[error("Game {0} not exists")]
+    GameNotExist(String),
+    #[error("Invalid game name: {0:?}")]
+    InvalidGameName(String),
+    #[error("Game name {0} is already used")]
+    GameNameConflict(String),
+    #[error("Save unit {index} of {name} not exists")]
+    SaveUnitNotExist { name: String, index: usize },
+    #[error("Device {0} not exists")]
+    DeviceNotExist(String),
+    #[error("Invalid save path: {0:?}")]
+    InvalidSavePath(String),
+    #[error("No save unit of {0} has a path for this device")]
+    NoSavePathForDevice(String),
+    #[error("All save units of {0} are disabled")]
+    NoEnabledSaveUnit(String),
+    #[error("Invalid backup root: {0}")]
+    InvalidBackupRoot(String),
+    #[error("Backup root {0} is unavailable, the drive may be disconnected")]
+    BackupRootUnavailable(String),
+    #[error("Backup folder {0:?} does not exist")]
+    BackupFolderMissing(PathBuf),
+    #[error("Snapshot {path:?} was written but failed verification: {reason}")]
+    VerificationFailed { path: PathBuf, reason: String },
+    #[error("Cannot move {path:?} to the trash: {reason}")]
+    Trash { path: PathBuf, reason: String },
+    #[error("{0:?} already exists in the new backup root")]
+    BackupRootConflict(PathBuf),
+    #[error("Not enough free space, {required} bytes required but only {available} available")]
+    InsufficientSpace { required: u64, available: u64 },
+    #[error("Failed to migrate the backup root: {reason}, games left in the new root: {stuck:?}")]
+    BackupRootMigrationFailed { reason: String, stuck: Vec<String> },
+    #[error("No game was restored, failed games: {0:?}")]
+    NothingApplied(Vec<(String, String)>),
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
+impl BackupError {
+    /// 因被其他进程占用而无法备份的文件
+    pub fn files_in_use(&self) -> Vec<&PathBuf> {
+        fn in_use(e: &BackupFileError) -> Option<&PathBuf> {
+            match e {
+                BackupFileError::FileInUse(path) => Some(path),
+                _ => None,
+            }
+        }
+        match self {
+            BackupError::Compress(CompressError::Single(e)) => in_use(e).into_iter().collect(),
+            BackupError::Compress(CompressError::Multiple(errors)) => {
+                errors.iter().filter_map(in_use).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl From<opendal::Error> for BackupError {
     fn from(e: opendal::Error) -> Self {
         Self::Backend(Box::new(BackendError::from(e)))
@@ -154,3 +279,61 @@ pub enum UpdaterError {
     #[error(transparent)]
     Unexpected(#[from] anyhow::Error),
 }
+
+/// 管理设备时可能产生的错误
+#[derive(Debug, Error)]
+pub enum DeviceError {
+    #[error("Device {0} not exists")]
+    NotExist(String),
+    #[error("The current device cannot be removed")]
+    RemoveCurrent,
+    #[error("Invalid device name: {0:?}")]
+    InvalidName(String),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+/// 修改收藏夹时可能产生的错误
+#[derive(Debug, Error)]
+pub enum FavoriteError {
+    #[error("Favorite node {0} not exists")]
+    NodeNotExist(String),
+    #[error("Favorite node {0} is a game and cannot have children")]
+    LeafParent(String),
+    #[error("Favorite node {0} cannot be moved into itself")]
+    MoveIntoItself(String),
+    #[error(transparent)]
+    Invalid(#[from] FavoriteIssue),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+/// 启动游戏时可能产生的错误
+#[derive(Debug, Error)]
+pub enum LaunchError {
+    #[error("Game {0} has no launch path for the current device")]
+    NoPathForDevice(String),
+    #[error("Launch target not exists: {0:?}")]
+    NotExist(PathBuf),
+    #[error("Path resolution error: {0}")]
+    PathResolution(#[from] ResolveError),
+    #[error("IO error: {0:#?}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+/// 注册全局快捷键时可能产生的错误
+#[derive(Debug, Error)]
+pub enum HotkeyError {
+    #[error("Invalid hotkey {keys}: {reason}")]
+    Invalid { keys: String, reason: String },
+    #[error("Hotkey {keys} is already taken by the system or another application: {reason}")]
+    Conflict { keys: String, reason: String },
+    #[error("Hotkey {keys} is bound to both {first} and {second}")]
+    Duplicate {
+        keys: String,
+        first: String,
+        second: String,
+    },
+}
@@ -0,0 +1,322 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use thiserror::Error;
+
+use super::errors::*;
+use super::utils::from_long_path;
+
+/// 前端可识别的错误码
+///
+/// 错误码是稳定的接口，前端按 `error.codes.<ErrorCode>` 查找翻译，新增变体时不要修改已有名称
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum ErrorCode {
+    Io,
+    DiskFull,
+    PermissionDenied,
+    Deserialize,
+    ConfigInvalid,
+    ConfigVersionTooOld,
+    ConfigVersionTooNew,
+    SaveFileNotExist,
+    FileInUse,
+    ArchiveCorrupted,
+    InvalidGlob,
+    PathResolution,
+    NoPathForDevice,
+    NoEnabledSaveUnit,
+    InvalidSavePath,
+    BackupNotExist,
+    NoBackupAvailable,
+    InvalidArchive,
+    ImportConflict,
+    SnapshotRecordsCorrupted,
+    GameNotExist,
+    InvalidGameName,
+    GameNameConflict,
+    SaveUnitNotExist,
+    DeviceNotExist,
+    InvalidBackupRoot,
+    BackupRootUnavailable,
+    BackupFolderMissing,
+    BackupVerificationFailed,
+    BackupRootConflict,
+    InsufficientSpace,
+    BackupRootMigrationFailed,
+    RestorePathConflict,
+    TrashUnavailable,
+    CloudDisabled,
+    CloudBackendNotFound,
+    CloudAuthFailed,
+    CloudConfigInvalid,
+    CloudNotFound,
+    CloudUnavailable,
+    CloudCheckFailed,
+    CloudCertificateInvalid,
+    Cloud,
+    PassphraseRequired,
+    WrongPassphrase,
+    EncryptionFailed,
+    ScanFailed,
+    ScanPlatformMismatch,
+    Cancelled,
+    SnapshotEntryNotFound,
+    RestoreTargetExists,
+    NothingApplied,
+    Unexpected,
+}
+
+/// IPC 命令返回给前端的错误
+///
+/// - `code`：稳定的错误码，用于前端本地化
+/// - `message`：原始错误信息，便于记录日志
+/// - `context`：错误涉及的对象（路径、游戏名、快照日期等），没有时为 `None`
+#[derive(Debug, Clone, Error, Serialize, Deserialize, Type)]
+#[error("{message}")]
+pub struct IpcError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub context: Option<String>,
+}
+
+impl IpcError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            context: None,
+        }
+    }
+
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+type Classified = (ErrorCode, Option<String>);
+
+fn classify_io(e: &io::Error) -> Classified {
+    let code = match e.kind() {
+        io::ErrorKind::StorageFull => ErrorCode::DiskFull,
+        io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+        _ => ErrorCode::Io,
+    };
+    (code, None)
+}
+
+fn classify_cloud(e: &opendal::Error) -> Classified {
+    let code = match e.kind() {
+        opendal::ErrorKind::PermissionDenied => ErrorCode::CloudAuthFailed,
+        opendal::ErrorKind::ConfigInvalid => ErrorCode::CloudConfigInvalid,
+        opendal::ErrorKind::NotFound => ErrorCode::CloudNotFound,
+        opendal::ErrorKind::RateLimited => ErrorCode::CloudUnavailable,
+        _ if e.is_temporary() => ErrorCode::CloudUnavailable,
+        _ => ErrorCode::Cloud,
+    };
+    (code, None)
+}
+
+fn path_context(path: &std::path::Path) -> Option<String> {
+    Some(from_long_path(path).to_string_lossy().to_string())
+}
+
+fn classify_backup_file(e: &BackupFileError) -> Classified {
+    match e {
+        BackupFileError::CreateFileFailed(e) => classify_io(e),
+        BackupFileError::NotExists(path) => (ErrorCode::SaveFileNotExist, path_context(path)),
+        BackupFileError::Zip(zip::result::ZipError::Io(e)) => classify_io(e),
+        BackupFileError::Zip(_) => (ErrorCode::ArchiveCorrupted, None),
+        BackupFileError::Fs(_) => (ErrorCode::Io, None),
+        BackupFileError::NonePathError => (ErrorCode::InvalidSavePath, None),
+        BackupFileError::NoPathForDevice => (ErrorCode::NoPathForDevice, None),
+        BackupFileError::PathResolution(_) => (ErrorCode::PathResolution, None),
+        BackupFileError::InvalidGlob(_) => (ErrorCode::InvalidGlob, None),
+        BackupFileError::FileInUse(path) => (ErrorCode::FileInUse, path_context(path)),
+        BackupFileError::Cancelled => (ErrorCode::Cancelled, None),
+        BackupFileError::PathConflict(path) => (ErrorCode::RestorePathConflict, path_context(path)),
+        BackupFileError::InsufficientSpace { path, .. } => {
+            (ErrorCode::InsufficientSpace, path_context(path))
+        }
+        BackupFileError::Unexpected(_) => (ErrorCode::Unexpected, None),
+    }
+}
+
+/// 多个文件出错时以第一个错误为准，完整列表保留在 `message` 中
+fn classify_compress(e: &CompressError) -> Classified {
+    match e {
+        CompressError::Single(e) => classify_backup_file(e),
+        CompressError::Multiple(errors) => errors
+            .first()
+            .map_or((ErrorCode::Unexpected, None), classify_backup_file),
+        CompressError::Unexpected(_) => (ErrorCode::Unexpected, None),
+    }
+}
+
+fn classify_backend(e: &BackendError) -> Classified {
+    match e {
+        BackendError::Disabled => (ErrorCode::CloudDisabled, None),
+        BackendError::UnknownBackend(name) => (ErrorCode::CloudBackendNotFound, Some(name.clone())),
+        BackendError::Io(e) => classify_io(e),
+        BackendError::Cloud(e) => classify_cloud(e),
+        BackendError::ReadCloudInfo(_) | BackendError::Deserialize(_) => {
+            (ErrorCode::Deserialize, None)
+        }
+        BackendError::OperatorCheck(_) => (ErrorCode::CloudCheckFailed, None),
+        BackendError::Certificate(_) => (ErrorCode::CloudCertificateInvalid, None),
+        BackendError::PassphraseRequired => (ErrorCode::PassphraseRequired, None),
+        BackendError::WrongPassphrase(file) => (ErrorCode::WrongPassphrase, Some(file.clone())),
+        BackendError::PassphraseMismatch => (ErrorCode::WrongPassphrase, None),
+        BackendError::Encryption(_) => (ErrorCode::EncryptionFailed, None),
+        BackendError::Unexpected(_) => (ErrorCode::Unexpected, None),
+    }
+}
+
+fn classify_backup(e: &BackupError) -> Classified {
+    match e {
+        BackupError::BackupNotExist { date, .. } => (ErrorCode::BackupNotExist, Some(date.clone())),
+        BackupError::NoBackupAvailable => (ErrorCode::NoBackupAvailable, None),
+        BackupError::SnapshotEntryNotFound { entry, .. } => {
+            (ErrorCode::SnapshotEntryNotFound, Some(entry.clone()))
+        }
+        BackupError::RestoreTargetExists(path) => {
+            (ErrorCode::RestoreTargetExists, path_context(path))
+        }
+        BackupError::Backend(e) => classify_backend(e),
+        BackupError::Compress(e) => classify_compress(e),
+        BackupError::Deserialize(_) => (ErrorCode::Deserialize, None),
+        BackupError::NonePathError => (ErrorCode::InvalidSavePath, None),
+        BackupError::Io(e) => classify_io(e),
+        BackupError::InvalidArchive(missing) => (ErrorCode::InvalidArchive, Some(missing.clone())),
+        BackupError::InvalidSnapshotDate(date) => (ErrorCode::InvalidArchive, Some(date.clone())),
+        BackupError::ImportConflict { name, .. } => (ErrorCode::ImportConflict, Some(name.clone())),
+        BackupError::SnapshotRecordsCorrupted(name) => {
+            (ErrorCode::SnapshotRecordsCorrupted, Some(name.clone()))
+        }
+        BackupError::GameNotExist(name) => (ErrorCode::GameNotExist, Some(name.clone())),
+        BackupError::InvalidGameName(name) => (ErrorCode::InvalidGameName, Some(name.clone())),
+        BackupError::GameNameConflict(name) => (ErrorCode::GameNameConflict, Some(name.clone())),
+        BackupError::SaveUnitNotExist { name, .. } => {
+            (ErrorCode::SaveUnitNotExist, Some(name.clone()))
+        }
+        BackupError::DeviceNotExist(id) => (ErrorCode::DeviceNotExist, Some(id.clone())),
+        BackupError::InvalidSavePath(path) => (ErrorCode::InvalidSavePath, Some(path.clone())),
+        BackupError::NoSavePathForDevice(name) => (ErrorCode::NoPathForDevice, Some(name.clone())),
+        BackupError::NoEnabledSaveUnit(name) => (ErrorCode::NoEnabledSaveUnit, Some(name.clone())),
+        BackupError::InvalidBackupRoot(root) => (ErrorCode::InvalidBackupRoot, Some(root.clone())),
+        BackupError::BackupRootUnavailable(root) => {
+            (ErrorCode::BackupRootUnavailable, Some(root.clone()))
+        }
+        BackupError::BackupFolderMissing(path) => {
+            (ErrorCode::BackupFolderMissing, path_context(path))
+        }
+        BackupError::VerificationFailed { path, .. } => {
+            (ErrorCode::BackupVerificationFailed, path_context(path))
+        }
+        BackupError::BackupRootConflict(path) => {
+            (ErrorCode::BackupRootConflict, path_context(path))
+        }
+        BackupError::InsufficientSpace { .. } => (ErrorCode::InsufficientSpace, None),
+        BackupError::BackupRootMigrationFailed { .. } => {
+            (ErrorCode::BackupRootMigrationFailed, None)
+        }
+        BackupError::Trash { path, .. } => (ErrorCode::TrashUnavailable, path_context(path)),
+        BackupError::NothingApplied(failed) => {
+            (ErrorCode::NothingApplied, Some(failed.len().to_string()))
+        }
+        BackupError::Unexpected(_) => (ErrorCode::Unexpected, None),
+    }
+}
+
+fn classify_updater(e: &UpdaterError) -> Classified {
+    match e {
+        UpdaterError::Deserialize(_) => (ErrorCode::Deserialize, None),
+        UpdaterError::Io(e) => classify_io(e),
+        UpdaterError::Semver(_) | UpdaterError::MissingVersion => (ErrorCode::ConfigInvalid, None),
+        UpdaterError::ConfigVersionTooOld => (ErrorCode::ConfigVersionTooOld, None),
+        UpdaterError::ConfigVersionTooNew => (ErrorCode::ConfigVersionTooNew, None),
+        UpdaterError::Unexpected(_) => (ErrorCode::Unexpected, None),
+    }
+}
+
+fn classify_config(e: &ConfigError) -> Classified {
+    match e {
+        ConfigError::Deserialize(_) => (ErrorCode::Deserialize, None),
+        ConfigError::Io(e) => classify_io(e),
+        ConfigError::Backend(e) => classify_backend(e),
+        ConfigError::Tauri(_) => (ErrorCode::Unexpected, None),
+        ConfigError::Updater(e) => classify_updater(e),
+    }
+}
+
+macro_rules! impl_from_error {
+    ($($error:ty => $classify:ident),* $(,)?) => {
+        $(
+            impl From<$error> for IpcError {
+                fn from(e: $error) -> Self {
+                    let (code, context) = $classify(&e);
+                    Self {
+                        code,
+                        message: e.to_string(),
+                        context,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_error! {
+    io::Error => classify_io,
+    BackupFileError => classify_backup_file,
+    CompressError => classify_compress,
+    BackendError => classify_backend,
+    BackupError => classify_backup,
+    UpdaterError => classify_updater,
+    ConfigError => classify_config,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn nested_errors_keep_the_inner_code() {
+        let e: IpcError = BackupError::from(BackendError::PassphraseRequired).into();
+        assert_eq!(e.code, ErrorCode::PassphraseRequired);
+
+        let in_use = BackupFileError::FileInUse(PathBuf::from("save.dat"));
+        let e: IpcError = BackupError::Compress(CompressError::Multiple(vec![
+            in_use,
+            BackupFileError::NoPathForDevice,
+        ]))
+        .into();
+        assert_eq!(e.code, ErrorCode::FileInUse);
+        assert_eq!(e.context.as_deref(), Some("save.dat"));
+    }
+
+    #[test]
+    fn disk_full_is_distinguished_from_other_io_errors() {
+        let full = io::Error::from(io::ErrorKind::StorageFull);
+        let e: IpcError = BackupError::Compress(BackupFileError::from(full).into()).into();
+        assert_eq!(e.code, ErrorCode::DiskFull);
+
+        let e: IpcError = ConfigError::from(io::Error::other("broken")).into();
+        assert_eq!(e.code, ErrorCode::Io);
+    }
+
+    #[test]
+    fn serializes_code_as_a_plain_string() {
+        let e = IpcError::new(ErrorCode::GameNotExist, "Game a not exists").with_context("a");
+        assert_eq!(
+            serde_json::to_value(e).unwrap(),
+            serde_json::json!({
+                "code": "GameNotExist",
+                "message": "Game a not exists",
+                "context": "a"
+            })
+        );
+    }
+}
@@ -1,7 +1,11 @@
 mod errors;
+mod ipc_error;
+mod redaction;
 mod traits;
 mod utils;
 
 pub use errors::*;
+pub use ipc_error::*;
+pub use redaction::*;
 pub use traits::*;
 pub use utils::*;
@@ -1,4 +1,9 @@
-use log::error;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use log::{error, warn};
+use serde::de::DeserializeOwned;
 
 pub fn show_notification<T1: AsRef<str>, T2: AsRef<str>>(title: T1, body: T2) {
     if let Err(e) = notify_rust::Notification::new()
@@ -10,3 +15,141 @@ pub fn show_notification<T1: AsRef<str>, T2: AsRef<str>>(title: T1, body: T2) {
         error!(target:"rgsm::quick_action", "Failed to show notification: {}", e);
     }
 }
+
+/// The rolling copy of the previous content kept by `write_atomic`
+pub fn backup_file_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Replace the file at `path` without leaving a truncated file behind on a crash
+///
+/// The content goes to a temporary file in the same directory, which is synced and
+/// then renamed over `path`. The previous content is kept in `backup_file_path`.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.is_file() {
+        fs::copy(path, backup_file_path(path))?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+/// Parse the JSON file at `path`, falling back to its backup when it cannot be parsed
+///
+/// A readable backup is restored over the broken file. The flag tells whether that happened,
+/// if the backup is unusable too the original parse error is returned.
+pub fn read_json_or_backup<T, E>(path: &Path) -> Result<(T, bool), E>
+where
+    T: DeserializeOwned,
+    E: From<io::Error> + From<serde_json::Error>,
+{
+    let err = match serde_json::from_slice(&fs::read(path)?) {
+        Ok(value) => return Ok((value, false)),
+        Err(e) => e,
+    };
+    let backup_path = backup_file_path(path);
+    let backup = match fs::read(&backup_path) {
+        Ok(backup) => backup,
+        Err(_) => return Err(err.into()),
+    };
+    let value = match serde_json::from_slice(&backup) {
+        Ok(value) => value,
+        Err(_) => return Err(err.into()),
+    };
+    warn!(target:"rgsm::preclude", "{:?} is corrupted ({err}), restored it from {:?}", path, backup_path);
+    // 保留损坏的文件以便排查，不覆盖好的备份
+    let mut broken_name = path.file_name().unwrap_or_default().to_os_string();
+    broken_name.push(".broken");
+    fs::rename(path, path.with_file_name(broken_name))?;
+    fs::copy(&backup_path, path)?;
+    Ok((value, true))
+}
+
+/// 将 Windows 上的本地盘符路径转换为 `\\?\` 开头的扩展长度路径，以突破 260 字符（MAX_PATH）的限制
+///
+/// - 相对路径会先转换为绝对路径，扩展长度路径不再解析 `.`、`..` 与 `/`
+/// - UNC、已带前缀的路径以及其他平台保持不变
+/// - 转换后的路径仅用于访问文件系统，不应写入配置或显示给用户
+#[cfg(windows)]
+pub fn to_long_path(path: &Path) -> PathBuf {
+    use std::path::{Component, Prefix};
+
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    match absolute.components().next() {
+        Some(Component::Prefix(prefix)) if matches!(prefix.kind(), Prefix::Disk(_)) => {
+            let mut long = std::ffi::OsString::from(r"\\?\");
+            long.push(absolute.as_os_str());
+            PathBuf::from(long)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn to_long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// 去掉 `to_long_path` 添加的 `\\?\` 前缀，用于显示或写入配置
+pub fn from_long_path(path: &Path) -> PathBuf {
+    let short = path
+        .to_str()
+        .and_then(|s| s.strip_prefix(r"\\?\"))
+        .filter(|s| !s.starts_with(r"UNC\"));
+    match short {
+        Some(short) if cfg!(windows) => PathBuf::from(short),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomic_write_keeps_backup_for_recovery() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let path = dir.path().join("Backups.json");
+        write_atomic(&path, b"[1]").unwrap();
+        write_atomic(&path, b"[2]").unwrap();
+        assert_eq!(fs::read(backup_file_path(&path)).unwrap(), b"[1]");
+
+        // 模拟写入中途崩溃留下的截断文件
+        fs::write(&path, b"[2").unwrap();
+        let (value, recovered): (Vec<u32>, bool) =
+            read_json_or_backup::<_, anyhow::Error>(&path).unwrap();
+        assert_eq!((value, recovered), (vec![1], true));
+        assert_eq!(fs::read(&path).unwrap(), b"[1]");
+
+        let (value, recovered): (Vec<u32>, bool) =
+            read_json_or_backup::<_, anyhow::Error>(&path).unwrap();
+        assert_eq!((value, recovered), (vec![1], false));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_prefix_only_applies_to_disk_paths() {
+        assert_eq!(
+            to_long_path(Path::new(r"C:\Games/Save\..\Data")),
+            Path::new(r"\\?\C:\Games\Data")
+        );
+        for path in [r"\\server\share\Save", r"\\?\C:\Save"] {
+            assert_eq!(to_long_path(Path::new(path)), Path::new(path));
+        }
+        assert_eq!(
+            from_long_path(&to_long_path(Path::new(r"C:\Games\Data"))),
+            Path::new(r"C:\Games\Data")
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use log::{info, warn};
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::config::get_config;
+use crate::quick_actions::{QuickActionManager, QuickActionType};
+
+use super::utils::notify_invalid_deep_link;
+
+/// The custom URI scheme, also listed under `plugins.deep-link` in `tauri.conf.json`
+const SCHEME: &str = "rgsm";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeepLinkAction {
+    Apply,
+    Backup,
+}
+
+/// What a link like `rgsm://backup?game=Elden%20Ring` asks for, `game` is `None` for the
+/// quick action game
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeepLinkTarget {
+    action: DeepLinkAction,
+    game: Option<String>,
+}
+
+fn parse_deep_link(url: &Url) -> Option<DeepLinkTarget> {
+    if url.scheme() != SCHEME {
+        return None;
+    }
+    // `rgsm://backup` puts the action in the host, `rgsm:backup` in the path
+    let action = match url.host_str() {
+        Some(host) => host,
+        None => url.path().trim_matches('/'),
+    };
+    let action = match action.to_ascii_lowercase().as_str() {
+        "backup" => DeepLinkAction::Backup,
+        "apply" => DeepLinkAction::Apply,
+        _ => return None,
+    };
+    let game = url
+        .query_pairs()
+        .find(|(key, _)| key == "game")
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|game| !game.is_empty());
+    Some(DeepLinkTarget { action, game })
+}
+
+/// Whether the arguments of a second instance carry a link, which the deep-link plugin
+/// forwards to `handle_deep_link`
+pub fn is_deep_link_args(args: &[String]) -> bool {
+    let prefix = format!("{SCHEME}:");
+    args.iter().any(|arg| arg.starts_with(&prefix))
+}
+
+/// Run the quick action a link asks for, the same way a hotkey would
+fn handle_deep_link(app: &AppHandle, url: &Url) {
+    info!(target:"rgsm::quick_action::deep_link", "Opened link: {}", url);
+    let Some(target) = parse_deep_link(url) else {
+        warn!(target:"rgsm::quick_action::deep_link", "Unrecognized link: {}", url);
+        notify_invalid_deep_link(app, url.as_str(), None);
+        return;
+    };
+    // 打开链接时再查找游戏，保证使用最新的配置
+    let game = match &target.game {
+        Some(name) => {
+            let game = get_config()
+                .ok()
+                .and_then(|c| c.games.into_iter().find(|g| &g.name == name));
+            if game.is_none() {
+                warn!(target:"rgsm::quick_action::deep_link", "Game {} in link not found", name);
+                notify_invalid_deep_link(app, url.as_str(), Some(name));
+                return;
+            }
+            game
+        }
+        None => None,
+    };
+    let manager = app.state::<Arc<QuickActionManager>>();
+    match target.action {
+        DeepLinkAction::Apply => manager.trigger_game_apply(QuickActionType::DeepLink, game),
+        DeepLinkAction::Backup => manager.trigger_game_backup(QuickActionType::DeepLink, game),
+    }
+}
+
+/// Listen for links and handle the one the app was started with
+///
+/// Must run after the quick action manager is managed.
+pub fn setup_deep_link(app: &mut tauri::App) -> anyhow::Result<()> {
+    // 安装包会注册协议，便携版与开发时需要在运行时注册
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    {
+        if let Err(e) = app.deep_link().register_all() {
+            warn!(target:"rgsm::quick_action::deep_link", "Failed to register the {} scheme: {e}", SCHEME);
+        }
+    }
+
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_deep_link(&handle, &url);
+        }
+    });
+    if let Some(urls) = app.deep_link().get_current()? {
+        for url in urls {
+            handle_deep_link(app.handle(), &url);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Option<DeepLinkTarget> {
+        parse_deep_link(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn links_are_parsed() {
+        assert_eq!(
+            parse("rgsm://backup"),
+            Some(DeepLinkTarget {
+                action: DeepLinkAction::Backup,
+                game: None,
+            })
+        );
+        assert_eq!(
+            parse("rgsm://Apply/?game=Elden%20Ring"),
+            Some(DeepLinkTarget {
+                action: DeepLinkAction::Apply,
+                game: Some("Elden Ring".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("rgsm:backup?game="),
+            Some(DeepLinkTarget {
+                action: DeepLinkAction::Backup,
+                game: None,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_links_are_rejected() {
+        assert_eq!(parse("rgsm://delete?game=Celeste"), None);
+        assert_eq!(parse("https://backup"), None);
+        assert_eq!(parse("rgsm://"), None);
+        assert!(is_deep_link_args(&[
+            "rgsm.exe".into(),
+            "rgsm://backup".into()
+        ]));
+        assert!(!is_deep_link_args(&["rgsm.exe".into(), "--config".into()]));
+    }
+}
@@ -1,68 +1,242 @@
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
 
-use log::info;
-use tauri::{App, Manager};
+use log::{info, warn};
+use tauri::{AppHandle, Manager};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
 use crate::{
-    config::Config,
+    config::{Config, get_config},
+    preclude::*,
     quick_actions::{QuickActionManager, QuickActionType},
 };
 
-pub fn setup_hotkeys(config: &Config, app: &mut App) -> anyhow::Result<()> {
-    info!(target:"rgsm::quick_action::hotkeys", "Setting up hotkeys");
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum HotkeyAction {
+    Apply,
+    Backup,
+}
 
-    let manager_state: tauri::State<Arc<QuickActionManager>> = app.state();
-    let manager = Arc::clone(manager_state.inner());
+/// What a shortcut does, `game` is `None` for the global pair acting on the quick action game
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct HotkeyTarget {
+    action: HotkeyAction,
+    game: Option<String>,
+}
 
-    let apply_keys = config
-        .quick_action
-        .hotkeys
-        .apply
-        .clone()
-        .into_iter()
-        .filter(|x| !x.is_empty())
-        .collect::<Vec<_>>();
-    let backup_keys = config
-        .quick_action
-        .hotkeys
-        .backup
-        .clone()
-        .into_iter()
+impl fmt::Display for HotkeyTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.action {
+            HotkeyAction::Apply => "apply",
+            HotkeyAction::Backup => "backup",
+        };
+        match &self.game {
+            Some(game) => write!(f, "{action} of {game}"),
+            None => write!(f, "{action} of the quick action game"),
+        }
+    }
+}
+
+/// Shortcuts currently registered by us, kept so a reload only touches the changed ones
+#[derive(Debug, Default)]
+pub struct RegisteredHotkeys {
+    shortcuts: HashMap<HotkeyTarget, Shortcut>,
+}
+
+/// Join the configured keys, `None` means the hotkey is not set
+fn parse_keys(keys: &[String]) -> Result<Option<(String, Shortcut)>, HotkeyError> {
+    let keys = keys
+        .iter()
         .filter(|x| !x.is_empty())
+        .cloned()
         .collect::<Vec<_>>();
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    let keys = keys.join("+");
+    let shortcut = Shortcut::try_from(keys.as_str()).map_err(|e| HotkeyError::Invalid {
+        keys: keys.clone(),
+        reason: e.to_string(),
+    })?;
+    Ok(Some((keys, shortcut)))
+}
 
-    if !apply_keys.is_empty() {
-        info!(
-            target:"rgsm::quick_action::hotkeys",
-            "Registering apply hotkey: {}", apply_keys.join("+")
-        );
-        let apply_manager = Arc::clone(&manager);
-        let apply_shortcut = Shortcut::try_from(apply_keys.join("+"))?;
-        app.global_shortcut()
-            .on_shortcut(apply_shortcut, move |_app, _shortcut, event| {
-                if event.state() == ShortcutState::Released {
-                    info!(target:"rgsm::quick_action::hotkeys", "Apply hotkey pressed");
-                    apply_manager.trigger_apply(QuickActionType::Hotkey);
-                }
-            })?;
+/// All hotkeys in `config`, the global pair first
+///
+/// A shortcut already used by an earlier entry is dropped and reported as
+/// `HotkeyError::Duplicate` naming both games
+fn desired_hotkeys(config: &Config) -> (Vec<(HotkeyTarget, String, Shortcut)>, Vec<HotkeyError>) {
+    let quick_action = &config.quick_action;
+    let mut entries = vec![
+        (HotkeyAction::Apply, None, &quick_action.hotkeys.apply),
+        (HotkeyAction::Backup, None, &quick_action.hotkeys.backup),
+    ];
+    for binding in &quick_action.game_hotkeys {
+        let game = Some(binding.game_name.clone());
+        entries.push((HotkeyAction::Apply, game.clone(), &binding.apply_keys));
+        entries.push((HotkeyAction::Backup, game, &binding.backup_keys));
     }
 
-    if !backup_keys.is_empty() {
-        info!(
-            target:"rgsm::quick_action::hotkeys",
-            "Registering backup hotkey: {}", backup_keys.join("+")
-        );
-        let backup_manager = Arc::clone(&manager);
-        let backup_shortcut = Shortcut::try_from(backup_keys.join("+"))?;
-        app.global_shortcut()
-            .on_shortcut(backup_shortcut, move |_app, _shortcut, event| {
-                if event.state() == ShortcutState::Released {
-                    info!(target:"rgsm::quick_action::hotkeys", "Backup hotkey pressed");
-                    backup_manager.trigger_backup(QuickActionType::Hotkey);
+    let mut desired: Vec<(HotkeyTarget, String, Shortcut)> = Vec::new();
+    let mut errors = Vec::new();
+    for (action, game, keys) in entries {
+        let target = HotkeyTarget { action, game };
+        let (keys, shortcut) = match parse_keys(keys) {
+            Ok(Some(parsed)) => parsed,
+            Ok(None) => continue,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        if let Some((first, _, _)) = desired.iter().find(|(_, _, s)| *s == shortcut) {
+            errors.push(HotkeyError::Duplicate {
+                keys,
+                first: first.to_string(),
+                second: target.to_string(),
+            });
+            continue;
+        }
+        desired.push((target, keys, shortcut));
+    }
+    (desired, errors)
+}
+
+fn register(
+    app: &AppHandle,
+    target: HotkeyTarget,
+    keys: &str,
+    shortcut: Shortcut,
+) -> Result<(), HotkeyError> {
+    info!(target:"rgsm::quick_action::hotkeys", "Registering hotkey {} for {}", keys, target);
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state() != ShortcutState::Released {
+                return;
+            }
+            info!(target:"rgsm::quick_action::hotkeys", "Hotkey pressed: {}", target);
+            // 按下时再查找游戏，保证使用最新的配置
+            let game = match &target.game {
+                Some(name) => {
+                    let game = get_config()
+                        .ok()
+                        .and_then(|c| c.games.into_iter().find(|g| &g.name == name));
+                    if game.is_none() {
+                        warn!(target:"rgsm::quick_action::hotkeys", "Game {} bound to hotkey not found", name);
+                        return;
+                    }
+                    game
                 }
-            })?;
+                None => None,
+            };
+            let manager = app.state::<Arc<QuickActionManager>>();
+            match target.action {
+                HotkeyAction::Apply => manager.trigger_game_apply(QuickActionType::Hotkey, game),
+                HotkeyAction::Backup => manager.trigger_game_backup(QuickActionType::Hotkey, game),
+            }
+        })
+        .map_err(|e| HotkeyError::Conflict {
+            keys: keys.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Bring the registered shortcuts in line with `config`
+///
+/// Unchanged hotkeys stay registered. Every changed one is unregistered first, so
+/// swapping keys between entries works. Registration keeps going after a
+/// failure and the first error is returned.
+pub fn reload_hotkeys(
+    app: &AppHandle,
+    registered: &mut RegisteredHotkeys,
+    config: &Config,
+) -> Result<(), HotkeyError> {
+    let (desired, mut errors) = desired_hotkeys(config);
+
+    let stale: Vec<HotkeyTarget> = registered
+        .shortcuts
+        .iter()
+        .filter(|(target, shortcut)| {
+            !desired
+                .iter()
+                .any(|(t, _, s)| t == *target && s == *shortcut)
+        })
+        .map(|(target, _)| target.clone())
+        .collect();
+    for target in stale {
+        if let Some(old) = registered.shortcuts.remove(&target) {
+            info!(target:"rgsm::quick_action::hotkeys", "Unregistering hotkey for {}", target);
+            if let Err(e) = app.global_shortcut().unregister(old) {
+                warn!(target:"rgsm::quick_action::hotkeys", "Failed to unregister hotkey for {}: {e}", target);
+            }
+        }
+    }
+
+    for (target, keys, shortcut) in desired {
+        if registered.shortcuts.contains_key(&target) {
+            continue;
+        }
+        match register(app, target.clone(), &keys, shortcut) {
+            Ok(()) => {
+                registered.shortcuts.insert(target, shortcut);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    for e in &errors {
+        warn!(target:"rgsm::quick_action::hotkeys", "{e}");
+    }
+    match errors.into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::QuickActionGameHotkeys;
+
+    fn keys(k: &[&str]) -> Vec<String> {
+        k.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_keys_skips_empty_slots() {
+        assert!(parse_keys(&keys(&["", "", ""])).unwrap().is_none());
+        let (joined, _) = parse_keys(&keys(&["Ctrl", "", "B"])).unwrap().unwrap();
+        assert_eq!(joined, "Ctrl+B");
+        assert!(matches!(
+            parse_keys(&keys(&["Ctrl", "NotAKey"])),
+            Err(HotkeyError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn duplicate_game_hotkeys_name_both_games() {
+        let mut config = Config::default();
+        config.quick_action.game_hotkeys = vec![
+            QuickActionGameHotkeys {
+                game_name: "Game A".into(),
+                backup_keys: keys(&["Ctrl", "F5"]),
+                apply_keys: Vec::new(),
+            },
+            QuickActionGameHotkeys {
+                game_name: "Game B".into(),
+                backup_keys: keys(&["Ctrl", "F5"]),
+                apply_keys: keys(&["Ctrl", "F6"]),
+            },
+        ];
+        let (desired, errors) = desired_hotkeys(&config);
+        assert_eq!(desired.len(), 2);
+        match &errors[..] {
+            [HotkeyError::Duplicate { first, second, .. }] => {
+                assert_eq!(first, "backup of Game A");
+                assert_eq!(second, "backup of Game B");
+            }
+            other => panic!("unexpected errors: {other:?}"),
+        }
     }
-    info!(target:"rgsm::quick_action::hotkeys","All hotkey are registered.");
-    Ok(())
 }
@@ -8,26 +8,62 @@ use std::{
 use anyhow::Context;
 use log::{info, warn};
 use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use specta::Type;
 use tauri::AppHandle;
+use tauri_specta::Event;
 use tokio::sync::{
+    Mutex as AsyncMutex, OwnedMutexGuard,
     mpsc::{self, UnboundedReceiver, UnboundedSender},
-    oneshot,
+    oneshot, watch,
 };
 use tokio::time::{self, Sleep};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    backup::Game,
-    config::{get_config, set_config},
+    backup::{Game, check_backup_root},
+    config::{
+        BackupSchedule, Config, ConfigManager, QuickActionBusyBehavior, get_config, set_config,
+    },
+    preclude::HotkeyError,
 };
 
+use super::hotkeys::{self, RegisteredHotkeys};
+use super::tray::build_tray_menu;
+use super::utils::{QuickActionOperation, notify_action_in_progress, notify_backup_root_status};
 use super::{QuickActionType, quick_apply, quick_backup};
 
 const TIMER_TICK_SECONDS: u64 = 60;
 
+/// State of the auto backup timer, emitted on every tick and whenever the interval changes
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct AutoBackupTick {
+    pub elapsed_minutes: u32,
+    /// 0 means auto backup is off
+    pub interval_minutes: u32,
+    pub game_name: Option<String>,
+    /// Auto backups are paused because the backup root cannot be reached
+    pub paused: bool,
+}
+
+/// Emitted when a per-game backup schedule is due, before its backup starts
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct BackupScheduleFired {
+    pub game_name: String,
+    pub interval_minutes: u32,
+}
+
+/// A backup schedule and the minutes since it last ran
+#[derive(Debug, Clone)]
+struct ScheduleCountdown {
+    schedule: BackupSchedule,
+    elapsed_minutes: u32,
+}
+
 pub enum QuickActionCommand {
     RegisterTrayItems {
-        game_item: tauri::menu::MenuItem<tauri::Wry>,
+        game_menu: tauri::menu::Submenu<tauri::Wry>,
+        game_items: HashMap<String, tauri::menu::CheckMenuItem<tauri::Wry>>,
         duration_items: HashMap<u32, tauri::menu::CheckMenuItem<tauri::Wry>>,
     },
     SetCurrentGame {
@@ -37,8 +73,14 @@ pub enum QuickActionCommand {
     UpdateInterval {
         minutes: u32,
     },
-    TriggerBackup(QuickActionType),
-    TriggerApply(QuickActionType),
+    /// `None` uses the configured quick action game
+    RebuildTrayMenu,
+    TriggerBackup(QuickActionType, Option<Game>),
+    TriggerApply(QuickActionType, Option<Game>),
+    /// Re-check the backup root, pausing or resuming auto backups
+    CheckBackupRoot {
+        respond_to: Option<oneshot::Sender<bool>>,
+    },
 }
 
 #[derive(Default)]
@@ -46,13 +88,20 @@ struct QuickActionState {
     current_game: Option<Game>,
     auto_backup_minutes: u32,
     elapsed_minutes: u32,
-    tray_game_item: Option<tauri::menu::MenuItem<tauri::Wry>>,
+    schedules: Vec<ScheduleCountdown>,
+    /// The unreachable backup root while auto backups are paused
+    unavailable_backup_root: Option<String>,
+    tray_game_menu: Option<tauri::menu::Submenu<tauri::Wry>>,
+    tray_game_items: HashMap<String, tauri::menu::CheckMenuItem<tauri::Wry>>,
     tray_duration_items: HashMap<u32, tauri::menu::CheckMenuItem<tauri::Wry>>,
 }
 
 pub struct QuickActionManager {
     app: AppHandle,
     state: Mutex<QuickActionState>,
+    hotkeys: Mutex<RegisteredHotkeys>,
+    /// One lock per game name, held while a backup or apply of that game runs
+    action_locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
     command_tx: UnboundedSender<QuickActionCommand>,
     cancel_token: CancellationToken,
 }
@@ -67,16 +116,24 @@ impl QuickActionManager {
     pub fn new(app: &AppHandle) -> Arc<Self> {
         let cancel_token = CancellationToken::new();
         let (command_tx, command_rx) = mpsc::unbounded_channel();
-        let current_game = get_config()
-            .ok()
-            .and_then(|cfg| cfg.quick_action.quick_action_game.clone());
+        let quick_action = get_config().ok().map(|cfg| cfg.quick_action);
+        let current_game = quick_action
+            .as_ref()
+            .and_then(|q| q.quick_action_game.clone());
+        // 只恢复数值用于托盘勾选，计时器由 setup 启动
+        let auto_backup_minutes = quick_action.as_ref().map_or(0, |q| q.auto_backup_minutes);
+        let schedules = quick_action.map_or_else(Vec::new, |q| sync_countdowns(&[], &q.schedules));
 
         let manager = Arc::new(Self {
             app: app.clone(),
             state: Mutex::new(QuickActionState {
                 current_game,
+                auto_backup_minutes,
+                schedules,
                 ..Default::default()
             }),
+            hotkeys: Mutex::new(RegisteredHotkeys::default()),
+            action_locks: Mutex::new(HashMap::new()),
             command_tx,
             cancel_token: cancel_token.clone(),
         });
@@ -109,36 +166,85 @@ impl QuickActionManager {
     }
 
     pub fn trigger_backup(&self, trigger: QuickActionType) {
+        self.trigger_game_backup(trigger, None);
+    }
+
+    pub fn trigger_apply(&self, trigger: QuickActionType) {
+        self.trigger_game_apply(trigger, None);
+    }
+
+    pub fn trigger_game_backup(&self, trigger: QuickActionType, game: Option<Game>) {
         if let Err(err) = self
             .command_tx
-            .send(QuickActionCommand::TriggerBackup(trigger))
+            .send(QuickActionCommand::TriggerBackup(trigger, game))
         {
             warn!(target: "rgsm::quick_action::manager", "Failed to send TriggerBackup command: {err}");
         }
     }
 
-    pub fn trigger_apply(&self, trigger: QuickActionType) {
+    pub fn trigger_game_apply(&self, trigger: QuickActionType, game: Option<Game>) {
         if let Err(err) = self
             .command_tx
-            .send(QuickActionCommand::TriggerApply(trigger))
+            .send(QuickActionCommand::TriggerApply(trigger, game))
         {
             warn!(target: "rgsm::quick_action::manager", "Failed to send TriggerApply command: {err}");
         }
     }
 
+    /// Re-check the backup root in the background, e.g. after a backup failed on it
+    pub fn check_backup_root(&self) {
+        if let Err(err) = self
+            .command_tx
+            .send(QuickActionCommand::CheckBackupRoot { respond_to: None })
+        {
+            warn!(target: "rgsm::quick_action::manager", "Failed to send CheckBackupRoot command: {err}");
+        }
+    }
+
+    /// Re-check the backup root now and return whether it is available
+    pub async fn retry_backup_root(&self) -> anyhow::Result<bool> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(QuickActionCommand::CheckBackupRoot {
+                respond_to: Some(tx),
+            })
+            .context("failed to send CheckBackupRoot command")?;
+        rx.await.context("manager dropped CheckBackupRoot response")
+    }
+
     pub fn register_tray_items(
         &self,
-        game_item: tauri::menu::MenuItem<tauri::Wry>,
+        game_menu: tauri::menu::Submenu<tauri::Wry>,
+        game_items: HashMap<String, tauri::menu::CheckMenuItem<tauri::Wry>>,
         duration_items: HashMap<u32, tauri::menu::CheckMenuItem<tauri::Wry>>,
     ) {
         if let Err(err) = self.command_tx.send(QuickActionCommand::RegisterTrayItems {
-            game_item,
+            game_menu,
+            game_items,
             duration_items,
         }) {
             warn!(target: "rgsm::quick_action::manager", "Failed to send RegisterTrayItems command: {err}");
         }
     }
 
+    /// Rebuild the tray menu after games were added or removed
+    pub fn rebuild_tray_menu(&self) {
+        if let Err(err) = self.command_tx.send(QuickActionCommand::RebuildTrayMenu) {
+            warn!(target: "rgsm::quick_action::manager", "Failed to send RebuildTrayMenu command: {err}");
+        }
+    }
+
+    /// Re-register global shortcuts after the hotkey settings changed
+    ///
+    /// Runs synchronously so the caller learns about conflicting shortcuts
+    pub fn reload_hotkeys(&self, config: &Config) -> Result<(), HotkeyError> {
+        let mut registered = self
+            .hotkeys
+            .lock()
+            .expect("QuickActionManager hotkeys poisoned");
+        hotkeys::reload_hotkeys(&self.app, &mut registered, config)
+    }
+
     pub fn app_handle(&self) -> AppHandle {
         self.app.clone()
     }
@@ -147,10 +253,65 @@ impl QuickActionManager {
         self.lock_state().auto_backup_minutes
     }
 
+    pub fn auto_backup_status(&self) -> AutoBackupTick {
+        let state = self.lock_state();
+        AutoBackupTick {
+            elapsed_minutes: state.elapsed_minutes,
+            interval_minutes: state.auto_backup_minutes,
+            game_name: state.current_game.as_ref().map(|game| game.name.clone()),
+            paused: state.unavailable_backup_root.is_some(),
+        }
+    }
+
+    /// The backup root that cannot be reached, `None` unless auto backups are paused
+    pub fn unavailable_backup_root(&self) -> Option<String> {
+        self.lock_state().unavailable_backup_root.clone()
+    }
+
     pub fn current_game(&self) -> Option<Game> {
         self.lock_state().current_game.clone()
     }
 
+    /// Schedules that are currently counting down
+    pub fn active_schedules(&self) -> Vec<BackupSchedule> {
+        self.lock_state()
+            .schedules
+            .iter()
+            .map(|countdown| countdown.schedule.clone())
+            .collect()
+    }
+
+    /// Whether a quick action or restore of `game_name` is running
+    pub(crate) fn is_game_busy(&self, game_name: &str) -> bool {
+        self.action_locks
+            .lock()
+            .expect("QuickActionManager action locks poisoned")
+            .get(game_name)
+            .is_some_and(|lock| lock.try_lock().is_err())
+    }
+
+    /// Wait for or give up on the running action of `game_name`, depending on `behavior`
+    ///
+    /// Returns `None` when the action should be dropped. The guard is released when
+    /// dropped, which also happens if the action panics.
+    pub(crate) async fn lock_game(
+        &self,
+        game_name: &str,
+        behavior: QuickActionBusyBehavior,
+    ) -> Option<OwnedMutexGuard<()>> {
+        let lock = Arc::clone(
+            self.action_locks
+                .lock()
+                .expect("QuickActionManager action locks poisoned")
+                .entry(game_name.to_string())
+                .or_default(),
+        );
+        match behavior {
+            QuickActionBusyBehavior::Drop => lock.try_lock_owned().ok(),
+            QuickActionBusyBehavior::Queue => Some(lock.lock_owned().await),
+        }
+    }
+
     fn lock_state(&self) -> std::sync::MutexGuard<'_, QuickActionState> {
         self.state
             .lock()
@@ -161,6 +322,8 @@ impl QuickActionManager {
 struct QuickActionWorker {
     manager: Arc<QuickActionManager>,
     command_rx: UnboundedReceiver<QuickActionCommand>,
+    /// Follows config changes made outside the manager, e.g. by a cloud download
+    config_rx: watch::Receiver<Option<Config>>,
     timer_sleep: Option<Pin<Box<Sleep>>>,
     cancel_token: CancellationToken,
 }
@@ -174,9 +337,12 @@ impl QuickActionWorker {
         let mut worker = Self {
             manager,
             command_rx,
+            config_rx: ConfigManager::global().subscribe(),
             timer_sleep: None,
             cancel_token,
         };
+        // 只有按游戏的定时备份时也需要计时
+        worker.ensure_timer();
 
         tauri::async_runtime::spawn(async move { worker.run().await });
     }
@@ -199,6 +365,9 @@ impl QuickActionWorker {
                             break;
                         }
                     }
+                    Ok(()) = self.config_rx.changed() => {
+                        self.handle_config_changed().await;
+                    }
                 }
             } else {
                 tokio::select! {
@@ -212,6 +381,9 @@ impl QuickActionWorker {
                             None => break,
                         }
                     }
+                    Ok(()) = self.config_rx.changed() => {
+                        self.handle_config_changed().await;
+                    }
                 }
             }
         }
@@ -223,9 +395,11 @@ impl QuickActionWorker {
     async fn handle_command(&mut self, command: QuickActionCommand) {
         match command {
             QuickActionCommand::RegisterTrayItems {
-                game_item,
+                game_menu,
+                game_items,
                 duration_items,
-            } => self.handle_register_tray(game_item, duration_items),
+            } => self.handle_register_tray(game_menu, game_items, duration_items),
+            QuickActionCommand::RebuildTrayMenu => self.handle_rebuild_tray_menu(),
             QuickActionCommand::SetCurrentGame { game, respond_to } => {
                 let result = self.handle_set_current_game(game).await;
                 let _ = respond_to.send(result);
@@ -233,24 +407,30 @@ impl QuickActionWorker {
             QuickActionCommand::UpdateInterval { minutes } => {
                 self.handle_update_interval(minutes).await;
             }
-            QuickActionCommand::TriggerBackup(trigger) => {
-                let app = self.manager.app_handle();
-                quick_backup(&app, trigger).await;
+            QuickActionCommand::TriggerBackup(trigger, game) => {
+                self.spawn_action(QuickActionOperation::Backup, trigger, game);
             }
-            QuickActionCommand::TriggerApply(trigger) => {
-                let app = self.manager.app_handle();
-                quick_apply(&app, trigger).await;
+            QuickActionCommand::TriggerApply(trigger, game) => {
+                self.spawn_action(QuickActionOperation::Apply, trigger, game);
+            }
+            QuickActionCommand::CheckBackupRoot { respond_to } => {
+                let available = self.refresh_backup_root();
+                if let Some(respond_to) = respond_to {
+                    let _ = respond_to.send(available);
+                }
             }
         }
     }
 
     fn handle_register_tray(
         &mut self,
-        game_item: tauri::menu::MenuItem<tauri::Wry>,
+        game_menu: tauri::menu::Submenu<tauri::Wry>,
+        game_items: HashMap<String, tauri::menu::CheckMenuItem<tauri::Wry>>,
         duration_items: HashMap<u32, tauri::menu::CheckMenuItem<tauri::Wry>>,
     ) {
         let mut state = self.manager.lock_state();
-        state.tray_game_item = Some(game_item);
+        state.tray_game_menu = Some(game_menu);
+        state.tray_game_items = game_items;
         state.tray_duration_items = duration_items;
 
         drop(state);
@@ -258,6 +438,19 @@ impl QuickActionWorker {
         self.refresh_tray_duration_checks();
     }
 
+    fn handle_rebuild_tray_menu(&mut self) {
+        let app = self.manager.app_handle();
+        let result = build_tray_menu(&app, &self.manager).and_then(|menu| {
+            app.tray_by_id("tray_icon")
+                .ok_or_else(|| anyhow::anyhow!("Cannot get tray"))?
+                .set_menu(Some(menu))?;
+            Ok(())
+        });
+        if let Err(err) = result {
+            warn!(target: "rgsm::quick_action::manager", "Failed to rebuild tray menu: {err:?}");
+        }
+    }
+
     async fn handle_set_current_game(&mut self, game: Game) -> anyhow::Result<()> {
         let mut config = get_config().context("failed to load config")?;
         config.quick_action.quick_action_game = Some(game.clone());
@@ -287,9 +480,16 @@ impl QuickActionWorker {
             state.elapsed_minutes = 0;
         }
         self.refresh_tray_duration_checks();
+        self.emit_tick();
+        if let Err(err) = persist_interval(minutes).await {
+            warn!(
+                target: "rgsm::quick_action::manager",
+                "Failed to persist auto backup interval: {err:?}"
+            );
+        }
 
         if minutes == 0 {
-            self.timer_sleep = None;
+            self.ensure_timer();
             return;
         }
 
@@ -298,55 +498,266 @@ impl QuickActionWorker {
         ))));
     }
 
+    /// Keep the tick timer running while the tray timer or any schedule needs it, or while
+    /// the backup root has to be re-checked
+    fn ensure_timer(&mut self) {
+        let needed = {
+            let state = self.manager.lock_state();
+            state.auto_backup_minutes > 0
+                || !state.schedules.is_empty()
+                || state.unavailable_backup_root.is_some()
+        };
+        if !needed {
+            self.timer_sleep = None;
+        } else if self.timer_sleep.is_none() {
+            self.timer_sleep = Some(Box::pin(time::sleep(Duration::from_secs(
+                TIMER_TICK_SECONDS,
+            ))));
+        }
+    }
+
+    /// Apply interval, game and hotkey changes from a config written elsewhere
+    ///
+    /// Changes made by the manager itself end up here too and are no-ops.
+    async fn handle_config_changed(&mut self) {
+        let Some(config) = self.config_rx.borrow_and_update().clone() else {
+            return;
+        };
+        let quick_action = &config.quick_action;
+
+        if quick_action.auto_backup_minutes != self.manager.current_interval() {
+            self.handle_update_interval(quick_action.auto_backup_minutes)
+                .await;
+        }
+
+        let game_changed = {
+            let mut state = self.manager.lock_state();
+            let changed = state.current_game.as_ref().map(|g| &g.name)
+                != quick_action.quick_action_game.as_ref().map(|g| &g.name);
+            if changed {
+                state.current_game = quick_action.quick_action_game.clone();
+            }
+            changed
+        };
+        if game_changed {
+            self.refresh_tray_game_label();
+        }
+
+        let schedules_changed = {
+            let mut state = self.manager.lock_state();
+            let schedules = sync_countdowns(&state.schedules, &quick_action.schedules);
+            let changed = schedules
+                .iter()
+                .map(|c| &c.schedule)
+                .ne(state.schedules.iter().map(|c| &c.schedule));
+            state.schedules = schedules;
+            changed
+        };
+        if schedules_changed {
+            self.ensure_timer();
+            self.handle_rebuild_tray_menu();
+        }
+
+        if let Err(err) = self.manager.reload_hotkeys(&config) {
+            warn!(
+                target: "rgsm::quick_action::manager",
+                "Failed to reload hotkeys after config change: {err}"
+            );
+        }
+    }
+
+    /// Check whether the backup root can be reached, pausing auto backups when it cannot and
+    /// resuming them once it is back
+    ///
+    /// Returns whether the backup root is available
+    fn refresh_backup_root(&mut self) -> bool {
+        let unavailable = match get_config() {
+            Ok(config) => check_backup_root(&config).err().map(|_| config.backup_path),
+            Err(err) => {
+                warn!(target: "rgsm::quick_action::manager", "Failed to load config: {err:?}");
+                return self.manager.unavailable_backup_root().is_none();
+            }
+        };
+        let previous = std::mem::replace(
+            &mut self.manager.lock_state().unavailable_backup_root,
+            unavailable.clone(),
+        );
+        if previous.is_some() != unavailable.is_some() {
+            match &unavailable {
+                Some(path) => {
+                    warn!(target: "rgsm::quick_action::manager", "Backup root {path} is unavailable, pausing auto backups")
+                }
+                None => {
+                    info!(target: "rgsm::quick_action::manager", "Backup root is available again, resuming auto backups")
+                }
+            }
+            notify_backup_root_status(&self.manager.app_handle(), unavailable.as_deref());
+            self.handle_rebuild_tray_menu();
+            self.emit_tick();
+            self.ensure_timer();
+        }
+        unavailable.is_none()
+    }
+
     async fn handle_timer_tick(&mut self) {
-        let should_trigger = {
+        // 备份目录不可用时暂停计时，每次 tick 重新检查，恢复后从暂停处继续
+        if !self.refresh_backup_root() {
+            self.timer_sleep = None;
+            self.ensure_timer();
+            return;
+        }
+        let (timer_enabled, should_trigger, due) = {
             let mut state = self.manager.lock_state();
-            if state.auto_backup_minutes == 0 {
-                self.timer_sleep = None;
-                false
-            } else {
+            let timer_enabled = state.auto_backup_minutes > 0;
+            let mut should_trigger = false;
+            if timer_enabled {
                 state.elapsed_minutes = state.elapsed_minutes.saturating_add(1);
                 if state.elapsed_minutes >= state.auto_backup_minutes {
                     state.elapsed_minutes = 0;
-                    true
-                } else {
-                    false
+                    should_trigger = true;
                 }
             }
+            (
+                timer_enabled,
+                should_trigger,
+                tick_countdowns(&mut state.schedules),
+            )
         };
 
         if should_trigger {
-            let app = self.manager.app_handle();
-            quick_backup(&app, QuickActionType::Timer).await;
+            self.spawn_action(QuickActionOperation::Backup, QuickActionType::Timer, None);
+        }
+        for schedule in due {
+            self.fire_schedule(schedule);
         }
 
-        if self.timer_sleep.is_some() {
-            self.timer_sleep = Some(Box::pin(time::sleep(Duration::from_secs(
-                TIMER_TICK_SECONDS,
-            ))));
+        if timer_enabled {
+            self.emit_tick();
+        }
+        self.timer_sleep = None;
+        self.ensure_timer();
+    }
+
+    fn fire_schedule(&self, schedule: BackupSchedule) {
+        let game = get_config().ok().and_then(|config| {
+            config
+                .games
+                .into_iter()
+                .find(|g| g.name == schedule.game_name)
+        });
+        let Some(game) = game else {
+            warn!(target: "rgsm::quick_action::manager", "Scheduled game {} no longer exists", schedule.game_name);
+            return;
+        };
+        info!(target: "rgsm::quick_action::manager", "Backup schedule of {} is due", game.name);
+        if let Err(err) = (BackupScheduleFired {
+            game_name: schedule.game_name,
+            interval_minutes: schedule.interval_minutes,
+        })
+        .emit(&self.manager.app_handle())
+        {
+            warn!(
+                target: "rgsm::quick_action::manager",
+                "Failed to emit backup schedule event: {err:?}"
+            );
+        }
+        self.spawn_action(
+            QuickActionOperation::Backup,
+            QuickActionType::Schedule,
+            Some(game),
+        );
+    }
+
+    /// Run a quick action outside the worker loop, guarded by the per-game lock
+    fn spawn_action(
+        &self,
+        operation: QuickActionOperation,
+        trigger: QuickActionType,
+        game: Option<Game>,
+    ) {
+        let manager = Arc::clone(&self.manager);
+        tauri::async_runtime::spawn(async move {
+            let app = manager.app_handle();
+            let game_name = game
+                .as_ref()
+                .map(|g| g.name.clone())
+                .or_else(|| manager.current_game().map(|g| g.name));
+            // 没有游戏时不加锁，由 quick_backup/quick_apply 报告错误
+            let _guard = match game_name {
+                // 存档变化触发的备份不排队，恢复后再备份只会得到刚恢复的存档
+                Some(name) if trigger == QuickActionType::FileChange => {
+                    match manager
+                        .lock_game(&name, QuickActionBusyBehavior::Drop)
+                        .await
+                    {
+                        Some(guard) => Some(guard),
+                        None => {
+                            info!(target: "rgsm::quick_action::manager", "{name} is busy, skipping backup on save change");
+                            return;
+                        }
+                    }
+                }
+                Some(name) => {
+                    let behavior = get_config()
+                        .map(|c| c.quick_action.busy_behavior)
+                        .unwrap_or_default();
+                    match manager.lock_game(&name, behavior).await {
+                        Some(guard) => Some(guard),
+                        None => {
+                            notify_action_in_progress(&app, trigger, &name);
+                            return;
+                        }
+                    }
+                }
+                None => None,
+            };
+            match operation {
+                QuickActionOperation::Backup => quick_backup(&app, trigger, game).await,
+                QuickActionOperation::Apply => quick_apply(&app, trigger, game).await,
+            }
+        });
+    }
+
+    fn emit_tick(&self) {
+        if let Err(err) = self
+            .manager
+            .auto_backup_status()
+            .emit(&self.manager.app_handle())
+        {
+            warn!(
+                target: "rgsm::quick_action::manager",
+                "Failed to emit auto backup tick: {err:?}"
+            );
         }
     }
 
     fn refresh_tray_game_label(&self) {
-        let (label, item) = {
+        let (current, label, menu, items) = {
             let state = self.manager.lock_state();
-            let label = state
-                .current_game
-                .as_ref()
-                .map(|game| game.name.clone())
+            let current = state.current_game.as_ref().map(|game| game.name.clone());
+            let label = current
+                .clone()
                 .unwrap_or_else(|| t!("backend.tray.no_game_selected").into());
-            let item = state.tray_game_item.clone();
-            (label, item)
+            let menu = state.tray_game_menu.clone();
+            (current, label, menu, state.tray_game_items.clone())
         };
 
-        if let Some(item) = item {
-            if let Err(err) = item.set_text(label) {
+        if let Some(menu) = menu {
+            if let Err(err) = menu.set_text(label) {
                 warn!(
                     target: "rgsm::quick_action::manager",
                     "Failed to refresh quick action game label: {err:?}"
                 );
             }
         }
+        for (name, item) in items {
+            if let Err(err) = item.set_checked(current.as_deref() == Some(name.as_str())) {
+                warn!(
+                    target: "rgsm::quick_action::manager",
+                    "Failed to refresh quick action game check for {name}: {err:?}"
+                );
+            }
+        }
     }
 
     fn refresh_tray_duration_checks(&self) {
@@ -365,3 +776,93 @@ impl QuickActionWorker {
         }
     }
 }
+
+/// Keep the interval in config so the timer is restored after a restart
+async fn persist_interval(minutes: u32) -> anyhow::Result<()> {
+    let mut config = get_config().context("failed to load config")?;
+    if config.quick_action.auto_backup_minutes == minutes {
+        return Ok(());
+    }
+    config.quick_action.auto_backup_minutes = minutes;
+    set_config(&config)
+        .await
+        .context("failed to persist auto backup interval")?;
+    Ok(())
+}
+
+/// Build countdowns for `schedules`, keeping the progress of ones whose game and interval
+/// did not change. Disabled schedules are left out.
+fn sync_countdowns(
+    previous: &[ScheduleCountdown],
+    schedules: &[BackupSchedule],
+) -> Vec<ScheduleCountdown> {
+    schedules
+        .iter()
+        .filter(|schedule| schedule.interval_minutes > 0)
+        .map(|schedule| {
+            let elapsed_minutes = previous
+                .iter()
+                .find(|c| {
+                    c.schedule.game_name == schedule.game_name
+                        && c.schedule.interval_minutes == schedule.interval_minutes
+                })
+                .map_or(0, |c| c.elapsed_minutes);
+            ScheduleCountdown {
+                schedule: schedule.clone(),
+                elapsed_minutes,
+            }
+        })
+        .collect()
+}
+
+/// Advance every countdown by one minute and return the schedules that are due
+fn tick_countdowns(countdowns: &mut [ScheduleCountdown]) -> Vec<BackupSchedule> {
+    let mut due = Vec::new();
+    for countdown in countdowns {
+        countdown.elapsed_minutes = countdown.elapsed_minutes.saturating_add(1);
+        if countdown.elapsed_minutes >= countdown.schedule.interval_minutes {
+            countdown.elapsed_minutes = 0;
+            due.push(countdown.schedule.clone());
+        }
+    }
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(game_name: &str, interval_minutes: u32) -> BackupSchedule {
+        BackupSchedule {
+            game_name: game_name.to_string(),
+            interval_minutes,
+            only_when_running: false,
+        }
+    }
+
+    #[test]
+    fn schedules_count_down_independently() {
+        let mut countdowns = sync_countdowns(&[], &[schedule("A", 2), schedule("B", 3)]);
+        let fired: Vec<_> = (0..6)
+            .flat_map(|_| tick_countdowns(&mut countdowns))
+            .map(|s| s.game_name)
+            .collect();
+        assert_eq!(fired, ["A", "B", "A", "A", "B"]);
+    }
+
+    #[test]
+    fn config_changes_keep_unchanged_countdowns() {
+        let mut countdowns = sync_countdowns(&[], &[schedule("A", 10), schedule("B", 10)]);
+        tick_countdowns(&mut countdowns);
+
+        let countdowns = sync_countdowns(
+            &countdowns,
+            &[schedule("A", 10), schedule("B", 20), schedule("C", 0)],
+        );
+        let elapsed: Vec<_> = countdowns
+            .iter()
+            .map(|c| (c.schedule.game_name.as_str(), c.elapsed_minutes))
+            .collect();
+        assert_eq!(elapsed, [("A", 1), ("B", 0)]);
+    }
+}
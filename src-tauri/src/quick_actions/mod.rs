@@ -1,12 +1,20 @@
+mod deep_link;
 mod hotkeys;
 mod manager;
+mod notification;
 mod tray;
 mod utils;
 
-pub use manager::QuickActionManager;
-pub use utils::{QuickActionCompleted, QuickActionType, quick_apply, quick_backup};
+pub use deep_link::is_deep_link_args;
+pub use manager::{AutoBackupTick, BackupScheduleFired, QuickActionManager};
+pub use notification::{NotificationClicked, PendingNotificationTarget, show_app_notification};
+pub use utils::{
+    QuickActionCompleted, QuickActionType, clear_quick_action_override, quick_apply, quick_backup,
+    set_quick_action_override,
+};
 
-use hotkeys::setup_hotkeys;
+use deep_link::setup_deep_link;
+use log::warn;
 use tauri::Manager;
 use tray::setup_tray;
 
@@ -14,10 +22,22 @@ use crate::config::get_config;
 
 pub fn setup(app: &mut tauri::App) -> anyhow::Result<()> {
     let manager = QuickActionManager::new(app.handle());
-    app.manage(manager);
+    app.manage(manager.clone());
+    app.manage(PendingNotificationTarget::default());
 
     let config = get_config()?;
     setup_tray(app)?;
-    setup_hotkeys(&config, app)?;
+    // 快捷键被占用时不应阻止软件启动
+    if let Err(e) = manager.reload_hotkeys(&config) {
+        warn!(target:"rgsm::quick_action", "Failed to register hotkeys: {e}");
+    }
+    // 恢复上次选择的自动备份间隔
+    if config.quick_action.auto_backup_minutes > 0 {
+        manager.update_interval(config.quick_action.auto_backup_minutes);
+    }
+    // 协议注册失败不应阻止软件启动
+    if let Err(e) = setup_deep_link(app) {
+        warn!(target:"rgsm::quick_action", "Failed to set up deep links: {e}");
+    }
     Ok(())
 }
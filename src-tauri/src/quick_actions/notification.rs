@@ -0,0 +1,124 @@
+use std::sync::{Mutex, PoisonError};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+
+use super::tray::create_main_window;
+
+/// Emitted when a notification about `game_name` is clicked while the main window is open
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct NotificationClicked {
+    pub game_name: String,
+}
+
+/// The game to open once the main window recreated by a notification click has loaded
+#[derive(Default)]
+pub struct PendingNotificationTarget(Mutex<Option<String>>);
+
+impl PendingNotificationTarget {
+    pub fn take(&self) -> Option<String> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take()
+    }
+
+    fn set(&self, game_name: String) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(game_name);
+    }
+}
+
+/// Show a desktop notification
+///
+/// When `target` is set, clicking the notification opens that game's page. Click actions are
+/// supported on Windows and on Linux desktops implementing the notification spec.
+pub fn show_app_notification(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    target: Option<String>,
+) -> anyhow::Result<()> {
+    show(app, title, body, target)
+}
+
+#[cfg(target_os = "windows")]
+fn show(app: &AppHandle, title: &str, body: &str, target: Option<String>) -> anyhow::Result<()> {
+    use tauri_winrt_notification::Toast;
+
+    // Only an installed app has its identifier registered with Windows
+    let app_id = if cfg!(debug_assertions) {
+        Toast::POWERSHELL_APP_ID.to_string()
+    } else {
+        app.config().identifier.clone()
+    };
+    let mut toast = Toast::new(&app_id).title(title).text1(body);
+    if let Some(game_name) = target {
+        let app = app.clone();
+        toast = toast.on_activated(move || {
+            open_game_page(&app, game_name.clone());
+            Ok(())
+        });
+    }
+    toast
+        .show()
+        .map_err(|e| anyhow::anyhow!("Failed to show toast: {e:?}"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show(app: &AppHandle, title: &str, body: &str, target: Option<String>) -> anyhow::Result<()> {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname(&app.package_info().name)
+        .summary(title)
+        .body(body)
+        .timeout(6000); // milliseconds
+    let Some(game_name) = target else {
+        notification.show()?;
+        return Ok(());
+    };
+    // The "default" action is invoked by clicking the notification body
+    notification.action("default", &rust_i18n::t!("backend.notification.open_game"));
+    let handle = notification.show()?;
+    let app = app.clone();
+    std::thread::spawn(move || {
+        handle.wait_for_action(|action| {
+            if action == "default" {
+                open_game_page(&app, game_name);
+            }
+        })
+    });
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn show(_app: &AppHandle, title: &str, body: &str, _target: Option<String>) -> anyhow::Result<()> {
+    notify_rust::Notification::new()
+        .summary(title)
+        .body(body)
+        .show()?;
+    Ok(())
+}
+
+fn open_game_page(app: &AppHandle, game_name: String) {
+    info!(target: "rgsm::quick_action", "Notification clicked, opening {game_name}");
+    match app.get_webview_window("main") {
+        Some(window) => {
+            let _ = window.unminimize();
+            let _ = window.show();
+            let _ = window.set_focus();
+            if let Err(err) = (NotificationClicked { game_name }).emit(app) {
+                warn!(target: "rgsm::quick_action", "Failed to emit notification click: {err:?}");
+            }
+        }
+        None => {
+            // The new window asks for the target once its frontend has loaded
+            app.state::<PendingNotificationTarget>().set(game_name);
+            let app = app.clone();
+            let handle = app.clone();
+            // Windows must be created on the main thread
+            if let Err(err) = handle.run_on_main_thread(move || create_main_window(&app)) {
+                warn!(target: "rgsm::quick_action", "Failed to open main window: {err:?}");
+            }
+        }
+    }
+}
@@ -1,34 +1,78 @@
 use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use log::info;
+use log::{info, warn};
 use tauri::{
     AppHandle, Manager, State, Wry,
-    menu::{CheckMenuItemBuilder, MenuBuilder, MenuEvent, MenuItemBuilder, SubmenuBuilder},
+    menu::{CheckMenuItemBuilder, Menu, MenuBuilder, MenuEvent, MenuItemBuilder, SubmenuBuilder},
     tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
     utils::config::WindowConfig,
 };
 use tauri_plugin_window_state::{StateFlags, WindowExt};
 
 use super::{QuickActionManager, QuickActionType};
+use crate::config::get_config;
 
 use rust_i18n::t;
 
+/// Menu id prefix of the entries in the quick action game submenu
+const GAME_ID_PREFIX: &str = "game.";
+
 pub fn setup_tray(app: &mut tauri::App) -> anyhow::Result<()> {
     info!(target: "rgsm::quick_action::tray", "Setting up tray icon");
 
     let manager_state: State<Arc<QuickActionManager>> = app.state();
     let manager = Arc::clone(manager_state.inner());
+    let tray_menu = build_tray_menu(app.handle(), &manager)?;
+
+    TrayIconBuilder::with_id("tray_icon")
+        .icon(app.default_window_icon().unwrap().clone())
+        .show_menu_on_left_click(false)
+        .menu(&tray_menu)
+        .on_tray_icon_event(tray_event_handler)
+        .on_menu_event(menu_event_handler)
+        .build(app)?;
+
+    info!(target: "rgsm::quick_action::tray", "Tray icon created");
+    Ok(())
+}
 
+/// Build the tray menu from the current config and hand its stateful items to the manager
+///
+/// Called on startup and again by `QuickActionManager::rebuild_tray_menu` when games change
+pub fn build_tray_menu(app: &AppHandle, manager: &QuickActionManager) -> anyhow::Result<Menu<Wry>> {
     let selected_duration = manager.current_interval();
-    let current_game_label = manager
-        .current_game()
-        .map(|game| game.name)
+    let current_game = manager.current_game().map(|game| game.name);
+    let current_game_label = current_game
+        .clone()
         .unwrap_or_else(|| t!("backend.tray.no_game_selected").into());
 
-    let current_quick_action_game = MenuItemBuilder::new(current_game_label)
-        .id("game")
-        .enabled(true)
-        .build(app)?;
+    // 快捷操作游戏子菜单，列出所有已配置的游戏
+    let games = get_config()?.games;
+    let mut game_items = Vec::with_capacity(games.len());
+    let mut game_item_map = HashMap::with_capacity(games.len());
+    for game in games {
+        let item = CheckMenuItemBuilder::new(&game.name)
+            .id(format!("{GAME_ID_PREFIX}{}", game.name))
+            .checked(current_game.as_deref() == Some(game.name.as_str()))
+            .build(app)?;
+        game_item_map.insert(game.name, item.clone());
+        game_items.push(item);
+    }
+    let game_item_refs: Vec<&dyn tauri::menu::IsMenuItem<Wry>> = game_items
+        .iter()
+        .map(|item| item as &dyn tauri::menu::IsMenuItem<Wry>)
+        .collect();
+    let mut game_menu = SubmenuBuilder::with_id(app, "game", current_game_label);
+    if game_item_refs.is_empty() {
+        game_menu = game_menu.item(
+            &MenuItemBuilder::new(t!("backend.tray.no_games"))
+                .enabled(false)
+                .build(app)?,
+        );
+    } else {
+        game_menu = game_menu.items(game_item_refs.as_slice());
+    }
+    let game_menu = game_menu.build()?;
 
     let timer_options = [
         (0_u32, t!("backend.tray.turn_off_auto_backup")),
@@ -58,10 +102,40 @@ pub fn setup_tray(app: &mut tauri::App) -> anyhow::Result<()> {
         .items(timer_item_refs.as_slice())
         .build()?;
 
-    let tray_menu = MenuBuilder::new(app)
+    let mut tray_menu = MenuBuilder::new(app).items(&[&game_menu, &timer_backup]);
+
+    // 备份目录不可用时自动备份已暂停，提供手动重试入口
+    if let Some(path) = manager.unavailable_backup_root() {
+        tray_menu = tray_menu.items(&[
+            &MenuItemBuilder::new(t!("backend.tray.auto_backup_paused_item", path = path))
+                .enabled(false)
+                .build(app)?,
+            &MenuItemBuilder::new(t!("backend.tray.retry_backup_root"))
+                .id("retry_backup_root")
+                .build(app)?,
+        ]);
+    }
+
+    // 按游戏的定时备份仅用于展示，在设置中修改
+    let schedules = manager.active_schedules();
+    if !schedules.is_empty() {
+        let mut schedule_menu = SubmenuBuilder::new(app, t!("backend.tray.schedules"));
+        for schedule in schedules {
+            schedule_menu = schedule_menu.item(
+                &MenuItemBuilder::new(t!(
+                    "backend.tray.schedule_item",
+                    name = schedule.game_name,
+                    minutes = schedule.interval_minutes
+                ))
+                .enabled(false)
+                .build(app)?,
+            );
+        }
+        tray_menu = tray_menu.item(&schedule_menu.build()?);
+    }
+
+    let tray_menu = tray_menu
         .items(&[
-            &current_quick_action_game,
-            &timer_backup,
             &MenuItemBuilder::new(t!("backend.tray.quick_backup"))
                 .id("backup")
                 .build(app)?,
@@ -74,18 +148,8 @@ pub fn setup_tray(app: &mut tauri::App) -> anyhow::Result<()> {
         ])
         .build()?;
 
-    manager.register_tray_items(current_quick_action_game.clone(), timer_item_map);
-
-    TrayIconBuilder::with_id("tray_icon")
-        .icon(app.default_window_icon().unwrap().clone())
-        .show_menu_on_left_click(false)
-        .menu(&tray_menu)
-        .on_tray_icon_event(tray_event_handler)
-        .on_menu_event(menu_event_handler)
-        .build(app)?;
-
-    info!(target: "rgsm::quick_action::tray", "Tray icon created");
-    Ok(())
+    manager.register_tray_items(game_menu, game_item_map, timer_item_map);
+    Ok(tray_menu)
 }
 
 pub fn tray_event_handler(tray: &TrayIcon, event: TrayIconEvent) {
@@ -98,29 +162,34 @@ pub fn tray_event_handler(tray: &TrayIcon, event: TrayIconEvent) {
         info!(target: "rgsm::quick_action::tray", "Tray left click");
         let app = tray.app_handle();
         if app.get_webview_window("main").is_none() {
-            let window = tauri::WebviewWindowBuilder::from_config(
-                app,
-                &WindowConfig {
-                    label: "main".to_string(),
-                    url: tauri::WebviewUrl::App(PathBuf::from("index.html")),
-                    drag_drop_enabled: false,
-                    title: "RustyManager".to_string(),
-                    ..Default::default()
-                },
-            )
-            .unwrap()
-            .build()
-            .unwrap();
-
-            window
-                .restore_state(StateFlags::all())
-                .expect("Cannot restore window state");
-            window.show().expect("Cannot show window");
-            window.set_focus().expect("Cannot set focus");
+            create_main_window(app);
         }
     }
 }
 
+/// Recreate the main window after it was closed to the tray
+pub(super) fn create_main_window(app: &AppHandle) {
+    let window = tauri::WebviewWindowBuilder::from_config(
+        app,
+        &WindowConfig {
+            label: "main".to_string(),
+            url: tauri::WebviewUrl::App(PathBuf::from("index.html")),
+            drag_drop_enabled: false,
+            title: "RustyManager".to_string(),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .build()
+    .unwrap();
+
+    window
+        .restore_state(StateFlags::all())
+        .expect("Cannot restore window state");
+    window.show().expect("Cannot show window");
+    window.set_focus().expect("Cannot set focus");
+}
+
 pub fn menu_event_handler(app: &AppHandle, event: MenuEvent) {
     let manager_state: State<Arc<QuickActionManager>> = app.state();
     let manager = Arc::clone(manager_state.inner());
@@ -132,6 +201,9 @@ pub fn menu_event_handler(app: &AppHandle, event: MenuEvent) {
         "apply" => {
             manager.trigger_apply(QuickActionType::Tray);
         }
+        "retry_backup_root" => {
+            manager.check_backup_root();
+        }
         "quit" => {
             app.exit(0);
         }
@@ -140,7 +212,9 @@ pub fn menu_event_handler(app: &AppHandle, event: MenuEvent) {
                 target: "rgsm::quick_action::tray",
                 "Tray menu item clicked: {other}."
             );
-            if other.starts_with("timer.") {
+            if let Some(name) = other.strip_prefix(GAME_ID_PREFIX) {
+                select_quick_action_game(manager, name.to_string());
+            } else if other.starts_with("timer.") {
                 if let Some(duration) = other
                     .split('.')
                     .next_back()
@@ -152,3 +226,19 @@ pub fn menu_event_handler(app: &AppHandle, event: MenuEvent) {
         }
     }
 }
+
+/// Same path as the `set_quick_backup_game` command
+fn select_quick_action_game(manager: Arc<QuickActionManager>, name: String) {
+    tauri::async_runtime::spawn(async move {
+        let game = get_config()
+            .ok()
+            .and_then(|config| config.games.into_iter().find(|g| g.name == name));
+        let Some(game) = game else {
+            warn!(target: "rgsm::quick_action::tray", "Game {name} selected in tray no longer exists");
+            return;
+        };
+        if let Err(err) = manager.set_quick_backup_game(game).await {
+            warn!(target: "rgsm::quick_action::tray", "Failed to set quick action game from tray: {err:?}");
+        }
+    });
+}
@@ -1,5 +1,9 @@
 use crate::{
-    config::{QuickActionSoundPreferences, QuickActionsSettings, get_config},
+    backup::{ArchiveMonitor, Game, Snapshot, auto_describe},
+    config::{
+        QuickActionOverride, QuickActionSoundPreferences, QuickActionsSettings, get_config,
+        modify_config,
+    },
     preclude::*,
     sound::{QuickActionSoundEffect, play_quick_action_sound},
 };
@@ -7,24 +11,41 @@ use log::{error, info, warn};
 use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use tauri::AppHandle;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
 use tauri_specta::Event;
 
+use super::{QuickActionManager, show_app_notification};
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
 pub enum QuickActionType {
     Timer,
     Tray,
     Hotkey,
+    Schedule,
+    FileChange,
+    DeepLink,
 }
 
 impl QuickActionType {
     fn generate_describe(self) -> String {
         match self {
-            QuickActionType::Timer => String::from("Auto Backup (Timer)"),
-            QuickActionType::Tray => String::from("Quick Backup (Tray)"),
-            QuickActionType::Hotkey => String::from("Quick Backup (Hotkey)"),
+            QuickActionType::Timer => t!("backend.tray.describe_timer").to_string(),
+            QuickActionType::Tray => t!("backend.tray.describe_tray").to_string(),
+            QuickActionType::Hotkey => t!("backend.tray.describe_hotkey").to_string(),
+            QuickActionType::Schedule => t!("backend.tray.describe_schedule").to_string(),
+            QuickActionType::FileChange => t!("backend.tray.describe_file_change").to_string(),
+            QuickActionType::DeepLink => t!("backend.tray.describe_deep_link").to_string(),
         }
     }
+
+    /// Triggered without user interaction
+    fn is_automatic(self) -> bool {
+        matches!(
+            self,
+            QuickActionType::Timer | QuickActionType::Schedule | QuickActionType::FileChange
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
@@ -45,6 +66,10 @@ pub struct QuickActionCompleted {
     pub status: QuickActionStatus,
     pub trigger: QuickActionType,
     pub game_name: Option<String>,
+    /// The snapshot created by a successful backup
+    pub snapshot: Option<Snapshot>,
+    /// The snapshot restored by a successful apply
+    pub restored_date: Option<String>,
 }
 
 fn emit_quick_action_event(
@@ -54,14 +79,21 @@ fn emit_quick_action_event(
     status: QuickActionStatus,
     game_name: Option<String>,
 ) {
-    if let Err(err) = (QuickActionCompleted {
-        operation,
-        status,
-        trigger,
-        game_name,
-    })
-    .emit(app)
-    {
+    send_quick_action_event(
+        app,
+        QuickActionCompleted {
+            operation,
+            status,
+            trigger,
+            game_name,
+            snapshot: None,
+            restored_date: None,
+        },
+    );
+}
+
+fn send_quick_action_event(app: &AppHandle, event: QuickActionCompleted) {
+    if let Err(err) = event.emit(app) {
         warn!(
             target: "rgsm::quick_action",
             "Failed to emit quick action event: {err:?}"
@@ -69,7 +101,8 @@ fn emit_quick_action_event(
     }
 }
 
-pub async fn quick_apply(app: &AppHandle, t: QuickActionType) {
+/// `game` overrides the configured quick action game, e.g. for per-game hotkeys
+pub async fn quick_apply(app: &AppHandle, t: QuickActionType, game: Option<Game>) {
     info!(target:"rgsm::quick_action", "Auto apply triggered: {:#?}", t.generate_describe());
     let config = match get_config() {
         Ok(config) => config,
@@ -84,7 +117,7 @@ pub async fn quick_apply(app: &AppHandle, t: QuickActionType) {
         QuickActionSoundPreferences::from(&quick_settings);
 
     // 检查游戏是否已选择
-    let game = match quick_settings.quick_action_game.clone() {
+    let game = match game.or_else(|| quick_settings.quick_action_game.clone()) {
         Some(game) => game,
         None => {
             emit_quick_action_event(
@@ -101,6 +134,13 @@ pub async fn quick_apply(app: &AppHandle, t: QuickActionType) {
 
     info!(target:"rgsm::quick_action", "Quick apply game: {:#?}", game);
 
+    // 使用该游戏的覆盖设置
+    let quick_settings = config.quick_action.for_game(&game.name);
+    let sound_preferences = QuickActionSoundPreferences::from(&quick_settings);
+    let extra_backup = config
+        .quick_action
+        .extra_backup_when_apply(&game.name, config.settings.extra_backup_when_apply);
+
     // 执行恢复操作
     let result = async {
         let newest_date = game
@@ -110,7 +150,13 @@ pub async fn quick_apply(app: &AppHandle, t: QuickActionType) {
             .ok_or(BackupError::NoBackupAvailable)?
             .date
             .clone();
-        game.restore_snapshot(&newest_date, None)
+        game.restore_snapshot_with_extra_backup(
+            &newest_date,
+            None,
+            extra_backup,
+            &mut ArchiveMonitor::silent(),
+        )?;
+        Ok::<_, BackupError>(newest_date)
     }
     .await;
 
@@ -119,9 +165,11 @@ pub async fn quick_apply(app: &AppHandle, t: QuickActionType) {
         Err(e) => {
             error!(target:"rgsm::quick_action", "Quick apply failed: {:#?}", &e);
             maybe_show_notification(
+                app,
                 &quick_settings,
-                t!("backend.tray.error"),
-                format!("{:#?}\n{:#?}", t!("backend.tray.find_error_detail"), e),
+                t!("backend.tray.quick_apply_failed", name = game.name),
+                format!("{}\n{:#?}", t!("backend.tray.find_error_detail"), e),
+                Some(&game.name),
             );
             play_quick_action_sound(app, sound_preferences, QuickActionSoundEffect::Failure);
             emit_quick_action_event(
@@ -132,31 +180,32 @@ pub async fn quick_apply(app: &AppHandle, t: QuickActionType) {
                 Some(game.name.clone()),
             );
         }
-        Ok(_) => {
+        Ok(restored_date) => {
             maybe_show_success_notification(
+                app,
                 &quick_settings,
                 true,
                 t!("backend.tray.success"),
-                format!(
-                    "{:#?} {} {}",
-                    game.name,
-                    t!("backend.tray.quick_apply"),
-                    t!("backend.tray.success")
-                ),
+                t!("backend.tray.quick_apply_success", name = game.name),
             );
             play_quick_action_sound(app, sound_preferences, QuickActionSoundEffect::Success);
-            emit_quick_action_event(
+            send_quick_action_event(
                 app,
-                t,
-                QuickActionOperation::Apply,
-                QuickActionStatus::Success,
-                Some(game.name.clone()),
+                QuickActionCompleted {
+                    operation: QuickActionOperation::Apply,
+                    status: QuickActionStatus::Success,
+                    trigger: t,
+                    game_name: Some(game.name.clone()),
+                    snapshot: None,
+                    restored_date: Some(restored_date),
+                },
             );
         }
     }
 }
 
-pub async fn quick_backup(app: &AppHandle, t: QuickActionType) {
+/// `game` overrides the configured quick action game, e.g. for per-game hotkeys
+pub async fn quick_backup(app: &AppHandle, t: QuickActionType, game: Option<Game>) {
     info!(target:"rgsm::quick_action", "Auto backup triggered: {:#?}", t.generate_describe());
     let config = match get_config() {
         Ok(config) => config,
@@ -172,7 +221,7 @@ pub async fn quick_backup(app: &AppHandle, t: QuickActionType) {
         QuickActionSoundPreferences::from(&quick_settings);
 
     // 检查游戏是否已选择
-    let game = match quick_settings.quick_action_game.clone() {
+    let game = match game.or_else(|| quick_settings.quick_action_game.clone()) {
         Some(game) => game,
         None => {
             emit_quick_action_event(
@@ -187,17 +236,53 @@ pub async fn quick_backup(app: &AppHandle, t: QuickActionType) {
         }
     };
 
+    // 使用该游戏的覆盖设置
+    let quick_settings = config.quick_action.for_game(&game.name);
+    let sound_preferences = QuickActionSoundPreferences::from(&quick_settings);
+
     // 执行备份操作
-    let result = game.create_snapshot(&t.generate_describe()).await;
+    let describe = auto_describe(&config, &t.generate_describe(), &game.name);
+    let result = game.create_snapshot(&describe).await;
 
     // 处理结果
     match result {
+        Err(BackupError::BackupRootUnavailable(path)) => {
+            warn!(target:"rgsm::quick_action", "Quick backup skipped, backup root {path} is unavailable");
+            // 自动备份由管理器暂停并统一提示，只有手动触发时才单独提示
+            if !t.is_automatic() {
+                maybe_show_notification(
+                    app,
+                    &quick_settings,
+                    t!("backend.tray.quick_backup_failed", name = game.name),
+                    t!("backend.tray.backup_root_unavailable", path = path),
+                    None,
+                );
+                play_quick_action_sound(app, sound_preferences, QuickActionSoundEffect::Failure);
+            }
+            app.state::<Arc<QuickActionManager>>().check_backup_root();
+            emit_quick_action_event(
+                app,
+                t,
+                QuickActionOperation::Backup,
+                QuickActionStatus::Failure,
+                Some(game.name.clone()),
+            );
+        }
         Err(e) => {
             error!(target:"rgsm::quick_action", "Quick backup failed: {:#?}", &e);
+            let in_use = e.files_in_use();
+            let detail = if in_use.is_empty() {
+                format!("{}\n{:#?}", t!("backend.tray.find_error_detail"), e)
+            } else {
+                let names: Vec<_> = in_use.iter().map(|p| p.to_string_lossy()).collect();
+                t!("backend.backup.file_in_use", name = names.join(", ")).to_string()
+            };
             maybe_show_notification(
+                app,
                 &quick_settings,
-                t!("backend.tray.error"),
-                format!("{:#?}\n{:#?}", t!("backend.tray.find_error_detail"), e),
+                t!("backend.tray.quick_backup_failed", name = game.name),
+                detail,
+                Some(&game.name),
             );
             play_quick_action_sound(app, sound_preferences, QuickActionSoundEffect::Failure);
             emit_quick_action_event(
@@ -208,30 +293,125 @@ pub async fn quick_backup(app: &AppHandle, t: QuickActionType) {
                 Some(game.name.clone()),
             );
         }
-        Ok(_) => {
+        Ok(snapshot) => {
             maybe_show_success_notification(
+                app,
                 &quick_settings,
-                prompt_when_auto_backup || t != QuickActionType::Timer,
+                prompt_when_auto_backup || !t.is_automatic(),
                 t!("backend.tray.success"),
-                format!(
-                    "{:#?} {} {}",
-                    game.name,
-                    t!("backend.tray.quick_backup"),
-                    t!("backend.tray.success")
-                ),
+                t!("backend.tray.quick_backup_success", name = game.name),
             );
             play_quick_action_sound(app, sound_preferences, QuickActionSoundEffect::Success);
-            emit_quick_action_event(
+            send_quick_action_event(
                 app,
-                t,
-                QuickActionOperation::Backup,
-                QuickActionStatus::Success,
-                Some(game.name.clone()),
+                QuickActionCompleted {
+                    operation: QuickActionOperation::Backup,
+                    status: QuickActionStatus::Success,
+                    trigger: t,
+                    game_name: Some(game.name.clone()),
+                    snapshot: Some(snapshot),
+                    restored_date: None,
+                },
             );
         }
     }
 }
 
+/// Set the quick action override of `game_name`, replacing any previous one
+pub async fn set_quick_action_override(
+    game_name: &str,
+    game_override: QuickActionOverride,
+) -> Result<(), BackupError> {
+    modify_config(|config| {
+        if !config.games.iter().any(|g| g.name == game_name) {
+            return Err(BackupError::GameNotExist(game_name.to_string()));
+        }
+        config
+            .quick_action
+            .overrides
+            .insert(game_name.to_string(), game_override);
+        Ok(())
+    })
+    .await
+}
+
+/// Remove the quick action override of `game_name`, so it uses the global settings again
+pub async fn clear_quick_action_override(game_name: &str) -> Result<(), BackupError> {
+    modify_config(|config| {
+        config.quick_action.overrides.remove(game_name);
+        Ok::<_, BackupError>(())
+    })
+    .await
+}
+
+/// Tell the user a quick action was dropped because another one for `game_name` is running
+pub fn notify_action_in_progress(app: &AppHandle, trigger: QuickActionType, game_name: &str) {
+    warn!(
+        target: "rgsm::quick_action",
+        "Quick action for {game_name} is still running, dropping {:#?}",
+        trigger.generate_describe()
+    );
+    let settings = match get_config() {
+        Ok(config) => config.quick_action,
+        Err(err) => {
+            error!(target:"rgsm::quick_action", "Failed to load config: {err:?}");
+            return;
+        }
+    };
+    maybe_show_notification(
+        app,
+        &settings,
+        t!("backend.tray.error"),
+        t!("backend.tray.action_in_progress", name = game_name),
+        None,
+    );
+}
+
+/// Tell the user a link could not be handled, `missing_game` names the game it asked for
+/// when that game does not exist
+pub fn notify_invalid_deep_link(app: &AppHandle, url: &str, missing_game: Option<&str>) {
+    let settings = match get_config() {
+        Ok(config) => config.quick_action,
+        Err(err) => {
+            error!(target:"rgsm::quick_action", "Failed to load config: {err:?}");
+            return;
+        }
+    };
+    let body = match missing_game {
+        Some(name) => t!("backend.tray.deep_link_game_not_found", name = name),
+        None => t!("backend.tray.invalid_deep_link", url = url),
+    };
+    maybe_show_notification(app, &settings, t!("backend.tray.error"), body, None);
+}
+
+/// Tell the user auto backups were paused because `unavailable` cannot be reached, or
+/// resumed when it is `None`
+pub fn notify_backup_root_status(app: &AppHandle, unavailable: Option<&str>) {
+    let settings = match get_config() {
+        Ok(config) => config.quick_action,
+        Err(err) => {
+            error!(target:"rgsm::quick_action", "Failed to load config: {err:?}");
+            return;
+        }
+    };
+    match unavailable {
+        Some(path) => maybe_show_notification(
+            app,
+            &settings,
+            t!("backend.tray.auto_backup_paused"),
+            t!("backend.tray.backup_root_unavailable", path = path),
+            None,
+        ),
+        None => maybe_show_notification(
+            app,
+            &settings,
+            t!("backend.tray.auto_backup_resumed"),
+            t!("backend.tray.backup_root_available"),
+            None,
+        ),
+    }
+}
+
 fn show_no_game_selected_error(
     app: &AppHandle,
     settings: &QuickActionsSettings,
@@ -239,9 +419,11 @@ fn show_no_game_selected_error(
 ) {
     warn!(target:"rgsm::quick_action", "No game selected, cannot quick backup/apply");
     maybe_show_notification(
+        app,
         settings,
         t!("backend.tray.error"),
         t!("backend.tray.no_game_selected"),
+        None,
     );
     play_quick_action_sound(
         app,
@@ -250,23 +432,33 @@ fn show_no_game_selected_error(
     );
 }
 
+/// Clicking a notification with a `target` game opens that game's page
 fn maybe_show_notification<T1: AsRef<str>, T2: AsRef<str>>(
+    app: &AppHandle,
     settings: &QuickActionsSettings,
     title: T1,
     body: T2,
+    target: Option<&str>,
 ) {
     if settings.enable_notification {
-        show_notification(title, body);
+        notify(app, title.as_ref(), body.as_ref(), target);
     }
 }
 
 fn maybe_show_success_notification<T1: AsRef<str>, T2: AsRef<str>>(
+    app: &AppHandle,
     settings: &QuickActionsSettings,
     should_notify: bool,
     title: T1,
     body: T2,
 ) {
     if settings.enable_notification && should_notify {
-        show_notification(title, body);
+        notify(app, title.as_ref(), body.as_ref(), None);
+    }
+}
+
+fn notify(app: &AppHandle, title: &str, body: &str, target: Option<&str>) {
+    if let Err(e) = show_app_notification(app, title, body, target.map(str::to_string)) {
+        error!(target:"rgsm::quick_action", "Failed to show notification: {e:?}");
     }
 }
@@ -0,0 +1,545 @@
+//! Watches the save paths of the games on the current device
+//!
+//! - While the frontend has started the watcher, every game is watched and the frontend is told
+//!   when a save changes, so it can mark games whose save is newer than the latest snapshot
+//! - Games opted in with `auto_backup_on_change` are always watched, a snapshot is taken once
+//!   their saves settle
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    watch,
+};
+use tokio::time::{self, Sleep};
+use tokio_util::sync::CancellationToken;
+
+use crate::backup::SaveUnitType;
+use crate::config::{ChangeBackupTiming, Config, ConfigManager, get_config};
+use crate::device::get_current_device_id;
+use crate::path_resolver::resolve_path;
+use crate::quick_actions::{QuickActionManager, QuickActionType};
+
+/// Changes of one game are collected for this long before a single event is emitted
+const DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// Emitted when a watched save changes, at most once per game every few seconds
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct SaveChanged {
+    pub game_name: String,
+    /// The last changed file or folder
+    pub path: String,
+    /// Modification time of `path`, in the same format as snapshot dates so the two
+    /// can be compared directly
+    pub mtime: String,
+}
+
+enum SaveWatcherCommand {
+    Start,
+    Stop,
+    Changed(Vec<PathBuf>),
+}
+
+/// A resolved save path of a game
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchTarget {
+    game_name: String,
+    path: PathBuf,
+    is_folder: bool,
+}
+
+impl WatchTarget {
+    /// Files are watched through their parent folder, games often replace a save by
+    /// renaming a new file over it, which would end a watch on the file itself
+    fn watch_path(&self) -> Option<(&Path, RecursiveMode)> {
+        if self.is_folder {
+            Some((&self.path, RecursiveMode::Recursive))
+        } else {
+            self.path
+                .parent()
+                .map(|parent| (parent, RecursiveMode::NonRecursive))
+        }
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        if self.is_folder {
+            path.starts_with(&self.path)
+        } else {
+            path == self.path
+        }
+    }
+}
+
+/// Save paths on the current device of the games accepted by `include`, paths that cannot
+/// be resolved are skipped
+fn watch_targets(config: &Config, include: impl Fn(&str) -> bool) -> Vec<WatchTarget> {
+    let device = get_current_device_id();
+    let mut targets = Vec::new();
+    for game in config.games.iter().filter(|g| include(&g.name)) {
+        for unit in &game.save_paths {
+            let Some(raw) = unit.get_path_for_device(device) else {
+                continue;
+            };
+            match resolve_path(raw, Some(game), config) {
+                Ok(path) => targets.push(WatchTarget {
+                    game_name: game.name.clone(),
+                    path,
+                    is_folder: matches!(unit.unit_type, SaveUnitType::Folder),
+                }),
+                Err(e) => {
+                    debug!(target: "rgsm::save_watcher", "Cannot resolve {raw} of {}: {e}", game.name)
+                }
+            }
+        }
+    }
+    targets
+}
+
+/// Changes of one game with no gap longer than the settle time
+#[derive(Debug, Clone, Copy)]
+struct ChangeBurst {
+    last_change: Instant,
+    /// Some change happened while a restore or quick action of the game was running, the
+    /// saves were written by us and must not be backed up again
+    tainted: bool,
+}
+
+/// Decides when the saves of opted-in games have settled and may be backed up
+#[derive(Debug, Default)]
+struct ChangeBackupState {
+    bursts: HashMap<String, ChangeBurst>,
+    last_backup: HashMap<String, Instant>,
+}
+
+impl ChangeBackupState {
+    fn record(&mut self, game_name: &str, now: Instant, busy: bool) {
+        let burst = self
+            .bursts
+            .entry(game_name.to_string())
+            .or_insert(ChangeBurst {
+                last_change: now,
+                tainted: false,
+            });
+        burst.last_change = now;
+        burst.tainted |= busy;
+    }
+
+    /// When the burst of `game_name` is due, the rate limit may push it past the settle time
+    fn deadline(
+        &self,
+        game_name: &str,
+        burst: &ChangeBurst,
+        timing: ChangeBackupTiming,
+    ) -> Instant {
+        let settled = burst.last_change + Duration::from_secs(timing.settle_seconds.into());
+        if burst.tainted {
+            return settled;
+        }
+        match self.last_backup.get(game_name) {
+            Some(last) => settled
+                .max(*last + Duration::from_secs(u64::from(timing.min_interval_minutes) * 60)),
+            None => settled,
+        }
+    }
+
+    fn next_deadline(&self, timing: ChangeBackupTiming) -> Option<Instant> {
+        self.bursts
+            .iter()
+            .map(|(name, burst)| self.deadline(name, burst, timing))
+            .min()
+    }
+
+    /// Games to back up now, tainted bursts are dropped once settled
+    fn take_due(&mut self, now: Instant, timing: ChangeBackupTiming) -> Vec<String> {
+        let due: Vec<_> = self
+            .bursts
+            .iter()
+            .filter(|(name, burst)| self.deadline(name, burst, timing) <= now)
+            .map(|(name, burst)| (name.clone(), burst.tainted))
+            .collect();
+        let mut games = Vec::new();
+        for (name, tainted) in due {
+            self.bursts.remove(&name);
+            if !tainted {
+                self.last_backup.insert(name.clone(), now);
+                games.push(name);
+            }
+        }
+        games
+    }
+
+    /// Forget games that are no longer opted in
+    fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.bursts.retain(|name, _| keep(name));
+        self.last_backup.retain(|name, _| keep(name));
+    }
+}
+
+/// Watches save paths in the background
+pub struct SaveWatcher {
+    command_tx: UnboundedSender<SaveWatcherCommand>,
+    cancel_token: CancellationToken,
+}
+
+impl Drop for SaveWatcher {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+    }
+}
+
+impl SaveWatcher {
+    pub fn new(app: &AppHandle) -> Self {
+        let cancel_token = CancellationToken::new();
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        SaveWatcherWorker::spawn(
+            app.clone(),
+            command_tx.clone(),
+            command_rx,
+            cancel_token.clone(),
+        );
+        Self {
+            command_tx,
+            cancel_token,
+        }
+    }
+
+    pub fn start(&self) {
+        self.send(SaveWatcherCommand::Start);
+    }
+
+    pub fn stop(&self) {
+        self.send(SaveWatcherCommand::Stop);
+    }
+
+    fn send(&self, command: SaveWatcherCommand) {
+        if self.command_tx.send(command).is_err() {
+            warn!(target: "rgsm::save_watcher", "Save watcher worker is not running");
+        }
+    }
+}
+
+struct SaveWatcherWorker {
+    app: AppHandle,
+    /// Handed to the notify callback, which runs on its own thread
+    command_tx: UnboundedSender<SaveWatcherCommand>,
+    command_rx: UnboundedReceiver<SaveWatcherCommand>,
+    /// Games being added, deleted or edited all go through the config
+    config_rx: watch::Receiver<Option<Config>>,
+    cancel_token: CancellationToken,
+    /// `None` while nothing needs to be watched
+    watcher: Option<RecommendedWatcher>,
+    targets: Vec<WatchTarget>,
+    /// Whether the frontend has started the watcher
+    ui_active: bool,
+    /// Last changed path of each game, waiting for `flush_sleep`
+    pending: HashMap<String, PathBuf>,
+    flush_sleep: Option<Pin<Box<Sleep>>>,
+    /// Games opted in to `auto_backup_on_change`
+    auto_games: HashSet<String>,
+    timing: ChangeBackupTiming,
+    change_backup: ChangeBackupState,
+    settle_sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl SaveWatcherWorker {
+    fn spawn(
+        app: AppHandle,
+        command_tx: UnboundedSender<SaveWatcherCommand>,
+        command_rx: UnboundedReceiver<SaveWatcherCommand>,
+        cancel_token: CancellationToken,
+    ) {
+        let mut worker = Self {
+            app,
+            command_tx,
+            command_rx,
+            config_rx: ConfigManager::global().subscribe(),
+            cancel_token,
+            watcher: None,
+            targets: Vec::new(),
+            ui_active: false,
+            pending: HashMap::new(),
+            flush_sleep: None,
+            auto_games: HashSet::new(),
+            timing: ChangeBackupTiming::default(),
+            change_backup: ChangeBackupState::default(),
+            settle_sleep: None,
+        };
+        tauri::async_runtime::spawn(async move { worker.run().await });
+    }
+
+    async fn run(&mut self) {
+        match get_config() {
+            Ok(config) => self.refresh(&config),
+            Err(e) => warn!(target: "rgsm::save_watcher", "Cannot read config: {e:?}"),
+        }
+        loop {
+            tokio::select! {
+                _ = self.cancel_token.cancelled() => break,
+                cmd = self.command_rx.recv() => {
+                    match cmd {
+                        Some(cmd) => self.handle_command(cmd),
+                        None => break,
+                    }
+                }
+                Ok(()) = self.config_rx.changed() => self.handle_config_changed(),
+                _ = wait_for(&mut self.flush_sleep) => self.flush(),
+                _ = wait_for(&mut self.settle_sleep) => self.backup_settled(),
+            }
+        }
+        info!(
+            target: "rgsm::save_watcher",
+            "SaveWatcherWorker received cancel signal or channel closed, shutting down gracefully"
+        );
+    }
+
+    fn handle_command(&mut self, command: SaveWatcherCommand) {
+        match command {
+            SaveWatcherCommand::Start | SaveWatcherCommand::Stop => {
+                self.ui_active = matches!(command, SaveWatcherCommand::Start);
+                if !self.ui_active {
+                    self.pending.clear();
+                    self.flush_sleep = None;
+                }
+                match get_config() {
+                    Ok(config) => self.refresh(&config),
+                    Err(e) => warn!(target: "rgsm::save_watcher", "Cannot read config: {e:?}"),
+                }
+            }
+            SaveWatcherCommand::Changed(paths) => self.handle_changed(paths),
+        }
+    }
+
+    /// Watch what `config` and the frontend currently need, the watcher only exists while
+    /// something is watched
+    fn refresh(&mut self, config: &Config) {
+        self.timing = config.quick_action.change_backup;
+        self.auto_games = config
+            .games
+            .iter()
+            .filter(|g| config.quick_action.auto_backup_on_change(&g.name))
+            .map(|g| g.name.clone())
+            .collect();
+        let auto_games = &self.auto_games;
+        self.change_backup.retain(|name| auto_games.contains(name));
+        self.arm_settle_sleep();
+
+        if !self.ui_active && self.auto_games.is_empty() {
+            if self.watcher.take().is_some() {
+                self.targets.clear();
+                info!(target: "rgsm::save_watcher", "Stopped watching saves");
+            }
+            return;
+        }
+        let targets = watch_targets(config, |name| {
+            self.ui_active || self.auto_games.contains(name)
+        });
+        if self.watcher.is_none() {
+            self.start();
+        } else if targets == self.targets {
+            return;
+        }
+        self.watch(targets);
+    }
+
+    fn start(&mut self) {
+        let command_tx = self.command_tx.clone();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                // Reading the saves, e.g. while backing them up, is not a change
+                Ok(event) if !matches!(event.kind, EventKind::Access(_) | EventKind::Other) => {
+                    let _ = command_tx.send(SaveWatcherCommand::Changed(event.paths));
+                }
+                Ok(_) => {}
+                Err(e) => warn!(target: "rgsm::save_watcher", "Watch error: {e:?}"),
+            }
+        });
+        match watcher {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => warn!(target: "rgsm::save_watcher", "Cannot create save watcher: {e:?}"),
+        }
+    }
+
+    /// Replace the watched paths with the ones of `targets`
+    fn watch(&mut self, targets: Vec<WatchTarget>) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+        let old: HashSet<_> = self.targets.iter().filter_map(|t| t.watch_path()).collect();
+        let new: HashSet<_> = targets.iter().filter_map(|t| t.watch_path()).collect();
+        for (path, _) in old.difference(&new) {
+            // The folder may be gone already, which also ends the watch
+            let _ = watcher.unwatch(path);
+        }
+        // A missing or inaccessible path must not stop the other games from being watched
+        for (path, mode) in new.difference(&old) {
+            if let Err(e) = watcher.watch(path, *mode) {
+                debug!(target: "rgsm::save_watcher", "Cannot watch {}: {e}", path.display());
+            }
+        }
+        info!(target: "rgsm::save_watcher", "Watching {} save paths", new.len());
+        self.targets = targets;
+    }
+
+    fn handle_config_changed(&mut self) {
+        let Some(config) = self.config_rx.borrow_and_update().clone() else {
+            return;
+        };
+        self.refresh(&config);
+    }
+
+    fn handle_changed(&mut self, paths: Vec<PathBuf>) {
+        let manager = self.app.state::<Arc<QuickActionManager>>();
+        let now = Instant::now();
+        for path in paths {
+            for target in self.targets.iter().filter(|t| t.contains(&path)) {
+                if self.ui_active {
+                    self.pending.insert(target.game_name.clone(), path.clone());
+                }
+                if self.auto_games.contains(&target.game_name) {
+                    let busy = manager.is_game_busy(&target.game_name);
+                    self.change_backup.record(&target.game_name, now, busy);
+                }
+            }
+        }
+        if !self.pending.is_empty() && self.flush_sleep.is_none() {
+            self.flush_sleep = Some(Box::pin(time::sleep(DEBOUNCE)));
+        }
+        self.arm_settle_sleep();
+    }
+
+    fn flush(&mut self) {
+        self.flush_sleep = None;
+        for (game_name, path) in self.pending.drain() {
+            let event = SaveChanged {
+                mtime: modified_time(&path),
+                path: path.to_string_lossy().to_string(),
+                game_name,
+            };
+            if let Err(e) = event.emit(&self.app) {
+                warn!(target: "rgsm::save_watcher", "Failed to emit save change: {e:?}");
+            }
+        }
+    }
+
+    fn arm_settle_sleep(&mut self) {
+        self.settle_sleep = self
+            .change_backup
+            .next_deadline(self.timing)
+            .map(|deadline| Box::pin(time::sleep_until(time::Instant::from_std(deadline))));
+    }
+
+    fn backup_settled(&mut self) {
+        let games = self.change_backup.take_due(Instant::now(), self.timing);
+        self.arm_settle_sleep();
+        if games.is_empty() {
+            return;
+        }
+        let config = match get_config() {
+            Ok(config) => config,
+            Err(e) => {
+                warn!(target: "rgsm::save_watcher", "Cannot read config: {e:?}");
+                return;
+            }
+        };
+        let manager = self.app.state::<Arc<QuickActionManager>>();
+        for name in games {
+            if let Some(game) = config.games.iter().find(|g| g.name == name) {
+                info!(target: "rgsm::save_watcher", "Saves of {name} settled, backing up");
+                manager.trigger_game_backup(QuickActionType::FileChange, Some(game.clone()));
+            }
+        }
+    }
+}
+async fn wait_for(sleep: &mut Option<Pin<Box<Sleep>>>) {
+    match sleep {
+        Some(sleep) => sleep.as_mut().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Deleted paths have no modification time, the time of the change is used instead
+fn modified_time(path: &Path) -> String {
+    let time = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_or_else(|_| chrono::Local::now(), chrono::DateTime::from);
+    time.format("%Y-%m-%d_%H-%M-%S").to_string()
+}
+
+pub fn setup(app: &mut tauri::App) -> anyhow::Result<()> {
+    app.manage(SaveWatcher::new(app.handle()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_matched_to_the_watched_unit() {
+        let folder = WatchTarget {
+            game_name: "A".to_string(),
+            path: PathBuf::from("/saves/a"),
+            is_folder: true,
+        };
+        let file = WatchTarget {
+            game_name: "B".to_string(),
+            path: PathBuf::from("/saves/b/save.dat"),
+            is_folder: false,
+        };
+        assert!(folder.contains(Path::new("/saves/a/slot1/save.dat")));
+        assert!(!folder.contains(Path::new("/saves/ab")));
+        assert!(file.contains(Path::new("/saves/b/save.dat")));
+        // Siblings share the watched parent folder but belong to another game or none
+        assert!(!file.contains(Path::new("/saves/b/options.ini")));
+        assert_eq!(
+            file.watch_path(),
+            Some((Path::new("/saves/b"), RecursiveMode::NonRecursive))
+        );
+    }
+
+    #[test]
+    fn settled_changes_are_backed_up_at_most_once_per_interval() {
+        let timing = ChangeBackupTiming {
+            settle_seconds: 10,
+            min_interval_minutes: 10,
+        };
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut state = ChangeBackupState::default();
+
+        state.record("A", at(0), false);
+        state.record("A", at(5), false);
+        assert_eq!(state.next_deadline(timing), Some(at(15)));
+        assert!(state.take_due(at(14), timing).is_empty());
+        assert_eq!(state.take_due(at(15), timing), vec!["A".to_string()]);
+        assert_eq!(state.next_deadline(timing), None);
+
+        // Changes right after a backup wait for the interval to pass
+        state.record("A", at(20), false);
+        assert_eq!(state.next_deadline(timing), Some(at(615)));
+        assert_eq!(state.take_due(at(615), timing), vec!["A".to_string()]);
+    }
+
+    #[test]
+    fn changes_during_a_restore_are_not_backed_up() {
+        let timing = ChangeBackupTiming::default();
+        let start = Instant::now();
+        let mut state = ChangeBackupState::default();
+        state.record("A", start, true);
+        state.record("A", start + Duration::from_secs(1), false);
+        let settled = start + Duration::from_secs(11);
+        assert_eq!(state.next_deadline(timing), Some(settled));
+        assert!(state.take_due(settled, timing).is_empty());
+        assert_eq!(state.next_deadline(timing), None);
+    }
+}
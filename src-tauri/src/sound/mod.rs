@@ -1,16 +1,23 @@
 use std::{
+    collections::HashSet,
+    fs,
     io::BufReader,
     path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result, anyhow};
 use log::warn;
 use rodio::{
-    Decoder, OutputStream, OutputStreamHandle, Sink, buffer::SamplesBuffer, source::Source,
+    Decoder, OutputStream, OutputStreamHandle, PlayError, Sink, buffer::SamplesBuffer,
+    source::Source,
 };
+use rust_i18n::t;
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use tauri::{AppHandle, Manager};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_dialog::DialogExt;
 use tokio::sync::{
     mpsc::{self, UnboundedReceiver, UnboundedSender},
@@ -18,6 +25,7 @@ use tokio::sync::{
 };
 
 use crate::config::{QuickActionSoundPreferences, QuickActionSoundSlots, QuickActionSoundSource};
+use crate::ipc_handler::{IpcNotification, NotificationLevel};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
 pub enum QuickActionSoundEffect {
@@ -25,22 +33,87 @@ pub enum QuickActionSoundEffect {
     Failure,
 }
 
+/// Whether a preview toggle started or stopped playback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum SoundPreviewState {
+    Playing,
+    Stopped,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SoundMode {
     QuickAction,
     Preview,
 }
 
-#[derive(Default)]
+/// Reopen the default device when nothing has been played for this long, so an output
+/// switched in the OS is picked up without restarting the app
+const DEVICE_REFRESH_IDLE: Duration = Duration::from_secs(5);
+
+/// Sound paths starting with this are relative to `AppData/RGSM`, e.g. `appdata:sounds/done.wav`
+const APP_DATA_PREFIX: &str = "appdata:";
+/// Folder under `AppData/RGSM` that holds the copied sound files
+const SOUND_DIR: &str = "sounds";
+
+/// `AppData/RGSM`, resolved in `setup`
+static APP_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// An opened audio output, behind a trait so tests can simulate a device that went away
+trait OutputDevice {
+    fn create_sink(&self) -> Result<Sink, PlayError>;
+}
+
+struct DefaultOutputDevice {
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+}
+
+impl OutputDevice for DefaultOutputDevice {
+    fn create_sink(&self) -> Result<Sink, PlayError> {
+        Sink::try_new(&self.handle)
+    }
+}
+
+fn open_default_output() -> Result<Box<dyn OutputDevice>> {
+    let (stream, handle) = OutputStream::try_default().context("failed to open output stream")?;
+    Ok(Box::new(DefaultOutputDevice {
+        _stream: stream,
+        handle,
+    }))
+}
+
+type OutputOpener = Box<dyn Fn() -> Result<Box<dyn OutputDevice>>>;
+
 struct SoundPlayer {
-    stream: Option<OutputStream>,
-    handle: Option<OutputStreamHandle>,
+    open_output: OutputOpener,
+    output: Option<Box<dyn OutputDevice>>,
     sink: Option<Sink>,
     active_mode: Option<SoundMode>,
     active_effect: Option<QuickActionSoundEffect>,
+    last_played: Option<Instant>,
+    /// Custom sound of the last quick action that was missing and replaced by the default one
+    missing_file: Option<PathBuf>,
+}
+
+impl Default for SoundPlayer {
+    fn default() -> Self {
+        Self::with_opener(Box::new(open_default_output))
+    }
 }
 
 impl SoundPlayer {
+    fn with_opener(open_output: OutputOpener) -> Self {
+        Self {
+            open_output,
+            output: None,
+            sink: None,
+            active_mode: None,
+            active_effect: None,
+            last_played: None,
+            missing_file: None,
+        }
+    }
+
     fn clear_finished_state(&mut self) {
         if let Some(sink) = self.sink.as_ref() {
             if sink.empty() {
@@ -54,14 +127,31 @@ impl SoundPlayer {
         }
     }
 
-    fn ensure_stream(&mut self) -> Result<()> {
-        if self.stream.is_none() || self.handle.is_none() {
-            let (stream, handle) =
-                OutputStream::try_default().context("failed to open output stream")?;
-            self.stream = Some(stream);
-            self.handle = Some(handle);
+    fn ensure_output(&mut self) -> Result<&dyn OutputDevice> {
+        if self
+            .last_played
+            .is_some_and(|t| t.elapsed() >= DEVICE_REFRESH_IDLE)
+        {
+            self.output = None;
         }
-        Ok(())
+        if self.output.is_none() {
+            self.output = Some((self.open_output)()?);
+        }
+        self.output.as_deref().context("audio output not available")
+    }
+
+    /// A stale device (e.g. an unplugged headset) is dropped and reopened once before giving up
+    fn create_sink(&mut self) -> Result<Sink> {
+        match self.ensure_output()?.create_sink() {
+            Ok(sink) => return Ok(sink),
+            Err(err) => {
+                warn!(target: "rgsm::sound", "Audio output is unavailable, reopening default device: {err}");
+            }
+        }
+        self.output = None;
+        self.ensure_output()?
+            .create_sink()
+            .context("failed to create audio sink")
     }
 
     fn stop(&mut self) {
@@ -79,21 +169,30 @@ impl SoundPlayer {
         mode: SoundMode,
     ) -> Result<()> {
         self.clear_finished_state();
-        let source = load_source(effect, slots)?;
-        self.ensure_stream()?;
+        // A quick action still gives feedback when its custom sound was deleted, a preview
+        // reports the error so it can be fixed in the settings
+        let source = match missing_sound_file(effect, slots) {
+            Some(path) if mode == SoundMode::QuickAction => {
+                warn!(target: "rgsm::sound", "Custom sound {} is missing, playing the default sound", path.display());
+                self.missing_file = Some(path);
+                default_source(effect)
+            }
+            _ => load_source(effect, slots)?,
+        };
+        let source: Box<dyn Source<Item = f32> + Send> = match max_duration(effect, slots) {
+            Some(limit) => Box::new(source.take_duration(limit)),
+            None => source,
+        };
         self.stop();
 
-        let handle = self
-            .handle
-            .as_ref()
-            .context("audio output stream handle not available")?;
-        let sink = Sink::try_new(handle).context("failed to create audio sink")?;
+        let sink = self.create_sink()?;
         sink.append(source);
         sink.play();
 
         self.sink = Some(sink);
         self.active_mode = Some(mode);
         self.active_effect = Some(effect);
+        self.last_played = Some(Instant::now());
         Ok(())
     }
 
@@ -101,13 +200,14 @@ impl SoundPlayer {
         &mut self,
         effect: QuickActionSoundEffect,
         slots: &QuickActionSoundSlots,
-    ) -> Result<()> {
+    ) -> Result<SoundPreviewState> {
         self.clear_finished_state();
         if self.active_mode == Some(SoundMode::Preview) && self.active_effect == Some(effect) {
             self.stop();
-            return Ok(());
+            return Ok(SoundPreviewState::Stopped);
         }
-        self.play(effect, slots, SoundMode::Preview)
+        self.play(effect, slots, SoundMode::Preview)?;
+        Ok(SoundPreviewState::Playing)
     }
 }
 
@@ -116,10 +216,10 @@ pub struct SoundManager {
 }
 
 impl SoundManager {
-    pub fn new() -> Self {
+    pub fn new(app: AppHandle) -> Self {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         tauri::async_runtime::spawn_blocking(move || {
-            let mut worker = SoundWorker::new(command_rx);
+            let mut worker = SoundWorker::new(command_rx, app);
             worker.run();
         });
 
@@ -152,7 +252,7 @@ impl SoundManager {
         &self,
         preferences: QuickActionSoundPreferences,
         effect: QuickActionSoundEffect,
-    ) -> Result<()> {
+    ) -> Result<SoundPreviewState> {
         let (tx, rx) = oneshot::channel();
         self.command_tx
             .send(SoundCommand::Play {
@@ -182,7 +282,7 @@ enum SoundCommand {
         effect: QuickActionSoundEffect,
         preferences: QuickActionSoundPreferences,
         mode: SoundMode,
-        respond_to: Option<oneshot::Sender<Result<()>>>,
+        respond_to: Option<oneshot::Sender<Result<SoundPreviewState>>>,
     },
     Stop {
         respond_to: Option<oneshot::Sender<()>>,
@@ -192,14 +292,36 @@ enum SoundCommand {
 struct SoundWorker {
     command_rx: UnboundedReceiver<SoundCommand>,
     player: SoundPlayer,
+    app: AppHandle,
+    /// Missing custom sounds the user was already told about in this session
+    reported_missing: HashSet<PathBuf>,
 }
 
 impl SoundWorker {
-    fn new(command_rx: UnboundedReceiver<SoundCommand>) -> Self {
+    fn new(command_rx: UnboundedReceiver<SoundCommand>, app: AppHandle) -> Self {
         Self {
             command_rx,
             player: SoundPlayer::default(),
+            app,
+            reported_missing: HashSet::new(),
+        }
+    }
+
+    fn report_missing_file(&mut self) {
+        let Some(path) = self.player.missing_file.take() else {
+            return;
+        };
+        if !self.reported_missing.insert(path.clone()) {
+            return;
         }
+        let _ = self.app.emit(
+            "Notification",
+            IpcNotification {
+                level: NotificationLevel::warning,
+                title: t!("backend.notification.warning").to_string(),
+                msg: t!("backend.sound.custom_sound_missing", path = path.display()).to_string(),
+            },
+        );
     }
 
     fn run(&mut self) {
@@ -219,8 +341,9 @@ impl SoundWorker {
                 let result = self
                     .player
                     .play(effect, &preferences.sounds, SoundMode::QuickAction);
+                self.report_missing_file();
                 if let Some(tx) = respond_to {
-                    let _ = tx.send(result);
+                    let _ = tx.send(result.map(|()| SoundPreviewState::Playing));
                 } else if let Err(err) = result {
                     warn!(target: "rgsm::sound", "Failed to play quick action sound: {err:?}");
                 }
@@ -261,6 +384,32 @@ fn load_source(
     }
 }
 
+/// The custom sound file of `effect` when it is set but no longer exists
+fn missing_sound_file(
+    effect: QuickActionSoundEffect,
+    slots: &QuickActionSoundSlots,
+) -> Option<PathBuf> {
+    let source = match effect {
+        QuickActionSoundEffect::Success => &slots.success,
+        QuickActionSoundEffect::Failure => &slots.failure,
+    };
+    match source {
+        QuickActionSoundSource::File { path } if !path.trim().is_empty() => {
+            Some(resolve_path(path)).filter(|resolved| !resolved.exists())
+        }
+        _ => None,
+    }
+}
+
+/// How long the sound of `effect` may play, `None` when it plays to the end
+fn max_duration(effect: QuickActionSoundEffect, slots: &QuickActionSoundSlots) -> Option<Duration> {
+    let seconds = match effect {
+        QuickActionSoundEffect::Success => slots.success_max_seconds,
+        QuickActionSoundEffect::Failure => slots.failure_max_seconds,
+    };
+    (seconds > 0).then(|| Duration::from_secs(u64::from(seconds)))
+}
+
 fn default_source(effect: QuickActionSoundEffect) -> Box<dyn Source<Item = f32> + Send> {
     const SAMPLE_RATE: u32 = 44_100;
     let (sequence, amplitude) = match effect {
@@ -303,6 +452,10 @@ fn load_from_file(path: &str) -> Result<Box<dyn Source<Item = f32> + Send>> {
 }
 
 fn resolve_path(path: &str) -> PathBuf {
+    if let Some(relative) = path.strip_prefix(APP_DATA_PREFIX) {
+        let root = APP_DATA_DIR.get().cloned().unwrap_or_default();
+        return root.join(relative);
+    }
     let candidate = Path::new(path);
     if candidate.is_absolute() {
         return candidate.to_path_buf();
@@ -319,8 +472,73 @@ fn resolve_path(path: &str) -> PathBuf {
         .join(candidate)
 }
 
+/// Check that the file can be opened and decoded, so a broken file is rejected when it is chosen
+fn validate_sound_file(path: &Path) -> Result<()> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("failed to open audio file at {}", path.display()))?;
+    let mut decoder = Decoder::new(BufReader::new(file))
+        .with_context(|| format!("failed to decode audio file at {}", path.display()))?;
+    if decoder.next().is_none() {
+        anyhow::bail!("audio file at {} has no samples", path.display());
+    }
+    Ok(())
+}
+
+/// Total duration of a sound file, so the settings can warn about long files
+///
+/// Formats whose header has no duration, e.g. most mp3 files, are decoded to the end
+pub fn probe_sound_file(path: &str) -> Result<Duration> {
+    let resolved = resolve_path(path);
+    let file = fs::File::open(&resolved)
+        .with_context(|| format!("failed to open audio file at {}", resolved.display()))?;
+    let decoder = Decoder::new(BufReader::new(file))
+        .with_context(|| format!("failed to decode audio file at {}", resolved.display()))?;
+    if let Some(duration) = decoder.total_duration() {
+        return Ok(duration);
+    }
+    let samples_per_second = u64::from(decoder.channels()) * u64::from(decoder.sample_rate());
+    if samples_per_second == 0 {
+        anyhow::bail!("audio file at {} has no samples", resolved.display());
+    }
+    let seconds = decoder.count() as f64 / samples_per_second as f64;
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// Copy `source` into the sound folder under `app_dir`, returns its `appdata:` reference
+///
+/// An existing file with the same name gets a `-1`, `-2`, ... suffix instead of being replaced,
+/// it may still be used by the other slot
+fn copy_sound_file(source: &Path, app_dir: &Path) -> Result<String> {
+    let dir = app_dir.join(SOUND_DIR);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create sound folder at {}", dir.display()))?;
+    let stem = source
+        .file_stem()
+        .context("audio file has no name")?
+        .to_string_lossy();
+    let extension = source
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let name = (0..)
+        .map(|n| match n {
+            0 => format!("{stem}{extension}"),
+            n => format!("{stem}-{n}{extension}"),
+        })
+        .find(|name| !dir.join(name).exists())
+        .context("no free file name in the sound folder")?;
+    fs::copy(source, dir.join(&name))
+        .with_context(|| format!("failed to copy audio file to {}", dir.display()))?;
+    Ok(format!("{APP_DATA_PREFIX}{SOUND_DIR}/{name}"))
+}
+
 pub fn setup(app: &mut tauri::App) -> Result<()> {
-    let manager = SoundManager::new();
+    let dir = app
+        .path()
+        .resolve("RGSM", BaseDirectory::AppData)
+        .context("Failed to resolve AppData/RGSM directory")?;
+    let _ = APP_DATA_DIR.set(dir);
+    let manager = SoundManager::new(app.handle().clone());
     app.manage(manager);
     Ok(())
 }
@@ -335,14 +553,251 @@ pub fn play_quick_action_sound(
     }
 }
 
-pub fn choose_quick_action_sound_file(app: &AppHandle) -> Result<String, String> {
-    match app
+/// Let the user pick a sound file, `None` when the dialog is closed
+///
+/// The file is decoded once before it is accepted. With `copy_to_app_dir` a copy is kept in
+/// the app data folder, so the sound keeps working after the original is moved or deleted
+pub fn choose_quick_action_sound_file(
+    app: &AppHandle,
+    copy_to_app_dir: bool,
+) -> Result<Option<String>, String> {
+    let Some(picked) = app
         .dialog()
         .file()
         .add_filter("Audio", &["mp3", "wav", "flac", "ogg"])
         .blocking_pick_file()
-    {
-        Some(path) => Ok(path.to_string()),
-        None => Err("Failed to open dialog.".to_string()),
+    else {
+        return Ok(None);
+    };
+    let path = picked.to_string();
+    let checked = validate_sound_file(Path::new(&path)).and_then(|()| {
+        if !copy_to_app_dir {
+            return Ok(path.clone());
+        }
+        let app_dir = APP_DATA_DIR
+            .get()
+            .context("app data folder is not resolved")?;
+        copy_sound_file(Path::new(&path), app_dir)
+    });
+    checked.map(Some).map_err(|e| {
+        warn!(target: "rgsm::sound", "Rejected sound file {}: {e:?}", path);
+        t!(
+            "backend.sound.invalid_file",
+            path = path,
+            reason = format!("{e:#}")
+        )
+        .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, rc::Rc};
+
+    struct FakeDevice {
+        closed: bool,
+    }
+
+    impl OutputDevice for FakeDevice {
+        fn create_sink(&self) -> Result<Sink, PlayError> {
+            if self.closed {
+                return Err(PlayError::NoDevice);
+            }
+            Ok(Sink::new_idle().0)
+        }
+    }
+
+    /// The first opened device is already closed, later ones work
+    fn player_with_stale_device() -> (SoundPlayer, Rc<Cell<usize>>) {
+        let opened = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&opened);
+        let player = SoundPlayer::with_opener(Box::new(move || {
+            counter.set(counter.get() + 1);
+            Ok(Box::new(FakeDevice {
+                closed: counter.get() == 1,
+            }))
+        }));
+        (player, opened)
+    }
+
+    #[test]
+    fn stale_device_is_reopened_once() {
+        let (mut player, opened) = player_with_stale_device();
+        let slots = QuickActionSoundSlots::default();
+        player
+            .play(
+                QuickActionSoundEffect::Success,
+                &slots,
+                SoundMode::QuickAction,
+            )
+            .unwrap();
+        assert_eq!(opened.get(), 2);
+        assert_eq!(player.active_effect, Some(QuickActionSoundEffect::Success));
+
+        // The working device is reused while it keeps working
+        player.stop();
+        player
+            .play(
+                QuickActionSoundEffect::Failure,
+                &slots,
+                SoundMode::QuickAction,
+            )
+            .unwrap();
+        assert_eq!(opened.get(), 2);
+    }
+
+    #[test]
+    fn gives_up_when_reopened_device_is_also_closed() {
+        let mut player =
+            SoundPlayer::with_opener(Box::new(|| Ok(Box::new(FakeDevice { closed: true }))));
+        let slots = QuickActionSoundSlots::default();
+        assert!(
+            player
+                .play(
+                    QuickActionSoundEffect::Success,
+                    &slots,
+                    SoundMode::QuickAction
+                )
+                .is_err()
+        );
+        assert!(player.sink.is_none());
+    }
+
+    #[test]
+    fn missing_custom_sound_falls_back_for_quick_actions_only() {
+        let mut player =
+            SoundPlayer::with_opener(Box::new(|| Ok(Box::new(FakeDevice { closed: false }))));
+        let dir = temp_dir::TempDir::new().unwrap();
+        let missing = dir.path().join("gone.wav");
+        let slots = QuickActionSoundSlots {
+            success: QuickActionSoundSource::File {
+                path: missing.to_string_lossy().to_string(),
+            },
+            ..Default::default()
+        };
+        player
+            .play(
+                QuickActionSoundEffect::Success,
+                &slots,
+                SoundMode::QuickAction,
+            )
+            .unwrap();
+        assert_eq!(player.missing_file.take(), Some(missing));
+        assert!(
+            player
+                .toggle_preview(QuickActionSoundEffect::Success, &slots)
+                .is_err()
+        );
+        assert!(player.missing_file.is_none());
+    }
+
+    #[test]
+    fn chosen_sound_files_are_checked_and_copied() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let broken = dir.path().join("broken.wav");
+        fs::write(&broken, b"not audio").unwrap();
+        assert!(validate_sound_file(&broken).is_err());
+
+        let app_dir = dir.path().join("app");
+        let first = copy_sound_file(&broken, &app_dir).unwrap();
+        let second = copy_sound_file(&broken, &app_dir).unwrap();
+        assert_eq!(first, "appdata:sounds/broken.wav");
+        assert_eq!(second, "appdata:sounds/broken-1.wav");
+        assert!(app_dir.join("sounds/broken-1.wav").is_file());
+    }
+
+    /// A silent 16 bit mono WAV file
+    fn wav_bytes(sample_rate: u32, samples: u32) -> Vec<u8> {
+        let data_len = samples * 2;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16_u32.to_le_bytes());
+        bytes.extend_from_slice(&1_u16.to_le_bytes());
+        bytes.extend_from_slice(&1_u16.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        bytes.extend_from_slice(&2_u16.to_le_bytes());
+        bytes.extend_from_slice(&16_u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        bytes.resize(bytes.len() + data_len as usize, 0);
+        bytes
+    }
+
+    #[test]
+    fn probe_reports_the_file_duration() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let wav = dir.path().join("long.wav");
+        fs::write(&wav, wav_bytes(8_000, 24_000)).unwrap();
+        let duration = probe_sound_file(&wav.to_string_lossy()).unwrap();
+        assert_eq!(duration, Duration::from_secs(3));
+        assert!(probe_sound_file(&dir.path().join("gone.wav").to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn playback_is_limited_per_slot() {
+        let mut slots = QuickActionSoundSlots {
+            failure_max_seconds: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            max_duration(QuickActionSoundEffect::Success, &slots),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(max_duration(QuickActionSoundEffect::Failure, &slots), None);
+        slots.success_max_seconds = 2;
+        assert_eq!(
+            max_duration(QuickActionSoundEffect::Success, &slots),
+            Some(Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn preview_toggle_reports_its_state() {
+        let mut player =
+            SoundPlayer::with_opener(Box::new(|| Ok(Box::new(FakeDevice { closed: false }))));
+        let slots = QuickActionSoundSlots::default();
+        let toggle =
+            |player: &mut SoundPlayer, effect| player.toggle_preview(effect, &slots).unwrap();
+        assert_eq!(
+            toggle(&mut player, QuickActionSoundEffect::Success),
+            SoundPreviewState::Playing
+        );
+        // Previewing the other slot replaces the current preview instead of stopping it
+        assert_eq!(
+            toggle(&mut player, QuickActionSoundEffect::Failure),
+            SoundPreviewState::Playing
+        );
+        assert_eq!(
+            toggle(&mut player, QuickActionSoundEffect::Failure),
+            SoundPreviewState::Stopped
+        );
+        assert!(player.sink.is_none());
+    }
+
+    #[test]
+    fn default_device_is_reresolved_after_idle() {
+        let (mut player, opened) = player_with_stale_device();
+        let slots = QuickActionSoundSlots::default();
+        player
+            .play(
+                QuickActionSoundEffect::Success,
+                &slots,
+                SoundMode::QuickAction,
+            )
+            .unwrap();
+        player.stop();
+        player.last_played = Instant::now().checked_sub(DEVICE_REFRESH_IDLE);
+        player
+            .play(
+                QuickActionSoundEffect::Success,
+                &slots,
+                SoundMode::QuickAction,
+            )
+            .unwrap();
+        assert_eq!(opened.get(), 3);
     }
 }
@@ -9,7 +9,10 @@ use crate::config::Config;
 use crate::preclude::*;
 use crate::updater::{
     probe::probe_config_version,
-    versions::{CURRENT_VERSION, Config1_4_0, MIN_SUPPORTED_VERSION, VERSION_1_4_0},
+    versions::{
+        CURRENT_VERSION, Config1_4_0, Config1_5_4, MIN_SUPPORTED_VERSION, VERSION_1_4_0,
+        VERSION_1_5_4,
+    },
 };
 
 /// Update configuration file to the latest version
@@ -17,9 +20,10 @@ use crate::updater::{
 /// This function handles the entire migration process:
 /// 1. Version probing
 /// 2. Version compatibility check
-/// 3. Backup creation
-/// 4. Data migration
-/// 5. New config writing
+/// 3. Migration of shape changes not tied to a version
+/// 4. Backup creation
+/// 5. Data migration
+/// 6. New config writing
 ///
 /// # Arguments
 /// * `path` - Path to the config file
@@ -42,48 +46,112 @@ pub fn update_config<P: AsRef<Path>>(path: P) -> Result<(), UpdaterError> {
         error!(target: "rgsm::updater", "Config version too old: {} < {}", version, min_supported);
         return Err(UpdaterError::ConfigVersionTooOld);
     }
-    if version == current {
+
+    // Read original content
+    let content = fs::read_to_string(path)?;
+    let mut value: serde_json::Value = serde_json::from_str(&content)?;
+    let reshaped = migrate_single_cloud_backend(&mut value);
+    if version == current && !reshaped {
         return Ok(());
     }
 
-    warn!(target: "rgsm::updater", "Config version is older than current version, updating...");
+    warn!(target: "rgsm::updater", "Config is outdated, updating...");
     // Create backup
-    backup_config(path)?;
-
-    // Read original content
-    let content = fs::read_to_string(path)?;
+    backup_config(path, &version)?;
 
     // Migrate based on version
-    let new_cfg = migrate_config(&content, &version)?;
+    let new_cfg = migrate_config(value, &version)?;
 
     // Write new config
-    fs::write(path, serde_json::to_string_pretty(&new_cfg)?)?;
+    write_atomic(path, serde_json::to_string_pretty(&new_cfg)?.as_bytes())?;
     info!(target: "rgsm::updater", "Config updated successfully to version {}", CURRENT_VERSION);
     Ok(())
 }
 
 /// Migrate config content based on its version
-fn migrate_config(content: &str, version: &Version) -> Result<Config, UpdaterError> {
-    if version.to_string().as_str() <= VERSION_1_4_0 {
-        let old_cfg: Config1_4_0 = serde_json::from_str(content)?;
+///
+/// Versions are compared as semver, so 1.10.0 is newer than 1.4.0
+fn migrate_config(content: serde_json::Value, version: &Version) -> Result<Config, UpdaterError> {
+    if *version <= Version::parse(VERSION_1_4_0)? {
+        let old_cfg: Config1_4_0 = serde_json::from_value(content)?;
+        Ok(Config::from(old_cfg))
+    } else if *version <= Version::parse(VERSION_1_5_4)? {
+        let old_cfg: Config1_5_4 = serde_json::from_value(content)?;
         Ok(Config::from(old_cfg))
     } else {
         // Try direct deserialization for compatible versions
-        let mut new_cfg: Config = serde_json::from_str(content)?;
+        let mut new_cfg: Config = serde_json::from_value(content)?;
         new_cfg.version = CURRENT_VERSION.to_string();
         Ok(new_cfg)
     }
 }
 
-/// Create a backup of the config file
-fn backup_config<P: AsRef<Path>>(path: P) -> Result<PathBuf, UpdaterError> {
+/// Turn the single `backend` and `root_path` of the cloud settings into a `backends` list
+///
+/// Configs of the current version may still have the single backend, so this runs for
+/// every version. The backend is named after its type and becomes the primary one, a
+/// disabled backend becomes an empty list. Returns whether anything changed.
+fn migrate_single_cloud_backend(config: &mut serde_json::Value) -> bool {
+    let Some(cloud) = config
+        .pointer_mut("/settings/cloud_settings")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        return false;
+    };
+    if cloud.contains_key("backends") {
+        return false;
+    }
+    let Some(backend) = cloud.remove("backend") else {
+        return false;
+    };
+    let root_path = cloud.remove("root_path");
+    let name = match backend.get("type").and_then(serde_json::Value::as_str) {
+        Some("Disabled") => None,
+        Some(kind) => Some(kind.to_string()),
+        None => Some("default".to_string()),
+    };
+    let mut backends = Vec::new();
+    if let Some(name) = name {
+        let mut named = serde_json::json!({
+            "name": name,
+            "backend": backend,
+            "enabled": true,
+        });
+        if let Some(root_path) = root_path {
+            named["root_path"] = root_path;
+        }
+        backends.push(named);
+        cloud.insert("primary".to_string(), name.into());
+    }
+    cloud.insert("backends".to_string(), backends.into());
+    true
+}
+
+/// The backup of the config file written by `version`, like `config-1.4.0.json.bak`
+fn backup_config_path(path: &Path, version: &Version) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}-{version}.json.bak"))
+}
+
+/// Create a backup of the config file, named after the version it was written by
+///
+/// The plain `.bak` is the rolling copy kept by `write_atomic`, so it is not used here.
+/// An existing backup of the same version is kept, it holds the original file
+fn backup_config<P: AsRef<Path>>(path: P, version: &Version) -> Result<PathBuf, UpdaterError> {
     let path = path.as_ref();
-    let backup_path = path.with_extension("json.bak");
+    let backup_path = backup_config_path(path, version);
+    if backup_path.exists() {
+        info!(target: "rgsm::updater", "Keeping the existing backup at {:?}", backup_path);
+        return Ok(backup_path);
+    }
 
     // Show notification
     show_notification(
         t!("backend.config.updating_config_title"),
-        t!("backend.config.updating_config_body"),
+        t!(
+            "backend.config.updating_config_body",
+            path = backup_path.display()
+        ),
     );
 
     // Create backup
@@ -92,3 +160,144 @@ fn backup_config<P: AsRef<Path>>(path: P) -> Result<PathBuf, UpdaterError> {
 
     Ok(backup_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::ApplyCleanMode;
+
+    /// Write `fixture` as a config file, run `update_config` on it and parse the result
+    fn migrate_fixture(fixture: &str) -> (Config, temp_dir::TempDir) {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, fixture).unwrap();
+        update_config(&path).unwrap();
+        let config: Config = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config.version, CURRENT_VERSION);
+        (config, dir)
+    }
+
+    fn unit_paths(config: &Config, game: usize, unit: usize) -> Vec<&str> {
+        let mut paths: Vec<_> = config.games[game].save_paths[unit]
+            .paths
+            .values()
+            .map(String::as_str)
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn configs_from_1_0_0_are_migrated() {
+        let (config, _dir) = migrate_fixture(include_str!("fixtures/config-1.0.0.json"));
+        assert_eq!(config.backup_path, "./save_data");
+        assert_eq!(config.games[0].name, "Hollow Knight");
+        assert_eq!(
+            unit_paths(&config, 0, 0),
+            ["C:\\Users\\Player\\AppData\\LocalLow\\Team Cherry\\Hollow Knight"]
+        );
+        assert!(config.games[0].game_paths.is_empty());
+        assert!(!config.settings.prompt_when_not_described);
+        assert!(config.settings.extra_backup_when_apply);
+    }
+
+    #[test]
+    fn configs_from_1_4_0_are_migrated() {
+        let fixture = include_str!("fixtures/config-1.4.0.json");
+        let (config, dir) = migrate_fixture(fixture);
+        let names: Vec<_> = config.games.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["Elden Ring", "Celeste"]);
+        let units = &config.games[0].save_paths;
+        assert_eq!(units[0].delete_before_apply, ApplyCleanMode::Full);
+        assert_eq!(units[1].delete_before_apply, ApplyCleanMode::None);
+        assert_eq!(
+            unit_paths(&config, 0, 1),
+            ["C:\\Users\\Player\\Documents\\EldenRing\\settings.ini"]
+        );
+        let game_paths: Vec<_> = config.games[0].game_paths.values().collect();
+        assert_eq!(game_paths, ["C:\\Games\\ELDEN RING\\eldenring.exe"]);
+        assert_eq!(config.devices.len(), 1);
+        assert!(!config.settings.extra_backup_when_apply);
+        assert!(!config.settings.exit_to_tray);
+        assert_eq!(config.settings.locale, "en_US");
+
+        // The original file is kept under its version
+        let backup = dir.path().join("config-1.4.0.json.bak");
+        assert_eq!(fs::read_to_string(backup).unwrap(), fixture);
+    }
+
+    #[test]
+    fn configs_from_1_5_0_are_migrated() {
+        let (config, _dir) = migrate_fixture(include_str!("fixtures/config-1.5.0.json"));
+        let unit = &config.games[0].save_paths[0];
+        assert_eq!(unit.delete_before_apply, ApplyCleanMode::Full);
+        assert_eq!(
+            unit_paths(&config, 0, 0),
+            [
+                "<home>\\Documents\\My Games\\Terraria\\Players",
+                "D:\\Terraria\\Players"
+            ]
+        );
+        assert_eq!(config.games[0].game_paths.len(), 1);
+        assert_eq!(config.devices["laptop-id"].name, "Laptop");
+        assert_eq!(config.settings.locale, "zh_SIMPLIFIED");
+        assert!(config.settings.show_edit_button);
+    }
+
+    #[test]
+    fn repeated_migrations_keep_the_original_backup() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        let backup = backup_config_path(&path, &Version::new(1, 4, 0));
+        assert_eq!(backup, dir.path().join("config-1.4.0.json.bak"));
+        fs::write(&backup, "original").unwrap();
+        fs::write(&path, include_str!("fixtures/config-1.4.0.json")).unwrap();
+        update_config(&path).unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "original");
+    }
+
+    #[test]
+    fn versions_are_compared_as_semver() {
+        // "1.10.0" sorts before "1.4.0" as a string, it must not be read as a 1.4.0 config
+        let content = include_str!("fixtures/config-1.5.0.json");
+        let content = serde_json::from_str(content).unwrap();
+        let config = migrate_config(content, &Version::new(1, 10, 0)).unwrap();
+        assert_eq!(config.games[0].name, "Terraria");
+        assert_eq!(config.version, CURRENT_VERSION);
+    }
+
+    /// A config of the current version that still has a single cloud backend
+    fn single_backend_config(backend: serde_json::Value) -> String {
+        let mut config = serde_json::to_value(Config::default()).unwrap();
+        config["settings"]["cloud_settings"] = serde_json::json!({
+            "always_sync": true,
+            "root_path": "/saves",
+            "backend": backend,
+        });
+        config.to_string()
+    }
+
+    #[test]
+    fn single_cloud_backends_are_migrated() {
+        let (config, dir) = migrate_fixture(&single_backend_config(serde_json::json!({
+            "type": "LocalFs",
+            "path": "/mnt/nas",
+        })));
+        let cloud = &config.settings.cloud_settings;
+        assert!(cloud.always_sync);
+        assert_eq!(cloud.primary, "LocalFs");
+        assert_eq!(cloud.backends.len(), 1);
+        assert_eq!(cloud.backends[0].root_path, "/saves");
+        assert!(cloud.backends[0].enabled);
+        let backup = dir
+            .path()
+            .join(format!("config-{CURRENT_VERSION}.json.bak"));
+        assert!(backup.exists());
+
+        let (config, _dir) = migrate_fixture(&single_backend_config(
+            serde_json::json!({"type": "Disabled"}),
+        ));
+        assert!(config.settings.cloud_settings.backends.is_empty());
+        assert!(config.settings.cloud_settings.find_backend(None).is_err());
+    }
+}
@@ -5,11 +5,16 @@
 //! - Data migration between versions
 //! - Backup creation
 //! - Component updates
+//! - Checking for newer releases
 
 pub mod migration;
 pub mod probe;
+pub mod release_check;
+pub mod snapshots;
 
 #[allow(dead_code)]
 pub mod versions;
 
 pub use migration::update_config;
+pub use release_check::{AppUpdateInfo, check_for_app_update};
+pub use snapshots::normalize_snapshot_paths;
@@ -0,0 +1,226 @@
+//! Checking GitHub releases for a newer version of the app
+//!
+//! The result is cached in `AppData/RGSM/update_check.json`, so the frontend can ask as
+//! often as it likes while GitHub is queried at most every few hours.
+
+use std::fs;
+use std::time::Duration;
+
+use log::{info, warn};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::config::get_config;
+use crate::preclude::*;
+use crate::updater::versions::CURRENT_VERSION;
+
+/// How long a successful check is reused
+const CACHE_SECS: i64 = 6 * 60 * 60;
+/// How long a failed check is reused, short so a connection coming back is noticed soon
+const FAILED_CACHE_SECS: i64 = 30 * 60;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of a check for a newer release
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct AppUpdateInfo {
+    pub current: String,
+    /// `None` when the check is disabled or failed
+    pub latest: Option<String>,
+    pub update_available: bool,
+    pub release_notes_url: Option<String>,
+    /// Why the check failed, like being offline or rate limited
+    pub error: Option<String>,
+}
+
+impl AppUpdateInfo {
+    fn unknown(error: Option<String>) -> Self {
+        AppUpdateInfo {
+            current: CURRENT_VERSION.to_string(),
+            latest: None,
+            update_available: false,
+            release_notes_url: None,
+            error,
+        }
+    }
+}
+
+/// The part of the GitHub release API response that is used
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedCheck {
+    /// Unix seconds
+    checked_at: i64,
+    url: String,
+    info: AppUpdateInfo,
+}
+
+impl CachedCheck {
+    /// Whether the cached result still answers a check of `url` at `now`
+    fn is_fresh(&self, url: &str, now: i64) -> bool {
+        let max_age = if self.info.error.is_none() {
+            CACHE_SECS
+        } else {
+            FAILED_CACHE_SECS
+        };
+        // A result from another version or endpoint says nothing about this one
+        self.url == url
+            && self.info.current == CURRENT_VERSION
+            && (0..max_age).contains(&(now - self.checked_at))
+    }
+}
+
+/// Parse a release tag like `v1.5.4` or `1.5.4`
+fn parse_tag(tag: &str) -> Option<Version> {
+    Version::parse(tag.trim().trim_start_matches(['v', 'V'])).ok()
+}
+
+/// Compare the release `release` with the running version
+fn compare_release(release: GithubRelease) -> AppUpdateInfo {
+    let Some(latest) = parse_tag(&release.tag_name) else {
+        return AppUpdateInfo::unknown(Some(format!(
+            "Unrecognized release tag {:?}",
+            release.tag_name
+        )));
+    };
+    let update_available = Version::parse(CURRENT_VERSION).is_ok_and(|current| latest > current);
+    AppUpdateInfo {
+        current: CURRENT_VERSION.to_string(),
+        latest: Some(latest.to_string()),
+        update_available,
+        release_notes_url: release.html_url,
+        error: None,
+    }
+}
+
+async fn fetch_latest_release(url: &str) -> Result<GithubRelease, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        // GitHub rejects requests without a user agent
+        .user_agent(concat!("rgsm/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
+        return Err(format!("Rate limited by the release server ({status})"));
+    }
+    if !status.is_success() {
+        return Err(format!("The release server answered {status}"));
+    }
+    response
+        .json::<GithubRelease>()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Ask the release server for the latest version, reusing a recent answer
+///
+/// Never fails because of the network, a failed check is reported in `AppUpdateInfo::error`.
+/// Nothing is requested when `Settings::check_updates` is off.
+pub async fn check_for_app_update(app: &AppHandle) -> Result<AppUpdateInfo, ConfigError> {
+    let config = get_config()?;
+    if !config.settings.check_updates {
+        return Ok(AppUpdateInfo::unknown(None));
+    }
+    let url = config.settings.update_check_url;
+    let cache_path = app
+        .path()
+        .resolve("RGSM/update_check.json", BaseDirectory::AppData)
+        .ok();
+    let now = chrono::Utc::now().timestamp();
+
+    let cached = cache_path
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<CachedCheck>(&bytes).ok());
+    if let Some(cached) = cached.filter(|cached| cached.is_fresh(&url, now)) {
+        return Ok(cached.info);
+    }
+
+    let info = match fetch_latest_release(&url).await {
+        Ok(release) => compare_release(release),
+        Err(e) => {
+            warn!(target: "rgsm::updater", "Failed to check for updates: {}", e);
+            AppUpdateInfo::unknown(Some(e))
+        }
+    };
+    info!(target: "rgsm::updater", "Latest release: {:?}", info.latest);
+
+    if let Some(path) = cache_path {
+        let cached = CachedCheck {
+            checked_at: now,
+            url,
+            info: info.clone(),
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, serde_json::to_vec(&cached)?));
+        if let Err(e) = written {
+            warn!(target: "rgsm::updater", "Failed to cache the update check: {:?}", e);
+        }
+    }
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str) -> GithubRelease {
+        GithubRelease {
+            tag_name: tag.to_string(),
+            html_url: Some("https://example.com/release".to_string()),
+        }
+    }
+
+    #[test]
+    fn releases_are_compared_as_semver() {
+        let current = Version::parse(CURRENT_VERSION).unwrap();
+        let newer = Version::new(current.major, current.minor + 10, 0);
+        let info = compare_release(release(&format!("v{newer}")));
+        assert!(info.update_available);
+        assert_eq!(info.latest, Some(newer.to_string()));
+
+        let info = compare_release(release(CURRENT_VERSION));
+        assert!(!info.update_available);
+        assert!(info.error.is_none());
+
+        let info = compare_release(release("nightly"));
+        assert!(!info.update_available);
+        assert!(info.error.is_some());
+    }
+
+    #[test]
+    fn failed_checks_expire_sooner() {
+        let url = "https://example.com/latest";
+        let mut cached = CachedCheck {
+            checked_at: 1_000_000,
+            url: url.to_string(),
+            info: compare_release(release(CURRENT_VERSION)),
+        };
+        let later = cached.checked_at + FAILED_CACHE_SECS + 1;
+        assert!(cached.is_fresh(url, later));
+        assert!(!cached.is_fresh("https://example.com/other", later));
+        assert!(!cached.is_fresh(url, cached.checked_at + CACHE_SECS));
+
+        cached.info = AppUpdateInfo::unknown(Some("offline".to_string()));
+        assert!(!cached.is_fresh(url, later));
+        // A clock set back invalidates the cache
+        assert!(!cached.is_fresh(url, cached.checked_at - 1));
+    }
+}
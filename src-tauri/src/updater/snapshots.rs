@@ -0,0 +1,25 @@
+use log::{info, warn};
+
+use crate::backup::normalize_snapshot_records;
+use crate::config::Config;
+
+/// Rewrite snapshot records that older versions stored with absolute paths
+///
+/// Only changed records are written, so this is cheap enough to run on every
+/// start. A record that cannot be read is skipped and does not block startup
+pub fn normalize_snapshot_paths(config: &Config) {
+    let root = config.backup_root();
+    let mut updated = 0;
+    for game in &config.games {
+        match normalize_snapshot_records(&root, &game.name) {
+            Ok(true) => updated += 1,
+            Ok(false) => {}
+            Err(e) => {
+                warn!(target: "rgsm::updater", "Cannot normalize snapshot records of {}: {:?}", game.name, e)
+            }
+        }
+    }
+    if updated > 0 {
+        info!(target: "rgsm::updater", "Normalized snapshot records of {} games", updated);
+    }
+}
@@ -8,3 +8,7 @@ pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 // 1.4.X
 mod v1_4_0;
 pub use v1_4_0::{Config as Config1_4_0, VERSION as VERSION_1_4_0};
+
+// 1.5.X
+mod v1_5_4;
+pub use v1_5_4::{Config as Config1_5_4, VERSION as VERSION_1_5_4};
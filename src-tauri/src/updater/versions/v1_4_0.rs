@@ -2,7 +2,7 @@ use serde::Deserialize;
 use std::collections::HashMap;
 
 use crate::{
-    backup::{Game as CurrentGame, SaveUnit, SaveUnitType},
+    backup::{ApplyCleanMode, Game as CurrentGame, SaveUnit, SaveUnitType},
     config::{Config as CurrentConfig, FavoriteTreeNode, QuickActionsSettings, Settings},
     device::Device,
 };
@@ -63,7 +63,12 @@ impl From<Config> for CurrentConfig {
                         SaveUnit {
                             unit_type: su.unit_type,
                             paths,
-                            delete_before_apply: su.delete_before_apply,
+                            delete_before_apply: if su.delete_before_apply {
+                                ApplyCleanMode::Full
+                            } else {
+                                ApplyCleanMode::None
+                            },
+                            ..Default::default()
                         }
                     })
                     .collect();
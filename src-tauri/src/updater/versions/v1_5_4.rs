@@ -0,0 +1,64 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{
+    backup::{Game as CurrentGame, SaveUnit},
+    config::{Config as CurrentConfig, FavoriteTreeNode, QuickActionsSettings, Settings},
+    device::{Device, DeviceId},
+};
+
+/// Version constant for 1.5.4
+///
+/// 1.4.x 之后、1.5.4 及之前的配置都使用这一结构
+pub const VERSION: &str = "1.5.4";
+
+/// Config structure for version 1.5.4
+///
+/// 下次修改配置结构时，先把这里引用的当前类型复制成冻结的版本，再修改当前类型
+#[derive(Deserialize)]
+pub struct Config {
+    version: String,
+    backup_path: String,
+    games: Vec<Game>,
+    #[serde(default)]
+    settings: Settings,
+    #[serde(default)]
+    favorites: Vec<FavoriteTreeNode>,
+    #[serde(default)]
+    quick_action: QuickActionsSettings,
+    #[serde(default)]
+    devices: HashMap<DeviceId, Device>,
+}
+
+/// Game structure for version 1.5.4
+#[derive(Deserialize)]
+pub struct Game {
+    name: String,
+    save_paths: Vec<SaveUnit>,
+    #[serde(default)]
+    game_paths: HashMap<DeviceId, String>,
+}
+
+impl From<Config> for CurrentConfig {
+    fn from(old: Config) -> Self {
+        let games = old
+            .games
+            .into_iter()
+            .map(|g| CurrentGame {
+                name: g.name,
+                save_paths: g.save_paths,
+                game_paths: g.game_paths,
+            })
+            .collect();
+
+        CurrentConfig {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            backup_path: old.backup_path,
+            games,
+            settings: old.settings,
+            favorites: old.favorites,
+            quick_action: old.quick_action,
+            devices: old.devices,
+        }
+    }
+}
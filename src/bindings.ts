@@ -13,6 +13,17 @@ async openUrl(url: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * 检查 GitHub 上是否有新版本，结果会缓存数小时，网络错误放在返回值的 error 中
+ */
+async checkForAppUpdate() : Promise<Result<AppUpdateInfo, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_for_app_update") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async openFileOrFolder(path: string) : Promise<Result<null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("open_file_or_folder", { path }) };
@@ -21,17 +32,44 @@ async openFileOrFolder(path: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async chooseSaveFile() : Promise<Result<string, string>> {
+async launchGame(game: Game) : Promise<Result<LaunchResult, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("launch_game", { game }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async chooseSaveFile(options: PickerOptions | null) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("choose_save_file", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 一次选择多个文件，用于同时添加多个存档文件
+ */
+async chooseSaveFiles(options: PickerOptions | null) : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("choose_save_files", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async chooseSaveDir(options: PickerOptions | null) : Promise<Result<string, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("choose_save_file") };
+    return { status: "ok", data: await TAURI_INVOKE("choose_save_dir", { options }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async chooseSaveDir() : Promise<Result<string, string>> {
+async chooseExportPath(defaultName: string) : Promise<Result<string, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("choose_save_dir") };
+    return { status: "ok", data: await TAURI_INVOKE("choose_export_path", { defaultName }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
@@ -53,413 +91,1624 @@ async addGame(game: Game) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async restoreSnapshot(game: Game, date: string) : Promise<Result<null, string>> {
+async validateGame(game: Game) : Promise<Result<GameValidationReport, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("restore_snapshot", { game, date }) };
+    return { status: "ok", data: await TAURI_INVOKE("validate_game", { game }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deleteSnapshot(game: Game, date: string) : Promise<Result<null, string>> {
+/**
+ * Validate every configured game, so the UI can point out stale save paths
+ */
+async checkAllGames() : Promise<Result<GameValidationReport[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_snapshot", { game, date }) };
+    return { status: "ok", data: await TAURI_INVOKE("check_all_games") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deleteGame(game: Game) : Promise<Result<null, string>> {
+async exportGameArchive(game: Game, targetPath: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_game", { game }) };
+    return { status: "ok", data: await TAURI_INVOKE("export_game_archive", { game, targetPath }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getGameSnapshotsInfo(game: Game) : Promise<Result<GameSnapshots, string>> {
+async importGameArchive(filePath: string, mergeStrategy: ImportMergeStrategy | null) : Promise<Result<Game, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_game_snapshots_info", { game }) };
+    return { status: "ok", data: await TAURI_INVOKE("import_game_archive", { filePath, mergeStrategy }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setConfig(config: Config) : Promise<Result<null, string>> {
+/**
+ * Exports the config without cloud credentials, for sharing or bug reports
+ */
+async exportSanitizedConfig(targetPath: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_config", { config }) };
+    return { status: "ok", data: await TAURI_INVOKE("export_sanitized_config", { targetPath }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async resetSettings() : Promise<Result<null, string>> {
+/**
+ * Imports only the games of an exported config, merged by name or replacing the local ones
+ */
+async importGamesFromConfig(filePath: string, merge: boolean) : Promise<Result<Game[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("reset_settings") };
+    return { status: "ok", data: await TAURI_INVOKE("import_games_from_config", { filePath, merge }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async createSnapshot(game: Game, describe: string) : Promise<Result<null, string>> {
+async restoreSnapshot(game: Game, date: string) : Promise<Result<null, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("create_snapshot", { game, describe }) };
+    return { status: "ok", data: await TAURI_INVOKE("restore_snapshot", { game, date }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async openBackupFolder(game: Game) : Promise<Result<boolean, string>> {
+/**
+ * 预览恢复快照会新增、覆盖、删除哪些文件，不会修改任何文件
+ */
+async previewRestore(game: Game, date: string) : Promise<Result<RestorePlan, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("open_backup_folder", { game }) };
+    return { status: "ok", data: await TAURI_INVOKE("preview_restore", { game, date }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async checkCloudBackend(backend: Backend) : Promise<Result<null, string>> {
+/**
+ * 列出快照中的文件，按存档单元分组，不会解压
+ */
+async listSnapshotContents(game: Game, date: string) : Promise<Result<SnapshotContents, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("check_cloud_backend", { backend }) };
+    return { status: "ok", data: await TAURI_INVOKE("list_snapshot_contents", { game, date }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async cloudUploadAll(backend: Backend) : Promise<Result<null, string>> {
+/**
+ * 比较两个快照中的文件，按存档单元分组，不会解压
+ */
+async diffSnapshots(game: Game, dateA: string, dateB: string) : Promise<Result<SnapshotDiff, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("cloud_upload_all", { backend }) };
+    return { status: "ok", data: await TAURI_INVOKE("diff_snapshots", { game, dateA, dateB }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async cloudDownloadAll(backend: Backend) : Promise<Result<null, string>> {
+/**
+ * 从快照中恢复单个文件到其原位置，返回恢复后的路径
+ */
+async restoreSingleFile(game: Game, date: string, entryPath: string, overwrite: boolean) : Promise<Result<string, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("cloud_download_all", { backend }) };
+    return { status: "ok", data: await TAURI_INVOKE("restore_single_file", { game, date, entryPath, overwrite }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setSnapshotDescription(game: Game, date: string, describe: string) : Promise<Result<null, string>> {
+async deleteSnapshot(game: Game, date: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_snapshot_description", { game, date, describe }) };
+    return { status: "ok", data: await TAURI_INVOKE("delete_snapshot", { game, date }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async backupAll() : Promise<Result<null, string>> {
+/**
+ * 将误记在 `from_game` 下的快照移动到 `to_game`
+ */
+async moveSnapshot(fromGame: string, date: string, toGame: string) : Promise<Result<null, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("backup_all") };
+    return { status: "ok", data: await TAURI_INVOKE("move_snapshot", { fromGame, date, toGame }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async applyAll() : Promise<Result<null, string>> {
+/**
+ * `move_snapshot` 的批量版本，任一日期冲突时不会移动任何快照
+ */
+async moveSnapshots(fromGame: string, dates: string[], toGame: string) : Promise<Result<null, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("apply_all") };
+    return { status: "ok", data: await TAURI_INVOKE("move_snapshots", { fromGame, dates, toGame }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setQuickBackupGame(game: Game) : Promise<Result<null, string>> {
+async deleteGame(game: Game) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_quick_backup_game", { game }) };
+    return { status: "ok", data: await TAURI_INVOKE("delete_game", { game }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Resolves a path string containing variables to an actual filesystem path
- * 
- * This command allows the frontend to resolve paths with variables like <home>, <winAppData>, etc.
+ * Put back a game deleted within the retention period
  */
-async resolvePath(path: string) : Promise<Result<string, string>> {
+async undoDeleteGame(gameName: string) : Promise<Result<Game, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("resolve_path", { path }) };
+    return { status: "ok", data: await TAURI_INVOKE("undo_delete_game", { gameName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async renameGame(oldName: string, newName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_game", { oldName, newName }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Returns the current device, if not found, returns a default device
+ * Add a favorites node, `game_ref` names the game a leaf points at
  */
-async getCurrentDeviceInfo() : Promise<Result<Device, string>> {
+async favoritesAddNode(parentId: string | null, label: string, isLeaf: boolean, gameRef: string | null) : Promise<Result<string, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_current_device_info") };
+    return { status: "ok", data: await TAURI_INVOKE("favorites_add_node", { parentId, label, isLeaf, gameRef }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async toggleQuickActionSoundPreview(preferences: QuickActionSoundPreferences, effect: QuickActionSoundEffect) : Promise<Result<null, string>> {
+async favoritesRemoveNode(nodeId: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("toggle_quick_action_sound_preview", { preferences, effect }) };
+    return { status: "ok", data: await TAURI_INVOKE("favorites_remove_node", { nodeId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async stopSoundPlayback() : Promise<Result<null, string>> {
+async favoritesMoveNode(nodeId: string, newParentId: string | null, index: number) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("stop_sound_playback") };
+    return { status: "ok", data: await TAURI_INVOKE("favorites_move_node", { nodeId, newParentId, index }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async chooseQuickActionSoundFile() : Promise<Result<string, string>> {
+async favoritesRenameNode(nodeId: string, label: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("choose_quick_action_sound_file") };
+    return { status: "ok", data: await TAURI_INVOKE("favorites_rename_node", { nodeId, label }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-/**
- * 触发扫描流程的命令（最小实现）
- * 
- * - 输入：`ScanOptions` 控制扫描选项，`AppHandle` 用于事件发送
- * - 输出：`ScanResult` 扫描结果（当前为最小实现，返回空集合）
- * - 行为：按阶段发送两到三次 `ScanProgress` 事件，便于前端调试 UI 与绑定
- */
-async scanGames(options: ScanOptions) : Promise<Result<ScanResult, string>> {
+async migrateBackupRoot(newPath: string, moveFiles: boolean) : Promise<Result<BackupRootMigration, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("scan_games", { options }) };
+    return { status: "ok", data: await TAURI_INVOKE("migrate_backup_root", { newPath, moveFiles }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * 查询 PCGamingWiki 索引中的游戏信息（名称或别名匹配）
- * 
- * - 输入：`name` 为待查询的游戏名称或别名，`AppHandle` 用于解析资源路径
- * - 输出：匹配到的 `GameInfo`，无匹配时返回 `None`
- * - 错误：资源读取或解析失败返回错误信息字符串（已转换为友好可读）
+ * Disk usage of the backup folder, including zips that no snapshot record refers to
  */
-async pcgwQuery(name: string) : Promise<Result<GameInfo | null, string>> {
+async getStorageReport() : Promise<Result<StorageReport, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("pcgw_query", { name }) };
+    return { status: "ok", data: await TAURI_INVOKE("get_storage_report") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * 完整查询 PCGamingWiki 索引（支持模糊、平台过滤与结果上限）
- * 
- * - 输入：`name` 查询关键字（名称或别名），`options` 查询选项
- * - 行为：按以下优先级计算评分并排序：
- * 1. 主名称完全匹配：score=1.0，matched_by="name"
- * 2. 别名完全匹配：score=0.95，matched_by="alias"
- * 3. 模糊匹配（包含）：name 包含则 score≈0.75~1.0，alias 包含则 score≈0.7~1.0，matched_by="fuzzy"
- * - 过滤：若设置 `platform`，仅保留有保存规则包含该平台的条目
- * - 限制：返回不超过 `limit` 个结果（默认 20）
+ * Reconcile snapshot zips with their records, only reporting the changes when `dry_run` is set
  */
-async pcgwSearch(name: string, options: PcgwQueryOptions) : Promise<Result<PcgwQueryItem[], string>> {
+async cleanupBackupStorage(dryRun: boolean) : Promise<Result<CleanupReport, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("pcgw_search", { name, options }) };
+    return { status: "ok", data: await TAURI_INVOKE("cleanup_backup_storage", { dryRun }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getGameSnapshotsInfo(game: Game) : Promise<Result<GameSnapshots, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_game_snapshots_info", { game }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * 为已检测到的游戏生成 SaveUnit 列表（带设备映射）
- * 
- * - 输入：`game_info`（PCGW 索引中的游戏信息）、`install_path`（解析出的安装目录字符串）
- * - 行为：调用平台实现的 `generate_save_units`，只返回存在的文件/文件夹并映射到当前设备
- * - 输出：`SaveUnit` 列表，供前端“一键填充/添加”使用
+ * 根据备份目录中的压缩包重建 Backups.json，旧文件中仍可读取的记录会被保留
  */
-async generateSaveUnitsForGame(gameInfo: GameInfo, installPath: string) : Promise<Result<SaveUnit[], string>> {
+async rebuildGameSnapshots(game: Game) : Promise<Result<GameSnapshots, IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("generate_save_units_for_game", { gameInfo, installPath }) };
+    return { status: "ok", data: await TAURI_INVOKE("rebuild_game_snapshots", { game }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * 刷新 PCGW 索引（返回版本与条目数量）
- * 
- * - 行为：首先尝试从远端拉取并缓存索引；失败则回退读取打包资源
- * - 返回：索引版本与条目数量，便于前端显示状态
+ * 检查配置与所有游戏的快照记录，返回发现的问题
  */
-async pcgwRefreshIndex() : Promise<Result<PcgwIndexMeta, string>> {
+async validateData() : Promise<Result<DataIssue[], IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("pcgw_refresh_index") };
+    return { status: "ok", data: await TAURI_INVOKE("validate_data") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * 从本地文件导入 PCGW 索引（覆盖缓存并返回元信息）
+ * 检查配置中的游戏与备份目录下的文件夹是否一致，修复同样通过 `apply_data_fixes` 执行
  */
-async pcgwImportIndexFromFile(filePath: string) : Promise<Result<PcgwIndexMeta, string>> {
+async getConsistencyReport() : Promise<Result<DataIssue[], IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("pcgw_import_index_from_file", { filePath }) };
+    return { status: "ok", data: await TAURI_INVOKE("get_consistency_report") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * 从SQLite数据库导入 PCGW 索引（例如 Game-Save-Manager 的 database.db）
+ * 执行 `validate_data` 给出的修复操作，返回实际执行了的 ID
  */
-async pcgwImportIndexFromSqlite(filePath: string) : Promise<Result<PcgwIndexMeta, string>> {
+async applyDataFixes(ids: string[]) : Promise<Result<string[], IpcError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("pcgw_import_index_from_sqlite", { filePath }) };
+    return { status: "ok", data: await TAURI_INVOKE("apply_data_fixes", { ids }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+async setConfig(config: Config) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
 }
+},
+async resetSettings() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
 }
-
-/** user-defined events **/
-
-
-export const events = __makeEvents__<{
-ipcNotification: IpcNotification,
-quickActionCompleted: QuickActionCompleted,
-scanProgress: ScanProgress
-}>({
-ipcNotification: "ipc-notification",
-quickActionCompleted: "quick-action-completed",
-scanProgress: "scan-progress"
-})
-
-/** user-defined constants **/
-
-export const DEFAULT_CONFIG = {"backup_path":"./save_data","devices":{},"favorites":[],"games":[],"quick_action":{"enable_notification":true,"enable_sound":true,"hotkeys":{"apply":["","",""],"backup":["","",""]},"quick_action_game":null,"sounds":{"failure":{"kind":"default"},"success":{"kind":"default"}}},"settings":{"add_new_to_favorites":false,"cloud_settings":{"always_sync":false,"auto_sync_interval":0,"backend":{"type":"Disabled"},"root_path":"/game-save-manager"},"default_delete_before_apply":false,"default_expend_favorites_tree":false,"exit_to_tray":true,"extra_backup_when_apply":true,"home_page":"/","locale":"zh_SIMPLIFIED","log_to_file":true,"prompt_when_auto_backup":true,"prompt_when_not_described":false,"save_list_expand_behavior":"always_closed","save_list_last_expanded":false,"show_edit_button":false},"version":"1.5.4"} as const;
-
-/** user-defined types **/
-
-export type Backend = { type: "Disabled" } | 
+},
+async createSnapshot(game: Game, describe: string) : Promise<Result<Snapshot, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_snapshot", { game, describe }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * WebDAV 后端
- * 参考：https://docs.rs/opendal/latest/opendal/services/struct.Webdav.html
- * 不支持 blocking
+ * 取消正在创建或恢复的快照，返回是否有正在进行的操作
+ * 
+ * 创建时会删除未完成的压缩包，恢复时只有在解压完成前才能取消，此时存档不会被修改
  */
-{ type: "WebDAV"; endpoint: string; username: string; password: string } | 
+async cancelBackupOperation(game: Game) : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_backup_operation", { game }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openBackupFolder(game: Game) : Promise<Result<null, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_backup_folder", { game }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * Amazon S3 后端
- * 参考：https://docs.rs/opendal/latest/opendal/services/struct.S3.html
- * 不支持 rename 和 blocking
+ * 打开日志所在的文件夹
  */
-{ type: "S3"; endpoint: string; bucket: string; region: string; access_key_id: string; secret_access_key: string }
-export type CloudSettings = { 
+async openLogFolder() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_log_folder") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * 是否启用跟随云同步（用户添加、删除时自动同步）
+ * 将最近的日志、脱敏后的配置与上次扫描结果打包到 `target_path`，便于反馈问题
  */
-always_sync?: boolean; 
+async collectDiagnostics(targetPath: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("collect_diagnostics", { targetPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * 同步间隔，单位分钟，为0则不自动同步
+ * 检查已保存的云同步后端，`backend` 为后端名称，未指定时检查主后端
  */
-auto_sync_interval?: number; 
+async checkCloudBackend(backend: string | null) : Promise<Result<null, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_cloud_backend", { backend }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * 云同步根目录
+ * 上传全部备份，`backend` 为后端名称，未指定时使用主后端
  */
-root_path?: string; 
+async cloudUploadAll(backend: string | null) : Promise<Result<SyncSummary, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cloud_upload_all", { backend }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * 云同步后端设置
+ * 下载全部备份，`backend` 为后端名称，未指定时使用主后端
  */
-backend?: Backend }
+async cloudDownloadAll(backend: string | null) : Promise<Result<SyncSummary, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cloud_download_all", { backend }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * The software's configuration
- * include the version, backup's location path, games'info,
- * and the settings
+ * Last cloud sync of each game, read from the local sync state instead of the remote
  */
-export type Config = { version: string; backup_path: string; games: Game[]; settings: Settings; favorites?: FavoriteTreeNode[]; quick_action?: QuickActionsSettings; 
+async getSyncStatus() : Promise<Result<GameSyncStatus[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_sync_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
- * 设备ID到设备名称的映射
+ * Cloud operations waiting to be sent, oldest first
  */
-devices?: Partial<{ [key in string]: Device }> }
+async getCloudOutbox() : Promise<Result<OutboxEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_cloud_outbox") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Send the queued cloud operations now
+ */
+async flushCloudOutbox() : Promise<Result<OutboxFlushSummary, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("flush_cloud_outbox") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Enable snapshot encryption for this session, generating the salt on first use
+ * 
+ * The first passphrase is recorded with a check value, a different one is rejected later
+ */
+async setCloudPassphrase(passphrase: string) : Promise<Result<null, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_cloud_passphrase", { passphrase }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearCloudPassphrase() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_cloud_passphrase") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSnapshotDescription(game: Game, date: string, describe: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_snapshot_description", { game, date, describe }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async backupAll() : Promise<Result<BatchSummary, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("backup_all") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async applyAll() : Promise<Result<BatchSummary, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("apply_all") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async applyLatest(gameNames: string[]) : Promise<Result<BatchSummary, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("apply_latest", { gameNames }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setQuickBackupGame(game: Game) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_quick_backup_game", { game }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setQuickActionOverride(gameName: string, gameOverride: QuickActionOverride) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_quick_action_override", { gameName, gameOverride }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearQuickActionOverride(gameName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_quick_action_override", { gameName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAutoBackupStatus() : Promise<Result<AutoBackupTick, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_auto_backup_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 重新检查备份目录，可用时恢复已暂停的自动备份，返回备份目录是否可用
+ */
+async retryBackupRoot() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("retry_backup_root") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Resolves a path string containing variables to an actual filesystem path
+ * 
+ * This command allows the frontend to resolve paths with variables like <home>, <winAppData>, etc.
+ */
+async resolvePath(path: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resolve_path", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 首次运行时设置默认备份目录并登记当前设备，已完成设置时不会修改配置
+ */
+async initializeFirstRun() : Promise<Result<FirstRunSetup, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("initialize_first_run") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns the current device, if not found, returns a default device
+ */
+async getCurrentDeviceInfo() : Promise<Result<Device, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_current_device_info") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns all known devices, the current one first
+ */
+async listDevices() : Promise<Result<Device[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_devices") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async renameDevice(deviceId: string, newName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_device", { deviceId, newName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setCurrentDeviceName(name: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_current_device_name", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeDevice(deviceId: string, stripPaths: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_device", { deviceId, stripPaths }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listDevicePaths(gameName: string) : Promise<Result<SaveUnitDevicePaths[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_device_paths", { gameName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setUnitPathForDevice(gameName: string, unitIndex: number, deviceId: string, path: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_unit_path_for_device", { gameName, unitIndex, deviceId, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeUnitPathForDevice(gameName: string, unitIndex: number, deviceId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_unit_path_for_device", { gameName, unitIndex, deviceId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async toggleQuickActionSoundPreview(preferences: QuickActionSoundPreferences, effect: QuickActionSoundEffect) : Promise<Result<SoundPreviewState, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("toggle_quick_action_sound_preview", { preferences, effect }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopSoundPlayback() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_sound_playback") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async chooseQuickActionSoundFile(copyToAppDir: boolean) : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("choose_quick_action_sound_file", { copyToAppDir }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 读取音频文件的总时长（秒），供设置页提示过长的提示音
+ */
+async probeSoundFile(path: string) : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("probe_sound_file", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 显示一条测试通知，便于检查系统的通知设置
+ */
+async testNotification() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_notification") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 点击通知重新打开主窗口后，前端读取需要打开的游戏
+ */
+async takeNotificationTarget() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("take_notification_target") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Starts watching the save paths of all games, `SaveChanged` is emitted on changes
+ */
+async startSaveWatchers() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_save_watchers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stops watching the save paths started by `start_save_watchers`
+ */
+async stopSaveWatchers() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_save_watchers") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 触发扫描流程的命令（最小实现）
+ * 
+ * - 输入：`ScanOptions` 控制扫描选项，`AppHandle` 用于事件发送
+ * - 输出：`ScanResult` 扫描结果（当前为最小实现，返回空集合）
+ * - 行为：按阶段发送两到三次 `ScanProgress` 事件，便于前端调试 UI 与绑定
+ * 扫描入口命令
+ * 
+ * - 输入：`options` 控制扫描行为；`app` 用于事件发送与资源解析
+ * - 行为：阶段化发送 `ScanProgress` 事件，并记录各阶段耗时；
+ * - 输出：返回聚合的检测与存档匹配结果
+ * - 取消：同一时间只运行一个扫描，新扫描会取消旧扫描；被取消的扫描返回部分结果并置 `cancelled`
+ * - 持久化：未被取消的扫描结果写入 `AppData/RGSM/last_scan.json`，写入失败只记录日志
+ * - 平台：`options.platform` 与当前系统不一致时返回 `ScanPlatformMismatch`，不会执行扫描；
+ * 通过校验的选项写入 `AppData/RGSM/scan_options.json`，供 `get_default_scan_options` 返回
+ */
+async scanGames(options: ScanOptions) : Promise<Result<ScanResult, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("scan_games", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 获取扫描选项的默认值
+ * 
+ * - 优先返回上次扫描使用的选项，记录不适用于当前系统时返回平台默认值
+ * - 记录无法读取时视为没有记录
+ */
+async getDefaultScanOptions() : Promise<Result<ScanOptions, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_default_scan_options") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 取消正在运行的扫描
+ * 
+ * - 行为：触发当前扫描的取消令牌，扫描将在下一个检查点返回部分结果
+ * - 无扫描运行时为空操作
+ */
+async cancelScan() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_scan") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 获取上次完成的扫描结果（含扫描时间与选项），从未扫描过时返回 `None`
+ */
+async getLastScanResult() : Promise<Result<LastScan | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_last_scan_result") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 重新扫描并与上次结果对比
+ * 
+ * - 行为：与 `scan_games` 相同地执行并保存扫描，然后为每个检测结果标注 `diff`（New / Unchanged），
+ * 上次存在而本次未检测到的游戏以 `Missing` 追加到列表末尾
+ * - 上次记录无法读取时视为没有记录，所有游戏均为 New
+ * - 被取消的扫描结果不完整，不做对比
+ */
+async rescanDiff(options: ScanOptions) : Promise<Result<ScanResult, IpcError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rescan_diff", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 获取扫描忽略列表
+ */
+async getScanIgnoreList() : Promise<Result<ScanIgnoreList, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_scan_ignore_list") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 将检测结果加入忽略列表，之后的扫描会跳过它
+ * 
+ * - 输入：`path_or_name` 含路径分隔符时按安装路径忽略（包括其子目录），否则按游戏名忽略，均不区分大小写
+ * - 输出：更新后的忽略列表
+ */
+async ignoreDetectedGame(pathOrName: string) : Promise<Result<ScanIgnoreList, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("ignore_detected_game", { pathOrName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 将条目移出忽略列表，`path_or_name` 的含义与 `ignore_detected_game` 相同
+ */
+async unignoreDetectedGame(pathOrName: string) : Promise<Result<ScanIgnoreList, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unignore_detected_game", { pathOrName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 查询 PCGamingWiki 索引中的游戏信息（名称或别名匹配）
+ * 
+ * - 输入：`name` 为待查询的游戏名称或别名，`AppHandle` 用于解析资源路径
+ * - 输出：匹配到的 `GameInfo`，无匹配时返回 `None`
+ * - 错误：资源读取或解析失败返回错误信息字符串（已转换为友好可读）
+ */
+async pcgwQuery(name: string) : Promise<Result<GameInfo | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pcgw_query", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 完整查询 PCGamingWiki 索引（支持模糊、平台过滤与结果上限）
+ * 
+ * - 输入：`name` 查询关键字（名称或别名），`options` 查询选项
+ * - 行为：按以下优先级计算评分并排序：
+ * 1. 主名称完全匹配：score=1.0，matched_by="name"
+ * 2. 别名完全匹配：score=0.95，matched_by="alias"
+ * 3. 模糊匹配：score≈0.68~0.94，matched_by="fuzzy"，低于任何精确匹配；
+ * 综合拼写相近、逐词/前缀、首字母缩写与片段包含（见 `fuzzy` 模块），别名命中乘以 0.97
+ * - 过滤：若设置 `platform`，仅保留有保存规则包含该平台的条目
+ * - 限制：返回不超过 `limit` 个结果（默认 20）
+ */
+async pcgwSearch(name: string, options: PcgwQueryOptions) : Promise<Result<PcgwQueryItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pcgw_search", { name, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 为已检测到的游戏生成 SaveUnit 列表（带设备映射）
+ * 
+ * - 输入：`game_info`（PCGW 索引中的游戏信息）、`install_path`（解析出的安装目录字符串）、
+ * `options`（候选的可信度阈值、数量上限与取舍策略，为空时采用所有存在的候选）
+ * - 行为：调用平台实现的 `generate_save_units`，只返回存在的文件/文件夹并映射到当前设备
+ * - 输出：采用的 `SaveUnit` 列表，供前端“一键填充/添加”使用；未采用的候选作为“其他可能位置”
+ */
+async generateSaveUnitsForGame(gameInfo: GameInfo, installPath: string, options: SaveUnitOptions | null) : Promise<Result<GeneratedSaveUnits, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("generate_save_units_for_game", { gameInfo, installPath, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 将扫描检测到的游戏直接转换为已配置的 `Game`（含备份目录）
+ * 
+ * - 输入：`detected` 扫描结果条目；`name_override` 自定义游戏名（为空时使用检测到的名称）
+ * - 行为：
+ * 1. 调用平台实现的 `generate_save_units` 生成存档单元，无可用存档单元时返回错误
+ * 2. 将安装路径记录到当前设备的 `game_paths`
+ * 3. 调用 `backup::create_game_backup` 写入配置并创建备份目录
+ * 4. `take_initial_snapshot` 为真时立即创建首个快照
+ * - 约束：已存在同名游戏时返回错误，除非 `overwrite` 为真
+ * - 输出：新建的 `Game`，供前端直接刷新列表
+ */
+async adoptDetectedGame(detected: DetectedGame, nameOverride: string | null, takeInitialSnapshot: boolean, overwrite: boolean) : Promise<Result<Game, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("adopt_detected_game", { detected, nameOverride, takeInitialSnapshot, overwrite }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 刷新 PCGW 索引（返回版本与条目数量）
+ * 
+ * - 行为：首先尝试从远端拉取并缓存索引；失败则回退读取打包资源
+ * - 返回：索引版本与条目数量，便于前端显示状态
+ */
+async pcgwRefreshIndex() : Promise<Result<PcgwIndexMeta, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pcgw_refresh_index") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 从本地文件导入 PCGW 索引（覆盖缓存并返回元信息）
+ */
+async pcgwImportIndexFromFile(filePath: string) : Promise<Result<PcgwIndexMeta, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pcgw_import_index_from_file", { filePath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * 从SQLite数据库导入 PCGW 索引（例如 Game-Save-Manager 的 database.db）
+ */
+async pcgwImportIndexFromSqlite(filePath: string) : Promise<Result<PcgwIndexMeta, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pcgw_import_index_from_sqlite", { filePath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+export const events = __makeEvents__<{
+archiveProgress: ArchiveProgress,
+autoBackupTick: AutoBackupTick,
+backupRootMigrationProgress: BackupRootMigrationProgress,
+backupScheduleFired: BackupScheduleFired,
+cloudSyncProgress: CloudSyncProgress,
+cloudSyncStateChanged: CloudSyncStateChanged,
+gameSoftDeleted: GameSoftDeleted,
+ipcNotification: IpcNotification,
+notificationClicked: NotificationClicked,
+quickActionCompleted: QuickActionCompleted,
+saveChanged: SaveChanged,
+scanProgress: ScanProgress
+}>({
+archiveProgress: "archive-progress",
+autoBackupTick: "auto-backup-tick",
+backupRootMigrationProgress: "backup-root-migration-progress",
+backupScheduleFired: "backup-schedule-fired",
+cloudSyncProgress: "cloud-sync-progress",
+cloudSyncStateChanged: "cloud-sync-state-changed",
+gameSoftDeleted: "game-soft-deleted",
+ipcNotification: "ipc-notification",
+notificationClicked: "notification-clicked",
+quickActionCompleted: "quick-action-completed",
+saveChanged: "save-changed",
+scanProgress: "scan-progress"
+})
+
+/** user-defined constants **/
+
+export const DEFAULT_CONFIG = {"backup_path":"./save_data","devices":{},"favorites":[],"games":[],"quick_action":{"auto_backup_minutes":0,"busy_behavior":"drop","change_backup":{"min_interval_minutes":10,"settle_seconds":10},"enable_notification":true,"enable_sound":true,"game_hotkeys":[],"hotkeys":{"apply":["","",""],"backup":["","",""]},"overrides":{},"quick_action_game":null,"schedules":[],"sounds":{"failure":{"kind":"default"},"failure_max_seconds":10,"success":{"kind":"default"},"success_max_seconds":10}},"settings":{"add_new_to_favorites":false,"auto_describe_template":"{trigger}","backup_after_exit":false,"backup_before_launch":false,"backup_retry":{"attempts":1,"delay_ms":500},"check_updates":true,"cloud_settings":{"always_sync":false,"auto_sync_interval":0,"backends":[],"encryption":null,"max_attempts":4,"primary":"","upload_concurrency":1},"default_delete_before_apply":false,"default_expend_favorites_tree":false,"deleted_game_retention_days":7,"exit_to_tray":true,"extra_backup_when_apply":true,"home_page":"/","locale":"zh_SIMPLIFIED","log_level":"info","log_to_file":true,"prompt_when_auto_backup":true,"prompt_when_not_described":false,"restore_timestamps":true,"save_list_expand_behavior":"always_closed","save_list_last_expanded":false,"show_edit_button":false,"steam_user_id":null,"update_check_url":"https://api.github.com/repos/mcthesw/game-save-manager/releases/latest","use_trash_on_delete":true,"verify_after_backup":false},"version":"1.5.4"} as const;
+
+/** user-defined types **/
+
+/**
+ * Outcome of a check for a newer release
+ */
+export type AppUpdateInfo = { current: string; 
+/**
+ * `None` when the check is disabled or failed
+ */
+latest: string | null; update_available: boolean; release_notes_url: string | null; 
+/**
+ * Why the check failed, like being offline or rate limited
+ */
+error: string | null }
+/**
+ * What to delete under the unit path before a snapshot is applied
+ * 
+ * With include/exclude globs on a folder unit, `Full` only deletes the files
+ * matched by the filter, and `TrackedOnly` never sees excluded files because
+ * they are not restored, so excluded files survive both modes
+ */
+export type ApplyCleanMode = 
+/**
+ * Overwrite files in the snapshot, keep everything else. A file in the way of
+ * a folder of the snapshot, or the reverse, fails the restore of the unit
+ */
+"None" | 
+/**
+ * Delete only the paths that exist in the snapshot before applying it,
+ * files created after the snapshot are kept
+ */
+"TrackedOnly" | 
+/**
+ * Delete the whole unit before applying the snapshot
+ */
+"Full"
+export type ArchiveOperation = "Compress" | "Extract"
+/**
+ * Emitted while a snapshot of `game_name` is being created or restored from the main window
+ */
+export type ArchiveProgress = { game_name: string; operation: ArchiveOperation; processed_bytes: number; 
+/**
+ * Estimated from the save files when compressing, exact when extracting
+ */
+total_bytes: number; current_file: string }
+/**
+ * State of the auto backup timer, emitted on every tick and whenever the interval changes
+ */
+export type AutoBackupTick = { elapsed_minutes: number; 
+/**
+ * 0 means auto backup is off
+ */
+interval_minutes: number; game_name: string | null; 
+/**
+ * Auto backups are paused because the backup root cannot be reached
+ */
+paused: boolean }
+export type Backend = { type: "Disabled" } | 
+/**
+ * WebDAV 后端
+ * 参考：https://docs.rs/opendal/latest/opendal/services/struct.Webdav.html
+ * 不支持 blocking
+ */
+{ type: "WebDAV"; endpoint: string; username: string; password: string; danger_accept_invalid_certs?: boolean; ca_cert_path?: string | null } | 
+/**
+ * Amazon S3 后端
+ * 参考：https://docs.rs/opendal/latest/opendal/services/struct.S3.html
+ * 不支持 rename 和 blocking
+ */
+{ type: "S3"; endpoint: string; bucket: string; region: string; access_key_id: string; secret_access_key: string } | 
+/**
+ * Google Drive 后端
+ * 参考：https://docs.rs/opendal/latest/opendal/services/struct.Gdrive.html
+ * 可只填写 access_token，或填写 refresh_token、client_id、client_secret 以自动刷新令牌
+ */
+{ type: "GoogleDrive"; access_token: string; refresh_token: string; client_id: string; client_secret: string } | 
+/**
+ * OneDrive 后端
+ * 参考：https://docs.rs/opendal/latest/opendal/services/struct.Onedrive.html
+ */
+{ type: "OneDrive"; access_token: string } | 
+/**
+ * 本地文件夹后端，可指向第二块硬盘或已挂载的网络共享（如 NAS 的 SMB 共享）
+ * 参考：https://docs.rs/opendal/latest/opendal/services/struct.Fs.html
+ */
+{ type: "LocalFs"; path: string }
+/**
+ * 备份时文件被游戏占用的重试策略
+ * 
+ * 不少游戏自动存档后一秒内就会释放文件锁，默认只尝试一次，与旧版本行为一致
+ */
+export type BackupRetry = { 
+/**
+ * 读取单个文件的总尝试次数，小于 1 时按 1 处理
+ */
+attempts: number; 
+/**
+ * 两次尝试之间的等待时间（毫秒）
+ */
+delay_ms: number }
+/**
+ * Result of a successful `migrate_backup_root`
+ */
+export type BackupRootMigration = { old_path: string; new_path: string; 
+/**
+ * Games whose backup folder was moved to the new root
+ */
+moved: string[] }
+/**
+ * Emitted after each game folder is handled by `migrate_backup_root`
+ */
+export type BackupRootMigrationProgress = { game_name: string; 
+/**
+ * Games handled so far, including the ones without a backup folder
+ */
+current: number; total: number }
+/**
+ * Auto backup of one game on its own interval, independent of the tray timer
+ */
+export type BackupSchedule = { game_name: string; 
+/**
+ * 0 disables the schedule
+ */
+interval_minutes: number; 
+/**
+ * Reserved for process detection, schedules currently run whether the game is running or not
+ */
+only_when_running?: boolean }
+/**
+ * Emitted when a per-game backup schedule is due, before its backup starts
+ */
+export type BackupScheduleFired = { game_name: string; interval_minutes: number }
+/**
+ * `backup_all` 与 `apply_all` 的结果，单个游戏失败不会中断其他游戏
+ */
+export type BatchSummary = { succeeded: string[]; 
+/**
+ * 游戏名与失败原因
+ */
+failed: ([string, string])[]; 
+/**
+ * 本地操作成功但有需要提示的问题，如云端上传失败
+ */
+warnings: ([string, string])[]; 
+/**
+ * 没有快照可恢复的游戏
+ */
+skipped: string[]; 
+/**
+ * `backup_all` 为成功的游戏创建的快照，与游戏名对应
+ */
+snapshots: ([string, Snapshot])[] }
+/**
+ * Timing of the snapshots taken when saves change, shared by all games
+ */
+export type ChangeBackupTiming = { 
+/**
+ * Seconds without writes before the saves are considered settled
+ */
+settle_seconds: number; 
+/**
+ * At most one automatic snapshot per game within this many minutes
+ */
+min_interval_minutes: number }
+/**
+ * One change made, or proposed on a dry run, by `cleanup_backup_storage`
+ */
+export type CleanupAction = 
+/**
+ * A zip no record refers to, moved into the game's `orphaned` folder
+ */
+{ kind: "quarantine_orphan"; game: string; path: string; size: number } | 
+/**
+ * A record whose zip is gone locally but still on the cloud, downloaded again
+ */
+{ kind: "recover_from_cloud"; game: string; date: string } | 
+/**
+ * A record whose zip is gone, removed from `Backups.json`
+ */
+{ kind: "drop_dangling"; game: string; date: string } | 
+/**
+ * A record written before sizes were tracked, filled in from the zip
+ */
+{ kind: "fix_size"; game: string; date: string; size: number } | 
+/**
+ * A cloud object no record refers to, deleted from the cloud
+ */
+{ kind: "delete_remote_orphan"; game: string; path: string }
+/**
+ * Returned by `cleanup_backup_storage`
+ */
+export type CleanupReport = { 
+/**
+ * Whether the actions were only proposed
+ */
+dry_run: boolean; actions: CleanupAction[]; 
+/**
+ * Bytes of orphaned zips moved out of the snapshot folders
+ */
+quarantined_bytes: number }
+export type CloudSettings = { 
+/**
+ * 是否启用跟随云同步（用户添加、删除时自动同步）
+ */
+always_sync?: boolean; 
+/**
+ * 同步间隔，单位分钟，为0则不自动同步
+ */
+auto_sync_interval?: number; 
+/**
+ * 已配置的云同步后端，跟随云同步与定时同步会同步到其中每个已启用的后端
+ */
+backends?: NamedBackend[]; 
+/**
+ * 主后端的名称，未指定后端的操作（如全部上传、全部下载）使用主后端
+ */
+primary?: string; 
+/**
+ * 上传前加密存档，口令仅在运行时输入，不会写入配置
+ */
+encryption?: EncryptionSettings | null; 
+/**
+ * 单个云端请求的最大尝试次数，只有限流、服务暂时不可用等临时错误会重试
+ */
+max_attempts?: number; 
+/**
+ * 跟随云同步在后台同时上传的快照数量，同一游戏的存档记录只在其快照上传完后写入一次
+ */
+upload_concurrency?: number }
+/**
+ * Emitted after each snapshot is handled by `upload_all` or `download_all`
+ */
+export type CloudSyncProgress = { direction: SyncDirection; 
+/**
+ * Snapshots handled so far, including skipped and failed ones
+ */
+current: number; total: number; transferred: number }
+export type CloudSyncState = "Started" | "Finished" | "Failed"
+/**
+ * Emitted when a cloud operation starts, finishes or fails
+ */
+export type CloudSyncStateChanged = { 
+/**
+ * `None` for operations covering all games
+ */
+game: string | null; direction: SyncDirection; state: CloudSyncState; error: string | null }
+/**
+ * The software's configuration
+ * include the version, backup's location path, games'info,
+ * and the settings
+ */
+export type Config = { version: string; backup_path: string; games: Game[]; settings: Settings; favorites?: FavoriteTreeNode[]; quick_action?: QuickActionsSettings; 
+/**
+ * 设备ID到设备名称的映射
+ */
+devices?: Partial<{ [key in string]: Device }> }
+/**
+ * 一个检查出的问题
+ */
+export type DataIssue = { 
+/**
+ * 问题所属的游戏，与具体游戏无关时为 `None`
+ */
+game: string | null; severity: IssueSeverity; kind: DataIssueKind; 
+/**
+ * 可以安全地自动修复时为修复操作的 ID，传给 `apply_data_fixes` 执行
+ */
+fix: string | null }
+/**
+ * 配置或快照记录中的问题
+ */
+export type DataIssueKind = 
+/**
+ * 有 `count` 个游戏使用同一名称，它们共用同一个备份目录
+ */
+{ kind: "duplicate_game_name"; count: number } | 
+/**
+ * 游戏没有任何存档单元
+ */
+{ kind: "no_save_paths" } | 
+/**
+ * Backups.json 无法读取
+ */
+{ kind: "unreadable_records"; reason: string } | 
+/**
+ * 快照日期为空或无法解析
+ */
+{ kind: "invalid_snapshot_date"; date: string } | 
+/**
+ * 有 `count` 条快照记录使用同一日期
+ */
+{ kind: "duplicate_snapshot_date"; date: string; count: number } | 
+/**
+ * 快照记录对应的压缩包不存在
+ */
+{ kind: "missing_snapshot_file"; date: string } | 
+/**
+ * 记录的大小与磁盘上的压缩包不一致
+ */
+{ kind: "snapshot_size_mismatch"; date: string; recorded: number; actual: number } | 
+/**
+ * 序号为 `unit` 的存档单元中有不在设备列表中的设备路径
+ */
+{ kind: "unknown_device"; unit: number; device_id: string } | 
+/**
+ * 备份目录不可用，可能是外置硬盘未连接
+ */
+{ kind: "backup_root_unavailable"; path: string } | 
+/**
+ * 配置中的游戏没有备份文件夹
+ */
+{ kind: "missing_backup_folder" } | 
+/**
+ * 游戏的备份文件夹名不是它的存储名，如旧版本未安全化的 `Who?`，需要改名为 `expected`
+ */
+{ kind: "misnamed_backup_folder"; folder: string; expected: string } | 
+/**
+ * 备份目录下的文件夹不属于任何游戏，导入时使用 `name` 作为游戏名
+ */
+{ kind: "orphaned_backup_folder"; folder: string; name: string; snapshots: number }
 /**
  * 已检测到的游戏条目
  */
 export type DetectedGame = { 
 /**
- * 游戏基础信息
+ * 游戏基础信息
+ */
+info: GameInfo; 
+/**
+ * 解析出的安装路径（若无法解析则为 None）
+ */
+install_path: string | null; 
+/**
+ * 检测来源
+ */
+source: DetectionSource; 
+/**
+ * 来源平台中的游戏 ID，目前为 Steam 应用清单中的 appid
+ */
+platform_id?: string | null; 
+/**
+ * 与 PCGW 索引的匹配分数（1.0 为名称精确匹配），未匹配时为 None
+ */
+pcgw_score?: number | null; 
+/**
+ * 最佳匹配的 PCGW 条目已分配给另一个检测结果时，记录该结果的名称，此时 `info` 保持原样
+ */
+ambiguous_with?: string | null; 
+/**
+ * 与上次扫描的对比结果，仅由 `rescan_diff` 填充
+ */
+diff?: ScanDiffStatus | null }
+/**
+ * 安装来源，用于标注检测到的依据
+ */
+export type DetectionSource = "Steam" | "Epic" | "Origin" | "Registry" | "CommonDir" | "Process" | "Manual"
+export type Device = { id: string; name: string }
+/**
+ * The path of a save unit on one device
+ */
+export type DevicePath = { device_id: string; 
+/**
+ * `None` when the device is not in `config.devices` anymore
+ */
+device_name: string | null; 
+/**
+ * `None` when the unit has no path for this device yet
+ */
+path: string | null; is_current: boolean }
+export type EncryptionSettings = { 
+/**
+ * Encrypt snapshots before uploading them
+ */
+enabled: boolean; 
+/**
+ * Hex encoded Argon2 salt, generated when the passphrase is first set
+ */
+key_derivation_salt?: string; 
+/**
+ * Hex encoded `KEY_CHECK` encrypted with the first key derived for the salt,
+ * used to reject a different passphrase
+ */
+key_check?: string }
+/**
+ * 前端可识别的错误码
+ * 
+ * 错误码是稳定的接口，前端按 `error.codes.<ErrorCode>` 查找翻译，新增变体时不要修改已有名称
+ */
+export type ErrorCode = "Io" | "DiskFull" | "PermissionDenied" | "Deserialize" | "ConfigInvalid" | "ConfigVersionTooOld" | "ConfigVersionTooNew" | "SaveFileNotExist" | "FileInUse" | "ArchiveCorrupted" | "InvalidGlob" | "PathResolution" | "NoPathForDevice" | "NoEnabledSaveUnit" | "InvalidSavePath" | "BackupNotExist" | "NoBackupAvailable" | "InvalidArchive" | "ImportConflict" | "SnapshotRecordsCorrupted" | "GameNotExist" | "InvalidGameName" | "GameNameConflict" | "SaveUnitNotExist" | "DeviceNotExist" | "InvalidBackupRoot" | "BackupRootUnavailable" | "BackupFolderMissing" | "BackupVerificationFailed" | "BackupRootConflict" | "InsufficientSpace" | "BackupRootMigrationFailed" | "RestorePathConflict" | "TrashUnavailable" | "CloudDisabled" | "CloudBackendNotFound" | "CloudAuthFailed" | "CloudConfigInvalid" | "CloudNotFound" | "CloudUnavailable" | "CloudCheckFailed" | "CloudCertificateInvalid" | "Cloud" | "PassphraseRequired" | "WrongPassphrase" | "EncryptionFailed" | "ScanFailed" | "ScanPlatformMismatch" | "Cancelled" | "SnapshotEntryNotFound" | "RestoreTargetExists" | "NothingApplied" | "Unexpected"
+/**
+ * 收藏夹树的节点，是文件夹或指向游戏的游戏节点
+ */
+export type FavoriteTreeNode = { node_id: string; label: string; is_leaf: boolean; children: FavoriteTreeNode[] | null }
+/**
+ * 文件的大小与修改时间（本地时间）
+ */
+export type FileStat = { size: number; modified: string | null }
+/**
+ * 首次运行初始化的结果，供引导页展示
+ */
+export type FirstRunSetup = { 
+/**
+ * 最终使用的备份目录
+ */
+backup_path: string; 
+/**
+ * 配置文件所在位置
+ */
+config_path: string; 
+/**
+ * 当前设备
+ */
+device: Device; 
+/**
+ * 本次调用是否修改了配置，已完成初始化的配置不会被修改
+ */
+changed: boolean; 
+/**
+ * 当前设备是否在本次启动时才写入配置，为真时引导页应让用户确认设备名称
+ */
+new_device: boolean }
+/**
+ * A game struct contains the save units and the game's launcher
+ */
+export type Game = { name: string; save_paths: SaveUnit[]; game_paths?: Partial<{ [key in string]: string }> }
+/**
+ * 游戏基础信息与路径规则集合
+ */
+export type GameInfo = { 
+/**
+ * 游戏显示名称
+ */
+name: string; 
+/**
+ * 可选的别名（匹配安装来源/进程名时使用）
+ */
+aliases: string[]; 
+/**
+ * PCGamingWiki 对应的条目 ID（用于外部索引）
+ */
+pcgw_id: string | null; 
+/**
+ * 安装路径匹配规则集合
+ */
+install_rules: InstallPathRule[]; 
+/**
+ * 存档路径匹配规则集合
+ */
+save_rules: SavePathRule[] }
+/**
+ * A backup list info is a json file in a backup folder for a game.
+ * It contains the name of the game,
+ * and all backups' path
+ */
+export type GameSnapshots = { name: string; 
+/**
+ * Folder name of the game, both in the backup root and under `save_data` on the cloud.
+ * Derived from `name` by `sanitize_windows_path_component`, empty in records written
+ * by older versions until they are normalized
+ */
+storage_name?: string; backups: Snapshot[] }
+/**
+ * Emitted when a game was deleted, the frontend offers to undo it
+ */
+export type GameSoftDeleted = { game_name: string; 
+/**
+ * Days the deletion can be undone for
+ */
+retention_days: number }
+/**
+ * Storage used by a single game
+ */
+export type GameStorage = { name: string; 
+/**
+ * Snapshots listed in `Backups.json` that still exist on disk
+ */
+snapshots: SizeStats; 
+/**
+ * Snapshot sizes in date order, for charting growth over time
+ */
+timeline: SnapshotSize[]; 
+/**
+ * Size of the `extra_backup` folder written before each restore
+ */
+extra_backup_bytes: number; orphaned: OrphanedSnapshot[]; 
+/**
+ * Dates listed in `Backups.json` whose zip is gone
+ */
+missing: string[] }
+/**
+ * Cloud sync status of one game, times are RFC 3339
+ */
+export type GameSyncStatus = { name: string; last_upload: string | null; last_download: string | null; 
+/**
+ * The game has snapshots newer than the last successful sync
+ */
+local_newer: boolean; 
+/**
+ * Last time the remote copy of this game was reached, even if the sync then failed
+ */
+remote_probe_time: string | null; 
+/**
+ * Changes of the game are waiting in the cloud outbox
+ */
+pending_upload: boolean; 
+/**
+ * Last successful upload to each backend, by backend name
+ */
+backend_uploads: Partial<{ [key in string]: string }> }
+/**
+ * 一个游戏所有存档路径的检查结果
+ */
+export type GameValidationReport = { game_name: string; units: SaveUnitReport[] }
+/**
+ * 存档单元生成结果
+ */
+export type GeneratedSaveUnits = { 
+/**
+ * 自动采用的存档单元，按得分从高到低
+ */
+units: SaveUnit[]; 
+/**
+ * 未被采用的其他可能位置，按得分从高到低，不会自动添加
+ */
+alternatives: SaveMatchResult[] }
+/**
+ * How to handle snapshots whose date already exists locally when importing
+ */
+export type ImportMergeStrategy = 
+/**
+ * Keep the local snapshot and skip the imported one
+ */
+"KeepExisting" | 
+/**
+ * Replace the local snapshot with the imported one
+ */
+"Overwrite"
+/**
+ * 安装路径匹配规则
+ */
+export type InstallPathRule = { 
+/**
+ * 规则标识符（便于调试与日志）
+ */
+id: string; 
+/**
+ * 规则简短描述
+ */
+description: string | null; 
+/**
+ * 通用的路径模式（支持变量，如 `<home>`, `<winAppData>` 等）
+ */
+patterns: string[]; 
+/**
+ * 可选的注册表键（Windows），用于提升匹配可靠度
+ */
+registry_keys: string[] | null }
+/**
+ * IPC 命令返回给前端的错误
+ * 
+ * - `code`：稳定的错误码，用于前端本地化
+ * - `message`：原始错误信息，便于记录日志
+ * - `context`：错误涉及的对象（路径、游戏名、快照日期等），没有时为 `None`
+ */
+export type IpcError = { code: ErrorCode; message: string; context: string | null }
+export type IpcNotification = { level: NotificationLevel; title: string; msg: string }
+/**
+ * 问题的严重程度
+ */
+export type IssueSeverity = 
+/**
+ * 会导致备份或恢复出错
+ */
+"Error" | 
+/**
+ * 数据不一致，但不影响备份与恢复
+ */
+"Warning" | "Info"
+/**
+ * 持久化的上次扫描记录
+ */
+export type LastScan = { 
+/**
+ * 扫描结果（不含对比标注）
+ */
+result: ScanResult; 
+/**
+ * 扫描完成时间（RFC 3339）
+ */
+scanned_at: string; 
+/**
+ * 本次扫描使用的选项
+ */
+options: ScanOptions }
+/**
+ * 启动游戏的结果
+ */
+export type LaunchResult = 
+/**
+ * 直接启动了可执行文件，可以追踪进程
+ */
+{ kind: "process"; pid: number } | 
+/**
+ * 交给系统打开，例如 `steam://` 链接或快捷方式，无法追踪进程
  */
-info: GameInfo; 
+{ kind: "opened" }
 /**
- * 解析出的安装路径（若无法解析则为 None）
+ * 一个已配置的云同步后端
  */
-install_path: string | null; 
+export type NamedBackend = { 
 /**
- * 检测来源
+ * 后端名称，在所有后端中唯一
  */
-source: DetectionSource }
+name: string; 
 /**
- * 安装来源，用于标注检测到的依据
+ * 云同步后端设置
  */
-export type DetectionSource = "Steam" | "Epic" | "Origin" | "Registry" | "CommonDir" | "Process" | "Manual"
-export type Device = { id: string; name: string }
-export type FavoriteTreeNode = { node_id: string; label: string; is_leaf: boolean; children: FavoriteTreeNode[] | null }
+backend: Backend; 
 /**
- * A game struct contains the save units and the game's launcher
+ * 该后端的云同步根目录
  */
-export type Game = { name: string; save_paths: SaveUnit[]; game_paths?: Partial<{ [key in string]: string }> }
+root_path?: string; 
 /**
- * 游戏基础信息与路径规则集合
+ * 是否参与跟随云同步与定时同步
  */
-export type GameInfo = { 
+enabled?: boolean }
 /**
- * 游戏显示名称
+ * Emitted when a notification about `game_name` is clicked while the main window is open
  */
-name: string; 
+export type NotificationClicked = { game_name: string }
+export type NotificationLevel = "info" | "warning" | "error"
 /**
- * 可选的别名（匹配安装来源/进程名时使用）
+ * A zip in a game's backup folder that no `Backups.json` entry refers to
  */
-aliases: string[]; 
+export type OrphanedSnapshot = { 
 /**
- * PCGamingWiki 对应的条目 ID（用于外部索引）
+ * Relative to `backup_path`, like "Game1/date.zip"
  */
-pcgw_id: string | null; 
+path: string; size: number }
+export type OutboxEntry = { operation: OutboxOperation; 
 /**
- * 安装路径匹配规则集合
+ * Name of the backend the entry is sent to, empty in entries queued before several
+ * backends could be configured, which go to the primary one
  */
-install_rules: InstallPathRule[]; 
+backend?: string; 
 /**
- * 存档路径匹配规则集合
+ * The remote file written or deleted
  */
-save_rules: SavePathRule[] }
+remote_path: string; 
 /**
- * A backup list info is a json file in a backup folder for a game.
- * It contains the name of the game,
- * and all backups' path
+ * When the entry was queued, RFC 3339
  */
-export type GameSnapshots = { name: string; backups: Snapshot[] }
+queued_at: string }
 /**
- * 安装路径匹配规则
+ * Result of replaying the queue
  */
-export type InstallPathRule = { 
+export type OutboxFlushSummary = { completed: number; 
 /**
- * 规则标识符（便于调试与日志）
+ * Entries whose local files no longer exist
  */
-id: string; 
+dropped: number }
+export type OutboxOperation = { type: "UploadConfig" } | 
 /**
- * 规则简短描述
+ * Upload the snapshot records of a game as they are when the entry is replayed
  */
-description: string | null; 
+{ type: "UploadRecords"; game: string } | { type: "UploadSnapshot"; game: string; date: string } | { type: "DeleteSnapshot"; game: string; date: string } | 
 /**
- * 通用的路径模式（支持变量，如 `<home>`, `<winAppData>` 等）
+ * Move the cloud folder of a renamed game
  */
-patterns: string[]; 
+{ type: "RenameGame"; from: string; to: string } | 
 /**
- * 可选的注册表键（Windows），用于提升匹配可靠度
+ * Move the zip of a snapshot that was moved to another game
  */
-registry_keys: string[] | null }
-export type IpcNotification = { level: NotificationLevel; title: string; msg: string }
-export type NotificationLevel = "info" | "warning" | "error"
+{ type: "MoveSnapshot"; from: string; to: string; date: string }
 /**
  * PCGW 索引元信息（用于刷新与状态显示）
  */
@@ -481,7 +1730,7 @@ export type PcgwQueryItem = {
  */
 info: GameInfo; 
 /**
- * 匹配评分（0.0~1.0），用于排序显示
+ * 匹配评分（0.0~1.0），用于排序显示：名称精确匹配为 1.0，别名精确匹配为 0.95，模糊匹配恒低于 0.95
  */
 score: number; 
 /**
@@ -493,7 +1742,7 @@ matched_by: string }
  */
 export type PcgwQueryOptions = { 
 /**
- * 是否启用模糊匹配（令包含与前后缀匹配生效）
+ * 是否启用模糊匹配（拼写相近、逐词前缀、首字母缩写与片段包含）
  */
 fuzzy: boolean; 
 /**
@@ -504,16 +1753,138 @@ platform: string | null;
  * 返回条目上限，缺省为 20
  */
 limit: number | null }
-export type QuickActionCompleted = { operation: QuickActionOperation; status: QuickActionStatus; trigger: QuickActionType; game_name: string | null }
+/**
+ * 选择文件或文件夹的对话框选项
+ */
+export type PickerOptions = { 
+/**
+ * 对话框打开的目录，可以包含 `<winAppData>` 等变量，不存在时使用最近的上级目录。
+ * 为空时使用本次运行中上次选择的目录
+ */
+start_dir: string | null; 
+/**
+ * 只显示这些扩展名的文件，不含 `.`，为空时显示所有文件。选择文件夹时忽略
+ */
+extensions?: string[] }
+/**
+ * What to do when a quick action is triggered while one for the same game is still running
+ */
+export type QuickActionBusyBehavior = 
+/**
+ * Skip the new request and notify the user
+ */
+"drop" | 
+/**
+ * Run the new request once the current one finished
+ */
+"queue"
+export type QuickActionCompleted = { operation: QuickActionOperation; status: QuickActionStatus; trigger: QuickActionType; game_name: string | null; 
+/**
+ * The snapshot created by a successful backup
+ */
+snapshot: Snapshot | null; 
+/**
+ * The snapshot restored by a successful apply
+ */
+restored_date: string | null }
+/**
+ * Hotkeys bound to one specific game, independent of `quick_action_game`
+ */
+export type QuickActionGameHotkeys = { game_name: string; backup_keys?: string[]; apply_keys?: string[] }
 export type QuickActionHotkeys = { apply: string[]; backup: string[] }
 export type QuickActionOperation = "Backup" | "Apply"
+/**
+ * Quick action settings of one game, `None` falls back to the global setting
+ */
+export type QuickActionOverride = { enable_sound?: boolean | null; enable_notification?: boolean | null; 
+/**
+ * Overrides `Settings::extra_backup_when_apply` for quick apply
+ */
+extra_backup_when_apply?: boolean | null; 
+/**
+ * Take a snapshot automatically once the saves changed and settled, opt-in per game
+ */
+auto_backup_on_change?: boolean }
 export type QuickActionSoundEffect = "Success" | "Failure"
 export type QuickActionSoundPreferences = { enable_sound?: boolean; sounds?: QuickActionSoundSlots }
-export type QuickActionSoundSlots = { success?: QuickActionSoundSource; failure?: QuickActionSoundSource }
+export type QuickActionSoundSlots = { success?: QuickActionSoundSource; failure?: QuickActionSoundSource; 
+/**
+ * Longest time the success sound plays, in seconds, 0 plays the whole file
+ */
+success_max_seconds?: number; 
+/**
+ * Longest time the failure sound plays, in seconds, 0 plays the whole file
+ */
+failure_max_seconds?: number }
 export type QuickActionSoundSource = { kind: "default" } | { kind: "file"; path: string }
 export type QuickActionStatus = "Success" | "Failure"
-export type QuickActionType = "Timer" | "Tray" | "Hotkey"
-export type QuickActionsSettings = { quick_action_game?: Game | null; hotkeys?: QuickActionHotkeys; enable_sound?: boolean; enable_notification?: boolean; sounds?: QuickActionSoundSlots }
+export type QuickActionType = "Timer" | "Tray" | "Hotkey" | "Schedule" | "FileChange" | "DeepLink"
+export type QuickActionsSettings = { quick_action_game?: Game | null; hotkeys?: QuickActionHotkeys; 
+/**
+ * Per-game hotkeys, registered alongside the global pair
+ */
+game_hotkeys?: QuickActionGameHotkeys[]; 
+/**
+ * Auto backup interval picked in the tray, in minutes, 0 means off
+ */
+auto_backup_minutes?: number; busy_behavior?: QuickActionBusyBehavior; enable_sound?: boolean; enable_notification?: boolean; sounds?: QuickActionSoundSlots; 
+/**
+ * Per-game overrides, keyed by game name
+ */
+overrides?: Partial<{ [key in string]: QuickActionOverride }>; 
+/**
+ * Per-game auto backups, running alongside the tray timer
+ */
+schedules?: BackupSchedule[]; change_backup?: ChangeBackupTiming }
+/**
+ * 恢复时单个文件的变化，`unit_index` 为其所属存档单元的序号
+ * 
+ * - `snapshot`：快照中的文件，被删除的文件没有
+ * - `current`：当前磁盘上的文件，新增的文件没有
+ */
+export type RestoreFileChange = { path: string; unit_index: number; snapshot: FileStat | null; current: FileStat | null }
+/**
+ * 恢复快照前的预览，不会修改任何文件
+ * 
+ * - `added`：目标位置不存在的文件
+ * - `overwritten`：目标位置存在且内容不同的文件
+ * - `deleted`：按单元的 `delete_before_apply` 会被删除、且不会被快照重新写入的文件
+ * - `unchanged`：大小与 CRC32 都相同的文件
+ */
+export type RestorePlan = { added: RestoreFileChange[]; overwritten: RestoreFileChange[]; deleted: RestoreFileChange[]; unchanged: RestoreFileChange[]; skipped_units: SkippedRestoreUnit[] }
+/**
+ * 存档单元不参与恢复的原因
+ */
+export type RestoreSkipReason = 
+/**
+ * 当前设备没有为该单元配置路径
+ */
+{ kind: "no_path_for_device" } | 
+/**
+ * 路径中的变量无法解析
+ */
+{ kind: "unresolvable"; reason: string } | 
+/**
+ * 快照中没有该单元，通常是快照创建后才添加的单元
+ */
+{ kind: "not_in_snapshot" } | 
+/**
+ * 单元已停用，恢复时不会处理
+ */
+{ kind: "disabled" }
+/**
+ * Emitted when a watched save changes, at most once per game every few seconds
+ */
+export type SaveChanged = { game_name: string; 
+/**
+ * The last changed file or folder
+ */
+path: string; 
+/**
+ * Modification time of `path`, in the same format as snapshot dates so the two
+ * can be compared directly
+ */
+mtime: string }
 /**
  * Settings that can be configured by user
  */
@@ -570,15 +1941,130 @@ confidence: number }
  * A save unit declares one of the files/folders
  * that should be backup for a game
  */
-export type SaveUnit = { unit_type: SaveUnitType; paths?: Partial<{ [key in string]: string }>; delete_before_apply?: boolean }
+export type SaveUnit = { unit_type: SaveUnitType; paths?: Partial<{ [key in string]: string }>; delete_before_apply?: ApplyCleanMode; 
+/**
+ * Glob patterns relative to the unit root, only matching entries of a
+ * folder unit are backed up. Empty means everything
+ */
+include_globs?: string[]; 
+/**
+ * Glob patterns relative to the unit root, matching entries of a folder
+ * unit are neither backed up nor deleted on restore
+ */
+exclude_globs?: string[]; 
+/**
+ * How links inside a folder unit are handled
+ */
+symlinks?: SymlinkMode; 
+/**
+ * Disabled units keep their definition but are left out of new snapshots
+ * and are not touched on restore
+ */
+enabled?: boolean }
+/**
+ * Paths of one save unit on every known device, `index` is its position in `save_paths`
+ */
+export type SaveUnitDevicePaths = { index: number; unit_type: SaveUnitType; paths: DevicePath[] }
+/**
+ * 单个存档单元存在的问题
+ */
+export type SaveUnitIssue = 
+/**
+ * 当前设备没有为该单元配置路径
+ */
+{ kind: "no_path_for_device" } | 
+/**
+ * 路径中的变量无法解析
+ */
+{ kind: "unresolvable"; reason: string } | 
+/**
+ * 路径不存在，通常是游戏被卸载或移动
+ */
+{ kind: "not_exists" } | 
+/**
+ * 声明为文件但实际是文件夹，或者相反
+ */
+{ kind: "type_mismatch"; expected: SaveUnitType } | 
+/**
+ * 与序号为 `other` 的单元指向同一路径
+ */
+{ kind: "duplicate"; other: number }
+/**
+ * 生成存档单元的选项，默认采用所有存在的候选
+ */
+export type SaveUnitOptions = { 
+/**
+ * 候选得分的下限，低于该值的候选只作为备选
+ */
+min_confidence?: number; 
+/**
+ * 最多采用的存档单元数量，为空时不限制
+ */
+max_units?: number | null; 
+/**
+ * 取舍策略
+ */
+prefer?: SaveUnitStrategy }
+/**
+ * 单个存档单元的检查结果，`index` 为其在 `save_paths` 中的序号
+ */
+export type SaveUnitReport = { index: number; resolved_path: string | null; issues: SaveUnitIssue[] }
+/**
+ * 生成存档单元时候选路径的取舍策略
+ */
+export type SaveUnitStrategy = 
+/**
+ * 只采用得分最高的一个候选
+ */
+"HighestConfidenceOnly" | 
+/**
+ * 采用所有达到阈值的候选（受 `max_units` 限制）
+ */
+"AllAboveThreshold" | 
+/**
+ * 不自动采用，所有候选都作为备选交给用户选择
+ */
+"InteractiveAll"
 /**
  * A save unit should be a file or a folder
  */
 export type SaveUnitType = "File" | "Folder"
+/**
+ * 重新扫描时与上次扫描结果的对比
+ */
+export type ScanDiffStatus = 
+/**
+ * 上次扫描中没有的游戏
+ */
+"New" | 
+/**
+ * 上次扫描中已存在的游戏
+ */
+"Unchanged" | 
+/**
+ * 上次扫描中存在、本次未检测到的游戏（通常为已卸载）
+ */
+"Missing"
+/**
+ * 持久化的忽略列表
+ */
+export type ScanIgnoreList = { 
+/**
+ * 忽略的安装路径（统一为小写与 `/` 分隔），其子目录同样被忽略
+ */
+paths?: string[]; 
+/**
+ * 忽略的游戏名（小写）
+ */
+names?: string[] }
 /**
  * 扫描选项
  */
 export type ScanOptions = { 
+/**
+ * 选项版本，缺失时视为首个版本
+ */
+version?: number; 
 /**
  * 平台标识（如 `windows`、`macos`、`linux`）
  */
@@ -606,7 +2092,15 @@ search_common_dirs: boolean;
 /**
  * 是否通过当前运行进程进行辅助匹配
  */
-search_processes: boolean }
+search_processes: boolean; 
+/**
+ * 是否对没有存档规则的游戏启用启发式深度扫描（较慢）
+ */
+deep_scan?: boolean; 
+/**
+ * 深度扫描时相对于每个根目录的最大层数
+ */
+deep_scan_max_depth?: number }
 /**
  * 扫描进度事件（用于前端订阅显示）
  */
@@ -646,14 +2140,216 @@ matches: SaveMatchResult[];
 /**
  * 错误消息（若有）
  */
-errors: string[] }
-export type Settings = { prompt_when_not_described?: boolean; extra_backup_when_apply?: boolean; show_edit_button?: boolean; prompt_when_auto_backup?: boolean; exit_to_tray?: boolean; cloud_settings?: CloudSettings; locale?: string; default_delete_before_apply?: boolean; default_expend_favorites_tree?: boolean; home_page?: string; log_to_file?: boolean; add_new_to_favorites?: boolean; save_list_expand_behavior?: SaveListExpandBehavior; save_list_last_expanded?: boolean }
+errors: string[]; 
+/**
+ * 扫描是否被取消（为真时上述结果仅为取消前已完成的部分）
+ */
+cancelled: boolean; 
+/**
+ * 因命中忽略列表而跳过的检测结果数量
+ */
+skipped_ignored?: number; 
+/**
+ * 被忽略的检测结果（不参与 PCGW 丰富与存档匹配），供前端“显示已忽略”使用
+ */
+ignored?: DetectedGame[] }
+export type Settings = { prompt_when_not_described?: boolean; extra_backup_when_apply?: boolean; show_edit_button?: boolean; prompt_when_auto_backup?: boolean; exit_to_tray?: boolean; cloud_settings?: CloudSettings; locale?: string; default_delete_before_apply?: boolean; default_expend_favorites_tree?: boolean; home_page?: string; log_to_file?: boolean; 
+/**
+ * 日志等级，如 "info"、"debug"，重启后生效
+ */
+log_level?: string; add_new_to_favorites?: boolean; save_list_expand_behavior?: SaveListExpandBehavior; save_list_last_expanded?: boolean; 
+/**
+ * 通过管理器启动游戏前先创建快照
+ */
+backup_before_launch?: boolean; 
+/**
+ * 通过管理器启动的游戏进程退出后创建快照，仅对直接启动的可执行文件有效
+ */
+backup_after_exit?: boolean; 
+/**
+ * 备份时文件被占用的重试策略
+ */
+backup_retry?: BackupRetry; 
+/**
+ * 恢复快照时还原文件的修改时间，关闭后恢复的文件以恢复时间为修改时间
+ */
+restore_timestamps?: boolean; 
+/**
+ * 删除快照、游戏与多余的额外备份时移入系统回收站，关闭后永久删除
+ */
+use_trash_on_delete?: boolean; 
+/**
+ * 创建快照后从磁盘读回压缩包并校验每个文件，未通过时删除该快照并报错
+ */
+verify_after_backup?: boolean; 
+/**
+ * 删除的游戏保留的天数，期间可以撤销删除，之后才真正删除本地与云端的备份
+ */
+deleted_game_retention_days?: number; 
+/**
+ * 定期检查 GitHub 上是否有新版本
+ */
+check_updates?: boolean; 
+/**
+ * 检查新版本时请求的 GitHub releases API 地址
+ */
+update_check_url?: string; 
+/**
+ * 自动生成的快照描述模板，用于快捷操作、全部备份以及未填写描述的快照
+ * 
+ * 支持 `{trigger}`、`{game}`、`{local_time}` 与 `{device}` 占位符
+ */
+auto_describe_template?: string; 
+/**
+ * 解析 `<steamUserId>` 时使用的 Steam 账号 ID，为空时自动选择最近登录的账号
+ */
+steam_user_id?: string | null }
+/**
+ * Size statistics over a set of snapshots, all in bytes
+ */
+export type SizeStats = { count: number; total: number; min: number; max: number; avg: number }
+/**
+ * 被跳过的存档单元
+ */
+export type SkippedRestoreUnit = { index: number; reason: RestoreSkipReason }
 /**
  * A backup is a zip file that contains
  * all the file that the save unit has declared.
  * The date is the unique indicator for a backup
  */
-export type Snapshot = { date: string; describe: string; path: string; size?: number }
+export type Snapshot = { date: string; describe: string; 
+/**
+ * Relative to `backup_path`, like "Game1/date.zip".
+ * Records written by older versions may still hold absolute paths
+ */
+path: string; size?: number }
+/**
+ * A file or folder stored in a snapshot
+ */
+export type SnapshotContentEntry = { 
+/**
+ * Separated by `/` and starting with the unit folder or file name
+ */
+path: string; is_dir: boolean; size: number; compressed_size: number; 
+/**
+ * Local time as stored in the zip, like "2024-01-01 12:00:00"
+ */
+modified: string | null }
+export type SnapshotContents = { units: SnapshotUnitContents[]; 
+/**
+ * Entries of no known unit, which is every entry of a snapshot taken before manifests
+ * were written
+ */
+ungrouped: SnapshotContentEntry[] }
+export type SnapshotDiff = { units: SnapshotUnitDiff[]; 
+/**
+ * Files of no unit known to either manifest
+ */
+ungrouped: SnapshotFileChanges }
+export type SnapshotFileChanges = { 
+/**
+ * Only in the second snapshot
+ */
+added: SnapshotFileDiff[]; 
+/**
+ * Only in the first snapshot
+ */
+removed: SnapshotFileDiff[]; 
+/**
+ * In both, with a different size or CRC
+ */
+modified: SnapshotFileDiff[]; unchanged: SnapshotFileDiff[] }
+/**
+ * A file present in at least one of the compared snapshots
+ */
+export type SnapshotFileDiff = { 
+/**
+ * Separated by `/` and starting with the unit folder or file name
+ */
+path: string; 
+/**
+ * `None` when the file is missing from the first snapshot
+ */
+size_a: number | null; 
+/**
+ * `None` when the file is missing from the second snapshot
+ */
+size_b: number | null }
+/**
+ * One snapshot as a point on a size chart
+ */
+export type SnapshotSize = { game: string; date: string; size: number }
+/**
+ * The entries backed up from one save unit
+ */
+export type SnapshotUnitContents = { 
+/**
+ * Index of the unit in `Game::save_paths` when the snapshot was taken
+ */
+index: number; root: string; entries: SnapshotContentEntry[] }
+/**
+ * The changes under one save unit
+ */
+export type SnapshotUnitDiff = { 
+/**
+ * Index of the unit in `Game::save_paths`, taken from the second snapshot when both
+ * know the unit
+ */
+index: number; root: string; changes: SnapshotFileChanges }
+/**
+ * Whether a preview toggle started or stopped playback
+ */
+export type SoundPreviewState = "Playing" | "Stopped"
+/**
+ * Disk usage of the whole backup folder, returned by `get_storage_report`
+ */
+export type StorageReport = { games: GameStorage[]; 
+/**
+ * Statistics over the snapshots of all games
+ */
+snapshots: SizeStats; extra_backup_bytes: number; 
+/**
+ * Bytes taken by orphaned zips, which a cleanup could free
+ */
+reclaimable_bytes: number; 
+/**
+ * The largest snapshots across all games, largest first
+ */
+largest: SnapshotSize[] }
+/**
+ * How symbolic links and junctions inside a folder unit are backed up
+ * 
+ * The unit path itself is always followed, only links found while walking
+ * the folder are affected
+ */
+export type SymlinkMode = 
+/**
+ * Back up what the link points to. A link back to one of its own parent
+ * folders is skipped, so link loops do not recurse forever
+ */
+"Follow" | 
+/**
+ * Leave links out of the snapshot and untouched on restore
+ */
+"Skip" | 
+/**
+ * Store the link itself and recreate it on restore where the platform
+ * allows creating links
+ */
+"Preserve"
+export type SyncDirection = "Upload" | "Download"
+/**
+ * Counters returned by `upload_all` and `download_all`
+ */
+export type SyncSummary = { 
+/**
+ * Snapshots actually transferred
+ */
+transferred: number; 
+/**
+ * Snapshots already present with the same size on the other side
+ */
+skipped: number; failed: number }
 
 /** tauri-specta globals **/
 
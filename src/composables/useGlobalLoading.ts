@@ -2,6 +2,10 @@ import { computed, ref } from 'vue'
 import { $t } from '../i18n'
 
 const messageStack = ref<string[]>([])
+// 当前操作的进度（0-100），为 null 时不显示进度条
+const loadingProgress = ref<number | null>(null)
+// 当前操作可以取消时的回调
+const cancelHandler = ref<(() => void) | null>(null)
 
 function startLoading(message?: string) {
   messageStack.value.push(message ?? $t('common.operation_in_progress'))
@@ -13,15 +17,28 @@ function stopLoading() {
   }
 }
 
-async function withLoading<T>(operation: () => Promise<T>, message?: string): Promise<T> {
+function setLoadingProgress(percentage: number | null) {
+  loadingProgress.value = percentage
+}
+
+function cancelLoading() {
+  cancelHandler.value?.()
+}
+
+async function withLoading<T>(operation: () => Promise<T>, message?: string, onCancel?: () => void): Promise<T> {
   startLoading(message)
+  cancelHandler.value = onCancel ?? null
   try {
     return await operation()
   } finally {
     stopLoading()
+    cancelHandler.value = null
+    loadingProgress.value = null
   }
 }
 
+const canCancel = computed(() => cancelHandler.value !== null)
+
 const isLoading = computed(() => messageStack.value.length > 0)
 const loadingMessage = computed(() => {
   if (messageStack.value.length === 0) {
@@ -34,8 +51,12 @@ export function useGlobalLoading() {
   return {
     isLoading,
     loadingMessage,
+    loadingProgress,
+    canCancel,
     startLoading,
     stopLoading,
+    setLoadingProgress,
+    cancelLoading,
     withLoading,
   }
 }
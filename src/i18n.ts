@@ -1,4 +1,5 @@
 import { createI18n } from 'vue-i18n'
+import type { IpcError } from './bindings'
 
 import en_US from '../locales/en_US.json'
 import fr from '../locales/fr.json'
@@ -29,6 +30,21 @@ export function $t(key: string) {
     return i18n.global.t(key)
 }
 
+/**
+ * 将后端返回的 IpcError 转换为本地化的提示
+ *
+ * - 按错误码查找 `error.codes.<code>`，附带错误涉及的对象
+ * - 没有对应翻译时回退到后端的原始信息
+ */
+export function ipcErrorMessage(err: IpcError) {
+    const key = `error.codes.${err.code}`
+    if (!i18n.global.te(key) && !i18n.global.te(key, 'en_US')) {
+        return err.message
+    }
+    const message = i18n.global.t(key)
+    return err.context ? `${message}: ${err.context}` : message
+}
+
 // 导出所有支持的语言及其本地化名称
 export function getSupportedLanguages() {
     const messages = i18n.global.messages.value as Record<string, any>;